@@ -1,5 +1,6 @@
 use std::hint::black_box;
 
+use ai_history_explorer::bookmarks::Bookmarks;
 use ai_history_explorer::filters::apply::apply_filters;
 use ai_history_explorer::filters::parser::parse_filter;
 use ai_history_explorer::models::{EntryType, SearchEntry};
@@ -19,6 +20,7 @@ fn generate_search_entries(num_entries: usize) -> Vec<SearchEntry> {
                 None
             },
             session_id: format!("session-{}", i),
+            ..Default::default()
         })
         .collect()
 }
@@ -33,7 +35,14 @@ fn bench_filter_application(c: &mut Criterion) {
 
         group.throughput(Throughput::Elements(*size as u64));
         group.bench_with_input(BenchmarkId::new("type_filter", size), size, |b, _| {
-            b.iter(|| apply_filters(black_box(entries.clone()), black_box(&filter_expr)).unwrap());
+            b.iter(|| {
+                apply_filters(
+                    black_box(entries.clone()),
+                    black_box(&filter_expr),
+                    &Bookmarks::default(),
+                )
+                .unwrap()
+            });
         });
     }
 
@@ -44,7 +53,14 @@ fn bench_filter_application(c: &mut Criterion) {
 
         group.throughput(Throughput::Elements(*size as u64));
         group.bench_with_input(BenchmarkId::new("project_filter", size), size, |b, _| {
-            b.iter(|| apply_filters(black_box(entries.clone()), black_box(&filter_expr)).unwrap());
+            b.iter(|| {
+                apply_filters(
+                    black_box(entries.clone()),
+                    black_box(&filter_expr),
+                    &Bookmarks::default(),
+                )
+                .unwrap()
+            });
         });
     }
 
@@ -55,7 +71,14 @@ fn bench_filter_application(c: &mut Criterion) {
 
         group.throughput(Throughput::Elements(*size as u64));
         group.bench_with_input(BenchmarkId::new("complex_filter", size), size, |b, _| {
-            b.iter(|| apply_filters(black_box(entries.clone()), black_box(&filter_expr)).unwrap());
+            b.iter(|| {
+                apply_filters(
+                    black_box(entries.clone()),
+                    black_box(&filter_expr),
+                    &Bookmarks::default(),
+                )
+                .unwrap()
+            });
         });
     }
 
@@ -31,8 +31,8 @@ fn generate_search_entries(num_entries: usize) -> Vec<SearchEntry> {
                 entry_type: EntryType::UserPrompt,
                 display_text: format!("{} task {} with additional context for matching", word, i),
                 timestamp: Utc::now(),
-                project_path: None,
                 session_id: format!("session-{}", i),
+                ..Default::default()
             }
         })
         .collect()
@@ -17,6 +17,7 @@ fn generate_search_entries(num_entries: usize) -> Vec<SearchEntry> {
                 None
             },
             session_id: format!("session-{}", i),
+            ..Default::default()
         })
         .collect()
 }
@@ -33,7 +34,7 @@ fn bench_build_index(c: &mut Criterion) {
             b.iter(|| {
                 // Benchmark just the index building/sorting logic
                 let mut cloned_entries = black_box(entries.clone());
-                cloned_entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+                cloned_entries.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
                 cloned_entries
             });
         });
@@ -0,0 +1,50 @@
+//! Combine the filter syntax with a predicate of your own over `SearchEntry`.
+//!
+//! The filter language covers projects, types and dates; anything else is a plain function of
+//! the entry. This keeps the long prompts of the last month that asked for tests.
+//!
+//! ```bash
+//! cargo run --example custom_filter -- ~/.claude
+//! ```
+
+use std::path::PathBuf;
+
+use ai_history_explorer::bookmarks::Bookmarks;
+use ai_history_explorer::filters::{apply_filters, parse_filter};
+use ai_history_explorer::{SearchEntry, build_index};
+use anyhow::{Context, Result};
+
+/// Entries whose text is at least `min_chars` long and mentions one of `keywords`
+struct DetailedRequest<'a> {
+    min_chars: usize,
+    keywords: &'a [&'a str],
+}
+
+impl DetailedRequest<'_> {
+    fn matches(&self, entry: &SearchEntry) -> bool {
+        let text = entry.display_text.to_lowercase();
+        text.chars().count() >= self.min_chars && self.keywords.iter().any(|k| text.contains(k))
+    }
+}
+
+fn main() -> Result<()> {
+    let claude_dir = match std::env::args_os().nth(1) {
+        Some(dir) => PathBuf::from(dir),
+        None => dirs::home_dir().context("No home directory")?.join(".claude"),
+    };
+    let index = build_index(&claude_dir)?;
+
+    // Narrow with the built-in syntax first, then apply the custom predicate
+    let filter = parse_filter("type:user after:30d")?;
+    let recent_prompts = apply_filters(index, &filter, &Bookmarks::default())?;
+    let predicate = DetailedRequest { min_chars: 200, keywords: &["test", "coverage"] };
+    let matches: Vec<&SearchEntry> =
+        recent_prompts.iter().filter(|entry| predicate.matches(entry)).collect();
+
+    println!("{} of {} recent prompts asked for tests:", matches.len(), recent_prompts.len());
+    for entry in matches.iter().take(10) {
+        let first_line = entry.display_text.lines().next().unwrap_or_default();
+        println!("  {}  {}", entry.timestamp.format("%Y-%m-%d"), first_line);
+    }
+    Ok(())
+}
@@ -0,0 +1,72 @@
+//! Write the search index into a SQLite database for ad-hoc SQL queries.
+//!
+//! Entries are inserted one by one inside a single transaction with a prepared statement, so
+//! memory stays flat however large the history is.
+//!
+//! ```bash
+//! cargo run --example export_sqlite -- history.db ~/.claude
+//! sqlite3 history.db "SELECT project, count(*) FROM entries GROUP BY project ORDER BY 2 DESC"
+//! ```
+
+use std::path::PathBuf;
+
+use ai_history_explorer::build_index;
+use ai_history_explorer::models::EntryType;
+use anyhow::{Context, Result};
+use rusqlite::{Connection, params};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS entries (
+        session_id TEXT NOT NULL,
+        uuid TEXT,
+        parent_uuid TEXT,
+        timestamp TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        source TEXT NOT NULL,
+        project TEXT,
+        text TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS entries_by_session ON entries (session_id, timestamp);
+";
+
+fn main() -> Result<()> {
+    let mut args = std::env::args_os().skip(1);
+    let database = PathBuf::from(args.next().unwrap_or_else(|| "history.db".into()));
+    let claude_dir = match args.next() {
+        Some(dir) => PathBuf::from(dir),
+        None => dirs::home_dir().context("No home directory")?.join(".claude"),
+    };
+
+    let index = build_index(&claude_dir)?;
+    let mut connection = Connection::open(&database)
+        .with_context(|| format!("Failed to open {}", database.display()))?;
+    connection.execute_batch(SCHEMA)?;
+
+    let transaction = connection.transaction()?;
+    {
+        let mut insert = transaction.prepare(
+            "INSERT INTO entries (session_id, uuid, parent_uuid, timestamp, kind, source, project, text)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+        for entry in &index {
+            let kind = match entry.entry_type {
+                EntryType::UserPrompt => "prompt",
+                EntryType::AgentMessage => "agent",
+            };
+            insert.execute(params![
+                entry.session_id,
+                entry.uuid,
+                entry.parent_uuid,
+                entry.timestamp.to_rfc3339(),
+                kind,
+                entry.source.label(),
+                entry.project_path.as_ref().map(|p| p.to_string_lossy().into_owned()),
+                entry.display_text,
+            ])?;
+        }
+    }
+    transaction.commit()?;
+
+    println!("Wrote {} entries to {}", index.len(), database.display());
+    Ok(())
+}
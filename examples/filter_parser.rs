@@ -0,0 +1,48 @@
+//! Embed the filter parser: validate user input, point at syntax errors and explain what a
+//! filter matches.
+//!
+//! ```bash
+//! cargo run --example filter_parser -- 'project:api type:user OR after:7d'
+//! cargo run --example filter_parser -- 'project:"unterminated'
+//! ```
+
+use ai_history_explorer::bookmarks::Bookmarks;
+use ai_history_explorer::filters::parser::{FilterSyntaxError, tokenize};
+use ai_history_explorer::filters::{FilterOperator, explain_filter, mark_span, parse_filter};
+use anyhow::Result;
+
+fn main() -> Result<()> {
+    let input = std::env::args().nth(1).unwrap_or_else(|| "project:api type:user".to_string());
+
+    let expr = match parse_filter(&input) {
+        Ok(expr) => expr,
+        Err(e) => {
+            // Syntax errors carry the span of the input they refer to
+            match e.downcast_ref::<FilterSyntaxError>() {
+                Some(syntax) => eprintln!("{}\n\n{}", syntax, mark_span(&input, syntax.span)),
+                None => eprintln!("{:#}", e),
+            }
+            std::process::exit(2);
+        }
+    };
+
+    println!("Clauses:");
+    for (i, clause) in expr.filters.iter().enumerate() {
+        if i > 0 {
+            let operator = match expr.operators[i - 1] {
+                FilterOperator::And => "AND",
+                FilterOperator::Or => "OR",
+            };
+            println!("  {}", operator);
+        }
+        println!("  {:?} = {:?}", clause.field, clause.value);
+    }
+
+    // Without an index the explanation still describes each clause (with zero matches)
+    let explanation = explain_filter(&expr, &tokenize(&input)?, &[], &Bookmarks::default())?;
+    println!("\nEvaluated as: {}", explanation.grouped());
+    for clause in &explanation.clauses {
+        println!("  {}: {}", clause.label(), clause.semantics);
+    }
+    Ok(())
+}
@@ -0,0 +1,49 @@
+//! Build the search index of a Claude directory and print usage statistics.
+//!
+//! ```bash
+//! cargo run --example index_stats -- ~/.claude
+//! ```
+
+use std::path::PathBuf;
+
+use ai_history_explorer::analytics::UsageSummary;
+use ai_history_explorer::indexer::{IndexReport, build_index_with_report};
+use ai_history_explorer::models::EntryType;
+use anyhow::{Context, Result};
+use chrono::Local;
+
+fn main() -> Result<()> {
+    let claude_dir = match std::env::args_os().nth(1) {
+        Some(dir) => PathBuf::from(dir),
+        None => dirs::home_dir().context("No home directory")?.join(".claude"),
+    };
+
+    // The report collects what was skipped; the index is sorted newest first
+    let mut report = IndexReport::default();
+    let index = build_index_with_report(&claude_dir, &mut report)?;
+
+    let prompts = index.iter().filter(|e| e.entry_type == EntryType::UserPrompt).count();
+    println!(
+        "{} entries ({} prompts, {} agent messages)",
+        index.len(),
+        prompts,
+        index.len() - prompts
+    );
+    if let (Some(newest), Some(oldest)) = (index.first(), index.last()) {
+        println!("From {} to {}", oldest.timestamp.date_naive(), newest.timestamp.date_naive());
+    }
+
+    let usage = UsageSummary::compute(&index, &Local);
+    println!("\nMost active projects:");
+    for project in usage.projects.iter().take(5) {
+        let name = project.project.as_ref().map_or("global".into(), |p| p.display().to_string());
+        println!("  {:<50} {:>5} prompts {:>3} sessions", name, project.prompts, project.sessions);
+    }
+    println!("\nMost used tools:");
+    for (tool, calls) in usage.tool_calls.iter().take(5) {
+        println!("  {:<20} {:>6}", tool, calls);
+    }
+
+    println!("\nIndex status: {:?} ({} items skipped)", report.status, report.skipped.len());
+    Ok(())
+}
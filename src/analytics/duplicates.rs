@@ -0,0 +1,155 @@
+//! Repeated prompts.
+//!
+//! Prompts retyped across sessions ("run the tests and fix failures", "commit this") are worth
+//! turning into a slash command or a snippet. Prompts are the same when their words match
+//! after lowercasing, dropping punctuation and a plural `s`, so "Run the tests!" and "run the
+//! test" are one prompt. Claude Code records each prompt both in `history.jsonl` and in the
+//! conversation file; copies in the same session less than [`MIRROR_WINDOW_SECS`] apart count
+//! as one occurrence.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::models::{EntryType, SearchEntry};
+
+/// Copies of a prompt in one session closer together than this are the same occurrence
+pub const MIRROR_WINDOW_SECS: i64 = 60;
+
+/// A prompt and how often it was typed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PromptGroup {
+    /// Newest occurrence
+    #[serde(flatten)]
+    pub entry: SearchEntry,
+    pub occurrences: usize,
+    pub sessions: usize,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// Lowercase words of `text`, without punctuation and with a plural `s` dropped
+pub fn normalized_words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty()).map(|word| {
+        let word = word.to_lowercase();
+        match word.strip_suffix('s') {
+            Some(stem) if stem.len() >= 3 && !stem.ends_with('s') => stem.to_string(),
+            _ => word,
+        }
+    })
+}
+
+/// Key under which near-identical prompts are grouped (empty for prompts without words)
+pub fn prompt_key(text: &str) -> String {
+    normalized_words(text).collect::<Vec<_>>().join(" ")
+}
+
+/// Collapse the user prompts of `index` into one group per distinct prompt, newest first
+pub fn group_prompts(index: &[SearchEntry]) -> Vec<PromptGroup> {
+    let mut by_key: HashMap<String, Vec<&SearchEntry>> = HashMap::new();
+    for entry in index.iter().filter(|e| e.entry_type == EntryType::UserPrompt) {
+        let key = prompt_key(&entry.display_text);
+        if !key.is_empty() {
+            by_key.entry(key).or_default().push(entry);
+        }
+    }
+
+    let mut groups: Vec<PromptGroup> = by_key
+        .into_values()
+        .map(|mut copies| {
+            copies.sort_by(|a, b| {
+                a.session_id.cmp(&b.session_id).then_with(|| a.timestamp.cmp(&b.timestamp))
+            });
+            let occurrences = copies
+                .iter()
+                .enumerate()
+                .filter(|(i, entry)| {
+                    *i == 0 || {
+                        let previous = copies[i - 1];
+                        previous.session_id != entry.session_id
+                            || (entry.timestamp - previous.timestamp).num_seconds()
+                                >= MIRROR_WINDOW_SECS
+                    }
+                })
+                .count();
+            let sessions: HashSet<&str> = copies.iter().map(|e| e.session_id.as_str()).collect();
+            let newest = copies.iter().max_by_key(|e| e.timestamp).expect("group is not empty");
+            PromptGroup {
+                entry: (*newest).clone(),
+                occurrences,
+                sessions: sessions.len(),
+                first_seen: copies.iter().map(|e| e.timestamp).min().expect("group is not empty"),
+                last_seen: newest.timestamp,
+            }
+        })
+        .collect();
+    groups.sort_by_key(|group| std::cmp::Reverse(group.last_seen));
+    groups
+}
+
+/// Prompts typed more than once, most repeated first (ties: most recently used first)
+pub fn repeated_prompts(index: &[SearchEntry]) -> Vec<PromptGroup> {
+    let mut groups: Vec<PromptGroup> =
+        group_prompts(index).into_iter().filter(|group| group.occurrences > 1).collect();
+    groups.sort_by(|a, b| {
+        b.occurrences.cmp(&a.occurrences).then_with(|| b.last_seen.cmp(&a.last_seen))
+    });
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(text: &str, session: &str, secs: i64) -> SearchEntry {
+        SearchEntry {
+            entry_type: EntryType::UserPrompt,
+            display_text: text.to_string(),
+            timestamp: DateTime::from_timestamp(1_704_099_600 + secs, 0).unwrap(),
+            session_id: session.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_prompt_key_ignores_case_punctuation_and_plurals() {
+        assert_eq!(prompt_key("Run the tests!"), prompt_key("run the test"));
+        assert_eq!(prompt_key("  fix   CI, please "), "fix ci please");
+        assert_ne!(prompt_key("run the tests"), prompt_key("run the tests again"));
+        assert_eq!(prompt_key("..."), "");
+    }
+
+    #[test]
+    fn test_group_prompts_counts_mirrored_copies_once() {
+        let mut index = vec![
+            // history.jsonl and conversation copies of the same prompt
+            prompt("Run the tests", "a", 0),
+            prompt("Run the tests", "a", 1),
+            // Retyped later in the same session
+            prompt("run the tests!", "a", 600),
+            prompt("run the test", "b", 3600),
+            prompt("commit this", "b", 3700),
+            prompt("...", "b", 3800),
+        ];
+        let mut reply = prompt("Run the tests", "b", 3601);
+        reply.entry_type = EntryType::AgentMessage;
+        index.push(reply);
+
+        let groups = group_prompts(&index);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].entry.display_text, "commit this");
+        assert_eq!(groups[0].occurrences, 1);
+
+        let tests = &groups[1];
+        assert_eq!(tests.entry.display_text, "run the test");
+        assert_eq!(tests.occurrences, 3);
+        assert_eq!(tests.sessions, 2);
+        assert_eq!(tests.first_seen, index[0].timestamp);
+        assert_eq!(tests.last_seen, index[3].timestamp);
+
+        let repeated = repeated_prompts(&index);
+        assert_eq!(repeated.len(), 1);
+        assert_eq!(repeated[0].occurrences, 3);
+    }
+}
@@ -0,0 +1,22 @@
+//! Analytics over parsed conversation history.
+//!
+//! Size reports work on full [`ConversationEntry`](crate::models::ConversationEntry) data
+//! rather than the truncated text kept in the search index, so sizes and counts reflect what
+//! was actually recorded in the conversation files. Activity breakdowns (per user and per
+//! session outcome), repeated prompts and the usage dashboard of `stats` work on the search
+//! index.
+
+pub mod duplicates;
+pub mod outcomes;
+pub mod tool_output;
+pub mod usage;
+pub mod users;
+
+pub use duplicates::{PromptGroup, group_prompts, normalized_words, prompt_key, repeated_prompts};
+pub use outcomes::{OutcomeStats, outcome_breakdown};
+pub use tool_output::{
+    ToolOutputReport, ToolResultRecord, ToolUsage, ToolUsageSort, build_tool_output_report,
+    collect_tool_outputs, format_bytes, scan_indexed_tool_outputs, scan_tool_outputs,
+};
+pub use usage::{ProjectUsage, UsageSummary};
+pub use users::{UNKNOWN_USER, UserStats, has_user_identities, user_breakdown};
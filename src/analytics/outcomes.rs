@@ -0,0 +1,80 @@
+//! Breakdown of sessions by outcome.
+//!
+//! Outcomes are guessed per conversation file while indexing (see
+//! [`classify_outcome`](crate::indexer::classify_outcome)); the breakdown counts the sessions of
+//! the index under each one, to show what share of agent sessions actually conclude.
+
+use std::collections::HashMap;
+
+use crate::models::{SearchEntry, SessionOutcome};
+
+/// Sessions with one outcome
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutcomeStats {
+    pub outcome: SessionOutcome,
+    pub sessions: usize,
+}
+
+/// Count the sessions of `index` per outcome, in [`SessionOutcome::ALL`] order
+///
+/// Sessions without an outcome are left out; the result is empty when no session has one.
+pub fn outcome_breakdown(index: &[SearchEntry]) -> Vec<OutcomeStats> {
+    let mut sessions: HashMap<&str, SessionOutcome> = HashMap::new();
+    for entry in index {
+        if let Some(outcome) = entry.outcome {
+            sessions.entry(entry.session_id.as_str()).or_insert(outcome);
+        }
+    }
+    if sessions.is_empty() {
+        return Vec::new();
+    }
+    SessionOutcome::ALL
+        .into_iter()
+        .map(|outcome| OutcomeStats {
+            outcome,
+            sessions: sessions.values().filter(|&&o| o == outcome).count(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+
+    use super::*;
+    use crate::models::EntryType;
+
+    fn entry(session: &str, outcome: Option<SessionOutcome>) -> SearchEntry {
+        SearchEntry {
+            entry_type: EntryType::UserPrompt,
+            display_text: "text".to_string(),
+            timestamp: DateTime::from_timestamp(0, 0).unwrap(),
+            session_id: session.to_string(),
+            outcome,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_outcome_breakdown_counts_sessions() {
+        let index = vec![
+            entry("s1", Some(SessionOutcome::Success)),
+            entry("s1", Some(SessionOutcome::Success)),
+            entry("s2", Some(SessionOutcome::Success)),
+            entry("s3", Some(SessionOutcome::Abandoned)),
+            entry("s4", None),
+        ];
+
+        let counts: Vec<_> =
+            outcome_breakdown(&index).iter().map(|s| (s.outcome, s.sessions)).collect();
+        assert_eq!(
+            counts,
+            vec![
+                (SessionOutcome::Success, 2),
+                (SessionOutcome::Abandoned, 1),
+                (SessionOutcome::Unclear, 0),
+            ]
+        );
+        assert!(outcome_breakdown(&[entry("s4", None)]).is_empty());
+    }
+}
@@ -0,0 +1,368 @@
+//! Tool-output size leaderboard.
+//!
+//! Measures how many bytes each tool result contributed to a conversation, attributes it to
+//! the tool that produced it (via the `tool_use_id` of the matching `tool_use` block), and
+//! aggregates per tool and project. Large, frequent outputs are what flood the context window,
+//! so the report lists both the single largest results and the most verbose tools.
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+use crate::indexer::{IndexReport, discover_projects};
+use crate::models::{ContentBlock, ConversationEntry, MessageContent, SearchEntry};
+use crate::parsers::{ParseOptions, parse_conversation_file_with_options};
+
+/// Tool name used when a result's `tool_use` block is not in the same file
+pub const UNKNOWN_TOOL: &str = "(unknown)";
+
+/// Size of a single tool result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolResultRecord {
+    pub tool: String,
+    pub project: Option<PathBuf>,
+    pub session_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub bytes: usize,
+}
+
+/// Aggregated output of one tool within one project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolUsage {
+    pub tool: String,
+    pub project: Option<PathBuf>,
+    pub calls: usize,
+    pub total_bytes: usize,
+    pub max_bytes: usize,
+}
+
+/// Column a [`ToolUsage`] table is sorted by (all numeric columns descending)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ToolUsageSort {
+    #[default]
+    TotalBytes,
+    Calls,
+    MaxBytes,
+    Tool,
+}
+
+impl ToolUsageSort {
+    /// Next sort column, cycling through all of them
+    pub fn next(self) -> Self {
+        match self {
+            ToolUsageSort::TotalBytes => ToolUsageSort::Calls,
+            ToolUsageSort::Calls => ToolUsageSort::MaxBytes,
+            ToolUsageSort::MaxBytes => ToolUsageSort::Tool,
+            ToolUsageSort::Tool => ToolUsageSort::TotalBytes,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ToolUsageSort::TotalBytes => "total bytes",
+            ToolUsageSort::Calls => "calls",
+            ToolUsageSort::MaxBytes => "largest",
+            ToolUsageSort::Tool => "tool",
+        }
+    }
+
+    /// Sort usages in place by this column
+    pub fn sort(self, usages: &mut [ToolUsage]) {
+        match self {
+            ToolUsageSort::TotalBytes => usages.sort_by_key(|u| Reverse(u.total_bytes)),
+            ToolUsageSort::Calls => usages.sort_by_key(|u| Reverse(u.calls)),
+            ToolUsageSort::MaxBytes => usages.sort_by_key(|u| Reverse(u.max_bytes)),
+            ToolUsageSort::Tool => {
+                usages.sort_by(|a, b| a.tool.cmp(&b.tool).then_with(|| a.project.cmp(&b.project)))
+            }
+        }
+    }
+}
+
+/// Largest individual results plus per-tool/project totals
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToolOutputReport {
+    /// Largest individual tool results, biggest first
+    pub largest: Vec<ToolResultRecord>,
+    /// Per tool and project, sorted by total bytes (descending)
+    pub usage: Vec<ToolUsage>,
+    pub total_results: usize,
+    pub total_bytes: usize,
+}
+
+/// Collect the size of every tool result in one conversation
+pub fn collect_tool_outputs(
+    entries: &[ConversationEntry],
+    project: Option<&Path>,
+) -> Vec<ToolResultRecord> {
+    let mut tool_names: HashMap<&str, &str> = HashMap::new();
+    for entry in entries {
+        if let MessageContent::Array(blocks) = &entry.message.content {
+            for block in blocks {
+                if let ContentBlock::ToolUse { id, name, .. } = block {
+                    tool_names.insert(id, name);
+                }
+            }
+        }
+    }
+
+    let mut records = Vec::new();
+    for entry in entries {
+        let MessageContent::Array(blocks) = &entry.message.content else {
+            continue;
+        };
+        for block in blocks {
+            if let ContentBlock::ToolResult { tool_use_id, content, .. } = block {
+                records.push(ToolResultRecord {
+                    tool: tool_names.get(tool_use_id.as_str()).unwrap_or(&UNKNOWN_TOOL).to_string(),
+                    project: project.map(Path::to_path_buf),
+                    session_id: entry.session_id.clone(),
+                    timestamp: entry.timestamp,
+                    bytes: content_bytes(content),
+                });
+            }
+        }
+    }
+    records
+}
+
+/// Scan every conversation file under `claude_dir` for tool results
+///
+/// Files larger than `max_file_size` bytes and unparseable files are skipped and recorded in
+/// `report`, matching index building.
+///
+/// # Errors
+///
+/// Returns an error if the projects directory cannot be read.
+pub fn scan_tool_outputs(
+    claude_dir: &Path,
+    max_file_size: u64,
+    report: &mut IndexReport,
+) -> Result<Vec<ToolResultRecord>> {
+    let options = ParseOptions::with_file_size_limit(max_file_size);
+    let mut records = Vec::new();
+    for project in discover_projects(claude_dir)? {
+        for agent_file in &project.agent_files {
+            match parse_conversation_file_with_options(agent_file, &options) {
+                Ok(parsed) => records
+                    .extend(collect_tool_outputs(&parsed.entries, Some(&project.decoded_path))),
+                Err(e) => report.record_skip(agent_file, format!("{:#}", e)),
+            }
+        }
+    }
+    Ok(records)
+}
+
+/// Scan the conversation files behind already-indexed entries for tool results
+///
+/// Each source file is parsed once; entries without a source file (history prompts) are
+/// ignored. Files that no longer parse or are larger than `max_file_size` bytes are skipped
+/// silently since the TUI owns the terminal.
+pub fn scan_indexed_tool_outputs(
+    entries: &[SearchEntry],
+    max_file_size: u64,
+) -> Vec<ToolResultRecord> {
+    let options = ParseOptions::with_file_size_limit(max_file_size);
+    let mut seen = HashSet::new();
+    let mut records = Vec::new();
+    for entry in entries {
+        let Some(path) = entry.source_file.as_deref() else {
+            continue;
+        };
+        if !seen.insert(path) {
+            continue;
+        }
+        if let Ok(parsed) = parse_conversation_file_with_options(path, &options) {
+            records.extend(collect_tool_outputs(&parsed.entries, entry.project_path.as_deref()));
+        }
+    }
+    records
+}
+
+/// Aggregate records into a leaderboard keeping the `top` largest individual results
+pub fn build_tool_output_report(records: Vec<ToolResultRecord>, top: usize) -> ToolOutputReport {
+    let mut usage: HashMap<(String, Option<PathBuf>), ToolUsage> = HashMap::new();
+    let mut total_bytes = 0;
+    for record in &records {
+        total_bytes += record.bytes;
+        let key = (record.tool.clone(), record.project.clone());
+        let entry = usage.entry(key).or_insert_with(|| ToolUsage {
+            tool: record.tool.clone(),
+            project: record.project.clone(),
+            calls: 0,
+            total_bytes: 0,
+            max_bytes: 0,
+        });
+        entry.calls += 1;
+        entry.total_bytes += record.bytes;
+        entry.max_bytes = entry.max_bytes.max(record.bytes);
+    }
+
+    let mut usage: Vec<ToolUsage> = usage.into_values().collect();
+    // Tie-break by name so output is deterministic
+    ToolUsageSort::Tool.sort(&mut usage);
+    ToolUsageSort::TotalBytes.sort(&mut usage);
+
+    let total_results = records.len();
+    let mut largest = records;
+    largest.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| b.timestamp.cmp(&a.timestamp)));
+    largest.truncate(top);
+
+    ToolOutputReport { largest, usage, total_results, total_bytes }
+}
+
+/// Bytes of text a tool result contributed
+///
+/// Strings count their length; text blocks (`{"type":"text","text":...}`) count their text;
+/// anything else counts its compact JSON serialization.
+fn content_bytes(value: &serde_json::Value) -> usize {
+    match value {
+        serde_json::Value::String(s) => s.len(),
+        serde_json::Value::Array(items) => items.iter().map(content_bytes).sum(),
+        serde_json::Value::Object(map) => match map.get("text") {
+            Some(serde_json::Value::String(text)) => text.len(),
+            _ => serde_json::to_string(value).map(|s| s.len()).unwrap_or(0),
+        },
+        serde_json::Value::Null => 0,
+        other => other.to_string().len(),
+    }
+}
+
+/// Format a byte count for display (`512 B`, `12.3 KB`, `4.0 MB`)
+pub fn format_bytes(bytes: usize) -> String {
+    const KB: f64 = 1024.0;
+    const MB: f64 = 1024.0 * 1024.0;
+    let b = bytes as f64;
+    if b >= MB {
+        format!("{:.1} MB", b / MB)
+    } else if b >= KB {
+        format!("{:.1} KB", b / KB)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::utils::DEFAULT_MAX_FILE_SIZE_BYTES;
+
+    const SESSION: &str = "550e8400-e29b-41d4-a716-446655440000";
+
+    fn conversation_lines() -> [String; 2] {
+        [
+            format!(
+                r#"{{"type":"assistant","message":{{"role":"assistant","content":[{{"type":"tool_use","id":"t1","name":"Bash","input":{{}}}},{{"type":"tool_use","id":"t2","name":"Read","input":{{}}}}]}},"timestamp":1000,"sessionId":"{SESSION}","uuid":"a1"}}"#
+            ),
+            format!(
+                r#"{{"type":"user","message":{{"role":"user","content":[{{"type":"tool_result","tool_use_id":"t1","content":"0123456789"}},{{"type":"tool_result","tool_use_id":"t2","content":[{{"type":"text","text":"abcd"}}]}},{{"type":"tool_result","tool_use_id":"t9","content":"xy"}}]}},"timestamp":2000,"sessionId":"{SESSION}","uuid":"u1"}}"#
+            ),
+        ]
+    }
+
+    fn conversation() -> Vec<ConversationEntry> {
+        conversation_lines().iter().map(|l| serde_json::from_str(l).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_collect_tool_outputs_attributes_tools() {
+        let records = collect_tool_outputs(&conversation(), Some(Path::new("/p")));
+
+        let summary: Vec<_> = records.iter().map(|r| (r.tool.as_str(), r.bytes)).collect();
+        assert_eq!(summary, vec![("Bash", 10), ("Read", 4), (UNKNOWN_TOOL, 2)]);
+        assert_eq!(records[0].project, Some(PathBuf::from("/p")));
+    }
+
+    #[test]
+    fn test_build_report_aggregates_and_ranks() {
+        let mut records = collect_tool_outputs(&conversation(), Some(Path::new("/p")));
+        records.extend(collect_tool_outputs(&conversation(), Some(Path::new("/q"))));
+        records[0].bytes = 100;
+
+        let report = build_tool_output_report(records, 2);
+
+        assert_eq!(report.total_results, 6);
+        assert_eq!(report.total_bytes, 100 + 4 + 2 + 10 + 4 + 2);
+        assert_eq!(report.largest.len(), 2);
+        assert_eq!(report.largest[0].bytes, 100);
+        assert_eq!(report.largest[1].bytes, 10);
+
+        let top = &report.usage[0];
+        assert_eq!((top.tool.as_str(), top.calls, top.total_bytes), ("Bash", 1, 100));
+        assert_eq!(report.usage.len(), 6);
+    }
+
+    #[test]
+    fn test_usage_sort_columns() {
+        let usage = |tool: &str, calls, total_bytes, max_bytes| ToolUsage {
+            tool: tool.to_string(),
+            project: None,
+            calls,
+            total_bytes,
+            max_bytes,
+        };
+        let mut usages = vec![usage("b", 5, 10, 9), usage("a", 1, 50, 50), usage("c", 3, 30, 12)];
+
+        ToolUsageSort::Calls.sort(&mut usages);
+        assert_eq!(usages[0].tool, "b");
+        ToolUsageSort::MaxBytes.sort(&mut usages);
+        assert_eq!(usages[0].tool, "a");
+        ToolUsageSort::Tool.sort(&mut usages);
+        assert_eq!(usages[2].tool, "c");
+        assert_eq!(ToolUsageSort::Tool.next(), ToolUsageSort::TotalBytes);
+    }
+
+    #[test]
+    fn test_scan_tool_outputs() {
+        let dir = TempDir::new().unwrap();
+        let project = dir.path().join("projects").join("-Users%2Ftest%2Fproject");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(project.join("agent-1.jsonl"), conversation_lines().join("\n")).unwrap();
+        fs::write(project.join("agent-2.jsonl"), "not json").unwrap();
+
+        let mut report = IndexReport::default();
+        let records =
+            scan_tool_outputs(dir.path(), DEFAULT_MAX_FILE_SIZE_BYTES, &mut report).unwrap();
+
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].project, Some(PathBuf::from("/Users/test/project")));
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].path, project.join("agent-2.jsonl"));
+    }
+
+    #[test]
+    fn test_scan_indexed_tool_outputs_parses_each_file_once() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("agent-1.jsonl");
+        fs::write(&file, conversation_lines().join("\n")).unwrap();
+
+        let entry = |source_file: Option<PathBuf>| SearchEntry {
+            entry_type: crate::models::EntryType::AgentMessage,
+            timestamp: DateTime::from_timestamp(0, 0).unwrap(),
+            project_path: Some(PathBuf::from("/p")),
+            session_id: SESSION.to_string(),
+            source_file,
+            ..Default::default()
+        };
+        let entries = vec![entry(Some(file.clone())), entry(Some(file)), entry(None)];
+
+        let records = scan_indexed_tool_outputs(&entries, DEFAULT_MAX_FILE_SIZE_BYTES);
+
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].project, Some(PathBuf::from("/p")));
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(2048), "2.0 KB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MB");
+    }
+}
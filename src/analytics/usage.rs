@@ -0,0 +1,211 @@
+//! Usage dashboard: where and when the history was written.
+//!
+//! Everything is computed from the search index: entries per project, prompts per day, week
+//! and hour of day, prompt length and how often each tool was called. Tool calls are counted
+//! from the `[Tool: name]` markers the indexer puts in agent messages.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+
+use chrono::{Datelike, IsoWeek, NaiveDate, TimeZone, Timelike};
+
+use crate::models::{EntryType, SearchEntry};
+
+/// Marker the indexer writes in front of each tool call in an agent message
+const TOOL_MARKER: &str = "[Tool: ";
+
+/// Activity in one project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectUsage {
+    /// `None` for entries without a project
+    pub project: Option<PathBuf>,
+    pub prompts: usize,
+    pub messages: usize,
+    pub sessions: usize,
+}
+
+/// Usage statistics of an index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsageSummary {
+    /// Most active first (by entries), ties by path
+    pub projects: Vec<ProjectUsage>,
+    pub prompts_per_day: BTreeMap<NaiveDate, usize>,
+    pub prompts_per_week: BTreeMap<IsoWeek, usize>,
+    /// Prompts per hour of the day (0-23)
+    pub prompts_per_hour: [usize; 24],
+    /// Mean prompt length in characters (`None` without prompts)
+    pub average_prompt_chars: Option<usize>,
+    /// Calls per tool, most used first, ties by name
+    pub tool_calls: Vec<(String, usize)>,
+}
+
+impl UsageSummary {
+    /// Compute the statistics, with days and hours in the time zone `tz`
+    pub fn compute<Tz: TimeZone>(index: &[SearchEntry], tz: &Tz) -> Self {
+        let mut projects: HashMap<Option<&PathBuf>, (ProjectUsage, HashSet<&str>)> = HashMap::new();
+        let mut prompts_per_day = BTreeMap::new();
+        let mut prompts_per_week = BTreeMap::new();
+        let mut prompts_per_hour = [0; 24];
+        let mut prompt_chars = 0;
+        let mut prompts = 0;
+        let mut tool_calls: HashMap<&str, usize> = HashMap::new();
+
+        for entry in index {
+            let (usage, sessions) =
+                projects.entry(entry.project_path.as_ref()).or_insert_with(|| {
+                    let usage = ProjectUsage {
+                        project: entry.project_path.clone(),
+                        prompts: 0,
+                        messages: 0,
+                        sessions: 0,
+                    };
+                    (usage, HashSet::new())
+                });
+            sessions.insert(entry.session_id.as_str());
+
+            match entry.entry_type {
+                EntryType::UserPrompt => {
+                    usage.prompts += 1;
+                    prompts += 1;
+                    prompt_chars += entry.display_text.chars().count();
+                    let local = entry.timestamp.with_timezone(tz);
+                    *prompts_per_day.entry(local.date_naive()).or_insert(0) += 1;
+                    *prompts_per_week.entry(local.iso_week()).or_insert(0) += 1;
+                    prompts_per_hour[local.hour() as usize] += 1;
+                }
+                EntryType::AgentMessage => {
+                    usage.messages += 1;
+                    for tool in tool_names(&entry.display_text) {
+                        *tool_calls.entry(tool).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        let mut projects: Vec<ProjectUsage> = projects
+            .into_values()
+            .map(|(mut usage, sessions)| {
+                usage.sessions = sessions.len();
+                usage
+            })
+            .collect();
+        projects.sort_by(|a, b| {
+            (b.prompts + b.messages)
+                .cmp(&(a.prompts + a.messages))
+                .then_with(|| a.project.cmp(&b.project))
+        });
+        let mut tool_calls: Vec<(String, usize)> =
+            tool_calls.into_iter().map(|(tool, calls)| (tool.to_string(), calls)).collect();
+        tool_calls.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        Self {
+            projects,
+            prompts_per_day,
+            prompts_per_week,
+            prompts_per_hour,
+            average_prompt_chars: (prompts > 0).then(|| prompt_chars / prompts),
+            tool_calls,
+        }
+    }
+
+    /// The `n` hours of the day with the most prompts, busiest first (ties: earlier hour)
+    pub fn busiest_hours(&self, n: usize) -> Vec<(u32, usize)> {
+        let mut hours: Vec<(u32, usize)> = (0..24u32)
+            .map(|hour| (hour, self.prompts_per_hour[hour as usize]))
+            .filter(|(_, prompts)| *prompts > 0)
+            .collect();
+        hours.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        hours.truncate(n);
+        hours
+    }
+
+    /// Mean prompts per day with at least one prompt
+    pub fn prompts_per_active_day(&self) -> Option<f64> {
+        let days = self.prompts_per_day.len();
+        let prompts: usize = self.prompts_per_day.values().sum();
+        (days > 0).then(|| prompts as f64 / days as f64)
+    }
+}
+
+/// Names of the tools called in an indexed agent message, in order
+fn tool_names(text: &str) -> impl Iterator<Item = &str> {
+    text.match_indices(TOOL_MARKER).filter_map(|(start, _)| {
+        let rest = &text[start + TOOL_MARKER.len()..];
+        let name = &rest[..rest.find(']')?];
+        (!name.is_empty() && !name.contains(char::is_whitespace)).then_some(name)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, FixedOffset, Utc};
+
+    use super::*;
+
+    fn entry(project: &str, entry_type: EntryType, text: &str, ts: &str) -> SearchEntry {
+        SearchEntry {
+            entry_type,
+            display_text: text.to_string(),
+            timestamp: ts.parse::<DateTime<Utc>>().unwrap(),
+            project_path: Some(PathBuf::from(project)),
+            session_id: format!("{}-session", project),
+            ..Default::default()
+        }
+    }
+
+    fn index() -> Vec<SearchEntry> {
+        vec![
+            entry("/work/api", EntryType::UserPrompt, "fix it", "2024-06-03T09:15:00Z"),
+            entry(
+                "/work/api",
+                EntryType::AgentMessage,
+                "[Tool: Bash] Input: {} [Tool: Read] Input: {} [Tool: Bash] Input: {}",
+                "2024-06-03T09:16:00Z",
+            ),
+            entry("/work/api", EntryType::UserPrompt, "and test", "2024-06-03T14:00:00Z"),
+            entry("/work/web", EntryType::UserPrompt, "style", "2024-06-12T09:30:00Z"),
+        ]
+    }
+
+    #[test]
+    fn test_compute() {
+        let summary = UsageSummary::compute(&index(), &Utc);
+
+        let api = &summary.projects[0];
+        assert_eq!(api.project, Some(PathBuf::from("/work/api")));
+        assert_eq!((api.prompts, api.messages, api.sessions), (2, 1, 1));
+        assert_eq!(summary.projects.len(), 2);
+
+        let days: Vec<_> = summary.prompts_per_day.values().copied().collect();
+        assert_eq!(days, vec![2, 1]);
+        assert_eq!(summary.prompts_per_week.len(), 2);
+        assert_eq!(summary.busiest_hours(2), vec![(9, 2), (14, 1)]);
+        assert_eq!(summary.prompts_per_active_day(), Some(1.5));
+        assert_eq!(summary.average_prompt_chars, Some(6));
+        assert_eq!(summary.tool_calls, vec![("Bash".to_string(), 2), ("Read".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_days_and_hours_follow_the_time_zone() {
+        let sydney = FixedOffset::east_opt(11 * 3600).unwrap();
+        let summary = UsageSummary::compute(&index(), &sydney);
+        assert_eq!(summary.busiest_hours(1), vec![(20, 2)]);
+        // 14:00 UTC is already the next day in Sydney
+        assert_eq!(summary.prompts_per_day.len(), 3);
+    }
+
+    #[test]
+    fn test_empty_index() {
+        let summary = UsageSummary::compute(&[], &Utc);
+        assert!(summary.projects.is_empty());
+        assert_eq!(summary.average_prompt_chars, None);
+        assert_eq!(summary.prompts_per_active_day(), None);
+        assert!(summary.busiest_hours(3).is_empty());
+    }
+
+    #[test]
+    fn test_tool_names() {
+        let names: Vec<_> = tool_names("[Tool: Edit][truncated] Input: [Tool: ] x").collect();
+        assert_eq!(names, vec!["Edit"]);
+    }
+}
@@ -0,0 +1,112 @@
+//! Per-user breakdown for shared histories.
+//!
+//! When several people use one machine account, conversations may record who was behind them
+//! (see [`ConversationEntry::user_identity`](crate::models::ConversationEntry::user_identity)).
+//! The breakdown counts entries, sessions and activity range per identity.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+
+use crate::models::{EntryType, SearchEntry};
+
+/// Label for entries without a recorded user
+pub const UNKNOWN_USER: &str = "(unknown)";
+
+/// Activity of one user identity
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserStats {
+    /// Identity, or [`UNKNOWN_USER`]
+    pub user: String,
+    pub entries: usize,
+    pub prompts: usize,
+    pub sessions: usize,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// Group `index` by user, most active first
+///
+/// Entries without a user are grouped under [`UNKNOWN_USER`]. Ties are broken by name.
+pub fn user_breakdown(index: &[SearchEntry]) -> Vec<UserStats> {
+    let mut by_user: HashMap<&str, (UserStats, HashSet<&str>)> = HashMap::new();
+    for entry in index {
+        let user = entry.user.as_deref().unwrap_or(UNKNOWN_USER);
+        let (stats, sessions) = by_user.entry(user).or_insert_with(|| {
+            let stats = UserStats {
+                user: user.to_string(),
+                entries: 0,
+                prompts: 0,
+                sessions: 0,
+                first_seen: entry.timestamp,
+                last_seen: entry.timestamp,
+            };
+            (stats, HashSet::new())
+        });
+        stats.entries += 1;
+        if entry.entry_type == EntryType::UserPrompt {
+            stats.prompts += 1;
+        }
+        stats.first_seen = stats.first_seen.min(entry.timestamp);
+        stats.last_seen = stats.last_seen.max(entry.timestamp);
+        sessions.insert(entry.session_id.as_str());
+    }
+
+    let mut breakdown: Vec<UserStats> = by_user
+        .into_values()
+        .map(|(mut stats, sessions)| {
+            stats.sessions = sessions.len();
+            stats
+        })
+        .collect();
+    breakdown.sort_by(|a, b| b.entries.cmp(&a.entries).then_with(|| a.user.cmp(&b.user)));
+    breakdown
+}
+
+/// Whether any entry has a recorded user (otherwise a breakdown is not worth showing)
+pub fn has_user_identities(index: &[SearchEntry]) -> bool {
+    index.iter().any(|entry| entry.user.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(user: Option<&str>, session: &str, entry_type: EntryType, ts: i64) -> SearchEntry {
+        SearchEntry {
+            entry_type,
+            display_text: "text".to_string(),
+            timestamp: DateTime::from_timestamp_millis(ts).unwrap(),
+            session_id: session.to_string(),
+            user: user.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_user_breakdown_groups_and_sorts() {
+        let index = vec![
+            entry(Some("bob"), "s3", EntryType::UserPrompt, 5_000),
+            entry(Some("alice"), "s1", EntryType::AgentMessage, 4_000),
+            entry(Some("alice"), "s1", EntryType::UserPrompt, 3_000),
+            entry(Some("alice"), "s2", EntryType::UserPrompt, 2_000),
+            entry(None, "s4", EntryType::UserPrompt, 1_000),
+        ];
+
+        let breakdown = user_breakdown(&index);
+
+        let users: Vec<_> = breakdown.iter().map(|s| s.user.as_str()).collect();
+        assert_eq!(users, vec!["alice", UNKNOWN_USER, "bob"]);
+        let alice = &breakdown[0];
+        assert_eq!((alice.entries, alice.prompts, alice.sessions), (3, 2, 2));
+        assert_eq!(alice.first_seen.timestamp_millis(), 2_000);
+        assert_eq!(alice.last_seen.timestamp_millis(), 4_000);
+    }
+
+    #[test]
+    fn test_has_user_identities() {
+        assert!(!has_user_identities(&[entry(None, "s1", EntryType::UserPrompt, 0)]));
+        assert!(has_user_identities(&[entry(Some("a"), "s1", EntryType::UserPrompt, 0)]));
+        assert!(user_breakdown(&[]).is_empty());
+    }
+}
@@ -0,0 +1,167 @@
+//! Starred entries.
+//!
+//! Entries starred in the TUI (Ctrl+F) are saved to `bookmarks.json` in the cache directory
+//! and can be filtered with `starred:true`. An entry is identified by its session id and
+//! message uuid; prompts from `history.jsonl` have no uuid and are identified by their
+//! timestamp instead.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::cache::CACHE_DIR_NAME;
+use crate::models::SearchEntry;
+use crate::utils::{create_private_dir_all, write_private_file};
+
+/// Name of the bookmarks file inside the cache directory
+pub const BOOKMARKS_FILE_NAME: &str = "bookmarks.json";
+
+/// Identifies a starred entry across runs
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BookmarkKey {
+    pub session_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    /// Only set for entries without a uuid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+impl BookmarkKey {
+    pub fn of(entry: &SearchEntry) -> Self {
+        Self {
+            session_id: entry.session_id.clone(),
+            uuid: entry.uuid.clone(),
+            timestamp: entry.uuid.is_none().then_some(entry.timestamp),
+        }
+    }
+}
+
+/// Set of starred entries
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Bookmarks {
+    starred: BTreeSet<BookmarkKey>,
+}
+
+impl Bookmarks {
+    /// Default location of the bookmarks file, if the platform has a cache directory
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join(CACHE_DIR_NAME).join(BOOKMARKS_FILE_NAME))
+    }
+
+    /// Read the bookmarks at the default location (none without a cache directory)
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Bookmarks::load`].
+    pub fn load_default() -> Result<Self> {
+        Self::default_path().map_or_else(|| Ok(Self::default()), |path| Self::load(&path))
+    }
+
+    /// Read the bookmarks file; a missing file means no bookmarks
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed, so that saving
+    /// doesn't overwrite bookmarks that were only unreadable.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", path.display()));
+            }
+        };
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse bookmarks in {}", path.display()))
+    }
+
+    /// Write the bookmarks, creating their directory owner-only if needed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            create_private_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize bookmarks")?;
+        write_private_file(path, json)
+    }
+
+    pub fn contains(&self, entry: &SearchEntry) -> bool {
+        !self.starred.is_empty() && self.starred.contains(&BookmarkKey::of(entry))
+    }
+
+    /// Star `entry`, or unstar it if it was starred; returns whether it is starred now
+    pub fn toggle(&mut self, entry: &SearchEntry) -> bool {
+        let key = BookmarkKey::of(entry);
+        if self.starred.remove(&key) {
+            false
+        } else {
+            self.starred.insert(key);
+            true
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.starred.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.starred.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::EntryType;
+
+    fn entry(uuid: Option<&str>, timestamp: i64) -> SearchEntry {
+        SearchEntry {
+            entry_type: EntryType::UserPrompt,
+            display_text: "fix the parser".to_string(),
+            timestamp: DateTime::from_timestamp(timestamp, 0).unwrap(),
+            session_id: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+            uuid: uuid.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_toggle_and_roundtrip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("nested").join(BOOKMARKS_FILE_NAME);
+        let message = entry(Some("u1"), 1_704_099_600);
+        let prompt = entry(None, 1_704_099_600);
+
+        let mut bookmarks = Bookmarks::load(&path).unwrap();
+        assert!(bookmarks.is_empty());
+        assert!(bookmarks.toggle(&message));
+        assert!(bookmarks.toggle(&prompt));
+        assert!(bookmarks.contains(&message));
+        // Prompts without a uuid are told apart by their timestamp
+        assert!(!bookmarks.contains(&entry(None, 1_704_099_601)));
+        bookmarks.save(&path).unwrap();
+
+        let mut loaded = Bookmarks::load(&path).unwrap();
+        assert_eq!(loaded, bookmarks);
+        assert!(!loaded.toggle(&message));
+        assert_eq!(loaded.len(), 1);
+    }
+
+    #[test]
+    fn test_load_rejects_corrupt_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(BOOKMARKS_FILE_NAME);
+        std::fs::write(&path, "{not json").unwrap();
+
+        let err = Bookmarks::load(&path).unwrap_err();
+        assert!(format!("{:#}", err).contains("Failed to parse bookmarks"));
+    }
+}
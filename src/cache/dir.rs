@@ -0,0 +1,244 @@
+//! Cache directory selection with a startup writability check.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+
+use crate::utils::{create_private_dir_all, format_path_with_tilde, write_private_file};
+
+/// Name of the cache directory under the platform cache directory
+pub const CACHE_DIR_NAME: &str = "ai-history-explorer";
+
+/// File written and removed again to check that a directory is writable
+const PROBE_FILE_NAME: &str = ".write-test";
+
+/// Marker left in the temp-dir fallback once the fallback notice has been shown
+const NOTICE_MARKER_NAME: &str = ".fallback-notice-shown";
+
+/// Where the cache is kept
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CacheLocation {
+    /// The platform cache directory
+    Platform(PathBuf),
+    /// A private directory under the system temp dir (platform directory not writable)
+    TempDir(PathBuf),
+    /// Nothing is persisted (no writable directory found)
+    InMemory,
+}
+
+impl CacheLocation {
+    /// Directory the cache is persisted in, if any
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            CacheLocation::Platform(path) | CacheLocation::TempDir(path) => Some(path),
+            CacheLocation::InMemory => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CacheLocation::Platform(_) => "platform cache directory",
+            CacheLocation::TempDir(_) => "temp dir fallback",
+            CacheLocation::InMemory => "in memory only",
+        }
+    }
+}
+
+/// The selected cache directory and why any preferred candidate was rejected
+#[derive(Debug, Clone)]
+pub struct CacheDir {
+    pub location: CacheLocation,
+    /// One message per rejected candidate directory, in the order they were tried
+    pub problems: Vec<String>,
+    /// Whether the fallback notice should be shown (only once per fallback directory)
+    notice_pending: bool,
+}
+
+impl CacheDir {
+    /// Pick the platform cache directory, falling back to the temp dir, then to memory
+    pub fn resolve() -> Self {
+        let platform = dirs::cache_dir().map(|dir| dir.join(CACHE_DIR_NAME));
+        let fallback = std::env::temp_dir().join(format!("{}-{}", CACHE_DIR_NAME, user_suffix()));
+        Self::resolve_from(platform, fallback)
+    }
+
+    /// Like [`CacheDir::resolve`] with explicit candidate directories
+    ///
+    /// Each candidate is created (owner-only) if missing and checked by writing a probe file.
+    pub fn resolve_from(platform: Option<PathBuf>, fallback: PathBuf) -> Self {
+        let mut problems = Vec::new();
+
+        match platform {
+            Some(dir) => match check_writable(&dir) {
+                Ok(()) => {
+                    return Self {
+                        location: CacheLocation::Platform(dir),
+                        problems,
+                        notice_pending: false,
+                    };
+                }
+                Err(e) => problems.push(format!("{:#}", e)),
+            },
+            None => problems.push("No platform cache directory on this system".to_string()),
+        }
+
+        match check_writable(&fallback) {
+            Ok(()) => {
+                let notice_pending = !fallback.join(NOTICE_MARKER_NAME).exists();
+                Self { location: CacheLocation::TempDir(fallback), problems, notice_pending }
+            }
+            Err(e) => {
+                problems.push(format!("{:#}", e));
+                Self { location: CacheLocation::InMemory, problems, notice_pending: true }
+            }
+        }
+    }
+
+    /// Cache kept in memory without trying any directory
+    pub fn in_memory() -> Self {
+        Self { location: CacheLocation::InMemory, problems: Vec::new(), notice_pending: false }
+    }
+
+    /// Notice to show when the platform directory could not be used
+    ///
+    /// Returned the first time a temp-dir fallback is used, and on every run when the cache is
+    /// memory-only (there is nowhere to remember that it was shown). Call
+    /// [`CacheDir::mark_notice_shown`] after showing it.
+    pub fn notice(&self) -> Option<String> {
+        if !self.notice_pending {
+            return None;
+        }
+        let reason = self.problems.first().map(String::as_str).unwrap_or("unknown error");
+        Some(match &self.location {
+            CacheLocation::TempDir(path) => format!(
+                "Note: cache directory is not writable ({}); caching in {} instead",
+                reason,
+                format_path_with_tilde(path)
+            ),
+            _ => format!(
+                "Note: no writable cache directory ({}); the index is cached in memory for this run only",
+                reason
+            ),
+        })
+    }
+
+    /// Remember that the fallback notice was shown, so later runs stay quiet
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the marker can't be written; the notice is then shown again on the
+    /// next run.
+    pub fn mark_notice_shown(&mut self) -> Result<()> {
+        self.notice_pending = false;
+        match &self.location {
+            CacheLocation::TempDir(path) => write_private_file(&path.join(NOTICE_MARKER_NAME), ""),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Create `dir` if needed and check that files can be written to it
+///
+/// On Unix the directory must also belong to the current user, so a directory pre-created by
+/// someone else in a shared temp dir is never used.
+fn check_writable(dir: &Path) -> Result<()> {
+    create_private_dir_all(dir)?;
+    let metadata =
+        fs::symlink_metadata(dir).with_context(|| format!("Failed to read {}", dir.display()))?;
+    if !metadata.is_dir() {
+        bail!("{} is not a directory", dir.display());
+    }
+
+    let probe = dir.join(PROBE_FILE_NAME);
+    write_private_file(&probe, "")
+        .with_context(|| format!("{} is not writable", format_path_with_tilde(dir)))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let probe_owner = fs::metadata(&probe).map(|m| m.uid()).ok();
+        if probe_owner != Some(metadata.uid()) {
+            let _ = fs::remove_file(&probe);
+            bail!("{} belongs to another user", dir.display());
+        }
+    }
+
+    fs::remove_file(&probe).with_context(|| format!("Failed to remove {}", probe.display()))
+}
+
+/// Per-user suffix for the shared temp dir fallback
+fn user_suffix() -> String {
+    let user = std::env::var("USER").or_else(|_| std::env::var("USERNAME")).unwrap_or_default();
+    let user: String =
+        user.chars().filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_')).collect();
+    if user.is_empty() { "user".to_string() } else { user }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// A path that can't be created even as root: its parent is a regular file
+    fn unwritable(temp: &TempDir) -> PathBuf {
+        let file = temp.path().join("not-a-dir");
+        fs::write(&file, "").unwrap();
+        file.join("cache")
+    }
+
+    #[test]
+    fn test_resolve_uses_writable_platform_dir() {
+        let temp = TempDir::new().unwrap();
+        let platform = temp.path().join("cache");
+
+        let dir = CacheDir::resolve_from(Some(platform.clone()), temp.path().join("fallback"));
+
+        assert_eq!(dir.location, CacheLocation::Platform(platform.clone()));
+        assert!(dir.problems.is_empty());
+        assert!(dir.notice().is_none());
+        assert!(platform.is_dir());
+        assert!(!platform.join(PROBE_FILE_NAME).exists());
+    }
+
+    #[test]
+    fn test_resolve_falls_back_to_temp_dir_once() {
+        let temp = TempDir::new().unwrap();
+        let fallback = temp.path().join("fallback");
+
+        let mut dir = CacheDir::resolve_from(Some(unwritable(&temp)), fallback.clone());
+
+        assert_eq!(dir.location, CacheLocation::TempDir(fallback.clone()));
+        assert_eq!(dir.problems.len(), 1);
+        let notice = dir.notice().unwrap();
+        assert!(notice.contains("not writable") || notice.contains("Failed to create"));
+        assert!(notice.contains("caching in"));
+
+        dir.mark_notice_shown().unwrap();
+        assert!(dir.notice().is_none());
+
+        // The next run finds the marker and stays quiet
+        let again = CacheDir::resolve_from(Some(unwritable(&temp)), fallback);
+        assert!(again.notice().is_none());
+    }
+
+    #[test]
+    fn test_resolve_falls_back_to_memory() {
+        let temp = TempDir::new().unwrap();
+
+        let dir = CacheDir::resolve_from(None, unwritable(&temp));
+
+        assert_eq!(dir.location, CacheLocation::InMemory);
+        assert_eq!(dir.problems.len(), 2);
+        assert!(dir.location.path().is_none());
+        assert!(dir.notice().unwrap().contains("in memory for this run only"));
+    }
+
+    #[test]
+    fn test_user_suffix_is_path_safe() {
+        let suffix = user_suffix();
+        assert!(!suffix.is_empty());
+        assert!(suffix.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+    }
+}
@@ -0,0 +1,639 @@
+//! Search entries per agent conversation file, persisted between runs.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+use std::time::SystemTime;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use super::dir::CacheDir;
+use crate::error::ExplorerError;
+use crate::indexer::Diagnostic;
+use crate::models::SearchEntry;
+use crate::parsers::ParseOptions;
+
+/// Name of the cache file; the version is bumped whenever the stored layout changes
+pub const INDEX_FILE_NAME: &str = "index-v10.bin";
+
+/// How the index cache is stored (`index_storage` in the config)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexStorage {
+    /// One bincode file, rewritten on every save
+    #[default]
+    Bincode,
+    /// A SQLite database with a full-text table, updated file by file (`sqlite` feature)
+    Sqlite,
+}
+
+impl IndexStorage {
+    /// Name of the cache file in the cache directory
+    pub fn file_name(self) -> &'static str {
+        match self {
+            IndexStorage::Bincode => INDEX_FILE_NAME,
+            IndexStorage::Sqlite => SQLITE_FILE_NAME,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            IndexStorage::Bincode => "bincode",
+            IndexStorage::Sqlite => "sqlite",
+        }
+    }
+}
+
+/// Name of the SQLite cache database (the schema version is kept inside it)
+pub const SQLITE_FILE_NAME: &str = "index.sqlite3";
+
+/// Storage of caches opened from now on (an [`IndexStorage`] as `u8`)
+static INDEX_STORAGE: AtomicU8 = AtomicU8::new(IndexStorage::Bincode as u8);
+
+/// Store caches opened from now on as `storage`
+///
+/// Returns `false`, keeping bincode, for SQLite in a build without the `sqlite` feature.
+pub fn set_index_storage(storage: IndexStorage) -> bool {
+    if storage == IndexStorage::Sqlite && !cfg!(feature = "sqlite") {
+        return false;
+    }
+    INDEX_STORAGE.store(storage as u8, Ordering::Relaxed);
+    true
+}
+
+/// Storage caches are opened with (see [`set_index_storage`])
+pub fn index_storage() -> IndexStorage {
+    match INDEX_STORAGE.load(Ordering::Relaxed) {
+        value if value == IndexStorage::Sqlite as u8 => IndexStorage::Sqlite,
+        _ => IndexStorage::Bincode,
+    }
+}
+
+/// Files whose map slots are reserved before reading a cache file's records
+#[cfg(feature = "cache")]
+const MAX_PREALLOCATED_FILES: usize = 65_536;
+
+/// Search entries of one conversation file plus what parsing left out of them
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedFile {
+    pub entries: Vec<SearchEntry>,
+    /// Malformed lines that were skipped
+    pub skipped_lines: usize,
+    /// Bytes of tool output dropped by sampling/skipping oversized tool results
+    pub tool_output_bytes_skipped: usize,
+    /// Why the first few skipped lines failed to parse
+    pub line_errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct CachedFile {
+    pub(super) len: u64,
+    pub(super) modified: SystemTime,
+    pub(super) indexed: IndexedFile,
+}
+
+/// Thread-safe cache of indexed conversation files keyed by path, size and mtime
+pub struct IndexCache {
+    dir: CacheDir,
+    options: ParseOptions,
+    storage: IndexStorage,
+    files: Mutex<HashMap<PathBuf, CachedFile>>,
+    hits: AtomicUsize,
+    changed: AtomicBool,
+    /// Why the cache file was ignored when opening, until reported
+    load_problems: Mutex<Vec<Diagnostic>>,
+}
+
+impl IndexCache {
+    /// Open the cache in `dir`, loading files indexed with the same parse options
+    ///
+    /// The cache is stored as set with [`set_index_storage`]. A missing, unreadable or
+    /// outdated cache file just starts an empty cache; why an unreadable one was ignored is
+    /// kept for [`take_load_problems`](Self::take_load_problems).
+    pub fn open(dir: CacheDir, options: ParseOptions) -> Self {
+        let storage = index_storage();
+        let mut load_problems = Vec::new();
+        let files = match dir.location.path() {
+            Some(path) => {
+                let path = path.join(storage.file_name());
+                load(storage, &path, &options).unwrap_or_else(|e| {
+                    let message = format!("{:#}; starting an empty cache", e);
+                    load_problems.push(Diagnostic { path, message });
+                    HashMap::new()
+                })
+            }
+            None => HashMap::new(),
+        };
+        Self {
+            dir,
+            options,
+            storage,
+            files: Mutex::new(files),
+            hits: AtomicUsize::new(0),
+            changed: AtomicBool::new(false),
+            load_problems: Mutex::new(load_problems),
+        }
+    }
+
+    /// Why the cache file was ignored when opening, if it was; returned once
+    ///
+    /// Index building records them as diagnostics of its report.
+    pub fn take_load_problems(&self) -> Vec<Diagnostic> {
+        std::mem::take(&mut *self.load_problems.lock().unwrap_or_else(|p| p.into_inner()))
+    }
+
+    /// Cache that is never persisted
+    pub fn in_memory(options: ParseOptions) -> Self {
+        Self::open(CacheDir::in_memory(), options)
+    }
+
+    /// Whether this build can persist the cache (the `cache` feature)
+    pub const fn persistence_enabled() -> bool {
+        cfg!(feature = "cache")
+    }
+
+    pub fn dir(&self) -> &CacheDir {
+        &self.dir
+    }
+
+    pub fn storage(&self) -> IndexStorage {
+        self.storage
+    }
+
+    /// Parse options the cached entries were built with
+    pub fn options(&self) -> &ParseOptions {
+        &self.options
+    }
+
+    /// Path of the cache file, if the cache is persisted
+    pub fn index_file(&self) -> Option<PathBuf> {
+        if !Self::persistence_enabled() {
+            return None;
+        }
+        self.dir.location.path().map(|dir| dir.join(self.storage.file_name()))
+    }
+
+    /// Number of cached files
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of lookups answered from the cache so far
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of cached entries of each cached file
+    pub fn entry_counts(&self) -> Vec<(PathBuf, usize)> {
+        self.lock().iter().map(|(path, file)| (path.clone(), file.indexed.entries.len())).collect()
+    }
+
+    /// Cached entries of `path` (not counted as a hit), and whether the file still has the
+    /// size and modification time they were cached for
+    pub fn cached(&self, path: &Path) -> Option<(IndexedFile, bool)> {
+        let files = self.lock();
+        let cached = files.get(path)?;
+        let current = fs::metadata(path)
+            .ok()
+            .and_then(|m| Some((m.len(), m.modified().ok()?)))
+            .is_some_and(|(len, modified)| cached.len == len && cached.modified == modified);
+        Some((cached.indexed.clone(), current))
+    }
+
+    /// When the cache file was last written, if it exists
+    pub fn last_saved(&self) -> Option<SystemTime> {
+        self.index_file().and_then(|path| fs::metadata(path).ok()?.modified().ok())
+    }
+
+    /// Drop every cached file and delete the cache file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache file exists but cannot be deleted.
+    pub fn clear(&self) -> Result<(), ExplorerError> {
+        self.lock().clear();
+        self.changed.store(false, Ordering::Relaxed);
+        let Some(path) = self.index_file() else {
+            return Ok(());
+        };
+        // SQLite keeps recent writes in a write-ahead log next to the database
+        let logs = ["-wal", "-shm"].map(|suffix| {
+            let mut name = path.clone().into_os_string();
+            name.push(suffix);
+            PathBuf::from(name)
+        });
+        for file in logs.iter().chain([&path]) {
+            match fs::remove_file(file) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(ExplorerError::CacheWrite { path: file.clone(), source: e.into() });
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop files that no longer exist and rewrite the cache file; returns how many were dropped
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache file cannot be written.
+    pub fn compact(&self) -> Result<usize, ExplorerError> {
+        let mut files = self.lock();
+        let before = files.len();
+        files.retain(|file, _| file.exists());
+        if let Some(path) = self.index_file() {
+            store(self.storage, &path, &self.options, &files)
+                .map_err(|source| ExplorerError::CacheWrite { path, source })?;
+        }
+        self.changed.store(false, Ordering::Relaxed);
+        Ok(before - files.len())
+    }
+
+    /// Cached entries of `path`, or the result of `index` (cached for next time)
+    ///
+    /// The cached copy is only used if the file's size and modification time are unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `index`; failures are not cached.
+    pub fn get_or_index(
+        &self,
+        path: &Path,
+        index: impl FnOnce() -> Result<IndexedFile>,
+    ) -> Result<IndexedFile> {
+        // Stamp before parsing, so a write during the parse invalidates the entry next time
+        let stamp = fs::metadata(path).ok().and_then(|m| Some((m.len(), m.modified().ok()?)));
+
+        if let Some((len, modified)) = stamp
+            && let Some(cached) = self.lock().get(path)
+            && cached.len == len
+            && cached.modified == modified
+        {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(cached.indexed.clone());
+        }
+
+        let indexed = index()?;
+        if let Some((len, modified)) = stamp {
+            self.lock()
+                .insert(path.to_path_buf(), CachedFile { len, modified, indexed: indexed.clone() });
+            self.changed.store(true, Ordering::Relaxed);
+        }
+        Ok(indexed)
+    }
+
+    /// Write the cache to disk if anything changed, dropping files that no longer exist
+    ///
+    /// Does nothing for an in-memory cache.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache file cannot be written.
+    pub fn save(&self) -> Result<(), ExplorerError> {
+        let Some(path) = self.index_file() else {
+            return Ok(());
+        };
+        if !self.changed.swap(false, Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let mut files = self.lock();
+        files.retain(|file, _| file.exists());
+        store(self.storage, &path, &self.options, &files)
+            .map_err(|source| ExplorerError::CacheWrite { path, source })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<PathBuf, CachedFile>> {
+        self.files.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Files of the cache at `path` stored as `storage`
+fn load(
+    storage: IndexStorage,
+    path: &Path,
+    options: &ParseOptions,
+) -> Result<HashMap<PathBuf, CachedFile>> {
+    match storage {
+        #[cfg(feature = "sqlite")]
+        IndexStorage::Sqlite => super::sqlite::load(path, options),
+        _ => load_bincode(path, options),
+    }
+}
+
+/// Write the cache at `path` as `storage`
+fn store(
+    storage: IndexStorage,
+    path: &Path,
+    options: &ParseOptions,
+    files: &HashMap<PathBuf, CachedFile>,
+) -> Result<()> {
+    match storage {
+        #[cfg(feature = "sqlite")]
+        IndexStorage::Sqlite => super::sqlite::store(path, options, files),
+        _ => store_bincode(path, options, files),
+    }
+}
+
+/// Read the cache file one record at a time
+///
+/// The file holds the parse options, the number of files and one `(path, file)` record per
+/// file (bincode's encoding of the options and the map, written piece by piece). Reading it
+/// from a buffered stream never holds the whole file in memory next to the decoded entries,
+/// and a cache built with other options is dropped after reading just its header.
+///
+/// Every read is limited to the file's length: bincode allocates whatever length prefix it
+/// reads, so a corrupt prefix would otherwise abort the process instead of failing the load.
+#[cfg(feature = "cache")]
+fn load_bincode(path: &Path, options: &ParseOptions) -> Result<HashMap<PathBuf, CachedFile>> {
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(anyhow::anyhow!("Failed to read index cache: {}", e)),
+    };
+    let len =
+        file.metadata().map_err(|e| anyhow::anyhow!("Failed to read index cache: {}", e))?.len();
+    match read_records(std::io::BufReader::new(file), len, options) {
+        Ok(files) => Ok(files.unwrap_or_default()),
+        Err(e) => Err(anyhow::anyhow!("Corrupt index cache: {}", e)),
+    }
+}
+
+/// Records of a cache built with `options`, or `None` for a cache built with other options
+///
+/// `len` is the length of the file behind `reader`; no single read may need more.
+#[cfg(feature = "cache")]
+fn read_records(
+    mut reader: impl std::io::Read,
+    len: u64,
+    options: &ParseOptions,
+) -> bincode::Result<Option<HashMap<PathBuf, CachedFile>>> {
+    use bincode::Options;
+
+    // The encoding of `bincode::serialize_into`, plus the limit
+    let bincode = bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .with_limit(len);
+    let stored: ParseOptions = bincode.deserialize_from(&mut reader)?;
+    if !stored.same_entries(options) {
+        return Ok(None);
+    }
+    let count: u64 = bincode.deserialize_from(&mut reader)?;
+    // The count is only a hint: a corrupt file must not reserve gigabytes up front
+    let mut files = HashMap::with_capacity((count as usize).min(MAX_PREALLOCATED_FILES));
+    for _ in 0..count {
+        let (path, file): (PathBuf, CachedFile) = bincode.deserialize_from(&mut reader)?;
+        files.insert(path, file);
+    }
+    Ok(Some(files))
+}
+
+#[cfg(not(feature = "cache"))]
+fn load_bincode(_path: &Path, _options: &ParseOptions) -> Result<HashMap<PathBuf, CachedFile>> {
+    Ok(HashMap::new())
+}
+
+/// Write via a temporary file and rename, so readers never see a partial cache
+#[cfg(feature = "cache")]
+fn store_bincode(
+    path: &Path,
+    options: &ParseOptions,
+    files: &HashMap<PathBuf, CachedFile>,
+) -> Result<()> {
+    use anyhow::Context;
+
+    let temp = path.with_extension("bin.tmp");
+    let mut writer = std::io::BufWriter::new(crate::utils::create_private_file(&temp)?);
+    // Record by record in the layout `read_records` expects, without serializing the whole
+    // cache into memory first
+    let written: bincode::Result<()> = (|| {
+        bincode::serialize_into(&mut writer, options)?;
+        bincode::serialize_into(&mut writer, &(files.len() as u64))?;
+        for record in files {
+            bincode::serialize_into(&mut writer, &record)?;
+        }
+        Ok(())
+    })();
+    written.context("Failed to serialize index cache")?;
+    writer
+        .into_inner()
+        .map_err(|e| e.into_error())
+        .with_context(|| format!("Failed to write {}", temp.display()))?;
+    fs::rename(&temp, path).with_context(|| format!("Failed to rename {}", temp.display()))
+}
+
+#[cfg(not(feature = "cache"))]
+fn store_bincode(
+    _path: &Path,
+    _options: &ParseOptions,
+    _files: &HashMap<PathBuf, CachedFile>,
+) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::models::EntryType;
+
+    fn indexed(text: &str) -> IndexedFile {
+        IndexedFile {
+            entries: vec![SearchEntry {
+                entry_type: EntryType::AgentMessage,
+                display_text: text.to_string(),
+                timestamp: DateTime::from_timestamp_millis(1_704_099_600_000).unwrap(),
+                project_path: Some(PathBuf::from("/work/app")),
+                session_id: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+                ..Default::default()
+            }],
+            skipped_lines: 1,
+            tool_output_bytes_skipped: 0,
+            line_errors: vec!["line 2: expected value".to_string()],
+        }
+    }
+
+    #[cfg(feature = "cache")]
+    fn persistent(temp: &TempDir, options: ParseOptions) -> IndexCache {
+        use crate::cache::CacheLocation;
+
+        let dir = CacheDir::resolve_from(Some(temp.path().join("cache")), temp.path().join("tmp"));
+        assert!(matches!(dir.location, CacheLocation::Platform(_)));
+        IndexCache::open(dir, options)
+    }
+
+    #[test]
+    fn test_get_or_index_reuses_unchanged_file() {
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join("agent.jsonl");
+        fs::write(&file, "{}").unwrap();
+        let cache = IndexCache::in_memory(ParseOptions::default());
+
+        let first = cache.get_or_index(&file, || Ok(indexed("first"))).unwrap();
+        let second = cache.get_or_index(&file, || panic!("should be cached")).unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(cache.hits(), 1);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_get_or_index_reindexes_changed_file() {
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join("agent.jsonl");
+        fs::write(&file, "{}").unwrap();
+        let cache = IndexCache::in_memory(ParseOptions::default());
+        cache.get_or_index(&file, || Ok(indexed("old"))).unwrap();
+
+        fs::write(&file, "{}\n{}").unwrap();
+        let result = cache.get_or_index(&file, || Ok(indexed("new"))).unwrap();
+
+        assert_eq!(result.entries[0].display_text, "new");
+        assert_eq!(cache.hits(), 0);
+    }
+
+    #[test]
+    fn test_errors_are_not_cached() {
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join("agent.jsonl");
+        fs::write(&file, "{}").unwrap();
+        let cache = IndexCache::in_memory(ParseOptions::default());
+
+        assert!(cache.get_or_index(&file, || anyhow::bail!("broken")).is_err());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_in_memory_cache_is_not_persisted() {
+        let cache = IndexCache::in_memory(ParseOptions::default());
+        assert!(cache.index_file().is_none());
+        assert!(cache.save().is_ok());
+    }
+
+    #[cfg(feature = "cache")]
+    #[test]
+    fn test_save_and_reload() {
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join("agent.jsonl");
+        fs::write(&file, "{}").unwrap();
+        let gone = temp.path().join("deleted.jsonl");
+        fs::write(&gone, "{}").unwrap();
+
+        let cache = persistent(&temp, ParseOptions::default());
+        cache.get_or_index(&file, || Ok(indexed("kept"))).unwrap();
+        cache.get_or_index(&gone, || Ok(indexed("dropped"))).unwrap();
+        fs::remove_file(&gone).unwrap();
+        cache.save().unwrap();
+
+        let reopened = persistent(&temp, ParseOptions::default());
+        assert_eq!(reopened.len(), 1);
+        let result = reopened.get_or_index(&file, || panic!("should be cached")).unwrap();
+        assert_eq!(result.entries[0].display_text, "kept");
+    }
+
+    #[cfg(feature = "cache")]
+    #[test]
+    fn test_reload_with_other_options_starts_empty() {
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join("agent.jsonl");
+        fs::write(&file, "{}").unwrap();
+
+        let cache = persistent(&temp, ParseOptions::default());
+        cache.get_or_index(&file, || Ok(indexed("full"))).unwrap();
+        cache.save().unwrap();
+
+        let limited = ParseOptions { max_tool_result_bytes: Some(100), ..Default::default() };
+        assert!(persistent(&temp, limited).is_empty());
+    }
+
+    #[cfg(feature = "cache")]
+    #[test]
+    fn test_compact_and_clear() {
+        let temp = TempDir::new().unwrap();
+        let kept = temp.path().join("kept.jsonl");
+        fs::write(&kept, "{}").unwrap();
+        let gone = temp.path().join("deleted.jsonl");
+        fs::write(&gone, "{}").unwrap();
+
+        let cache = persistent(&temp, ParseOptions::default());
+        cache.get_or_index(&kept, || Ok(indexed("kept"))).unwrap();
+        cache.get_or_index(&gone, || Ok(indexed("dropped"))).unwrap();
+        cache.save().unwrap();
+        assert!(cache.last_saved().is_some());
+
+        fs::remove_file(&gone).unwrap();
+        assert_eq!(cache.compact().unwrap(), 1);
+        assert_eq!(cache.entry_counts(), vec![(kept, 1)]);
+        assert_eq!(persistent(&temp, ParseOptions::default()).len(), 1);
+
+        cache.clear().unwrap();
+        assert!(cache.is_empty());
+        assert!(cache.last_saved().is_none());
+        assert!(persistent(&temp, ParseOptions::default()).is_empty());
+    }
+
+    #[cfg(feature = "cache")]
+    #[test]
+    fn test_records_match_whole_cache_encoding() {
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join("agent.jsonl");
+        fs::write(&file, "{}").unwrap();
+        let cache = persistent(&temp, ParseOptions::default());
+        cache.get_or_index(&file, || Ok(indexed("streamed"))).unwrap();
+        cache.save().unwrap();
+
+        // Caches written before records were streamed serialized the options and map at once
+        let written = fs::read(cache.index_file().unwrap()).unwrap();
+        let whole = bincode::serialize(&(ParseOptions::default(), &*cache.lock())).unwrap();
+        assert_eq!(written, whole);
+
+        let truncated = &written[..written.len() - 1];
+        let len = truncated.len() as u64;
+        assert!(read_records(truncated, len, &ParseOptions::default()).is_err());
+    }
+
+    #[cfg(feature = "cache")]
+    #[test]
+    fn test_corrupt_cache_file_is_ignored() {
+        let temp = TempDir::new().unwrap();
+        let cache = persistent(&temp, ParseOptions::default());
+        fs::write(cache.index_file().unwrap(), b"not bincode").unwrap();
+
+        let reopened = persistent(&temp, ParseOptions::default());
+        assert!(reopened.is_empty());
+        let problems = reopened.take_load_problems();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].path, cache.index_file().unwrap());
+        assert!(problems[0].message.starts_with("Corrupt index cache: "));
+        assert!(reopened.take_load_problems().is_empty());
+    }
+
+    #[cfg(feature = "cache")]
+    #[test]
+    fn test_huge_length_prefix_is_corrupt_not_allocated() {
+        let temp = TempDir::new().unwrap();
+        let cache = persistent(&temp, ParseOptions::default());
+        // A valid header and count, then a path claiming to be 32 TiB long
+        let mut bytes = bincode::serialize(&ParseOptions::default()).unwrap();
+        bytes.extend(bincode::serialize(&1u64).unwrap());
+        bytes.extend((1u64 << 45).to_le_bytes());
+        fs::write(cache.index_file().unwrap(), bytes).unwrap();
+
+        let reopened = persistent(&temp, ParseOptions::default());
+        assert!(reopened.is_empty());
+        let problems = reopened.take_load_problems();
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].message.starts_with("Corrupt index cache: "));
+    }
+}
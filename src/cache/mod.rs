@@ -0,0 +1,28 @@
+//! On-disk cache of indexed agent conversation files.
+//!
+//! Building the index re-parses every conversation file on each start. The index cache
+//! stores the search entries of each file keyed by path, size and modification time, so only
+//! new or changed files are parsed again.
+//!
+//! The cache lives in the platform cache directory (`~/.cache/ai-history-explorer` on
+//! Linux). If that directory is not writable (read-only home, sandboxes, locked-down
+//! machines) a private directory under the system temp dir is used instead, and if that
+//! fails too the cache is kept in memory for the current run only. See [`CacheDir`].
+//!
+//! Persistence requires the `cache` feature; without it the cache is always in memory. The
+//! cache is one bincode file by default, or a SQLite database with the `sqlite` feature and
+//! `"index_storage": "sqlite"` (see [`IndexStorage`]).
+//! [`verify_cache`] checks a cache against a fresh parse (`cache verify`).
+
+pub mod dir;
+pub mod index;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+pub mod verify;
+
+pub use dir::{CACHE_DIR_NAME, CacheDir, CacheLocation};
+pub use index::{
+    INDEX_FILE_NAME, IndexCache, IndexStorage, IndexedFile, SQLITE_FILE_NAME, index_storage,
+    set_index_storage,
+};
+pub use verify::{CacheVerification, FileMismatch, verify_cache};
@@ -0,0 +1,375 @@
+//! Index cache stored as a SQLite database (`"index_storage": "sqlite"`).
+//!
+//! The database runs in WAL mode, so other processes can read it while the explorer writes,
+//! and saving only rewrites the rows of files that changed since the last save. Besides the
+//! serialized entries the tables keep their main fields as columns, with an FTS5 table over
+//! the message text, for querying the index with external tools:
+//!
+//! ```sql
+//! SELECT e.session_id, e.display_text FROM entries_fts
+//! JOIN entries e ON e.id = entries_fts.rowid WHERE entries_fts MATCH 'borrow checker';
+//! ```
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OptionalExtension, params};
+
+use super::index::{CachedFile, IndexedFile};
+use crate::models::{EntryType, SearchEntry};
+use crate::parsers::ParseOptions;
+
+/// Bumped whenever the tables or the stored entries change; older databases are rebuilt
+const SCHEMA_VERSION: i64 = 4;
+
+/// How long a save waits for another process holding the write lock
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SCHEMA: &str = "
+CREATE TABLE meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+CREATE TABLE files (
+    path TEXT PRIMARY KEY,
+    len INTEGER NOT NULL,
+    modified_ns INTEGER NOT NULL,
+    skipped_lines INTEGER NOT NULL,
+    tool_output_bytes_skipped INTEGER NOT NULL,
+    line_errors TEXT NOT NULL
+);
+CREATE TABLE entries (
+    id INTEGER PRIMARY KEY,
+    file TEXT NOT NULL REFERENCES files(path),
+    position INTEGER NOT NULL,
+    entry_type TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    project_path TEXT,
+    session_id TEXT NOT NULL,
+    display_text TEXT NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX entries_file ON entries(file, position);
+CREATE INDEX entries_session ON entries(session_id);
+CREATE VIRTUAL TABLE entries_fts USING fts5(display_text, content='entries', content_rowid='id');
+CREATE TRIGGER entries_insert AFTER INSERT ON entries BEGIN
+    INSERT INTO entries_fts(rowid, display_text) VALUES (new.id, new.display_text);
+END;
+CREATE TRIGGER entries_delete AFTER DELETE ON entries BEGIN
+    INSERT INTO entries_fts(entries_fts, rowid, display_text)
+    VALUES ('delete', old.id, old.display_text);
+END;
+";
+
+/// Tables of an older schema version, dropped before the current ones are created
+const DROP_SCHEMA: &str = "
+DROP TABLE IF EXISTS entries_fts;
+DROP TABLE IF EXISTS entries;
+DROP TABLE IF EXISTS files;
+DROP TABLE IF EXISTS meta;
+";
+
+/// Files of the database at `path` indexed with `options`
+///
+/// A missing database, one of another schema version or built with other options is empty.
+///
+/// # Errors
+///
+/// Returns an error if the database exists but cannot be read.
+pub(super) fn load(path: &Path, options: &ParseOptions) -> Result<HashMap<PathBuf, CachedFile>> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    read_files(path, options).context("Unreadable index cache")
+}
+
+fn read_files(path: &Path, options: &ParseOptions) -> Result<HashMap<PathBuf, CachedFile>> {
+    let conn = connect(path)?;
+    if schema_version(&conn)? != SCHEMA_VERSION
+        || !stored_options(&conn)?.is_some_and(|stored| stored.same_entries(options))
+    {
+        return Ok(HashMap::new());
+    }
+
+    let mut files = HashMap::new();
+    let mut statement = conn.prepare(
+        "SELECT path, len, modified_ns, skipped_lines, tool_output_bytes_skipped, line_errors
+         FROM files",
+    )?;
+    let mut rows = statement.query([])?;
+    while let Some(row) = rows.next()? {
+        let line_errors: String = row.get(5)?;
+        let file = CachedFile {
+            len: row.get::<_, i64>(1)? as u64,
+            modified: from_nanos(row.get(2)?),
+            indexed: IndexedFile {
+                entries: Vec::new(),
+                skipped_lines: row.get::<_, i64>(3)? as usize,
+                tool_output_bytes_skipped: row.get::<_, i64>(4)? as usize,
+                line_errors: serde_json::from_str(&line_errors)?,
+            },
+        };
+        files.insert(PathBuf::from(row.get::<_, String>(0)?), file);
+    }
+
+    let mut statement = conn.prepare("SELECT file, data FROM entries ORDER BY file, position")?;
+    let mut rows = statement.query([])?;
+    while let Some(row) = rows.next()? {
+        let file: String = row.get(0)?;
+        let entry: SearchEntry = serde_json::from_str(&row.get::<_, String>(1)?)?;
+        if let Some(cached) = files.get_mut(Path::new(&file)) {
+            cached.indexed.entries.push(entry);
+        }
+    }
+    Ok(files)
+}
+
+/// Bring the database at `path` in line with `files` in one transaction
+///
+/// Only files added or changed (by size or mtime) since the stored ones are written, and
+/// files no longer cached are deleted. A database of another schema version or built with
+/// other options is rebuilt.
+pub(super) fn store(
+    path: &Path,
+    options: &ParseOptions,
+    files: &HashMap<PathBuf, CachedFile>,
+) -> Result<()> {
+    if !path.exists() {
+        crate::utils::create_private_file(path)?;
+    }
+    let mut conn = connect(path)?;
+    let tx = conn.transaction()?;
+
+    if schema_version(&tx)? != SCHEMA_VERSION {
+        tx.execute_batch(DROP_SCHEMA)?;
+        tx.execute_batch(SCHEMA)?;
+        tx.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+    }
+    if !stored_options(&tx)?.is_some_and(|stored| stored.same_entries(options)) {
+        tx.execute_batch("DELETE FROM entries; DELETE FROM files;")?;
+        tx.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('parse_options', ?1)",
+            [serde_json::to_string(options)?],
+        )?;
+    }
+
+    let stored: HashMap<String, (i64, i64)> = {
+        let mut statement = tx.prepare("SELECT path, len, modified_ns FROM files")?;
+        let rows = statement.query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+    {
+        let mut delete_entries = tx.prepare("DELETE FROM entries WHERE file = ?1")?;
+        let mut delete_file = tx.prepare("DELETE FROM files WHERE path = ?1")?;
+        let mut insert_file = tx.prepare(
+            "INSERT INTO files
+             (path, len, modified_ns, skipped_lines, tool_output_bytes_skipped, line_errors)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        let mut insert_entry = tx.prepare(
+            "INSERT INTO entries (file, position, entry_type, timestamp, project_path,
+             session_id, display_text, data) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+
+        let cached: HashMap<String, &CachedFile> =
+            files.iter().map(|(path, file)| (path.to_string_lossy().into_owned(), file)).collect();
+        for file in stored.keys().filter(|file| !cached.contains_key(*file)) {
+            delete_entries.execute([file])?;
+            delete_file.execute([file])?;
+        }
+        for (file, cached) in &cached {
+            let stamp = (cached.len as i64, to_nanos(cached.modified));
+            if stored.get(file) == Some(&stamp) {
+                continue;
+            }
+            delete_entries.execute([file])?;
+            delete_file.execute([file])?;
+            let indexed = &cached.indexed;
+            insert_file.execute(params![
+                file,
+                stamp.0,
+                stamp.1,
+                indexed.skipped_lines as i64,
+                indexed.tool_output_bytes_skipped as i64,
+                serde_json::to_string(&indexed.line_errors)?,
+            ])?;
+            for (position, entry) in indexed.entries.iter().enumerate() {
+                let entry_type = match entry.entry_type {
+                    EntryType::UserPrompt => "user_prompt",
+                    EntryType::AgentMessage => "agent_message",
+                };
+                insert_entry.execute(params![
+                    file,
+                    position as i64,
+                    entry_type,
+                    entry.timestamp.timestamp_millis(),
+                    entry.project_path.as_ref().map(|path| path.to_string_lossy()),
+                    entry.session_id,
+                    entry.display_text,
+                    serde_json::to_string(entry)?,
+                ])?;
+            }
+        }
+    }
+    tx.commit().with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn connect(path: &Path) -> Result<Connection> {
+    let conn =
+        Connection::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    Ok(conn)
+}
+
+fn schema_version(conn: &Connection) -> rusqlite::Result<i64> {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0))
+}
+
+fn stored_options(conn: &Connection) -> Result<Option<ParseOptions>> {
+    let value: Option<String> = conn
+        .query_row("SELECT value FROM meta WHERE key = 'parse_options'", [], |row| row.get(0))
+        .optional()?;
+    Ok(value.map(|value| serde_json::from_str(&value)).transpose()?)
+}
+
+/// Modification time as nanoseconds since the Unix epoch (negative before it)
+fn to_nanos(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(after) => after.as_nanos() as i64,
+        Err(before) => -(before.duration().as_nanos() as i64),
+    }
+}
+
+fn from_nanos(nanos: i64) -> SystemTime {
+    let offset = Duration::from_nanos(nanos.unsigned_abs());
+    if nanos >= 0 { UNIX_EPOCH + offset } else { UNIX_EPOCH - offset }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn cached(len: u64, texts: &[&str]) -> CachedFile {
+        let entries = texts
+            .iter()
+            .map(|text| SearchEntry {
+                entry_type: EntryType::AgentMessage,
+                display_text: text.to_string(),
+                timestamp: DateTime::from_timestamp(1_704_099_600, 0).unwrap(),
+                project_path: Some(PathBuf::from("/work/app")),
+                session_id: "s1".to_string(),
+                tools: vec!["Bash".to_string()],
+                ..Default::default()
+            })
+            .collect();
+        CachedFile {
+            len,
+            modified: UNIX_EPOCH + Duration::from_nanos(1_700_000_000_123_456_789),
+            indexed: IndexedFile {
+                entries,
+                skipped_lines: 1,
+                tool_output_bytes_skipped: 0,
+                line_errors: vec!["line 3: EOF".to_string()],
+            },
+        }
+    }
+
+    fn texts(files: &HashMap<PathBuf, CachedFile>, path: &str) -> Vec<String> {
+        files[Path::new(path)].indexed.entries.iter().map(|e| e.display_text.clone()).collect()
+    }
+
+    #[test]
+    fn test_store_and_load_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("index.sqlite3");
+        let options = ParseOptions::default();
+        let files = HashMap::from([(PathBuf::from("/a.jsonl"), cached(10, &["one", "two"]))]);
+
+        store(&path, &options, &files).unwrap();
+        let loaded = load(&path, &options).unwrap();
+
+        let file = &loaded[Path::new("/a.jsonl")];
+        assert_eq!(file.len, 10);
+        assert_eq!(file.modified, files[Path::new("/a.jsonl")].modified);
+        assert_eq!(file.indexed.entries, files[Path::new("/a.jsonl")].indexed.entries);
+        assert_eq!(file.indexed.line_errors, vec!["line 3: EOF"]);
+    }
+
+    #[test]
+    fn test_store_updates_only_changed_files() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("index.sqlite3");
+        let options = ParseOptions::default();
+        let mut files = HashMap::from([
+            (PathBuf::from("/a.jsonl"), cached(10, &["one"])),
+            (PathBuf::from("/b.jsonl"), cached(20, &["two"])),
+        ]);
+        store(&path, &options, &files).unwrap();
+        let conn = Connection::open(&path).unwrap();
+        let id_of = |text: &str| -> i64 {
+            conn.query_row("SELECT id FROM entries WHERE display_text = ?1", [text], |row| {
+                row.get(0)
+            })
+            .unwrap()
+        };
+        let untouched = id_of("one");
+
+        files.insert(PathBuf::from("/b.jsonl"), cached(25, &["two", "three"]));
+        files.insert(PathBuf::from("/c.jsonl"), cached(5, &["four"]));
+        store(&path, &options, &files).unwrap();
+
+        assert_eq!(id_of("one"), untouched);
+        files.remove(Path::new("/c.jsonl"));
+        store(&path, &options, &files).unwrap();
+        let loaded = load(&path, &options).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(texts(&loaded, "/b.jsonl"), vec!["two", "three"]);
+    }
+
+    #[test]
+    fn test_full_text_table_follows_entries() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("index.sqlite3");
+        let options = ParseOptions::default();
+        let mut files =
+            HashMap::from([(PathBuf::from("/a.jsonl"), cached(10, &["fix the borrow checker"]))]);
+        store(&path, &options, &files).unwrap();
+
+        let matches = |query: &str| -> Vec<String> {
+            let conn = Connection::open(&path).unwrap();
+            let mut statement = conn
+                .prepare(
+                    "SELECT e.display_text FROM entries_fts JOIN entries e
+                     ON e.id = entries_fts.rowid WHERE entries_fts MATCH ?1",
+                )
+                .unwrap();
+            statement.query_map([query], |row| row.get(0)).unwrap().map(Result::unwrap).collect()
+        };
+        assert_eq!(matches("borrow"), vec!["fix the borrow checker"]);
+
+        files.insert(PathBuf::from("/a.jsonl"), cached(11, &["rename the module"]));
+        store(&path, &options, &files).unwrap();
+        assert!(matches("borrow").is_empty());
+        assert_eq!(matches("module"), vec!["rename the module"]);
+    }
+
+    #[test]
+    fn test_other_parse_options_start_empty() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("index.sqlite3");
+        let options = ParseOptions::default();
+        let files = HashMap::from([(PathBuf::from("/a.jsonl"), cached(10, &["one"]))]);
+        store(&path, &options, &files).unwrap();
+
+        let other = ParseOptions { max_tool_result_bytes: Some(1024), ..Default::default() };
+        assert!(load(&path, &other).unwrap().is_empty());
+        assert!(load(&temp.path().join("missing.sqlite3"), &options).unwrap().is_empty());
+
+        store(&path, &other, &HashMap::new()).unwrap();
+        assert!(load(&path, &options).unwrap().is_empty());
+        assert!(load(&path, &other).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+//! Check the index cache against a fresh parse.
+//!
+//! The cache only re-parses files whose size or modification time changed, so a bug in that
+//! logic (or in what gets stored) would go unnoticed: stale entries are served as if they were
+//! current. [`verify_cache`] compares each cached file with the entries a fresh, in-memory
+//! parse produced for it, and lists the files whose entries differ.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use super::index::{IndexCache, IndexedFile};
+use crate::models::SearchEntry;
+
+/// Result of comparing a cache with a fresh parse
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CacheVerification {
+    /// Files present in both and unchanged on disk since they were cached
+    pub compared: usize,
+    /// Cached files that changed on disk since (re-parsed on the next run, so not compared)
+    pub stale: usize,
+    /// Parsed files the cache doesn't have yet
+    pub uncached: usize,
+    /// Cached files under the checked roots that the fresh parse no longer indexes
+    pub orphaned: Vec<PathBuf>,
+    /// Compared files whose cached entries differ from the fresh ones
+    pub mismatches: Vec<FileMismatch>,
+}
+
+impl CacheVerification {
+    /// Whether every compared file matched
+    pub fn is_consistent(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// A cached file whose entries differ from a fresh parse
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMismatch {
+    pub path: PathBuf,
+    pub cached_entries: usize,
+    pub fresh_entries: usize,
+    /// Message uuids the fresh parse has and the cache lacks
+    pub missing_uuids: Vec<String>,
+    /// Message uuids the cache has and the fresh parse doesn't
+    pub extra_uuids: Vec<String>,
+    /// Entries with the same uuid (or position, without one) but different contents
+    pub changed_entries: usize,
+    /// Whether the skipped line or tool output counts differ
+    pub counters_differ: bool,
+}
+
+/// Compare the files of `cache` under `roots` with `fresh`, an in-memory cache filled by
+/// indexing the same roots from scratch
+pub fn verify_cache(
+    cache: &IndexCache,
+    fresh: &IndexCache,
+    roots: &[PathBuf],
+) -> CacheVerification {
+    let mut result = CacheVerification::default();
+    let fresh_files: BTreeSet<PathBuf> = fresh.entry_counts().into_iter().map(|(p, _)| p).collect();
+
+    for path in &fresh_files {
+        let Some((expected, _)) = fresh.cached(path) else { continue };
+        match cache.cached(path) {
+            None => result.uncached += 1,
+            Some((_, false)) => result.stale += 1,
+            Some((cached, true)) => {
+                result.compared += 1;
+                if let Some(mismatch) = compare(path, &cached, &expected) {
+                    result.mismatches.push(mismatch);
+                }
+            }
+        }
+    }
+
+    let mut orphaned: Vec<PathBuf> = cache
+        .entry_counts()
+        .into_iter()
+        .map(|(path, _)| path)
+        .filter(|path| roots.iter().any(|root| path.starts_with(root)))
+        .filter(|path| !fresh_files.contains(path))
+        .collect();
+    orphaned.sort();
+    result.orphaned = orphaned;
+    result.mismatches.sort_by(|a, b| a.path.cmp(&b.path));
+    result
+}
+
+fn compare(path: &Path, cached: &IndexedFile, fresh: &IndexedFile) -> Option<FileMismatch> {
+    if cached == fresh {
+        return None;
+    }
+
+    let by_uuid = |entries: &[SearchEntry]| -> BTreeMap<String, Vec<SearchEntry>> {
+        let mut map: BTreeMap<String, Vec<SearchEntry>> = BTreeMap::new();
+        for entry in entries {
+            if let Some(uuid) = &entry.uuid {
+                map.entry(uuid.clone()).or_default().push(entry.clone());
+            }
+        }
+        map
+    };
+    let cached_uuids = by_uuid(&cached.entries);
+    let fresh_uuids = by_uuid(&fresh.entries);
+    let missing_uuids =
+        fresh_uuids.keys().filter(|uuid| !cached_uuids.contains_key(*uuid)).cloned().collect();
+    let extra_uuids =
+        cached_uuids.keys().filter(|uuid| !fresh_uuids.contains_key(*uuid)).cloned().collect();
+
+    let changed_with_uuid = cached_uuids
+        .iter()
+        .filter(|(uuid, entries)| fresh_uuids.get(*uuid).is_some_and(|fresh| fresh != *entries))
+        .count();
+    let without_uuid = |entries: &[SearchEntry]| -> Vec<SearchEntry> {
+        entries.iter().filter(|entry| entry.uuid.is_none()).cloned().collect()
+    };
+    let (cached_rest, fresh_rest) = (without_uuid(&cached.entries), without_uuid(&fresh.entries));
+    let changed_without_uuid = cached_rest.iter().zip(&fresh_rest).filter(|(a, b)| a != b).count()
+        + cached_rest.len().abs_diff(fresh_rest.len());
+
+    Some(FileMismatch {
+        path: path.to_path_buf(),
+        cached_entries: cached.entries.len(),
+        fresh_entries: fresh.entries.len(),
+        missing_uuids,
+        extra_uuids,
+        changed_entries: changed_with_uuid + changed_without_uuid,
+        counters_differ: cached.skipped_lines != fresh.skipped_lines
+            || cached.tool_output_bytes_skipped != fresh.tool_output_bytes_skipped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use chrono::DateTime;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::models::EntryType;
+    use crate::parsers::ParseOptions;
+
+    fn entry(uuid: &str, text: &str) -> SearchEntry {
+        SearchEntry {
+            entry_type: EntryType::AgentMessage,
+            display_text: text.to_string(),
+            timestamp: DateTime::from_timestamp(1_704_099_600, 0).unwrap(),
+            session_id: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+            uuid: Some(uuid.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn fill(cache: &IndexCache, path: &Path, entries: Vec<SearchEntry>) {
+        let indexed = IndexedFile { entries, ..IndexedFile::default() };
+        cache.get_or_index(path, || Ok(indexed)).unwrap();
+    }
+
+    #[test]
+    fn test_verify_reports_differences() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().to_path_buf();
+        let paths: Vec<PathBuf> = (0..4).map(|i| root.join(format!("agent-{}.jsonl", i))).collect();
+        for path in &paths {
+            fs::write(path, "{}").unwrap();
+        }
+
+        let cache = IndexCache::in_memory(ParseOptions::default());
+        let fresh = IndexCache::in_memory(ParseOptions::default());
+        // Same entries
+        fill(&cache, &paths[0], vec![entry("a", "one")]);
+        fill(&fresh, &paths[0], vec![entry("a", "one")]);
+        // Cache missed a message and kept an outdated one
+        fill(&cache, &paths[1], vec![entry("b", "old"), entry("c", "gone")]);
+        fill(&fresh, &paths[1], vec![entry("b", "new"), entry("d", "added")]);
+        // Not cached yet
+        fill(&fresh, &paths[2], vec![entry("e", "later")]);
+        // Cached, but no longer indexed
+        fill(&cache, &paths[3], vec![entry("f", "orphan")]);
+        // Changed on disk since it was cached
+        let stale = root.join("agent-stale.jsonl");
+        fs::write(&stale, "{}").unwrap();
+        fill(&cache, &stale, vec![entry("g", "before")]);
+        fill(&fresh, &stale, vec![entry("g", "after")]);
+        fs::write(&stale, "{}\n{}").unwrap();
+
+        let result = verify_cache(&cache, &fresh, &[root]);
+        assert_eq!(result.compared, 2);
+        assert_eq!(result.uncached, 1);
+        assert_eq!(result.stale, 1);
+        assert_eq!(result.orphaned, vec![paths[3].clone()]);
+        assert!(!result.is_consistent());
+        assert_eq!(
+            result.mismatches,
+            vec![FileMismatch {
+                path: paths[1].clone(),
+                cached_entries: 2,
+                fresh_entries: 2,
+                missing_uuids: vec!["d".to_string()],
+                extra_uuids: vec!["c".to_string()],
+                changed_entries: 1,
+                counters_differ: false,
+            }]
+        );
+    }
+}
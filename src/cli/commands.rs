@@ -1,75 +1,2810 @@
-use std::path::Path;
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashSet};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::analytics::{
+    OutcomeStats, PromptGroup, ToolOutputReport, UsageSummary, UserStats, build_tool_output_report,
+    format_bytes, group_prompts, has_user_identities, outcome_breakdown, repeated_prompts,
+    scan_tool_outputs, user_breakdown,
+};
+use crate::bookmarks::Bookmarks;
+use crate::cache::{
+    CacheDir, CacheLocation, CacheVerification, IndexCache, IndexStorage, set_index_storage,
+    verify_cache,
+};
+use crate::config::Config;
+#[cfg(feature = "tui")]
+use crate::config::StartupView;
+use crate::error::ExplorerError;
+use crate::export::exchange::tool_result_text;
+use crate::export::timeline::condense;
+use crate::export::{
+    ATTACHMENTS_DIR_NAME, Attachments, Exchange, PromptTimeline, extract_attachments,
+    extract_session, plan_attachments, render_html_page, render_session_page_with_attachments,
+    render_transcript,
+};
+use crate::filters::{
+    Cursor, FilterExplanation, FilterExpr, FilterField, FilterOperator, FilterSyntaxError,
+    apply_filters, explain_filter, mark_span, paginate, parse_filter, tokenize,
+};
+use crate::indexer::highlights::DEFAULT_HIGHLIGHTS_PER_GROUP;
+use crate::indexer::roots::DEFAULT_ROOT_NAME;
+use crate::indexer::{
+    ClaudeRoot, HighlightGroup, HistorySource, IndexReport, IndexStatus, RenameCandidate,
+    RootStatus, RunMetrics, apply_project_merges, build_index_with_cache,
+    build_workspace_index_with_cache, detect_renames, merge_sources, sample_highlights,
+};
+use crate::models::{ContentBlock, ConversationEntry, EntryType, MessageContent, SearchEntry};
+use crate::parsers::{
+    EntryRelations, OversizedToolResults, ParseOptions, parse_conversation_file_with_options,
+};
+use crate::resume::ResumeTarget;
+use crate::summarize::{Summaries, Summarizer, group_sessions};
+use crate::sync::{ExportSummary, SyncStore};
+use crate::utils::permissions::{PermissionScan, find_permissive_files, fix_permissions};
+use crate::utils::{
+    CompletionNotifier, DEFAULT_NOTIFY_AFTER, DisplayZone, NotifyMethod, Plan, TimestampStyle,
+    format_path_with_tilde, format_time, get_claude_dir, set_display_zone, write_private_file,
+};
 
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+#[derive(Parser)]
+#[command(name = "ai-history-explorer")]
+#[command(version = "0.1.0")]
+#[command(about = "Search through Claude Code conversation history", long_about = None)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+
+    /// Claude directory to index, as `name=path` or `path` (repeatable; default:
+    /// `claude_dirs` in the config, else ~/.claude)
+    #[arg(long = "claude-dir", value_name = "[NAME=]PATH", global = true)]
+    pub claude_dirs: Vec<String>,
+
+    /// Also index the Codex CLI history in this directory, e.g. ~/.codex (default:
+    /// `codex_dir` in the config)
+    #[arg(long, value_name = "PATH", global = true)]
+    pub codex_dir: Option<PathBuf>,
+
+    /// Also index this aider chat history, a `.aider.chat.history.md` file (repeatable; adds
+    /// to `aider_histories` in the config)
+    #[arg(long = "aider-history", value_name = "FILE", global = true)]
+    pub aider_histories: Vec<PathBuf>,
+
+    /// Also index the Markdown chat transcripts (`### User` / `### Assistant`) in this
+    /// directory (repeatable; adds to `transcript_dirs` in the config)
+    #[arg(long = "transcript-dir", value_name = "DIR", global = true)]
+    pub transcript_dirs: Vec<PathBuf>,
+
+    /// Print a machine-readable indexing report to stdout (stats then prints only the report)
+    #[arg(long, value_enum, global = true)]
+    pub report: Option<ReportFormat>,
+
+    /// Reduce tool results larger than this many bytes while parsing (default: no limit)
+    #[arg(long, value_name = "BYTES", global = true)]
+    pub max_tool_output: Option<usize>,
+
+    /// How tool results above --max-tool-output are reduced
+    #[arg(long, value_enum, default_value_t = OversizedMode::Sample, global = true)]
+    pub oversized_tool_output: OversizedMode,
+
+    /// Parse at most this many megabytes of conversation files at once, trading some speed
+    /// for lower peak memory (default: one file per thread)
+    #[arg(long, value_name = "MB", global = true)]
+    pub parse_memory_limit: Option<usize>,
+
+    /// Largest history or conversation file read, in megabytes (default: 10, or
+    /// `max_file_size_mb` in the config); only the most recent entries of a larger
+    /// history.jsonl are read
+    #[arg(long, value_name = "MB", global = true)]
+    pub max_file_size: Option<u64>,
+
+    /// How to signal that a slow index build finished
+    #[arg(long, value_enum, default_value_t = NotifyMode::Bell, global = true)]
+    pub notify: NotifyMode,
+
+    /// Only signal index builds that took at least this many seconds
+    #[arg(long, value_name = "SECONDS", default_value_t = DEFAULT_NOTIFY_AFTER.as_secs(), global = true)]
+    pub notify_after: u64,
+
+    /// Write Prometheus metrics of each run (index size, build time, parse failures, run
+    /// counts) to this file, for node_exporter's textfile collector
+    #[arg(long, value_name = "PATH", global = true)]
+    pub metrics_file: Option<PathBuf>,
+
+    /// Also print what indexing counted (entries, parsed files, skipped lines) to stderr
+    #[arg(long, short, global = true)]
+    pub verbose: bool,
+}
+
+impl Cli {
+    /// Completion notice for slow index builds selected on the command line
+    fn notifier(&self) -> CompletionNotifier {
+        let method = match self.notify {
+            NotifyMode::Off => NotifyMethod::Off,
+            NotifyMode::Bell => NotifyMethod::Bell,
+            NotifyMode::Desktop => NotifyMethod::Desktop,
+        };
+        CompletionNotifier::new(method, Duration::from_secs(self.notify_after))
+    }
+
+    /// Histories of other tools to index, from the command line and `config`
+    fn history_sources(&self, config: &Config) -> Vec<Arc<dyn HistorySource>> {
+        let mut config = config.clone();
+        if let Some(dir) = &self.codex_dir {
+            config.codex_dir = Some(dir.clone());
+        }
+        config.aider_histories.extend(self.aider_histories.iter().cloned());
+        config.transcript_dirs.extend(self.transcript_dirs.iter().cloned());
+        config.history_sources()
+    }
+
+    /// Parse-time limits selected on the command line
+    fn parse_options(&self) -> ParseOptions {
+        ParseOptions {
+            max_tool_result_bytes: self.max_tool_output,
+            oversized_tool_results: match self.oversized_tool_output {
+                OversizedMode::Sample => OversizedToolResults::Sample,
+                OversizedMode::Skip => OversizedToolResults::Skip,
+            },
+            max_file_size: self.max_file_size.map(|mb| mb.saturating_mul(BYTES_PER_MB as u64)),
+            parse_memory_limit: self.parse_memory_limit.map(|mb| mb.saturating_mul(BYTES_PER_MB)),
+        }
+    }
+}
+
+/// Completion signal selected with `--notify`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum NotifyMode {
+    /// No signal
+    Off,
+    /// Terminal bell
+    Bell,
+    /// Desktop notification (needs the `desktop-notifications` feature; bell otherwise)
+    Desktop,
+}
+
+/// Treatment of tool results above `--max-tool-output`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OversizedMode {
+    /// Keep the beginning and end of the output
+    Sample,
+    /// Drop the output entirely
+    Skip,
+}
+
+/// Format of the `--report` indexing summary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    Json,
+}
+
+/// Format of `list` and `search` results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable list
+    #[default]
+    Text,
+    /// One JSON array
+    Json,
+    /// One JSON object per line
+    Jsonl,
+    /// Comma-separated values for spreadsheets: timestamp, type, project, session id and the
+    /// start of the text
+    Csv,
+}
+
+/// When `show` colors a session transcript
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorMode {
+    /// When printing to a terminal and `NO_COLOR` is not set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    fn enabled(self) -> bool {
+        match self {
+            ColorMode::Auto => {
+                std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+            }
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        }
+    }
+}
+
+/// Order of `search` results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum SearchSort {
+    /// Best match first
+    #[default]
+    Relevance,
+    /// Most recent first
+    Newest,
+    /// Oldest first
+    Oldest,
+}
+
+/// How `search` matches its query
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SearchMode {
+    FullText,
+    Similar,
+    Semantic,
+    Fuzzy,
+}
+
+/// Query prefix that makes `search` semantic, like `--semantic`
+const SEMANTIC_PREFIX: &str = "semantic:";
+
+/// Unit of `--parse-memory-limit`
+const BYTES_PER_MB: usize = 1024 * 1024;
+
+/// Characters of an entry's text in `--output csv` rows
+const CSV_TEXT_CHARS: usize = 200;
+
+/// Title of exported HTML pages unless `--title` is given
+const DEFAULT_HTML_TITLE: &str = "Claude Code History";
+
+/// Default number of individual results in the `stats --tools` leaderboard
+const DEFAULT_LEADERBOARD_SIZE: usize = 10;
+
+/// Default number of `search` results
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+/// Width of `show` session transcripts when not printing to a terminal
+const DEFAULT_TRANSCRIPT_WIDTH: usize = 100;
+
+/// Projects, recent weeks and tools listed by `stats`
+const STATS_TOP_PROJECTS: usize = 10;
+const STATS_RECENT_WEEKS: usize = 8;
+const STATS_TOP_TOOLS: usize = 10;
+
+/// Width of the longest bar in `stats` histograms
+const STATS_BAR_WIDTH: usize = 20;
+
+#[derive(Subcommand)]
+pub enum Commands {
+    /// Show statistics about the history
+    Stats {
+        /// Also show a leaderboard of the largest tool outputs and most verbose tools
+        #[arg(long)]
+        tools: bool,
+        /// Number of individual tool results listed in the leaderboard
+        #[arg(long, default_value_t = DEFAULT_LEADERBOARD_SIZE)]
+        top: usize,
+    },
+    /// Launch interactive fuzzy-finder TUI
+    Interactive {
+        /// Open a search saved in the TUI (Alt+S) instead of the startup view
+        #[arg(long, value_name = "NAME")]
+        preset: Option<String>,
+    },
+    /// Browse a digest of a few highlights per project and week
+    Highlights {
+        /// Number of entries sampled per project/week
+        #[arg(long, default_value_t = DEFAULT_HIGHLIGHTS_PER_GROUP)]
+        per_group: usize,
+        /// Print the digest instead of opening the TUI
+        #[arg(long)]
+        print: bool,
+    },
+    /// Check Claude directories and file permissions for problems
+    Doctor {
+        /// Restrict files readable by other users to the owner (0600 files, 0700 directories)
+        #[arg(long)]
+        fix: bool,
+        /// Print the permissions `--fix` would change instead of changing them
+        #[arg(long, requires = "fix")]
+        dry_run: bool,
+    },
+    /// Inspect the TUI key bindings
+    Keymap {
+        #[command(subcommand)]
+        command: KeymapCommand,
+    },
+    /// Export history for use outside the tool
+    Export {
+        #[command(subcommand)]
+        command: ExportCommand,
+    },
+    /// Show how a filter is parsed and how many entries each of its clauses matches
+    Explain {
+        /// Filter expression, e.g. "project:app type:user since:2024-01-01"
+        filter: String,
+    },
+    /// Print a whole session as a colored transcript, a conversation message by uuid, or with
+    /// `parent:<uuid>` the chain of messages (prompt, tool calls and results) that led to it
+    Show {
+        /// `<session-id>`, `<uuid>` or `parent:<uuid>`
+        target: String,
+        /// Color the session transcript: on a terminal unless `NO_COLOR` is set (auto), or
+        /// always (e.g. for `less -R`), or never
+        #[arg(long, value_enum, default_value_t)]
+        color: ColorMode,
+        /// Wrap the session transcript to this many columns (default: the terminal width, or
+        /// 100 when not printing to a terminal)
+        #[arg(long)]
+        width: Option<usize>,
+    },
+    /// Print the entries matching a filter, newest first
+    List {
+        /// Only list entries matching this filter (e.g. "project:app type:user")
+        #[arg(long)]
+        filter: Option<String>,
+        /// Maximum number of entries (default: all)
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Continue after the page that printed this cursor (see `--limit`)
+        #[arg(long)]
+        cursor: Option<String>,
+        /// Print entries as text, as JSON for `jq` and other tools, or as CSV for spreadsheets
+        #[arg(long, value_enum, default_value_t)]
+        output: OutputFormat,
+    },
+    /// Continue a conversation with `claude --resume`, from its project directory
+    Resume {
+        /// Session id, as in `list --output jsonl`
+        session_id: String,
+    },
+    /// Write a one-line summary of each session with the config's `summarizer`, shown on the
+    /// session rows of the TUI
+    Summarize {
+        /// Only summarize sessions with an entry matching this filter (e.g. "project:app")
+        #[arg(long)]
+        filter: Option<String>,
+        /// Maximum number of sessions to summarize, most recently active first
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Summarize sessions again even if their summary is up to date
+        #[arg(long)]
+        refresh: bool,
+        /// Run the summarizer, but print the summaries file it would save instead of saving it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Collapse repeated prompts into one entry with an occurrence count, newest first
+    Dedupe {
+        /// Only consider prompts matching this filter (e.g. "project:app since:2024-01-01")
+        #[arg(long)]
+        filter: Option<String>,
+        /// Only list prompts typed more than once, most repeated first
+        #[arg(long)]
+        show_duplicates: bool,
+        /// Maximum number of prompts (default: all)
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Print prompts as text, as JSON (with `occurrences`, `sessions`, `first_seen` and
+        /// `last_seen` per prompt) or as CSV
+        #[arg(long, value_enum, default_value_t)]
+        output: OutputFormat,
+    },
+    /// Full-text search with phrases, AND/OR/NOT and ranked results (needs the `fulltext`
+    /// feature, `similar` for `--similar` or `semantic` for `--semantic`), or the TUI's fuzzy
+    /// search with `--fuzzy`
+    Search {
+        /// Query, e.g. `"connection pool" NOT timeout` or `retry project:api`; with `--fuzzy`
+        /// as typed into the TUI, e.g. `project:api type:user | retry`. A `semantic:` prefix
+        /// searches like `--semantic`
+        query: String,
+        /// Maximum number of results
+        #[arg(long, default_value_t = DEFAULT_SEARCH_LIMIT)]
+        limit: usize,
+        /// Rank entries by the words and word parts they share with the query instead of
+        /// requiring its terms; lexical only, so word forms match but synonyms don't
+        #[arg(long)]
+        similar: bool,
+        /// Match like the TUI: the filter left of `|` narrows the entries and the rest is
+        /// matched fuzzily. Needs no index, so it works without the `fulltext` feature
+        #[arg(long, conflicts_with = "similar")]
+        fuzzy: bool,
+        /// Rank entries by meaning: the nearest neighbours of the query among sentence
+        /// embeddings from a local language model (`embedding_model` in the config)
+        #[arg(long, conflicts_with_all = ["similar", "fuzzy"])]
+        semantic: bool,
+        /// Order of the results. Fuzzy search sorts all matches before `--limit`; the other
+        /// modes order the `--limit` best matches
+        #[arg(long, value_enum, default_value_t)]
+        sort: SearchSort,
+        /// Print results as text, as JSON (with a `score` per entry) or as CSV
+        #[arg(long, value_enum, default_value_t)]
+        output: OutputFormat,
+    },
+    /// Copy new and changed history files into a dated, compressed snapshot archive
+    Snapshot {
+        /// Archive directory (default: ai-history-explorer/snapshots in the platform data dir)
+        #[arg(long, global = true)]
+        archive: Option<PathBuf>,
+        /// Print the files the snapshot or restore would write instead of writing them
+        #[arg(long, global = true)]
+        dry_run: bool,
+        #[command(subcommand)]
+        command: Option<SnapshotCommand>,
+    },
+    /// Exchange history with other machines through bundle files
+    Sync {
+        /// Sync store directory (default: ai-history-explorer/sync in the platform data dir)
+        #[arg(long, global = true)]
+        store: Option<PathBuf>,
+        #[command(subcommand)]
+        command: SyncCommand,
+    },
+    /// Find projects whose directory was moved and merge their history into one project
+    Renames {
+        /// Merge every probable rename without asking
+        #[arg(long)]
+        yes: bool,
+        /// Print the config change merging every probable rename instead of saving it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Inspect the on-disk index cache
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+}
+
+impl Commands {
+    /// Whether the command only prints what it would write (`--dry-run`)
+    fn is_dry_run(&self) -> bool {
+        match self {
+            Commands::Doctor { dry_run, .. }
+            | Commands::Summarize { dry_run, .. }
+            | Commands::Snapshot { dry_run, .. }
+            | Commands::Sync { command: SyncCommand::Export { dry_run, .. }, .. }
+            | Commands::Sync { command: SyncCommand::Import { dry_run, .. }, .. }
+            | Commands::Renames { dry_run, .. }
+            | Commands::Keymap { command: KeymapCommand::Export { dry_run, .. } } => *dry_run,
+            Commands::Export { command } => match command {
+                ExportCommand::Html { dry_run, .. }
+                | ExportCommand::Markdown { dry_run, .. }
+                | ExportCommand::Timeline { dry_run, .. } => *dry_run,
+            },
+            _ => false,
+        }
+    }
+
+    /// Subcommand name, as typed on the command line
+    fn name(&self) -> &'static str {
+        match self {
+            Commands::Stats { .. } => "stats",
+            Commands::Interactive { .. } => "interactive",
+            Commands::Highlights { .. } => "highlights",
+            Commands::Doctor { .. } => "doctor",
+            Commands::Keymap { .. } => "keymap",
+            Commands::Export { .. } => "export",
+            Commands::Explain { .. } => "explain",
+            Commands::Show { .. } => "show",
+            Commands::List { .. } => "list",
+            Commands::Summarize { .. } => "summarize",
+            Commands::Dedupe { .. } => "dedupe",
+            Commands::Resume { .. } => "resume",
+            Commands::Search { .. } => "search",
+            Commands::Snapshot { .. } => "snapshot",
+            Commands::Sync { .. } => "sync",
+            Commands::Renames { .. } => "renames",
+            Commands::Cache { .. } => "cache",
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum ExportCommand {
+    /// Write a self-contained HTML page with the entries and a fuzzy search box, or with one
+    /// whole session
+    Html {
+        /// Only export entries matching this filter (e.g. "project:app since:2024-01-01")
+        #[arg(long)]
+        filter: Option<String>,
+        /// Export this session's full conversation, with a table of contents and collapsible
+        /// thinking and tool sections
+        #[arg(long, conflicts_with = "filter")]
+        session: Option<String>,
+        /// Page title
+        #[arg(long, default_value = DEFAULT_HTML_TITLE)]
+        title: String,
+        /// File to write
+        #[arg(long, short)]
+        output: PathBuf,
+        /// With `--session`, save the session's images in `attachments/` next to the page and
+        /// show them in it
+        #[arg(long, requires = "session")]
+        attachments: bool,
+        /// Print what would be written instead of writing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Write one whole session as Markdown: every turn, with tool calls and results as fenced
+    /// blocks
+    Markdown {
+        /// Session to export, as in `list --output jsonl`
+        #[arg(long)]
+        session: String,
+        /// File to write
+        #[arg(long, short)]
+        output: PathBuf,
+        /// Save the session's images in `attachments/` next to the file and link to them
+        #[arg(long)]
+        attachments: bool,
+        /// Print what would be written instead of writing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Write a project's user prompts as a chronological Markdown list, one line per prompt
+    Timeline {
+        /// Project path, or a unique part of it
+        #[arg(long)]
+        project: String,
+        /// File to write (prints to stdout when omitted)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        /// Print what would be written instead of writing it
+        #[arg(long, requires = "output")]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SnapshotCommand {
+    /// List the snapshots of each Claude directory
+    List,
+    /// Rebuild a snapshot as a Claude directory that can be indexed with --claude-dir
+    Restore {
+        /// Snapshot to restore, by date (default: the latest)
+        #[arg(long)]
+        date: Option<String>,
+        /// Directory to restore into (must not exist or be empty)
+        #[arg(long)]
+        to: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SyncCommand {
+    /// Write the entries indexed on this machine since the last export to a bundle
+    Export {
+        /// Only export entries after this cursor (default: after the previous export)
+        #[arg(long)]
+        since: Option<String>,
+        /// Bundle file to write
+        #[arg(long, short)]
+        output: PathBuf,
+        /// Print the bundle and store files the export would write instead of writing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Merge a bundle exported on another machine into the index
+    Import {
+        /// Bundle file to read
+        bundle: PathBuf,
+        /// Print the store files the import would write instead of writing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum CacheCommand {
+    /// Show where the index cache lives, whether it is writable and how large it is
+    Info,
+    /// Re-index from scratch in memory and compare with the cache (exit code 1 on differences)
+    Verify,
+}
+
+#[derive(Subcommand)]
+pub enum KeymapCommand {
+    /// Print the effective keymap as a Markdown cheat sheet
+    Export {
+        /// Write the cheat sheet to a file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        /// Print what would be written instead of writing it
+        #[arg(long, requires = "output")]
+        dry_run: bool,
+    },
+}
+
+/// Run the CLI and return the process exit code
+///
+/// Commands that build an index exit with 0 on a clean run, 3 if some input was skipped
+/// and 4 if indexing failed its failure threshold. Other errors are returned as `Err`.
+pub fn run() -> Result<ExitCode> {
+    let mut cli = Cli::parse();
+    let config = load_config();
+    if cli.claude_dirs.is_empty() {
+        cli.claude_dirs = config.claude_dir_specs();
+    }
+    cli.max_file_size = cli.max_file_size.or(config.max_file_size_mb);
+    if !set_index_storage(config.index_storage) {
+        eprintln!(
+            "Warning: \"index_storage\": \"{}\" needs a build with the `sqlite` feature; using {}",
+            config.index_storage.label(),
+            IndexStorage::default().label()
+        );
+    }
+    match DisplayZone::parse(config.timezone.as_deref().unwrap_or("local")) {
+        Ok(zone) => set_display_zone(zone),
+        Err(e) => eprintln!("Warning: {}; showing times in UTC", e),
+    }
+    let mut report = IndexReport::default();
+    let human_output = cli.report.is_none();
+    let builds_index = matches!(
+        &cli.command,
+        Some(
+            Commands::Stats { .. }
+                | Commands::Interactive { .. }
+                | Commands::Highlights { .. }
+                | Commands::Export { .. }
+                | Commands::Explain { .. }
+                | Commands::Show { .. }
+                | Commands::List { .. }
+                | Commands::Dedupe { .. }
+                | Commands::Summarize { .. }
+                | Commands::Resume { .. }
+                | Commands::Search { .. }
+                | Commands::Sync { command: SyncCommand::Export { .. }, .. }
+                | Commands::Renames { .. },
+        )
+    );
+    // A dry run leaves the on-disk index cache (and the metrics file) alone as well
+    let dry_run = cli.command.as_ref().is_some_and(Commands::is_dry_run);
+    let (cache, project_merges, sources) = if builds_index {
+        let sources = cli.history_sources(&config);
+        let cache = if dry_run {
+            IndexCache::in_memory(cli.parse_options())
+        } else {
+            open_index_cache(cli.parse_options())
+        };
+        (cache, config.project_merges, sources)
+    } else {
+        (IndexCache::in_memory(cli.parse_options()), BTreeMap::new(), Vec::new())
+    };
+    let ctx = IndexContext {
+        cache: Arc::new(cache),
+        notifier: cli.notifier(),
+        build_time: Cell::new(Duration::ZERO),
+        sync_store: SyncStore::default_dir().map(SyncStore::new),
+        project_merges,
+        sources,
+    };
+
+    let result = match &cli.command {
+        Some(Commands::Stats { tools, top }) => {
+            let result = if cli.claude_dirs.is_empty() {
+                show_stats(&ctx, &mut report, human_output)
+            } else {
+                show_workspace_stats(
+                    &resolve_roots(&cli.claude_dirs)?,
+                    &ctx,
+                    &mut report,
+                    human_output,
+                )
+            };
+            if result.is_ok() && *tools && human_output {
+                let max_file_size = cli.parse_options().file_size_limit();
+                print_tool_leaderboard(&resolve_roots(&cli.claude_dirs)?, *top, max_file_size)?;
+            }
+            result
+        }
+        Some(Commands::Interactive { preset }) if !cli.claude_dirs.is_empty() => {
+            run_interactive_workspace(
+                &resolve_roots(&cli.claude_dirs)?,
+                &ctx,
+                &mut report,
+                preset.as_deref(),
+            )
+        }
+        Some(Commands::Interactive { preset }) => {
+            run_interactive(&ctx, &mut report, preset.as_deref())
+        }
+        Some(Commands::Highlights { per_group, print }) => show_highlights(
+            &resolve_roots(&cli.claude_dirs)?,
+            &ctx,
+            *per_group,
+            *print && human_output,
+            &mut report,
+        ),
+        Some(Commands::Export {
+            command:
+                ExportCommand::Html {
+                    session: Some(session), title, output, attachments, dry_run, ..
+                },
+        }) => export_session(
+            &resolve_roots(&cli.claude_dirs)?,
+            &ctx,
+            (session, SessionFormat::Html(title)),
+            (output, *attachments, *dry_run),
+            &mut report,
+            human_output,
+        ),
+        Some(Commands::Export {
+            command: ExportCommand::Markdown { session, output, attachments, dry_run },
+        }) => export_session(
+            &resolve_roots(&cli.claude_dirs)?,
+            &ctx,
+            (session, SessionFormat::Markdown),
+            (output, *attachments, *dry_run),
+            &mut report,
+            human_output,
+        ),
+        Some(Commands::Export {
+            command: ExportCommand::Html { filter, title, output, dry_run, .. },
+        }) => export_html(
+            &resolve_roots(&cli.claude_dirs)?,
+            &ctx,
+            filter.as_deref(),
+            title,
+            (output, *dry_run),
+            &mut report,
+            human_output,
+        ),
+        Some(Commands::Export {
+            command: ExportCommand::Timeline { project, output, dry_run },
+        }) => export_timeline(
+            &resolve_roots(&cli.claude_dirs)?,
+            &ctx,
+            project,
+            (output.as_deref(), *dry_run),
+            &mut report,
+            human_output,
+        ),
+        Some(Commands::Explain { filter }) => {
+            explain(&resolve_roots(&cli.claude_dirs)?, &ctx, filter, &mut report, human_output)
+        }
+        Some(Commands::Show { target, color, width }) => show_message(
+            &resolve_roots(&cli.claude_dirs)?,
+            &ctx,
+            target,
+            (color.enabled(), width.unwrap_or_else(transcript_width)),
+            &mut report,
+        ),
+        Some(Commands::List { filter, limit, cursor, output }) => list_entries(
+            &resolve_roots(&cli.claude_dirs)?,
+            &ctx,
+            filter.as_deref(),
+            (*limit, cursor.as_deref()),
+            *output,
+            &mut report,
+            human_output,
+        ),
+        Some(Commands::Resume { session_id }) => ctx
+            .build_workspace_index(&resolve_roots(&cli.claude_dirs)?, &mut report)
+            .and_then(|(index, _)| ResumeTarget::find(&index, session_id))
+            .and_then(|target| target.run()),
+        Some(Commands::Summarize { filter, limit, refresh, dry_run }) => summarize_sessions(
+            &resolve_roots(&cli.claude_dirs)?,
+            &ctx,
+            config.summarizer.as_ref(),
+            filter.as_deref(),
+            (*limit, *refresh, *dry_run),
+            &mut report,
+        ),
+        Some(Commands::Dedupe { filter, show_duplicates, limit, output }) => dedupe_prompts(
+            &resolve_roots(&cli.claude_dirs)?,
+            &ctx,
+            filter.as_deref(),
+            (*show_duplicates, *limit),
+            *output,
+            &mut report,
+            human_output,
+        ),
+        Some(Commands::Search { query, limit, similar, fuzzy, semantic, sort, output }) => {
+            let (query, semantic) = match query.strip_prefix(SEMANTIC_PREFIX) {
+                Some(rest) => (rest.trim_start(), true),
+                None => (query.as_str(), *semantic),
+            };
+            let mode = match (*similar, *fuzzy, semantic) {
+                (_, _, true) => SearchMode::Semantic,
+                (true, _, _) => SearchMode::Similar,
+                (_, true, _) => SearchMode::Fuzzy,
+                _ => SearchMode::FullText,
+            };
+            search(
+                &resolve_roots(&cli.claude_dirs)?,
+                &ctx,
+                query,
+                (*limit, *sort, mode),
+                *output,
+                &mut report,
+                human_output,
+            )
+        }
+        Some(Commands::Doctor { fix, dry_run }) => {
+            let healthy = run_doctor(&resolve_roots(&cli.claude_dirs)?, (*fix, *dry_run))?;
+            return Ok(if healthy { ExitCode::SUCCESS } else { ExitCode::FAILURE });
+        }
+        Some(Commands::Keymap { command: KeymapCommand::Export { output, dry_run } }) => {
+            return export_keymap(output.as_deref(), *dry_run).map(|()| ExitCode::SUCCESS);
+        }
+        Some(Commands::Snapshot { archive, dry_run, command }) => {
+            let roots = resolve_roots(&cli.claude_dirs)?;
+            return run_snapshot(&roots, (archive.as_deref(), *dry_run), command.as_ref())
+                .map(|()| ExitCode::SUCCESS);
+        }
+        Some(Commands::Sync { store, command: SyncCommand::Export { since, output, dry_run } }) => {
+            let store = sync_store(store.as_deref())?;
+            let index = if cli.claude_dirs.is_empty() {
+                ctx.build_index(&get_claude_dir()?, &mut report)
+            } else {
+                ctx.build_workspace_index(&resolve_roots(&cli.claude_dirs)?, &mut report)
+                    .map(|(index, _)| index)
+            };
+            index.and_then(|index| {
+                if *dry_run {
+                    let mut plan = Plan::default();
+                    store.plan_export(&index, since.as_deref(), output, &mut plan)?;
+                    plan.print();
+                    return Ok(());
+                }
+                let summary = store.export(&index, since.as_deref(), output)?;
+                if human_output {
+                    println!("{}", format_export_summary(&summary, output));
+                }
+                Ok(())
+            })
+        }
+        Some(Commands::Sync { store, command: SyncCommand::Import { bundle, dry_run } }) => {
+            let store = sync_store(store.as_deref())?;
+            if *dry_run {
+                let mut plan = Plan::default();
+                store.plan_import(bundle, &mut plan)?;
+                plan.print();
+                return Ok(ExitCode::SUCCESS);
+            }
+            let summary = store.import(bundle)?;
+            println!(
+                "Imported {} new entries from machine {} ({} already present)",
+                summary.imported, summary.machine_id, summary.already_present
+            );
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Commands::Renames { yes, dry_run }) => {
+            let index = if cli.claude_dirs.is_empty() {
+                ctx.build_index(&get_claude_dir()?, &mut report)
+            } else {
+                ctx.build_workspace_index(&resolve_roots(&cli.claude_dirs)?, &mut report)
+                    .map(|(index, _)| index)
+            };
+            index.and_then(|index| merge_renamed_projects(&index, *yes, *dry_run))
+        }
+        Some(Commands::Cache { command: CacheCommand::Info }) => {
+            let cache = IndexCache::open(CacheDir::resolve(), cli.parse_options());
+            print!("{}", format_cache_info(&cache));
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Commands::Cache { command: CacheCommand::Verify }) => {
+            let consistent =
+                verify_index_cache(&resolve_roots(&cli.claude_dirs)?, cli.parse_options())?;
+            return Ok(if consistent { ExitCode::SUCCESS } else { ExitCode::FAILURE });
+        }
+        None => {
+            println!("Use --help for usage information");
+            return Ok(ExitCode::SUCCESS);
+        }
+    };
+
+    if let Err(e) = ctx.cache.save() {
+        eprintln!("Warning: Failed to save index cache: {:#}", e);
+    }
+    // Indexing prints nothing itself; the TUI has already listed the warnings (Ctrl+W)
+    if builds_index && !matches!(cli.command, Some(Commands::Interactive { .. })) {
+        eprint!("{}", format_index_warnings(&report));
+    }
+    if builds_index && cli.verbose {
+        eprint!("{}", format_index_summary(&report));
+    }
+    if let (Some(path), Some(command), false) = (&cli.metrics_file, &cli.command, dry_run) {
+        let metrics = RunMetrics {
+            command: command.name(),
+            report: &report,
+            build_duration: ctx.build_time.get(),
+            finished_at: Utc::now(),
+        };
+        if let Err(e) = metrics.write(path) {
+            eprintln!("Warning: Failed to write metrics: {:#}", e);
+        }
+    }
+
+    // Indexing failures are reported through the exit code; anything else is a plain error
+    match result {
+        Ok(()) => {}
+        Err(e) if report.status == IndexStatus::Failed => eprintln!("Error: {:#}", e),
+        Err(e) => return Err(e),
+    }
+
+    if cli.report == Some(ReportFormat::Json) {
+        println!("{}", report.to_json());
+    }
+
+    Ok(ExitCode::from(report.exit_code()))
+}
+
+/// What the index-building commands share: the cache and the completion notice
+struct IndexContext {
+    /// Shared with the TUI's cache management screen
+    cache: Arc<IndexCache>,
+    notifier: CompletionNotifier,
+    /// Time spent building indexes so far, for `--metrics-file`
+    build_time: Cell<Duration>,
+    /// Store whose imported entries join every index (`None`: no data directory)
+    sync_store: Option<SyncStore>,
+    /// Project paths shown as another project (`project_merges` in the config)
+    project_merges: BTreeMap<PathBuf, PathBuf>,
+    /// Histories of other tools added to every index
+    sources: Vec<Arc<dyn HistorySource>>,
+}
+
+impl IndexContext {
+    /// In-memory cache, no notices
+    #[cfg(test)]
+    fn in_memory() -> Self {
+        Self {
+            cache: Arc::new(IndexCache::in_memory(ParseOptions::default())),
+            notifier: CompletionNotifier::off(),
+            build_time: Cell::new(Duration::ZERO),
+            sync_store: None,
+            project_merges: BTreeMap::new(),
+            sources: Vec::new(),
+        }
+    }
+
+    fn build_index(&self, claude_dir: &Path, report: &mut IndexReport) -> Result<Vec<SearchEntry>> {
+        let started = Instant::now();
+        let result = match build_index_with_cache(claude_dir, &self.cache, report) {
+            // A missing Claude directory is an empty history to the CLI
+            Err(ExplorerError::ClaudeDirNotFound(path)) => {
+                report.record_diagnostic(&path, "Claude directory not found");
+                Ok(Vec::new())
+            }
+            result => result.map_err(anyhow::Error::from),
+        }
+        .map(|index| self.finish_index(index, report));
+        self.notify_finished(started, result.as_ref().map(Vec::len));
+        result
+    }
+
+    fn build_workspace_index(
+        &self,
+        roots: &[ClaudeRoot],
+        report: &mut IndexReport,
+    ) -> Result<(Vec<SearchEntry>, Vec<RootStatus>)> {
+        let started = Instant::now();
+        let result = build_workspace_index_with_cache(roots, &self.cache, report)
+            .map(|(index, statuses)| (self.finish_index(index, report), statuses));
+        self.notify_finished(started, result.as_ref().map(|(index, _)| index.len()));
+        result
+    }
+
+    /// Add the entries of other tools and those imported from other machines to `index`,
+    /// and merge renamed projects
+    fn finish_index(
+        &self,
+        mut index: Vec<SearchEntry>,
+        report: &mut IndexReport,
+    ) -> Vec<SearchEntry> {
+        merge_sources(&mut index, &self.sources, self.cache.options(), report);
+        if let Some(store) = &self.sync_store {
+            store.merge_into(&mut index, report);
+        }
+        apply_project_merges(&mut index, &self.project_merges);
+        index
+    }
+
+    /// Handle for managing the cache from the TUI, rebuilding from `roots`
+    #[cfg(feature = "tui")]
+    fn cache_control(&self, roots: Vec<ClaudeRoot>) -> crate::tui::CacheControl {
+        let control = crate::tui::CacheControl::new(Arc::clone(&self.cache), roots)
+            .with_project_merges(self.project_merges.clone())
+            .with_sources(self.sources.clone());
+        match &self.sync_store {
+            Some(store) => control.with_sync_store(store.clone()),
+            None => control,
+        }
+    }
+
+    /// Signal a slow index build, e.g. while the user waits in another window
+    fn notify_finished(&self, started: Instant, entries: Result<usize, &anyhow::Error>) {
+        self.build_time.set(self.build_time.get() + started.elapsed());
+        let message = match entries {
+            Ok(count) => format!("Index ready: {} entries", count),
+            Err(_) => "Index build failed".to_string(),
+        };
+        if let Err(e) = self.notifier.finished(started, &message) {
+            eprintln!("Warning: {:#}", e);
+        }
+    }
+}
+
+/// Open the index cache, checking that its directory is writable
+///
+/// If the platform cache directory can't be used, the fallback notice is printed to stderr
+/// (once per fallback directory; every run when caching in memory).
+fn open_index_cache(options: ParseOptions) -> IndexCache {
+    let mut dir = CacheDir::resolve();
+    if let Some(notice) = dir.notice() {
+        eprintln!("{}", notice);
+        if let Err(e) = dir.mark_notice_shown() {
+            eprintln!("Warning: {:#}", e);
+        }
+    }
+    IndexCache::open(dir, options)
+}
+
+/// Describe the cache location, its state and size for `cache info`
+fn format_cache_info(cache: &IndexCache) -> String {
+    let dir = cache.dir();
+    let mut out = String::from("Index cache\n===========\n");
+
+    match dir.location.path() {
+        Some(path) => out.push_str(&format!(
+            "Location:     {} ({})\n",
+            format_path_with_tilde(path),
+            dir.location.label()
+        )),
+        None => out.push_str(&format!("Location:     {}\n", dir.location.label())),
+    }
+
+    let state = if !IndexCache::persistence_enabled() {
+        "in memory only (built without the `cache` feature)"
+    } else {
+        match dir.location {
+            CacheLocation::Platform(_) => "writable",
+            CacheLocation::TempDir(_) => "writable (fallback; platform directory unavailable)",
+            CacheLocation::InMemory => "in memory only (no writable directory)",
+        }
+    };
+    out.push_str(&format!("State:        {}\n", state));
+    out.push_str(&format!("Storage:      {}\n", cache.storage().label()));
+
+    if let Some(index_file) = cache.index_file() {
+        let size = std::fs::metadata(&index_file).map_or(0, |m| m.len() as usize);
+        out.push_str(&format!("Cached files: {}\n", cache.len()));
+        out.push_str(&format!("Size:         {}\n", format_bytes(size)));
+    }
+
+    if !dir.problems.is_empty() {
+        out.push_str("\nProblems:\n");
+        for problem in &dir.problems {
+            out.push_str(&format!("  - {}\n", problem));
+        }
+    }
+    out
+}
+
+/// Index `roots` from scratch in memory, compare with the index cache and print the result
+///
+/// Returns whether every cached file matched its fresh parse.
+fn verify_index_cache(roots: &[ClaudeRoot], options: ParseOptions) -> Result<bool> {
+    let cache = IndexCache::open(CacheDir::resolve(), options);
+    let fresh = IndexCache::in_memory(options);
+    for root in roots {
+        build_index_with_cache(&root.path, &fresh, &mut IndexReport::default())?;
+    }
+    let paths: Vec<PathBuf> = roots.iter().map(|root| root.path.clone()).collect();
+    let result = verify_cache(&cache, &fresh, &paths);
+    print!("{}", format_cache_verification(&result, cache.index_file().as_deref()));
+    Ok(result.is_consistent())
+}
+
+/// Uuids listed per mismatching file before the rest is summarized
+const MAX_LISTED_UUIDS: usize = 5;
+
+fn format_cache_verification(result: &CacheVerification, index_file: Option<&Path>) -> String {
+    let mut out = String::from("Cache verification\n==================\n");
+    out.push_str(&format!("Compared:          {} files\n", result.compared));
+    out.push_str(&format!("Changed on disk:   {} (re-indexed on the next run)\n", result.stale));
+    out.push_str(&format!("Not cached yet:    {}\n", result.uncached));
+    out.push_str(&format!(
+        "No longer indexed: {} (dropped when the cache is compacted)\n",
+        result.orphaned.len()
+    ));
+
+    if result.is_consistent() {
+        out.push_str("\nThe cache matches a fresh parse.\n");
+        return out;
+    }
+
+    let list = |uuids: &[String]| {
+        let mut listed =
+            uuids.iter().take(MAX_LISTED_UUIDS).cloned().collect::<Vec<_>>().join(", ");
+        if uuids.len() > MAX_LISTED_UUIDS {
+            listed.push_str(&format!(", ... ({} more)", uuids.len() - MAX_LISTED_UUIDS));
+        }
+        listed
+    };
+    out.push_str(&format!("\nMismatches: {}\n", result.mismatches.len()));
+    for mismatch in &result.mismatches {
+        out.push_str(&format!(
+            "  {}: {} cached, {} fresh entries; {} changed\n",
+            format_path_with_tilde(&mismatch.path),
+            mismatch.cached_entries,
+            mismatch.fresh_entries,
+            mismatch.changed_entries
+        ));
+        if !mismatch.missing_uuids.is_empty() {
+            out.push_str(&format!("    missing: {}\n", list(&mismatch.missing_uuids)));
+        }
+        if !mismatch.extra_uuids.is_empty() {
+            out.push_str(&format!("    extra:   {}\n", list(&mismatch.extra_uuids)));
+        }
+        if mismatch.counters_differ {
+            out.push_str("    skipped line or tool output counts differ\n");
+        }
+    }
+    out.push_str("\nRebuild the cache from the TUI (F4)");
+    match index_file {
+        Some(path) => out.push_str(&format!(" or delete {}.\n", format_path_with_tilde(path))),
+        None => out.push_str(".\n"),
+    }
+    out
+}
+
+#[cfg(feature = "tui")]
+fn run_interactive(
+    ctx: &IndexContext,
+    report: &mut IndexReport,
+    preset: Option<&str>,
+) -> Result<()> {
+    let config = load_config();
+    let query = preset.map(|name| config.saved_search(name)).transpose()?;
+    let claude_dir = get_claude_dir()?;
+    let index = ctx.build_index(&claude_dir, report)?;
+    let roots = vec![ClaudeRoot::new(DEFAULT_ROOT_NAME, claude_dir)];
+    crate::tui::run_interactive(index, report, &config, Some(ctx.cache_control(roots)), query)
+}
+
+#[cfg(not(feature = "tui"))]
+fn run_interactive(
+    _ctx: &IndexContext,
+    _report: &mut IndexReport,
+    _preset: Option<&str>,
+) -> Result<()> {
+    Err(tui_unavailable())
+}
+
+/// Read the config file, falling back to the defaults (with a warning) if it is broken
+fn load_config() -> Config {
+    Config::load().unwrap_or_else(|e| {
+        eprintln!("Warning: {:#}; using default settings", e);
+        Config::default()
+    })
+}
+
+/// How text output shows times: the config's `timestamps`, absolute unless set
+fn text_timestamps(config: &Config) -> TimestampStyle {
+    config.timestamps.unwrap_or(TimestampStyle::Absolute)
+}
+
+/// Error for commands that need the interactive interface in a build without it
+#[cfg(not(feature = "tui"))]
+fn tui_unavailable() -> anyhow::Error {
+    anyhow::anyhow!("ai-history-explorer was built without the `tui` feature")
+}
+
+/// Check each root and report problems; returns whether everything is healthy
+///
+/// With `fix`, overly permissive files are restricted to the owner instead of just reported;
+/// with `dry_run` as well, the restrictions are printed as a plan instead.
+fn run_doctor(roots: &[ClaudeRoot], (fix, dry_run): (bool, bool)) -> Result<bool> {
+    let mut healthy = true;
+    let mut plan = Plan::default();
+
+    println!("ai-history-explorer doctor");
+    println!("==========================");
+    for root in roots {
+        println!();
+        println!("Root '{}': {}", root.name, format_path_with_tilde(&root.path));
+
+        if !root.path.is_dir() {
+            println!("  [fail] directory not found");
+            healthy = false;
+            continue;
+        }
+        println!("  [ok]   directory exists");
+
+        if root.path.join("history.jsonl").is_file() {
+            println!("  [ok]   history.jsonl found");
+        } else {
+            println!("  [warn] history.jsonl not found");
+        }
+
+        let Some(PermissionScan { issues, unreadable }) = find_permissive_files(&root.path)? else {
+            println!("  [skip] permissions not checked: unsupported on this platform");
+            continue;
+        };
+        for (path, reason) in &unreadable {
+            println!("  [warn] could not check {}: {}", format_path_with_tilde(path), reason);
+        }
+        if issues.is_empty() {
+            println!("  [ok]   permissions restricted to owner");
+            continue;
+        }
+
+        println!("  [fail] {} files/directories accessible by other users:", issues.len());
+        for issue in &issues {
+            println!("           {:04o}  {}", issue.mode, format_path_with_tilde(&issue.path));
+        }
+        if fix && dry_run {
+            for issue in &issues {
+                plan.restrict(&issue.path, issue.mode, issue.restricted_mode());
+            }
+            healthy = false;
+        } else if fix {
+            fix_permissions(&issues)?;
+            println!("  [ok]   fixed: restricted to owner");
+        } else {
+            healthy = false;
+        }
+    }
+
+    if dry_run {
+        println!();
+        plan.print();
+    } else if !healthy && !fix {
+        println!();
+        println!("Run `ai-history-explorer doctor --fix` to restrict permissions to the owner.");
+    }
+    Ok(healthy)
+}
+
+/// Write the keymap cheat sheet to `output`, or stdout when no file is given
+#[cfg(feature = "tui")]
+fn export_keymap(output: Option<&Path>, dry_run: bool) -> Result<()> {
+    let markdown = crate::tui::keymap(&load_config()).to_markdown();
+    match output {
+        Some(path) if dry_run => {
+            print_write_plan(path, markdown.len(), "keymap cheat sheet".to_string())
+        }
+        Some(path) => {
+            write_private_file(path, &markdown)
+                .with_context(|| format!("Failed to write keymap to {}", path.display()))?;
+            println!("Keymap written to {}", path.display());
+        }
+        None => print!("{}", markdown),
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "tui"))]
+fn export_keymap(_output: Option<&Path>, _dry_run: bool) -> Result<()> {
+    Err(tui_unavailable())
+}
+
+/// Take a snapshot of every root, or list / restore snapshots
+///
+/// Each root is kept in its own directory of the archive, named after the root. With
+/// `dry_run`, a snapshot or restore prints the files it would write instead.
+#[cfg(feature = "snapshot")]
+fn run_snapshot(
+    roots: &[ClaudeRoot],
+    (archive, dry_run): (Option<&Path>, bool),
+    command: Option<&SnapshotCommand>,
+) -> Result<()> {
+    use crate::snapshot::{
+        default_archive_dir, list_snapshots, plan_restore, plan_snapshot, restore_snapshot,
+        take_snapshot,
+    };
+
+    let archive = match archive {
+        Some(archive) => archive.to_path_buf(),
+        None => default_archive_dir()
+            .context("No data directory on this system; pass --archive to choose one")?,
+    };
+
+    match command {
+        None => {
+            let mut plan = Plan::default();
+            for root in roots {
+                let root_archive = root_archive(&archive, root)?;
+                let now = chrono::Utc::now();
+                let summary = if dry_run {
+                    plan_snapshot(&root.path, &root_archive, now, &mut plan)
+                } else {
+                    take_snapshot(&root.path, &root_archive, now)
+                }
+                .with_context(|| format!("Failed to snapshot root '{}'", root.name))?;
+                for (path, reason) in &summary.skipped {
+                    eprintln!("Warning: Skipped {}: {}", path.display(), reason);
+                }
+                if !dry_run {
+                    println!("{}", format_snapshot_summary(&root.name, &summary));
+                }
+            }
+            if dry_run {
+                plan.print();
+            }
+        }
+        Some(SnapshotCommand::List) => {
+            for root in roots {
+                let root_archive = root_archive(&archive, root)?;
+                let snapshots = list_snapshots(&root_archive)?;
+                print!("{}", format_snapshot_list(&root.name, &root_archive, &snapshots));
+            }
+        }
+        Some(SnapshotCommand::Restore { date, to }) => {
+            let [root] = roots else {
+                anyhow::bail!(
+                    "Restore one Claude directory at a time (pass a single --claude-dir)"
+                );
+            };
+            if dry_run {
+                let mut plan = Plan::default();
+                plan_restore(&root_archive(&archive, root)?, date.as_deref(), to, &mut plan)?;
+                plan.print();
+                return Ok(());
+            }
+            let summary = restore_snapshot(&root_archive(&archive, root)?, date.as_deref(), to)?;
+            println!(
+                "Restored snapshot {} of '{}' ({} files) to {}",
+                summary.name,
+                root.name,
+                summary.files,
+                to.display()
+            );
+            println!(
+                "Browse it with: ai-history-explorer --claude-dir {} interactive",
+                to.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "snapshot"))]
+fn run_snapshot(
+    _roots: &[ClaudeRoot],
+    _archive: (Option<&Path>, bool),
+    _command: Option<&SnapshotCommand>,
+) -> Result<()> {
+    anyhow::bail!("ai-history-explorer was built without the `snapshot` feature")
+}
+
+/// Offer to merge each probable rename of a project, saving accepted merges to the config
+///
+/// Reads the answers from stdin; with `yes`, every candidate is merged without asking. With
+/// `dry_run`, every candidate is merged into a plan of the config change, which is printed
+/// instead of saved.
+fn merge_renamed_projects(index: &[SearchEntry], yes: bool, dry_run: bool) -> Result<()> {
+    let candidates = detect_renames(index);
+    if candidates.is_empty() {
+        println!("No renamed projects found");
+        return Ok(());
+    }
+
+    let path = Config::default_path().context("No config directory on this system")?;
+    let mut config = Config::load_from(&path)?;
+    let mut merged = Vec::new();
+    let mut answers = std::io::stdin().lines();
+    for candidate in &candidates {
+        if dry_run {
+            if !config.project_merges.contains_key(&candidate.from) {
+                config.project_merges.insert(candidate.from.clone(), candidate.to.clone());
+                merged.push(format!("merge {}", format_rename_candidate(candidate)));
+            }
+            continue;
+        }
+        println!("{}", format_rename_candidate(candidate));
+        let merge = yes || {
+            print!("Merge into {}? [y/N] ", format_path_with_tilde(&candidate.to));
+            std::io::Write::flush(&mut std::io::stdout())?;
+            let answer = answers.next().transpose()?.unwrap_or_default();
+            println!();
+            matches!(answer.trim(), "y" | "Y" | "yes")
+        };
+        // A path merged earlier in this run is already gone
+        if merge && !config.project_merges.contains_key(&candidate.from) {
+            config.project_merges.insert(candidate.from.clone(), candidate.to.clone());
+            merged.push(format_rename_candidate(candidate));
+        }
+    }
+
+    if dry_run {
+        let mut plan = Plan::default();
+        if !merged.is_empty() {
+            let change = plan.write_file(&path, config.to_json()?.len() as u64);
+            for merge in merged {
+                change.note(merge);
+            }
+        }
+        plan.print();
+    } else if !merged.is_empty() {
+        config.save_to(&path)?;
+        println!("Merged {} projects; saved to {}", merged.len(), format_path_with_tilde(&path));
+    }
+    Ok(())
+}
+
+/// Print the `--dry-run` plan of writing `bytes` bytes to `path`
+fn print_write_plan(path: &Path, bytes: usize, note: String) {
+    let mut plan = Plan::default();
+    plan.write_file(path, bytes as u64).note(note);
+    plan.print();
+}
+
+fn format_rename_candidate(candidate: &RenameCandidate) -> String {
+    let evidence = if candidate.shared_sessions > 0 {
+        format!("{} shared sessions", candidate.shared_sessions)
+    } else {
+        format!(
+            "last used {}, moved {}",
+            candidate.from_last.format("%Y-%m-%d"),
+            candidate.to_first.format("%Y-%m-%d")
+        )
+    };
+    format!(
+        "{} → {} ({})",
+        format_path_with_tilde(&candidate.from),
+        format_path_with_tilde(&candidate.to),
+        evidence
+    )
+}
+
+/// Sync store at `dir`, or in the platform data directory
+fn sync_store(dir: Option<&Path>) -> Result<SyncStore> {
+    match dir {
+        Some(dir) => Ok(SyncStore::new(dir)),
+        None => SyncStore::default_dir()
+            .map(SyncStore::new)
+            .context("No data directory on this system; pass --store to choose one"),
+    }
+}
+
+/// One `Warning:` line per skipped file or entry and per diagnostic of `report`
+fn format_index_warnings(report: &IndexReport) -> String {
+    let skipped = report.skipped.iter().map(|item| (&item.path, &item.reason));
+    let diagnostics = report.diagnostics.iter().map(|d| (&d.path, &d.message));
+    skipped
+        .chain(diagnostics)
+        .map(|(path, message)| format!("Warning: {}: {}\n", path.display(), message))
+        .collect()
+}
+
+/// What indexing counted, for `--verbose`
+fn format_index_summary(report: &IndexReport) -> String {
+    let mut out = format!(
+        "Indexed {} entries ({} agent files parsed, {} failed)\n",
+        report.entries_indexed, report.agent_files_parsed, report.agent_files_failed
+    );
+    if report.history_lines_skipped > 0 || report.agent_lines_skipped > 0 {
+        out.push_str(&format!(
+            "Skipped {} malformed history lines and {} malformed agent file lines\n",
+            report.history_lines_skipped, report.agent_lines_skipped
+        ));
+    }
+    if report.history_entries_normalized > 0 {
+        out.push_str(&format!(
+            "Completed {} history entries without a session id or timestamp\n",
+            report.history_entries_normalized
+        ));
+    }
+    if report.tool_output_bytes_skipped > 0 {
+        out.push_str(&format!(
+            "Skipped {} bytes of oversized tool output\n",
+            report.tool_output_bytes_skipped
+        ));
+    }
+    out.push_str(&format!(
+        "{} skipped, {} diagnostics\n",
+        report.skipped.len(),
+        report.diagnostics.len()
+    ));
+    out
+}
+
+fn format_export_summary(summary: &ExportSummary, output: &Path) -> String {
+    let mut out = format!("Exported {} entries to {}", summary.entries, output.display());
+    if let Some(cursor) = &summary.cursor {
+        out.push_str(&format!("\nNext export starts after: --since {}", cursor));
+    }
+    out
+}
+
+/// Directory of `root` inside the snapshot archive
+#[cfg(feature = "snapshot")]
+fn root_archive(archive: &Path, root: &ClaudeRoot) -> Result<PathBuf> {
+    let mut components = Path::new(&root.name).components();
+    match (components.next(), components.next()) {
+        (Some(std::path::Component::Normal(_)), None) => Ok(archive.join(&root.name)),
+        _ => anyhow::bail!("Root name '{}' can't be used as a directory name", root.name),
+    }
+}
+
+#[cfg(feature = "snapshot")]
+fn format_snapshot_summary(root: &str, summary: &crate::snapshot::SnapshotSummary) -> String {
+    format!(
+        "Snapshot {} of '{}': {} new, {} changed, {} unchanged, {} removed files; {} written to {}",
+        summary.name,
+        root,
+        summary.new,
+        summary.changed,
+        summary.unchanged,
+        summary.removed,
+        format_bytes(summary.bytes_written as usize),
+        format_path_with_tilde(&summary.dir)
+    )
+}
+
+#[cfg(feature = "snapshot")]
+fn format_snapshot_list(
+    root: &str,
+    archive: &Path,
+    snapshots: &[crate::snapshot::SnapshotInfo],
+) -> String {
+    if snapshots.is_empty() {
+        return format!("No snapshots of '{}' in {}\n", root, format_path_with_tilde(archive));
+    }
+    let mut out = format!("Snapshots of '{}' in {}:\n", root, format_path_with_tilde(archive));
+    for snapshot in snapshots {
+        out.push_str(&format!(
+            "  {}  {:>6} files  {:>6} stored  {:>10}\n",
+            snapshot.name,
+            snapshot.files,
+            snapshot.stored,
+            format_bytes(snapshot.total_bytes as usize)
+        ));
+    }
+    out
+}
+
+/// Resolve `--claude-dir` specifications into workspace roots
+///
+/// Falls back to a single default root at `~/.claude` when no directories are given.
+fn resolve_roots(specs: &[String]) -> Result<Vec<ClaudeRoot>> {
+    if specs.is_empty() {
+        return Ok(vec![ClaudeRoot::new(DEFAULT_ROOT_NAME, get_claude_dir()?)]);
+    }
+    specs.iter().map(|spec| ClaudeRoot::parse(spec)).collect()
+}
+
+#[cfg(feature = "tui")]
+fn run_interactive_workspace(
+    roots: &[ClaudeRoot],
+    ctx: &IndexContext,
+    report: &mut IndexReport,
+    preset: Option<&str>,
+) -> Result<()> {
+    let config = load_config();
+    let query = preset.map(|name| config.saved_search(name)).transpose()?;
+    let (index, statuses) = ctx.build_workspace_index(roots, report)?;
+    crate::tui::run_interactive_workspace(
+        index,
+        statuses,
+        report,
+        &config,
+        Some(ctx.cache_control(roots.to_vec()).for_workspace()),
+        query,
+    )
+}
+
+#[cfg(not(feature = "tui"))]
+fn run_interactive_workspace(
+    _roots: &[ClaudeRoot],
+    _ctx: &IndexContext,
+    _report: &mut IndexReport,
+    _preset: Option<&str>,
+) -> Result<()> {
+    Err(tui_unavailable())
+}
+
+fn show_workspace_stats(
+    roots: &[ClaudeRoot],
+    ctx: &IndexContext,
+    report: &mut IndexReport,
+    human_output: bool,
+) -> Result<()> {
+    let (index, statuses) = ctx.build_workspace_index(roots, report)?;
+    if !human_output {
+        return Ok(());
+    }
+    match roots {
+        [root] => print_stats(&index, &root.path),
+        _ => print_workspace_stats(&index, &statuses),
+    }
+    Ok(())
+}
+
+fn print_workspace_stats(index: &[crate::models::SearchEntry], statuses: &[RootStatus]) {
+    println!("Claude Code History Statistics");
+    println!("================================");
+    println!("Total entries: {}", index.len());
+    println!();
+    println!("Roots:");
+    for status in statuses {
+        println!(
+            "  {:<12} {:>8} entries  [{}]  {}",
+            status.name,
+            status.entry_count,
+            status.health.label(),
+            format_path_with_tilde(&status.path)
+        );
+        if let Some(error) = &status.report.error {
+            println!("  {:<12} {}", "", error);
+        }
+    }
+
+    if let Some(oldest) = index.last() {
+        println!();
+        println!("Oldest entry: {}", oldest.timestamp.format("%Y-%m-%d %H:%M:%S"));
+    }
+    if let Some(newest) = index.first() {
+        println!("Newest entry: {}", newest.timestamp.format("%Y-%m-%d %H:%M:%S"));
+    }
+    print_usage_summary(index);
+    print_user_breakdown(index);
+    print_outcome_breakdown(index);
+}
+
+/// Index the roots, apply `filter` and write the HTML search page to `output`
+fn export_html(
+    roots: &[ClaudeRoot],
+    ctx: &IndexContext,
+    filter: Option<&str>,
+    title: &str,
+    (output, dry_run): (&Path, bool),
+    report: &mut IndexReport,
+    human_output: bool,
+) -> Result<()> {
+    // Parse the filter first: a typo shouldn't cost a full index build
+    let filter = parse_filter(filter.unwrap_or_default())?;
+    let (index, _) = ctx.build_workspace_index(roots, report)?;
+    let entries = apply_filters(index, &filter, &saved_bookmarks(&filter)?)?;
+
+    let page = render_html_page(&entries, title, &load_config().path_display())?;
+    if dry_run {
+        print_write_plan(output, page.len(), format!("{} entries", entries.len()));
+        return Ok(());
+    }
+    write_private_file(output, page)
+        .with_context(|| format!("Failed to write HTML export to {}", output.display()))?;
+    if human_output {
+        println!("Exported {} entries to {}", entries.len(), output.display());
+    }
+    Ok(())
+}
+
+/// Format of a whole-session export
+#[derive(Debug, Clone, Copy)]
+enum SessionFormat<'a> {
+    /// HTML page with this title
+    Html(&'a str),
+    Markdown,
+}
+
+/// Write the full conversation of session `session_id` as an HTML page or Markdown
+///
+/// The default HTML title is replaced by the session's first prompt. With `attachments`, the
+/// session's images are saved in `attachments/` next to `output` and shown in it.
+fn export_session(
+    roots: &[ClaudeRoot],
+    ctx: &IndexContext,
+    (session_id, format): (&str, SessionFormat<'_>),
+    (output, attachments, dry_run): (&Path, bool, bool),
+    report: &mut IndexReport,
+    human_output: bool,
+) -> Result<()> {
+    let (index, _) = ctx.build_workspace_index(roots, report)?;
+    let max_file_size = ctx.cache.options().file_size_limit();
+    let (session, session_entries) = load_session(&index, session_id, max_file_size)?;
+
+    let mut plan = Plan::default();
+    let images = match session_entries.iter().find_map(|e| e.source_file.as_deref()) {
+        Some(file) if attachments && dry_run => {
+            plan_attachments(&session, file, output, max_file_size, &mut plan)?
+        }
+        Some(file) if attachments => extract_attachments(&session, file, output, max_file_size)?,
+        _ => Attachments::default(),
+    };
+
+    let paths = load_config().path_display();
+    let (contents, label) = match format {
+        SessionFormat::Html(title) => {
+            let title = if title == DEFAULT_HTML_TITLE {
+                session_entries
+                    .iter()
+                    .filter(|e| e.entry_type == EntryType::UserPrompt)
+                    .min_by_key(|e| e.timestamp)
+                    .map_or_else(
+                        || title.to_string(),
+                        |e| condense(&e.display_text, TRACE_LINE_CHARS),
+                    )
+            } else {
+                title.to_string()
+            };
+            (render_session_page_with_attachments(&session, &title, &paths, &images), "HTML")
+        }
+        SessionFormat::Markdown => {
+            let mut markdown = session.to_markdown_with_attachments(&paths, &images);
+            if !markdown.ends_with('\n') {
+                markdown.push('\n');
+            }
+            (markdown, "Markdown")
+        }
+    };
+
+    let turns = format!("{} turns", session.turns.len());
+    if dry_run {
+        plan.write_file(output, contents.len() as u64).note(turns);
+        plan.print();
+        return Ok(());
+    }
+    write_private_file(output, contents)
+        .with_context(|| format!("Failed to write {} export to {}", label, output.display()))?;
+    if human_output {
+        let mut message = format!("Exported {} to {}", turns, output.display());
+        if attachments {
+            message.push_str(&format!(" and {} to {}/", images.summary(), ATTACHMENTS_DIR_NAME));
+        }
+        println!("{}", message);
+    }
+    Ok(())
+}
+
+/// Parse the conversation of session `session_id`, with its entries in the index
+///
+/// A conversation file larger than `max_file_size` bytes is an error.
+fn load_session<'a>(
+    index: &'a [SearchEntry],
+    session_id: &str,
+    max_file_size: u64,
+) -> Result<(Exchange, Vec<&'a SearchEntry>)> {
+    let session_entries: Vec<&SearchEntry> =
+        index.iter().filter(|e| e.session_id == session_id).collect();
+    if session_entries.is_empty() {
+        anyhow::bail!("No session '{}' in the history (see `list --output jsonl`)", session_id);
+    }
+    let source_file = session_entries
+        .iter()
+        .find_map(|e| e.source_file.as_deref())
+        .ok_or_else(|| anyhow::anyhow!("No conversation file for session '{}'", session_id))?;
+    let project = session_entries.iter().find_map(|e| e.project_path.as_deref());
+
+    let options = ParseOptions::with_file_size_limit(max_file_size);
+    let conversation: Vec<ConversationEntry> =
+        parse_conversation_file_with_options(source_file, &options)?
+            .entries
+            .into_iter()
+            .filter(|e| e.session_id == session_id)
+            .collect();
+    let session = extract_session(&conversation, project).ok_or_else(|| {
+        anyhow::anyhow!("Conversation file {} has no messages", source_file.display())
+    })?;
+    Ok((session, session_entries))
+}
+
+/// Width session transcripts are wrapped to: the terminal's, or 100 columns
+fn transcript_width() -> usize {
+    #[cfg(feature = "tui")]
+    if std::io::stdout().is_terminal()
+        && let Ok((columns, _)) = crossterm::terminal::size()
+    {
+        return usize::from(columns);
+    }
+    DEFAULT_TRANSCRIPT_WIDTH
+}
+
+/// Write (or print) the prompt timeline of the project matching `project`
+fn export_timeline(
+    roots: &[ClaudeRoot],
+    ctx: &IndexContext,
+    project: &str,
+    (output, dry_run): (Option<&Path>, bool),
+    report: &mut IndexReport,
+    human_output: bool,
+) -> Result<()> {
+    let (index, _) = ctx.build_workspace_index(roots, report)?;
+    let project = resolve_project(&index, project)?;
+    let timeline = PromptTimeline::for_project(&index, &project);
+    let markdown = timeline.to_markdown(&load_config().path_display());
+
+    match output {
+        Some(output) if dry_run => {
+            print_write_plan(output, markdown.len(), format!("{} prompts", timeline.prompts.len()));
+        }
+        Some(output) => {
+            write_private_file(output, markdown).with_context(|| {
+                format!("Failed to write prompt timeline to {}", output.display())
+            })?;
+            if human_output {
+                println!("Exported {} prompts to {}", timeline.prompts.len(), output.display());
+            }
+        }
+        None => print!("{}", markdown),
+    }
+    Ok(())
+}
+
+/// Find the indexed project named by `query`: an exact path, or the only project whose path
+/// contains it (case-insensitive). Paths may be written with `~` for the home directory.
+fn resolve_project(entries: &[SearchEntry], query: &str) -> Result<PathBuf> {
+    let mut projects: Vec<&Path> =
+        entries.iter().filter_map(|e| e.project_path.as_deref()).collect();
+    projects.sort();
+    projects.dedup();
+
+    if let Some(project) =
+        projects.iter().find(|p| p.as_os_str() == query || format_path_with_tilde(p) == query)
+    {
+        return Ok(project.to_path_buf());
+    }
+    let needle = query.to_lowercase();
+    let candidates: Vec<&Path> = projects
+        .into_iter()
+        .filter(|p| {
+            p.to_string_lossy().to_lowercase().contains(&needle)
+                || format_path_with_tilde(p).to_lowercase().contains(&needle)
+        })
+        .collect();
+    match candidates.as_slice() {
+        [project] => Ok(project.to_path_buf()),
+        [] => anyhow::bail!("No indexed project matches '{}'", query),
+        _ => anyhow::bail!(
+            "'{}' matches {} projects, be more specific:\n{}",
+            query,
+            candidates.len(),
+            candidates
+                .iter()
+                .map(|p| format!("  {}", format_path_with_tilde(p)))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ),
+    }
+}
+
+/// Parse `filter`, index the roots and print the per-clause explanation
+///
+/// Syntax errors point at the offending part of the filter.
+fn explain(
+    roots: &[ClaudeRoot],
+    ctx: &IndexContext,
+    filter: &str,
+    report: &mut IndexReport,
+    human_output: bool,
+) -> Result<()> {
+    let parsed = parse_filter(filter).and_then(|expr| Ok((expr, tokenize(filter)?)));
+    let (expr, tokens) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            let marker = e.downcast_ref::<FilterSyntaxError>().map(|s| mark_span(filter, s.span));
+            return Err(match marker {
+                Some(marker) => e.context(format!(
+                    "Invalid filter:\n\n{}",
+                    marker.lines().map(|line| format!("  {}", line)).collect::<Vec<_>>().join("\n")
+                )),
+                None => e,
+            });
+        }
+    };
+    if expr.is_empty() {
+        anyhow::bail!("Empty filter: nothing to explain");
+    }
+
+    let (index, _) = ctx.build_workspace_index(roots, report)?;
+    if human_output {
+        print!(
+            "{}",
+            format_explanation(
+                filter,
+                &explain_filter(&expr, &tokens, &index, &saved_bookmarks(&expr)?)?
+            )
+        );
+    }
+    Ok(())
+}
+
+/// Rank the entries against a full-text, lexical similarity or semantic query, bringing the
+/// persistent index up to date first
+#[cfg(any(feature = "fulltext", feature = "similar", feature = "semantic", feature = "tui"))]
+fn search(
+    roots: &[ClaudeRoot],
+    ctx: &IndexContext,
+    query: &str,
+    (limit, sort, mode): (usize, SearchSort, SearchMode),
+    output: OutputFormat,
+    report: &mut IndexReport,
+    human_output: bool,
+) -> Result<()> {
+    /// Search result as printed with `--output json`
+    #[derive(serde::Serialize)]
+    struct ScoredEntry<'a> {
+        #[serde(flatten)]
+        entry: &'a SearchEntry,
+        score: f32,
+    }
+
+    let (index, mut hits) = match mode {
+        SearchMode::FullText | SearchMode::Similar | SearchMode::Semantic => {
+            let (index, _) = ctx.build_workspace_index(roots, report)?;
+            let hits = match mode {
+                SearchMode::Similar => similar_hits(ctx, &index, query, limit, report)?,
+                SearchMode::Semantic => semantic_hits(ctx, &index, query, limit, report)?,
+                _ => fulltext_hits(ctx, &index, query, limit, report)?,
+            };
+            (index, hits)
+        }
+        SearchMode::Fuzzy => fuzzy_hits(roots, ctx, query, report)?,
+    };
+    match sort {
+        SearchSort::Relevance => {}
+        SearchSort::Newest => hits.sort_by_key(|hit| std::cmp::Reverse(index[hit.index].timestamp)),
+        SearchSort::Oldest => hits.sort_by_key(|hit| index[hit.index].timestamp),
+    }
+    hits.truncate(limit);
+    if human_output {
+        match output {
+            OutputFormat::Text => {
+                let config = load_config();
+                let (paths, times) = (config.path_display(), text_timestamps(&config));
+                print!("{}", format_search_results(query, &index, &hits, sort, &paths, times))
+            }
+            OutputFormat::Csv => {
+                print!("{}", format_csv_results(hits.iter().map(|hit| &index[hit.index])))
+            }
+            OutputFormat::Json | OutputFormat::Jsonl => {
+                let results: Vec<_> = hits
+                    .iter()
+                    .map(|hit| ScoredEntry { entry: &index[hit.index], score: hit.score })
+                    .collect();
+                print!("{}", format_json_results(&results, output)?);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(any(feature = "fulltext", feature = "similar", feature = "semantic", feature = "tui")))]
+fn search(
+    _roots: &[ClaudeRoot],
+    _ctx: &IndexContext,
+    _query: &str,
+    (_limit, _sort, mode): (usize, SearchSort, SearchMode),
+    _output: OutputFormat,
+    _report: &mut IndexReport,
+    _human_output: bool,
+) -> Result<()> {
+    let feature = match mode {
+        SearchMode::FullText => "fulltext",
+        SearchMode::Similar => "similar",
+        SearchMode::Semantic => "semantic",
+        SearchMode::Fuzzy => "tui",
+    };
+    anyhow::bail!("ai-history-explorer was built without the `{}` feature", feature)
+}
+
+/// Index the roots, keep the entries matching the filter left of the query's `|` and match
+/// the rest fuzzily, as the TUI does
+///
+/// Returns the filtered entries and the hits into them, best first.
+#[cfg(feature = "tui")]
+fn fuzzy_hits(
+    roots: &[ClaudeRoot],
+    ctx: &IndexContext,
+    query: &str,
+    report: &mut IndexReport,
+) -> Result<(Vec<SearchEntry>, Vec<crate::search::SearchHit>)> {
+    let (filter, pattern) = crate::tui::split_search_query(query);
+    // Parse the filter first: a typo shouldn't cost a full index build
+    let filter = parse_filter(filter.unwrap_or_default())?;
+    let (index, _) = ctx.build_workspace_index(roots, report)?;
+    let entries = apply_filters(index, &filter, &saved_bookmarks(&filter)?)?;
+    let hits = crate::search::fuzzy_search(&entries, pattern);
+    Ok((entries, hits))
+}
+
+#[cfg(all(
+    any(feature = "fulltext", feature = "similar", feature = "semantic"),
+    not(feature = "tui")
+))]
+fn fuzzy_hits(
+    _roots: &[ClaudeRoot],
+    _ctx: &IndexContext,
+    _query: &str,
+    _report: &mut IndexReport,
+) -> Result<(Vec<SearchEntry>, Vec<crate::search::SearchHit>)> {
+    anyhow::bail!("ai-history-explorer was built without the `tui` feature")
+}
+
+#[cfg(feature = "fulltext")]
+fn fulltext_hits(
+    ctx: &IndexContext,
+    index: &[SearchEntry],
+    query: &str,
+    limit: usize,
+    report: &mut IndexReport,
+) -> Result<Vec<crate::search::SearchHit>> {
+    let mut fulltext = crate::search::FullTextIndex::open(ctx.cache.dir())?;
+    for problem in fulltext.take_load_problems() {
+        report.record_diagnostic(&problem.path, problem.message);
+    }
+    fulltext.sync(index)?;
+    fulltext.search(query, limit)
+}
+
+#[cfg(all(
+    any(feature = "similar", feature = "semantic", feature = "tui"),
+    not(feature = "fulltext")
+))]
+fn fulltext_hits(
+    _ctx: &IndexContext,
+    _index: &[SearchEntry],
+    _query: &str,
+    _limit: usize,
+    _report: &mut IndexReport,
+) -> Result<Vec<crate::search::SearchHit>> {
+    let hint = if cfg!(feature = "tui") { "--fuzzy" } else { "--similar" };
+    anyhow::bail!("ai-history-explorer was built without the `fulltext` feature (try {})", hint)
+}
+
+#[cfg(feature = "similar")]
+fn similar_hits(
+    ctx: &IndexContext,
+    index: &[SearchEntry],
+    query: &str,
+    limit: usize,
+    report: &mut IndexReport,
+) -> Result<Vec<crate::search::SearchHit>> {
+    let mut vectors = crate::search::LexicalIndex::open(ctx.cache.dir());
+    for problem in vectors.take_load_problems() {
+        report.record_diagnostic(&problem.path, problem.message);
+    }
+    vectors.sync(index)?;
+    Ok(vectors.search(query, limit))
+}
+
+#[cfg(all(
+    any(feature = "fulltext", feature = "semantic", feature = "tui"),
+    not(feature = "similar")
+))]
+fn similar_hits(
+    _ctx: &IndexContext,
+    _index: &[SearchEntry],
+    _query: &str,
+    _limit: usize,
+    _report: &mut IndexReport,
+) -> Result<Vec<crate::search::SearchHit>> {
+    anyhow::bail!("ai-history-explorer was built without the `similar` feature")
+}
+
+#[cfg(feature = "semantic")]
+fn semantic_hits(
+    ctx: &IndexContext,
+    index: &[SearchEntry],
+    query: &str,
+    limit: usize,
+    report: &mut IndexReport,
+) -> Result<Vec<crate::search::SearchHit>> {
+    let embedder = crate::search::BertEmbedder::load(&load_config().embedding_model())?;
+    let mut vectors = crate::search::SemanticIndex::open(ctx.cache.dir(), Box::new(embedder));
+    for problem in vectors.take_load_problems() {
+        report.record_diagnostic(&problem.path, problem.message);
+    }
+    vectors.sync(index)?;
+    vectors.search(query, limit)
+}
+
+#[cfg(all(
+    any(feature = "fulltext", feature = "similar", feature = "tui"),
+    not(feature = "semantic")
+))]
+fn semantic_hits(
+    _ctx: &IndexContext,
+    _index: &[SearchEntry],
+    _query: &str,
+    _limit: usize,
+    _report: &mut IndexReport,
+) -> Result<Vec<crate::search::SearchHit>> {
+    anyhow::bail!("ai-history-explorer was built without the `semantic` feature")
+}
+
+/// The saved bookmarks if `filter` has a `starred:` clause (none are needed otherwise)
+fn saved_bookmarks(filter: &FilterExpr) -> Result<Bookmarks> {
+    if filter.uses(FilterField::Starred) {
+        Bookmarks::load_default()
+    } else {
+        Ok(Bookmarks::default())
+    }
+}
+
+/// Index the roots and print the entries matching `filter`, newest first
+fn list_entries(
+    roots: &[ClaudeRoot],
+    ctx: &IndexContext,
+    filter: Option<&str>,
+    (limit, cursor): (Option<usize>, Option<&str>),
+    output: OutputFormat,
+    report: &mut IndexReport,
+    human_output: bool,
+) -> Result<()> {
+    // Parse the filter and cursor first: a typo shouldn't cost a full index build
+    let filter = parse_filter(filter.unwrap_or_default())?;
+    let cursor = cursor.map(Cursor::decode).transpose()?;
+    let (index, _) = ctx.build_workspace_index(roots, report)?;
+    let entries = apply_filters(index, &filter, &saved_bookmarks(&filter)?)?;
+    let page = paginate(entries, cursor.as_ref(), limit.unwrap_or(usize::MAX));
+
+    if human_output {
+        match output {
+            OutputFormat::Text => {
+                let config = load_config();
+                let times = text_timestamps(&config);
+                print!("{}", format_entry_list(&page.entries, &config.path_display(), times))
+            }
+            OutputFormat::Csv => print!("{}", format_csv_results(&page.entries)),
+            OutputFormat::Json | OutputFormat::Jsonl => {
+                print!("{}", format_json_results(&page.entries, output)?)
+            }
+        }
+        // On stderr, so JSON output stays a plain array of entries
+        if let Some(next) = &page.next_cursor {
+            eprintln!("Next page: --cursor {}", next);
+        }
+    }
+    Ok(())
+}
+
+fn format_entry_list(
+    entries: &[SearchEntry],
+    paths: &crate::utils::PathDisplay,
+    times: TimestampStyle,
+) -> String {
+    let mut out = format!("Entries: {} (newest first)\n", entries.len());
+    for entry in entries {
+        let project = entry
+            .project_path
+            .as_deref()
+            .map(|p| paths.format(p))
+            .unwrap_or_else(|| "(no project)".to_string());
+        out.push_str(&format!(
+            "\n{}  {}  {}\n  {}\n",
+            format_time(&entry.timestamp, times, Utc::now()),
+            match entry.entry_type {
+                EntryType::UserPrompt => "user",
+                EntryType::AgentMessage => "agent",
+            },
+            project,
+            condense(&entry.display_text, TRACE_LINE_CHARS)
+        ));
+    }
+    out
+}
+
+/// Summarize the sessions without an up-to-date summary and save them after each one
+///
+/// Sessions are picked when one of their entries matches `filter`. Failures are printed and
+/// skipped. With `dry_run`, the summaries are printed but only planned to be saved.
+fn summarize_sessions(
+    roots: &[ClaudeRoot],
+    ctx: &IndexContext,
+    summarizer: Option<&Summarizer>,
+    filter: Option<&str>,
+    (limit, refresh, dry_run): (Option<usize>, bool, bool),
+    report: &mut IndexReport,
+) -> Result<()> {
+    let Some(summarizer) = summarizer else {
+        anyhow::bail!(
+            "No summarizer configured; add one to the config file, e.g. \
+             \"summarizer\": {{ \"command\": [\"ollama\", \"run\", \"llama3.2\"] }}"
+        );
+    };
+    let path = Summaries::default_path().context("No cache directory for the summaries")?;
+    let mut summaries = Summaries::load(&path)?;
+
+    let filter = parse_filter(filter.unwrap_or_default())?;
+    let (index, _) = ctx.build_workspace_index(roots, report)?;
+    let matched: HashSet<String> =
+        apply_filters(index.clone(), &filter, &saved_bookmarks(&filter)?)?
+            .into_iter()
+            .map(|e| e.session_id)
+            .collect();
+    let pending: Vec<Vec<&SearchEntry>> = group_sessions(&index)
+        .into_iter()
+        .filter(|session| matched.contains(&session[0].session_id))
+        .filter(|session| refresh || !summaries.is_current(session))
+        .take(limit.unwrap_or(usize::MAX))
+        .collect();
+
+    let mut failed = 0;
+    for session in &pending {
+        let session_id = &session[0].session_id;
+        match summarizer.summarize(session) {
+            Ok(summary) => {
+                println!("{}  {}", session_id, summary);
+                summaries.insert(session, summary);
+                if !dry_run {
+                    summaries.save(&path)?;
+                }
+            }
+            Err(e) => {
+                eprintln!("Warning: {}: {:#}", session_id, e);
+                failed += 1;
+            }
+        }
+    }
+    if dry_run {
+        let mut plan = Plan::default();
+        if pending.len() > failed {
+            plan.write_file(&path, summaries.to_json()?.len() as u64).note(format!(
+                "{} summaries ({} from this run)",
+                summaries.len(),
+                pending.len() - failed
+            ));
+        }
+        plan.print();
+        return Ok(());
+    }
+    println!(
+        "Summarized {} of {} sessions ({} failed, {} summaries saved)",
+        pending.len() - failed,
+        pending.len(),
+        failed,
+        summaries.len()
+    );
+    Ok(())
+}
+
+fn dedupe_prompts(
+    roots: &[ClaudeRoot],
+    ctx: &IndexContext,
+    filter: Option<&str>,
+    (show_duplicates, limit): (bool, Option<usize>),
+    output: OutputFormat,
+    report: &mut IndexReport,
+    human_output: bool,
+) -> Result<()> {
+    let filter = parse_filter(filter.unwrap_or_default())?;
+    let (index, _) = ctx.build_workspace_index(roots, report)?;
+    let entries = apply_filters(index, &filter, &saved_bookmarks(&filter)?)?;
+    let mut groups =
+        if show_duplicates { repeated_prompts(&entries) } else { group_prompts(&entries) };
+    groups.truncate(limit.unwrap_or(usize::MAX));
+
+    if human_output {
+        match output {
+            OutputFormat::Text => {
+                let config = load_config();
+                let (paths, times) = (config.path_display(), text_timestamps(&config));
+                print!("{}", format_prompt_groups(&groups, show_duplicates, &paths, times))
+            }
+            OutputFormat::Csv => {
+                print!("{}", format_csv_results(groups.iter().map(|group| &group.entry)))
+            }
+            OutputFormat::Json | OutputFormat::Jsonl => {
+                print!("{}", format_json_results(&groups, output)?)
+            }
+        }
+    }
+    Ok(())
+}
+
+fn format_prompt_groups(
+    groups: &[PromptGroup],
+    show_duplicates: bool,
+    paths: &crate::utils::PathDisplay,
+    times: TimestampStyle,
+) -> String {
+    let mut out = if show_duplicates {
+        format!("Repeated prompts: {} (most repeated first)\n", groups.len())
+    } else {
+        format!("Distinct prompts: {} (newest first)\n", groups.len())
+    };
+    for group in groups {
+        let project = group
+            .entry
+            .project_path
+            .as_deref()
+            .map(|p| paths.format(p))
+            .unwrap_or_else(|| "(no project)".to_string());
+        out.push_str(&format!(
+            "\n×{:<4} {}  {}  ({} session{}, since {})\n  {}\n",
+            group.occurrences,
+            format_time(&group.last_seen, times, Utc::now()),
+            project,
+            group.sessions,
+            if group.sessions == 1 { "" } else { "s" },
+            group.first_seen.format("%Y-%m-%d"),
+            condense(&group.entry.display_text, TRACE_LINE_CHARS)
+        ));
+    }
+    out
+}
+
+/// Results as a JSON array (`json`) or one JSON object per line (`jsonl`)
+fn format_json_results<T: serde::Serialize>(results: &[T], format: OutputFormat) -> Result<String> {
+    let mut out = match format {
+        OutputFormat::Jsonl => {
+            let lines = results
+                .iter()
+                .map(serde_json::to_string)
+                .collect::<serde_json::Result<Vec<_>>>()
+                .context("Failed to serialize results")?;
+            lines.join("\n")
+        }
+        OutputFormat::Json | OutputFormat::Text | OutputFormat::Csv => {
+            serde_json::to_string_pretty(results).context("Failed to serialize results")?
+        }
+    };
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    Ok(out)
+}
 
-use crate::indexer::build_index;
-use crate::models::EntryType;
-use crate::utils::{format_path_with_tilde, get_claude_dir};
+/// Entries as CSV with a header row, one row per entry
+///
+/// Times are UTC, projects are full paths and the text is cut to [`CSV_TEXT_CHARS`]
+/// characters (newlines kept, inside quotes).
+fn format_csv_results<'a>(entries: impl IntoIterator<Item = &'a SearchEntry>) -> String {
+    let mut out = String::from("timestamp,type,project,session_id,text\r\n");
+    for entry in entries {
+        let fields = [
+            entry.timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+            match entry.entry_type {
+                EntryType::UserPrompt => "user",
+                EntryType::AgentMessage => "agent",
+            }
+            .to_string(),
+            entry.project_path.as_deref().map(|p| p.display().to_string()).unwrap_or_default(),
+            entry.session_id.clone(),
+            entry.display_text.chars().take(CSV_TEXT_CHARS).collect(),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        out.push_str(&row.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
 
-#[derive(Parser)]
-#[command(name = "ai-history-explorer")]
-#[command(version = "0.1.0")]
-#[command(about = "Search through Claude Code conversation history", long_about = None)]
-pub struct Cli {
-    #[command(subcommand)]
-    pub command: Option<Commands>,
+/// Quote a CSV field when needed (RFC 4180)
+///
+/// Text starting like a formula (`=`, `+`, `-`, `@`) gets a leading `'`, so a spreadsheet
+/// shows a prompt such as `=HYPERLINK(...)` instead of evaluating it.
+fn csv_field(field: &str) -> String {
+    let field = if field.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", field)
+    } else {
+        field.to_string()
+    };
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
 }
 
-#[derive(Subcommand)]
-pub enum Commands {
-    /// Show statistics about the history
-    Stats,
-    /// Launch interactive fuzzy-finder TUI
-    Interactive,
+#[cfg(any(feature = "fulltext", feature = "similar", feature = "semantic", feature = "tui"))]
+fn format_search_results(
+    query: &str,
+    index: &[SearchEntry],
+    hits: &[crate::search::SearchHit],
+    sort: SearchSort,
+    paths: &crate::utils::PathDisplay,
+    times: TimestampStyle,
+) -> String {
+    let order = match sort {
+        SearchSort::Relevance => "best first",
+        SearchSort::Newest => "newest first",
+        SearchSort::Oldest => "oldest first",
+    };
+    let mut out = format!("Query:   {}\nMatches: {} ({})\n", query, hits.len(), order);
+    for (i, hit) in hits.iter().enumerate() {
+        let entry = &index[hit.index];
+        let project = entry
+            .project_path
+            .as_deref()
+            .map(|p| paths.format(p))
+            .unwrap_or_else(|| "(no project)".to_string());
+        out.push_str(&format!(
+            "\n{:>3}. {}  {:.2}  {}\n     {}\n",
+            i + 1,
+            format_time(&entry.timestamp, times, Utc::now()),
+            hit.score,
+            project,
+            condense(&entry.display_text, TRACE_LINE_CHARS)
+        ));
+    }
+    out
+}
+
+fn format_explanation(filter: &str, explanation: &FilterExplanation) -> String {
+    let mut out = format!("Filter: {}\nParsed: {}\n", filter, explanation.grouped());
+    out.push_str(&format!(
+        "\nClauses (entries matched by each clause alone, of {}):\n",
+        explanation.total
+    ));
+
+    for (i, clause) in explanation.clauses.iter().enumerate() {
+        if i > 0 {
+            let operator = &explanation.operators[i - 1];
+            let same_field = explanation.clauses[i - 1].filter.field == clause.filter.field;
+            let (keyword, implied_by) = match operator.operator {
+                FilterOperator::And if same_field && clause.filter.field.is_size() => {
+                    ("AND", "a size range")
+                }
+                FilterOperator::And if same_field => ("AND", "NOT or parentheses"),
+                FilterOperator::And => ("AND", "different fields"),
+                FilterOperator::Or => ("OR", "same field"),
+            };
+            if operator.explicit {
+                out.push_str(&format!("     {}\n", keyword));
+            } else {
+                out.push_str(&format!("     {} (implicit: {})\n", keyword, implied_by));
+            }
+        }
+        out.push_str(&format!(
+            "  {}. {:<28} {:>8}  {}\n",
+            i + 1,
+            clause.label(),
+            clause.matches,
+            clause.semantics
+        ));
+    }
+
+    out.push_str(&format!(
+        "\nWhole filter: {} of {} entries\n",
+        explanation.matches, explanation.total
+    ));
+    if explanation.matches == 0 {
+        for (i, clause) in explanation.clauses.iter().enumerate() {
+            if clause.matches == 0 {
+                out.push_str(&format!("Clause {} matches nothing on its own\n", i + 1));
+            }
+        }
+    }
+    out
+}
+
+/// Longest line of a block summary in `show parent:<uuid>`, in characters
+const TRACE_LINE_CHARS: usize = 160;
+
+/// Print the message `target` names (`<uuid>`), or its ancestor chain (`parent:<uuid>`)
+/// Print a session transcript, a message or the chain that led to a message
+///
+/// A plain id is looked up as a session first, then as a message uuid.
+fn show_message(
+    roots: &[ClaudeRoot],
+    ctx: &IndexContext,
+    target: &str,
+    (color, width): (bool, usize),
+    report: &mut IndexReport,
+) -> Result<()> {
+    let (uuid, trace) = match target.strip_prefix("parent:") {
+        Some(uuid) => (uuid.trim(), true),
+        None => (target.trim(), false),
+    };
+    if uuid.is_empty() {
+        anyhow::bail!("Missing id: use `show <session-id>`, `show <uuid>` or `show parent:<uuid>`");
+    }
+
+    let (index, _) = ctx.build_workspace_index(roots, report)?;
+    if !trace && index.iter().any(|e| e.session_id == uuid) {
+        let (session, _) = load_session(&index, uuid, ctx.cache.options().file_size_limit())?;
+        print!("{}", render_transcript(&session, width, color, &load_config().path_display()));
+        return Ok(());
+    }
+    let (file, entries) = find_conversation(&index, uuid, ctx.cache.options().file_size_limit())?;
+    let relations = EntryRelations::from_entries(&entries);
+    if trace {
+        print!("{}", format_trace(&entries, &relations, uuid));
+    } else {
+        print!("{}", format_message(&entries, &relations, uuid, &file));
+    }
+    Ok(())
 }
 
-pub fn run() -> Result<()> {
-    let cli = Cli::parse();
+/// Parse the conversation file containing the message `uuid`
+///
+/// Messages without text (e.g. tool results) aren't indexed, so files are searched for the
+/// uuid when no indexed entry carries it.
+fn find_conversation(
+    index: &[SearchEntry],
+    uuid: &str,
+    max_file_size: u64,
+) -> Result<(PathBuf, Vec<ConversationEntry>)> {
+    let options = ParseOptions::with_file_size_limit(max_file_size);
+    let indexed = index.iter().find(|e| e.uuid.as_deref() == Some(uuid));
+    let mut files: Vec<&Path> = match indexed.and_then(|e| e.source_file.as_deref()) {
+        Some(file) => vec![file],
+        None => index.iter().filter_map(|e| e.source_file.as_deref()).collect(),
+    };
+    files.sort();
+    files.dedup();
+
+    for file in files {
+        match std::fs::read_to_string(file) {
+            Ok(contents) if contents.contains(uuid) => {}
+            _ => continue,
+        }
+        let entries = parse_conversation_file_with_options(file, &options)?.entries;
+        if entries.iter().any(|e| e.uuid == uuid) {
+            return Ok((file.to_path_buf(), entries));
+        }
+    }
+    anyhow::bail!("No conversation message with uuid '{}'", uuid)
+}
 
-    match &cli.command {
-        Some(Commands::Stats) => {
-            show_stats()?;
+fn format_message(
+    entries: &[ConversationEntry],
+    relations: &EntryRelations,
+    uuid: &str,
+    file: &Path,
+) -> String {
+    let Some(entry) = entries.iter().find(|e| e.uuid == uuid) else {
+        return format!("No message with uuid '{}'\n", uuid);
+    };
+    let replies = relations.children(uuid);
+    let mut out = format!("Message {}\n", entry.uuid);
+    out.push_str(&format!("Role:     {}\n", entry.message.role));
+    out.push_str(&format!("Time:     {}\n", entry.timestamp.format("%Y-%m-%d %H:%M:%S")));
+    out.push_str(&format!("Session:  {}\n", entry.session_id));
+    out.push_str(&format!("File:     {}\n", format_path_with_tilde(file)));
+    out.push_str(&format!("Parent:   {}\n", relations.parent(uuid).unwrap_or("(none)")));
+    out.push_str(&format!(
+        "Replies:  {}\n",
+        if replies.is_empty() { "(none)".to_string() } else { replies.join(", ") }
+    ));
+
+    out.push('\n');
+    match &entry.message.content {
+        MessageContent::String(text) => out.push_str(&format!("{}\n", text.trim_end())),
+        MessageContent::Array(blocks) => {
+            for block in blocks {
+                out.push_str(&format!("{}\n", summarize_block(block, None)));
+            }
         }
-        Some(Commands::Interactive) => {
-            run_interactive()?;
+    }
+    out
+}
+
+/// Ancestors of `uuid` oldest first, ending with the message itself; one line per block
+fn format_trace(entries: &[ConversationEntry], relations: &EntryRelations, uuid: &str) -> String {
+    let mut chain: Vec<&str> = relations.ancestors(uuid);
+    chain.reverse();
+    chain.push(uuid);
+
+    let mut out = format!("Trace of {} ({} messages, oldest first)\n", uuid, chain.len());
+    for (i, message) in chain.iter().enumerate() {
+        let Some(entry) = entries.iter().find(|e| e.uuid == *message) else {
+            out.push_str(&format!("\n{:>3}. {} (not in this file)\n", i + 1, message));
+            continue;
+        };
+        out.push_str(&format!(
+            "\n{:>3}. {}  {:<9}  {}\n",
+            i + 1,
+            entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            entry.message.role,
+            entry.uuid
+        ));
+        match &entry.message.content {
+            MessageContent::String(text) => {
+                out.push_str(&format!("     {}\n", condense(text, TRACE_LINE_CHARS)))
+            }
+            MessageContent::Array(blocks) => {
+                for block in blocks {
+                    out.push_str(&format!(
+                        "     {}\n",
+                        summarize_block(block, Some(TRACE_LINE_CHARS))
+                    ));
+                }
+            }
         }
-        None => {
-            println!("Use --help for usage information");
+    }
+    out
+}
+
+/// Content block with a label for non-text blocks, condensed to one line if `max_chars` is set
+fn summarize_block(block: &ContentBlock, max_chars: Option<usize>) -> String {
+    let shorten = |text: &str| match max_chars {
+        Some(max_chars) => condense(text, max_chars),
+        None => text.trim_end().to_string(),
+    };
+    match block {
+        ContentBlock::Text { text } => shorten(text),
+        ContentBlock::Thinking { thinking, .. } => format!("[thinking] {}", shorten(thinking)),
+        ContentBlock::ToolUse { name, input, .. } => {
+            format!("[tool call {}] {}", name, shorten(&input.to_string()))
         }
+        ContentBlock::ToolResult { content, is_error, .. } => {
+            let label = if *is_error == Some(true) { "tool error" } else { "tool result" };
+            format!("[{}] {}", label, shorten(&tool_result_text(content)))
+        }
+        ContentBlock::Image { alt_text, .. } => match alt_text {
+            Some(alt) => format!("[image] {}", shorten(alt)),
+            None => "[image]".to_string(),
+        },
     }
+}
 
-    Ok(())
+/// Sample highlights and either print them or browse them in the TUI
+fn show_highlights(
+    roots: &[ClaudeRoot],
+    ctx: &IndexContext,
+    per_group: usize,
+    print: bool,
+    report: &mut IndexReport,
+) -> Result<()> {
+    let (index, statuses) = ctx.build_workspace_index(roots, report)?;
+    let groups = sample_highlights(&index, per_group);
+
+    if print {
+        print!("{}", format_highlights(&index, &groups));
+        return Ok(());
+    }
+    browse_highlights(&index, &groups, statuses)
 }
 
-fn run_interactive() -> Result<()> {
-    let claude_dir = get_claude_dir()?;
-    let index = build_index(&claude_dir)?;
-    crate::tui::run_interactive(index)
+/// Open the sampled highlights in the TUI, newest first
+#[cfg(feature = "tui")]
+fn browse_highlights(
+    index: &[SearchEntry],
+    groups: &[HighlightGroup],
+    statuses: Vec<RootStatus>,
+) -> Result<()> {
+    let mut sampled: Vec<SearchEntry> = groups
+        .iter()
+        .flat_map(|group| group.highlights.iter().map(|h| index[h.index].clone()))
+        .collect();
+    sampled.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+
+    // The digest always opens as a plain list
+    let config = Config { startup_view: StartupView::Blank, ..load_config() };
+    // Re-parsed files would merge all their entries into the digest, so no warnings view
+    if statuses.len() > 1 {
+        crate::tui::run_interactive_workspace(
+            sampled,
+            statuses,
+            &IndexReport::default(),
+            &config,
+            None,
+            None,
+        )
+    } else {
+        crate::tui::run_interactive(sampled, &IndexReport::default(), &config, None, None)
+    }
 }
 
-fn show_stats() -> Result<()> {
-    show_stats_impl(None)
+#[cfg(not(feature = "tui"))]
+fn browse_highlights(
+    _index: &[SearchEntry],
+    _groups: &[HighlightGroup],
+    _statuses: Vec<RootStatus>,
+) -> Result<()> {
+    Err(tui_unavailable())
+}
+
+fn format_highlights(index: &[SearchEntry], groups: &[HighlightGroup]) -> String {
+    const MAX_PREVIEW_CHARS: usize = 80;
+
+    let mut out = String::from("Highlights\n==========\n");
+    for group in groups {
+        let project = group
+            .project
+            .as_deref()
+            .map(format_path_with_tilde)
+            .unwrap_or_else(|| "(no project)".to_string());
+        out.push_str(&format!(
+            "\n{}  ({}-W{:02})\n",
+            project,
+            group.week.year(),
+            group.week.week()
+        ));
+
+        for highlight in &group.highlights {
+            let entry = &index[highlight.index];
+            let first_line = entry.display_text.lines().next().unwrap_or("");
+            let mut preview: String = first_line.chars().take(MAX_PREVIEW_CHARS).collect();
+            if first_line.chars().count() > MAX_PREVIEW_CHARS {
+                preview.push_str("...");
+            }
+            out.push_str(&format!(
+                "  {}  [{}]  {}\n",
+                entry.timestamp.format("%Y-%m-%d %H:%M"),
+                highlight.reason.label(),
+                preview
+            ));
+        }
+    }
+    out
+}
+
+fn show_stats(ctx: &IndexContext, report: &mut IndexReport, human_output: bool) -> Result<()> {
+    show_stats_impl(None, ctx, report, human_output)
 }
 
 // Internal implementation that allows passing in a custom claude_dir for testing
 #[cfg(not(test))]
-fn show_stats_impl(_claude_dir_override: Option<&Path>) -> Result<()> {
+fn show_stats_impl(
+    _claude_dir_override: Option<&Path>,
+    ctx: &IndexContext,
+    report: &mut IndexReport,
+    human_output: bool,
+) -> Result<()> {
     let claude_dir = get_claude_dir()?;
-    let index = build_index(&claude_dir)?;
-    print_stats(&index, &claude_dir);
+    let index = ctx.build_index(&claude_dir, report)?;
+    if human_output {
+        print_stats(&index, &claude_dir);
+    }
     Ok(())
 }
 
 #[cfg(test)]
-fn show_stats_impl(claude_dir_override: Option<&Path>) -> Result<()> {
+fn show_stats_impl(
+    claude_dir_override: Option<&Path>,
+    ctx: &IndexContext,
+    report: &mut IndexReport,
+    human_output: bool,
+) -> Result<()> {
     let claude_dir =
         if let Some(dir) = claude_dir_override { dir.to_path_buf() } else { get_claude_dir()? };
-    let index = build_index(&claude_dir)?;
-    print_stats(&index, &claude_dir);
+    let index = ctx.build_index(&claude_dir, report)?;
+    if human_output {
+        print_stats(&index, &claude_dir);
+    }
+    Ok(())
+}
+
+/// Scan every root's conversations and print the tool-output leaderboard
+fn print_tool_leaderboard(roots: &[ClaudeRoot], top: usize, max_file_size: u64) -> Result<()> {
+    let mut records = Vec::new();
+    let mut report = IndexReport::default();
+    for root in roots {
+        records.extend(scan_tool_outputs(&root.path, max_file_size, &mut report)?);
+    }
+    eprint!("{}", format_index_warnings(&report));
+    println!();
+    print!("{}", format_tool_leaderboard(&build_tool_output_report(records, top)));
     Ok(())
 }
 
+fn format_tool_leaderboard(report: &ToolOutputReport) -> String {
+    let project_label = |project: &Option<PathBuf>| {
+        project.as_deref().map(format_path_with_tilde).unwrap_or_else(|| "(no project)".into())
+    };
+
+    let mut out = String::from("Tool Output Leaderboard\n=======================\n");
+    out.push_str(&format!(
+        "Tool results: {}  ({} total)\n",
+        report.total_results,
+        format_bytes(report.total_bytes)
+    ));
+    if report.total_results == 0 {
+        return out;
+    }
+
+    out.push_str("\nLargest results:\n");
+    for record in &report.largest {
+        out.push_str(&format!(
+            "  {:>10}  {:<16}  {}  {}\n",
+            format_bytes(record.bytes),
+            record.tool,
+            record.timestamp.format("%Y-%m-%d %H:%M"),
+            project_label(&record.project)
+        ));
+    }
+
+    out.push_str("\nMost verbose tools:\n");
+    for usage in &report.usage {
+        out.push_str(&format!(
+            "  {:>10}  {:<16}  {:>6} calls  max {:>10}  {}\n",
+            format_bytes(usage.total_bytes),
+            usage.tool,
+            usage.calls,
+            format_bytes(usage.max_bytes),
+            project_label(&usage.project)
+        ));
+    }
+    out
+}
+
 fn print_stats(index: &[crate::models::SearchEntry], claude_dir: &Path) {
     let user_prompts =
         index.iter().filter(|e| matches!(e.entry_type, EntryType::UserPrompt)).count();
@@ -90,6 +2825,126 @@ fn print_stats(index: &[crate::models::SearchEntry], claude_dir: &Path) {
     if let Some(newest) = index.first() {
         println!("Newest entry: {}", newest.timestamp.format("%Y-%m-%d %H:%M:%S"));
     }
+    print_usage_summary(index);
+    print_user_breakdown(index);
+    print_outcome_breakdown(index);
+}
+
+/// Print the per-user breakdown, if the history records any user identities
+/// Print the usage dashboard (days and hours in local time), if there is anything to show
+fn print_usage_summary(index: &[SearchEntry]) {
+    if !index.is_empty() {
+        println!();
+        print!("{}", format_usage_summary(&UsageSummary::compute(index, &chrono::Local)));
+    }
+}
+
+fn format_usage_summary(summary: &UsageSummary) -> String {
+    let mut out = String::from("Projects:\n");
+    for usage in summary.projects.iter().take(STATS_TOP_PROJECTS) {
+        let project =
+            usage.project.as_deref().map(format_path_with_tilde).unwrap_or("(no project)".into());
+        out.push_str(&format!(
+            "  {:>6} prompts  {:>7} messages  {:>5} sessions  {}\n",
+            usage.prompts, usage.messages, usage.sessions, project
+        ));
+    }
+    if summary.projects.len() > STATS_TOP_PROJECTS {
+        out.push_str(&format!("  ... and {} more\n", summary.projects.len() - STATS_TOP_PROJECTS));
+    }
+
+    if let Some(per_day) = summary.prompts_per_active_day() {
+        out.push_str(&format!(
+            "\nPrompts per active day: {:.1} ({} days)\n",
+            per_day,
+            summary.prompts_per_day.len()
+        ));
+        let busiest = summary.prompts_per_day.iter().max_by_key(|(day, prompts)| (**prompts, *day));
+        if let Some((day, prompts)) = busiest {
+            out.push_str(&format!("Busiest day: {} ({} prompts)\n", day, prompts));
+        }
+
+        let max_week = summary.prompts_per_week.values().copied().max().unwrap_or(0);
+        out.push_str("\nPrompts per week:\n");
+        let weeks = summary.prompts_per_week.iter().rev().take(STATS_RECENT_WEEKS).rev();
+        for (week, prompts) in weeks {
+            out.push_str(&format!(
+                "  {}-W{:02} {:>6}  {}\n",
+                week.year(),
+                week.week(),
+                prompts,
+                bar(*prompts, max_week)
+            ));
+        }
+
+        let hours = summary.busiest_hours(3);
+        let hours: Vec<String> =
+            hours.iter().map(|(hour, prompts)| format!("{:02}:00 ({})", hour, prompts)).collect();
+        out.push_str(&format!("\nBusiest hours: {}\n", hours.join(", ")));
+    }
+
+    if let Some(chars) = summary.average_prompt_chars {
+        out.push_str(&format!("Average prompt length: {} characters\n", chars));
+    }
+
+    if !summary.tool_calls.is_empty() {
+        out.push_str("\nTool calls:\n");
+        for (tool, calls) in summary.tool_calls.iter().take(STATS_TOP_TOOLS) {
+            out.push_str(&format!("  {:>7}  {}\n", calls, tool));
+        }
+    }
+    out
+}
+
+/// Histogram bar for `value` out of `max`
+fn bar(value: usize, max: usize) -> String {
+    "█".repeat((value * STATS_BAR_WIDTH).div_ceil(max.max(1)))
+}
+
+fn print_user_breakdown(index: &[SearchEntry]) {
+    if has_user_identities(index) {
+        println!();
+        print!("{}", format_user_breakdown(&user_breakdown(index)));
+    }
+}
+
+/// Print the sessions per outcome, if any session was classified
+fn print_outcome_breakdown(index: &[SearchEntry]) {
+    let breakdown = outcome_breakdown(index);
+    if !breakdown.is_empty() {
+        println!();
+        print!("{}", format_outcome_breakdown(&breakdown));
+    }
+}
+
+fn format_outcome_breakdown(breakdown: &[OutcomeStats]) -> String {
+    let total: usize = breakdown.iter().map(|stats| stats.sessions).sum();
+    let mut out = String::from("Session outcomes (guessed from how each session ended):\n");
+    for stats in breakdown {
+        out.push_str(&format!(
+            "  {:<10} {:>6} sessions  {:>3}%\n",
+            stats.outcome.label(),
+            stats.sessions,
+            stats.sessions * 100 / total.max(1)
+        ));
+    }
+    out
+}
+
+fn format_user_breakdown(breakdown: &[UserStats]) -> String {
+    let mut out = String::from("Users:\n");
+    for stats in breakdown {
+        out.push_str(&format!(
+            "  {:<24} {:>8} entries  {:>6} prompts  {:>5} sessions  {} – {}\n",
+            stats.user,
+            stats.entries,
+            stats.prompts,
+            stats.sessions,
+            stats.first_seen.format("%Y-%m-%d"),
+            stats.last_seen.format("%Y-%m-%d")
+        ));
+    }
+    out
 }
 
 #[cfg(test)]
@@ -101,6 +2956,7 @@ mod tests {
     use tempfile::TempDir;
 
     use super::*;
+    use crate::cache::FileMismatch;
 
     /// Helper to create a test .claude directory structure
     fn create_test_claude_dir() -> TempDir {
@@ -123,7 +2979,12 @@ mod tests {
 {"display":"Test prompt 2","timestamp":1234567891,"sessionId":"550e8400-e29b-41d4-a716-446655440001"}"#;
         write_history_file(claude_dir.path(), history_content);
 
-        let result = show_stats_impl(Some(claude_dir.path()));
+        let result = show_stats_impl(
+            Some(claude_dir.path()),
+            &IndexContext::in_memory(),
+            &mut IndexReport::default(),
+            true,
+        );
         assert!(result.is_ok());
     }
 
@@ -134,7 +2995,12 @@ mod tests {
         // Create empty history.jsonl
         write_history_file(claude_dir.path(), "");
 
-        let result = show_stats_impl(Some(claude_dir.path()));
+        let result = show_stats_impl(
+            Some(claude_dir.path()),
+            &IndexContext::in_memory(),
+            &mut IndexReport::default(),
+            true,
+        );
         assert!(result.is_ok());
     }
 
@@ -151,7 +3017,8 @@ mod tests {
             env::set_var("HOME", "/nonexistent/directory");
         }
 
-        let result = show_stats_impl(None);
+        let result =
+            show_stats_impl(None, &IndexContext::in_memory(), &mut IndexReport::default(), true);
         // Should propagate error from get_claude_dir or build_index
         // The exact error depends on whether .claude exists
 
@@ -176,15 +3043,15 @@ mod tests {
                 entry_type: EntryType::UserPrompt,
                 display_text: "Test 1".to_string(),
                 timestamp: Utc.timestamp_opt(1234567892, 0).unwrap(),
-                project_path: None,
                 session_id: "session1".to_string(),
+                ..Default::default()
             },
             crate::models::SearchEntry {
                 entry_type: EntryType::UserPrompt,
                 display_text: "Test 2".to_string(),
                 timestamp: Utc.timestamp_opt(1234567890, 0).unwrap(),
-                project_path: None,
                 session_id: "session2".to_string(),
+                ..Default::default()
             },
         ];
 
@@ -214,8 +3081,8 @@ mod tests {
             entry_type: EntryType::UserPrompt,
             display_text: "\x1b[31mRed text\x1b[0m with escape codes".to_string(),
             timestamp: Utc.timestamp_opt(1234567890, 0).unwrap(),
-            project_path: None,
             session_id: "session1".to_string(),
+            ..Default::default()
         }];
 
         let claude_dir = PathBuf::from("/Users/test/.claude");
@@ -236,16 +3103,16 @@ mod tests {
                 // Cursor movement: ESC[2J (clear screen), ESC[H (home)
                 display_text: "\x1b[2J\x1b[H Cleared screen".to_string(),
                 timestamp: Utc.timestamp_opt(1234567890, 0).unwrap(),
-                project_path: None,
                 session_id: "session1".to_string(),
+                ..Default::default()
             },
             crate::models::SearchEntry {
                 entry_type: EntryType::UserPrompt,
                 // Bell character
                 display_text: "Alert! \x07".to_string(),
                 timestamp: Utc.timestamp_opt(1234567891, 0).unwrap(),
-                project_path: None,
                 session_id: "session2".to_string(),
+                ..Default::default()
             },
         ];
 
@@ -263,8 +3130,8 @@ mod tests {
             entry_type: EntryType::UserPrompt,
             display_text: "Multi\nline\ntext\nwith\nnewlines".to_string(),
             timestamp: Utc.timestamp_opt(1234567890, 0).unwrap(),
-            project_path: None,
             session_id: "session1".to_string(),
+            ..Default::default()
         }];
 
         let claude_dir = PathBuf::from("/Users/test/.claude");
@@ -282,15 +3149,15 @@ mod tests {
                 entry_type: EntryType::UserPrompt,
                 display_text: "Hello 👋 World 🌍".to_string(),
                 timestamp: Utc.timestamp_opt(1234567890, 0).unwrap(),
-                project_path: None,
                 session_id: "session1".to_string(),
+                ..Default::default()
             },
             crate::models::SearchEntry {
                 entry_type: EntryType::UserPrompt,
                 display_text: "测试 中文 テスト العربية".to_string(),
                 timestamp: Utc.timestamp_opt(1234567891, 0).unwrap(),
-                project_path: None,
                 session_id: "session2".to_string(),
+                ..Default::default()
             },
         ];
 
@@ -309,8 +3176,8 @@ mod tests {
             // Zero-width joiner, zero-width non-joiner, zero-width space
             display_text: "Text\u{200D}with\u{200C}zero\u{200B}width".to_string(),
             timestamp: Utc.timestamp_opt(1234567890, 0).unwrap(),
-            project_path: None,
             session_id: "session1".to_string(),
+            ..Default::default()
         }];
 
         let claude_dir = PathBuf::from("/Users/test/.claude");
@@ -329,8 +3196,8 @@ mod tests {
             entry_type: EntryType::UserPrompt,
             display_text: long_text,
             timestamp: Utc.timestamp_opt(1234567890, 0).unwrap(),
-            project_path: None,
             session_id: "session1".to_string(),
+            ..Default::default()
         }];
 
         let claude_dir = PathBuf::from("/Users/test/.claude");
@@ -352,7 +3219,7 @@ mod tests {
             env::set_var("HOME", "/nonexistent/directory");
         }
 
-        let result = run_interactive();
+        let result = run_interactive(&IndexContext::in_memory(), &mut IndexReport::default(), None);
         // Should propagate error from get_claude_dir or build_index
 
         // Restore original HOME
@@ -370,10 +3237,709 @@ mod tests {
     #[test]
     fn test_cli_run_with_none_command() {
         // Test the None branch in the match statement
-        let cli = Cli { command: None };
+        let cli = Cli {
+            command: None,
+            claude_dirs: vec![],
+            codex_dir: None,
+            aider_histories: vec![],
+            transcript_dirs: vec![],
+            report: None,
+            max_tool_output: None,
+            oversized_tool_output: OversizedMode::Sample,
+            notify: NotifyMode::Bell,
+            notify_after: 5,
+            metrics_file: None,
+            verbose: false,
+            parse_memory_limit: None,
+            max_file_size: None,
+        };
 
         // Should just print help message (we can't easily test stdout in unit tests)
         // Just verify the struct can be created
         assert!(cli.command.is_none());
     }
+
+    #[test]
+    fn test_resolve_roots_parses_specs() {
+        let roots = resolve_roots(&[
+            "work=/mnt/work/.claude".to_string(),
+            "/mnt/laptop/.claude".to_string(),
+        ])
+        .unwrap();
+
+        assert_eq!(roots.len(), 2);
+        assert_eq!(roots[0].name, "work");
+        assert_eq!(roots[1].name, "laptop");
+    }
+
+    #[test]
+    fn test_show_workspace_stats_with_multiple_roots() {
+        let work = create_test_claude_dir();
+        let home = create_test_claude_dir();
+        write_history_file(
+            work.path(),
+            r#"{"display":"Work prompt","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000"}"#,
+        );
+        write_history_file(home.path(), "");
+
+        let roots = vec![
+            ClaudeRoot::new("work", work.path()),
+            ClaudeRoot::new("home", home.path()),
+            ClaudeRoot::new("gone", "/nonexistent/claude"),
+        ];
+
+        let mut report = IndexReport::default();
+        assert!(
+            show_workspace_stats(&roots, &IndexContext::in_memory(), &mut report, true).is_ok()
+        );
+        assert_eq!(report.status, IndexStatus::Partial);
+    }
+
+    #[test]
+    fn test_cli_parses_stats_tools() {
+        let cli =
+            Cli::try_parse_from(["ai-history-explorer", "stats", "--tools", "--top", "3"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Stats { tools: true, top: 3 })));
+    }
+
+    #[test]
+    fn test_format_tool_leaderboard() {
+        let record = |tool: &str, bytes| crate::analytics::ToolResultRecord {
+            tool: tool.to_string(),
+            project: Some(PathBuf::from("/work/app")),
+            session_id: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+            timestamp: chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            bytes,
+        };
+        let report = build_tool_output_report(vec![record("Bash", 4096), record("Read", 10)], 1);
+
+        let out = format_tool_leaderboard(&report);
+
+        assert!(out.contains("Tool results: 2  (4.0 KB total)"));
+        let largest = out.split("Most verbose tools:").next().unwrap();
+        assert!(largest.contains("Bash"));
+        assert!(!largest.contains("Read"));
+        assert!(out.contains("/work/app"));
+    }
+
+    #[test]
+    fn test_format_tool_leaderboard_empty() {
+        let out = format_tool_leaderboard(&ToolOutputReport::default());
+        assert!(out.contains("Tool results: 0"));
+        assert!(!out.contains("Largest results"));
+    }
+
+    #[test]
+    fn test_cli_parses_repeated_claude_dir() {
+        let cli = Cli::try_parse_from([
+            "ai-history-explorer",
+            "stats",
+            "--claude-dir",
+            "work=/a/.claude",
+            "--claude-dir",
+            "/b/.claude",
+        ])
+        .unwrap();
+
+        assert!(matches!(cli.command, Some(Commands::Stats { tools: false, .. })));
+        assert_eq!(cli.claude_dirs, vec!["work=/a/.claude", "/b/.claude"]);
+    }
+
+    #[test]
+    #[cfg(feature = "tui")]
+    fn test_export_keymap_to_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("keymap.md");
+
+        export_keymap(Some(&path), true).unwrap();
+        assert!(!path.exists());
+
+        export_keymap(Some(&path), false).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("# ai-history-explorer keymap"));
+        assert!(content.contains("`Ctrl+Y`"));
+    }
+
+    #[test]
+    fn test_export_keymap_unwritable_path() {
+        let result = export_keymap(Some(Path::new("/nonexistent/dir/keymap.md")), false);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cli_parses_keymap_export() {
+        let cli =
+            Cli::try_parse_from(["ai-history-explorer", "keymap", "export", "-o", "k.md"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Keymap {
+                command: KeymapCommand::Export { output: Some(_), dry_run: false }
+            })
+        ));
+        // Nothing to preview when printing to stdout
+        assert!(
+            Cli::try_parse_from(["ai-history-explorer", "keymap", "export", "--dry-run"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_format_usage_summary() {
+        use chrono::TimeZone;
+        let entry = |project: &str, entry_type, text: &str, secs| SearchEntry {
+            entry_type,
+            display_text: text.to_string(),
+            timestamp: chrono::Utc.timestamp_opt(secs, 0).unwrap(),
+            project_path: Some(PathBuf::from(project)),
+            session_id: "s1".to_string(),
+            ..Default::default()
+        };
+        let index = vec![
+            entry("/work/api", EntryType::UserPrompt, "fix the bug", 1_717_405_200),
+            entry("/work/api", EntryType::AgentMessage, "[Tool: Bash] Input: {}", 1_717_405_260),
+            entry("/work/web", EntryType::UserPrompt, "add a page", 1_718_010_000),
+        ];
+
+        let out = format_usage_summary(&UsageSummary::compute(&index, &chrono::Utc));
+
+        assert!(out.starts_with("Projects:\n"));
+        assert!(out.contains("       1 prompts        1 messages      1 sessions  /work/api\n"));
+        assert!(out.contains("Prompts per active day: 1.0 (2 days)\n"));
+        assert!(out.contains("  2024-W23      1  ████████████████████\n"));
+        assert!(out.contains("Busiest hours: 09:00 (2)\n"));
+        assert!(out.contains("Average prompt length: 10 characters\n"));
+        assert!(out.contains("Tool calls:\n        1  Bash\n"));
+    }
+
+    #[test]
+    fn test_format_user_breakdown() {
+        let stats = UserStats {
+            user: "alice@example.com".to_string(),
+            entries: 12,
+            prompts: 4,
+            sessions: 2,
+            first_seen: chrono::DateTime::from_timestamp_millis(1_704_099_600_000).unwrap(),
+            last_seen: chrono::DateTime::from_timestamp_millis(1_706_778_000_000).unwrap(),
+        };
+
+        let out = format_user_breakdown(&[stats]);
+
+        assert!(out.starts_with("Users:\n"));
+        assert!(out.contains("alice@example.com"));
+        assert!(out.contains("12 entries"));
+        assert!(out.contains("2024-01-01 – 2024-02-01"));
+    }
+
+    #[test]
+    fn test_format_outcome_breakdown() {
+        let breakdown = [
+            OutcomeStats { outcome: crate::models::SessionOutcome::Success, sessions: 3 },
+            OutcomeStats { outcome: crate::models::SessionOutcome::Abandoned, sessions: 1 },
+            OutcomeStats { outcome: crate::models::SessionOutcome::Unclear, sessions: 0 },
+        ];
+
+        let out = format_outcome_breakdown(&breakdown);
+
+        assert!(out.starts_with("Session outcomes"));
+        assert!(out.contains("  success         3 sessions   75%\n"));
+        assert!(out.contains("  abandoned       1 sessions   25%\n"));
+        assert!(out.contains("  unclear         0 sessions    0%\n"));
+    }
+
+    #[test]
+    fn test_cli_parses_export_html() {
+        let cli = Cli::try_parse_from([
+            "ai-history-explorer",
+            "export",
+            "html",
+            "--filter",
+            "type:user",
+            "-o",
+            "page.html",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::Export {
+                command:
+                    ExportCommand::Html { filter, session, title, output, attachments, dry_run },
+            }) => {
+                assert_eq!(filter.as_deref(), Some("type:user"));
+                assert_eq!(session, None);
+                assert!(!attachments && !dry_run);
+                assert_eq!(title, DEFAULT_HTML_TITLE);
+                assert_eq!(output, PathBuf::from("page.html"));
+            }
+            _ => panic!("expected export html"),
+        }
+    }
+
+    #[test]
+    fn test_export_html_rejects_bad_filter_before_indexing() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("page.html");
+        let mut report = IndexReport::default();
+
+        let result = export_html(
+            &[ClaudeRoot::new("default", temp_dir.path().join("missing"))],
+            &IndexContext::in_memory(),
+            Some("colour:red"),
+            DEFAULT_HTML_TITLE,
+            (&output, false),
+            &mut report,
+            false,
+        );
+
+        assert!(result.unwrap_err().to_string().contains("Unknown field"));
+        assert!(report.skipped.is_empty());
+        assert!(!output.exists());
+    }
+
+    #[test]
+    fn test_cli_parses_snapshot_restore() {
+        let cli = Cli::try_parse_from([
+            "ai-history-explorer",
+            "snapshot",
+            "restore",
+            "--archive",
+            "/backups",
+            "--to",
+            "restored",
+            "--dry-run",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::Snapshot {
+                archive,
+                dry_run,
+                command: Some(SnapshotCommand::Restore { date, to }),
+            }) => {
+                assert_eq!(archive, Some(PathBuf::from("/backups")));
+                assert!(dry_run);
+                assert_eq!(date, None);
+                assert_eq!(to, PathBuf::from("restored"));
+            }
+            _ => panic!("expected snapshot restore"),
+        }
+    }
+
+    #[cfg(feature = "snapshot")]
+    #[test]
+    fn test_root_archive_rejects_path_like_names() {
+        let archive = Path::new("/backups");
+        assert_eq!(
+            root_archive(archive, &ClaudeRoot::new("laptop", "/x")).unwrap(),
+            PathBuf::from("/backups/laptop")
+        );
+        assert!(root_archive(archive, &ClaudeRoot::new("../x", "/x")).is_err());
+        assert!(root_archive(archive, &ClaudeRoot::new("a/b", "/x")).is_err());
+    }
+
+    #[test]
+    fn test_resolve_project() {
+        let entry = |project: &str| SearchEntry {
+            entry_type: EntryType::UserPrompt,
+            display_text: "prompt".to_string(),
+            timestamp: chrono::Utc::now(),
+            project_path: Some(PathBuf::from(project)),
+            session_id: "s1".to_string(),
+            ..Default::default()
+        };
+        let entries = vec![entry("/work/app"), entry("/work/app-web"), entry("/work/lib")];
+
+        assert_eq!(resolve_project(&entries, "LIB").unwrap(), PathBuf::from("/work/lib"));
+        // An exact path wins over longer paths containing it
+        assert_eq!(resolve_project(&entries, "/work/app").unwrap(), PathBuf::from("/work/app"));
+
+        let ambiguous = resolve_project(&entries, "app").unwrap_err().to_string();
+        assert!(ambiguous.contains("matches 2 projects"));
+        assert!(ambiguous.contains("/work/app-web"));
+        assert!(resolve_project(&entries, "docs").is_err());
+    }
+
+    fn traced_conversation() -> Vec<ConversationEntry> {
+        let lines = [
+            r#"{"type":"user","message":{"role":"user","content":"List the files"},"timestamp":1704099600000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u1"}"#,
+            r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"Checking."},{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"ls"}}]},"timestamp":1704099601000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u2","parentUuid":"u1"}"#,
+            r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"a.rs\nb.rs"}]},"timestamp":1704099602000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u3","parentUuid":"u2"}"#,
+            r#"{"type":"assistant","message":{"role":"assistant","content":"Two files."},"timestamp":1704099603000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u4","parentUuid":"u3"}"#,
+        ];
+        lines.iter().map(|line| serde_json::from_str(line).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_format_trace_lists_ancestors_oldest_first() {
+        let entries = traced_conversation();
+        let relations = EntryRelations::from_entries(&entries);
+
+        let out = format_trace(&entries, &relations, "u4");
+
+        assert!(out.starts_with("Trace of u4 (4 messages, oldest first)\n"));
+        assert!(out.contains("  1. 2024-01-01 09:00:00  user       u1\n     List the files\n"));
+        assert!(out.contains("     [tool call Bash] {\"command\":\"ls\"}\n"));
+        assert!(out.contains("     [tool result] a.rs b.rs\n"));
+        assert!(out.find("u1").unwrap() < out.find("Two files.").unwrap());
+    }
+
+    #[test]
+    fn test_format_message_shows_relations() {
+        let entries = traced_conversation();
+        let relations = EntryRelations::from_entries(&entries);
+
+        let out = format_message(&entries, &relations, "u2", Path::new("/tmp/s1.jsonl"));
+
+        assert!(out.starts_with("Message u2\nRole:     assistant\n"));
+        assert!(out.contains("Parent:   u1\nReplies:  u3\n"));
+        assert!(out.ends_with("\nChecking.\n[tool call Bash] {\"command\":\"ls\"}\n"));
+    }
+
+    #[test]
+    fn test_explain_points_at_bad_clause_before_indexing() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut report = IndexReport::default();
+
+        let result = explain(
+            &[ClaudeRoot::new("default", temp_dir.path().join("missing"))],
+            &IndexContext::in_memory(),
+            "project:foo color:red",
+            &mut report,
+            false,
+        );
+
+        let message = format!("{:#}", result.unwrap_err());
+        assert!(message.contains("project:foo color:red\n              ^^^^^"));
+        assert!(message.contains("Unknown field: 'color'"));
+        assert!(report.skipped.is_empty());
+    }
+
+    #[test]
+    fn test_format_explanation() {
+        let mut entry = SearchEntry {
+            entry_type: EntryType::UserPrompt,
+            display_text: "prompt".to_string(),
+            timestamp: chrono::DateTime::from_timestamp_millis(1_704_099_600_000).unwrap(),
+            project_path: Some(PathBuf::from("/work/app")),
+            session_id: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+            ..Default::default()
+        };
+        let entries = vec![entry.clone(), {
+            entry.entry_type = EntryType::AgentMessage;
+            entry
+        }];
+        let filter = "project:app type:user OR since:2030-01-01";
+
+        let explanation = explain_filter(
+            &parse_filter(filter).unwrap(),
+            &tokenize(filter).unwrap(),
+            &entries,
+            &Bookmarks::default(),
+        )
+        .unwrap();
+        let out = format_explanation(filter, &explanation);
+
+        assert!(out.contains(r#"Parsed: (project:"app" AND type:"user") OR since:"2030-01-01""#));
+        assert!(out.contains("AND (implicit: different fields)"));
+        assert!(out.contains("     OR\n"));
+        assert!(out.contains("2. type:\"user\""));
+        assert!(out.contains("Whole filter: 1 of 2 entries"));
+        assert!(!out.contains("matches nothing"));
+    }
+
+    #[test]
+    fn test_cli_notify_flags() {
+        let cli = Cli::try_parse_from(["ai-history-explorer", "stats"]).unwrap();
+        assert_eq!(cli.notifier(), CompletionNotifier::default());
+
+        let cli = Cli::try_parse_from([
+            "ai-history-explorer",
+            "interactive",
+            "--notify",
+            "desktop",
+            "--notify-after",
+            "12",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.notifier(),
+            CompletionNotifier::new(NotifyMethod::Desktop, Duration::from_secs(12))
+        );
+    }
+
+    #[test]
+    fn test_cli_parses_cache_info() {
+        let cli = Cli::try_parse_from(["ai-history-explorer", "cache", "info"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Cache { command: CacheCommand::Info })));
+    }
+
+    #[test]
+    fn test_format_cache_verification() {
+        let consistent = CacheVerification { compared: 3, uncached: 1, ..Default::default() };
+        let text = format_cache_verification(&consistent, None);
+        assert!(text.contains("Compared:          3 files\n"));
+        assert!(text.contains("Not cached yet:    1\n"));
+        assert!(text.ends_with("The cache matches a fresh parse.\n"));
+
+        let uuids: Vec<String> = (0..7).map(|i| format!("u{}", i)).collect();
+        let mismatching = CacheVerification {
+            compared: 1,
+            mismatches: vec![FileMismatch {
+                path: PathBuf::from("/work/agent-1.jsonl"),
+                cached_entries: 2,
+                fresh_entries: 9,
+                missing_uuids: uuids,
+                extra_uuids: vec![],
+                changed_entries: 0,
+                counters_differ: true,
+            }],
+            ..Default::default()
+        };
+        let text = format_cache_verification(&mismatching, Some(Path::new("/c/index-v5.bin")));
+        assert!(text.contains("  /work/agent-1.jsonl: 2 cached, 9 fresh entries; 0 changed\n"));
+        assert!(text.contains("    missing: u0, u1, u2, u3, u4, ... (2 more)\n"));
+        assert!(!text.contains("extra:"));
+        assert!(text.contains("counts differ"));
+        assert!(text.ends_with("or delete /c/index-v5.bin.\n"));
+    }
+
+    #[test]
+    fn test_format_index_warnings_and_summary() {
+        let mut report =
+            IndexReport { entries_indexed: 4, agent_files_parsed: 2, ..Default::default() };
+        report.record_skip(Path::new("/c/projects/-p/agent-1.jsonl"), "parse error");
+        report.record_diagnostic(Path::new("/c/history.jsonl"), "line 3: expected value");
+        report.record_history_lines_skipped(1);
+
+        assert_eq!(
+            format_index_warnings(&report),
+            "Warning: /c/projects/-p/agent-1.jsonl: parse error\n\
+             Warning: /c/history.jsonl: line 3: expected value\n"
+        );
+        assert_eq!(
+            format_index_summary(&report),
+            "Indexed 4 entries (2 agent files parsed, 0 failed)\n\
+             Skipped 1 malformed history lines and 0 malformed agent file lines\n\
+             1 skipped, 1 diagnostics\n"
+        );
+    }
+
+    #[test]
+    fn test_format_cache_info_writable() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = CacheDir::resolve_from(
+            Some(temp_dir.path().join("cache")),
+            temp_dir.path().join("fallback"),
+        );
+        let cache = IndexCache::open(dir, ParseOptions::default());
+
+        let info = format_cache_info(&cache);
+
+        assert!(info.starts_with("Index cache\n"));
+        assert!(info.contains("(platform cache directory)"));
+        assert!(info.contains("Storage:      bincode\n"));
+        assert!(!info.contains("Problems:"));
+        if IndexCache::persistence_enabled() {
+            assert!(info.contains("State:        writable\n"));
+            assert!(info.contains("Cached files: 0"));
+        }
+    }
+
+    #[test]
+    fn test_format_cache_info_in_memory_lists_problems() {
+        let temp_dir = TempDir::new().unwrap();
+        let blocker = temp_dir.path().join("file");
+        fs::write(&blocker, "").unwrap();
+        let dir = CacheDir::resolve_from(Some(blocker.join("cache")), blocker.join("fallback"));
+        let cache = IndexCache::open(dir, ParseOptions::default());
+
+        let info = format_cache_info(&cache);
+
+        assert!(info.contains("Location:     in memory only\n"));
+        assert!(info.contains("State:        in memory only"));
+        assert!(info.contains("Problems:"));
+        assert!(!info.contains("Cached files"));
+    }
+
+    #[test]
+    fn test_show_stats_records_partial_report() {
+        let claude_dir = create_test_claude_dir();
+        write_history_file(
+            claude_dir.path(),
+            r#"{"display":"Test prompt","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000"}
+invalid line"#,
+        );
+
+        let mut report = IndexReport::default();
+        show_stats_impl(Some(claude_dir.path()), &IndexContext::in_memory(), &mut report, false)
+            .unwrap();
+
+        assert_eq!(report.status, IndexStatus::Partial);
+        assert_eq!(report.exit_code(), 3);
+        assert_eq!(report.history_lines_skipped, 1);
+    }
+
+    #[test]
+    fn test_cli_parses_report_format() {
+        let cli =
+            Cli::try_parse_from(["ai-history-explorer", "stats", "--report", "json"]).unwrap();
+        assert_eq!(cli.report, Some(ReportFormat::Json));
+        assert!(Cli::try_parse_from(["ai-history-explorer", "stats", "--report", "xml"]).is_err());
+    }
+
+    #[test]
+    fn test_format_highlights() {
+        let claude_dir = create_test_claude_dir();
+        write_history_file(
+            claude_dir.path(),
+            r#"{"display":"Short","timestamp":1704099600000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/tmp/project"}
+{"display":"A considerably longer prompt\nwith a second line","timestamp":1704103200000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/tmp/project"}"#,
+        );
+        let index =
+            crate::indexer::build_index_with_report(claude_dir.path(), &mut IndexReport::default())
+                .unwrap();
+
+        let output = format_highlights(&index, &sample_highlights(&index, 3));
+
+        assert!(output.contains("/tmp/project  (2024-W01)"));
+        assert!(output.contains("[first of day]  Short"));
+        assert!(output.contains("[longest prompt]  A considerably longer prompt\n"));
+        assert!(!output.contains("second line"));
+    }
+
+    #[test]
+    fn test_format_entry_list_and_json_results() {
+        let claude_dir = create_test_claude_dir();
+        write_history_file(
+            claude_dir.path(),
+            r#"{"display":"First prompt","timestamp":1704099600000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/tmp/project"}
+{"display":"Second prompt","timestamp":1704103200000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/tmp/project"}"#,
+        );
+        let index =
+            crate::indexer::build_index_with_report(claude_dir.path(), &mut IndexReport::default())
+                .unwrap();
+
+        let paths = crate::utils::PathDisplay::default();
+        let text = format_entry_list(&index, &paths, TimestampStyle::Absolute);
+        assert!(text.starts_with("Entries: 2 (newest first)\n"));
+        assert!(text.contains("2024-01-01 10:00  user  /tmp/project\n  Second prompt\n"));
+        let text = format_entry_list(&index, &paths, TimestampStyle::Relative);
+        assert!(text.contains("Jan 1, 2024  user  /tmp/project\n  Second prompt\n"));
+
+        let jsonl = format_json_results(&index, OutputFormat::Jsonl).unwrap();
+        let lines: Vec<serde_json::Value> =
+            jsonl.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["display_text"], "Second prompt");
+        assert_eq!(lines[0]["entry_type"], "UserPrompt");
+        assert_eq!(lines[0]["project_path"], "/tmp/project");
+        assert_eq!(lines[0]["session_id"], "550e8400-e29b-41d4-a716-446655440000");
+
+        let json: serde_json::Value =
+            serde_json::from_str(&format_json_results(&index, OutputFormat::Json).unwrap())
+                .unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 2);
+        assert_eq!(json[1]["display_text"], "First prompt");
+
+        assert_eq!(format_json_results::<SearchEntry>(&[], OutputFormat::Jsonl).unwrap(), "");
+        assert_eq!(format_json_results::<SearchEntry>(&[], OutputFormat::Json).unwrap(), "[]\n");
+
+        let csv = format_csv_results(&index);
+        assert_eq!(
+            csv.lines().take(2).collect::<Vec<_>>(),
+            [
+                "timestamp,type,project,session_id,text",
+                "2024-01-01 10:00:00,user,/tmp/project,550e8400-e29b-41d4-a716-446655440000,\
+                 Second prompt",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_csv_field_quotes_and_defuses_formulas() {
+        assert_eq!(csv_field("plain text"), "plain text");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\"\nthen"), "\"say \"\"hi\"\"\nthen\"");
+        assert_eq!(csv_field("=SUM(A1:A9)"), "'=SUM(A1:A9)");
+        assert_eq!(csv_field("-rf, really"), "\"'-rf, really\"");
+        assert_eq!(csv_field(""), "");
+    }
+
+    #[test]
+    fn test_cli_parses_list_output() {
+        let cli = Cli::try_parse_from([
+            "ai-history-explorer",
+            "list",
+            "--filter",
+            "type:user",
+            "--output",
+            "jsonl",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::List { filter, limit, cursor, output }) => {
+                assert_eq!(filter.as_deref(), Some("type:user"));
+                assert_eq!(limit, None);
+                assert_eq!(cursor, None);
+                assert_eq!(output, OutputFormat::Jsonl);
+            }
+            _ => panic!("expected list"),
+        }
+        assert!(Cli::try_parse_from(["ai-history-explorer", "list", "--output", "xml"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parses_dedupe() {
+        let cli = Cli::try_parse_from([
+            "ai-history-explorer",
+            "dedupe",
+            "--show-duplicates",
+            "--limit",
+            "10",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::Dedupe { filter, show_duplicates, limit, output }) => {
+                assert_eq!(filter, None);
+                assert!(show_duplicates);
+                assert_eq!(limit, Some(10));
+                assert_eq!(output, OutputFormat::Text);
+            }
+            _ => panic!("expected dedupe"),
+        }
+    }
+
+    #[test]
+    fn test_cli_parses_highlights() {
+        let cli = Cli::try_parse_from([
+            "ai-history-explorer",
+            "highlights",
+            "--per-group",
+            "5",
+            "--print",
+        ])
+        .unwrap();
+        assert!(matches!(cli.command, Some(Commands::Highlights { per_group: 5, print: true })));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_doctor_reports_and_fixes_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let claude_dir = create_test_claude_dir();
+        fs::set_permissions(claude_dir.path(), fs::Permissions::from_mode(0o700)).unwrap();
+        write_history_file(claude_dir.path(), "");
+        let history = claude_dir.path().join("history.jsonl");
+        fs::set_permissions(&history, fs::Permissions::from_mode(0o644)).unwrap();
+        let roots = vec![ClaudeRoot::new("default", claude_dir.path())];
+
+        assert!(!run_doctor(&roots, (false, false)).unwrap());
+        assert!(run_doctor(&roots, (true, false)).unwrap());
+        assert_eq!(fs::metadata(&history).unwrap().permissions().mode() & 0o777, 0o600);
+        assert!(run_doctor(&roots, (false, false)).unwrap());
+    }
+
+    #[test]
+    fn test_run_doctor_missing_root() {
+        let roots = vec![ClaudeRoot::new("gone", "/nonexistent/claude")];
+        assert!(!run_doctor(&roots, (true, false)).unwrap());
+    }
 }
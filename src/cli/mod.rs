@@ -1,3 +1,3 @@
 mod commands;
 
-pub use commands::{Cli, Commands, run};
+pub use commands::{Cli, Commands, KeymapCommand, ReportFormat, run};
@@ -1,19 +1,25 @@
-use anyhow::{Context, Result};
+#[cfg(feature = "clipboard")]
+use anyhow::Context;
+use anyhow::Result;
+#[cfg(feature = "clipboard")]
 use arboard::Clipboard;
 
 /// Maximum clipboard size (10MB) to prevent DoS attacks
 const MAX_CLIPBOARD_SIZE: usize = 10 * 1024 * 1024;
 
 /// Trait for clipboard operations (allows mocking in tests)
+#[cfg_attr(not(feature = "clipboard"), allow(dead_code))]
 trait ClipboardProvider {
     fn set_text(&mut self, text: &str) -> Result<()>;
 }
 
 /// Real clipboard implementation using arboard
+#[cfg(feature = "clipboard")]
 struct SystemClipboard {
     clipboard: Clipboard,
 }
 
+#[cfg(feature = "clipboard")]
 impl SystemClipboard {
     fn new() -> Result<Self> {
         let clipboard = Clipboard::new().context("Failed to initialize clipboard")?;
@@ -21,6 +27,7 @@ impl SystemClipboard {
     }
 }
 
+#[cfg(feature = "clipboard")]
 impl ClipboardProvider for SystemClipboard {
     fn set_text(&mut self, text: &str) -> Result<()> {
         self.clipboard.set_text(text).context("Failed to set clipboard contents")?;
@@ -74,6 +81,9 @@ fn copy_with_provider(text: &str, provider: &mut dyn ClipboardProvider) -> Resul
 /// - macOS: Primary support via pasteboard API
 /// - Linux: X11 (xclip/xsel) or Wayland (wl-clipboard)
 /// - Windows: Not officially supported in Phase 2
+///
+/// Without the `clipboard` feature this validates `text` and then always returns an error.
+#[cfg(feature = "clipboard")]
 pub fn copy_to_clipboard(text: &str) -> Result<()> {
     // Validate first, before initializing clipboard (for better error messages in CI)
     validate_clipboard_text(text)?;
@@ -84,6 +94,13 @@ pub fn copy_to_clipboard(text: &str) -> Result<()> {
     Ok(())
 }
 
+/// Copy text to the system clipboard (unavailable: built without the `clipboard` feature)
+#[cfg(not(feature = "clipboard"))]
+pub fn copy_to_clipboard(text: &str) -> Result<()> {
+    validate_clipboard_text(text)?;
+    anyhow::bail!("Failed to access clipboard: built without the `clipboard` feature")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,580 @@
+//! User configuration file.
+//!
+//! Read from `ai-history-explorer/config.json` in the platform config directory (e.g.
+//! `~/.config` on Linux). Every setting is optional; a missing file means the defaults.
+//!
+//! ```json
+//! {
+//!   "startup_view": "last_query",
+//!   "power_mode": "auto",
+//!   "path_display": "workspace",
+//!   "workspace_roots": [{ "path": "~/code" }, { "path": "~/src/github.com", "label": "oss" }],
+//!   "project_merges": { "/home/alice/code/app": "/home/alice/work/app" },
+//!   "codex_dir": "~/.codex",
+//!   "aider_histories": ["~/code/app/.aider.chat.history.md"],
+//!   "transcript_dirs": ["~/notes/chats"],
+//!   "claude_dirs": ["work=~/sync/work/.claude", "~/.claude"],
+//!   "max_file_size_mb": 64,
+//!   "default_filter": "type:user",
+//!   "saved_searches": { "api-prompts": "project:~/work/api type:user | retry" },
+//!   "search_history_size": 1000,
+//!   "sensitive_projects": ["~/work/client"],
+//!   "sensitive_passphrase": "demo-unlock",
+//!   "search_selection": "keep",
+//!   "theme": "light",
+//!   "list_density": "comfortable",
+//!   "match_columns": "all",
+//!   "timestamps": "absolute",
+//!   "timezone": "Europe/Berlin",
+//!   "list_columns": ["icon", "timestamp", "project:24", "session", "text"],
+//!   "index_storage": "sqlite",
+//!   "summarizer": { "command": ["ollama", "run", "llama3.2"] },
+//!   "embedding_model": "~/models/all-MiniLM-L6-v2",
+//!   "accent_color": "#3b82f6",
+//!   "theme_colors": { "matched": "#f97316" },
+//!   "keybindings": { "copy": ["ctrl+y", "alt+c"] }
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::cache::IndexStorage;
+use crate::indexer::{AiderSource, CodexSource, HistorySource, TranscriptSource};
+use crate::summarize::Summarizer;
+use crate::utils::{PathDisplay, TimestampStyle, WorkspaceRoot};
+
+/// Name of the config directory under the platform config directory
+pub const CONFIG_DIR_NAME: &str = "ai-history-explorer";
+
+/// Name of the config file inside [`CONFIG_DIR_NAME`]
+pub const CONFIG_FILE_NAME: &str = "config.json";
+
+/// Sentence-embedding model of `search --semantic` when `embedding_model` isn't set
+pub const DEFAULT_EMBEDDING_MODEL: &str = "sentence-transformers/all-MiniLM-L6-v2";
+
+/// Settings read from the config file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// What the interactive TUI shows when it opens
+    pub startup_view: StartupView,
+    /// How project paths are shown
+    pub path_display: PathDisplayMode,
+    /// Roots that `workspace` path display shows project paths relative to (`~` allowed)
+    pub workspace_roots: Vec<WorkspaceRoot>,
+    /// When the TUI saves power by waking up less often
+    pub power_mode: PowerMode,
+    /// Project paths shown as another project, e.g. the old path of a moved repository
+    /// (written by the `renames` command)
+    pub project_merges: BTreeMap<PathBuf, PathBuf>,
+    /// Codex CLI home indexed next to Claude Code (`~` allowed), e.g. `~/.codex`
+    pub codex_dir: Option<PathBuf>,
+    /// aider chat histories (`.aider.chat.history.md`) indexed next to Claude Code
+    pub aider_histories: Vec<PathBuf>,
+    /// Directories of Markdown chat transcripts (`### User` / `### Assistant`) indexed next
+    /// to Claude Code
+    pub transcript_dirs: Vec<PathBuf>,
+    /// Claude directories indexed when no `--claude-dir` is given, written like that flag
+    /// (`[name=]path`, `~` allowed); empty means `~/.claude`
+    pub claude_dirs: Vec<String>,
+    /// Largest history or conversation file read, in megabytes (default 10); only the most
+    /// recent entries of a larger history.jsonl are read
+    pub max_file_size_mb: Option<u64>,
+    /// Which result is selected while the search query changes
+    pub search_selection: SearchSelection,
+    /// Filter the interactive TUI starts with, e.g. `type:user`
+    pub default_filter: Option<String>,
+    /// Searches saved by name, as typed in the TUI's search box (`filter | text`); saved from
+    /// the TUI and opened with `interactive --preset NAME`
+    pub saved_searches: BTreeMap<String, String>,
+    /// Searches kept in the TUI's search history, recalled with Ctrl+P/Ctrl+N (default 500;
+    /// 0 keeps none)
+    pub search_history_size: Option<usize>,
+    /// Projects (and their subdirectories) whose entries the TUI redacts until revealed
+    /// (`~` allowed)
+    pub sensitive_projects: Vec<PathBuf>,
+    /// Passphrase asked for when revealing a sensitive project; kept as plain text, it only
+    /// keeps onlookers out
+    pub sensitive_passphrase: Option<String>,
+    /// Color palette of the TUI
+    pub theme: ThemeBase,
+    /// Lines per result in the TUI list (saved when cycled in the TUI)
+    pub list_density: ListDensity,
+    /// What the TUI's fuzzy query is matched against (saved when cycled in the TUI)
+    pub match_columns: MatchColumns,
+    /// How times are shown: in the TUI (relative unless set; saved when toggled in the TUI)
+    /// and in the text output of `list`, `search` and `dedupe` (absolute unless set)
+    pub timestamps: Option<TimestampStyle>,
+    /// Time zone times are shown in: `local` (the default), `utc` or an IANA name such as
+    /// `Europe/Berlin`
+    pub timezone: Option<String>,
+    /// Columns of the TUI's results, in order, each `name` or `name:width` (`icon`,
+    /// `timestamp`, `project`, `session`, `text`)
+    pub list_columns: Vec<String>,
+    /// How the index cache is stored; `sqlite` needs a build with the `sqlite` feature
+    pub index_storage: IndexStorage,
+    /// What writes the session summaries of the `summarize` command
+    pub summarizer: Option<Summarizer>,
+    /// BERT sentence-embedding model of `search --semantic`: a Hugging Face model id,
+    /// downloaded once into the Hugging Face cache, or a directory holding its `config.json`,
+    /// `tokenizer.json` and `model.safetensors` (`~` allowed)
+    pub embedding_model: Option<String>,
+    /// Accent color of the TUI (`#rrggbb`) instead of the theme's
+    pub accent_color: Option<String>,
+    /// Colors of the TUI (`#rrggbb`) instead of the theme's, by role (`text`, `muted`, ...)
+    pub theme_colors: BTreeMap<String, String>,
+    /// Keys bound to actions instead of the defaults, by action name (as listed by
+    /// `keymap export`)
+    pub keybindings: BTreeMap<String, Vec<String>>,
+}
+
+/// Color palette of the TUI
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeBase {
+    /// Light or dark, whichever suits the terminal's background (dark if it is unknown)
+    #[default]
+    Auto,
+    /// Light text, for terminals with a dark background
+    Dark,
+    /// Dark text, for terminals with a light background
+    Light,
+    /// Solarized colors on a dark background
+    SolarizedDark,
+    /// Solarized colors on a light background
+    SolarizedLight,
+}
+
+/// How many lines each result takes in the TUI list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ListDensity {
+    /// One line: time, project and the start of the text
+    #[default]
+    Compact,
+    /// The text on one line, time and project below it
+    Comfortable,
+    /// As comfortable, plus a line of tags (tools, errors, outcome) and more of the text
+    Detailed,
+}
+
+impl ListDensity {
+    /// Lines per result
+    pub fn lines(self) -> usize {
+        match self {
+            ListDensity::Compact => 1,
+            ListDensity::Comfortable => 2,
+            ListDensity::Detailed => 3,
+        }
+    }
+
+    /// The next denser mode, wrapping around to compact
+    pub fn next(self) -> Self {
+        match self {
+            ListDensity::Compact => ListDensity::Comfortable,
+            ListDensity::Comfortable => ListDensity::Detailed,
+            ListDensity::Detailed => ListDensity::Compact,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ListDensity::Compact => "compact",
+            ListDensity::Comfortable => "comfortable",
+            ListDensity::Detailed => "detailed",
+        }
+    }
+}
+
+/// Fields of an entry the TUI's fuzzy query is matched against
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchColumns {
+    /// The entry text only
+    #[default]
+    Text,
+    /// The text and the project path (`~`-formatted)
+    Project,
+    /// The text, the project path and the session id
+    All,
+}
+
+impl MatchColumns {
+    pub fn includes_project(self) -> bool {
+        self != MatchColumns::Text
+    }
+
+    pub fn includes_session(self) -> bool {
+        self == MatchColumns::All
+    }
+
+    /// The next setting, wrapping around to text only
+    pub fn next(self) -> Self {
+        match self {
+            MatchColumns::Text => MatchColumns::Project,
+            MatchColumns::Project => MatchColumns::All,
+            MatchColumns::All => MatchColumns::Text,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MatchColumns::Text => "text",
+            MatchColumns::Project => "text + project",
+            MatchColumns::All => "text + project + session",
+        }
+    }
+}
+
+/// Which result the TUI selects while the search query changes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchSelection {
+    /// The best match, following the results live as they narrow
+    #[default]
+    Top,
+    /// An entry picked with the arrow keys while it still matches, else the best match
+    Keep,
+}
+
+/// How project paths are shown in the TUI and exports
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PathDisplayMode {
+    /// Absolute paths, with the home directory shortened to `~`
+    #[default]
+    Tilde,
+    /// Paths relative to the matching workspace root, e.g. `work/api`
+    Workspace,
+}
+
+/// When the interactive TUI runs in low-power mode
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerMode {
+    /// Poll for input and redraw every 100ms
+    #[default]
+    Normal,
+    /// Always poll less often and redraw only on changes; no watch refreshes on battery
+    Low,
+    /// Low-power mode while running on battery (where that can be detected)
+    Auto,
+}
+
+/// What the interactive TUI opens into
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupView {
+    /// An empty search over all entries
+    #[default]
+    Blank,
+    /// The query and selected entry of the previous session (saved when the TUI exits)
+    LastQuery,
+    /// The list of sessions, most recently active first
+    Sessions,
+    /// The list of projects, most recently active first
+    Projects,
+}
+
+impl Config {
+    /// Path formatting selected by `path_display` and `workspace_roots`
+    pub fn path_display(&self) -> PathDisplay {
+        match self.path_display {
+            PathDisplayMode::Tilde => PathDisplay::default(),
+            PathDisplayMode::Workspace => {
+                let home = dirs::home_dir();
+                let roots = self
+                    .workspace_roots
+                    .iter()
+                    .map(|root| WorkspaceRoot {
+                        path: expand_home(&root.path, home.as_deref()),
+                        label: root.label.clone(),
+                    })
+                    .collect();
+                PathDisplay::workspace(roots)
+            }
+        }
+    }
+
+    /// Histories of other tools to index: `codex_dir`, `aider_histories` and `transcript_dirs`
+    pub fn history_sources(&self) -> Vec<Arc<dyn HistorySource>> {
+        let home = dirs::home_dir();
+        let mut sources: Vec<Arc<dyn HistorySource>> = Vec::new();
+        if let Some(dir) = &self.codex_dir {
+            sources.push(Arc::new(CodexSource { dir: expand_home(dir, home.as_deref()) }));
+        }
+        for file in &self.aider_histories {
+            sources.push(Arc::new(AiderSource { file: expand_home(file, home.as_deref()) }));
+        }
+        for dir in &self.transcript_dirs {
+            sources.push(Arc::new(TranscriptSource { dir: expand_home(dir, home.as_deref()) }));
+        }
+        sources
+    }
+
+    /// `sensitive_projects` with `~` expanded
+    pub fn sensitive_project_paths(&self) -> Vec<PathBuf> {
+        let home = dirs::home_dir();
+        self.sensitive_projects.iter().map(|path| expand_home(path, home.as_deref())).collect()
+    }
+
+    /// `claude_dirs` with `~` expanded, as `--claude-dir` specifications
+    pub fn claude_dir_specs(&self) -> Vec<String> {
+        let home = dirs::home_dir();
+        self.claude_dirs
+            .iter()
+            .map(|spec| match spec.split_once('=') {
+                Some((name, path)) => {
+                    let path = expand_home(Path::new(path.trim()), home.as_deref());
+                    format!("{}={}", name, path.display())
+                }
+                None => expand_home(Path::new(spec.trim()), home.as_deref()).display().to_string(),
+            })
+            .collect()
+    }
+
+    /// `embedding_model` with `~` expanded, or [`DEFAULT_EMBEDDING_MODEL`]
+    pub fn embedding_model(&self) -> String {
+        match &self.embedding_model {
+            Some(model) => {
+                expand_home(Path::new(model), dirs::home_dir().as_deref()).display().to_string()
+            }
+            None => DEFAULT_EMBEDDING_MODEL.to_string(),
+        }
+    }
+
+    /// Search saved as `name` in `saved_searches`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error listing the saved searches if there is none named `name`.
+    pub fn saved_search(&self, name: &str) -> Result<&str> {
+        match self.saved_searches.get(name) {
+            Some(query) => Ok(query),
+            None if self.saved_searches.is_empty() => {
+                bail!("No saved search named '{}' (none are saved yet; Alt+S saves one)", name)
+            }
+            None => bail!(
+                "No saved search named '{}' (saved: {})",
+                name,
+                self.saved_searches.keys().cloned().collect::<Vec<_>>().join(", ")
+            ),
+        }
+    }
+
+    /// Default location of the config file, if the platform has a config directory
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME))
+    }
+
+    /// Read the config file at its default location (defaults if there is none)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn load() -> Result<Self> {
+        match Self::default_path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Read the config file at `path`; a missing file gives the defaults
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", path.display()));
+            }
+        };
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Write the config file to `path`, creating its directory
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or file cannot be written.
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        std::fs::write(path, self.to_json()?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Contents of the config file, as written by [`save_to`](Self::save_to)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config cannot be serialized.
+    pub fn to_json(&self) -> Result<String> {
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize config")?;
+        Ok(json + "\n")
+    }
+}
+
+/// Replace a leading `~` component with `home`
+fn expand_home(path: &Path, home: Option<&Path>) -> PathBuf {
+    match (path.strip_prefix("~"), home) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SourceKind;
+
+    #[test]
+    fn test_missing_file_gives_defaults() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = Config::load_from(&dir.path().join(CONFIG_FILE_NAME)).unwrap();
+        assert_eq!(config.startup_view, StartupView::Blank);
+    }
+
+    #[test]
+    fn test_saved_search() {
+        let mut config = Config::default();
+        let err = config.saved_search("api").unwrap_err();
+        assert!(err.to_string().contains("none are saved yet"));
+
+        config.saved_searches.insert("api".to_string(), "project:api | retry".to_string());
+        config.saved_searches.insert("web".to_string(), "type:user".to_string());
+        assert_eq!(config.saved_search("api").unwrap(), "project:api | retry");
+        let err = config.saved_search("cli").unwrap_err();
+        assert_eq!(err.to_string(), "No saved search named 'cli' (saved: api, web)");
+    }
+
+    #[test]
+    fn test_workspace_path_display() {
+        let config: Config = serde_json::from_str(
+            r#"{"path_display":"workspace","workspace_roots":[{"path":"/work"},{"path":"/src","label":"oss"}]}"#,
+        )
+        .unwrap();
+
+        let display = config.path_display();
+        assert_eq!(display.format(Path::new("/work/api")), "api");
+        assert_eq!(display.format(Path::new("/src/ratatui")), "oss/ratatui");
+
+        let tilde = Config { path_display: PathDisplayMode::Tilde, ..config };
+        assert_eq!(tilde.path_display(), PathDisplay::default());
+    }
+
+    #[test]
+    fn test_save_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME);
+        let config = Config {
+            startup_view: StartupView::Projects,
+            project_merges: BTreeMap::from([(
+                PathBuf::from("/code/app"),
+                PathBuf::from("/work/app"),
+            )]),
+            ..Config::default()
+        };
+        config.save_to(&path).unwrap();
+        assert_eq!(Config::load_from(&path).unwrap(), config);
+    }
+
+    #[test]
+    fn test_expand_home() {
+        let home = Path::new("/home/alice");
+        assert_eq!(expand_home(Path::new("~/code"), Some(home)), PathBuf::from("/home/alice/code"));
+        assert_eq!(expand_home(Path::new("~"), Some(home)), PathBuf::from("/home/alice"));
+        assert_eq!(expand_home(Path::new("/abs/~"), Some(home)), PathBuf::from("/abs/~"));
+        assert_eq!(expand_home(Path::new("~/code"), None), PathBuf::from("~/code"));
+    }
+
+    #[test]
+    fn test_claude_dir_specs() {
+        let config: Config = serde_json::from_str(
+            r#"{"claude_dirs":["work=/sync/work/.claude","/home/a/.claude"]}"#,
+        )
+        .unwrap();
+        assert_eq!(config.claude_dir_specs(), vec!["work=/sync/work/.claude", "/home/a/.claude"]);
+        if let Some(home) = dirs::home_dir() {
+            let config = Config { claude_dirs: vec!["laptop=~/.claude".into()], ..config };
+            assert_eq!(
+                config.claude_dir_specs(),
+                vec![format!("laptop={}", home.join(".claude").display())]
+            );
+        }
+    }
+
+    #[test]
+    fn test_embedding_model() {
+        assert_eq!(Config::default().embedding_model(), DEFAULT_EMBEDDING_MODEL);
+        let config: Config =
+            serde_json::from_str(r#"{"embedding_model":"BAAI/bge-small-en-v1.5"}"#).unwrap();
+        assert_eq!(config.embedding_model(), "BAAI/bge-small-en-v1.5");
+        if let Some(home) = dirs::home_dir() {
+            let config = Config { embedding_model: Some("~/models/minilm".into()), ..config };
+            assert_eq!(config.embedding_model(), home.join("models/minilm").display().to_string());
+        }
+    }
+
+    #[test]
+    fn test_history_sources() {
+        let config: Config = serde_json::from_str(
+            r#"{"codex_dir":"/home/alice/.codex","aider_histories":["/a/.aider.chat.history.md"],"transcript_dirs":["/notes/chats"]}"#,
+        )
+        .unwrap();
+        let sources: Vec<_> = config
+            .history_sources()
+            .iter()
+            .map(|source| (source.kind(), source.location().to_path_buf()))
+            .collect();
+        assert_eq!(
+            sources,
+            vec![
+                (SourceKind::Codex, PathBuf::from("/home/alice/.codex")),
+                (SourceKind::Aider, PathBuf::from("/a/.aider.chat.history.md")),
+                (SourceKind::Transcript, PathBuf::from("/notes/chats")),
+            ]
+        );
+        assert!(Config::default().history_sources().is_empty());
+    }
+
+    #[test]
+    fn test_load_startup_view() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(CONFIG_FILE_NAME);
+
+        std::fs::write(&path, r#"{"startup_view":"last_query"}"#).unwrap();
+        assert_eq!(Config::load_from(&path).unwrap().startup_view, StartupView::LastQuery);
+
+        std::fs::write(&path, r#"{"search_selection":"keep"}"#).unwrap();
+        assert_eq!(Config::load_from(&path).unwrap().search_selection, SearchSelection::Keep);
+
+        std::fs::write(&path, r#"{"sensitive_projects":["/work/client"]}"#).unwrap();
+        let config = Config::load_from(&path).unwrap();
+        assert_eq!(config.sensitive_project_paths(), vec![PathBuf::from("/work/client")]);
+        assert_eq!(config.sensitive_passphrase, None);
+
+        std::fs::write(&path, r#"{"theme":"solarized_dark"}"#).unwrap();
+        assert_eq!(Config::load_from(&path).unwrap().theme, ThemeBase::SolarizedDark);
+
+        std::fs::write(&path, "{}").unwrap();
+        assert_eq!(Config::load_from(&path).unwrap(), Config::default());
+        assert_eq!(Config::default().theme, ThemeBase::Auto);
+
+        std::fs::write(&path, r#"{"startup_view":"sideways"}"#).unwrap();
+        let err = format!("{:#}", Config::load_from(&path).unwrap_err());
+        assert!(err.contains("Failed to parse"));
+        assert!(err.contains("unknown variant `sideways`"));
+    }
+}
@@ -0,0 +1,98 @@
+//! Typed errors of the library API
+//!
+//! The parsers ([`parse_history_file`](crate::parsers::parse_history_file) and friends),
+//! index building ([`build_index`](crate::build_index) and friends), project discovery and
+//! the index cache return an [`ExplorerError`], so callers can tell a missing Claude
+//! directory from a corrupt history file. Anything without a variant of its own, such as an
+//! I/O error with its context, is [`ExplorerError::Other`].
+//!
+//! Functions that combine several steps (multi-root workspaces, other tools' histories) and
+//! the CLI keep returning [`anyhow::Error`]; the `ExplorerError` of a failed step can be
+//! recovered from it with [`anyhow::Error::downcast_ref`].
+
+use std::path::{Path, PathBuf};
+
+/// `Result` with [`ExplorerError`] as the default error
+pub type Result<T, E = ExplorerError> = std::result::Result<T, E>;
+
+/// Why a library call failed
+#[derive(Debug, thiserror::Error)]
+pub enum ExplorerError {
+    /// The Claude directory to index doesn't exist
+    #[error("Claude directory not found: {}", .0.display())]
+    ClaudeDirNotFound(PathBuf),
+
+    /// A history or conversation file doesn't exist
+    #[error("Failed to open {}: file not found", .0.display())]
+    FileNotFound(PathBuf),
+
+    /// A file is larger than the size limit
+    /// ([`ParseOptions::max_file_size`](crate::parsers::ParseOptions::max_file_size))
+    #[error(
+        "File too large: {} ({size} bytes, max {limit} bytes; raise with --max-file-size)",
+        path.display()
+    )]
+    FileTooLarge { path: PathBuf, size: u64, limit: u64 },
+
+    /// Too many lines of a history or conversation file failed to parse for the rest to be
+    /// trusted
+    #[error("{reason}")]
+    CorruptFile { path: PathBuf, reason: String },
+
+    /// A Claude directory holds more projects or files than are indexed (a misconfiguration
+    /// or an attempt to exhaust resources)
+    #[error("Resource limit exceeded: {0}")]
+    ResourceLimitExceeded(String),
+
+    /// More than half of the agent files failed to parse
+    #[error(
+        "Index building failed: {failed}/{total} agent files failed to parse ({}% failure rate)",
+        failed * 100 / total
+    )]
+    IndexBuildFailed { failed: usize, total: usize },
+
+    /// The index cache file couldn't be written or deleted
+    #[error("Failed to write index cache {}", path.display())]
+    CacheWrite {
+        path: PathBuf,
+        #[source]
+        source: anyhow::Error,
+    },
+
+    /// Anything else, e.g. an I/O error with its context
+    #[error(transparent)]
+    Other(anyhow::Error),
+}
+
+impl ExplorerError {
+    pub(crate) fn corrupt_file(path: &Path, reason: String) -> Self {
+        ExplorerError::CorruptFile { path: path.to_path_buf(), reason }
+    }
+}
+
+impl From<anyhow::Error> for ExplorerError {
+    /// Helpers such as [`safe_open_file`](crate::utils::safe_open_file) return `anyhow` errors
+    /// that may wrap an `ExplorerError`; it is taken back out instead of being nested
+    fn from(error: anyhow::Error) -> Self {
+        error.downcast::<ExplorerError>().unwrap_or_else(ExplorerError::Other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+
+    use super::*;
+
+    #[test]
+    fn test_from_anyhow_unwraps_explorer_errors() {
+        let wrapped = anyhow::Error::new(ExplorerError::FileNotFound("/c/history.jsonl".into()));
+        assert!(matches!(ExplorerError::from(wrapped), ExplorerError::FileNotFound(_)));
+
+        let other: anyhow::Result<()> =
+            Err(std::io::Error::other("disk full")).context("Failed to read /c/history.jsonl");
+        let error = ExplorerError::from(other.unwrap_err());
+        assert!(matches!(error, ExplorerError::Other(_)));
+        assert_eq!(format!("{:#}", error), "Failed to read /c/history.jsonl: disk full");
+    }
+}
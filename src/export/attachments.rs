@@ -0,0 +1,330 @@
+//! Image attachments of exported exchanges.
+//!
+//! Parsed conversations keep only the size of inline image data (see
+//! [`crate::models::ImageSource`]), so an export that keeps its screenshots reads the images
+//! again from the conversation file. Each base64 image block of the exchange is decoded into
+//! an `attachments/` folder next to the output file, and the Markdown or HTML links to it.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde_json::Value;
+
+use super::exchange::Exchange;
+use crate::utils::{Plan, create_private_dir_all, safe_open_file, write_private_file};
+
+/// Folder the images are written to, next to the exported file
+pub const ATTACHMENTS_DIR_NAME: &str = "attachments";
+
+/// Relative links to the images written for an export
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Attachments {
+    /// Link by turn uuid and position of the image among the turn's images
+    links: HashMap<(String, usize), String>,
+    /// Images whose data could not be decoded
+    pub skipped: usize,
+}
+
+impl Attachments {
+    /// Link to the `nth` image of the turn with `uuid`, if it was written
+    pub fn link(&self, uuid: &str, nth: usize) -> Option<&str> {
+        self.links.get(&(uuid.to_string(), nth)).map(String::as_str)
+    }
+
+    /// Number of images written
+    pub fn len(&self) -> usize {
+        self.links.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    /// Count of images written, e.g. `1 image` or `3 images (1 unreadable image skipped)`
+    pub fn summary(&self) -> String {
+        let mut summary = images(self.len());
+        if self.skipped > 0 {
+            summary.push_str(&format!(
+                " ({} unreadable {} skipped)",
+                self.skipped,
+                noun(self.skipped)
+            ));
+        }
+        summary
+    }
+}
+
+fn images(count: usize) -> String {
+    format!("{} {}", count, noun(count))
+}
+
+fn noun(count: usize) -> &'static str {
+    if count == 1 { "image" } else { "images" }
+}
+
+/// Decode the images of `exchange` from `conversation_file` into `attachments/` next to
+/// `output`
+///
+/// Files are named after the output file (`notes.md` → `attachments/notes-1.png`), so exports
+/// written to the same folder don't overwrite each other's images. Images without inline
+/// base64 data (e.g. URL sources) are left as placeholders. A conversation file larger than
+/// `max_file_size` bytes is not read.
+///
+/// # Errors
+///
+/// Returns an error if the conversation file cannot be read or an image cannot be written.
+pub fn extract_attachments(
+    exchange: &Exchange,
+    conversation_file: &Path,
+    output: &Path,
+    max_file_size: u64,
+) -> Result<Attachments> {
+    attachments_impl(exchange, (conversation_file, max_file_size), output, None)
+}
+
+/// Like [`extract_attachments`], but records the images it would write in `plan` instead of
+/// writing them
+///
+/// # Errors
+///
+/// Returns an error if the conversation file cannot be read.
+pub fn plan_attachments(
+    exchange: &Exchange,
+    conversation_file: &Path,
+    output: &Path,
+    max_file_size: u64,
+    plan: &mut Plan,
+) -> Result<Attachments> {
+    attachments_impl(exchange, (conversation_file, max_file_size), output, Some(plan))
+}
+
+/// Write the images, or record them in `plan` when there is one
+fn attachments_impl(
+    exchange: &Exchange,
+    (conversation_file, max_file_size): (&Path, u64),
+    output: &Path,
+    mut plan: Option<&mut Plan>,
+) -> Result<Attachments> {
+    let images = read_images(exchange, conversation_file, max_file_size)?;
+    let mut attachments = Attachments::default();
+    if images.is_empty() {
+        return Ok(attachments);
+    }
+
+    let dir = output.parent().unwrap_or(Path::new("")).join(ATTACHMENTS_DIR_NAME);
+    if plan.is_none() {
+        create_private_dir_all(&dir)?;
+    }
+    let stem = file_stem(output);
+    for (n, image) in images.into_iter().enumerate() {
+        let Ok(bytes) = STANDARD.decode(image.data.trim()) else {
+            attachments.skipped += 1;
+            continue;
+        };
+        let name = format!("{}-{}.{}", stem, n + 1, extension(image.media_type.as_deref()));
+        match plan.as_deref_mut() {
+            Some(plan) => {
+                plan.write_file(&dir.join(&name), bytes.len() as u64).note("image attachment");
+            }
+            None => write_private_file(&dir.join(&name), bytes)?,
+        }
+        attachments
+            .links
+            .insert((image.uuid, image.nth), format!("{}/{}", ATTACHMENTS_DIR_NAME, name));
+    }
+    Ok(attachments)
+}
+
+/// Base64 image of one turn
+struct RawImage {
+    uuid: String,
+    /// Position among the turn's images
+    nth: usize,
+    media_type: Option<String>,
+    data: String,
+}
+
+/// Base64 images of the exchange's turns, in file order
+fn read_images(
+    exchange: &Exchange,
+    conversation_file: &Path,
+    max_file_size: u64,
+) -> Result<Vec<RawImage>> {
+    let uuids: HashSet<&str> = exchange.turns.iter().map(|turn| turn.uuid.as_str()).collect();
+    let file = safe_open_file(conversation_file, max_file_size)?;
+
+    let mut images = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line =
+            line.with_context(|| format!("Failed to read {}", conversation_file.display()))?;
+        // Skip parsing lines that can't hold an image of the exchange
+        if !line.contains("\"image\"") || !uuids.iter().any(|uuid| line.contains(uuid)) {
+            continue;
+        }
+        let Ok(value) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        let Some(uuid) = value["uuid"].as_str().filter(|uuid| uuids.contains(uuid)) else {
+            continue;
+        };
+        let Some(blocks) = value["message"]["content"].as_array() else {
+            continue;
+        };
+        let turn_images = blocks.iter().filter(|block| block["type"] == "image");
+        for (nth, block) in turn_images.enumerate() {
+            let source = &block["source"];
+            if let Some(data) = source["data"].as_str().filter(|_| source["type"] == "base64") {
+                images.push(RawImage {
+                    uuid: uuid.to_string(),
+                    nth,
+                    media_type: source["media_type"].as_str().map(str::to_string),
+                    data: data.to_string(),
+                });
+            }
+        }
+    }
+    Ok(images)
+}
+
+/// File extension for an image media type
+fn extension(media_type: Option<&str>) -> &'static str {
+    match media_type {
+        Some("image/png") => "png",
+        Some("image/jpeg") => "jpg",
+        Some("image/gif") => "gif",
+        Some("image/webp") => "webp",
+        _ => "bin",
+    }
+}
+
+/// Stem of `output` with characters that need escaping in links replaced by `_`
+fn file_stem(output: &Path) -> String {
+    let stem = output.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+    let stem: String = stem
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' })
+        .collect();
+    if stem.is_empty() { "export".to_string() } else { stem }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use chrono::DateTime;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::export::extract_exchange;
+    use crate::parsers::parse_conversation_file;
+    use crate::utils::DEFAULT_MAX_FILE_SIZE_BYTES;
+
+    const SESSION: &str = "550e8400-e29b-41d4-a716-446655440000";
+    // "PNG" and "GIF" as base64
+    const PNG_DATA: &str = "UE5H";
+    const GIF_DATA: &str = "R0lG";
+
+    fn write_conversation(temp: &TempDir) -> std::path::PathBuf {
+        let path = temp.path().join("session.jsonl");
+        let lines = [
+            format!(
+                r#"{{"type":"user","message":{{"role":"user","content":[{{"type":"text","text":"see screenshot"}},{{"type":"image","source":{{"type":"base64","media_type":"image/png","data":"{PNG_DATA}"}}}},{{"type":"image","source":{{"type":"url","url":"https://example.com/a.png"}}}},{{"type":"image","source":{{"type":"base64","media_type":"image/gif","data":"{GIF_DATA}"}},"alt_text":"diff"}}]}},"timestamp":1000,"sessionId":"{SESSION}","uuid":"u1"}}"#
+            ),
+            format!(
+                r#"{{"type":"assistant","message":{{"role":"assistant","content":[{{"type":"text","text":"looks fine"}}]}},"timestamp":2000,"sessionId":"{SESSION}","uuid":"u2"}}"#
+            ),
+            format!(
+                r#"{{"type":"user","message":{{"role":"user","content":[{{"type":"image","source":{{"type":"base64","media_type":"image/png","data":"{PNG_DATA}"}}}}]}},"timestamp":3000,"sessionId":"{SESSION}","uuid":"u3"}}"#
+            ),
+        ];
+        fs::write(&path, lines.join("\n")).unwrap();
+        path
+    }
+
+    fn exchange(conversation: &Path) -> Exchange {
+        let turns = parse_conversation_file(conversation).unwrap();
+        extract_exchange(&turns, DateTime::from_timestamp_millis(1000).unwrap(), None).unwrap()
+    }
+
+    #[test]
+    fn test_extracts_base64_images_of_the_exchange() {
+        let temp = TempDir::new().unwrap();
+        let conversation = write_conversation(&temp);
+        let output = temp.path().join("out").join("my notes.md");
+        fs::create_dir_all(output.parent().unwrap()).unwrap();
+
+        let attachments = extract_attachments(
+            &exchange(&conversation),
+            &conversation,
+            &output,
+            DEFAULT_MAX_FILE_SIZE_BYTES,
+        )
+        .unwrap();
+
+        assert_eq!(attachments.len(), 2);
+        assert_eq!(attachments.link("u1", 0), Some("attachments/my_notes-1.png"));
+        assert_eq!(attachments.link("u1", 1), None);
+        assert_eq!(attachments.link("u1", 2), Some("attachments/my_notes-2.gif"));
+        let dir = temp.path().join("out").join(ATTACHMENTS_DIR_NAME);
+        assert_eq!(fs::read(dir.join("my_notes-1.png")).unwrap(), b"PNG");
+        assert_eq!(fs::read(dir.join("my_notes-2.gif")).unwrap(), b"GIF");
+        // The next prompt's image belongs to another exchange
+        assert!(!dir.join("my_notes-3.png").exists());
+
+        let markdown = exchange(&conversation)
+            .to_markdown_with_attachments(&crate::utils::PathDisplay::default(), &attachments);
+        assert!(markdown.contains("![image](attachments/my_notes-1.png)"));
+        assert!(markdown.contains("_[image]_"));
+        assert!(markdown.contains("![diff](attachments/my_notes-2.gif)"));
+    }
+
+    #[test]
+    fn test_invalid_data_is_skipped() {
+        let temp = TempDir::new().unwrap();
+        let conversation = temp.path().join("session.jsonl");
+        fs::write(
+            &conversation,
+            format!(
+                r#"{{"type":"user","message":{{"role":"user","content":[{{"type":"image","source":{{"type":"base64","media_type":"image/png","data":"not base64!"}}}}]}},"timestamp":1000,"sessionId":"{SESSION}","uuid":"u1"}}"#
+            ),
+        )
+        .unwrap();
+
+        let output = temp.path().join("x.md");
+        let attachments = extract_attachments(
+            &exchange(&conversation),
+            &conversation,
+            &output,
+            DEFAULT_MAX_FILE_SIZE_BYTES,
+        )
+        .unwrap();
+
+        assert!(attachments.is_empty());
+        assert_eq!(attachments.skipped, 1);
+        assert_eq!(attachments.summary(), "0 images (1 unreadable image skipped)");
+    }
+
+    #[test]
+    fn test_plan_writes_nothing_and_oversized_files_are_refused() {
+        let temp = TempDir::new().unwrap();
+        let conversation = write_conversation(&temp);
+        let output = temp.path().join("notes.md");
+
+        let mut plan = Plan::default();
+        let attachments =
+            plan_attachments(&exchange(&conversation), &conversation, &output, 1 << 20, &mut plan)
+                .unwrap();
+        assert_eq!(attachments.len(), 2);
+        assert_eq!(attachments.summary(), "2 images");
+        assert_eq!(plan.bytes_written(), 6);
+        assert!(!temp.path().join(ATTACHMENTS_DIR_NAME).exists());
+
+        let err =
+            extract_attachments(&exchange(&conversation), &conversation, &output, 16).unwrap_err();
+        assert!(format!("{:#}", err).contains("File too large"));
+    }
+}
@@ -0,0 +1,141 @@
+//! Reproduction context of a prompt.
+//!
+//! Referencing agent work in a bug tracker needs what was asked and where: the prompt, the
+//! project, the git branch, the model and when. [`ReproContext`] collects those for an entry
+//! and renders them as a short Markdown snippet. Branch and model are only recorded in
+//! conversation files, so they are unknown for prompts whose session has none.
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+
+use super::exchange::{extract_exchange, is_prompt, message_text};
+use crate::models::{ConversationEntry, EntryType, SearchEntry};
+use crate::utils::PathDisplay;
+
+/// Where and when a prompt was given
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReproContext {
+    pub prompt: String,
+    pub project: Option<PathBuf>,
+    pub branch: Option<String>,
+    pub model: Option<String>,
+    pub timestamp: DateTime<Utc>,
+    pub session_id: String,
+}
+
+impl ReproContext {
+    /// Context of `entry`, completed from the conversation of its session if available
+    ///
+    /// For an agent message the prompt is the one it answers. The branch is the one recorded
+    /// with the prompt and the model the first one that answered it.
+    pub fn for_entry(entry: &SearchEntry, conversation: Option<&[ConversationEntry]>) -> Self {
+        let exchange = conversation.and_then(|conversation| {
+            extract_exchange(conversation, entry.timestamp, entry.project_path.as_deref())
+        });
+        let turns = exchange.as_ref().map_or(&[][..], |exchange| &exchange.turns[..]);
+        let prompt_turn = turns.first().filter(|turn| is_prompt(turn));
+
+        let (prompt, timestamp) = match (&entry.entry_type, prompt_turn) {
+            (EntryType::AgentMessage, Some(turn)) => (message_text(turn), turn.timestamp),
+            _ => (entry.display_text.clone(), entry.timestamp),
+        };
+        Self {
+            prompt,
+            project: entry.project_path.clone(),
+            branch: turns.iter().find_map(|turn| turn.git_branch.clone()),
+            model: turns.iter().find_map(|turn| turn.message.model.clone()),
+            timestamp,
+            session_id: entry.session_id.clone(),
+        }
+    }
+
+    /// The prompt as a block quote followed by a list of the details that are known
+    pub fn to_markdown(&self, paths: &PathDisplay) -> String {
+        let mut out = String::new();
+        for line in self.prompt.trim().lines() {
+            out.push_str(if line.is_empty() { ">" } else { "> " });
+            out.push_str(line);
+            out.push('\n');
+        }
+        out.push('\n');
+        if let Some(project) = &self.project {
+            out.push_str(&format!("- Project: `{}`\n", paths.format_full(project)));
+        }
+        if let Some(branch) = &self.branch {
+            out.push_str(&format!("- Branch: `{}`\n", branch));
+        }
+        if let Some(model) = &self.model {
+            out.push_str(&format!("- Model: `{}`\n", model));
+        }
+        out.push_str(&format!("- Time: {}\n", self.timestamp.format("%Y-%m-%d %H:%M:%S UTC")));
+        out.push_str(&format!("- Session: `{}`\n", self.session_id));
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION: &str = "550e8400-e29b-41d4-a716-446655440000";
+
+    fn turn(line: &str) -> ConversationEntry {
+        serde_json::from_str(line).unwrap()
+    }
+
+    fn conversation() -> Vec<ConversationEntry> {
+        vec![
+            turn(&format!(
+                r#"{{"type":"user","message":{{"role":"user","content":"Fix the login form\n\nIt 500s"}},"timestamp":1000,"sessionId":"{SESSION}","uuid":"u1","gitBranch":"fix/login"}}"#
+            )),
+            turn(&format!(
+                r#"{{"type":"assistant","message":{{"role":"assistant","model":"claude-sonnet-4-5","content":[{{"type":"text","text":"Fixed"}}]}},"timestamp":2000,"sessionId":"{SESSION}","uuid":"u2","gitBranch":"fix/login"}}"#
+            )),
+        ]
+    }
+
+    fn entry(entry_type: EntryType, text: &str, timestamp_ms: i64) -> SearchEntry {
+        SearchEntry {
+            entry_type,
+            display_text: text.to_string(),
+            timestamp: DateTime::from_timestamp_millis(timestamp_ms).unwrap(),
+            project_path: Some(PathBuf::from("/work/app")),
+            session_id: SESSION.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_agent_message_refers_to_its_prompt() {
+        let context = ReproContext::for_entry(
+            &entry(EntryType::AgentMessage, "Fixed", 2000),
+            Some(&conversation()),
+        );
+        assert_eq!(context.prompt, "Fix the login form\n\nIt 500s");
+        assert_eq!(context.timestamp.timestamp_millis(), 1000);
+        assert_eq!(context.branch.as_deref(), Some("fix/login"));
+        assert_eq!(context.model.as_deref(), Some("claude-sonnet-4-5"));
+
+        assert_eq!(
+            context.to_markdown(&PathDisplay::default()),
+            format!(
+                "> Fix the login form\n>\n> It 500s\n\n- Project: `/work/app`\n\
+                 - Branch: `fix/login`\n- Model: `claude-sonnet-4-5`\n\
+                 - Time: 1970-01-01 00:00:01 UTC\n- Session: `{SESSION}`\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_without_conversation_details_are_left_out() {
+        let context =
+            ReproContext::for_entry(&entry(EntryType::UserPrompt, "Add tests", 5000), None);
+        assert_eq!(context.prompt, "Add tests");
+        assert_eq!(context.branch, None);
+
+        let markdown = context.to_markdown(&PathDisplay::default());
+        assert!(markdown.starts_with("> Add tests\n\n- Project: `/work/app`\n- Time: "));
+        assert!(!markdown.contains("Branch"));
+    }
+}
@@ -0,0 +1,395 @@
+//! Exchange extraction and rendering.
+//!
+//! An exchange is one user prompt plus every assistant and tool turn that follows it, up to
+//! (not including) the next user prompt. User-role messages that only carry tool results are
+//! part of the exchange, not the start of a new one.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::attachments::Attachments;
+use crate::models::{ContentBlock, ConversationEntry, MessageContent};
+use crate::utils::PathDisplay;
+
+/// Output format of an exported exchange
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    #[default]
+    Markdown,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn label(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "Markdown",
+            ExportFormat::Json => "JSON",
+        }
+    }
+
+    pub fn file_extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Json => "json",
+        }
+    }
+
+    /// The other format
+    pub fn toggle(self) -> Self {
+        match self {
+            ExportFormat::Markdown => ExportFormat::Json,
+            ExportFormat::Json => ExportFormat::Markdown,
+        }
+    }
+}
+
+/// A user prompt and the turns answering it, or a whole session ([`extract_session`])
+#[derive(Debug, Clone, Serialize)]
+pub struct Exchange {
+    pub session_id: String,
+    pub project: Option<PathBuf>,
+    /// Prompt first, then assistant/tool turns in file order
+    pub turns: Vec<ConversationEntry>,
+}
+
+impl Exchange {
+    /// Timestamp of the prompt that opens the exchange
+    pub fn started_at(&self) -> Option<DateTime<Utc>> {
+        self.turns.first().map(|turn| turn.timestamp)
+    }
+
+    /// Render in the given format; `paths` formats the project in Markdown
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if JSON serialization fails.
+    pub fn render(&self, format: ExportFormat, paths: &PathDisplay) -> Result<String> {
+        match format {
+            ExportFormat::Markdown => Ok(self.to_markdown(paths)),
+            ExportFormat::Json => {
+                serde_json::to_string_pretty(self).context("Failed to serialize exchange")
+            }
+        }
+    }
+
+    /// Render as Markdown: one section per turn, tool calls and results as fenced blocks
+    ///
+    /// Thinking blocks are left out; they are rarely wanted in a shared transcript.
+    pub fn to_markdown(&self, paths: &PathDisplay) -> String {
+        self.to_markdown_with_attachments(paths, &Attachments::default())
+    }
+
+    /// Render as Markdown, linking images that were written by
+    /// [`extract_attachments`](super::extract_attachments)
+    pub fn to_markdown_with_attachments(
+        &self,
+        paths: &PathDisplay,
+        attachments: &Attachments,
+    ) -> String {
+        let title = if self.turns.iter().filter(|turn| is_prompt(turn)).count() > 1 {
+            "Session"
+        } else {
+            "Exchange"
+        };
+        let mut out = format!("# {}\n\n", title);
+        if let Some(project) = &self.project {
+            out.push_str(&format!("- Project: `{}`\n", paths.format_full(project)));
+        }
+        out.push_str(&format!("- Session: `{}`\n", self.session_id));
+        if let Some(started) = self.started_at() {
+            out.push_str(&format!("- Started: {}\n", started.format("%Y-%m-%d %H:%M:%S UTC")));
+        }
+
+        for turn in &self.turns {
+            let heading = match turn.message.role.as_str() {
+                "user" if is_prompt(turn) => "User",
+                "user" => "Tool results",
+                "assistant" => "Assistant",
+                other => other,
+            };
+            out.push_str(&format!("\n## {} ({})\n\n", heading, turn.timestamp.format("%H:%M:%S")));
+            match &turn.message.content {
+                MessageContent::String(text) => push_paragraph(&mut out, text),
+                MessageContent::Array(blocks) => {
+                    let mut images = 0;
+                    for block in blocks {
+                        if let ContentBlock::Image { alt_text, .. } = block {
+                            let link = attachments.link(&turn.uuid, images);
+                            images += 1;
+                            if let Some(link) = link {
+                                let alt = alt_text.as_deref().unwrap_or("image");
+                                out.push_str(&format!("![{}]({})\n\n", alt, link));
+                                continue;
+                            }
+                        }
+                        push_block(&mut out, block);
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// Number of image blocks with inline data, which can be saved as attachments
+    pub fn image_count(&self) -> usize {
+        self.turns
+            .iter()
+            .filter_map(|turn| match &turn.message.content {
+                MessageContent::Array(blocks) => Some(blocks),
+                MessageContent::String(_) => None,
+            })
+            .flatten()
+            .filter(|block| {
+                matches!(block, ContentBlock::Image { source, .. } if source.data_bytes > 0)
+            })
+            .count()
+    }
+}
+
+/// Extract the exchange containing the entry at `timestamp` from a parsed conversation
+///
+/// The entry is matched by timestamp (the latest entry at or before it if there is no exact
+/// match). Returns `None` if the conversation has no entries at or before `timestamp`.
+pub fn extract_exchange(
+    conversation: &[ConversationEntry],
+    timestamp: DateTime<Utc>,
+    project: Option<&Path>,
+) -> Option<Exchange> {
+    let position = conversation
+        .iter()
+        .position(|e| e.timestamp == timestamp)
+        .or_else(|| conversation.iter().rposition(|e| e.timestamp <= timestamp))?;
+
+    // Walk back to the prompt; a conversation starting mid-exchange begins at its first entry
+    let start = conversation[..=position].iter().rposition(is_prompt).unwrap_or(0);
+    let end = conversation[start + 1..]
+        .iter()
+        .position(is_prompt)
+        .map_or(conversation.len(), |offset| start + 1 + offset);
+
+    let turns = conversation[start..end].to_vec();
+    Some(Exchange {
+        session_id: turns[0].session_id.clone(),
+        project: project.map(Path::to_path_buf),
+        turns,
+    })
+}
+
+/// Every turn of a parsed conversation, as one exchange spanning all its prompts
+///
+/// Returns `None` if the conversation is empty.
+pub fn extract_session(
+    conversation: &[ConversationEntry],
+    project: Option<&Path>,
+) -> Option<Exchange> {
+    let first = conversation.first()?;
+    Some(Exchange {
+        session_id: first.session_id.clone(),
+        project: project.map(Path::to_path_buf),
+        turns: conversation.to_vec(),
+    })
+}
+
+/// Whether an entry is a user prompt rather than a user-role message carrying tool results
+pub(super) fn is_prompt(entry: &ConversationEntry) -> bool {
+    if entry.message.role != "user" {
+        return false;
+    }
+    match &entry.message.content {
+        MessageContent::String(_) => true,
+        MessageContent::Array(blocks) => {
+            !blocks.iter().any(|b| matches!(b, ContentBlock::ToolResult { .. }))
+        }
+    }
+}
+
+/// Text blocks of a message, joined
+pub(super) fn message_text(entry: &ConversationEntry) -> String {
+    match &entry.message.content {
+        MessageContent::String(text) => text.clone(),
+        MessageContent::Array(blocks) => blocks
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+fn push_paragraph(out: &mut String, text: &str) {
+    out.push_str(text.trim_end());
+    out.push_str("\n\n");
+}
+
+/// Fence `body` with enough backticks that fences inside it can't close the block
+fn push_fenced(out: &mut String, language: &str, body: &str) {
+    let longest_run = body
+        .lines()
+        .map(|line| line.trim_start().chars().take_while(|c| *c == '`').count())
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    out.push_str(&format!("{}{}\n{}\n{}\n\n", fence, language, body.trim_end(), fence));
+}
+
+fn push_block(out: &mut String, block: &ContentBlock) {
+    match block {
+        ContentBlock::Text { text } => push_paragraph(out, text),
+        ContentBlock::Thinking { .. } => {}
+        ContentBlock::ToolUse { name, input, .. } => {
+            out.push_str(&format!("**Tool call:** `{}`\n\n", name));
+            let input = serde_json::to_string_pretty(input).unwrap_or_default();
+            push_fenced(out, "json", &input);
+        }
+        ContentBlock::ToolResult { content, is_error, .. } => {
+            let label = if *is_error == Some(true) { "Tool error" } else { "Tool result" };
+            out.push_str(&format!("**{}:**\n\n", label));
+            push_fenced(out, "", &tool_result_text(content));
+        }
+        ContentBlock::Image { alt_text, .. } => {
+            out.push_str(&format!("_[image{}]_\n\n", alt_text_suffix(alt_text.as_deref())));
+        }
+    }
+}
+
+fn alt_text_suffix(alt_text: Option<&str>) -> String {
+    alt_text.map(|alt| format!(": {}", alt)).unwrap_or_default()
+}
+
+/// Plain text of a tool result: strings and text blocks verbatim, anything else as JSON
+pub(crate) fn tool_result_text(content: &serde_json::Value) -> String {
+    match content {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Array(items) => {
+            items.iter().map(tool_result_text).collect::<Vec<_>>().join("\n")
+        }
+        serde_json::Value::Object(map) => match map.get("text") {
+            Some(serde_json::Value::String(text)) => text.clone(),
+            _ => serde_json::to_string_pretty(content).unwrap_or_default(),
+        },
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION: &str = "550e8400-e29b-41d4-a716-446655440000";
+
+    fn entry(role: &str, content: &str, timestamp_ms: i64) -> ConversationEntry {
+        let line = format!(
+            r#"{{"type":"{role}","message":{{"role":"{role}","content":{content}}},"timestamp":{timestamp_ms},"sessionId":"{SESSION}","uuid":"u{timestamp_ms}"}}"#
+        );
+        serde_json::from_str(&line).unwrap()
+    }
+
+    fn ts(timestamp_ms: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(timestamp_ms).unwrap()
+    }
+
+    fn conversation() -> Vec<ConversationEntry> {
+        vec![
+            entry("user", r#""first prompt""#, 1_000),
+            entry("assistant", r#"[{"type":"text","text":"first answer"}]"#, 2_000),
+            entry("user", r#"[{"type":"text","text":"second prompt"}]"#, 3_000),
+            entry(
+                "assistant",
+                r#"[{"type":"thinking","thinking":"hmm"},{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"ls"}}]"#,
+                4_000,
+            ),
+            entry(
+                "user",
+                r#"[{"type":"tool_result","tool_use_id":"t1","content":"src\n```\nCargo.toml"}]"#,
+                5_000,
+            ),
+            entry("assistant", r#"[{"type":"text","text":"done"}]"#, 6_000),
+            entry("user", r#""third prompt""#, 7_000),
+        ]
+    }
+
+    #[test]
+    fn test_extract_from_prompt_spans_tool_turns() {
+        let exchange = extract_exchange(&conversation(), ts(3_000), None).unwrap();
+
+        let times: Vec<_> = exchange.turns.iter().map(|t| t.timestamp.timestamp_millis()).collect();
+        assert_eq!(times, vec![3_000, 4_000, 5_000, 6_000]);
+        assert_eq!(exchange.session_id, SESSION);
+    }
+
+    #[test]
+    fn test_extract_from_assistant_turn_finds_prompt() {
+        let exchange = extract_exchange(&conversation(), ts(5_500), None).unwrap();
+        assert_eq!(exchange.started_at(), Some(ts(3_000)));
+        assert_eq!(exchange.turns.len(), 4);
+    }
+
+    #[test]
+    fn test_extract_last_exchange_and_missing() {
+        let exchange = extract_exchange(&conversation(), ts(7_000), None).unwrap();
+        assert_eq!(exchange.turns.len(), 1);
+
+        assert!(extract_exchange(&conversation(), ts(500), None).is_none());
+    }
+
+    #[test]
+    fn test_markdown_rendering() {
+        let exchange =
+            extract_exchange(&conversation(), ts(3_000), Some(Path::new("/work/app"))).unwrap();
+
+        let md = exchange.to_markdown(&PathDisplay::default());
+
+        assert!(md.starts_with("# Exchange\n"));
+        assert!(md.contains("- Project: `/work/app`"));
+        assert!(md.contains("## User"));
+        assert!(md.contains("second prompt"));
+        assert!(md.contains("**Tool call:** `Bash`"));
+        assert!(md.contains("\"command\": \"ls\""));
+        assert!(md.contains("## Tool results"));
+        // Result contains a ``` fence, so the block uses a longer one
+        assert!(md.contains("````\nsrc\n```\nCargo.toml\n````"));
+        assert!(!md.contains("hmm"));
+        assert!(!md.contains("first prompt"));
+        assert!(!md.contains("third prompt"));
+    }
+
+    #[test]
+    fn test_session_markdown_has_every_prompt() {
+        let session = extract_session(&conversation(), None).unwrap();
+        assert_eq!(session.turns.len(), conversation().len());
+
+        let md = session.to_markdown(&PathDisplay::default());
+        assert!(md.starts_with("# Session\n"));
+        for prompt in ["first prompt", "second prompt", "third prompt"] {
+            assert!(md.contains(prompt));
+        }
+        assert!(extract_session(&[], None).is_none());
+    }
+
+    #[test]
+    fn test_json_rendering() {
+        let exchange = extract_exchange(&conversation(), ts(1_000), None).unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(
+            &exchange.render(ExportFormat::Json, &PathDisplay::default()).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(json["session_id"], SESSION);
+        assert_eq!(json["turns"].as_array().unwrap().len(), 2);
+        assert_eq!(json["turns"][1]["message"]["role"], "assistant");
+    }
+
+    #[test]
+    fn test_export_format_toggle() {
+        assert_eq!(ExportFormat::default().toggle(), ExportFormat::Json);
+        assert_eq!(ExportFormat::Json.file_extension(), "json");
+        assert_eq!(ExportFormat::Json.toggle().label(), "Markdown");
+    }
+}
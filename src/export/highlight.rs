@@ -0,0 +1,94 @@
+//! Syntax highlighting of code blocks in HTML exports.
+//!
+//! With the `highlight` feature, code is tokenized with syntect's bundled grammars and marked
+//! up with `hl-` CSS classes, styled by [`highlight_css`]. Without it, code is only escaped.
+//! Either way the result is safe to use as markup: all code text is escaped.
+
+#[cfg(feature = "highlight")]
+use std::sync::LazyLock;
+
+#[cfg(feature = "highlight")]
+use syntect::highlighting::ThemeSet;
+#[cfg(feature = "highlight")]
+use syntect::html::{ClassStyle, ClassedHTMLGenerator, css_for_theme_with_class_style};
+#[cfg(feature = "highlight")]
+use syntect::parsing::SyntaxSet;
+#[cfg(feature = "highlight")]
+use syntect::util::LinesWithEndings;
+
+use super::html::escape_html;
+
+#[cfg(feature = "highlight")]
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+/// Light theme matching the page colors
+#[cfg(feature = "highlight")]
+const THEME: &str = "InspiredGitHub";
+
+#[cfg(feature = "highlight")]
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+
+/// `code` as HTML, highlighted for `language` (a name or file extension like `rust` or `py`)
+///
+/// Unknown languages and code without one are escaped as plain text.
+#[cfg(feature = "highlight")]
+pub fn highlight_code(code: &str, language: Option<&str>) -> String {
+    let syntaxes = &*SYNTAXES;
+    let syntax = language
+        .and_then(|language| syntaxes.find_syntax_by_token(language))
+        .unwrap_or_else(|| syntaxes.find_syntax_plain_text());
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, syntaxes, CLASS_STYLE);
+    for line in LinesWithEndings::from(code) {
+        // A grammar choking on odd input shouldn't lose the code
+        if generator.parse_html_for_line_which_includes_newline(line).is_err() {
+            return escape_html(code);
+        }
+    }
+    generator.finalize()
+}
+
+/// `code` as HTML (escaped only: built without the `highlight` feature)
+#[cfg(not(feature = "highlight"))]
+pub fn highlight_code(code: &str, _language: Option<&str>) -> String {
+    escape_html(code)
+}
+
+/// Style sheet for the classes used by [`highlight_code`]
+#[cfg(feature = "highlight")]
+pub fn highlight_css() -> String {
+    ThemeSet::load_defaults()
+        .themes
+        .get(THEME)
+        .and_then(|theme| css_for_theme_with_class_style(theme, CLASS_STYLE).ok())
+        .unwrap_or_default()
+}
+
+/// Style sheet for highlighted code (empty: built without the `highlight` feature)
+#[cfg(not(feature = "highlight"))]
+pub fn highlight_css() -> String {
+    String::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_is_escaped() {
+        let html = highlight_code("if a < b && c > d { \"x\" }", Some("rust"));
+        assert!(!html.contains("a < b"));
+        assert!(html.contains("&lt;"));
+        assert_eq!(html.matches("&amp;").count(), 2);
+
+        let plain = highlight_code("<script>alert(1)</script>", None);
+        assert!(!plain.contains("<script>"));
+    }
+
+    #[cfg(feature = "highlight")]
+    #[test]
+    fn test_known_languages_are_highlighted() {
+        let html = highlight_code("fn main() {}\n", Some("rs"));
+        assert!(html.contains(r#"<span class="hl-"#), "{}", html);
+        assert!(highlight_code("fn main() {}\n", Some("no-such-language")).contains("fn main"));
+        assert!(highlight_css().contains(".hl-"));
+    }
+}
@@ -0,0 +1,556 @@
+//! Self-contained HTML pages.
+//!
+//! [`render_html_page`] renders a slice of the search index into a single HTML file with the
+//! entries embedded as JSON and a small fuzzy search written in plain JavaScript, so the page
+//! can be opened in any browser without the tool or a server. [`render_session_page`] renders
+//! one whole conversation as a static page with a table of contents and collapsible thinking
+//! and tool sections, optionally showing images saved next to it as attachments.
+//!
+//! Code blocks are syntax-highlighted (see [`super::highlight`]). Entry text only becomes
+//! markup after escaping: the search page inserts plain text as text nodes, and the markup of
+//! highlighted entries is built here from escaped text.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use super::attachments::Attachments;
+use super::exchange::{Exchange, is_prompt, message_text, tool_result_text};
+use super::highlight::{highlight_code, highlight_css};
+use super::timeline::condense;
+use crate::models::{ContentBlock, ConversationEntry, EntryType, MessageContent, SearchEntry};
+use crate::utils::{PathDisplay, TextSegment, split_code_blocks};
+
+/// Characters of a prompt shown in the table of contents
+const TOC_PROMPT_CHARS: usize = 80;
+
+/// Entry as embedded in the page
+#[derive(Serialize)]
+struct PageEntry<'a> {
+    kind: &'static str,
+    text: &'a str,
+    time: String,
+    project: Option<String>,
+    session: &'a str,
+    user: Option<&'a str>,
+    /// Highlighted markup of entries with code blocks, shown instead of `text`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    html: Option<String>,
+}
+
+impl<'a> PageEntry<'a> {
+    fn new(entry: &'a SearchEntry, paths: &PathDisplay) -> Self {
+        Self {
+            kind: match entry.entry_type {
+                EntryType::UserPrompt => "user",
+                EntryType::AgentMessage => "agent",
+            },
+            text: &entry.display_text,
+            time: entry.timestamp.format("%Y-%m-%d %H:%M").to_string(),
+            project: entry.project_path.as_deref().map(|p| paths.format_full(p)),
+            session: &entry.session_id,
+            user: entry.user.as_deref(),
+            html: has_code_block(&entry.display_text)
+                .then(|| render_text_html(&entry.display_text)),
+        }
+    }
+}
+
+fn has_code_block(text: &str) -> bool {
+    split_code_blocks(text).iter().any(|segment| matches!(segment, TextSegment::Code(_)))
+}
+
+/// Render `entries` as a standalone HTML search page titled `title`
+///
+/// Project paths are shown in full unless `paths` maps them to a workspace-relative form.
+///
+/// # Errors
+///
+/// Returns an error if the entries cannot be serialized.
+pub fn render_html_page(
+    entries: &[SearchEntry],
+    title: &str,
+    paths: &PathDisplay,
+) -> Result<String> {
+    let page_entries: Vec<PageEntry> = entries.iter().map(|e| PageEntry::new(e, paths)).collect();
+    let data = serde_json::to_string(&page_entries).context("Failed to serialize entries")?;
+
+    Ok(PAGE_TEMPLATE
+        .replace("{{HIGHLIGHT_CSS}}", &highlight_css())
+        .replace("{{TITLE}}", &escape_html(title))
+        .replace("{{COUNT}}", &entries.len().to_string())
+        .replace("{{DATA}}", &escape_script_json(&data)))
+}
+
+/// Render a whole conversation as a standalone HTML page titled `title`
+///
+/// Prompts are listed in a table of contents linking to them; thinking, tool calls and tool
+/// results are collapsed into `<details>` sections. Project paths are formatted by `paths`.
+pub fn render_session_page(session: &Exchange, title: &str, paths: &PathDisplay) -> String {
+    render_session_page_with_attachments(session, title, paths, &Attachments::default())
+}
+
+/// Render a whole conversation as a standalone HTML page, showing the images that were
+/// written by [`extract_attachments`](super::extract_attachments)
+pub fn render_session_page_with_attachments(
+    session: &Exchange,
+    title: &str,
+    paths: &PathDisplay,
+    attachments: &Attachments,
+) -> String {
+    let mut body = String::new();
+    let mut meta = Vec::new();
+    if let Some(project) = &session.project {
+        meta.push(format!("Project {}", escape_html(&paths.format_full(project))));
+    }
+    meta.push(format!("Session {}", escape_html(&session.session_id)));
+    if let Some(started) = session.started_at() {
+        meta.push(format!("Started {}", started.format("%Y-%m-%d %H:%M UTC")));
+    }
+    body.push_str(&format!(
+        "<header>\n<h1>{}</h1>\n<div class=\"meta\">{}</div>\n</header>\n",
+        escape_html(title),
+        meta.join(" · ")
+    ));
+
+    let prompts: Vec<&ConversationEntry> =
+        session.turns.iter().filter(|turn| is_prompt(turn)).collect();
+    if !prompts.is_empty() {
+        body.push_str("<nav class=\"toc\">\n<h2>Contents</h2>\n<ol>\n");
+        for (i, prompt) in prompts.iter().enumerate() {
+            body.push_str(&format!(
+                "<li><a href=\"#prompt-{}\">{}</a> <span class=\"time\">{}</span></li>\n",
+                i + 1,
+                escape_html(&condense(&message_text(prompt), TOC_PROMPT_CHARS)),
+                prompt.timestamp.format("%H:%M")
+            ));
+        }
+        body.push_str("</ol>\n</nav>\n");
+    }
+
+    body.push_str("<main>\n");
+    let mut prompt_number = 0;
+    for turn in &session.turns {
+        let (class, heading) = match turn.message.role.as_str() {
+            "user" if is_prompt(turn) => ("user", "User"),
+            "user" => ("tools", "Tool results"),
+            "assistant" => ("assistant", "Assistant"),
+            other => ("other", other),
+        };
+        let id = if class == "user" {
+            prompt_number += 1;
+            format!(" id=\"prompt-{}\"", prompt_number)
+        } else {
+            String::new()
+        };
+        body.push_str(&format!(
+            "<section class=\"turn {}\"{}>\n<h2>{} <span class=\"time\">{}</span></h2>\n",
+            class,
+            id,
+            escape_html(heading),
+            turn.timestamp.format("%H:%M:%S")
+        ));
+        match &turn.message.content {
+            MessageContent::String(text) => body.push_str(&render_text_html(text)),
+            MessageContent::Array(blocks) => {
+                let mut images = 0;
+                for block in blocks {
+                    if let ContentBlock::Image { alt_text, .. } = block {
+                        let link = attachments.link(&turn.uuid, images);
+                        images += 1;
+                        if let Some(link) = link {
+                            let alt = alt_text.as_deref().unwrap_or("image");
+                            body.push_str(&format!(
+                                "<p class=\"image\"><img src=\"{}\" alt=\"{}\"></p>\n",
+                                escape_html(link),
+                                escape_html(alt)
+                            ));
+                            continue;
+                        }
+                    }
+                    push_block_html(&mut body, block);
+                }
+            }
+        }
+        body.push_str("</section>\n");
+    }
+    body.push_str("</main>\n");
+
+    let head = SESSION_HEAD
+        .replace("{{HIGHLIGHT_CSS}}", &highlight_css())
+        .replace("{{TITLE}}", &escape_html(title));
+    // The body is appended rather than substituted so message text is never scanned for
+    // placeholders
+    format!("{}{}{}", head, body, SESSION_FOOT)
+}
+
+/// Message text as HTML: prose in escaped blocks, fenced code highlighted
+fn render_text_html(text: &str) -> String {
+    let mut out = String::new();
+    for segment in split_code_blocks(text) {
+        match segment {
+            TextSegment::Prose(prose) => {
+                let prose = prose.trim_matches('\n');
+                if !prose.trim().is_empty() {
+                    out.push_str(&format!("<div class=\"prose\">{}</div>\n", escape_html(prose)));
+                }
+            }
+            TextSegment::Code(block) => {
+                push_code_html(&mut out, &block.code, block.language.as_deref());
+            }
+        }
+    }
+    out
+}
+
+fn push_code_html(out: &mut String, code: &str, language: Option<&str>) {
+    out.push_str("<pre class=\"code\">");
+    if let Some(language) = language {
+        out.push_str(&format!("<span class=\"lang\">{}</span>", escape_html(language)));
+    }
+    out.push_str(&format!("<code>{}</code></pre>\n", highlight_code(code, language)));
+}
+
+fn push_block_html(out: &mut String, block: &ContentBlock) {
+    match block {
+        ContentBlock::Text { text } => out.push_str(&render_text_html(text)),
+        ContentBlock::Thinking { thinking, .. } => {
+            out.push_str("<details class=\"thinking\">\n<summary>Thinking</summary>\n");
+            out.push_str(&render_text_html(thinking));
+            out.push_str("</details>\n");
+        }
+        ContentBlock::ToolUse { name, input, .. } => {
+            out.push_str(&format!(
+                "<details class=\"tool\">\n<summary>Tool call: <code>{}</code></summary>\n",
+                escape_html(name)
+            ));
+            let input = serde_json::to_string_pretty(input).unwrap_or_default();
+            push_code_html(out, &input, Some("json"));
+            out.push_str("</details>\n");
+        }
+        ContentBlock::ToolResult { content, is_error, .. } => {
+            let (class, label) = if *is_error == Some(true) {
+                ("tool error", "Tool error")
+            } else {
+                ("tool", "Tool result")
+            };
+            out.push_str(&format!("<details class=\"{}\">\n<summary>{}</summary>\n", class, label));
+            push_code_html(out, &tool_result_text(content), None);
+            out.push_str("</details>\n");
+        }
+        ContentBlock::Image { alt_text, .. } => {
+            let alt = alt_text.as_deref().map(|alt| format!(": {}", alt)).unwrap_or_default();
+            out.push_str(&format!("<p class=\"image\">[image{}]</p>\n", escape_html(&alt)));
+        }
+    }
+}
+
+/// Escape text for use in HTML element content
+pub(super) fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Make JSON safe inside a `<script>` element
+///
+/// `<` is escaped so entry text containing `</script>` or `<!--` can't end the element early;
+/// U+2028/U+2029 are escaped for older JavaScript parsers. The result is still valid JSON.
+fn escape_script_json(json: &str) -> String {
+    json.replace('<', "\\u003c").replace('\u{2028}', "\\u2028").replace('\u{2029}', "\\u2029")
+}
+
+const PAGE_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{{TITLE}}</title>
+<style>
+  body { font-family: system-ui, sans-serif; margin: 0; background: #fafafa; color: #222; }
+  header { position: sticky; top: 0; background: #fff; border-bottom: 1px solid #ddd; padding: 12px 20px; }
+  h1 { font-size: 1.1em; margin: 0 0 8px; }
+  #query { width: 100%; box-sizing: border-box; font-size: 1em; padding: 8px; }
+  #status { color: #666; font-size: 0.85em; margin-top: 6px; }
+  main { padding: 0 20px 20px; }
+  .entry { background: #fff; border: 1px solid #e4e4e4; border-radius: 6px; margin-top: 10px; padding: 10px 12px; }
+  .meta { color: #666; font-size: 0.8em; margin-bottom: 6px; }
+  .kind { font-weight: bold; margin-right: 8px; }
+  .kind.user { color: #1565c0; }
+  .kind.agent { color: #2e7d32; }
+  .text { white-space: pre-wrap; word-break: break-word; font-size: 0.92em; }
+  .text.collapsed { max-height: 8em; overflow: hidden; cursor: pointer; }
+  .text .prose { white-space: pre-wrap; }
+  .text pre.code { white-space: pre; overflow-x: auto; background: #f6f8fa; border-radius: 4px; padding: 8px; margin: 6px 0; }
+  .text .lang { float: right; color: #888; font-size: 0.8em; }
+{{HIGHLIGHT_CSS}}
+</style>
+</head>
+<body>
+<header>
+  <h1>{{TITLE}}</h1>
+  <input id="query" type="search" placeholder="Fuzzy search {{COUNT}} entries" autofocus>
+  <div id="status"></div>
+</header>
+<main id="results"></main>
+<script type="application/json" id="entries">{{DATA}}</script>
+<script>
+(function () {
+  "use strict";
+  var MAX_RESULTS = 200;
+  var entries = JSON.parse(document.getElementById("entries").textContent);
+  var haystacks = entries.map(function (e) {
+    return [e.text, e.project || "", e.user || ""].join("\n").toLowerCase();
+  });
+  var input = document.getElementById("query");
+  var results = document.getElementById("results");
+  var status = document.getElementById("status");
+
+  // Subsequence match; consecutive and word-start matches score higher. Returns null on no match.
+  function score(needle, haystack) {
+    var total = 0, run = 0, from = 0;
+    for (var i = 0; i < needle.length; i++) {
+      var c = needle[i];
+      if (c === " ") { run = 0; continue; }
+      var at = haystack.indexOf(c, from);
+      if (at < 0) { return null; }
+      run = at === from ? run + 1 : 1;
+      total += run * 2;
+      if (at === 0 || /[\s\/_\-.]/.test(haystack[at - 1])) { total += 3; }
+      from = at + 1;
+    }
+    return total;
+  }
+
+  function render(matches, query) {
+    results.textContent = "";
+    matches.slice(0, MAX_RESULTS).forEach(function (index) {
+      var e = entries[index];
+      var card = document.createElement("div");
+      card.className = "entry";
+      var meta = document.createElement("div");
+      meta.className = "meta";
+      var kind = document.createElement("span");
+      kind.className = "kind " + e.kind;
+      kind.textContent = e.kind;
+      meta.appendChild(kind);
+      var details = [e.time];
+      if (e.project) { details.push(e.project); }
+      if (e.user) { details.push(e.user); }
+      meta.appendChild(document.createTextNode(details.join(" · ")));
+      var text = document.createElement("div");
+      text.className = "text collapsed";
+      // `html` is generated from escaped text by the exporter
+      if (e.html) { text.innerHTML = e.html; } else { text.textContent = e.text; }
+      text.addEventListener("click", function () { text.classList.toggle("collapsed"); });
+      card.appendChild(meta);
+      card.appendChild(text);
+      results.appendChild(card);
+    });
+    var shown = Math.min(matches.length, MAX_RESULTS);
+    status.textContent = query
+      ? matches.length + " of " + entries.length + " entries match" + (shown < matches.length ? " (showing " + shown + ")" : "")
+      : entries.length + " entries" + (shown < entries.length ? " (showing newest " + shown + ")" : "");
+  }
+
+  function search() {
+    var query = input.value.trim().toLowerCase();
+    if (!query) {
+      render(entries.map(function (_, i) { return i; }), "");
+      return;
+    }
+    var scored = [];
+    for (var i = 0; i < haystacks.length; i++) {
+      var s = score(query, haystacks[i]);
+      if (s !== null) { scored.push([s, i]); }
+    }
+    scored.sort(function (a, b) { return b[0] - a[0] || a[1] - b[1]; });
+    render(scored.map(function (pair) { return pair[1]; }), query);
+  }
+
+  var pending;
+  input.addEventListener("input", function () {
+    clearTimeout(pending);
+    pending = setTimeout(search, 80);
+  });
+  search();
+})();
+</script>
+</body>
+</html>
+"#;
+
+const SESSION_HEAD: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{{TITLE}}</title>
+<style>
+  body { font-family: system-ui, sans-serif; margin: 0 auto; max-width: 960px; padding: 0 20px 40px; background: #fafafa; color: #222; }
+  header { padding: 16px 0 8px; border-bottom: 1px solid #ddd; }
+  h1 { font-size: 1.3em; margin: 0 0 6px; }
+  h2 { font-size: 0.95em; margin: 0 0 8px; }
+  .meta, .time { color: #666; font-size: 0.85em; font-weight: normal; }
+  nav.toc { background: #fff; border: 1px solid #e4e4e4; border-radius: 6px; margin-top: 16px; padding: 10px 16px; }
+  nav.toc ol { margin: 0; padding-left: 1.6em; }
+  nav.toc li { margin: 3px 0; }
+  nav.toc a { color: #1565c0; text-decoration: none; }
+  .turn { background: #fff; border: 1px solid #e4e4e4; border-left-width: 4px; border-radius: 6px; margin-top: 12px; padding: 10px 14px; }
+  .turn.user { border-left-color: #1565c0; }
+  .turn.assistant { border-left-color: #2e7d32; }
+  .turn.tools { border-left-color: #9e9e9e; }
+  .prose { white-space: pre-wrap; word-break: break-word; margin: 6px 0; }
+  pre.code { overflow-x: auto; background: #f6f8fa; border-radius: 4px; padding: 8px 10px; margin: 6px 0; font-size: 0.88em; }
+  pre.code .lang { float: right; color: #888; font-size: 0.85em; }
+  details { margin: 6px 0; }
+  details summary { cursor: pointer; color: #555; font-size: 0.9em; }
+  details.thinking .prose { color: #666; font-style: italic; }
+  details.error summary { color: #c62828; }
+  .image { color: #666; font-style: italic; }
+  .image img { max-width: 100%; }
+{{HIGHLIGHT_CSS}}
+</style>
+</head>
+<body>
+"#;
+
+const SESSION_FOOT: &str = "</body>\n</html>\n";
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use chrono::DateTime;
+
+    use super::*;
+
+    fn entry(text: &str) -> SearchEntry {
+        SearchEntry {
+            entry_type: EntryType::UserPrompt,
+            display_text: text.to_string(),
+            timestamp: DateTime::from_timestamp_millis(1_704_099_600_000).unwrap(),
+            project_path: Some(PathBuf::from("/work/app")),
+            session_id: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+            user: Some("alice".to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// The embedded JSON, as the page script would read it
+    fn embedded_entries(page: &str) -> serde_json::Value {
+        let start = page.find(r#"id="entries">"#).unwrap() + r#"id="entries">"#.len();
+        let end = start + page[start..].find("</script>").unwrap();
+        serde_json::from_str(&page[start..end]).unwrap()
+    }
+
+    #[test]
+    fn test_page_embeds_entries() {
+        let page = render_html_page(
+            &[entry("fix the parser"), entry("add tests")],
+            "History",
+            &PathDisplay::default(),
+        )
+        .unwrap();
+
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains("<title>History</title>"));
+        assert!(page.contains("Fuzzy search 2 entries"));
+        let data = embedded_entries(&page);
+        assert_eq!(data[0]["text"], "fix the parser");
+        assert_eq!(data[0]["kind"], "user");
+        assert_eq!(data[0]["project"], "/work/app");
+        assert_eq!(data[1]["user"], "alice");
+        assert_eq!(data[1]["time"], "2024-01-01 09:00");
+    }
+
+    #[test]
+    fn test_entry_text_cannot_break_out_of_script() {
+        let page = render_html_page(
+            &[entry("</script><script>alert(1)</script>")],
+            "t",
+            &PathDisplay::default(),
+        )
+        .unwrap();
+
+        assert_eq!(page.matches("</script>").count(), 2);
+        assert_eq!(embedded_entries(&page)[0]["text"], "</script><script>alert(1)</script>");
+    }
+
+    #[test]
+    fn test_title_is_escaped() {
+        let page = render_html_page(&[], "<b>&</b>", &PathDisplay::default()).unwrap();
+        assert!(page.contains("<title>&lt;b&gt;&amp;&lt;/b&gt;</title>"));
+        assert_eq!(embedded_entries(&page), serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_entries_with_code_carry_escaped_markup() {
+        let page = render_html_page(
+            &[entry("plain <b>text</b>"), entry("Try:\n```rust\nlet a = b < c;\n```\n<b>done</b>")],
+            "t",
+            &PathDisplay::default(),
+        )
+        .unwrap();
+
+        let data = embedded_entries(&page);
+        assert!(data[0].get("html").is_none());
+        let html = data[1]["html"].as_str().unwrap();
+        assert!(html.contains(r#"<pre class="code"><span class="lang">rust</span><code>"#));
+        assert!(html.contains("&lt;b&gt;done&lt;/b&gt;"));
+        assert!(!html.contains("b < c"));
+    }
+
+    fn turn(role: &str, content: &str, secs: i64) -> ConversationEntry {
+        let line = format!(
+            r#"{{"type":"{role}","message":{{"role":"{role}","content":{content}}},"timestamp":{},"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u{secs}"}}"#,
+            (1_704_099_600 + secs) * 1000
+        );
+        serde_json::from_str(&line).unwrap()
+    }
+
+    #[test]
+    fn test_session_page_has_contents_and_collapsed_sections() {
+        let session = Exchange {
+            session_id: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+            project: Some(PathBuf::from("/work/app")),
+            turns: vec![
+                turn("user", r#""Fix the <script> tag""#, 0),
+                turn(
+                    "assistant",
+                    r#"[{"type":"thinking","thinking":"look first"},{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"ls"}}]"#,
+                    1,
+                ),
+                turn(
+                    "user",
+                    r#"[{"type":"tool_result","tool_use_id":"t1","content":"no such file","is_error":true}]"#,
+                    2,
+                ),
+                turn("assistant", r#"[{"type":"text","text":"Done:\n```js\nx()\n```"}]"#, 3),
+                turn("user", r#""thanks""#, 4),
+            ],
+        };
+        let page = render_session_page(&session, "Session", &PathDisplay::default());
+
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains(r##"<a href="#prompt-1">Fix the &lt;script&gt; tag</a>"##));
+        assert!(page.contains(r##"<a href="#prompt-2">thanks</a>"##));
+        assert!(page.contains(r#"<section class="turn user" id="prompt-2">"#));
+        assert!(page.contains(r#"<section class="turn tools">"#));
+        assert!(page.contains("<details class=\"thinking\">\n<summary>Thinking</summary>"));
+        assert!(page.contains("<summary>Tool call: <code>Bash</code></summary>"));
+        assert!(page.contains(r#"<details class="tool error">"#));
+        assert!(page.contains(r#"<span class="lang">js</span>"#));
+        assert!(page.contains("Project /work/app"));
+        assert!(!page.contains("<script>"));
+        assert!(page.ends_with("</body>\n</html>\n"));
+    }
+}
@@ -0,0 +1,21 @@
+//! Export of conversation excerpts for sharing.
+//!
+//! Exchange exports work on the parsed conversation files rather than the search index, so
+//! they contain the full message content including tool calls and results. HTML pages of a
+//! session and terminal transcripts work on the conversation file too; the HTML search page and
+//! the prompt timeline work on (a filtered slice of) the search index.
+
+pub mod attachments;
+pub mod context;
+pub mod exchange;
+pub mod highlight;
+pub mod html;
+pub mod timeline;
+pub mod transcript;
+
+pub use attachments::{ATTACHMENTS_DIR_NAME, Attachments, extract_attachments, plan_attachments};
+pub use context::ReproContext;
+pub use exchange::{Exchange, ExportFormat, extract_exchange, extract_session};
+pub use html::{render_html_page, render_session_page, render_session_page_with_attachments};
+pub use timeline::{PromptTimeline, TimelinePrompt};
+pub use transcript::render_transcript;
@@ -0,0 +1,161 @@
+//! Prompt timeline of a project.
+//!
+//! Lists only the user prompts of one project, oldest first and condensed to one line each,
+//! so months of work can be skimmed like a changelog. Rendered to Markdown grouped by month.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+
+use crate::models::{EntryType, SearchEntry};
+use crate::utils::PathDisplay;
+
+/// Longest condensed prompt line, in characters
+pub const MAX_PROMPT_LINE_CHARS: usize = 200;
+
+/// One prompt of the timeline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimelinePrompt {
+    pub timestamp: DateTime<Utc>,
+    pub session_id: String,
+    /// Prompt text condensed to a single line
+    pub line: String,
+}
+
+/// User prompts of `project`, oldest first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptTimeline {
+    pub project: PathBuf,
+    pub prompts: Vec<TimelinePrompt>,
+}
+
+impl PromptTimeline {
+    /// Collect the user prompts recorded for exactly `project`
+    pub fn for_project(entries: &[SearchEntry], project: &Path) -> Self {
+        let mut prompts: Vec<TimelinePrompt> = entries
+            .iter()
+            .filter(|e| {
+                e.entry_type == EntryType::UserPrompt && e.project_path.as_deref() == Some(project)
+            })
+            .map(|e| TimelinePrompt {
+                timestamp: e.timestamp,
+                session_id: e.session_id.clone(),
+                line: condense(&e.display_text, MAX_PROMPT_LINE_CHARS),
+            })
+            .filter(|p| !p.line.is_empty())
+            .collect();
+        prompts.sort_by_key(|p| p.timestamp);
+        Self { project: project.to_path_buf(), prompts }
+    }
+
+    /// Render as Markdown: a heading per month and one bullet per prompt
+    pub fn to_markdown(&self, paths: &PathDisplay) -> String {
+        let mut out = format!("# Prompt timeline: {}\n\n", paths.format(&self.project));
+        match (self.prompts.first(), self.prompts.last()) {
+            (Some(first), Some(last)) => out.push_str(&format!(
+                "{} prompts from {} to {}\n",
+                self.prompts.len(),
+                first.timestamp.format("%Y-%m-%d"),
+                last.timestamp.format("%Y-%m-%d")
+            )),
+            _ => out.push_str("No prompts recorded for this project.\n"),
+        }
+
+        let mut month = String::new();
+        for prompt in &self.prompts {
+            let prompt_month = prompt.timestamp.format("%Y-%m").to_string();
+            if prompt_month != month {
+                out.push_str(&format!("\n## {}\n\n", prompt_month));
+                month = prompt_month;
+            }
+            out.push_str(&format!(
+                "- **{}** {}\n",
+                prompt.timestamp.format("%Y-%m-%d %H:%M"),
+                escape_markdown_line(&prompt.line)
+            ));
+        }
+        out
+    }
+}
+
+/// Collapse all whitespace (including newlines) to single spaces and truncate with `…`
+pub fn condense(text: &str, max_chars: usize) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() <= max_chars {
+        return collapsed;
+    }
+    let mut line: String = collapsed.chars().take(max_chars.saturating_sub(1)).collect();
+    line.push('…');
+    line
+}
+
+/// Keep a prompt from turning into markup when placed after a list bullet
+fn escape_markdown_line(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    for c in line.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(entry_type: EntryType, project: &str, text: &str, millis: i64) -> SearchEntry {
+        SearchEntry {
+            entry_type,
+            display_text: text.to_string(),
+            timestamp: DateTime::from_timestamp_millis(millis).unwrap(),
+            project_path: Some(PathBuf::from(project)),
+            session_id: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_for_project_keeps_only_its_prompts_oldest_first() {
+        let entries = vec![
+            entry(EntryType::UserPrompt, "/work/app", "second", 1_706_778_000_000),
+            entry(EntryType::AgentMessage, "/work/app", "answer", 1_706_000_000_000),
+            entry(EntryType::UserPrompt, "/work/app-old", "other project", 1_704_000_000_000),
+            entry(EntryType::UserPrompt, "/work/app", "first\n  line two", 1_704_099_600_000),
+        ];
+
+        let timeline = PromptTimeline::for_project(&entries, Path::new("/work/app"));
+
+        let lines: Vec<&str> = timeline.prompts.iter().map(|p| p.line.as_str()).collect();
+        assert_eq!(lines, vec!["first line two", "second"]);
+    }
+
+    #[test]
+    fn test_to_markdown_groups_by_month() {
+        let entries = vec![
+            entry(EntryType::UserPrompt, "/work/app", "fix *the* parser", 1_704_099_600_000),
+            entry(EntryType::UserPrompt, "/work/app", "add tests", 1_706_778_000_000),
+        ];
+
+        let markdown = PromptTimeline::for_project(&entries, Path::new("/work/app"))
+            .to_markdown(&PathDisplay::default());
+
+        assert!(markdown.starts_with("# Prompt timeline: /work/app\n\n2 prompts from 2024-01-01"));
+        assert!(markdown.contains("\n## 2024-01\n\n- **2024-01-01 09:00** fix \\*the\\* parser\n"));
+        assert!(markdown.contains("\n## 2024-02\n\n- **2024-02-01 09:00** add tests\n"));
+    }
+
+    #[test]
+    fn test_to_markdown_empty() {
+        let markdown = PromptTimeline::for_project(&[], Path::new("/work/app"))
+            .to_markdown(&PathDisplay::default());
+        assert!(markdown.contains("No prompts recorded"));
+    }
+
+    #[test]
+    fn test_condense_truncates() {
+        assert_eq!(condense("a  b\n\tc", 10), "a b c");
+        assert_eq!(condense("abcdefgh", 5), "abcd…");
+    }
+}
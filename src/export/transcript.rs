@@ -0,0 +1,274 @@
+//! Terminal transcripts of a session.
+//!
+//! [`render_transcript`] prints a whole conversation for reading in a terminal (or a pager
+//! such as `less -R`): a colored header per turn, text wrapped to the given width, thinking
+//! dimmed and indented, and tool calls and results in boxes. Without color the layout is the
+//! same, so the output also reads well in a file.
+//!
+//! Message text comes from the history files, so ANSI escape codes in it are stripped before
+//! it is printed (see [`crate::utils::terminal`]).
+
+use super::exchange::{Exchange, is_prompt, tool_result_text};
+use crate::models::{ContentBlock, ConversationEntry, MessageContent};
+use crate::utils::PathDisplay;
+use crate::utils::terminal::strip_ansi_codes;
+
+/// Narrowest wrapping width; narrower terminals get lines wider than the screen
+const MIN_WIDTH: usize = 20;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const BLUE: &str = "\x1b[34m";
+const CYAN: &str = "\x1b[36m";
+
+/// Output being built, with or without ANSI colors
+struct Printer {
+    out: String,
+    width: usize,
+    color: bool,
+}
+
+impl Printer {
+    fn paint(&self, style: &str, text: &str) -> String {
+        if self.color && !style.is_empty() {
+            format!("{}{}{}", style, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn line(&mut self, style: &str, text: &str) {
+        let painted = self.paint(style, text);
+        self.out.push_str(&painted);
+        self.out.push('\n');
+    }
+
+    /// Wrapped text, each line starting with the (already painted) `prefix`
+    fn text(&mut self, prefix: &str, prefix_width: usize, style: &str, text: &str) {
+        let width = self.width.saturating_sub(prefix_width).max(MIN_WIDTH);
+        for line in wrap(&strip_ansi_codes(text), width) {
+            let painted = self.paint(style, &line);
+            self.out.push_str(prefix);
+            self.out.push_str(&painted);
+            self.out.push('\n');
+        }
+    }
+
+    /// Heading line `━━ label ━━…━━ time`, filled to the width
+    fn heading(&mut self, style: &str, label: &str, time: &str) {
+        let fill = self.width.saturating_sub(label.chars().count() + time.chars().count() + 5);
+        let rule = format!("━━ {} {} {}", label, "━".repeat(fill.max(2)), time);
+        self.line(style, &rule);
+    }
+
+    /// Box with a title around wrapped text
+    fn boxed(&mut self, style: &str, title: &str, body: &str) {
+        let fill = self.width.saturating_sub(title.chars().count() + 4).max(2);
+        self.line(style, &format!("┌─ {} {}", title, "─".repeat(fill)));
+        let border = self.paint(style, "│ ");
+        self.text(&border, 2, "", body.trim_end());
+        self.line(style, &format!("└{}", "─".repeat(self.width.saturating_sub(1).max(2))));
+    }
+}
+
+/// Render `session` for the terminal, wrapped to `width` columns and colored if `color`
+///
+/// Project paths are formatted by `paths`.
+pub fn render_transcript(
+    session: &Exchange,
+    width: usize,
+    color: bool,
+    paths: &PathDisplay,
+) -> String {
+    let mut printer = Printer { out: String::new(), width: width.max(MIN_WIDTH), color };
+
+    let mut meta = vec![format!("Session {}", session.session_id)];
+    if let Some(project) = &session.project {
+        meta.push(strip_ansi_codes(&paths.format_full(project)));
+    }
+    if let Some(started) = session.started_at() {
+        meta.push(format!("started {}", started.format("%Y-%m-%d %H:%M UTC")));
+    }
+    printer.line(BOLD, &meta.join(" · "));
+
+    for turn in &session.turns {
+        printer.out.push('\n');
+        push_turn(&mut printer, turn);
+    }
+    printer.out
+}
+
+fn push_turn(printer: &mut Printer, turn: &ConversationEntry) {
+    let (style, heading) = match turn.message.role.as_str() {
+        "user" if is_prompt(turn) => (CYAN, "User"),
+        "user" => (YELLOW, "Tool results"),
+        "assistant" => (GREEN, "Assistant"),
+        _ => (BOLD, turn.message.role.as_str()),
+    };
+    let heading = strip_ansi_codes(heading);
+    printer.heading(
+        &format!("{}{}", BOLD, style),
+        &heading,
+        &turn.timestamp.format("%H:%M:%S").to_string(),
+    );
+
+    match &turn.message.content {
+        MessageContent::String(text) => printer.text("", 0, "", text.trim_end()),
+        MessageContent::Array(blocks) => {
+            for block in blocks {
+                push_block(printer, block);
+            }
+        }
+    }
+}
+
+fn push_block(printer: &mut Printer, block: &ContentBlock) {
+    match block {
+        ContentBlock::Text { text } => printer.text("", 0, "", text.trim_end()),
+        ContentBlock::Thinking { thinking, .. } => {
+            let border = printer.paint(DIM, "┊ ");
+            printer.line(DIM, "Thinking");
+            printer.text(&border, 2, DIM, thinking.trim_end());
+        }
+        ContentBlock::ToolUse { name, input, .. } => {
+            let input = serde_json::to_string_pretty(input).unwrap_or_default();
+            printer.boxed(BLUE, &format!("Tool call: {}", strip_ansi_codes(name)), &input);
+        }
+        ContentBlock::ToolResult { content, is_error, .. } => {
+            let (style, title) =
+                if *is_error == Some(true) { (RED, "Tool error") } else { (DIM, "Tool result") };
+            printer.boxed(style, title, &tool_result_text(content));
+        }
+        ContentBlock::Image { alt_text, .. } => {
+            let alt = alt_text.as_deref().map(|alt| format!(": {}", alt)).unwrap_or_default();
+            printer.line(DIM, &strip_ansi_codes(&format!("[image{}]", alt)));
+        }
+    }
+}
+
+/// Lines of `text` wrapped at word boundaries to `width` characters
+///
+/// Line breaks in the text are kept, leading indentation is kept on the first part of a
+/// line, and words longer than the width are split.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for source in text.split('\n') {
+        let indent_len = source.len() - source.trim_start().len();
+        let mut current = source[..indent_len].to_string();
+        let mut current_width = current.chars().count();
+        let mut at_line_start = true;
+        for word in source.split_whitespace() {
+            let mut word: Vec<char> = word.chars().collect();
+            let space = usize::from(!at_line_start);
+            if current_width + space + word.len() > width && !at_line_start {
+                lines.push(std::mem::take(&mut current));
+                current_width = 0;
+                at_line_start = true;
+            }
+            if !at_line_start {
+                current.push(' ');
+                current_width += 1;
+            }
+            // Split words that don't fit on a line of their own
+            while current_width + word.len() > width {
+                let take = width.saturating_sub(current_width).max(1);
+                current.extend(word.drain(..take));
+                lines.push(std::mem::take(&mut current));
+                current_width = 0;
+            }
+            current_width += word.len();
+            current.extend(word);
+            at_line_start = false;
+        }
+        lines.push(current);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn turn(role: &str, content: &str, secs: i64) -> ConversationEntry {
+        let line = format!(
+            r#"{{"type":"{role}","message":{{"role":"{role}","content":{content}}},"timestamp":{},"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u{secs}"}}"#,
+            (1_704_099_600 + secs) * 1000
+        );
+        serde_json::from_str(&line).unwrap()
+    }
+
+    fn session() -> Exchange {
+        Exchange {
+            session_id: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+            project: Some(PathBuf::from("/work/app")),
+            turns: vec![
+                turn("user", r#""Fix the \u001b[31mred\u001b[0m build""#, 0),
+                turn(
+                    "assistant",
+                    r#"[{"type":"thinking","thinking":"look first"},{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"ls"}}]"#,
+                    1,
+                ),
+                turn(
+                    "user",
+                    r#"[{"type":"tool_result","tool_use_id":"t1","content":"no such file","is_error":true}]"#,
+                    2,
+                ),
+                turn("assistant", r#"[{"type":"text","text":"Done"}]"#, 3),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_plain_transcript_layout() {
+        let out = render_transcript(&session(), 40, false, &PathDisplay::default());
+        let expected = "\
+Session 550e8400-e29b-41d4-a716-446655440000 · /work/app · started 2024-01-01 09:00 UTC
+
+━━ User ━━━━━━━━━━━━━━━━━━━━━━━ 09:00:00
+Fix the red build
+
+━━ Assistant ━━━━━━━━━━━━━━━━━━ 09:00:01
+Thinking
+┊ look first
+┌─ Tool call: Bash ─────────────────────
+│ {
+│   \"command\": \"ls\"
+│ }
+└───────────────────────────────────────
+
+━━ Tool results ━━━━━━━━━━━━━━━ 09:00:02
+┌─ Tool error ──────────────────────────
+│ no such file
+└───────────────────────────────────────
+
+━━ Assistant ━━━━━━━━━━━━━━━━━━ 09:00:03
+Done
+";
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_colors_roles_and_dims_thinking() {
+        let out = render_transcript(&session(), 80, true, &PathDisplay::default());
+        assert!(out.contains(&format!("{}{}━━ User", BOLD, CYAN)));
+        assert!(out.contains(&format!("{}{}━━ Assistant", BOLD, GREEN)));
+        assert!(out.contains(&format!("{}look first{}", DIM, RESET)));
+        assert!(out.contains(&format!("{}┌─ Tool error", RED)));
+        // Escape codes from the history are not passed through
+        assert!(out.contains("Fix the red build"));
+    }
+
+    #[test]
+    fn test_wrap_keeps_breaks_and_splits_long_words() {
+        assert_eq!(wrap("one two three four", 9), vec!["one two", "three", "four"]);
+        assert_eq!(wrap("a\n\n  indented text", 20), vec!["a", "", "  indented text"]);
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("", 10), vec![""]);
+    }
+}
@@ -1,8 +1,16 @@
-use anyhow::Result;
-use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::path::Path;
 
-use super::ast::{FieldFilter, FilterExpr, FilterField, FilterOperator};
-use crate::models::search::{EntryType, SearchEntry};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+
+use super::ast::{
+    DateValue, FieldFilter, FilterExpr, FilterField, FilterNode, FilterOperator, SizeValue,
+};
+use crate::bookmarks::Bookmarks;
+use crate::models::search::{ContentKinds, EntryType, SearchEntry, SessionOutcome, SourceKind};
+use crate::utils::{DisplayZone, display_zone};
 
 /// Apply filters to search entries, returning filtered results
 ///
@@ -10,28 +18,108 @@ use crate::models::search::{EntryType, SearchEntry};
 /// - Same-field OR: project:foo project:bar → (foo OR bar)
 /// - Cross-field AND: project:foo type:user → (foo AND user)
 /// - Explicit operators override defaults
+/// - Parenthesized groups are evaluated as one clause, and NOT inverts what follows it
+///
+/// Operators have no precedence over each other: they apply left to right, so
+/// `a OR b AND c` is `(a OR b) AND c`, and only parentheses group clauses differently.
+/// `starred:` is checked against `bookmarks` (the saved ones are
+/// [`Bookmarks::load_default`]), and `messages` counts the entries of each session among
+/// `entries`.
+///
+/// # Errors
 ///
-/// Filters are evaluated left-to-right with operator precedence
-pub fn apply_filters(entries: Vec<SearchEntry>, filter: &FilterExpr) -> Result<Vec<SearchEntry>> {
+/// Returns an error if a `regex:` value is not a valid regular expression.
+pub fn apply_filters(
+    entries: Vec<SearchEntry>,
+    filter: &FilterExpr,
+    bookmarks: &Bookmarks,
+) -> Result<Vec<SearchEntry>> {
     if filter.is_empty() {
         return Ok(entries);
     }
 
-    Ok(entries.into_iter().filter(|entry| evaluate_filter(entry, filter)).collect())
+    let ctx = EvalContext::new(filter, &entries, bookmarks)?;
+    Ok(entries.into_iter().filter(|entry| evaluate_filter(entry, filter, &ctx)).collect())
+}
+
+/// Like [`apply_filters`] over the entries at `indices`, returning the indices of those that
+/// match instead of moving the entries (`messages` still counts all `entries`)
+pub fn filter_indices(
+    entries: &[SearchEntry],
+    mut indices: Vec<usize>,
+    filter: &FilterExpr,
+    bookmarks: &Bookmarks,
+) -> Result<Vec<usize>> {
+    if filter.is_empty() {
+        return Ok(indices);
+    }
+
+    let ctx = EvalContext::new(filter, entries, bookmarks)?;
+    indices.retain(|&i| evaluate_filter(&entries[i], filter, &ctx));
+    Ok(indices)
+}
+
+/// What evaluating an expression needs besides the entries, prepared once per evaluation
+pub(super) struct EvalContext<'a> {
+    /// Relative dates are counted from this instant
+    now: DateTime<Utc>,
+    /// Days start at midnight in this zone
+    zone: DisplayZone,
+    /// Compiled `regex:` patterns, by pattern
+    regexes: HashMap<String, Regex>,
+    /// Starred entries, checked by `starred:` filters
+    bookmarks: &'a Bookmarks,
+    /// Number of entries of each session, counted only for `messages` filters
+    session_sizes: HashMap<String, usize>,
+}
+
+impl<'a> EvalContext<'a> {
+    /// Prepare to evaluate `filter` against `entries`, compiling its regex patterns
+    ///
+    /// `messages` counts the entries of a session among `entries`, so pass all of them even
+    /// when only some are evaluated. `starred:` checks `bookmarks`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a `regex:` value is not a valid regular expression.
+    pub(super) fn new(
+        filter: &FilterExpr,
+        entries: &[SearchEntry],
+        bookmarks: &'a Bookmarks,
+    ) -> Result<Self> {
+        let mut regexes = HashMap::new();
+        for f in filter.filters.iter().filter(|f| f.field == FilterField::Regex) {
+            if !regexes.contains_key(&f.value) {
+                let regex = Regex::new(&f.value)
+                    .with_context(|| format!("Invalid regex: '{}'", f.value))?;
+                regexes.insert(f.value.clone(), regex);
+            }
+        }
+        let mut session_sizes = HashMap::new();
+        if filter.uses(FilterField::Messages) {
+            for entry in entries {
+                *session_sizes.entry(entry.session_id.clone()).or_default() += 1;
+            }
+        }
+        Ok(Self { now: Utc::now(), zone: display_zone(), regexes, bookmarks, session_sizes })
+    }
 }
 
 /// Evaluate filter expression against a single entry
-fn evaluate_filter(entry: &SearchEntry, filter: &FilterExpr) -> bool {
+pub(super) fn evaluate_filter(entry: &SearchEntry, filter: &FilterExpr, ctx: &EvalContext) -> bool {
     if filter.filters.is_empty() {
         return true;
     }
+    if let Some(tree) = &filter.tree {
+        return evaluate_node(entry, filter, tree, ctx);
+    }
 
     // Start with first filter
-    let mut result = evaluate_field_filter(entry, &filter.filters[0]);
+    let mut result = evaluate_field_filter(entry, &filter.filters[0], ctx);
 
     // Apply operators and remaining filters
     for (i, operator) in filter.operators.iter().enumerate() {
-        let next_filter_result = evaluate_field_filter(entry, &filter.filters[i + 1]);
+        let next_filter_result = evaluate_field_filter(entry, &filter.filters[i + 1], ctx);
 
         result = match operator {
             FilterOperator::And => result && next_filter_result,
@@ -42,39 +130,117 @@ fn evaluate_filter(entry: &SearchEntry, filter: &FilterExpr) -> bool {
     result
 }
 
+/// Evaluate a group, negation or clause of `filter` against entry
+fn evaluate_node(
+    entry: &SearchEntry,
+    filter: &FilterExpr,
+    node: &FilterNode,
+    ctx: &EvalContext,
+) -> bool {
+    match node {
+        FilterNode::Clause(i) => {
+            filter.filters.get(*i).is_some_and(|f| evaluate_field_filter(entry, f, ctx))
+        }
+        FilterNode::Not(node) => !evaluate_node(entry, filter, node, ctx),
+        FilterNode::Group(nodes) => {
+            let Some((first, rest)) = nodes.split_first() else {
+                return true;
+            };
+            rest.iter().fold(evaluate_node(entry, filter, first, ctx), |result, node| {
+                let operator =
+                    node.first_clause().checked_sub(1).and_then(|i| filter.operators.get(i));
+                match operator {
+                    Some(FilterOperator::Or) => result || evaluate_node(entry, filter, node, ctx),
+                    _ => result && evaluate_node(entry, filter, node, ctx),
+                }
+            })
+        }
+    }
+}
+
 /// Evaluate single field filter against entry
-fn evaluate_field_filter(entry: &SearchEntry, filter: &FieldFilter) -> bool {
+pub(super) fn evaluate_field_filter(
+    entry: &SearchEntry,
+    filter: &FieldFilter,
+    ctx: &EvalContext,
+) -> bool {
+    let (now, zone) = (ctx.now, ctx.zone);
     match filter.field {
         FilterField::Project => match_project(entry, &filter.value),
         FilterField::Type => match_type(entry, &filter.value),
-        FilterField::Since => match_since(entry, &filter.value),
+        FilterField::Since | FilterField::After => match_after(entry, &filter.value, now, zone),
+        FilterField::Before => match_before(entry, &filter.value, now, zone),
+        FilterField::On => match_on(entry, &filter.value, now, zone),
+        FilterField::User => match_user(entry, &filter.value),
+        FilterField::Outcome => match_outcome(entry, &filter.value),
+        FilterField::Source => SourceKind::from_label(&filter.value) == Some(entry.source),
+        FilterField::Root => {
+            entry.root.as_deref().is_some_and(|root| root.eq_ignore_ascii_case(&filter.value))
+        }
+        FilterField::Tool => {
+            entry.tools.iter().any(|tool| tool.eq_ignore_ascii_case(&filter.value))
+        }
+        FilterField::Model => match_model(entry, &filter.value),
+        FilterField::Branch => match_branch(entry, &filter.value),
+        FilterField::Cwd => {
+            entry.cwd.as_deref().is_some_and(|cwd| path_matches(cwd, &filter.value))
+        }
+        FilterField::Starred => {
+            ctx.bookmarks.contains(entry) == filter.value.eq_ignore_ascii_case("true")
+        }
+        FilterField::Error => entry.had_error == filter.value.eq_ignore_ascii_case("true"),
+        FilterField::Regex => {
+            ctx.regexes.get(&filter.value).is_some_and(|regex| regex.is_match(&entry.display_text))
+        }
+        FilterField::Has => match_has(entry, &filter.value),
+        FilterField::Len => match_size(&filter.value, entry.display_text.chars().count()),
+        FilterField::Messages => match_size(
+            &filter.value,
+            ctx.session_sizes.get(&entry.session_id).copied().unwrap_or_default(),
+        ),
     }
 }
 
+/// Match a kind of content block, or `error` for a tool result flagged as an error
+/// (case-insensitive)
+fn match_has(entry: &SearchEntry, value: &str) -> bool {
+    match value.to_lowercase().as_str() {
+        "error" => entry.had_error,
+        kind => ContentKinds::from_name(&kind.to_uppercase())
+            .is_some_and(|kind| entry.content_kinds.contains(kind)),
+    }
+}
+
+/// Whether `count` satisfies the size comparison `value`
+fn match_size(value: &str, count: usize) -> bool {
+    SizeValue::parse(value).is_some_and(|size| size.matches(count))
+}
+
 /// Match project path (case-insensitive substring match)
 fn match_project(entry: &SearchEntry, value: &str) -> bool {
-    if let Some(ref project_path) = entry.project_path {
-        let path_str = project_path.to_string_lossy();
-        let lower_path = path_str.to_lowercase();
-        let lower_value = value.to_lowercase();
-
-        // Support ~ expansion
-        let search_value = if lower_value.starts_with('~') {
-            // Try to expand ~ to home directory
-            if let Some(home) = dirs::home_dir() {
-                let home_str = home.to_string_lossy().to_lowercase();
-                lower_value.replacen("~", &home_str, 1)
-            } else {
-                lower_value
-            }
+    entry.project_path.as_deref().is_some_and(|project_path| path_matches(project_path, value))
+}
+
+/// Whether `path` contains `value` (case-insensitive, a leading `~` is the home directory)
+fn path_matches(path: &Path, value: &str) -> bool {
+    let path_str = path.to_string_lossy();
+    let lower_path = path_str.to_lowercase();
+    let lower_value = value.to_lowercase();
+
+    // Support ~ expansion
+    let search_value = if lower_value.starts_with('~') {
+        // Try to expand ~ to home directory
+        if let Some(home) = dirs::home_dir() {
+            let home_str = home.to_string_lossy().to_lowercase();
+            lower_value.replacen("~", &home_str, 1)
         } else {
             lower_value
-        };
-
-        lower_path.contains(&search_value)
+        }
     } else {
-        false
-    }
+        lower_value
+    };
+
+    lower_path.contains(&search_value)
 }
 
 /// Match entry type (case-insensitive exact match)
@@ -87,15 +253,48 @@ fn match_type(entry: &SearchEntry, value: &str) -> bool {
     }
 }
 
-/// Match since date (timestamp >= date)
-fn match_since(entry: &SearchEntry, value: &str) -> bool {
-    // Parse YYYY-MM-DD format
-    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
-        let filter_datetime = date.and_hms_opt(0, 0, 0).expect("Valid time").and_utc();
-        entry.timestamp >= filter_datetime
-    } else {
-        false
-    }
+/// Match user identity (case-insensitive substring match); entries without one never match
+fn match_user(entry: &SearchEntry, value: &str) -> bool {
+    entry.user.as_ref().is_some_and(|user| user.to_lowercase().contains(&value.to_lowercase()))
+}
+
+/// Match the model (case-insensitive partial match, so `opus` matches every Opus version);
+/// entries without a recorded model never match
+fn match_model(entry: &SearchEntry, value: &str) -> bool {
+    entry.model.as_ref().is_some_and(|model| model.to_lowercase().contains(&value.to_lowercase()))
+}
+
+/// Match the git branch (case-insensitive partial match, so `feature/` matches every feature
+/// branch); entries without a recorded branch never match
+fn match_branch(entry: &SearchEntry, value: &str) -> bool {
+    entry
+        .git_branch
+        .as_ref()
+        .is_some_and(|branch| branch.to_lowercase().contains(&value.to_lowercase()))
+}
+
+/// Match session outcome (case-insensitive exact match); unclassified entries never match
+fn match_outcome(entry: &SearchEntry, value: &str) -> bool {
+    entry.outcome.is_some_and(|outcome| Some(outcome) == SessionOutcome::from_label(value))
+}
+
+/// Match since/after date (timestamp >= start of the date, or within the age)
+fn match_after(entry: &SearchEntry, value: &str, now: DateTime<Utc>, zone: DisplayZone) -> bool {
+    DateValue::parse(value).is_some_and(|date| entry.timestamp >= date.start(now, zone))
+}
+
+/// Match before date (timestamp < start of the date, or older than the age)
+fn match_before(entry: &SearchEntry, value: &str, now: DateTime<Utc>, zone: DisplayZone) -> bool {
+    DateValue::parse(value).is_some_and(|date| entry.timestamp < date.start(now, zone))
+}
+
+/// Match on date (timestamp within the day in `zone`; DST days are 23 or 25 hours long)
+fn match_on(entry: &SearchEntry, value: &str, now: DateTime<Utc>, zone: DisplayZone) -> bool {
+    DateValue::parse(value).is_some_and(|date| {
+        let day = date.day(now, zone);
+        let end = day.succ_opt().map_or(DateTime::<Utc>::MAX_UTC, |next| zone.start_of_day(next));
+        entry.timestamp >= zone.start_of_day(day) && entry.timestamp < end
+    })
 }
 
 #[cfg(test)]
@@ -117,6 +316,7 @@ mod tests {
             timestamp,
             project_path: project_path.map(PathBuf::from),
             session_id: "test-session".to_string(),
+            ..Default::default()
         }
     }
 
@@ -124,7 +324,7 @@ mod tests {
     fn test_apply_filters_empty() {
         let entries = vec![create_test_entry(EntryType::UserPrompt, Some("/foo/bar"), Utc::now())];
         let filter = FilterExpr::new();
-        let result = apply_filters(entries.clone(), &filter).unwrap();
+        let result = apply_filters(entries.clone(), &filter, &Bookmarks::default()).unwrap();
         assert_eq!(result.len(), entries.len());
     }
 
@@ -188,16 +388,88 @@ mod tests {
             Some("/foo"),
             Utc.with_ymd_and_hms(2024, 6, 15, 12, 0, 0).unwrap(),
         );
-        assert!(match_since(&entry, "2024-01-01")); // Entry after filter
-        assert!(match_since(&entry, "2024-06-15")); // Same day
-        assert!(!match_since(&entry, "2024-12-31")); // Entry before filter
+        assert!(match_after(&entry, "2024-01-01", Utc::now(), DisplayZone::Utc)); // Entry after filter
+        assert!(match_after(&entry, "2024-06-15", Utc::now(), DisplayZone::Utc)); // Same day
+        assert!(!match_after(&entry, "2024-12-31", Utc::now(), DisplayZone::Utc)); // Entry before filter
     }
 
     #[test]
     fn test_match_since_invalid_date() {
         let entry = create_test_entry(EntryType::UserPrompt, Some("/foo"), Utc::now());
-        assert!(!match_since(&entry, "invalid"));
-        assert!(!match_since(&entry, "2024-13-01"));
+        assert!(!match_after(&entry, "invalid", Utc::now(), DisplayZone::Utc));
+        assert!(!match_after(&entry, "2024-13-01", Utc::now(), DisplayZone::Utc));
+    }
+
+    #[test]
+    fn test_match_after_before_on() {
+        let now = Utc.with_ymd_and_hms(2024, 6, 20, 12, 0, 0).unwrap();
+        let entry = create_test_entry(
+            EntryType::UserPrompt,
+            Some("/foo"),
+            Utc.with_ymd_and_hms(2024, 6, 15, 12, 0, 0).unwrap(),
+        );
+
+        assert!(match_after(&entry, "7d", now, DisplayZone::Utc));
+        assert!(!match_after(&entry, "4d", now, DisplayZone::Utc));
+        assert!(match_before(&entry, "2024-06-16", now, DisplayZone::Utc));
+        assert!(!match_before(&entry, "2024-06-15", now, DisplayZone::Utc)); // Same day is not before
+        assert!(match_before(&entry, "2d", now, DisplayZone::Utc));
+        assert!(!match_before(&entry, "1w", now, DisplayZone::Utc));
+        assert!(match_on(&entry, "2024-06-15", now, DisplayZone::Utc));
+        assert!(!match_on(&entry, "2024-06-16", now, DisplayZone::Utc));
+        assert!(match_on(&entry, "5d", now, DisplayZone::Utc));
+        assert!(!match_on(&entry, "4d", now, DisplayZone::Utc));
+    }
+
+    #[test]
+    fn test_date_filters_use_display_zone_days() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 20, 12, 0, 0).unwrap();
+        // Shown as Jan 15 20:00 PST, though it is Jan 16 in UTC
+        let entry = create_test_entry(
+            EntryType::UserPrompt,
+            Some("/foo"),
+            Utc.with_ymd_and_hms(2024, 1, 16, 4, 0, 0).unwrap(),
+        );
+        let pacific = DisplayZone::parse("America/Los_Angeles").unwrap();
+
+        assert!(match_on(&entry, "2024-01-15", now, pacific));
+        assert!(!match_on(&entry, "2024-01-16", now, pacific));
+        assert!(match_before(&entry, "2024-01-16", now, pacific));
+        assert!(!match_after(&entry, "2024-01-16", now, pacific));
+        assert!(match_on(&entry, "2024-01-16", now, DisplayZone::Utc));
+    }
+
+    #[test]
+    fn test_apply_date_range() {
+        let entries: Vec<_> = (10..=20)
+            .map(|day| {
+                create_test_entry(
+                    EntryType::UserPrompt,
+                    Some("/foo"),
+                    Utc.with_ymd_and_hms(2024, 1, day, 9, 0, 0).unwrap(),
+                )
+            })
+            .collect();
+        let filter = crate::filters::parse_filter("after:2024-01-12 before:2024-01-15").unwrap();
+
+        let result = apply_filters(entries, &filter, &Bookmarks::default()).unwrap();
+
+        let days: Vec<_> = result.iter().map(|e| e.timestamp.format("%d").to_string()).collect();
+        assert_eq!(days, vec!["12", "13", "14"]);
+    }
+
+    #[test]
+    fn test_filter_indices_keeps_matching_positions() {
+        let entries = vec![
+            create_test_entry(EntryType::UserPrompt, Some("/foo"), Utc::now()),
+            create_test_entry(EntryType::AgentMessage, Some("/foo"), Utc::now()),
+            create_test_entry(EntryType::UserPrompt, Some("/bar"), Utc::now()),
+            create_test_entry(EntryType::UserPrompt, Some("/foo"), Utc::now()),
+        ];
+        let filter = crate::filters::parse_filter("project:foo type:user").unwrap();
+
+        let kept = filter_indices(&entries, vec![1, 2, 3], &filter, &Bookmarks::default());
+        assert_eq!(kept.unwrap(), vec![3]);
     }
 
     #[test]
@@ -206,7 +478,11 @@ mod tests {
         let mut filter = FilterExpr::new();
         filter.add_filter(FieldFilter::new(FilterField::Project, "foo".to_string()));
 
-        assert!(evaluate_filter(&entry, &filter));
+        assert!(evaluate_filter(
+            &entry,
+            &filter,
+            &EvalContext::new(&filter, &[], &Bookmarks::default()).unwrap()
+        ));
     }
 
     #[test]
@@ -222,7 +498,11 @@ mod tests {
         filter.add_operator(FilterOperator::And);
         filter.add_filter(FieldFilter::new(FilterField::Type, "user".to_string()));
 
-        assert!(evaluate_filter(&entry, &filter));
+        assert!(evaluate_filter(
+            &entry,
+            &filter,
+            &EvalContext::new(&filter, &[], &Bookmarks::default()).unwrap()
+        ));
     }
 
     #[test]
@@ -238,7 +518,11 @@ mod tests {
         filter.add_operator(FilterOperator::And);
         filter.add_filter(FieldFilter::new(FilterField::Type, "agent".to_string()));
 
-        assert!(!evaluate_filter(&entry, &filter)); // Type mismatch
+        assert!(!evaluate_filter(
+            &entry,
+            &filter,
+            &EvalContext::new(&filter, &[], &Bookmarks::default()).unwrap()
+        )); // Type mismatch
     }
 
     #[test]
@@ -250,7 +534,11 @@ mod tests {
         filter.add_operator(FilterOperator::Or);
         filter.add_filter(FieldFilter::new(FilterField::Project, "foo".to_string()));
 
-        assert!(evaluate_filter(&entry, &filter)); // Second filter matches
+        assert!(evaluate_filter(
+            &entry,
+            &filter,
+            &EvalContext::new(&filter, &[], &Bookmarks::default()).unwrap()
+        )); // Second filter matches
     }
 
     #[test]
@@ -269,7 +557,11 @@ mod tests {
         filter.add_operator(FilterOperator::And);
         filter.add_filter(FieldFilter::new(FilterField::Since, "2024-01-01".to_string()));
 
-        assert!(evaluate_filter(&entry, &filter));
+        assert!(evaluate_filter(
+            &entry,
+            &filter,
+            &EvalContext::new(&filter, &[], &Bookmarks::default()).unwrap()
+        ));
     }
 
     #[test]
@@ -296,7 +588,7 @@ mod tests {
         let mut filter = FilterExpr::new();
         filter.add_filter(FieldFilter::new(FilterField::Type, "user".to_string()));
 
-        let result = apply_filters(entries.clone(), &filter).unwrap();
+        let result = apply_filters(entries.clone(), &filter, &Bookmarks::default()).unwrap();
         assert_eq!(result.len(), 2); // Two UserPrompt entries
 
         // Filter: project:foo AND type:user
@@ -305,14 +597,218 @@ mod tests {
         filter2.add_operator(FilterOperator::And);
         filter2.add_filter(FieldFilter::new(FilterField::Type, "user".to_string()));
 
-        let result2 = apply_filters(entries.clone(), &filter2).unwrap();
+        let result2 = apply_filters(entries.clone(), &filter2, &Bookmarks::default()).unwrap();
         assert_eq!(result2.len(), 1); // Only first entry
 
         // Filter: since:2024-06-01
         let mut filter3 = FilterExpr::new();
         filter3.add_filter(FieldFilter::new(FilterField::Since, "2024-06-01".to_string()));
 
-        let result3 = apply_filters(entries, &filter3).unwrap();
+        let result3 = apply_filters(entries, &filter3, &Bookmarks::default()).unwrap();
         assert_eq!(result3.len(), 2); // First two entries
     }
+
+    #[test]
+    fn test_apply_filters_user() {
+        let mut alice = create_test_entry(EntryType::UserPrompt, None, Utc::now());
+        alice.user = Some("alice@example.com".to_string());
+        let mut bob = create_test_entry(EntryType::UserPrompt, None, Utc::now());
+        bob.user = Some("bob@example.com".to_string());
+        let unknown = create_test_entry(EntryType::UserPrompt, None, Utc::now());
+
+        let mut filter = FilterExpr::new();
+        filter.add_filter(FieldFilter::new(FilterField::User, "ALICE".to_string()));
+        let result = apply_filters(
+            vec![alice, bob.clone(), unknown.clone()],
+            &filter,
+            &Bookmarks::default(),
+        )
+        .unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].user.as_deref(), Some("alice@example.com"));
+
+        let mut filter = FilterExpr::new();
+        filter.add_filter(FieldFilter::new(FilterField::User, "example.com".to_string()));
+        assert_eq!(
+            apply_filters(vec![bob, unknown], &filter, &Bookmarks::default()).unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_apply_filters_outcome() {
+        let mut success = create_test_entry(EntryType::UserPrompt, None, Utc::now());
+        success.outcome = Some(SessionOutcome::Success);
+        let mut abandoned = create_test_entry(EntryType::AgentMessage, None, Utc::now());
+        abandoned.outcome = Some(SessionOutcome::Abandoned);
+        let unclassified = create_test_entry(EntryType::UserPrompt, None, Utc::now());
+
+        let mut filter = FilterExpr::new();
+        filter.add_filter(FieldFilter::new(FilterField::Outcome, "Abandoned".to_string()));
+        let result =
+            apply_filters(vec![success, abandoned, unclassified], &filter, &Bookmarks::default())
+                .unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].outcome, Some(SessionOutcome::Abandoned));
+    }
+
+    #[test]
+    fn test_apply_filters_source() {
+        let claude = create_test_entry(EntryType::UserPrompt, None, Utc::now());
+        let mut codex = create_test_entry(EntryType::UserPrompt, None, Utc::now());
+        codex.source = SourceKind::Codex;
+
+        let mut filter = FilterExpr::new();
+        filter.add_filter(FieldFilter::new(FilterField::Source, "CODEX".to_string()));
+        let result = apply_filters(vec![claude, codex], &filter, &Bookmarks::default()).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].source, SourceKind::Codex);
+    }
+
+    #[test]
+    fn test_apply_filters_root() {
+        let mut work = create_test_entry(EntryType::UserPrompt, None, Utc::now());
+        work.root = Some("work".to_string());
+        let mut personal = create_test_entry(EntryType::UserPrompt, None, Utc::now());
+        personal.root = Some("personal".to_string());
+        let untagged = create_test_entry(EntryType::UserPrompt, None, Utc::now());
+
+        let mut filter = FilterExpr::new();
+        filter.add_filter(FieldFilter::new(FilterField::Root, "Work".to_string()));
+        let result =
+            apply_filters(vec![work, personal, untagged], &filter, &Bookmarks::default()).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].root.as_deref(), Some("work"));
+    }
+
+    #[test]
+    fn test_apply_filters_tool() {
+        let mut bash = create_test_entry(EntryType::AgentMessage, None, Utc::now());
+        bash.tools = vec!["Read".to_string(), "Bash".to_string()];
+        let mut edit = create_test_entry(EntryType::AgentMessage, None, Utc::now());
+        edit.tools = vec!["Edit".to_string()];
+        let prompt = create_test_entry(EntryType::UserPrompt, None, Utc::now());
+
+        let mut filter = FilterExpr::new();
+        filter.add_filter(FieldFilter::new(FilterField::Tool, "bash".to_string()));
+        let result =
+            apply_filters(vec![bash, edit, prompt], &filter, &Bookmarks::default()).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].tools, vec!["Read", "Bash"]);
+    }
+
+    #[test]
+    fn test_apply_filters_has() {
+        let mut thinking =
+            create_test_entry(EntryType::AgentMessage, Some("/thinking"), Utc::now());
+        thinking.content_kinds = ContentKinds::THINKING | ContentKinds::TOOL_USE;
+        let mut failed = create_test_entry(EntryType::UserPrompt, Some("/failed"), Utc::now());
+        failed.content_kinds = ContentKinds::TOOL_RESULT;
+        failed.had_error = true;
+        // Markers in the text don't count
+        let mut quoted = create_test_entry(EntryType::UserPrompt, Some("/quoted"), Utc::now());
+        quoted.display_text = "[Thinking] pasted".to_string();
+        let entries = vec![thinking, failed, quoted];
+
+        let projects = |value: &str| {
+            let filter = crate::filters::parse_filter(value).unwrap();
+            let result = apply_filters(entries.clone(), &filter, &Bookmarks::default()).unwrap();
+            result.iter().map(|e| e.project_path.clone().unwrap()).collect::<Vec<_>>()
+        };
+        assert_eq!(projects("has:thinking"), vec![PathBuf::from("/thinking")]);
+        assert_eq!(projects("has:TOOL_USE"), vec![PathBuf::from("/thinking")]);
+        assert_eq!(projects("has:error"), vec![PathBuf::from("/failed")]);
+        assert_eq!(projects("has:tool_result -has:error"), Vec::<PathBuf>::new());
+        assert_eq!(projects("has:image"), Vec::<PathBuf>::new());
+        assert_eq!(projects("error:true"), projects("has:error"));
+        assert_eq!(projects("error:FALSE").len(), 2);
+    }
+
+    #[test]
+    fn test_apply_filters_model() {
+        let mut opus = create_test_entry(EntryType::AgentMessage, None, Utc::now());
+        opus.model = Some("claude-opus-4-1-20250805".to_string());
+        let mut sonnet = create_test_entry(EntryType::AgentMessage, None, Utc::now());
+        sonnet.model = Some("claude-sonnet-4-5-20250929".to_string());
+        let prompt = create_test_entry(EntryType::UserPrompt, None, Utc::now());
+
+        let mut filter = FilterExpr::new();
+        filter.add_filter(FieldFilter::new(FilterField::Model, "Opus".to_string()));
+        let result =
+            apply_filters(vec![opus, sonnet, prompt], &filter, &Bookmarks::default()).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].model.as_deref(), Some("claude-opus-4-1-20250805"));
+    }
+
+    #[test]
+    fn test_apply_filters_branch_and_cwd() {
+        let mut feature = create_test_entry(EntryType::UserPrompt, None, Utc::now());
+        feature.git_branch = Some("feature-x".to_string());
+        feature.cwd = Some(PathBuf::from("/work/app/crates/core"));
+        let mut main = create_test_entry(EntryType::UserPrompt, None, Utc::now());
+        main.git_branch = Some("main".to_string());
+        main.cwd = Some(PathBuf::from("/work/app"));
+        let unknown = create_test_entry(EntryType::UserPrompt, None, Utc::now());
+        let entries = vec![feature, main, unknown];
+
+        let mut filter = FilterExpr::new();
+        filter.add_filter(FieldFilter::new(FilterField::Branch, "Feature".to_string()));
+        let result = apply_filters(entries.clone(), &filter, &Bookmarks::default()).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].git_branch.as_deref(), Some("feature-x"));
+
+        let mut filter = FilterExpr::new();
+        filter.add_filter(FieldFilter::new(FilterField::Cwd, "/work/app".to_string()));
+        assert_eq!(
+            apply_filters(entries.clone(), &filter, &Bookmarks::default()).unwrap().len(),
+            2
+        );
+        let mut filter = FilterExpr::new();
+        filter.add_filter(FieldFilter::new(FilterField::Cwd, "crates/core".to_string()));
+        assert_eq!(apply_filters(entries, &filter, &Bookmarks::default()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_apply_filters_starred() {
+        let mut starred = create_test_entry(EntryType::AgentMessage, None, Utc::now());
+        starred.uuid = Some("u1".to_string());
+        let mut other = create_test_entry(EntryType::AgentMessage, None, Utc::now());
+        other.uuid = Some("u2".to_string());
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.toggle(&starred);
+
+        let mut filter = FilterExpr::new();
+        filter.add_filter(FieldFilter::new(FilterField::Starred, "true".to_string()));
+        let entries = vec![starred, other];
+        let result = apply_filters(entries.clone(), &filter, &bookmarks).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].uuid.as_deref(), Some("u1"));
+
+        let mut filter = FilterExpr::new();
+        filter.add_filter(FieldFilter::new(FilterField::Starred, "false".to_string()));
+        let result = apply_filters(entries, &filter, &bookmarks).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].uuid.as_deref(), Some("u2"));
+    }
+
+    #[test]
+    fn test_apply_filters_regex() {
+        let mut test_fn = create_test_entry(EntryType::AgentMessage, None, Utc::now());
+        test_fn.display_text = "Added fn parse_test() to the suite".to_string();
+        let mut other = create_test_entry(EntryType::AgentMessage, None, Utc::now());
+        other.display_text = "fn parse() -> Result".to_string();
+
+        let mut filter = FilterExpr::new();
+        filter.add_filter(FieldFilter::new(FilterField::Regex, r"fn \w+_test".to_string()));
+        let result =
+            apply_filters(vec![test_fn, other.clone()], &filter, &Bookmarks::default()).unwrap();
+        assert_eq!(result.len(), 1);
+        assert!(result[0].display_text.contains("parse_test"));
+
+        // Expressions built without the parser are checked when applied
+        let mut filter = FilterExpr::new();
+        filter.add_filter(FieldFilter::new(FilterField::Regex, "(".to_string()));
+        let err = apply_filters(vec![other], &filter, &Bookmarks::default()).unwrap_err();
+        assert!(err.to_string().contains("Invalid regex: '('"));
+    }
 }
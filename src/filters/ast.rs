@@ -1,3 +1,7 @@
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+
+use crate::utils::DisplayZone;
+
 /// Filter field types supported in Phase 2
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FilterField {
@@ -5,8 +9,169 @@ pub enum FilterField {
     Project,
     /// Filter by entry type (user or agent)
     Type,
-    /// Filter entries after date (YYYY-MM-DD format)
+    /// Filter entries on or after a date (same as `After`)
     Since,
+    /// Filter entries on or after a date or within an age ([`DateValue`])
+    After,
+    /// Filter entries before a date or older than an age ([`DateValue`])
+    Before,
+    /// Filter entries on a single day ([`DateValue`])
+    On,
+    /// Filter by user identity (case-insensitive partial match)
+    User,
+    /// Filter by session outcome (success, abandoned or unclear)
+    Outcome,
+    /// Filter by the tool the history comes from (claude, codex, aider or transcript)
+    Source,
+    /// Filter by the name of the Claude directory the entry was indexed from
+    Root,
+    /// Filter by the name of a tool the message called (case-insensitive)
+    Tool,
+    /// Filter by the model that wrote the message (case-insensitive partial match)
+    Model,
+    /// Filter by the git branch checked out when the message was written (case-insensitive
+    /// partial match)
+    Branch,
+    /// Filter by the working directory the message was written in (supports ~ and partial
+    /// matches)
+    Cwd,
+    /// Filter by whether the entry is starred ([`crate::bookmarks`])
+    Starred,
+    /// Filter by whether the message carried a tool result flagged as an error
+    Error,
+    /// Filter by a regular expression matched against the entry text
+    Regex,
+    /// Filter by the length of the entry text in characters ([`SizeValue`])
+    Len,
+    /// Filter by the number of entries in the entry's session ([`SizeValue`])
+    Messages,
+    /// Filter by a kind of content block in the message (thinking, tool_use, tool_result,
+    /// image) or a tool result flagged as an error
+    Has,
+}
+
+impl FilterField {
+    /// Whether the value is a [`DateValue`]
+    pub fn is_date(&self) -> bool {
+        matches!(
+            self,
+            FilterField::Since | FilterField::After | FilterField::Before | FilterField::On
+        )
+    }
+
+    /// Whether the value is a [`SizeValue`]
+    pub fn is_size(&self) -> bool {
+        matches!(self, FilterField::Len | FilterField::Messages)
+    }
+}
+
+/// How a [`SizeValue`] compares a count with its amount
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Equal => "=",
+            Comparison::GreaterOrEqual => ">=",
+            Comparison::Greater => ">",
+        }
+    }
+}
+
+/// Value of a size field: a comparison and a count (`>1000`, `<=20`, `=3`, or `3` for `=3`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeValue {
+    pub comparison: Comparison,
+    pub amount: usize,
+}
+
+impl SizeValue {
+    /// Parse an optional `<`, `<=`, `=`, `>=` or `>` followed by a non-negative number
+    pub fn parse(value: &str) -> Option<Self> {
+        let (comparison, amount) = [
+            ("<=", Comparison::LessOrEqual),
+            (">=", Comparison::GreaterOrEqual),
+            ("<", Comparison::Less),
+            (">", Comparison::Greater),
+            ("=", Comparison::Equal),
+        ]
+        .into_iter()
+        .find_map(|(symbol, comparison)| Some((comparison, value.strip_prefix(symbol)?)))
+        .unwrap_or((Comparison::Equal, value));
+        // `parse` would take a sign; only digits are a count
+        if amount.is_empty() || !amount.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        Some(Self { comparison, amount: amount.parse().ok()? })
+    }
+
+    /// Whether `count` satisfies the comparison
+    pub fn matches(&self, count: usize) -> bool {
+        match self.comparison {
+            Comparison::Less => count < self.amount,
+            Comparison::LessOrEqual => count <= self.amount,
+            Comparison::Equal => count == self.amount,
+            Comparison::GreaterOrEqual => count >= self.amount,
+            Comparison::Greater => count > self.amount,
+        }
+    }
+}
+
+/// Value of a date field: a calendar day (`2024-01-15`, in the display zone) or an age
+/// relative to now (`12h`, `7d`, `2w`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateValue {
+    Day(NaiveDate),
+    Ago(TimeDelta),
+}
+
+impl DateValue {
+    /// Parse `YYYY-MM-DD` or a positive number followed by `h`, `d` or `w`
+    pub fn parse(value: &str) -> Option<Self> {
+        // Strict YYYY-MM-DD; chrono also rejects impossible dates such as 2024-02-31
+        if value.len() == 10
+            && let Ok(day) = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        {
+            return Some(DateValue::Day(day));
+        }
+        let unit = value.chars().last()?;
+        let amount: i64 = value[..value.len() - unit.len_utf8()].parse().ok()?;
+        if amount <= 0 {
+            return None;
+        }
+        let age = match unit.to_ascii_lowercase() {
+            'h' => TimeDelta::try_hours(amount),
+            'd' => TimeDelta::try_days(amount),
+            'w' => TimeDelta::try_weeks(amount),
+            _ => None,
+        }?;
+        Some(DateValue::Ago(age))
+    }
+
+    /// Start of the value: midnight of the day in `zone`, or `now` minus the age
+    pub fn start(&self, now: DateTime<Utc>, zone: DisplayZone) -> DateTime<Utc> {
+        match self {
+            DateValue::Day(day) => zone.start_of_day(*day),
+            DateValue::Ago(age) => now.checked_sub_signed(*age).unwrap_or(DateTime::<Utc>::MIN_UTC),
+        }
+    }
+
+    /// The day of the value in `zone` (for ages, the day `now` minus the age falls on)
+    pub fn day(&self, now: DateTime<Utc>, zone: DisplayZone) -> NaiveDate {
+        match self {
+            DateValue::Day(day) => *day,
+            DateValue::Ago(_) => zone.date(&self.start(now, zone)),
+        }
+    }
 }
 
 /// Logical operators for combining filters
@@ -31,21 +196,49 @@ impl FieldFilter {
     }
 }
 
+/// Parenthesized groups and negations of a filter's clauses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterNode {
+    /// The clause `filters[i]` of the expression
+    Clause(usize),
+    /// Matches the entries the node doesn't (`NOT` or a `-` prefix)
+    Not(Box<FilterNode>),
+    /// Nodes evaluated left to right, each joined to the ones before it by the expression's
+    /// operator in front of its first clause
+    Group(Vec<FilterNode>),
+}
+
+impl FilterNode {
+    /// Index of the first clause in the node
+    pub fn first_clause(&self) -> usize {
+        match self {
+            FilterNode::Clause(i) => *i,
+            FilterNode::Not(node) => node.first_clause(),
+            FilterNode::Group(nodes) => nodes.first().map_or(0, FilterNode::first_clause),
+        }
+    }
+}
+
 /// Filter expression combining multiple field filters with operators
 ///
-/// Phase 2 limitation: No parentheses support
 /// - Same-field filters are OR'd together: project:foo project:bar → (foo OR bar)
 /// - Cross-field filters are AND'd together: project:foo type:user → (foo AND user)
 /// - Explicit operators override defaults
+/// - Operators apply left to right; parentheses group clauses and `NOT` negates them, as
+///   recorded in `tree`
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FilterExpr {
+    /// Every clause, in the order written
     pub filters: Vec<FieldFilter>,
+    /// `operators[i]` joins `filters[i + 1]` (or the group it opens) to what precedes it
     pub operators: Vec<FilterOperator>,
+    /// Groups and negations; `None` when the clauses simply apply left to right
+    pub tree: Option<FilterNode>,
 }
 
 impl FilterExpr {
     pub fn new() -> Self {
-        Self { filters: Vec::new(), operators: Vec::new() }
+        Self { filters: Vec::new(), operators: Vec::new(), tree: None }
     }
 
     pub fn add_filter(&mut self, filter: FieldFilter) {
@@ -59,6 +252,11 @@ impl FilterExpr {
     pub fn is_empty(&self) -> bool {
         self.filters.is_empty()
     }
+
+    /// Whether any clause filters on `field`
+    pub fn uses(&self, field: FilterField) -> bool {
+        self.filters.iter().any(|f| f.field == field)
+    }
 }
 
 impl Default for FilterExpr {
@@ -105,6 +303,55 @@ mod tests {
         assert_eq!(expr.operators[0], FilterOperator::And);
     }
 
+    #[test]
+    fn test_date_value_parse() {
+        let day = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        assert_eq!(DateValue::parse("2024-01-15"), Some(DateValue::Day(day)));
+        assert_eq!(DateValue::parse("7d"), Some(DateValue::Ago(TimeDelta::days(7))));
+        assert_eq!(DateValue::parse("12H"), Some(DateValue::Ago(TimeDelta::hours(12))));
+        assert_eq!(DateValue::parse("2w"), Some(DateValue::Ago(TimeDelta::weeks(2))));
+        for invalid in
+            ["2024-02-31", "2024-1-15", "0d", "-3d", "7", "d", "7y", "", "99999999999999w"]
+        {
+            assert_eq!(DateValue::parse(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_size_value_parse_and_match() {
+        let parse = |value| SizeValue::parse(value).unwrap();
+        assert_eq!(parse(">1000"), SizeValue { comparison: Comparison::Greater, amount: 1000 });
+        assert_eq!(parse("<=20").comparison, Comparison::LessOrEqual);
+        assert_eq!(parse("3"), SizeValue { comparison: Comparison::Equal, amount: 3 });
+        assert_eq!(parse("=0").amount, 0);
+        for invalid in [">", "", "<-5", ">+5", "1k", ">=>3", "=<3", "99999999999999999999999"] {
+            assert_eq!(SizeValue::parse(invalid), None, "{invalid}");
+        }
+
+        assert!(parse(">10").matches(11));
+        assert!(!parse(">10").matches(10));
+        assert!(parse(">=10").matches(10));
+        assert!(parse("<10").matches(9) && !parse("<10").matches(10));
+        assert!(parse("<=10").matches(10));
+        assert!(parse("10").matches(10) && !parse("10").matches(9));
+    }
+
+    #[test]
+    fn test_date_value_start_and_day() {
+        let now = DateTime::parse_from_rfc3339("2024-06-15T08:00:00Z").unwrap().to_utc();
+        let ago = DateValue::Ago(TimeDelta::hours(10));
+        let utc = DisplayZone::Utc;
+        assert_eq!(ago.start(now, utc).to_rfc3339(), "2024-06-14T22:00:00+00:00");
+        assert_eq!(ago.day(now, utc), NaiveDate::from_ymd_opt(2024, 6, 14).unwrap());
+
+        let day = DateValue::parse("2024-01-15").unwrap();
+        assert_eq!(day.start(now, utc).to_rfc3339(), "2024-01-15T00:00:00+00:00");
+
+        let berlin = DisplayZone::parse("Europe/Berlin").unwrap();
+        assert_eq!(ago.day(now, berlin), NaiveDate::from_ymd_opt(2024, 6, 15).unwrap());
+        assert_eq!(day.start(now, berlin).to_rfc3339(), "2024-01-14T23:00:00+00:00");
+    }
+
     #[test]
     fn test_filter_expr_default() {
         let expr = FilterExpr::default();
@@ -0,0 +1,512 @@
+//! Filter explanations for the `explain` command.
+//!
+//! Breaks a parsed filter into its clauses, states what each clause matches, whether each
+//! operator was written or implied, and counts how many entries each clause matches on its
+//! own, which shows which clause empties a result set.
+
+use anyhow::Result;
+
+use super::apply::{EvalContext, evaluate_field_filter, evaluate_filter};
+use super::ast::{
+    Comparison, DateValue, FieldFilter, FilterExpr, FilterField, FilterNode, FilterOperator,
+    SizeValue,
+};
+use super::parser::{Span, Token, TokenKind, quote_value};
+use crate::bookmarks::Bookmarks;
+use crate::models::SearchEntry;
+
+/// One clause of a filter and how many entries it matches alone
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClauseExplanation {
+    pub filter: FieldFilter,
+    /// Whether the clause itself is negated (`NOT clause` or `-clause`); `matches` and
+    /// `semantics` are then those of the negation
+    pub negated: bool,
+    pub semantics: String,
+    pub matches: usize,
+}
+
+impl ClauseExplanation {
+    /// The clause as written with a quoted value, e.g. `project:"my app"` or `NOT has:"image"`
+    pub fn label(&self) -> String {
+        if self.negated { format!("NOT {}", self.written()) } else { self.written() }
+    }
+
+    /// The clause without its negation
+    fn written(&self) -> String {
+        let name = field_name(&self.filter.field);
+        match SizeValue::parse(&self.filter.value) {
+            Some(size) if self.filter.field.is_size() => {
+                format!("{}{}{}", name, size.comparison.symbol(), size.amount)
+            }
+            _ => format!("{}:{}", name, quote_value(&self.filter.value)),
+        }
+    }
+}
+
+/// Operator between two clauses and whether it was written out or implied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperatorExplanation {
+    pub operator: FilterOperator,
+    pub explicit: bool,
+}
+
+/// Per-clause breakdown of a filter evaluated against an index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterExplanation {
+    pub clauses: Vec<ClauseExplanation>,
+    /// `operators[i]` joins `clauses[i]` (or everything before it) and `clauses[i + 1]`
+    pub operators: Vec<OperatorExplanation>,
+    /// Entries matching the whole filter
+    pub matches: usize,
+    /// Entries the filter was evaluated against
+    pub total: usize,
+    /// Parenthesized groups and negations of the clauses, if any
+    pub tree: Option<FilterNode>,
+}
+
+impl FilterExplanation {
+    /// The expression with its left-to-right grouping made explicit,
+    /// e.g. `(project:"a" OR project:"b") AND type:"user"`
+    pub fn grouped(&self) -> String {
+        match &self.tree {
+            Some(tree) => self.render(tree),
+            None => self.render(&FilterNode::Group(
+                (0..self.clauses.len()).map(FilterNode::Clause).collect(),
+            )),
+        }
+    }
+
+    fn render(&self, node: &FilterNode) -> String {
+        match node {
+            FilterNode::Clause(i) => self.clauses.get(*i).map(|c| c.written()).unwrap_or_default(),
+            FilterNode::Not(node) => format!("NOT {}", self.render_operand(node)),
+            FilterNode::Group(nodes) => {
+                let mut out = String::new();
+                for (i, node) in nodes.iter().enumerate() {
+                    if i > 0 {
+                        let operator = node
+                            .first_clause()
+                            .checked_sub(1)
+                            .and_then(|i| self.operators.get(i))
+                            .map(|operator| &operator.operator);
+                        let operator = match operator {
+                            Some(FilterOperator::Or) => "OR",
+                            _ => "AND",
+                        };
+                        if i > 1 {
+                            out = format!("({})", out);
+                        }
+                        out.push_str(&format!(" {} ", operator));
+                    }
+                    out.push_str(&self.render_operand(node));
+                }
+                out
+            }
+        }
+    }
+
+    /// A node as an operand, in parentheses if it joins several
+    fn render_operand(&self, node: &FilterNode) -> String {
+        match node {
+            FilterNode::Group(nodes) if nodes.len() > 1 => format!("({})", self.render(node)),
+            _ => self.render(node),
+        }
+    }
+}
+
+/// Explain `expr`, parsed from `tokens`, against `entries`, checking `starred:` against
+/// `bookmarks`
+///
+/// The tokens tell which operators were written out; pass the tokens of the same input the
+/// expression was parsed from.
+///
+/// # Errors
+///
+/// Returns an error if a `regex:` value is not a valid regular expression (never the case for
+/// expressions from [`parse_filter`](super::parse_filter)).
+pub fn explain_filter(
+    expr: &FilterExpr,
+    tokens: &[Token],
+    entries: &[SearchEntry],
+    bookmarks: &Bookmarks,
+) -> Result<FilterExplanation> {
+    let ctx = EvalContext::new(expr, entries, bookmarks)?;
+    let mut negated = vec![false; expr.filters.len()];
+    if let Some(tree) = &expr.tree {
+        mark_negated(tree, false, &mut negated);
+    }
+    let clauses = expr
+        .filters
+        .iter()
+        .zip(negated)
+        .map(|(filter, negated)| {
+            let semantics = clause_semantics(filter);
+            ClauseExplanation {
+                filter: filter.clone(),
+                negated,
+                semantics: if negated {
+                    format!("everything except: {}", semantics)
+                } else {
+                    semantics
+                },
+                matches: entries
+                    .iter()
+                    .filter(|e| evaluate_field_filter(e, filter, &ctx) != negated)
+                    .count(),
+            }
+        })
+        .collect();
+
+    let explicit = explicit_operators(tokens);
+    let operators = expr
+        .operators
+        .iter()
+        .enumerate()
+        .map(|(i, operator)| OperatorExplanation {
+            operator: operator.clone(),
+            explicit: explicit.get(i).copied().unwrap_or(false),
+        })
+        .collect();
+
+    Ok(FilterExplanation {
+        clauses,
+        operators,
+        matches: entries.iter().filter(|e| evaluate_filter(e, expr, &ctx)).count(),
+        total: entries.len(),
+        tree: expr.tree.clone(),
+    })
+}
+
+/// Record which clauses `node` negates on their own, `negated` being whether `node` is
+///
+/// A negated group of several clauses negates none of them alone; the grouped expression
+/// shows its `NOT`.
+fn mark_negated(node: &FilterNode, negated: bool, out: &mut [bool]) {
+    match node {
+        FilterNode::Clause(i) => {
+            if let Some(slot) = out.get_mut(*i) {
+                *slot = negated;
+            }
+        }
+        FilterNode::Not(node) => mark_negated(node, !negated, out),
+        FilterNode::Group(nodes) if nodes.len() == 1 => mark_negated(&nodes[0], negated, out),
+        FilterNode::Group(nodes) => {
+            for node in nodes {
+                mark_negated(node, false, out);
+            }
+        }
+    }
+}
+
+/// What a single clause matches, in words
+pub fn clause_semantics(filter: &FieldFilter) -> String {
+    match filter.field {
+        FilterField::Project => format!(
+            "project path contains {:?} (case-insensitive, ~ expands to the home directory); \
+             entries without a project never match",
+            filter.value
+        ),
+        FilterField::Type => match filter.value.to_lowercase().as_str() {
+            "user" => "entry is a user prompt".to_string(),
+            _ => "entry is an agent message".to_string(),
+        },
+        FilterField::Since | FilterField::After => match DateValue::parse(&filter.value) {
+            Some(DateValue::Ago(_)) => format!("timestamp is within the last {}", filter.value),
+            _ => format!("timestamp is on or after {} 00:00 (display time zone)", filter.value),
+        },
+        FilterField::Before => match DateValue::parse(&filter.value) {
+            Some(DateValue::Ago(_)) => format!("timestamp is more than {} ago", filter.value),
+            _ => format!("timestamp is before {} 00:00 (display time zone)", filter.value),
+        },
+        FilterField::On => match DateValue::parse(&filter.value) {
+            Some(DateValue::Ago(_)) => format!("timestamp is on the day {} ago", filter.value),
+            _ => format!("timestamp is on {} (display time zone)", filter.value),
+        },
+        FilterField::User => format!(
+            "user identity contains {:?} (case-insensitive); entries without a recorded user \
+             never match",
+            filter.value
+        ),
+        FilterField::Outcome => format!(
+            "session outcome is {} (a guess from how the session ended); prompts without a \
+             conversation file never match",
+            filter.value.to_lowercase()
+        ),
+        FilterField::Source => {
+            format!("entry comes from the {} history", filter.value.to_lowercase())
+        }
+        FilterField::Root => format!(
+            "entry was indexed from the Claude directory named {:?} (case-insensitive)",
+            filter.value
+        ),
+        FilterField::Tool => format!(
+            "message calls the tool {:?} (case-insensitive); only Claude Code conversations \
+             record tool calls",
+            filter.value
+        ),
+        FilterField::Model => format!(
+            "message was written by a model whose name contains {:?} (case-insensitive); \
+             only agent messages of Claude Code conversations record a model",
+            filter.value
+        ),
+        FilterField::Branch => format!(
+            "git branch contains {:?} (case-insensitive); only Claude Code conversations \
+             record the branch",
+            filter.value
+        ),
+        FilterField::Cwd => format!(
+            "working directory contains {:?} (case-insensitive, ~ is the home directory); \
+             history.jsonl prompts and aider and transcript entries never match",
+            filter.value
+        ),
+        FilterField::Starred => match filter.value.to_lowercase().as_str() {
+            "true" => "entry is starred".to_string(),
+            _ => "entry is not starred".to_string(),
+        },
+        FilterField::Error => match filter.value.to_lowercase().as_str() {
+            "true" => "message has a tool result flagged as an error (Claude Code conversations)"
+                .to_string(),
+            _ => "message has no tool result flagged as an error".to_string(),
+        },
+        FilterField::Regex => {
+            format!("entry text matches the regular expression {:?} (case-sensitive)", filter.value)
+        }
+        FilterField::Has => match filter.value.to_lowercase().as_str() {
+            "error" => "message has a tool result flagged as an error".to_string(),
+            kind => format!(
+                "message has {} {} block; only Claude Code conversations record content blocks",
+                if kind.starts_with(['a', 'e', 'i', 'o', 'u']) { "an" } else { "a" },
+                kind
+            ),
+        },
+        FilterField::Len => {
+            format!("entry text is {} characters long", size_in_words(&filter.value))
+        }
+        FilterField::Messages => format!(
+            "entry's session has {} entries (counting the indexed prompts and messages)",
+            size_in_words(&filter.value)
+        ),
+    }
+}
+
+/// A size comparison like `>1000` as "more than 1000"
+fn size_in_words(value: &str) -> String {
+    let Some(size) = SizeValue::parse(value) else {
+        return value.to_string();
+    };
+    let comparison = match size.comparison {
+        Comparison::Less => "fewer than",
+        Comparison::LessOrEqual => "at most",
+        Comparison::Equal => "exactly",
+        Comparison::GreaterOrEqual => "at least",
+        Comparison::Greater => "more than",
+    };
+    format!("{} {}", comparison, size.amount)
+}
+
+/// Mark `span` of `input` with carets on the line below it
+pub fn mark_span(input: &str, span: Span) -> String {
+    let before = input.get(..span.start).unwrap_or(input).chars().count();
+    let width = span.text(input).chars().count().max(1);
+    format!("{}\n{}{}", input, " ".repeat(before), "^".repeat(width))
+}
+
+/// For each operator between clauses, whether it was written as AND/OR
+fn explicit_operators(tokens: &[Token]) -> Vec<bool> {
+    let mut explicit = Vec::new();
+    let mut seen_clause = false;
+    let mut pending_keyword = false;
+    for token in tokens {
+        match token.kind {
+            TokenKind::FieldValue { .. } => {
+                if seen_clause {
+                    explicit.push(pending_keyword);
+                }
+                seen_clause = true;
+                pending_keyword = false;
+            }
+            TokenKind::And | TokenKind::Or => pending_keyword = true,
+            TokenKind::Not | TokenKind::LParen | TokenKind::RParen => {}
+        }
+    }
+    explicit
+}
+
+fn field_name(field: &FilterField) -> &'static str {
+    match field {
+        FilterField::Project => "project",
+        FilterField::Type => "type",
+        FilterField::Since => "since",
+        FilterField::After => "after",
+        FilterField::Before => "before",
+        FilterField::On => "on",
+        FilterField::User => "user",
+        FilterField::Outcome => "outcome",
+        FilterField::Source => "source",
+        FilterField::Root => "root",
+        FilterField::Tool => "tool",
+        FilterField::Model => "model",
+        FilterField::Branch => "branch",
+        FilterField::Cwd => "cwd",
+        FilterField::Starred => "starred",
+        FilterField::Error => "error",
+        FilterField::Regex => "regex",
+        FilterField::Len => "len",
+        FilterField::Messages => "messages",
+        FilterField::Has => "has",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+    use crate::filters::parser::{parse_filter, tokenize};
+    use crate::models::{ContentKinds, EntryType};
+
+    fn entry(entry_type: EntryType, project: &str) -> SearchEntry {
+        SearchEntry {
+            entry_type,
+            display_text: "text".to_string(),
+            timestamp: Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap(),
+            project_path: Some(PathBuf::from(project)),
+            session_id: "test-session".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn explain(input: &str, entries: &[SearchEntry]) -> FilterExplanation {
+        let (expr, tokens) = (parse_filter(input).unwrap(), tokenize(input).unwrap());
+        explain_filter(&expr, &tokens, entries, &Bookmarks::default()).unwrap()
+    }
+
+    #[test]
+    fn test_explain_counts_each_clause_alone() {
+        let entries = vec![
+            entry(EntryType::UserPrompt, "/work/app"),
+            entry(EntryType::AgentMessage, "/work/app"),
+            entry(EntryType::UserPrompt, "/work/lib"),
+        ];
+
+        let explanation = explain("project:app type:user user:alice", &entries);
+
+        let counts: Vec<usize> = explanation.clauses.iter().map(|c| c.matches).collect();
+        assert_eq!(counts, vec![2, 2, 0]);
+        assert_eq!(explanation.matches, 0);
+        assert_eq!(explanation.total, 3);
+    }
+
+    #[test]
+    fn test_explain_marks_implicit_operators() {
+        let explanation = explain("project:a project:b AND type:user", &[]);
+
+        assert_eq!(
+            explanation.operators,
+            vec![
+                OperatorExplanation { operator: FilterOperator::Or, explicit: false },
+                OperatorExplanation { operator: FilterOperator::And, explicit: true },
+            ]
+        );
+        assert_eq!(explanation.grouped(), r#"(project:"a" OR project:"b") AND type:"user""#);
+    }
+
+    #[test]
+    fn test_explain_groups_and_negations() {
+        let entries = vec![
+            entry(EntryType::UserPrompt, "/work/app"),
+            entry(EntryType::AgentMessage, "/work/app"),
+            entry(EntryType::UserPrompt, "/work/lib"),
+        ];
+
+        let explanation =
+            explain("type:agent OR (project:lib OR project:web) -type:user", &entries);
+        assert_eq!(
+            explanation.grouped(),
+            r#"(type:"agent" OR (project:"lib" OR project:"web")) AND NOT type:"user""#
+        );
+        assert_eq!(explanation.matches, 1);
+
+        let explanation = explain("NOT (project:app type:user)", &entries);
+        assert_eq!(explanation.grouped(), r#"NOT (project:"app" AND type:"user")"#);
+        assert_eq!(explanation.matches, 2);
+        // Only a clause negated on its own is labelled and counted as negated
+        assert!(explanation.clauses.iter().all(|c| !c.negated));
+    }
+
+    #[test]
+    fn test_explain_negated_clause() {
+        let mut entries = vec![
+            entry(EntryType::UserPrompt, "/work/app"),
+            entry(EntryType::UserPrompt, "/work/app"),
+            entry(EntryType::AgentMessage, "/work/lib"),
+        ];
+        entries[0].content_kinds = ContentKinds::IMAGE;
+
+        let explanation = explain("type:user -has:image", &entries);
+        assert_eq!(explanation.grouped(), r#"type:"user" AND NOT has:"image""#);
+        let labels: Vec<String> = explanation.clauses.iter().map(|c| c.label()).collect();
+        assert_eq!(labels, vec![r#"type:"user""#, r#"NOT has:"image""#]);
+        let counts: Vec<usize> = explanation.clauses.iter().map(|c| c.matches).collect();
+        assert_eq!(counts, vec![2, 2]);
+        assert_eq!(
+            explanation.clauses[1].semantics,
+            "everything except: message has an image block; only Claude Code conversations \
+             record content blocks"
+        );
+        assert_eq!(explanation.matches, 1);
+
+        let explanation = explain("NOT NOT has:image", &entries);
+        assert_eq!(explanation.grouped(), r#"NOT NOT has:"image""#);
+        assert_eq!(explanation.clauses[0].label(), r#"has:"image""#);
+        assert_eq!(explanation.clauses[0].matches, 1);
+    }
+
+    #[test]
+    fn test_explain_sizes() {
+        let mut entries = vec![
+            entry(EntryType::UserPrompt, "/work/app"),
+            entry(EntryType::AgentMessage, "/work/app"),
+            entry(EntryType::UserPrompt, "/work/lib"),
+        ];
+        entries[2].session_id = "other".to_string();
+
+        let explanation = explain("messages>=2 len:4", &entries);
+        assert_eq!(explanation.grouped(), "messages>=2 AND len=4");
+        let counts: Vec<usize> = explanation.clauses.iter().map(|c| c.matches).collect();
+        assert_eq!(counts, vec![2, 3]);
+
+        let len = FieldFilter::new(FilterField::Len, ">1000".to_string());
+        assert_eq!(clause_semantics(&len), "entry text is more than 1000 characters long");
+    }
+
+    #[test]
+    fn test_clause_semantics() {
+        let since = FieldFilter::new(FilterField::Since, "2024-01-01".to_string());
+        assert_eq!(
+            clause_semantics(&since),
+            "timestamp is on or after 2024-01-01 00:00 (display time zone)"
+        );
+        let after = FieldFilter::new(FilterField::After, "7d".to_string());
+        assert_eq!(clause_semantics(&after), "timestamp is within the last 7d");
+        let before = FieldFilter::new(FilterField::Before, "2024-01-15".to_string());
+        assert_eq!(
+            clause_semantics(&before),
+            "timestamp is before 2024-01-15 00:00 (display time zone)"
+        );
+        let on = FieldFilter::new(FilterField::On, "2024-01-15".to_string());
+        assert_eq!(clause_semantics(&on), "timestamp is on 2024-01-15 (display time zone)");
+        let agent = FieldFilter::new(FilterField::Type, "AGENT".to_string());
+        assert_eq!(clause_semantics(&agent), "entry is an agent message");
+    }
+
+    #[test]
+    fn test_mark_span() {
+        let input = "project:é after:7d";
+        assert_eq!(mark_span(input, Span::new(11, 16)), format!("{}\n          ^^^^^", input));
+        assert_eq!(mark_span("a", Span::new(1, 1)), "a\n ^");
+    }
+}
@@ -1,7 +1,11 @@
 pub mod apply;
 pub mod ast;
+pub mod explain;
+pub mod page;
 pub mod parser;
 
-pub use apply::apply_filters;
+pub use apply::{apply_filters, filter_indices};
 pub use ast::{FieldFilter, FilterExpr, FilterField, FilterOperator};
-pub use parser::parse_filter;
+pub use explain::{FilterExplanation, explain_filter, mark_span};
+pub use page::{Cursor, Page, paginate};
+pub use parser::{FilterSyntaxError, Span, Token, TokenKind, parse_filter, quote_value, tokenize};
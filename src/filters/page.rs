@@ -0,0 +1,148 @@
+//! Cursor-based pagination of query results.
+//!
+//! Offsets drift when the index is refreshed between two pages: new entries push older ones
+//! down and a page repeats or skips results. A [`Cursor`] instead remembers the last entry a
+//! page ended on (its timestamp and uuid) and the next page starts right after it in
+//! newest-first order, so entries indexed in the meantime never shift the pages that follow.
+
+use std::cmp::Reverse;
+
+use anyhow::{Context, Result, bail};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, SubsecRound, Utc};
+
+use crate::models::SearchEntry;
+
+/// Position after the last entry of a page
+///
+/// Its string form is opaque to consumers: pass back what [`Page::next_cursor`] returned.
+/// Cursors order like the entries they were made from, oldest first.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Cursor {
+    timestamp: DateTime<Utc>,
+    key: String,
+}
+
+impl Cursor {
+    /// Cursor pointing just after `entry`
+    pub fn after(entry: &SearchEntry) -> Self {
+        // Encoded cursors keep milliseconds; truncate so a decoded cursor equals the original
+        let timestamp = entry.timestamp.trunc_subsecs(3);
+        Self { timestamp, key: entry_key(entry).to_string() }
+    }
+
+    pub fn encode(&self) -> String {
+        let raw = format!("{}:{}", self.timestamp.timestamp_millis(), self.key);
+        URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    /// Parse a cursor returned by [`Cursor::encode`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `cursor` was not produced by [`Cursor::encode`].
+    pub fn decode(cursor: &str) -> Result<Self> {
+        let invalid = || format!("Invalid cursor: '{}'", cursor);
+        let raw =
+            URL_SAFE_NO_PAD.decode(cursor.trim()).ok().and_then(|b| String::from_utf8(b).ok());
+        let Some((millis, key)) = raw.as_deref().and_then(|raw| raw.split_once(':')) else {
+            bail!(invalid());
+        };
+        let timestamp =
+            millis.parse().ok().and_then(DateTime::from_timestamp_millis).with_context(invalid)?;
+        Ok(Self { timestamp, key: key.to_string() })
+    }
+
+    /// Sort key of the cursor position, comparable with [`sort_key`]
+    fn sort_key(&self) -> (Reverse<DateTime<Utc>>, Reverse<&str>) {
+        (Reverse(self.timestamp), Reverse(self.key.as_str()))
+    }
+}
+
+/// One page of results
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    pub entries: Vec<SearchEntry>,
+    /// Cursor for the following page; `None` on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// The page of at most `limit` entries following `after` (or the first page)
+///
+/// Entries are returned newest first; entries with the same timestamp are ordered by uuid
+/// (session id for history prompts, which have none) so that pages never overlap.
+pub fn paginate(mut entries: Vec<SearchEntry>, after: Option<&Cursor>, limit: usize) -> Page {
+    entries.sort_by(|a, b| sort_key(a).cmp(&sort_key(b)));
+    let start = after
+        .map_or(0, |cursor| entries.partition_point(|entry| sort_key(entry) <= cursor.sort_key()));
+    let end = start.saturating_add(limit).min(entries.len());
+    let next_cursor =
+        (end < entries.len() && end > start).then(|| Cursor::after(&entries[end - 1]).encode());
+    entries.truncate(end);
+    entries.drain(..start);
+    Page { entries, next_cursor }
+}
+
+/// Identity of an entry among entries with the same timestamp
+fn entry_key(entry: &SearchEntry) -> &str {
+    entry.uuid.as_deref().unwrap_or(&entry.session_id)
+}
+
+fn sort_key(entry: &SearchEntry) -> (Reverse<DateTime<Utc>>, Reverse<&str>) {
+    (Reverse(entry.timestamp), Reverse(entry_key(entry)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::EntryType;
+
+    fn entry(uuid: &str, secs: i64) -> SearchEntry {
+        SearchEntry {
+            entry_type: EntryType::AgentMessage,
+            display_text: uuid.to_string(),
+            timestamp: DateTime::from_timestamp(secs, 0).unwrap(),
+            session_id: "s1".to_string(),
+            uuid: Some(uuid.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn texts(page: &Page) -> Vec<&str> {
+        page.entries.iter().map(|e| e.display_text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_pages_stay_stable_when_entries_are_added() {
+        let index = vec![entry("a", 300), entry("b", 200), entry("c", 200), entry("d", 100)];
+
+        let first = paginate(index.clone(), None, 2);
+        assert_eq!(texts(&first), vec!["a", "c"]);
+        let cursor = Cursor::decode(first.next_cursor.as_deref().unwrap()).unwrap();
+
+        // New entries indexed between two requests don't shift the next page
+        let mut refreshed = index.clone();
+        refreshed.insert(0, entry("new", 400));
+        let second = paginate(refreshed, Some(&cursor), 2);
+        assert_eq!(texts(&second), vec!["b", "d"]);
+        assert_eq!(second.next_cursor, None);
+    }
+
+    #[test]
+    fn test_cursor_round_trip_and_errors() {
+        let cursor = Cursor::after(&entry("550e8400:x", 1_700_000_000));
+        assert_eq!(Cursor::decode(&cursor.encode()).unwrap(), cursor);
+
+        let err = Cursor::decode("not a cursor").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid cursor: 'not a cursor'");
+        assert!(Cursor::decode(&URL_SAFE_NO_PAD.encode("soon:a")).is_err());
+    }
+
+    #[test]
+    fn test_zero_limit_has_no_next_page() {
+        let page = paginate(vec![entry("a", 1)], None, 0);
+        assert!(page.entries.is_empty());
+        assert_eq!(page.next_cursor, None);
+    }
+}
@@ -6,17 +6,37 @@
 //! # Syntax
 //!
 //! ```text
-//! filter_expr := field_filter (operator field_filter)*
-//! field_filter := field_name:value | field_name:"quoted value"
+//! filter_expr := operand (operator? operand)*
+//! operand := field_filter | (NOT | -) operand | "(" filter_expr ")"
+//! field_filter := field_name:value | field_name:"quoted value" | size_name comparison number
+//! comparison := < | <= | = | >= | >
 //! operator := AND | OR (case-insensitive)
-//! field_name := project | type | since (case-insensitive)
+//! field_name := project | type | since | after | before | on | user (case-insensitive)
+//! size_name := len | messages (case-insensitive)
 //! ```
 //!
 //! # Supported Fields
 //!
 //! - `project:path` - Filter by project path (supports ~ expansion and partial matches)
 //! - `type:user|agent` - Filter by entry type (user prompts or agent messages)
-//! - `since:YYYY-MM-DD` - Filter by timestamp (entries on or after date)
+//! - `after:DATE` - Entries on or after the date (`since:` is an alias)
+//! - `before:DATE` - Entries before the date
+//! - `on:DATE` - Entries on the date
+//!
+//!   `DATE` is a day in the display zone (`YYYY-MM-DD`) or an age relative to now: `12h`, `7d`, `2w`. So
+//!   `after:7d` is the last week and `on:1d` is yesterday.
+//! - `user:name` - Filter by user identity recorded in the conversation (partial match)
+//! - `root:name` - Filter by the name of the Claude directory (`--claude-dir name=path`)
+//! - `starred:true|false` - Filter by whether the entry is starred in the TUI
+//! - `error:true|false` - Filter by whether the message carried a tool result flagged as an
+//!   error
+//! - `len>N` - Entries whose text is longer than N characters (also `<`, `<=`, `=`, `>=`)
+//! - `has:KIND` - Messages with a thinking, tool_use, tool_result or image block, or a tool
+//!   result flagged as an error (`has:error`)
+//! - `messages>N` - Entries of sessions with more than N entries (also `<`, `<=`, `=`, `>=`)
+//!
+//!   `len:>N` is the same as `len>N`, and two sizes of the same field without an operator
+//!   are a range: `len>=100 len<1000`.
 //!
 //! # Examples
 //!
@@ -39,47 +59,149 @@
 //!
 //! // Complex query
 //! let expr = parse_filter("project:ai-explorer AND type:user since:2024-01-01").unwrap();
+//!
+//! // Date range (different fields get implicit AND)
+//! let expr = parse_filter("after:2024-01-01 before:2024-02-01").unwrap();
+//!
+//! // Groups and negation
+//! let expr = parse_filter("(project:foo OR project:bar) -type:agent").unwrap();
 //! ```
 //!
 //! # Operator Precedence
 //!
-//! - Implicit operators (no keyword): AND for different fields, OR for same field
+//! - Implicit operators (no keyword): OR between clauses of the same field, AND otherwise
+//!   (different fields, sizes of the same field, a negated clause or a group)
 //! - Explicit operators (AND/OR keywords): Always respected
+//! - Operators apply left to right (`a OR b AND c` is `(a OR b) AND c`); parentheses group
+//!   clauses into one operand and NOT binds to the operand after it
 //!
 //! # Validation
 //!
 //! - `type` values must be "user" or "agent" (case-insensitive)
-//! - `since` dates must be YYYY-MM-DD format and semantically valid
+//! - `outcome` values must be "success", "abandoned" or "unclear" (case-insensitive)
+//! - `source` values must be "claude", "codex", "aider" or "transcript" (case-insensitive)
+//! - `has` values must be "thinking", "tool_use", "tool_result", "image" or "error"
+//!   (case-insensitive)
+//! - `regex` values must compile as regular expressions
+//! - Dates must be YYYY-MM-DD and semantically valid, or a positive age in h, d or w
+//! - Sizes must be a comparison and a non-negative number; other fields can't be compared
 //! - Empty field names or values are rejected
+//!
+//! # Tokens and Spans
+//!
+//! [`tokenize`] and the [`Token`]/[`TokenKind`]/[`Span`] types are public so other front-ends
+//! (editor plugins, a GUI) can highlight or complete the same syntax without re-implementing
+//! the grammar. Spans are byte ranges into the input. Syntax errors carry the span of the
+//! offending input as a [`FilterSyntaxError`] in the error chain:
+//!
+//! ```rust
+//! # use ai_history_explorer::filters::parser::{FilterSyntaxError, parse_filter};
+//! let err = parse_filter("project:foo color:red").unwrap_err();
+//! let syntax = err.downcast_ref::<FilterSyntaxError>().unwrap();
+//! assert_eq!(syntax.span.text("project:foo color:red"), "color");
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::CharIndices;
 
 use anyhow::{Context, Result, anyhow};
-use chrono::NaiveDate;
+use regex::Regex;
 
-use super::ast::{FieldFilter, FilterExpr, FilterField, FilterOperator};
+use super::ast::{
+    DateValue, FieldFilter, FilterExpr, FilterField, FilterNode, FilterOperator, SizeValue,
+};
+use crate::models::{ContentKinds, SessionOutcome, SourceKind};
 
-/// Token types produced by the tokenizer
+/// Byte range `start..end` into the filter input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub const fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// The spanned part of `input` (empty if the span doesn't fit)
+    pub fn text<'a>(&self, input: &'a str) -> &'a str {
+        input.get(self.start..self.end).unwrap_or("")
+    }
+}
+
+/// Kinds of tokens produced by the tokenizer
 #[derive(Debug, Clone, PartialEq, Eq)]
-enum Token {
-    /// field:value or field:"quoted value"
-    FieldValue { field: String, value: String },
+pub enum TokenKind {
+    /// field:value or field:"quoted value" (`value` without the quotes)
+    FieldValue {
+        field: String,
+        value: String,
+        /// Span of the field name
+        field_span: Span,
+        /// Span of the value, including quotes
+        value_span: Span,
+    },
     /// AND keyword
     And,
     /// OR keyword
     Or,
+    /// NOT keyword, or `-` in front of a clause or group
+    Not,
+    /// `(`
+    LParen,
+    /// `)`
+    RParen,
+}
+
+/// A token and where it is in the input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+/// Filter syntax error with the span of the input it refers to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterSyntaxError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl FilterSyntaxError {
+    pub fn new(message: impl Into<String>, span: Span) -> Self {
+        Self { message: message.into(), span }
+    }
+}
+
+impl fmt::Display for FilterSyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
 }
 
+impl std::error::Error for FilterSyntaxError {}
+
 /// Tokenize filter input string into tokens
 ///
 /// Supports:
 /// - field:value patterns
-/// - field:"quoted value" with spaces
-/// - AND/OR keywords (case-insensitive)
+/// - field:"quoted value" with spaces, `\"` and `\\` escaping a quote and a backslash
+/// - AND/OR/NOT keywords (case-insensitive) and `-` in front of a clause for NOT
+/// - Parentheses around clauses (a `)` closing a `(` inside a value, as in `regex:(a|b)`,
+///   belongs to the value)
 /// - Whitespace separation
-fn tokenize(input: &str) -> Result<Vec<Token>> {
+///
+/// # Errors
+///
+/// Returns a [`FilterSyntaxError`] for malformed tokens and unterminated quotes.
+pub fn tokenize(input: &str) -> Result<Vec<Token>> {
     let mut tokens = Vec::new();
-    let mut chars = input.chars().peekable();
+    let mut chars = input.char_indices().peekable();
 
-    while let Some(&ch) = chars.peek() {
+    while let Some(&(start, ch)) = chars.peek() {
         // Skip whitespace
         if ch.is_whitespace() {
             chars.next();
@@ -87,83 +209,208 @@ fn tokenize(input: &str) -> Result<Vec<Token>> {
         }
 
         // Try to parse a word or field:value
-        let word = read_word(&mut chars);
+        let mut word = read_word(&mut chars, input, start);
+        let mut start = start;
 
-        if word.is_empty() {
-            return Err(anyhow!("Unexpected character in filter input"));
+        // Opening parentheses and `-` negations in front of a clause
+        while let Some(kind) = match word.as_bytes() {
+            [b'(', ..] => Some(TokenKind::LParen),
+            [b'-', _, ..] => Some(TokenKind::Not),
+            _ => None,
+        } {
+            tokens.push(Token { kind, span: Span::new(start, start + 1) });
+            start += 1;
+            word = &word[1..];
         }
 
+        // Closing parentheses after it, except those closing a parenthesis of the value
+        let (word, closers) = split_closers(word);
+        let word_span = Span::new(start, start + word.len());
+        let mut end = word_span.end;
+
         // Check if it's an operator keyword
         match word.to_uppercase().as_str() {
-            "AND" => tokens.push(Token::And),
-            "OR" => tokens.push(Token::Or),
+            "" => {}
+            "AND" => tokens.push(Token { kind: TokenKind::And, span: word_span }),
+            "OR" => tokens.push(Token { kind: TokenKind::Or, span: word_span }),
+            "NOT" => tokens.push(Token { kind: TokenKind::Not, span: word_span }),
             _ => {
-                // Try to parse as field:value
-                if let Some(colon_pos) = word.find(':') {
+                // Try to parse as field:value, or a comparison like `len>1000`
+                if let Some(position) = comparison_position(word) {
+                    // The comparison is part of the value
+                    tokens.push(Token {
+                        kind: TokenKind::FieldValue {
+                            field: word[..position].to_string(),
+                            value: word[position..].to_string(),
+                            field_span: Span::new(start, start + position),
+                            value_span: Span::new(start + position, end),
+                        },
+                        span: word_span,
+                    });
+                } else if let Some(colon_pos) = word.find(':') {
                     let field = word[..colon_pos].to_string();
                     let mut value = word[colon_pos + 1..].to_string();
 
                     // Check if value starts with quote
                     if value.starts_with('"') {
                         // Need to read quoted value
-                        value = read_quoted_value(&mut chars, &value)?;
+                        let value_start = start + colon_pos + 1;
+                        (value, end) = read_quoted_value(&mut chars, &value, value_start, end)?;
                     }
 
                     if field.is_empty() || value.is_empty() {
-                        return Err(anyhow!("Invalid field:value format: {}", word));
+                        return Err(FilterSyntaxError::new(
+                            format!("Invalid field:value format: {}", word),
+                            Span::new(start, end),
+                        )
+                        .into());
                     }
 
-                    tokens.push(Token::FieldValue { field, value });
+                    tokens.push(Token {
+                        kind: TokenKind::FieldValue {
+                            field,
+                            value,
+                            field_span: Span::new(start, start + colon_pos),
+                            value_span: Span::new(start + colon_pos + 1, end),
+                        },
+                        span: Span::new(start, end),
+                    });
                 } else {
-                    return Err(anyhow!(
-                        "Invalid token: '{}' (expected field:value or AND/OR)",
-                        word
-                    ));
+                    return Err(FilterSyntaxError::new(
+                        format!(
+                            "Invalid token: '{}' (expected field:value, AND/OR/NOT or parentheses)",
+                            word
+                        ),
+                        word_span,
+                    )
+                    .into());
                 }
             }
         }
+        for i in 0..closers {
+            tokens.push(Token { kind: TokenKind::RParen, span: Span::new(end + i, end + i + 1) });
+        }
     }
 
     Ok(tokens)
 }
 
-/// Read a word (until whitespace or end)
-fn read_word(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
-    let mut word = String::new();
+/// Byte offset of the comparison in a word like `len>1000` (a field name, then `<`, `>` or `=`
+/// and a value)
+fn comparison_position(word: &str) -> Option<usize> {
+    let position = word.find(['<', '>', '='])?;
+    let (field, value) = word.split_at(position);
+    let valid = !field.is_empty()
+        && field.chars().all(|c| c.is_ascii_alphabetic())
+        && !value.trim_start_matches(['<', '>', '=']).is_empty();
+    valid.then_some(position)
+}
+
+/// Split the closing parentheses off the end of `word`, keeping those that close a parenthesis
+/// opened in an unquoted value (e.g. in `regex:(a|b)`) and leaving words with an open quote
+/// alone
+fn split_closers(word: &str) -> (&str, usize) {
+    if ends_in_quotes(word) {
+        return (word, 0);
+    }
+    let body = word.trim_end_matches(')');
+    let trailing = word.len() - body.len();
+    // Parentheses in a quoted value don't count
+    let unclosed = if body.contains('"') {
+        0
+    } else {
+        body.matches('(').count().saturating_sub(body.matches(')').count())
+    };
+    let closers = trailing - unclosed.min(trailing);
+    (&word[..word.len() - closers], closers)
+}
 
-    while let Some(&ch) = chars.peek() {
+/// Read a word starting at byte `start` (until whitespace or end)
+fn read_word<'a>(chars: &mut Peekable<CharIndices>, input: &'a str, start: usize) -> &'a str {
+    let mut end = start;
+
+    while let Some(&(i, ch)) = chars.peek() {
         if ch.is_whitespace() {
             break;
         }
-        word.push(ch);
+        end = i + ch.len_utf8();
         chars.next();
     }
 
-    word
+    &input[start..end]
 }
 
 /// Read a quoted value, handling the case where word already contains the opening quote
+///
+/// `initial` is the part of the word from the opening quote (at byte `start`) to `word_end`.
+/// Returns the unquoted value and the byte offset after the closing quote. Inside the quotes
+/// `\"` is a quote and `\\` a backslash; other backslashes are kept, so Windows paths and regex
+/// escapes like `\w` can be written as they are.
 fn read_quoted_value(
-    chars: &mut std::iter::Peekable<std::str::Chars>,
+    chars: &mut Peekable<CharIndices>,
     initial: &str,
-) -> Result<String> {
-    // initial is like "foo or "foo bar" depending on if quote was mid-word
-    let mut value = initial[1..].to_string(); // Remove opening quote
+    start: usize,
+    word_end: usize,
+) -> Result<(String, usize)> {
+    let mut value = String::new();
+    let mut escaped = false;
 
-    // If the initial part already has closing quote, we're done
-    if let Some(quote_pos) = value.find('"') {
-        return Ok(value[..quote_pos].to_string());
+    // initial is like "foo or "foo bar" depending on if quote was mid-word
+    for ch in initial[1..].chars() {
+        if unquote_char(&mut value, &mut escaped, ch) {
+            return Ok((value, word_end));
+        }
     }
 
     // Otherwise keep reading until closing quote
-    for ch in chars.by_ref() {
-        if ch == '"' {
-            return Ok(value);
+    let mut end = word_end;
+    for (i, ch) in chars.by_ref() {
+        if unquote_char(&mut value, &mut escaped, ch) {
+            return Ok((value, i + 1));
+        }
+        end = i + ch.len_utf8();
+    }
+
+    Err(FilterSyntaxError::new("Unterminated quoted string", Span::new(start, end)).into())
+}
+
+/// Add `ch` of a quoted value to `value`, handling escapes; true for the closing quote
+fn unquote_char(value: &mut String, escaped: &mut bool, ch: char) -> bool {
+    if std::mem::take(escaped) {
+        if ch != '"' && ch != '\\' {
+            value.push('\\');
         }
         value.push(ch);
+    } else if ch == '\\' {
+        *escaped = true;
+    } else if ch == '"' {
+        return true;
+    } else {
+        value.push(ch);
+    }
+    false
+}
+
+/// Whether `text` ends inside a quoted value (escaped quotes don't close it)
+fn ends_in_quotes(text: &str) -> bool {
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for ch in text.chars() {
+        match ch {
+            _ if std::mem::take(&mut escaped) => {}
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            _ => {}
+        }
     }
+    in_quotes
+}
 
-    Err(anyhow!("Unterminated quoted string"))
+/// Quote `value` so it reads back as the same filter value, e.g. `project:` + `"My Docs"`
+///
+/// Quotes and backslashes in the value are escaped.
+pub fn quote_value(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
 }
 
 /// Parse field name into FilterField enum
@@ -172,7 +419,28 @@ fn parse_field(field: &str) -> Result<FilterField> {
         "project" => Ok(FilterField::Project),
         "type" => Ok(FilterField::Type),
         "since" => Ok(FilterField::Since),
-        _ => Err(anyhow!("Unknown field: '{}' (valid fields: project, type, since)", field)),
+        "after" => Ok(FilterField::After),
+        "before" => Ok(FilterField::Before),
+        "on" => Ok(FilterField::On),
+        "user" => Ok(FilterField::User),
+        "outcome" => Ok(FilterField::Outcome),
+        "source" => Ok(FilterField::Source),
+        "root" => Ok(FilterField::Root),
+        "tool" => Ok(FilterField::Tool),
+        "model" => Ok(FilterField::Model),
+        "branch" => Ok(FilterField::Branch),
+        "cwd" => Ok(FilterField::Cwd),
+        "starred" => Ok(FilterField::Starred),
+        "error" => Ok(FilterField::Error),
+        "regex" => Ok(FilterField::Regex),
+        "len" => Ok(FilterField::Len),
+        "messages" => Ok(FilterField::Messages),
+        "has" => Ok(FilterField::Has),
+        _ => Err(anyhow!(
+            "Unknown field: '{}' (valid fields: project, type, since, after, before, on, user, \
+             outcome, source, root, tool, model, branch, cwd, starred, error, regex, len, messages, has)",
+            field
+        )),
     }
 }
 
@@ -184,6 +452,7 @@ fn parse_field(field: &str) -> Result<FilterField> {
 /// - "type:user OR type:agent" → two filters with explicit OR
 /// - "project:foo project:bar" → two filters with implicit OR (same field)
 /// - "project:\"foo bar\"" → filter with quoted value containing spaces
+/// - "(project:foo OR project:bar) -type:agent" → a group AND a negated clause
 pub fn parse_filter(input: &str) -> Result<FilterExpr> {
     if input.trim().is_empty() {
         return Ok(FilterExpr::new());
@@ -195,59 +464,18 @@ pub fn parse_filter(input: &str) -> Result<FilterExpr> {
         return Ok(FilterExpr::new());
     }
 
-    let mut expr = FilterExpr::new();
-    let mut expecting_filter = true;
-    let mut last_field: Option<FilterField> = None;
-
-    for token in tokens {
-        match token {
-            Token::FieldValue { field, value } => {
-                let filter_field = parse_field(&field)?;
-
-                // Validate value based on field type
-                validate_value(&filter_field, &value)?;
-
-                // Add implicit operator if we're not expecting a filter
-                // (meaning there was no explicit operator between filters)
-                if !expecting_filter && !expr.filters.is_empty() {
-                    // Need to add implicit operator
-                    let implicit_op = if let Some(ref prev_field) = last_field {
-                        if prev_field == &filter_field {
-                            FilterOperator::Or // Same field → OR
-                        } else {
-                            FilterOperator::And // Different field → AND
-                        }
-                    } else {
-                        FilterOperator::And
-                    };
-                    expr.add_operator(implicit_op);
-                }
-
-                expr.add_filter(FieldFilter::new(filter_field.clone(), value));
-                last_field = Some(filter_field);
-                expecting_filter = false;
-            }
-            Token::And => {
-                if expecting_filter {
-                    return Err(anyhow!("Unexpected AND operator (expected field:value)"));
-                }
-                expr.add_operator(FilterOperator::And);
-                expecting_filter = true;
-            }
-            Token::Or => {
-                if expecting_filter {
-                    return Err(anyhow!("Unexpected OR operator (expected field:value)"));
-                }
-                expr.add_operator(FilterOperator::Or);
-                expecting_filter = true;
-            }
-        }
-    }
-
-    if expecting_filter {
-        return Err(anyhow!("Filter ended with operator (expected field:value)"));
+    let mut parser = Parser {
+        tokens: tokens.into_iter().peekable(),
+        expr: FilterExpr::new(),
+        operators: BTreeMap::new(),
+    };
+    let nodes = parser.parse_group(0)?;
+    if let Some(Token { span, .. }) = parser.tokens.next() {
+        return Err(FilterSyntaxError::new("Unmatched closing parenthesis", span).into());
     }
 
+    let mut expr = parser.expr;
+    expr.operators = parser.operators.into_values().collect();
     // Validate operators count
     if expr.operators.len() != expr.filters.len().saturating_sub(1) {
         return Err(anyhow!(
@@ -256,10 +484,148 @@ pub fn parse_filter(input: &str) -> Result<FilterExpr> {
             expr.operators.len()
         ));
     }
+    if !nodes.iter().all(|node| matches!(node, FilterNode::Clause(_))) {
+        expr.tree = Some(FilterNode::Group(nodes));
+    }
 
     Ok(expr)
 }
 
+/// Parentheses and NOTs nested deeper than this are rejected
+const MAX_NESTING: usize = 32;
+
+/// Recursive descent over the tokens, collecting the clauses into `expr`
+struct Parser {
+    tokens: Peekable<std::vec::IntoIter<Token>>,
+    expr: FilterExpr,
+    /// Operators by the index of the clause they come before (a group's operator is only
+    /// known after its inner ones)
+    operators: BTreeMap<usize, FilterOperator>,
+}
+
+impl Parser {
+    /// Parse operands up to the end of the input or a closing parenthesis (not consumed)
+    ///
+    /// Operands without an operator between them are OR'd when both are clauses of the same
+    /// field, and AND'd otherwise (and for sizes, so `len>100 len<1000` is a range).
+    fn parse_group(&mut self, depth: usize) -> Result<Vec<FilterNode>> {
+        let mut nodes: Vec<FilterNode> = Vec::new();
+        let mut last_field: Option<FilterField> = None;
+        let mut operator: Option<(FilterOperator, Span)> = None;
+
+        while let Some(token) = self.tokens.peek() {
+            let span = token.span;
+            let keyword = match token.kind {
+                TokenKind::RParen => break,
+                TokenKind::And => Some((FilterOperator::And, "AND")),
+                TokenKind::Or => Some((FilterOperator::Or, "OR")),
+                _ => None,
+            };
+            if let Some((keyword_operator, name)) = keyword {
+                self.tokens.next();
+                if nodes.is_empty() || operator.is_some() {
+                    return Err(FilterSyntaxError::new(
+                        format!("Unexpected {} operator (expected field:value)", name),
+                        span,
+                    )
+                    .into());
+                }
+                operator = Some((keyword_operator, span));
+                continue;
+            }
+
+            let (node, field) = self.parse_operand(depth)?;
+            if !nodes.is_empty() {
+                let operator = match operator.take() {
+                    Some((operator, _)) => operator,
+                    // Two sizes of the same field make a range
+                    None if field.as_ref().is_some_and(|f| !f.is_size()) && field == last_field => {
+                        FilterOperator::Or
+                    }
+                    None => FilterOperator::And,
+                };
+                self.operators.insert(node.first_clause(), operator);
+            }
+            nodes.push(node);
+            last_field = field;
+        }
+
+        if let Some((_, span)) = operator {
+            return Err(FilterSyntaxError::new(
+                "Filter ended with operator (expected field:value)",
+                span,
+            )
+            .into());
+        }
+        Ok(nodes)
+    }
+
+    /// Parse a clause, a parenthesized group or a negated operand, with the field of a clause
+    fn parse_operand(&mut self, depth: usize) -> Result<(FilterNode, Option<FilterField>)> {
+        let Some(Token { kind, span }) = self.tokens.next() else {
+            return Err(anyhow!("Expected field:value"));
+        };
+        if depth >= MAX_NESTING && matches!(kind, TokenKind::Not | TokenKind::LParen) {
+            return Err(FilterSyntaxError::new("Filter is nested too deeply", span).into());
+        }
+        match kind {
+            TokenKind::FieldValue { field, value, field_span, value_span } => {
+                let filter_field = parse_field(&field)
+                    .map_err(|e| FilterSyntaxError::new(e.to_string(), field_span))?;
+                // Without a colon the value starts with a comparison (`len>1000`)
+                if value_span.start == field_span.end && !filter_field.is_size() {
+                    return Err(FilterSyntaxError::new(
+                        format!("'{}' doesn't compare sizes (expected {}:value)", field, field),
+                        span,
+                    )
+                    .into());
+                }
+
+                // Validate value based on field type
+                validate_value(&filter_field, &value)
+                    .map_err(|e| FilterSyntaxError::new(e.to_string(), value_span))?;
+
+                self.expr.add_filter(FieldFilter::new(filter_field.clone(), value));
+                Ok((FilterNode::Clause(self.expr.filters.len() - 1), Some(filter_field)))
+            }
+            TokenKind::Not => {
+                if !matches!(
+                    self.tokens.peek().map(|token| &token.kind),
+                    Some(TokenKind::FieldValue { .. } | TokenKind::Not | TokenKind::LParen)
+                ) {
+                    return Err(FilterSyntaxError::new(
+                        "NOT must be followed by field:value or a parenthesized group",
+                        span,
+                    )
+                    .into());
+                }
+                let (node, _) = self.parse_operand(depth + 1)?;
+                Ok((FilterNode::Not(Box::new(node)), None))
+            }
+            TokenKind::LParen => {
+                let nodes = self.parse_group(depth + 1)?;
+                match self.tokens.next() {
+                    Some(Token { kind: TokenKind::RParen, span: close }) if nodes.is_empty() => {
+                        Err(FilterSyntaxError::new(
+                            "Empty parentheses",
+                            Span::new(span.start, close.end),
+                        )
+                        .into())
+                    }
+                    Some(Token { kind: TokenKind::RParen, .. }) => {
+                        Ok((FilterNode::Group(nodes), None))
+                    }
+                    _ => Err(FilterSyntaxError::new("Unclosed parenthesis", span).into()),
+                }
+            }
+            // Operators are taken by `parse_group`, and `)` ends it
+            TokenKind::And | TokenKind::Or | TokenKind::RParen => {
+                Err(FilterSyntaxError::new("Expected field:value", span).into())
+            }
+        }
+    }
+}
+
 /// Validate filter value based on field type
 fn validate_value(field: &FilterField, value: &str) -> Result<()> {
     match field {
@@ -270,10 +636,12 @@ fn validate_value(field: &FilterField, value: &str) -> Result<()> {
                 _ => Err(anyhow!("Invalid type value: '{}' (must be 'user' or 'agent')", value)),
             }
         }
-        FilterField::Since => {
-            // Must be YYYY-MM-DD format
-            if !is_valid_date_format(value) {
-                return Err(anyhow!("Invalid date format: '{}' (expected YYYY-MM-DD)", value));
+        FilterField::Since | FilterField::After | FilterField::Before | FilterField::On => {
+            if DateValue::parse(value).is_none() {
+                return Err(anyhow!(
+                    "Invalid date format: '{}' (expected YYYY-MM-DD or an age like 12h, 7d, 2w)",
+                    value
+                ));
             }
             Ok(())
         }
@@ -284,19 +652,92 @@ fn validate_value(field: &FilterField, value: &str) -> Result<()> {
             }
             Ok(())
         }
+        FilterField::User => {
+            if value.is_empty() {
+                return Err(anyhow!("User cannot be empty"));
+            }
+            Ok(())
+        }
+        FilterField::Root => {
+            if value.is_empty() {
+                return Err(anyhow!("Root cannot be empty"));
+            }
+            Ok(())
+        }
+        FilterField::Tool => {
+            if value.is_empty() {
+                return Err(anyhow!("Tool cannot be empty"));
+            }
+            Ok(())
+        }
+        FilterField::Model => {
+            if value.is_empty() {
+                return Err(anyhow!("Model cannot be empty"));
+            }
+            Ok(())
+        }
+        FilterField::Branch => {
+            if value.is_empty() {
+                return Err(anyhow!("Branch cannot be empty"));
+            }
+            Ok(())
+        }
+        FilterField::Cwd => {
+            if value.is_empty() {
+                return Err(anyhow!("Cwd cannot be empty"));
+            }
+            Ok(())
+        }
+        FilterField::Starred => match value.to_lowercase().as_str() {
+            "true" | "false" => Ok(()),
+            _ => Err(anyhow!("Invalid starred value: '{}' (must be 'true' or 'false')", value)),
+        },
+        FilterField::Error => match value.to_lowercase().as_str() {
+            "true" | "false" => Ok(()),
+            _ => Err(anyhow!("Invalid error value: '{}' (must be 'true' or 'false')", value)),
+        },
+        FilterField::Outcome => match SessionOutcome::from_label(value) {
+            Some(_) => Ok(()),
+            None => Err(anyhow!(
+                "Invalid outcome value: '{}' (must be 'success', 'abandoned' or 'unclear')",
+                value
+            )),
+        },
+        FilterField::Source => match SourceKind::from_label(value) {
+            Some(_) => Ok(()),
+            None => Err(anyhow!(
+                "Invalid source value: '{}' (must be 'claude', 'codex', 'aider' or 'transcript')",
+                value
+            )),
+        },
+        FilterField::Has => match value.to_lowercase().as_str() {
+            "error" => Ok(()),
+            kind if ContentKinds::from_name(&kind.to_uppercase()).is_some() => Ok(()),
+            _ => Err(anyhow!(
+                "Invalid has value: '{}' (must be 'thinking', 'tool_use', 'tool_result', 'image' \
+                 or 'error')",
+                value
+            )),
+        },
+        FilterField::Len | FilterField::Messages => match SizeValue::parse(value) {
+            Some(_) => Ok(()),
+            None => Err(anyhow!(
+                "Invalid size: '{}' (expected a number after <, <=, =, >= or >, like >1000)",
+                value
+            )),
+        },
+        FilterField::Regex => match Regex::new(value) {
+            Ok(_) => Ok(()),
+            // The full message draws the pattern over several lines; keep the summary
+            Err(e) => Err(anyhow!(
+                "Invalid regex: '{}' ({})",
+                value,
+                e.to_string().lines().last().unwrap_or_default().trim_start_matches("error: ")
+            )),
+        },
     }
 }
 
-/// Check if string is valid YYYY-MM-DD format
-fn is_valid_date_format(s: &str) -> bool {
-    // Enforce strict YYYY-MM-DD format (10 chars)
-    if s.len() != 10 {
-        return false;
-    }
-    // Use chrono for semantic validation (e.g., reject 2024-02-31)
-    NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -307,7 +748,15 @@ mod tests {
         assert_eq!(tokens.len(), 1);
         assert_eq!(
             tokens[0],
-            Token::FieldValue { field: "project".to_string(), value: "foo".to_string() }
+            Token {
+                kind: TokenKind::FieldValue {
+                    field: "project".to_string(),
+                    value: "foo".to_string(),
+                    field_span: Span::new(0, 7),
+                    value_span: Span::new(8, 11),
+                },
+                span: Span::new(0, 11),
+            }
         );
     }
 
@@ -321,24 +770,134 @@ mod tests {
     fn test_tokenize_with_operators() {
         let tokens = tokenize("project:foo AND type:user").unwrap();
         assert_eq!(tokens.len(), 3);
-        assert_eq!(tokens[1], Token::And);
+        assert_eq!(tokens[1], Token { kind: TokenKind::And, span: Span::new(12, 15) });
     }
 
     #[test]
     fn test_tokenize_quoted_value() {
-        let tokens = tokenize("project:\"foo bar\"").unwrap();
-        assert_eq!(tokens.len(), 1);
+        let input = "project:\"foo bar\" type:user";
+        let tokens = tokenize(input).unwrap();
+        assert_eq!(tokens.len(), 2);
+        let TokenKind::FieldValue { field, value, value_span, .. } = &tokens[0].kind else {
+            panic!("expected field:value token");
+        };
+        assert_eq!(field, "project");
+        assert_eq!(value, "foo bar");
+        assert_eq!(value_span.text(input), "\"foo bar\"");
+        assert_eq!(tokens[0].span.text(input), "project:\"foo bar\"");
+        assert_eq!(tokens[1].span.text(input), "type:user");
+    }
+
+    #[test]
+    fn test_tokenize_escapes_in_quoted_values() {
+        let value_of = |input: &str| match &tokenize(input).unwrap()[0].kind {
+            TokenKind::FieldValue { value, .. } => value.clone(),
+            kind => panic!("expected field:value token, got {:?}", kind),
+        };
+        assert_eq!(value_of(r#"project:"say \"hi\" now""#), r#"say "hi" now"#);
+        assert_eq!(value_of(r#"project:"C:\Users\me\\""#), r"C:\Users\me\");
+        assert_eq!(value_of(r#"regex:"fn \w+_test""#), r"fn \w+_test");
+        // Colons after the field's belong to the value, quoted or not
+        assert_eq!(value_of(r"project:C:\work\api"), r"C:\work\api");
+        assert_eq!(value_of(r#"project:"My Docs: 2024""#), "My Docs: 2024");
+
+        let err = tokenize(r#"project:"C:\""#).unwrap_err();
+        assert!(err.to_string().contains("Unterminated"));
+    }
+
+    #[test]
+    fn test_quote_value_round_trips() {
+        for value in [
+            "My Docs",
+            r"C:\Program Files\app\",
+            r#"say "hi""#,
+            r#"\""#,
+            "a|b (c)",
+            "(x)",
+            r"tab\there",
+            "café au lait",
+            "project:nested",
+        ] {
+            let input = format!("(project:{} OR type:user)", quote_value(value));
+            let expr = parse_filter(&input).unwrap();
+            assert_eq!(expr.filters.len(), 2, "{}", input);
+            assert_eq!(expr.filters[0].value, value, "{}", input);
+        }
+        assert_eq!(quote_value(r#"a "b" \c"#), r#""a \"b\" \\c""#);
+    }
+
+    #[test]
+    fn test_parse_has() {
+        let expr = parse_filter("has:Thinking has:tool_result has:error").unwrap();
+        assert!(expr.filters.iter().all(|f| f.field == FilterField::Has));
+        assert_eq!(expr.operators, vec![FilterOperator::Or, FilterOperator::Or]);
+
+        let err = parse_filter("has:thoughts").unwrap_err();
+        assert!(err.to_string().contains("Invalid has value"), "{}", err);
+        assert!(parse_filter("has:tool-use").is_err());
+
+        let expr = parse_filter("error:true").unwrap();
+        assert_eq!(expr.filters[0].field, FilterField::Error);
+        let err = parse_filter("error:yes").unwrap_err();
+        assert!(err.to_string().contains("Invalid error value"), "{}", err);
+    }
+
+    #[test]
+    fn test_tokenize_comparison() {
+        let input = "-len>=1000 regex:a<b";
+        let tokens = tokenize(input).unwrap();
+        assert_eq!(tokens.len(), 3);
+        let TokenKind::FieldValue { field, value, field_span, value_span } = &tokens[1].kind else {
+            panic!("expected field:value token");
+        };
+        assert_eq!((field.as_str(), value.as_str()), ("len", ">=1000"));
+        assert_eq!(field_span.text(input), "len");
+        assert_eq!(value_span.text(input), ">=1000");
+        assert_eq!(tokens[1].span.text(input), "len>=1000");
+        // A comparison after the colon belongs to the value
+        let TokenKind::FieldValue { field, value, .. } = &tokens[2].kind else {
+            panic!("expected field:value token");
+        };
+        assert_eq!((field.as_str(), value.as_str()), ("regex", "a<b"));
+
+        assert!(tokenize("len>").is_err());
+    }
+
+    #[test]
+    fn test_parse_sizes() {
+        let expr = parse_filter("LEN>1000 len:<=5000 messages=3").unwrap();
+        let values: Vec<_> = expr.filters.iter().map(|f| (&f.field, f.value.as_str())).collect();
         assert_eq!(
-            tokens[0],
-            Token::FieldValue { field: "project".to_string(), value: "foo bar".to_string() }
+            values,
+            vec![
+                (&FilterField::Len, ">1000"),
+                (&FilterField::Len, "<=5000"),
+                (&FilterField::Messages, "=3")
+            ]
         );
+        // Sizes of the same field are a range
+        assert_eq!(expr.operators, vec![FilterOperator::And, FilterOperator::And]);
+        let expr = parse_filter("len<10 OR len>1000").unwrap();
+        assert_eq!(expr.operators, vec![FilterOperator::Or]);
+
+        let err = parse_filter("len>lots").unwrap_err();
+        assert!(err.to_string().contains("Invalid size"), "{}", err);
+        let syntax = err.downcast_ref::<FilterSyntaxError>().unwrap();
+        assert_eq!(syntax.span.text("len>lots"), ">lots");
+
+        let err = parse_filter("type:user project=api").unwrap_err();
+        assert!(err.to_string().contains("doesn't compare sizes"), "{}", err);
+        let syntax = err.downcast_ref::<FilterSyntaxError>().unwrap();
+        assert_eq!(syntax.span.text("type:user project=api"), "project=api");
     }
 
     #[test]
     fn test_tokenize_unterminated_quote() {
         let result = tokenize("project:\"foo bar");
         assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("Unterminated"));
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("Unterminated"));
+        assert_eq!(err.downcast_ref::<FilterSyntaxError>().unwrap().span, Span::new(8, 16));
     }
 
     #[test]
@@ -353,6 +912,15 @@ mod tests {
         assert_eq!(parse_field("project").unwrap(), FilterField::Project);
         assert_eq!(parse_field("type").unwrap(), FilterField::Type);
         assert_eq!(parse_field("since").unwrap(), FilterField::Since);
+        assert_eq!(parse_field("user").unwrap(), FilterField::User);
+        assert_eq!(parse_field("outcome").unwrap(), FilterField::Outcome);
+        assert_eq!(parse_field("source").unwrap(), FilterField::Source);
+        assert_eq!(parse_field("root").unwrap(), FilterField::Root);
+        assert_eq!(parse_field("tool").unwrap(), FilterField::Tool);
+        assert_eq!(parse_field("model").unwrap(), FilterField::Model);
+        assert_eq!(parse_field("branch").unwrap(), FilterField::Branch);
+        assert_eq!(parse_field("cwd").unwrap(), FilterField::Cwd);
+        assert_eq!(parse_field("starred").unwrap(), FilterField::Starred);
         assert_eq!(parse_field("PROJECT").unwrap(), FilterField::Project); // Case insensitive
     }
 
@@ -371,30 +939,58 @@ mod tests {
         assert!(validate_value(&FilterField::Type, "invalid").is_err());
     }
 
+    #[test]
+    fn test_validate_outcome_value() {
+        assert!(validate_value(&FilterField::Outcome, "success").is_ok());
+        assert!(validate_value(&FilterField::Outcome, "ABANDONED").is_ok());
+        assert!(validate_value(&FilterField::Outcome, "done").is_err());
+    }
+
+    #[test]
+    fn test_validate_source_value() {
+        assert!(validate_value(&FilterField::Source, "codex").is_ok());
+        assert!(validate_value(&FilterField::Source, "Claude").is_ok());
+        assert!(validate_value(&FilterField::Source, "cursor").is_err());
+    }
+
+    #[test]
+    fn test_parse_filter_regex() {
+        let expr = parse_filter(r#"regex:"fn \w+_test" type:agent"#).unwrap();
+        assert_eq!(
+            expr.filters[0],
+            FieldFilter::new(FilterField::Regex, r"fn \w+_test".to_string())
+        );
+
+        let err = parse_filter("regex:(unclosed").unwrap_err();
+        let syntax = err.downcast_ref::<FilterSyntaxError>().unwrap();
+        assert_eq!(syntax.span.text("regex:(unclosed"), "(unclosed");
+        assert_eq!(err.to_string(), "Invalid regex: '(unclosed' (unclosed group)");
+    }
+
     #[test]
     fn test_validate_date_format() {
         // Valid dates
-        assert!(is_valid_date_format("2024-01-15"));
-        assert!(is_valid_date_format("2024-12-31"));
-        assert!(is_valid_date_format("2024-02-29")); // Leap year
+        assert!(validate_value(&FilterField::Since, "2024-01-15").is_ok());
+        assert!(validate_value(&FilterField::Since, "2024-12-31").is_ok());
+        assert!(validate_value(&FilterField::Since, "2024-02-29").is_ok()); // Leap year
 
         // Invalid format
-        assert!(!is_valid_date_format("2024-1-15")); // Single digit month
-        assert!(!is_valid_date_format("24-01-15")); // 2-digit year
-        assert!(!is_valid_date_format("2024/01/15")); // Wrong separator
+        assert!(validate_value(&FilterField::Since, "2024-1-15").is_err()); // Single digit month
+        assert!(validate_value(&FilterField::Since, "24-01-15").is_err()); // 2-digit year
+        assert!(validate_value(&FilterField::Since, "2024/01/15").is_err()); // Wrong separator
 
         // Invalid month
-        assert!(!is_valid_date_format("2024-13-01"));
-        assert!(!is_valid_date_format("2024-00-01"));
+        assert!(validate_value(&FilterField::Since, "2024-13-01").is_err());
+        assert!(validate_value(&FilterField::Since, "2024-00-01").is_err());
 
         // Invalid day (format check)
-        assert!(!is_valid_date_format("2024-01-32"));
+        assert!(validate_value(&FilterField::Since, "2024-01-32").is_err());
 
         // Semantically invalid dates (format correct but date doesn't exist)
-        assert!(!is_valid_date_format("2024-02-31")); // Feb has max 29 days in 2024
-        assert!(!is_valid_date_format("2024-04-31")); // April has 30 days
-        assert!(!is_valid_date_format("2024-11-31")); // November has 30 days
-        assert!(!is_valid_date_format("2023-02-29")); // Not a leap year
+        assert!(validate_value(&FilterField::Since, "2024-02-31").is_err()); // Feb has max 29 days in 2024
+        assert!(validate_value(&FilterField::Since, "2024-04-31").is_err()); // April has 30 days
+        assert!(validate_value(&FilterField::Since, "2024-11-31").is_err()); // November has 30 days
+        assert!(validate_value(&FilterField::Since, "2023-02-29").is_err()); // Not a leap year
     }
 
     #[test]
@@ -457,6 +1053,18 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("Invalid date"));
     }
 
+    #[test]
+    fn test_parse_filter_date_range() {
+        let expr = parse_filter("after:2024-01-01 before:7d ON:2024-03-01").unwrap();
+        let fields: Vec<_> = expr.filters.iter().map(|f| f.field.clone()).collect();
+        assert_eq!(fields, vec![FilterField::After, FilterField::Before, FilterField::On]);
+        assert_eq!(expr.operators, vec![FilterOperator::And, FilterOperator::And]);
+
+        let err = parse_filter("after:yesterday").unwrap_err();
+        assert!(err.to_string().contains("Invalid date format: 'yesterday'"));
+        assert!(parse_filter("on:0d").is_err());
+    }
+
     #[test]
     fn test_parse_filter_ends_with_operator() {
         let result = parse_filter("project:foo AND");
@@ -491,4 +1099,88 @@ mod tests {
         assert!(result2.is_err());
         assert!(result2.unwrap_err().to_string().contains("Invalid field:value"));
     }
+
+    #[test]
+    fn test_tokenize_spans_are_byte_offsets() {
+        let input = "  project:café  OR user:bob";
+        let tokens = tokenize(input).unwrap();
+        let texts: Vec<&str> = tokens.iter().map(|t| t.span.text(input)).collect();
+        assert_eq!(texts, vec!["project:café", "OR", "user:bob"]);
+    }
+
+    #[test]
+    fn test_parse_filter_error_spans() {
+        let span_of = |input: &str| {
+            let err = parse_filter(input).unwrap_err();
+            let span = err.downcast_ref::<FilterSyntaxError>().unwrap().span;
+            span.text(input).to_string()
+        };
+
+        assert_eq!(span_of("project:foo color:red"), "color");
+        assert_eq!(span_of("type:robot"), "robot");
+        assert_eq!(span_of("AND project:foo"), "AND");
+        assert_eq!(span_of("project:foo OR"), "OR");
+        assert_eq!(span_of("project:foo bogus"), "bogus");
+        assert_eq!(span_of("(project:foo type:user"), "(");
+        assert_eq!(span_of("project:foo)"), ")");
+        assert_eq!(span_of("type:user ()"), "()");
+        assert_eq!(span_of("type:user NOT"), "NOT");
+        assert_eq!(span_of("(project:foo OR)"), "OR");
+    }
+
+    #[test]
+    fn test_tokenize_parentheses_and_not() {
+        let input = "(project:\"my app\" OR -project:b) NOT regex:(a|b))";
+        let tokens = tokenize(input).unwrap();
+        let texts: Vec<&str> = tokens.iter().map(|t| t.span.text(input)).collect();
+        assert_eq!(
+            texts,
+            vec!["(", "project:\"my app\"", "OR", "-", "project:b", ")", "NOT", "regex:(a|b)", ")"]
+        );
+        assert_eq!(tokens[3].kind, TokenKind::Not);
+
+        // A dash inside a value or on its own is not a negation
+        let tokens = tokenize("project:my-app").unwrap();
+        assert_eq!(tokens.len(), 1);
+        assert!(tokenize("- project:a").is_err());
+    }
+
+    #[test]
+    fn test_parse_filter_groups_and_not() {
+        use FilterNode::{Clause, Group, Not};
+
+        // Without groups or negations the clauses apply left to right as before
+        assert_eq!(parse_filter("project:a project:b type:user").unwrap().tree, None);
+
+        let expr = parse_filter("(project:foo OR project:bar) -type:agent").unwrap();
+        assert_eq!(expr.filters.len(), 3);
+        assert_eq!(expr.operators, vec![FilterOperator::Or, FilterOperator::And]);
+        assert_eq!(
+            expr.tree,
+            Some(Group(vec![Group(vec![Clause(0), Clause(1)]), Not(Box::new(Clause(2)))]))
+        );
+
+        // The operator in front of a group joins it; a negated clause of the same field is
+        // AND'd, not OR'd
+        let expr =
+            parse_filter("type:user OR (project:a project:b) project:c -project:c/tests").unwrap();
+        assert_eq!(
+            expr.operators,
+            vec![FilterOperator::Or, FilterOperator::Or, FilterOperator::And, FilterOperator::And]
+        );
+        let expr = parse_filter("NOT (type:user OR NOT starred:true)").unwrap();
+        assert_eq!(
+            expr.tree,
+            Some(Group(vec![Not(Box::new(Group(vec![Clause(0), Not(Box::new(Clause(1)))])))]))
+        );
+    }
+
+    #[test]
+    fn test_parse_filter_rejects_deep_nesting() {
+        let input = format!("{}type:user{}", "(".repeat(100), ")".repeat(100));
+        let err = parse_filter(&input).unwrap_err();
+        assert_eq!(err.to_string(), "Filter is nested too deeply");
+        assert!(parse_filter(&format!("{}type:user", "NOT ".repeat(100))).is_err());
+        assert!(parse_filter("((type:user))").is_ok());
+    }
 }
@@ -0,0 +1,108 @@
+//! Memory ceiling for parallel parsing.
+//!
+//! Parsing holds a file's full conversation in memory until it is reduced to search entries.
+//! With rayon, one large file per core can be in flight at once, which spikes memory on
+//! machines with many cores and little RAM. A [`MemoryBudget`] makes each parse reserve its
+//! file size first and wait while the reservations in flight would exceed the ceiling, so
+//! peak memory stays predictable at the cost of some parallelism.
+//!
+//! The ceiling is set per build with
+//! [`ParseOptions::parse_memory_limit`](crate::parsers::ParseOptions::parse_memory_limit) and
+//! unlimited by default; the roots of a workspace, indexed at the same time, share one budget.
+
+use std::sync::{Condvar, Mutex, MutexGuard};
+
+/// Bytes that may be reserved at the same time, shared by the parsing threads
+#[derive(Debug)]
+pub struct MemoryBudget {
+    limit: usize,
+    in_use: Mutex<usize>,
+    released: Condvar,
+}
+
+impl MemoryBudget {
+    pub fn new(limit: usize) -> Self {
+        Self { limit, in_use: Mutex::new(0), released: Condvar::new() }
+    }
+
+    /// Reserve `bytes`, waiting until they fit under the limit
+    ///
+    /// A reservation larger than the whole limit waits until nothing else is reserved, so
+    /// oversized files are parsed alone rather than never.
+    pub fn reserve(&self, bytes: usize) -> Reservation<'_> {
+        let mut in_use = self.lock();
+        while *in_use > 0 && in_use.saturating_add(bytes) > self.limit {
+            in_use = self.released.wait(in_use).unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+        *in_use += bytes;
+        Reservation { budget: self, bytes }
+    }
+
+    /// Bytes currently reserved
+    pub fn in_use(&self) -> usize {
+        *self.lock()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, usize> {
+        self.in_use.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Bytes held from a [`MemoryBudget`] until dropped
+#[derive(Debug)]
+pub struct Reservation<'a> {
+    budget: &'a MemoryBudget,
+    bytes: usize,
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        *self.budget.lock() -= self.bytes;
+        self.budget.released.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_reservations_wait_for_room() {
+        let budget = Arc::new(MemoryBudget::new(100));
+        let first = budget.reserve(60);
+        let reserved = Arc::new(AtomicBool::new(false));
+
+        let waiter = {
+            let budget = Arc::clone(&budget);
+            let reserved = Arc::clone(&reserved);
+            thread::spawn(move || {
+                let _second = budget.reserve(60);
+                reserved.store(true, Ordering::SeqCst);
+            })
+        };
+        thread::sleep(Duration::from_millis(50));
+        assert!(!reserved.load(Ordering::SeqCst));
+
+        drop(first);
+        waiter.join().unwrap();
+        assert!(reserved.load(Ordering::SeqCst));
+        assert_eq!(budget.in_use(), 0);
+    }
+
+    #[test]
+    fn test_oversized_reservation_runs_alone() {
+        let budget = MemoryBudget::new(100);
+        {
+            let _large = budget.reserve(500);
+            assert_eq!(budget.in_use(), 500);
+        }
+        let _small = budget.reserve(10);
+        let _other = budget.reserve(90);
+        assert_eq!(budget.in_use(), 100);
+    }
+}
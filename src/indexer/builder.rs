@@ -8,28 +8,54 @@
 //!   but don't fail the entire operation, allowing partial index building
 //! - **Parse-level errors**: Malformed lines/entries are skipped with warnings, tracked by parsers
 //! - **Failure thresholds**: Operations fail if >50% of items fail (parsers, agent files)
-//! - **User feedback**: Summary statistics printed at end showing success/warning/failure counts
+//! - **Parser panics**: A panic while parsing one agent file is caught and counted as that
+//!   file's failure, and one while parsing a history.jsonl line as a malformed line, so a
+//!   single pathological file can't abort the whole build; the panic message isn't printed
+//! - **User feedback**: Counts, skipped files and parse errors are collected in an
+//!   [`IndexReport`] instead of being printed, so a rebuild behind the TUI can't corrupt the
+//!   screen
 //!
 //! This approach balances robustness (handles corrupted files) with reliability (fails on
-//! systematic issues). Errors are reported via stderr (eprintln!) and critical failures
+//! systematic issues). Warnings are recorded in the report and critical failures
 //! propagated via Result types.
 
 use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
 use anyhow::Result;
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
-use crate::indexer::project_discovery::discover_projects;
-use crate::models::{ContentBlock, EntryType, MessageContent, SearchEntry};
-use crate::parsers::{parse_conversation_file, parse_history_file};
+use crate::cache::{IndexCache, IndexedFile};
+use crate::error::ExplorerError;
+use crate::indexer::budget::MemoryBudget;
+use crate::indexer::outcome::classify_outcome;
+use crate::indexer::project_discovery::{
+    discover_projects_with_report, project_path_for_agent_file,
+};
+use crate::indexer::report::IndexReport;
+use crate::models::{
+    ContentBlock, ContentKinds, ConversationEntry, EntryType, MessageContent, SearchEntry,
+    SessionOutcome, SourceKind,
+};
+use crate::parsers::panics::{QuietPanics, catch_quietly};
+use crate::parsers::{
+    EntryRelations, ParseOptions, parse_conversation_file_with_options,
+    parse_history_file_with_stats,
+};
 use crate::utils::strip_ansi_codes;
 
 const ENTRY_TYPE_USER: &str = "user";
 const ENTRY_TYPE_ASSISTANT: &str = "assistant";
 
+/// `model` of messages Claude Code wrote itself rather than a model
+const SYNTHETIC_MODEL: &str = "<synthetic>";
+
 /// Maximum bytes for thinking blocks and image alt text before truncation.
 /// Keeps internal reasoning/descriptions concise for search purposes.
 const MAX_THINKING_CONTENT: usize = 1024;
@@ -222,6 +248,214 @@ fn extract_text_from_content(content: &MessageContent) -> Vec<Cow<'_, str>> {
     }
 }
 
+/// Whether any tool result in the message content is flagged as an error
+fn has_tool_error(content: &MessageContent) -> bool {
+    match content {
+        MessageContent::String(_) => false,
+        MessageContent::Array(blocks) => blocks
+            .iter()
+            .any(|block| matches!(block, ContentBlock::ToolResult { is_error: Some(true), .. })),
+    }
+}
+
+/// Kinds of content blocks in the message content
+fn content_kinds(content: &MessageContent) -> ContentKinds {
+    let MessageContent::Array(blocks) = content else {
+        return ContentKinds::empty();
+    };
+    blocks
+        .iter()
+        .map(|block| match block {
+            ContentBlock::Text { .. } => ContentKinds::empty(),
+            ContentBlock::Thinking { .. } => ContentKinds::THINKING,
+            ContentBlock::ToolUse { .. } => ContentKinds::TOOL_USE,
+            ContentBlock::ToolResult { .. } => ContentKinds::TOOL_RESULT,
+            ContentBlock::Image { .. } => ContentKinds::IMAGE,
+        })
+        .collect()
+}
+
+/// Names of the tools called in the message content, in order of their first call
+fn tool_names(content: &MessageContent) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    if let MessageContent::Array(blocks) = content {
+        for block in blocks {
+            if let ContentBlock::ToolUse { name, .. } = block
+                && !names.contains(name)
+            {
+                names.push(name.clone());
+            }
+        }
+    }
+    names
+}
+
+/// Convert the parsed entries of one agent file into search entries
+///
+/// Keeps user and assistant messages with non-empty text; the text is sanitized of ANSI
+/// escape codes. Parents are resolved to the nearest kept ancestor, and every entry carries
+/// the outcome of the file's session.
+fn conversation_search_entries(
+    entries: Vec<ConversationEntry>,
+    project_path: &Path,
+    agent_file: &Path,
+) -> Vec<SearchEntry> {
+    let relations = EntryRelations::from_entries(&entries);
+    let outcome = classify_outcome(&entries);
+    let mut search_entries: Vec<SearchEntry> = entries
+        .into_iter()
+        .filter_map(|entry| {
+            // Include both user and assistant messages
+            if entry.message.role != ENTRY_TYPE_USER && entry.message.role != ENTRY_TYPE_ASSISTANT {
+                return None;
+            }
+
+            // Extract text from message content using helper function
+            let text_parts = extract_text_from_content(&entry.message.content);
+
+            let display_text = if !text_parts.is_empty() {
+                // Pre-allocate capacity: sum of all text lengths + newlines
+                let total_len: usize = text_parts.iter().map(|s| s.len()).sum();
+                let capacity = total_len + text_parts.len().saturating_sub(1);
+
+                let mut result = String::with_capacity(capacity);
+                result.push_str(&text_parts[0]);
+                for text in &text_parts[1..] {
+                    result.push('\n');
+                    result.push_str(text);
+                }
+                // Sanitize ANSI escape codes to prevent terminal injection
+                strip_ansi_codes(&result)
+            } else {
+                String::new()
+            };
+
+            // Filter out entries with no text content
+            if display_text.trim().is_empty() {
+                return None;
+            }
+
+            // Determine entry type based on message role
+            let entry_type = if entry.message.role == ENTRY_TYPE_ASSISTANT {
+                EntryType::AgentMessage
+            } else {
+                EntryType::UserPrompt
+            };
+            let user = entry.user_identity().map(str::to_string);
+            // Claude Code marks messages it wrote itself (e.g. API errors) as `<synthetic>`
+            let model = entry
+                .message
+                .model
+                .as_deref()
+                .filter(|model| *model != SYNTHETIC_MODEL)
+                .map(strip_ansi_codes);
+            let git_branch = entry.git_branch.as_deref().map(strip_ansi_codes);
+            let cwd = entry.cwd.clone();
+
+            Some(SearchEntry {
+                entry_type,
+                display_text,
+                timestamp: entry.timestamp,
+                project_path: Some(project_path.to_path_buf()),
+                session_id: entry.session_id,
+                root: None,
+                source_file: Some(agent_file.to_path_buf()),
+                had_error: has_tool_error(&entry.message.content),
+                content_kinds: content_kinds(&entry.message.content),
+                tools: tool_names(&entry.message.content),
+                user,
+                model,
+                git_branch,
+                cwd,
+                uuid: Some(entry.uuid),
+                parent_uuid: None,
+                outcome,
+                source: SourceKind::ClaudeCode,
+            })
+        })
+        .collect();
+
+    let kept: HashSet<&str> = search_entries.iter().filter_map(|e| e.uuid.as_deref()).collect();
+    let parents: Vec<Option<String>> = search_entries
+        .iter()
+        .map(|e| {
+            let uuid = e.uuid.as_deref()?;
+            relations.nearest_ancestor(uuid, |a| kept.contains(a)).map(str::to_string)
+        })
+        .collect();
+    for (entry, parent) in search_entries.iter_mut().zip(parents) {
+        entry.parent_uuid = parent;
+    }
+    search_entries
+}
+
+/// Give history.jsonl prompts the user and outcome of their session's conversation file
+///
+/// history.jsonl carries neither; prompts whose session has a conversation file with them
+/// inherit them.
+fn attribute_history_prompts(index: &mut [SearchEntry]) {
+    let session_users: HashMap<String, String> =
+        index.iter().filter_map(|e| Some((e.session_id.clone(), e.user.clone()?))).collect();
+    let session_outcomes: HashMap<String, SessionOutcome> =
+        index.iter().filter_map(|e| Some((e.session_id.clone(), e.outcome?))).collect();
+    if session_users.is_empty() && session_outcomes.is_empty() {
+        return;
+    }
+    for entry in index.iter_mut().filter(|e| e.source_file.is_none()) {
+        if entry.user.is_none() {
+            entry.user = session_users.get(&entry.session_id).cloned();
+        }
+        if entry.outcome.is_none() {
+            entry.outcome = session_outcomes.get(&entry.session_id).copied();
+        }
+    }
+}
+
+/// Parse one agent file into search entries, keeping what parsing left out for the report
+fn index_agent_file(
+    agent_file: &Path,
+    project_path: &Path,
+    options: &ParseOptions,
+) -> Result<IndexedFile> {
+    let parsed = parse_conversation_file_with_options(agent_file, options)?;
+    Ok(IndexedFile {
+        entries: conversation_search_entries(parsed.entries, project_path, agent_file),
+        skipped_lines: parsed.skipped_lines,
+        tool_output_bytes_skipped: parsed.tool_output_bytes_skipped,
+        line_errors: parsed.line_errors,
+    })
+}
+
+/// Run `index`, turning a panic into an error so it fails only the file being parsed
+///
+/// The panic is kept off stderr while a [`QuietPanics`] guard is alive.
+fn catch_parse_panic<T>(index: impl FnOnce() -> Result<T>) -> Result<T> {
+    catch_quietly(index)
+        .unwrap_or_else(|message| Err(anyhow::anyhow!("parser panicked: {}", message)))
+}
+
+/// Re-parse a single agent conversation file into search entries
+///
+/// Used to retry a file that failed during index building (e.g. after fixing it by hand)
+/// without rebuilding the whole index. The project path is decoded from the file's project
+/// directory, with the same validation as project discovery. Returned entries have no root
+/// and are sorted newest first.
+///
+/// # Errors
+///
+/// Returns an error if the file is not inside `<claude_dir>/projects/<encoded path>/`, is a
+/// symlink, or fails to parse with `options`.
+pub fn reindex_agent_file(
+    agent_file: &Path,
+    options: &ParseOptions,
+) -> Result<Vec<SearchEntry>, ExplorerError> {
+    let project_path = project_path_for_agent_file(agent_file)?;
+    let parsed = parse_conversation_file_with_options(agent_file, options)?;
+    let mut search_entries = conversation_search_entries(parsed.entries, &project_path, agent_file);
+    search_entries.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+    Ok(search_entries)
+}
+
 /// Build unified index from user prompts and agent messages
 ///
 /// Creates a searchable index by combining:
@@ -246,8 +480,9 @@ fn extract_text_from_content(content: &MessageContent) -> Vec<Cow<'_, str>> {
 /// - File size validation fails (files >10MB)
 /// - Parser error thresholds are exceeded (>50% lines fail or >100 consecutive errors)
 ///
-/// Individual missing files (history.jsonl) or failed agent files are logged as warnings
-/// and don't fail the entire operation, allowing partial index building.
+/// Individual missing files (history.jsonl) or failed agent files are recorded as warnings
+/// (see [`build_index_with_report`]) and don't fail the entire operation, allowing partial
+/// index building.
 ///
 /// # Examples
 ///
@@ -260,7 +495,78 @@ fn extract_text_from_content(content: &MessageContent) -> Vec<Cow<'_, str>> {
 /// println!("Indexed {} entries", index.len());
 /// # Ok::<(), anyhow::Error>(())
 /// ```
-pub fn build_index(claude_dir: &Path) -> Result<Vec<SearchEntry>> {
+pub fn build_index(claude_dir: &Path) -> Result<Vec<SearchEntry>, ExplorerError> {
+    build_index_with_report(claude_dir, &mut IndexReport::default())
+}
+
+/// Build the search index, recording skipped input and the outcome in `report`
+///
+/// Behaves exactly like [`build_index`]; counts are added to `report` rather than replacing
+/// it, so one report can accumulate several roots.
+///
+/// # Errors
+///
+/// Same as [`build_index`]. The failure is also recorded in `report`.
+pub fn build_index_with_report(
+    claude_dir: &Path,
+    report: &mut IndexReport,
+) -> Result<Vec<SearchEntry>, ExplorerError> {
+    build_index_with_options(claude_dir, &ParseOptions::default(), report)
+}
+
+/// Build the search index with parse-time limits on agent conversation files
+///
+/// Like [`build_index_with_report`]; oversized tool results are reduced according to
+/// `options` and the dropped bytes are added to `report`.
+///
+/// # Errors
+///
+/// Same as [`build_index`]. The failure is also recorded in `report`.
+pub fn build_index_with_options(
+    claude_dir: &Path,
+    options: &ParseOptions,
+    report: &mut IndexReport,
+) -> Result<Vec<SearchEntry>, ExplorerError> {
+    let budget = options.parse_memory_limit.map(MemoryBudget::new);
+    build_index_impl(claude_dir, options, None, budget.as_ref(), report)
+}
+
+/// Build the search index, reusing agent files indexed in `cache`
+///
+/// Like [`build_index_with_options`] with the cache's parse options. Unchanged agent files
+/// (same size and modification time) are taken from the cache instead of being parsed, and
+/// newly parsed files are added to it; call [`IndexCache::save`] to persist them.
+///
+/// # Errors
+///
+/// Same as [`build_index`]. The failure is also recorded in `report`.
+pub fn build_index_with_cache(
+    claude_dir: &Path,
+    cache: &IndexCache,
+    report: &mut IndexReport,
+) -> Result<Vec<SearchEntry>, ExplorerError> {
+    let budget = cache.options().parse_memory_limit.map(MemoryBudget::new);
+    build_index_impl(claude_dir, cache.options(), Some(cache), budget.as_ref(), report)
+}
+
+/// Build the search index of one Claude directory, taking `cache` if given
+///
+/// `budget` bounds the bytes of files being parsed at once (see [`ParseOptions::parse_memory_limit`]);
+/// indexing several directories at the same time shares one budget between them.
+pub(crate) fn build_index_impl(
+    claude_dir: &Path,
+    options: &ParseOptions,
+    cache: Option<&IndexCache>,
+    budget: Option<&MemoryBudget>,
+    report: &mut IndexReport,
+) -> Result<Vec<SearchEntry>, ExplorerError> {
+    if !claude_dir.is_dir() {
+        return Err(ExplorerError::ClaudeDirNotFound(claude_dir.to_path_buf()));
+    }
+    for problem in cache.map(IndexCache::take_load_problems).unwrap_or_default() {
+        report.record_diagnostic(&problem.path, problem.message);
+    }
+
     let mut index = Vec::new();
     let mut agent_files_success = 0;
     let mut agent_files_failed = 0;
@@ -268,9 +574,23 @@ pub fn build_index(claude_dir: &Path) -> Result<Vec<SearchEntry>> {
     // Parse user prompts from history.jsonl
     let history_path = claude_dir.join("history.jsonl");
     if history_path.exists() {
-        match parse_history_file(&history_path) {
-            Ok(entries) => {
-                for entry in entries {
+        match parse_history_file_with_stats(&history_path, options) {
+            Ok(parsed) => {
+                report.record_history_lines_skipped(parsed.skipped);
+                report.record_history_entries_normalized(parsed.normalized);
+                for error in parsed.line_errors {
+                    report.record_diagnostic(&history_path, error);
+                }
+                if parsed.truncated_bytes > 0 {
+                    report.record_skip(
+                        &history_path,
+                        format!(
+                            "larger than the size limit; first {} bytes not read",
+                            parsed.truncated_bytes
+                        ),
+                    );
+                }
+                for entry in parsed.entries {
                     // Filter out whitespace-only entries (not useful for search)
                     if entry.display.trim().is_empty() {
                         continue;
@@ -280,17 +600,17 @@ pub fn build_index(claude_dir: &Path) -> Result<Vec<SearchEntry>> {
                     let project_path = entry.project.as_ref().and_then(|p| {
                         let path = PathBuf::from(p);
                         if !path.is_absolute() {
-                            eprintln!(
-                                "Warning: Skipping entry with non-absolute project path: {}",
-                                p
+                            report.record_skip(
+                                &history_path,
+                                format!("non-absolute project path: {}", p),
                             );
                             return None;
                         }
                         // Reject paths with .. components
                         if path.components().any(|c| matches!(c, std::path::Component::ParentDir)) {
-                            eprintln!(
-                                "Warning: Skipping entry with suspicious project path: {}",
-                                p
+                            report.record_skip(
+                                &history_path,
+                                format!("suspicious project path: {}", p),
                             );
                             return None;
                         }
@@ -302,19 +622,32 @@ pub fn build_index(claude_dir: &Path) -> Result<Vec<SearchEntry>> {
                         timestamp: entry.timestamp,
                         project_path,
                         session_id: entry.session_id,
+                        root: None,
+                        source_file: None,
+                        had_error: false,
+                        content_kinds: ContentKinds::empty(),
+                        tools: Vec::new(),
+                        user: None,
+                        model: None,
+                        git_branch: None,
+                        cwd: None,
+                        uuid: None,
+                        parent_uuid: None,
+                        outcome: None,
+                        source: SourceKind::ClaudeCode,
                     });
                 }
             }
             Err(e) => {
-                eprintln!("Warning: Failed to parse history file: {}", e);
+                report.record_skip(&history_path, format!("{:#}", e));
             }
         }
     } else {
-        eprintln!("Warning: history.jsonl not found at {}", history_path.display());
+        report.record_diagnostic(&history_path, "history.jsonl not found");
     }
 
     // Discover projects and parse agent conversations in parallel
-    match discover_projects(claude_dir) {
+    match discover_projects_with_report(claude_dir, report) {
         Ok(projects) => {
             // Collect all (agent_file, project_path) pairs for parallel processing
             let agent_tasks: Vec<(PathBuf, PathBuf)> = projects
@@ -331,81 +664,54 @@ pub fn build_index(claude_dir: &Path) -> Result<Vec<SearchEntry>> {
             // Thread-safe counters for success/failure tracking
             let success_counter = AtomicUsize::new(0);
             let failure_counter = AtomicUsize::new(0);
-
-            // Process agent files in parallel using rayon
-            let agent_entries: Vec<Vec<SearchEntry>> = agent_tasks
-                .par_iter()
+            let skipped_lines_counter = AtomicUsize::new(0);
+            let tool_output_skipped_counter = AtomicUsize::new(0);
+            let failed_files = Mutex::new(Vec::new());
+            let line_errors = Mutex::new(Vec::new());
+            let _quiet = QuietPanics::install();
+
+            // Process agent files in parallel using rayon (sequentially without `parallel`)
+            #[cfg(feature = "parallel")]
+            let agent_iter = agent_tasks.par_iter();
+            #[cfg(not(feature = "parallel"))]
+            let agent_iter = agent_tasks.iter();
+            let agent_entries: Vec<Vec<SearchEntry>> = agent_iter
                 .filter_map(|(agent_file, project_path)| {
-                    match parse_conversation_file(agent_file) {
-                        Ok(entries) => {
+                    let index = || {
+                        catch_parse_panic(|| {
+                            let _reservation = budget.map(|budget| {
+                                budget.reserve(
+                                    fs::metadata(agent_file).map_or(0, |m| m.len() as usize),
+                                )
+                            });
+                            index_agent_file(agent_file, project_path, options)
+                        })
+                    };
+                    let indexed = match cache {
+                        Some(cache) => cache.get_or_index(agent_file, index),
+                        None => index(),
+                    };
+                    match indexed {
+                        Ok(indexed) => {
                             success_counter.fetch_add(1, Ordering::Relaxed);
-
-                            // Process entries for this agent file
-                            let search_entries: Vec<SearchEntry> = entries
-                                .into_iter()
-                                .filter_map(|entry| {
-                                    // Include both user and assistant messages
-                                    if entry.message.role == ENTRY_TYPE_USER
-                                        || entry.message.role == ENTRY_TYPE_ASSISTANT
-                                    {
-                                        // Extract text from message content using helper function
-                                        let text_parts =
-                                            extract_text_from_content(&entry.message.content);
-
-                                        let display_text = if !text_parts.is_empty() {
-                                            // Pre-allocate capacity: sum of all text lengths + newlines
-                                            let total_len: usize =
-                                                text_parts.iter().map(|s| s.len()).sum();
-                                            let capacity =
-                                                total_len + text_parts.len().saturating_sub(1);
-
-                                            let mut result = String::with_capacity(capacity);
-                                            result.push_str(&text_parts[0]);
-                                            for text in &text_parts[1..] {
-                                                result.push('\n');
-                                                result.push_str(text);
-                                            }
-                                            // Sanitize ANSI escape codes to prevent terminal injection
-                                            strip_ansi_codes(&result)
-                                        } else {
-                                            String::new()
-                                        };
-
-                                        // Filter out entries with no text content
-                                        if display_text.trim().is_empty() {
-                                            return None;
-                                        }
-
-                                        // Determine entry type based on message role
-                                        let entry_type =
-                                            if entry.message.role == ENTRY_TYPE_ASSISTANT {
-                                                EntryType::AgentMessage
-                                            } else {
-                                                EntryType::UserPrompt
-                                            };
-
-                                        Some(SearchEntry {
-                                            entry_type,
-                                            display_text,
-                                            timestamp: entry.timestamp,
-                                            project_path: Some(project_path.clone()),
-                                            session_id: entry.session_id,
-                                        })
-                                    } else {
-                                        None
-                                    }
-                                })
-                                .collect();
-
-                            Some(search_entries)
+                            skipped_lines_counter
+                                .fetch_add(indexed.skipped_lines, Ordering::Relaxed);
+                            tool_output_skipped_counter
+                                .fetch_add(indexed.tool_output_bytes_skipped, Ordering::Relaxed);
+                            if !indexed.line_errors.is_empty() {
+                                line_errors
+                                    .lock()
+                                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                                    .push((agent_file.clone(), indexed.line_errors));
+                            }
+                            Some(indexed.entries)
                         }
                         Err(e) => {
                             failure_counter.fetch_add(1, Ordering::Relaxed);
-                            eprintln!(
-                                "Warning: Failed to parse agent file {}: {}",
-                                agent_file.display(),
-                                e
-                            );
+                            failed_files
+                                .lock()
+                                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                                .push((agent_file.clone(), format!("{:#}", e)));
                             None
                         }
                     }
@@ -420,36 +726,53 @@ pub fn build_index(claude_dir: &Path) -> Result<Vec<SearchEntry>> {
             // Update counters from atomic values
             agent_files_success = success_counter.load(Ordering::Relaxed);
             agent_files_failed = failure_counter.load(Ordering::Relaxed);
+            report.record_agent_lines_skipped(skipped_lines_counter.load(Ordering::Relaxed));
+            report.record_tool_output_skipped(tool_output_skipped_counter.load(Ordering::Relaxed));
+
+            let mut failed_files =
+                failed_files.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner());
+            // Parallel parsing finishes in arbitrary order; keep the report deterministic
+            failed_files.sort();
+            for (path, reason) in failed_files {
+                report.record_skip(&path, reason);
+            }
+            let mut line_errors =
+                line_errors.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner());
+            line_errors.sort();
+            for (path, errors) in line_errors {
+                for error in errors {
+                    report.record_diagnostic(&path, error);
+                }
+            }
         }
         Err(e) => {
-            eprintln!("Warning: Failed to discover projects: {}", e);
+            report.record_skip(&claude_dir.join("projects"), format!("{:#}", e));
         }
     }
 
+    attribute_history_prompts(&mut index);
+
+    report.agent_files_parsed += agent_files_success;
+    report.agent_files_failed += agent_files_failed;
+
     // Check error rate and fail if >50% of agent files failed
     let total_agent_files = agent_files_success + agent_files_failed;
     if total_agent_files > 0 {
         let failure_rate = agent_files_failed as f64 / total_agent_files as f64;
         if failure_rate > 0.5 {
-            anyhow::bail!(
-                "Index building failed: {}/{} agent files failed to parse ({}% failure rate)",
-                agent_files_failed,
-                total_agent_files,
-                (failure_rate * 100.0) as u32
-            );
+            let error = ExplorerError::IndexBuildFailed {
+                failed: agent_files_failed,
+                total: total_agent_files,
+            };
+            report.record_failure(&error);
+            return Err(error);
         }
     }
 
-    // Print summary statistics
-    eprintln!(
-        "Indexed {} entries ({} agent files parsed, {} failed)",
-        index.len(),
-        agent_files_success,
-        agent_files_failed
-    );
+    report.entries_indexed += index.len();
 
     // Sort by timestamp (newest first)
-    index.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    index.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
 
     Ok(index)
 }
@@ -462,6 +785,7 @@ mod tests {
     use tempfile::TempDir;
 
     use super::*;
+    use crate::indexer::report::IndexStatus;
 
     /// Helper to create a test .claude directory structure
     fn create_test_claude_dir() -> TempDir {
@@ -496,6 +820,89 @@ mod tests {
         project_dir
     }
 
+    #[test]
+    fn test_build_index_attributes_users_and_outcomes_by_session() {
+        let claude_dir = create_test_claude_dir();
+        write_history_file(
+            claude_dir.path(),
+            r#"{"display":"Shared prompt","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440002"}
+{"display":"Other prompt","timestamp":1234567891,"sessionId":"550e8400-e29b-41d4-a716-446655440009"}"#,
+        );
+        let agent_content = r#"{"type":"user","message":{"role":"user","content":"Agent prompt"},"timestamp":1234567892,"sessionId":"550e8400-e29b-41d4-a716-446655440002","uuid":"uuid1","userEmail":"alice@example.com"}
+{"type":"user","message":{"role":"user","content":"thanks!"},"timestamp":1234567893,"sessionId":"550e8400-e29b-41d4-a716-446655440002","uuid":"uuid2","userEmail":"alice@example.com"}"#;
+        create_project(
+            claude_dir.path(),
+            "-Users%2Ftest%2Fproject",
+            &[("agent-1.jsonl", agent_content)],
+        );
+
+        let index = build_index(claude_dir.path()).unwrap();
+
+        let user_of =
+            |text: &str| index.iter().find(|e| e.display_text == text).and_then(|e| e.user.clone());
+        assert_eq!(user_of("Agent prompt").as_deref(), Some("alice@example.com"));
+        assert_eq!(user_of("Shared prompt").as_deref(), Some("alice@example.com"));
+        assert_eq!(user_of("Other prompt"), None);
+
+        let outcome_of =
+            |text: &str| index.iter().find(|e| e.display_text == text).and_then(|e| e.outcome);
+        assert_eq!(outcome_of("Agent prompt"), Some(SessionOutcome::Success));
+        assert_eq!(outcome_of("Shared prompt"), Some(SessionOutcome::Success));
+        assert_eq!(outcome_of("Other prompt"), None);
+    }
+
+    #[test]
+    fn test_build_index_records_model_branch_and_cwd() {
+        let claude_dir = create_test_claude_dir();
+        write_history_file(claude_dir.path(), "");
+        let agent_content = r#"{"type":"user","message":{"role":"user","content":"Fix it"},"timestamp":1234567892,"sessionId":"550e8400-e29b-41d4-a716-446655440002","uuid":"uuid1","gitBranch":"feature-x","cwd":"/Users/test/project/web"}
+{"type":"assistant","message":{"role":"assistant","model":"claude-opus-4-1-20250805","content":[{"type":"text","text":"Fixed"}]},"timestamp":1234567893,"sessionId":"550e8400-e29b-41d4-a716-446655440002","uuid":"uuid2"}
+{"type":"assistant","message":{"role":"assistant","model":"<synthetic>","content":[{"type":"text","text":"API Error"}]},"timestamp":1234567894,"sessionId":"550e8400-e29b-41d4-a716-446655440002","uuid":"uuid3"}"#;
+        create_project(
+            claude_dir.path(),
+            "-Users%2Ftest%2Fproject",
+            &[("agent-1.jsonl", agent_content)],
+        );
+
+        let index = build_index(claude_dir.path()).unwrap();
+
+        let model_of = |text: &str| {
+            index.iter().find(|e| e.display_text == text).and_then(|e| e.model.clone())
+        };
+        assert_eq!(model_of("Fixed").as_deref(), Some("claude-opus-4-1-20250805"));
+        assert_eq!(model_of("Fix it"), None);
+        assert_eq!(model_of("API Error"), None);
+
+        let prompt = index.iter().find(|e| e.display_text == "Fix it").unwrap();
+        assert_eq!(prompt.git_branch.as_deref(), Some("feature-x"));
+        assert_eq!(prompt.cwd, Some(PathBuf::from("/Users/test/project/web")));
+    }
+
+    #[test]
+    fn test_build_index_with_cache_reuses_unchanged_files() {
+        let claude_dir = create_test_claude_dir();
+        write_history_file(claude_dir.path(), "");
+        let agent_content = r#"{"type":"user","message":{"role":"user","content":"Cached prompt"},"timestamp":1234567892,"sessionId":"550e8400-e29b-41d4-a716-446655440002","uuid":"uuid1"}
+not json"#;
+        create_project(
+            claude_dir.path(),
+            "-Users%2Ftest%2Fproject",
+            &[("agent-1.jsonl", agent_content)],
+        );
+        let cache = IndexCache::in_memory(ParseOptions::default());
+
+        let first =
+            build_index_with_cache(claude_dir.path(), &cache, &mut IndexReport::default()).unwrap();
+        let mut report = IndexReport::default();
+        let second = build_index_with_cache(claude_dir.path(), &cache, &mut report).unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(cache.hits(), 1);
+        // Counts from the original parse are kept for cached files
+        assert_eq!(report.agent_files_parsed, 1);
+        assert_eq!(report.agent_lines_skipped, 1);
+    }
+
     #[test]
     fn test_build_index_with_valid_data() {
         let claude_dir = create_test_claude_dir();
@@ -638,6 +1045,16 @@ mod tests {
         let err = result.unwrap_err();
         assert!(err.to_string().contains("Index building failed"));
         assert!(err.to_string().contains("66%"));
+        assert!(matches!(err, ExplorerError::IndexBuildFailed { failed: 2, total: 3 }));
+    }
+
+    #[test]
+    fn test_build_index_missing_claude_dir() {
+        let temp = TempDir::new().unwrap();
+        let missing = temp.path().join(".claude");
+
+        let err = build_index(&missing).unwrap_err();
+        assert!(matches!(err, ExplorerError::ClaudeDirNotFound(path) if path == missing));
     }
 
     #[test]
@@ -672,6 +1089,16 @@ mod tests {
         assert_eq!(index.len(), 2);
     }
 
+    #[test]
+    fn test_catch_parse_panic() {
+        let _quiet = QuietPanics::install();
+        assert_eq!(catch_parse_panic(|| Ok(1)).unwrap(), 1);
+        let err = catch_parse_panic::<()>(|| panic!("bad line {}", 3)).unwrap_err();
+        assert_eq!(err.to_string(), "parser panicked: bad line 3");
+        let err = catch_parse_panic::<()>(|| panic!("static message")).unwrap_err();
+        assert_eq!(err.to_string(), "parser panicked: static message");
+    }
+
     #[test]
     fn test_build_index_rejects_path_traversal() {
         let claude_dir = create_test_claude_dir();
@@ -816,6 +1243,53 @@ mod tests {
         assert_eq!(index.len(), 2);
     }
 
+    #[test]
+    fn test_build_index_with_parse_memory_limit() {
+        let claude_dir = create_test_claude_dir();
+        let files: Vec<(String, String)> = (0..8)
+            .map(|i| {
+                (
+                    format!("agent-{}.jsonl", i),
+                    format!(
+                        r#"{{"type":"user","message":{{"role":"user","content":"Prompt {i}"}},"timestamp":{},"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"uuid{i}"}}"#,
+                        1234567890 + i
+                    ),
+                )
+            })
+            .collect();
+        let files: Vec<(&str, &str)> =
+            files.iter().map(|(name, content)| (name.as_str(), content.as_str())).collect();
+        create_project(claude_dir.path(), "-Users%2Ftest%2Fproject", &files);
+
+        // A ceiling below every file's size parses the files one at a time
+        let options = ParseOptions { parse_memory_limit: Some(1), ..Default::default() };
+        let mut report = IndexReport::default();
+        let index = build_index_with_options(claude_dir.path(), &options, &mut report).unwrap();
+        assert_eq!(index.len(), 8);
+        assert_eq!(index[0].display_text, "Prompt 7");
+    }
+
+    #[test]
+    fn test_max_file_size_is_per_build() {
+        let claude_dir = create_test_claude_dir();
+        let small = r#"{"type":"user","message":{"role":"user","content":"Short"},"timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"uuid1"}"#;
+        let large = small.replace("Short", &"Long ".repeat(100)).replace("uuid1", "uuid2");
+        create_project(
+            claude_dir.path(),
+            "-Users%2Ftest%2Fproject",
+            &[("agent-1.jsonl", small), ("agent-2.jsonl", &large)],
+        );
+
+        let tight = ParseOptions { max_file_size: Some(small.len() as u64), ..Default::default() };
+        let mut report = IndexReport::default();
+        let index = build_index_with_options(claude_dir.path(), &tight, &mut report).unwrap();
+        assert_eq!(index.len(), 1);
+        assert!(report.skipped[0].reason.contains("File too large"));
+
+        // The next build in the same process uses its own limit
+        assert_eq!(build_index(claude_dir.path()).unwrap().len(), 2);
+    }
+
     #[test]
     fn test_build_index_tool_input_unicode_truncation() {
         let claude_dir = create_test_claude_dir();
@@ -1125,4 +1599,156 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_build_index_with_report_clean() {
+        let temp_dir = create_test_claude_dir();
+        let claude_dir = temp_dir.path();
+        write_history_file(
+            claude_dir,
+            r#"{"display":"Prompt","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000"}"#,
+        );
+
+        let mut report = IndexReport::default();
+        let index = build_index_with_report(claude_dir, &mut report).unwrap();
+
+        assert_eq!(index.len(), 1);
+        assert_eq!(report.status, IndexStatus::Success);
+        assert_eq!(report.entries_indexed, 1);
+        assert!(report.skipped.is_empty());
+    }
+
+    #[test]
+    fn test_build_index_with_report_partial() {
+        let temp_dir = create_test_claude_dir();
+        let claude_dir = temp_dir.path();
+        write_history_file(
+            claude_dir,
+            r#"{"display":"Prompt","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000"}
+not json
+{"display":"Relative","timestamp":1234567891,"sessionId":"550e8400-e29b-41d4-a716-446655440001","project":"relative/path"}"#,
+        );
+
+        let mut report = IndexReport::default();
+        let index = build_index_with_report(claude_dir, &mut report).unwrap();
+
+        assert_eq!(index.len(), 2);
+        assert_eq!(report.status, IndexStatus::Partial);
+        assert_eq!(report.history_lines_skipped, 1);
+        assert_eq!(report.skipped.len(), 1);
+        assert!(report.skipped[0].reason.contains("non-absolute project path"));
+    }
+
+    #[test]
+    fn test_build_index_with_report_failed_threshold() {
+        let temp_dir = create_test_claude_dir();
+        let claude_dir = temp_dir.path();
+        write_history_file(claude_dir, "");
+        create_project(
+            claude_dir,
+            "-Users%2Ftest%2Fproject",
+            &[("agent-bad.jsonl", "invalid json")],
+        );
+
+        let mut report = IndexReport::default();
+        let result = build_index_with_report(claude_dir, &mut report);
+
+        assert!(result.is_err());
+        assert_eq!(report.status, IndexStatus::Failed);
+        assert_eq!(report.agent_files_failed, 1);
+        assert_eq!(report.skipped.len(), 1);
+        assert!(report.error.as_deref().unwrap().contains("agent files failed"));
+    }
+
+    #[test]
+    fn test_build_index_flags_tool_errors() {
+        let claude_dir = create_test_claude_dir();
+        let content = r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"command not found","is_error":true}]},"timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"uuid1"}
+{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t2","content":"ok"}]},"timestamp":1234567891,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"uuid2"}"#;
+        create_project(claude_dir.path(), "-Users%2Ftest%2Fproject", &[("agent-1.jsonl", content)]);
+
+        let index = build_index(claude_dir.path()).unwrap();
+
+        assert_eq!(index.len(), 2);
+        assert!(!index[0].had_error);
+        assert!(index[1].had_error);
+    }
+
+    #[test]
+    fn test_build_index_records_content_kinds() {
+        let claude_dir = create_test_claude_dir();
+        // The image has no alt text, so only its kind is recorded
+        let content = r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"thinking","thinking":"Hmm"},{"type":"text","text":"Look"},{"type":"image","source":{"type":"base64","data":"xyz"}}]},"timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"uuid1"}
+{"type":"assistant","message":{"role":"assistant","content":[{"type":"tool_use","id":"t1","name":"Bash","input":{}}]},"timestamp":1234567891,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"uuid2"}
+{"type":"assistant","message":{"role":"assistant","content":"Done"},"timestamp":1234567892,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"uuid3"}"#;
+        create_project(claude_dir.path(), "-Users%2Ftest%2Fproject", &[("agent-1.jsonl", content)]);
+
+        let index = build_index(claude_dir.path()).unwrap();
+
+        let kinds: Vec<_> = index.iter().map(|e| e.content_kinds).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ContentKinds::empty(),
+                ContentKinds::TOOL_USE,
+                ContentKinds::THINKING | ContentKinds::IMAGE
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_index_records_tool_names() {
+        let claude_dir = create_test_claude_dir();
+        let content = r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"ls"}},{"type":"tool_use","id":"t2","name":"Read","input":{}},{"type":"tool_use","id":"t3","name":"Bash","input":{"command":"pwd"}}]},"timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"uuid1"}
+{"type":"assistant","message":{"role":"assistant","content":"Done"},"timestamp":1234567891,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"uuid2"}"#;
+        create_project(claude_dir.path(), "-Users%2Ftest%2Fproject", &[("agent-1.jsonl", content)]);
+
+        let index = build_index(claude_dir.path()).unwrap();
+
+        assert_eq!(index.len(), 2);
+        assert!(index[0].tools.is_empty());
+        assert_eq!(index[1].tools, vec!["Bash", "Read"]);
+    }
+
+    #[test]
+    fn test_build_index_links_parents_past_skipped_entries() {
+        let claude_dir = create_test_claude_dir();
+        let content = r#"{"type":"user","message":{"role":"user","content":"Question"},"timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"uuid1"}
+{"type":"assistant","message":{"role":"assistant","content":[{"type":"image","source":"base64data"}]},"timestamp":1234567891,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"uuid2","parentUuid":"uuid1"}
+{"type":"assistant","message":{"role":"assistant","content":"Answer"},"timestamp":1234567892,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"uuid3","parentUuid":"uuid2"}"#;
+        create_project(claude_dir.path(), "-Users%2Ftest%2Fproject", &[("agent-1.jsonl", content)]);
+
+        let index = build_index(claude_dir.path()).unwrap();
+
+        assert_eq!(index.len(), 2);
+        assert_eq!(index[0].uuid.as_deref(), Some("uuid3"));
+        assert_eq!(index[0].parent_uuid.as_deref(), Some("uuid1"));
+        assert_eq!(index[1].parent_uuid, None);
+    }
+
+    #[test]
+    fn test_reindex_agent_file_after_fix() {
+        let claude_dir = create_test_claude_dir();
+        let project_dir = create_project(
+            claude_dir.path(),
+            "-Users%2Ftest%2Fproject",
+            &[("agent-1.jsonl", "not json\nstill not json")],
+        );
+        let agent_file = project_dir.join("agent-1.jsonl");
+        assert!(reindex_agent_file(&agent_file, &ParseOptions::default()).is_err());
+
+        fs::write(
+            &agent_file,
+            r#"{"type":"user","message":{"role":"user","content":"Fixed prompt"},"timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u1"}
+{"type":"assistant","message":{"role":"assistant","content":"Answer"},"timestamp":1234567891,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u2"}"#,
+        )
+        .unwrap();
+
+        let entries = reindex_agent_file(&agent_file, &ParseOptions::default()).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].display_text, "Answer");
+        assert_eq!(entries[1].project_path, Some(PathBuf::from("/Users/test/project")));
+        assert_eq!(entries[1].source_file.as_deref(), Some(agent_file.as_path()));
+    }
 }
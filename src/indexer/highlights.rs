@@ -0,0 +1,207 @@
+//! Highlights: a sampled digest of a large history.
+//!
+//! Entries are grouped by project and ISO week, and each group contributes a handful of
+//! representative entries: its longest prompt, sessions that hit tool errors, and the first
+//! entry of each day. Browsing the digest gives an overview of months of history without
+//! scrolling through every message.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use chrono::{Datelike, IsoWeek, NaiveDate};
+
+use crate::models::{EntryType, SearchEntry};
+
+/// Default number of highlights sampled per project/week group
+pub const DEFAULT_HIGHLIGHTS_PER_GROUP: usize = 3;
+
+/// Why an entry was picked as a highlight
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HighlightReason {
+    /// Longest user prompt of the group
+    LongestPrompt,
+    /// First entry of a session that hit a tool error
+    SessionWithErrors,
+    /// Earliest entry of a day
+    FirstOfDay,
+}
+
+impl HighlightReason {
+    pub fn label(&self) -> &'static str {
+        match self {
+            HighlightReason::LongestPrompt => "longest prompt",
+            HighlightReason::SessionWithErrors => "session with errors",
+            HighlightReason::FirstOfDay => "first of day",
+        }
+    }
+}
+
+/// A sampled entry, referenced by its position in the source index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Highlight {
+    pub index: usize,
+    pub reason: HighlightReason,
+}
+
+/// Highlights of one project during one ISO week
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighlightGroup {
+    pub project: Option<PathBuf>,
+    pub week: IsoWeek,
+    /// Sampled entries in chronological order
+    pub highlights: Vec<Highlight>,
+}
+
+/// Sample up to `per_group` representative entries per project/week
+///
+/// Candidates are taken in priority order (longest prompt, sessions with errors, first of
+/// day) without repeating an entry. Groups are returned newest week first.
+pub fn sample_highlights(entries: &[SearchEntry], per_group: usize) -> Vec<HighlightGroup> {
+    let mut groups: HashMap<(Option<PathBuf>, IsoWeek), Vec<usize>> = HashMap::new();
+    for (i, entry) in entries.iter().enumerate() {
+        let key = (entry.project_path.clone(), entry.timestamp.iso_week());
+        groups.entry(key).or_default().push(i);
+    }
+
+    let mut result: Vec<HighlightGroup> = groups
+        .into_iter()
+        .map(|((project, week), members)| HighlightGroup {
+            project,
+            week,
+            highlights: sample_group(entries, &members, per_group),
+        })
+        .filter(|group| !group.highlights.is_empty())
+        .collect();
+
+    result.sort_by(|a, b| {
+        (b.week.year(), b.week.week())
+            .cmp(&(a.week.year(), a.week.week()))
+            .then_with(|| a.project.cmp(&b.project))
+    });
+    result
+}
+
+fn sample_group(entries: &[SearchEntry], members: &[usize], per_group: usize) -> Vec<Highlight> {
+    let mut candidates = Vec::new();
+
+    // Longest prompt (ties go to the earliest)
+    if let Some(&i) = members
+        .iter()
+        .filter(|&&i| entries[i].entry_type == EntryType::UserPrompt)
+        .max_by_key(|&&i| (entries[i].display_text.chars().count(), std::cmp::Reverse(i)))
+    {
+        candidates.push(Highlight { index: i, reason: HighlightReason::LongestPrompt });
+    }
+
+    // First entry of each session that hit a tool error
+    let mut chronological = members.to_vec();
+    chronological.sort_by_key(|&i| entries[i].timestamp);
+    let error_sessions: HashSet<&str> = members
+        .iter()
+        .filter(|&&i| entries[i].had_error)
+        .map(|&i| entries[i].session_id.as_str())
+        .collect();
+    let mut seen_sessions = HashSet::new();
+    for &i in &chronological {
+        let session = entries[i].session_id.as_str();
+        if error_sessions.contains(session) && seen_sessions.insert(session) {
+            candidates.push(Highlight { index: i, reason: HighlightReason::SessionWithErrors });
+        }
+    }
+
+    // Earliest entry of each day
+    let mut seen_days: HashSet<NaiveDate> = HashSet::new();
+    for &i in &chronological {
+        if seen_days.insert(entries[i].timestamp.date_naive()) {
+            candidates.push(Highlight { index: i, reason: HighlightReason::FirstOfDay });
+        }
+    }
+
+    let mut picked: Vec<Highlight> = Vec::new();
+    for candidate in candidates {
+        if picked.len() == per_group {
+            break;
+        }
+        if !picked.iter().any(|h| h.index == candidate.index) {
+            picked.push(candidate);
+        }
+    }
+    picked.sort_by_key(|h| entries[h.index].timestamp);
+    picked
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    fn entry(text: &str, day: u32, hour: u32, project: &str, session: &str) -> SearchEntry {
+        SearchEntry {
+            entry_type: EntryType::UserPrompt,
+            display_text: text.to_string(),
+            timestamp: Utc.with_ymd_and_hms(2024, 1, day, hour, 0, 0).unwrap(),
+            project_path: Some(PathBuf::from(project)),
+            session_id: session.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_groups_by_project_and_week() {
+        // 2024-01-01 is a Monday: days 1-7 are ISO week 1, day 8 starts week 2
+        let entries = vec![
+            entry("a", 1, 9, "/p/one", "s1"),
+            entry("b", 2, 9, "/p/one", "s1"),
+            entry("c", 8, 9, "/p/one", "s2"),
+            entry("d", 2, 9, "/p/two", "s3"),
+        ];
+
+        let groups = sample_highlights(&entries, 3);
+
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0].week.week(), 2);
+        assert_eq!(groups[1].project, Some(PathBuf::from("/p/one")));
+        assert_eq!(groups[2].project, Some(PathBuf::from("/p/two")));
+    }
+
+    #[test]
+    fn test_priority_and_limit() {
+        let mut errored = entry("tool failed", 3, 12, "/p", "err-session");
+        errored.entry_type = EntryType::AgentMessage;
+        errored.had_error = true;
+        let entries = vec![
+            entry("short", 1, 9, "/p", "s1"),
+            entry("a much longer prompt than the others", 1, 10, "/p", "s1"),
+            entry("tue", 2, 9, "/p", "s2"),
+            entry("opening", 3, 8, "/p", "err-session"),
+            errored,
+        ];
+
+        let groups = sample_highlights(&entries, 3);
+        let highlights = &groups[0].highlights;
+
+        assert_eq!(highlights.len(), 3);
+        let reasons: Vec<_> = highlights.iter().map(|h| (h.index, h.reason)).collect();
+        // Chronological order of: longest prompt, error session's first entry, first of day 1
+        assert_eq!(
+            reasons,
+            vec![
+                (0, HighlightReason::FirstOfDay),
+                (1, HighlightReason::LongestPrompt),
+                (3, HighlightReason::SessionWithErrors),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_zero_per_group_yields_nothing() {
+        let entries = vec![entry("a", 1, 9, "/p", "s1")];
+        assert!(sample_highlights(&entries, 0).is_empty());
+    }
+
+    #[test]
+    fn test_empty_index() {
+        assert!(sample_highlights(&[], 3).is_empty());
+    }
+}
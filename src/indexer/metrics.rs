@@ -0,0 +1,264 @@
+//! Prometheus metrics of indexing runs, as a textfile.
+//!
+//! The tool has no long-running server to scrape, so each run rewrites a file in the
+//! Prometheus text format (`--metrics-file`) for node_exporter's textfile collector. Gauges
+//! describe the last run; counters are read back from the previous file and incremented, so
+//! they keep counting across runs.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+
+use super::report::IndexReport;
+
+/// Prefix of every metric name
+pub const METRIC_PREFIX: &str = "ai_history_explorer";
+
+/// Mode of the metrics file on Unix: node_exporter usually runs as another user, and the file
+/// holds only counts, no history
+pub const METRICS_FILE_MODE: u32 = 0o644;
+
+/// Counters carried over between runs, by series (name and labels)
+const COUNTERS: [(&str, &str); 2] = [
+    ("runs_total", "Commands run, by command"),
+    ("parse_failures_total", "Malformed lines and unreadable files skipped while indexing"),
+];
+
+/// What one run of an index-building command reports
+#[derive(Debug, Clone)]
+pub struct RunMetrics<'a> {
+    /// Subcommand name, e.g. `search`
+    pub command: &'a str,
+    pub report: &'a IndexReport,
+    /// Time spent building (or refreshing) the index
+    pub build_duration: Duration,
+    pub finished_at: DateTime<Utc>,
+}
+
+impl RunMetrics<'_> {
+    /// Render the metrics, continuing the counters of `previous` (an earlier rendering)
+    pub fn render(&self, previous: &str) -> String {
+        let mut counters = previous_counters(previous);
+        let mut add = |series: String, amount: usize| {
+            *counters.entry(series).or_default() += amount as f64;
+        };
+        add(format!("{}_runs_total{{command=\"{}\"}}", METRIC_PREFIX, self.command), 1);
+        let report = self.report;
+        for (kind, count) in [
+            ("history_line", report.history_lines_skipped),
+            ("agent_line", report.agent_lines_skipped),
+            ("agent_file", report.agent_files_failed),
+        ] {
+            add(format!("{}_parse_failures_total{{kind=\"{}\"}}", METRIC_PREFIX, kind), count);
+        }
+
+        let mut out = String::new();
+        for (name, help, value) in [
+            (
+                "index_entries",
+                "Entries in the index after the last run",
+                report.entries_indexed as f64,
+            ),
+            (
+                "index_build_duration_seconds",
+                "Time the last run spent building the index",
+                self.build_duration.as_secs_f64(),
+            ),
+            (
+                "index_skipped_items",
+                "Files and entries left out of the last index",
+                report.skipped.len() as f64,
+            ),
+            (
+                "last_run_exit_code",
+                "Exit code of the last run (0 clean, 3 partial, 4 failed)",
+                report.exit_code() as f64,
+            ),
+            (
+                "last_run_timestamp_seconds",
+                "Unix time the last run finished",
+                self.finished_at.timestamp() as f64,
+            ),
+        ] {
+            push_metric(&mut out, name, help, "gauge");
+            out.push_str(&format!("{}_{} {}\n", METRIC_PREFIX, name, value));
+        }
+        for (name, help) in COUNTERS {
+            push_metric(&mut out, name, help, "counter");
+            let family = format!("{}_{}{{", METRIC_PREFIX, name);
+            for (series, value) in counters.iter().filter(|(series, _)| series.starts_with(&family))
+            {
+                out.push_str(&format!("{} {}\n", series, value));
+            }
+        }
+        out
+    }
+
+    /// Rewrite the metrics file at `path`, continuing its counters
+    ///
+    /// The file is replaced atomically, so the collector never reads a half-written file, and
+    /// is readable by everyone ([`METRICS_FILE_MODE`]). The new contents are written to a fresh
+    /// `<path>.tmp` first; a symlink planted at that name is removed, never written through.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the existing file cannot be read or the new one cannot be written.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let previous = match fs::read_to_string(path) {
+            Ok(previous) => previous,
+            Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        let temp = Path::new(&temp);
+        let mut file = create_temp_file(temp)?;
+        file.write_all(self.render(&previous).as_bytes())
+            .with_context(|| format!("Failed to write {}", temp.display()))?;
+        drop(file);
+        fs::rename(temp, path).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Create `path` as a new file with [`METRICS_FILE_MODE`]
+///
+/// A leftover from an interrupted run is removed first (a symlink is removed, not its
+/// target); creating with `O_EXCL` then fails rather than opening anything put back at `path`
+/// in between.
+fn create_temp_file(path: &Path) -> Result<fs::File> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => {
+            return Err(e).with_context(|| format!("Failed to remove {}", path.display()));
+        }
+        _ => {}
+    }
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(METRICS_FILE_MODE).custom_flags(libc::O_NOFOLLOW);
+    }
+
+    let file =
+        options.open(path).with_context(|| format!("Failed to create {}", path.display()))?;
+
+    // The mode given at creation is narrowed by the umask
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(METRICS_FILE_MODE))
+            .with_context(|| format!("Failed to set permissions on {}", path.display()))?;
+    }
+
+    Ok(file)
+}
+
+fn push_metric(out: &mut String, name: &str, help: &str, kind: &str) {
+    out.push_str(&format!("# HELP {}_{} {}\n", METRIC_PREFIX, name, help));
+    out.push_str(&format!("# TYPE {}_{} {}\n", METRIC_PREFIX, name, kind));
+}
+
+/// Counter series of an earlier rendering; anything else in it is ignored
+fn previous_counters(previous: &str) -> BTreeMap<String, f64> {
+    previous
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.rsplit_once(' '))
+        .filter(|(series, _)| {
+            COUNTERS
+                .iter()
+                .any(|(name, _)| series.starts_with(&format!("{}_{}{{", METRIC_PREFIX, name)))
+        })
+        .filter_map(|(series, value)| Some((series.to_string(), value.parse().ok()?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn report() -> IndexReport {
+        IndexReport {
+            entries_indexed: 42,
+            history_lines_skipped: 2,
+            agent_files_failed: 1,
+            ..Default::default()
+        }
+    }
+
+    fn metrics<'a>(command: &'a str, report: &'a IndexReport) -> RunMetrics<'a> {
+        RunMetrics {
+            command,
+            report,
+            build_duration: Duration::from_millis(1500),
+            finished_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_render_gauges_and_counters() {
+        let report = report();
+        let text = metrics("list", &report).render("");
+
+        assert!(text.contains("# TYPE ai_history_explorer_index_entries gauge\n"));
+        assert!(text.contains("ai_history_explorer_index_entries 42\n"));
+        assert!(text.contains("ai_history_explorer_index_build_duration_seconds 1.5\n"));
+        assert!(text.contains("ai_history_explorer_last_run_timestamp_seconds 1700000000\n"));
+        assert!(text.contains("# TYPE ai_history_explorer_runs_total counter\n"));
+        assert!(text.contains("ai_history_explorer_runs_total{command=\"list\"} 1\n"));
+        assert!(
+            text.contains("ai_history_explorer_parse_failures_total{kind=\"history_line\"} 2\n")
+        );
+        assert!(text.contains("ai_history_explorer_parse_failures_total{kind=\"agent_file\"} 1\n"));
+    }
+
+    #[test]
+    fn test_counters_continue_across_runs() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("ai-history-explorer.prom");
+        let report = report();
+
+        metrics("list", &report).write(&path).unwrap();
+        metrics("list", &report).write(&path).unwrap();
+        metrics("search", &IndexReport::default()).write(&path).unwrap();
+
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.contains("ai_history_explorer_runs_total{command=\"list\"} 2\n"));
+        assert!(text.contains("ai_history_explorer_runs_total{command=\"search\"} 1\n"));
+        assert!(
+            text.contains("ai_history_explorer_parse_failures_total{kind=\"history_line\"} 4\n")
+        );
+        // Gauges describe the last run only
+        assert!(text.contains("ai_history_explorer_index_entries 0\n"));
+        assert!(!temp.path().join("ai-history-explorer.prom.tmp").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_is_world_readable_and_ignores_planted_symlink() {
+        use std::os::unix::fs::{PermissionsExt, symlink};
+
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("ai-history-explorer.prom");
+        let target = temp.path().join("target");
+        fs::write(&target, "untouched").unwrap();
+        symlink(&target, temp.path().join("ai-history-explorer.prom.tmp")).unwrap();
+
+        metrics("list", &report()).write(&path).unwrap();
+
+        assert_eq!(fs::read_to_string(&target).unwrap(), "untouched");
+        assert!(fs::read_to_string(&path).unwrap().contains("ai_history_explorer_runs_total"));
+        let mode = fs::symlink_metadata(&path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, METRICS_FILE_MODE);
+    }
+}
@@ -16,8 +16,34 @@
 //! - **Parser integration**: Delegates line-level error handling to parser modules, which
 //!   apply their own graceful degradation and failure rate checks.
 
+pub mod budget;
 pub mod builder;
+pub mod highlights;
+pub mod metrics;
+pub mod outcome;
 pub mod project_discovery;
+pub mod renames;
+pub mod report;
+pub mod roots;
+pub mod sources;
 
-pub use builder::build_index;
-pub use project_discovery::discover_projects;
+pub use budget::MemoryBudget;
+pub use builder::{
+    build_index, build_index_with_cache, build_index_with_options, build_index_with_report,
+    reindex_agent_file,
+};
+pub use highlights::{Highlight, HighlightGroup, HighlightReason, sample_highlights};
+pub use metrics::RunMetrics;
+pub use outcome::classify_outcome;
+pub use project_discovery::{
+    discover_projects, discover_projects_with_report, project_path_for_agent_file,
+};
+pub use renames::{RenameCandidate, apply_project_merges, detect_renames};
+pub use report::{Diagnostic, IndexReport, IndexStatus, SkippedItem};
+pub use roots::{
+    ClaudeRoot, RootHealth, RootStatus, build_workspace_index, build_workspace_index_with_cache,
+    build_workspace_index_with_options, build_workspace_index_with_report,
+};
+pub use sources::{
+    AiderSource, ClaudeSource, CodexSource, HistorySource, TranscriptSource, merge_sources,
+};
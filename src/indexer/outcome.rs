@@ -0,0 +1,212 @@
+//! Heuristic session outcomes.
+//!
+//! Conversation files don't record whether a session achieved what it set out to do, but how
+//! it ends is a good hint. A session is counted as a success when the user's last prompt thanks
+//! the agent or the final exchange ran `git commit`, and as abandoned when it stops on a
+//! failed tool call the agent never answered or on an interrupted request. Everything else is
+//! unclear. The guess is made once per conversation file while indexing and stored on each of
+//! its entries.
+
+use crate::models::{ContentBlock, ConversationEntry, MessageContent, SessionOutcome};
+
+/// Phrases that make a short last prompt count as thanks
+const THANKS_PHRASES: [&str; 8] =
+    ["thank", "thx", "perfect", "great job", "lgtm", "looks good", "works now", "awesome"];
+
+/// Longer last prompts are treated as new requests even when they start with thanks
+const MAX_THANKS_CHARS: usize = 80;
+
+/// Text Claude Code records as a user message when a request is interrupted
+const INTERRUPTED_MARKER: &str = "[Request interrupted by user";
+
+/// Guess how the session of a conversation file ended
+///
+/// Sidechain messages (subagent work) are ignored; files holding only sidechain messages
+/// have no outcome.
+pub fn classify_outcome(entries: &[ConversationEntry]) -> Option<SessionOutcome> {
+    let main: Vec<&ConversationEntry> =
+        entries.iter().filter(|entry| entry.is_sidechain != Some(true)).collect();
+    if main.is_empty() {
+        return None;
+    }
+
+    // The final exchange starts at the last prompt the user typed
+    let last_prompt = main.iter().rposition(|entry| prompt_text(entry).is_some());
+    if let Some(text) = last_prompt.and_then(|i| prompt_text(main[i])) {
+        if is_thanks(&text) {
+            return Some(SessionOutcome::Success);
+        }
+        if text.trim_start().starts_with(INTERRUPTED_MARKER) {
+            return Some(SessionOutcome::Abandoned);
+        }
+    }
+    if ends_on_error(&main) {
+        return Some(SessionOutcome::Abandoned);
+    }
+    let final_exchange = &main[last_prompt.unwrap_or(0)..];
+    if final_exchange.iter().any(|entry| runs_git_commit(entry)) {
+        return Some(SessionOutcome::Success);
+    }
+    Some(SessionOutcome::Unclear)
+}
+
+/// Text the user typed, if `entry` is a user message with any (tool results don't count)
+fn prompt_text(entry: &ConversationEntry) -> Option<String> {
+    if entry.message.role != "user" {
+        return None;
+    }
+    let text = match &entry.message.content {
+        MessageContent::String(text) => text.clone(),
+        MessageContent::Array(blocks) => blocks
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    };
+    Some(text).filter(|text| !text.trim().is_empty())
+}
+
+fn is_thanks(text: &str) -> bool {
+    let text = text.trim().to_lowercase();
+    text.chars().count() <= MAX_THANKS_CHARS
+        && THANKS_PHRASES.iter().any(|phrase| text.contains(phrase))
+}
+
+/// Whether the session stops on a failed tool result, with no reply or prompt after it
+fn ends_on_error(entries: &[&ConversationEntry]) -> bool {
+    for entry in entries.iter().rev() {
+        if prompt_text(entry).is_some() {
+            return false;
+        }
+        let blocks = match &entry.message.content {
+            MessageContent::Array(blocks) => blocks,
+            MessageContent::String(text) if text.trim().is_empty() => continue,
+            MessageContent::String(_) => return false,
+        };
+        for block in blocks.iter().rev() {
+            match block {
+                ContentBlock::ToolResult { is_error, .. } => return *is_error == Some(true),
+                ContentBlock::Text { text } if !text.trim().is_empty() => return false,
+                _ => {}
+            }
+        }
+    }
+    false
+}
+
+/// Whether `entry` calls a shell tool with a `git commit` command
+fn runs_git_commit(entry: &ConversationEntry) -> bool {
+    let MessageContent::Array(blocks) = &entry.message.content else {
+        return false;
+    };
+    blocks.iter().any(|block| match block {
+        ContentBlock::ToolUse { input, .. } => {
+            input["command"].as_str().is_some_and(|command| command.contains("git commit"))
+        }
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn entry(role: &str, content: serde_json::Value) -> ConversationEntry {
+        serde_json::from_value(json!({
+            "type": role,
+            "message": {"role": role, "content": content},
+            "timestamp": 1000,
+            "sessionId": "550e8400-e29b-41d4-a716-446655440000",
+            "uuid": "u1",
+        }))
+        .unwrap()
+    }
+
+    fn prompt(text: &str) -> ConversationEntry {
+        entry("user", json!(text))
+    }
+
+    fn reply(text: &str) -> ConversationEntry {
+        entry("assistant", json!([{"type": "text", "text": text}]))
+    }
+
+    fn tool_call(command: &str) -> ConversationEntry {
+        entry(
+            "assistant",
+            json!([{"type": "tool_use", "id": "t1", "name": "Bash", "input": {"command": command}}]),
+        )
+    }
+
+    fn tool_result(is_error: bool) -> ConversationEntry {
+        entry(
+            "user",
+            json!([{"type": "tool_result", "tool_use_id": "t1", "content": "out", "is_error": is_error}]),
+        )
+    }
+
+    #[test]
+    fn test_thanks_and_commits_are_successes() {
+        let thanked = [prompt("fix the build"), reply("Fixed."), prompt("Thanks!"), reply("Glad")];
+        assert_eq!(classify_outcome(&thanked), Some(SessionOutcome::Success));
+
+        let committed = [
+            prompt("fix the build and commit"),
+            tool_call("cargo build"),
+            tool_result(false),
+            tool_call("git commit -m 'Fix build'"),
+            tool_result(false),
+            reply("Committed."),
+        ];
+        assert_eq!(classify_outcome(&committed), Some(SessionOutcome::Success));
+
+        // A long prompt is a new request, even when it starts with thanks
+        let request = prompt(&format!("thanks, now {}", "refactor the parser ".repeat(5)));
+        assert_eq!(classify_outcome(&[request, reply("ok")]), Some(SessionOutcome::Unclear));
+    }
+
+    #[test]
+    fn test_errors_and_interruptions_are_abandoned() {
+        let failed = [prompt("run the tests"), tool_call("cargo test"), tool_result(true)];
+        assert_eq!(classify_outcome(&failed), Some(SessionOutcome::Abandoned));
+
+        let interrupted = [
+            prompt("run the tests"),
+            tool_call("cargo test"),
+            prompt("[Request interrupted by user]"),
+        ];
+        assert_eq!(classify_outcome(&interrupted), Some(SessionOutcome::Abandoned));
+
+        // An error the agent answered is not where the session stopped
+        let answered = [
+            prompt("run the tests"),
+            tool_call("cargo test"),
+            tool_result(true),
+            reply("Two tests fail."),
+        ];
+        assert_eq!(classify_outcome(&answered), Some(SessionOutcome::Unclear));
+    }
+
+    #[test]
+    fn test_commits_before_the_last_prompt_dont_count() {
+        let entries = [
+            prompt("commit"),
+            tool_call("git commit -m x"),
+            prompt("now add docs"),
+            reply("Done."),
+        ];
+        assert_eq!(classify_outcome(&entries), Some(SessionOutcome::Unclear));
+    }
+
+    #[test]
+    fn test_sidechain_only_files_have_no_outcome() {
+        let mut sidechain = prompt("thanks");
+        sidechain.is_sidechain = Some(true);
+        assert_eq!(classify_outcome(&[sidechain]), None);
+        assert_eq!(classify_outcome(&[]), None);
+    }
+}
@@ -1,7 +1,9 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result, bail};
+use anyhow::Context;
 
+use crate::error::{ExplorerError, Result};
+use crate::indexer::IndexReport;
 use crate::models::ProjectInfo;
 use crate::utils::{decode_and_validate_path, safe_open_dir, validate_path_not_symlink};
 
@@ -51,9 +53,18 @@ fn is_uuid_pattern(s: &str) -> bool {
 /// - More than [`MAX_PROJECTS`] (1000) projects are found (security: resource exhaustion)
 /// - A project has more than [`MAX_AGENT_FILES_PER_PROJECT`] (1000) agent files
 ///
-/// Individual project directories with invalid encoded names or read errors are logged
-/// as warnings and skipped (graceful degradation).
+/// Individual project directories with invalid encoded names or read errors are skipped
+/// (graceful degradation); [`discover_projects_with_report`] records why.
 pub fn discover_projects(claude_dir: &Path) -> Result<Vec<ProjectInfo>> {
+    discover_projects_with_report(claude_dir, &mut IndexReport::default())
+}
+
+/// Like [`discover_projects`], recording skipped directories and files as diagnostics in
+/// `report`
+pub fn discover_projects_with_report(
+    claude_dir: &Path,
+    report: &mut IndexReport,
+) -> Result<Vec<ProjectInfo>> {
     let projects_dir = claude_dir.join("projects");
 
     // Return empty vec if projects directory doesn't exist
@@ -85,26 +96,23 @@ pub fn discover_projects(claude_dir: &Path) -> Result<Vec<ProjectInfo>> {
         let decoded_path = match decode_and_validate_path(&encoded_name) {
             Ok(path) => path,
             Err(e) => {
-                eprintln!("Warning: Skipping invalid project directory {}: {}", encoded_name, e);
+                report.record_diagnostic(&path, format!("invalid project directory: {:#}", e));
                 continue;
             }
         };
 
         // Security: Validate project directory is not a symlink
         if let Err(e) = validate_path_not_symlink(&path) {
-            eprintln!(
-                "Warning: Skipping project directory (symlink not allowed) {}: {}",
-                encoded_name, e
-            );
+            report.record_diagnostic(&path, format!("symlink not allowed: {:#}", e));
             continue;
         }
 
         // Security: Enforce maximum projects limit
         if projects.len() >= MAX_PROJECTS {
-            bail!(
-                "Resource limit exceeded: Found more than {} projects. This may indicate a misconfiguration or attack.",
+            return Err(ExplorerError::ResourceLimitExceeded(format!(
+                "Found more than {} projects. This may indicate a misconfiguration or attack.",
                 MAX_PROJECTS
-            );
+            )));
         }
 
         // Find all *.jsonl conversation files in this project directory
@@ -116,10 +124,9 @@ pub fn discover_projects(claude_dir: &Path) -> Result<Vec<ProjectInfo>> {
                     let file = match entry {
                         Ok(file) => file,
                         Err(err) => {
-                            eprintln!(
-                                "Warning: Failed to read file entry inside {}: {}",
-                                path.display(),
-                                err
+                            report.record_diagnostic(
+                                &path,
+                                format!("failed to read a file entry: {}", err),
                             );
                             continue;
                         }
@@ -141,19 +148,17 @@ pub fn discover_projects(claude_dir: &Path) -> Result<Vec<ProjectInfo>> {
 
                             // Security: Enforce maximum agent files per project limit
                             if agent_files.len() >= MAX_AGENT_FILES_PER_PROJECT {
-                                bail!(
-                                    "Resource limit exceeded: Project {} has more than {} agent files",
-                                    encoded_name,
-                                    MAX_AGENT_FILES_PER_PROJECT
-                                );
+                                return Err(ExplorerError::ResourceLimitExceeded(format!(
+                                    "Project {} has more than {} agent files",
+                                    encoded_name, MAX_AGENT_FILES_PER_PROJECT
+                                )));
                             }
 
                             // Security: Skip symlinked agent files
                             if let Err(e) = validate_path_not_symlink(&file_path) {
-                                eprintln!(
-                                    "Warning: Skipping agent file (symlink not allowed) {}: {}",
-                                    file_path.display(),
-                                    e
+                                report.record_diagnostic(
+                                    &file_path,
+                                    format!("symlink not allowed: {:#}", e),
                                 );
                                 continue;
                             }
@@ -163,7 +168,7 @@ pub fn discover_projects(claude_dir: &Path) -> Result<Vec<ProjectInfo>> {
                 }
             }
             Err(e) => {
-                eprintln!("Warning: Failed to read project directory {}: {}", path.display(), e);
+                report.record_diagnostic(&path, format!("failed to read: {:#}", e));
                 continue;
             }
         }
@@ -174,6 +179,33 @@ pub fn discover_projects(claude_dir: &Path) -> Result<Vec<ProjectInfo>> {
     Ok(projects)
 }
 
+/// Decode the project path of a conversation file located at
+/// `<claude_dir>/projects/<encoded path>/<file>.jsonl`
+///
+/// Applies the same validation as [`discover_projects`] (valid encoding, no symlinked
+/// project directory or file).
+///
+/// # Errors
+///
+/// Returns an error if the file is not inside a project directory or fails validation.
+pub fn project_path_for_agent_file(agent_file: &Path) -> anyhow::Result<PathBuf> {
+    let project_dir = agent_file
+        .parent()
+        .filter(|dir| dir.parent().and_then(Path::file_name) == Some("projects".as_ref()))
+        .with_context(|| {
+            format!("Not inside a Claude projects directory: {}", agent_file.display())
+        })?;
+    let encoded_name = project_dir
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .context("Project directory has no name")?;
+
+    let decoded_path = decode_and_validate_path(&encoded_name)?;
+    validate_path_not_symlink(project_dir)?;
+    validate_path_not_symlink(agent_file)?;
+    Ok(decoded_path)
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -521,4 +553,20 @@ mod tests {
         assert_eq!(projects.len(), 1);
         assert_eq!(projects[0].agent_files.len(), MAX_AGENT_FILES_PER_PROJECT);
     }
+
+    #[test]
+    fn test_project_path_for_agent_file() {
+        let claude_dir = create_test_claude_dir();
+        let projects_dir = claude_dir.path().join("projects");
+        fs::create_dir(&projects_dir).unwrap();
+        let project_dir =
+            create_project_dir(&projects_dir, "-Users%2Ftest%2Fproject", &["agent-123.jsonl"]);
+
+        let path = project_path_for_agent_file(&project_dir.join("agent-123.jsonl")).unwrap();
+        assert_eq!(path, PathBuf::from("/Users/test/project"));
+
+        // history.jsonl is not inside a project directory
+        let history = claude_dir.path().join("history.jsonl");
+        assert!(project_path_for_agent_file(&history).is_err());
+    }
 }
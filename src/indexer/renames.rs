@@ -0,0 +1,181 @@
+//! Renamed project detection and merging.
+//!
+//! Claude Code keys history by the working directory, so moving a repository
+//! (`~/code/app` → `~/work/app`) splits its history into two projects. Two project paths
+//! are a probable rename when they share a directory name and either share sessions (a
+//! session resumed after the move) or follow each other in time without overlapping. Merges
+//! confirmed by the user map the old path to the new one before filtering and stats.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+
+use crate::models::SearchEntry;
+
+/// Longest chain of merges followed (`a` → `b` → `c`), which also stops merge cycles
+const MAX_MERGE_DEPTH: usize = 16;
+
+/// Two project paths that probably are the same project before and after a move
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameCandidate {
+    /// Path used before the move
+    pub from: PathBuf,
+    /// Path used after the move
+    pub to: PathBuf,
+    /// Sessions with entries under both paths
+    pub shared_sessions: usize,
+    /// Last activity under `from`
+    pub from_last: DateTime<Utc>,
+    /// First activity under `to`
+    pub to_first: DateTime<Utc>,
+}
+
+impl RenameCandidate {
+    /// Whether all activity under `from` happened before any under `to`
+    pub fn is_sequential(&self) -> bool {
+        self.from_last <= self.to_first
+    }
+}
+
+/// Activity of one project path
+struct ProjectActivity<'a> {
+    sessions: HashSet<&'a str>,
+    first: DateTime<Utc>,
+    last: DateTime<Utc>,
+}
+
+/// Find project paths that look like the same project after a move
+///
+/// Candidates sharing sessions come first, then the most recently moved.
+pub fn detect_renames(index: &[SearchEntry]) -> Vec<RenameCandidate> {
+    let mut projects: HashMap<&Path, ProjectActivity> = HashMap::new();
+    for entry in index {
+        let Some(path) = entry.project_path.as_deref() else { continue };
+        let activity = projects.entry(path).or_insert_with(|| ProjectActivity {
+            sessions: HashSet::new(),
+            first: entry.timestamp,
+            last: entry.timestamp,
+        });
+        activity.sessions.insert(&entry.session_id);
+        activity.first = activity.first.min(entry.timestamp);
+        activity.last = activity.last.max(entry.timestamp);
+    }
+
+    let mut by_name: BTreeMap<&std::ffi::OsStr, Vec<&Path>> = BTreeMap::new();
+    for path in projects.keys() {
+        if let Some(name) = path.file_name() {
+            by_name.entry(name).or_default().push(path);
+        }
+    }
+
+    let mut candidates = Vec::new();
+    for paths in by_name.values_mut() {
+        paths.sort();
+        for (i, a) in paths.iter().enumerate() {
+            for b in &paths[i + 1..] {
+                // The path active last is where the project lives now
+                let (from, to) = if projects[a].last <= projects[b].last { (a, b) } else { (b, a) };
+                let (old, new) = (&projects[from], &projects[to]);
+                let candidate = RenameCandidate {
+                    from: from.to_path_buf(),
+                    to: to.to_path_buf(),
+                    shared_sessions: old.sessions.intersection(&new.sessions).count(),
+                    from_last: old.last,
+                    to_first: new.first,
+                };
+                if candidate.shared_sessions > 0 || candidate.is_sequential() {
+                    candidates.push(candidate);
+                }
+            }
+        }
+    }
+    candidates.sort_by(|a, b| {
+        b.shared_sessions.cmp(&a.shared_sessions).then(b.to_first.cmp(&a.to_first))
+    });
+    candidates
+}
+
+/// Rewrite project paths merged into another one (following chains of merges)
+pub fn apply_project_merges(index: &mut [SearchEntry], merges: &BTreeMap<PathBuf, PathBuf>) {
+    if merges.is_empty() {
+        return;
+    }
+    for entry in index {
+        let Some(mut path) = entry.project_path.as_ref() else { continue };
+        let mut depth = 0;
+        while let Some(target) = merges.get(path) {
+            if depth == MAX_MERGE_DEPTH {
+                break;
+            }
+            path = target;
+            depth += 1;
+        }
+        if depth > 0 {
+            entry.project_path = Some(path.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::EntryType;
+
+    fn entry(project: &str, session: &str, secs: i64) -> SearchEntry {
+        SearchEntry {
+            entry_type: EntryType::UserPrompt,
+            display_text: "prompt".to_string(),
+            timestamp: DateTime::from_timestamp(secs, 0).unwrap(),
+            project_path: Some(PathBuf::from(project)),
+            session_id: session.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_detects_moves_and_shared_sessions() {
+        let index = vec![
+            // Moved: no overlap in time
+            entry("/code/app", "s1", 100),
+            entry("/work/app", "s2", 200),
+            // Same name, used side by side: not a rename
+            entry("/a/tools", "s3", 100),
+            entry("/b/tools", "s4", 150),
+            entry("/a/tools", "s5", 300),
+            // Session resumed after the move
+            entry("/old/api", "s6", 100),
+            entry("/new/api", "s6", 150),
+            entry("/old/api", "s7", 130),
+        ];
+
+        let candidates = detect_renames(&index);
+        let pairs: Vec<_> = candidates
+            .iter()
+            .map(|c| (c.from.to_str().unwrap(), c.to.to_str().unwrap(), c.shared_sessions))
+            .collect();
+        assert_eq!(pairs, vec![("/old/api", "/new/api", 1), ("/code/app", "/work/app", 0)]);
+        assert!(candidates[1].is_sequential());
+    }
+
+    #[test]
+    fn test_apply_project_merges_follows_chains() {
+        let mut index =
+            vec![entry("/a/app", "s1", 1), entry("/b/app", "s2", 2), entry("/c/app", "s3", 3)];
+        let merges = BTreeMap::from([
+            (PathBuf::from("/a/app"), PathBuf::from("/b/app")),
+            (PathBuf::from("/b/app"), PathBuf::from("/c/app")),
+        ]);
+        apply_project_merges(&mut index, &merges);
+        assert!(index.iter().all(|e| e.project_path == Some(PathBuf::from("/c/app"))));
+
+        // A cycle stops instead of looping forever
+        let cycle = BTreeMap::from([
+            (PathBuf::from("/x"), PathBuf::from("/y")),
+            (PathBuf::from("/y"), PathBuf::from("/x")),
+        ]);
+        let mut index = vec![entry("/x", "s1", 1)];
+        apply_project_merges(&mut index, &cycle);
+        assert_eq!(index[0].project_path, Some(PathBuf::from("/x")));
+    }
+}
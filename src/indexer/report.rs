@@ -0,0 +1,238 @@
+//! Machine-readable summary of an indexing run.
+//!
+//! Index building degrades gracefully: malformed lines, unreadable agent files and suspicious
+//! project paths are skipped with a warning instead of failing the run. [`IndexReport`]
+//! records what was skipped so scripted callers can tell a clean index from a partial one,
+//! and maps the outcome to a process exit code.
+//!
+//! Nothing is printed while indexing, which may happen behind the TUI: the CLI prints the
+//! report after the run (`--verbose`) and the TUI lists it in its warnings view.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+/// Exit code for a clean indexing run
+pub const EXIT_SUCCESS: u8 = 0;
+/// Exit code when the index was built but some input was skipped
+pub const EXIT_PARTIAL: u8 = 3;
+/// Exit code when indexing failed (e.g. the agent file failure threshold was exceeded)
+pub const EXIT_FAILED: u8 = 4;
+
+/// Overall outcome of an indexing run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexStatus {
+    #[default]
+    Success,
+    Partial,
+    Failed,
+}
+
+impl IndexStatus {
+    pub fn exit_code(self) -> u8 {
+        match self {
+            IndexStatus::Success => EXIT_SUCCESS,
+            IndexStatus::Partial => EXIT_PARTIAL,
+            IndexStatus::Failed => EXIT_FAILED,
+        }
+    }
+}
+
+/// A file (or entry within a file) that was left out of the index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SkippedItem {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+/// A warning about the input that doesn't leave anything out of the index on its own, such as
+/// the parse error of a line counted in the skipped lines
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+/// Accumulated statistics and skipped input of one or more indexing passes
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct IndexReport {
+    pub status: IndexStatus,
+    pub entries_indexed: usize,
+    pub history_lines_skipped: usize,
+    /// history.jsonl entries without a session id or timestamp that were indexed with fallbacks
+    pub history_entries_normalized: usize,
+    pub agent_files_parsed: usize,
+    pub agent_files_failed: usize,
+    pub agent_lines_skipped: usize,
+    /// Bytes of oversized tool output dropped at parse time (by request, so not partial)
+    pub tool_output_bytes_skipped: usize,
+    pub skipped: Vec<SkippedItem>,
+    pub diagnostics: Vec<Diagnostic>,
+    pub error: Option<String>,
+}
+
+impl IndexReport {
+    /// Record a skipped file or entry, downgrading the status to partial
+    pub fn record_skip(&mut self, path: &Path, reason: impl Into<String>) {
+        self.skipped.push(SkippedItem { path: path.to_path_buf(), reason: reason.into() });
+        self.mark_partial();
+    }
+
+    /// Record a warning that doesn't change the status
+    pub fn record_diagnostic(&mut self, path: &Path, message: impl Into<String>) {
+        self.diagnostics.push(Diagnostic { path: path.to_path_buf(), message: message.into() });
+    }
+
+    /// Record malformed history.jsonl lines that were skipped
+    pub fn record_history_lines_skipped(&mut self, count: usize) {
+        if count > 0 {
+            self.history_lines_skipped += count;
+            self.mark_partial();
+        }
+    }
+
+    /// Record history.jsonl entries completed with a fallback session id or timestamp
+    ///
+    /// Unlike skipped lines, these don't make the run partial: nothing was left out.
+    pub fn record_history_entries_normalized(&mut self, count: usize) {
+        self.history_entries_normalized += count;
+    }
+
+    /// Record malformed lines skipped in agent conversation files
+    pub fn record_agent_lines_skipped(&mut self, count: usize) {
+        if count > 0 {
+            self.agent_lines_skipped += count;
+            self.mark_partial();
+        }
+    }
+
+    /// Record tool output dropped by the oversized tool result limit
+    pub fn record_tool_output_skipped(&mut self, bytes: usize) {
+        self.tool_output_bytes_skipped += bytes;
+    }
+
+    /// Add the statistics and skipped input of another pass (e.g. one root of a workspace)
+    ///
+    /// A failed pass makes this report partial, not failed: its entries are just missing.
+    pub fn merge(&mut self, other: &IndexReport) {
+        self.entries_indexed += other.entries_indexed;
+        self.history_lines_skipped += other.history_lines_skipped;
+        self.history_entries_normalized += other.history_entries_normalized;
+        self.agent_files_parsed += other.agent_files_parsed;
+        self.agent_files_failed += other.agent_files_failed;
+        self.agent_lines_skipped += other.agent_lines_skipped;
+        self.tool_output_bytes_skipped += other.tool_output_bytes_skipped;
+        self.skipped.extend(other.skipped.iter().cloned());
+        self.diagnostics.extend(other.diagnostics.iter().cloned());
+        if other.status != IndexStatus::Success {
+            self.mark_partial();
+        }
+    }
+
+    /// Record a fatal indexing error
+    pub fn record_failure(&mut self, error: &dyn std::fmt::Display) {
+        self.status = IndexStatus::Failed;
+        self.error = Some(format!("{:#}", error));
+    }
+
+    pub fn exit_code(&self) -> u8 {
+        self.status.exit_code()
+    }
+
+    /// Serialize the report as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("IndexReport is always serializable")
+    }
+
+    fn mark_partial(&mut self) {
+        if self.status == IndexStatus::Success {
+            self.status = IndexStatus::Partial;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_report_is_success() {
+        let report = IndexReport::default();
+        assert_eq!(report.status, IndexStatus::Success);
+        assert_eq!(report.exit_code(), EXIT_SUCCESS);
+    }
+
+    #[test]
+    fn test_skips_downgrade_to_partial() {
+        let mut report = IndexReport::default();
+        report.record_history_lines_skipped(0);
+        assert_eq!(report.status, IndexStatus::Success);
+
+        report.record_skip(Path::new("/tmp/agent-1.jsonl"), "parse error");
+        assert_eq!(report.status, IndexStatus::Partial);
+        assert_eq!(report.exit_code(), EXIT_PARTIAL);
+        assert_eq!(report.skipped.len(), 1);
+    }
+
+    #[test]
+    fn test_diagnostics_keep_status() {
+        let mut report = IndexReport::default();
+        report.record_diagnostic(Path::new("/tmp/history.jsonl"), "line 3: expected value");
+
+        assert_eq!(report.status, IndexStatus::Success);
+        assert_eq!(report.diagnostics[0].message, "line 3: expected value");
+    }
+
+    #[test]
+    fn test_tool_output_skips_keep_status() {
+        let mut report = IndexReport::default();
+        report.record_tool_output_skipped(1024);
+        report.record_tool_output_skipped(512);
+
+        assert_eq!(report.tool_output_bytes_skipped, 1536);
+        assert_eq!(report.status, IndexStatus::Success);
+    }
+
+    #[test]
+    fn test_failure_is_not_downgraded() {
+        let mut report = IndexReport::default();
+        report.record_failure(&anyhow::anyhow!("too many failures"));
+        report.record_agent_lines_skipped(2);
+
+        assert_eq!(report.status, IndexStatus::Failed);
+        assert_eq!(report.exit_code(), EXIT_FAILED);
+        assert_eq!(report.error.as_deref(), Some("too many failures"));
+    }
+
+    #[test]
+    fn test_merge_downgrades_failure_to_partial() {
+        let mut root =
+            IndexReport { entries_indexed: 2, agent_files_failed: 3, ..Default::default() };
+        root.record_skip(Path::new("/tmp/agent-1.jsonl"), "parse error");
+        root.record_failure(&anyhow::anyhow!("too many failures"));
+
+        let mut report = IndexReport { entries_indexed: 5, ..Default::default() };
+        report.merge(&root);
+
+        assert_eq!(report.status, IndexStatus::Partial);
+        assert_eq!(report.entries_indexed, 7);
+        assert_eq!(report.agent_files_failed, 3);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.error, None);
+    }
+
+    #[test]
+    fn test_to_json() {
+        let mut report = IndexReport { entries_indexed: 5, ..Default::default() };
+        report.record_skip(Path::new("/tmp/agent-1.jsonl"), "parse error");
+
+        let value: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(value["status"], "partial");
+        assert_eq!(value["entries_indexed"], 5);
+        assert_eq!(value["skipped"][0]["path"], "/tmp/agent-1.jsonl");
+        assert_eq!(value["skipped"][0]["reason"], "parse error");
+        assert!(value["error"].is_null());
+        assert_eq!(value["tool_output_bytes_skipped"], 0);
+    }
+}
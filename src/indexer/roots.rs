@@ -0,0 +1,474 @@
+//! Multi-root workspace indexing.
+//!
+//! A workspace is a set of named Claude directories (e.g. a local `~/.claude` plus copies
+//! synced from other machines). Roots are indexed concurrently and independently, each with
+//! its own [`IndexReport`] and agent file failure threshold, so a corrupted root (say a
+//! half-synced backup) is reported as failed instead of rejecting the others. Entries are
+//! tagged with the root name, and a [`RootStatus`] records per-root counts, refresh time and
+//! health so front-ends can show an overview and toggle roots on and off.
+
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use anyhow::{Result, anyhow, bail};
+use chrono::{DateTime, Utc};
+
+use crate::cache::IndexCache;
+use crate::indexer::budget::MemoryBudget;
+use crate::indexer::builder::build_index_impl;
+use crate::indexer::report::IndexReport;
+use crate::models::SearchEntry;
+use crate::parsers::ParseOptions;
+use crate::parsers::panics::panic_message;
+
+/// Name given to the root when none is specified explicitly
+pub const DEFAULT_ROOT_NAME: &str = "default";
+
+/// A named Claude directory participating in the workspace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaudeRoot {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl ClaudeRoot {
+    pub fn new(name: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        Self { name: name.into(), path: path.into() }
+    }
+
+    /// Parse a root specification of the form `name=path` or just `path`
+    ///
+    /// When no name is given, the root is named after the directory containing it
+    /// (`/backups/laptop/.claude` → `laptop`), falling back to the last path component.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        if spec.is_empty() {
+            bail!("Empty Claude directory specification");
+        }
+
+        if let Some((name, path)) = spec.split_once('=') {
+            let name = name.trim();
+            let path = path.trim();
+            if name.is_empty() || path.is_empty() {
+                bail!("Invalid Claude directory specification: '{}' (expected name=path)", spec);
+            }
+            return Ok(Self::new(name, path));
+        }
+
+        let path = PathBuf::from(spec);
+        Ok(Self::new(derive_root_name(&path), path))
+    }
+}
+
+/// Derive a short display name for a root from its path
+fn derive_root_name(path: &Path) -> String {
+    let last = path.file_name().map(|n| n.to_string_lossy().to_string());
+    match last.as_deref() {
+        Some(".claude") | None => path
+            .parent()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| DEFAULT_ROOT_NAME.to_string()),
+        Some(name) => name.to_string(),
+    }
+}
+
+/// Health of a root after its last indexing pass
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RootHealth {
+    /// Indexed successfully with at least one entry
+    Ok,
+    /// Indexed successfully but contained no entries
+    Empty,
+    /// Root directory does not exist
+    Missing,
+    /// Indexing failed (e.g. too many corrupt agent files); see [`RootStatus::report`]
+    Failed,
+}
+
+impl RootHealth {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RootHealth::Ok => "ok",
+            RootHealth::Empty => "empty",
+            RootHealth::Missing => "missing",
+            RootHealth::Failed => "failed",
+        }
+    }
+}
+
+/// Per-root indexing summary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootStatus {
+    pub name: String,
+    pub path: PathBuf,
+    pub entry_count: usize,
+    pub last_refresh: DateTime<Utc>,
+    pub health: RootHealth,
+    /// Statistics and skipped input of this root alone (with the error if it failed)
+    pub report: IndexReport,
+}
+
+/// Build a combined index over several Claude roots
+///
+/// Every entry is tagged with the name of the root it came from. Missing root directories
+/// are reported as [`RootHealth::Missing`] and roots that fail to index as
+/// [`RootHealth::Failed`] rather than failing the whole workspace.
+///
+/// The combined index is sorted by timestamp (newest first), matching [`build_index`](crate::indexer::build_index).
+///
+/// # Errors
+///
+/// Returns an error if two roots share the same name, or if every existing root fails to
+/// index (with the error of the first one).
+pub fn build_workspace_index(roots: &[ClaudeRoot]) -> Result<(Vec<SearchEntry>, Vec<RootStatus>)> {
+    build_workspace_index_with_report(roots, &mut IndexReport::default())
+}
+
+/// Build a combined index over several Claude roots, accumulating all roots into `report`
+///
+/// Missing and failed roots are recorded as skipped, making the run partial.
+///
+/// # Errors
+///
+/// Same as [`build_workspace_index`].
+pub fn build_workspace_index_with_report(
+    roots: &[ClaudeRoot],
+    report: &mut IndexReport,
+) -> Result<(Vec<SearchEntry>, Vec<RootStatus>)> {
+    build_workspace_index_with_options(roots, &ParseOptions::default(), report)
+}
+
+/// Build a combined index over several Claude roots with parse-time limits
+///
+/// Like [`build_workspace_index_with_report`], passing `options` to every root.
+///
+/// # Errors
+///
+/// Same as [`build_workspace_index`].
+pub fn build_workspace_index_with_options(
+    roots: &[ClaudeRoot],
+    options: &ParseOptions,
+    report: &mut IndexReport,
+) -> Result<(Vec<SearchEntry>, Vec<RootStatus>)> {
+    let budget = options.parse_memory_limit.map(MemoryBudget::new);
+    build_workspace_index_impl(roots, budget.as_ref(), report, |path, budget, report| {
+        Ok(build_index_impl(path, options, None, budget, report)?)
+    })
+}
+
+/// Build a combined index over several Claude roots, reusing agent files indexed in `cache`
+///
+/// Like [`build_workspace_index_with_options`], indexing every root through
+/// [`build_index_with_cache`](crate::indexer::build_index_with_cache).
+///
+/// # Errors
+///
+/// Same as [`build_workspace_index`].
+pub fn build_workspace_index_with_cache(
+    roots: &[ClaudeRoot],
+    cache: &IndexCache,
+    report: &mut IndexReport,
+) -> Result<(Vec<SearchEntry>, Vec<RootStatus>)> {
+    let budget = cache.options().parse_memory_limit.map(MemoryBudget::new);
+    build_workspace_index_impl(roots, budget.as_ref(), report, |path, budget, report| {
+        Ok(build_index_impl(path, cache.options(), Some(cache), budget, report)?)
+    })
+}
+
+/// Index every root on its own thread, all parsing under the one memory `budget`
+///
+/// A root whose thread panics is reported as failed, like a root that fails to index.
+fn build_workspace_index_impl(
+    roots: &[ClaudeRoot],
+    budget: Option<&MemoryBudget>,
+    report: &mut IndexReport,
+    index_root: impl Fn(&Path, Option<&MemoryBudget>, &mut IndexReport) -> Result<Vec<SearchEntry>>
+    + Sync,
+) -> Result<(Vec<SearchEntry>, Vec<RootStatus>)> {
+    for (i, root) in roots.iter().enumerate() {
+        if roots[..i].iter().any(|other| other.name == root.name) {
+            bail!("Duplicate Claude root name: '{}'", root.name);
+        }
+    }
+
+    // Each root gets its own report, so failure thresholds and statistics don't mix
+    let index_root = &index_root;
+    let results: Vec<Option<(Result<Vec<SearchEntry>>, IndexReport)>> = thread::scope(|scope| {
+        let handles: Vec<_> = roots
+            .iter()
+            .map(|root| {
+                scope.spawn(move || {
+                    root.path.exists().then(|| {
+                        let mut root_report = IndexReport::default();
+                        (index_root(&root.path, budget, &mut root_report), root_report)
+                    })
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle.join().unwrap_or_else(|payload| {
+                    let error = anyhow!("indexing panicked: {}", panic_message(payload.as_ref()));
+                    let mut root_report = IndexReport::default();
+                    root_report.record_failure(&error);
+                    Some((Err(error), root_report))
+                })
+            })
+            .collect()
+    });
+
+    let mut index = Vec::new();
+    let mut statuses = Vec::with_capacity(roots.len());
+    let mut indexed_any = false;
+    let mut first_error = None;
+
+    for (root, result) in roots.iter().zip(results) {
+        let Some((result, root_report)) = result else {
+            report.record_skip(&root.path, format!("Claude root '{}' not found", root.name));
+            statuses.push(RootStatus {
+                name: root.name.clone(),
+                path: root.path.clone(),
+                entry_count: 0,
+                last_refresh: Utc::now(),
+                health: RootHealth::Missing,
+                report: IndexReport::default(),
+            });
+            continue;
+        };
+        report.merge(&root_report);
+
+        let (entry_count, health) = match result {
+            Ok(mut entries) => {
+                indexed_any = true;
+                for entry in &mut entries {
+                    entry.root = Some(root.name.clone());
+                }
+                let count = entries.len();
+                index.extend(entries);
+                (count, if count == 0 { RootHealth::Empty } else { RootHealth::Ok })
+            }
+            Err(error) => {
+                let reason = format!("Claude root '{}' failed to index: {:#}", root.name, error);
+                report.record_skip(&root.path, reason);
+                first_error.get_or_insert(error);
+                (0, RootHealth::Failed)
+            }
+        };
+        statuses.push(RootStatus {
+            name: root.name.clone(),
+            path: root.path.clone(),
+            entry_count,
+            last_refresh: Utc::now(),
+            health,
+            report: root_report,
+        });
+    }
+
+    if let Some(error) = first_error.filter(|_| !indexed_any) {
+        report.record_failure(&error);
+        return Err(error);
+    }
+
+    index.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+
+    Ok((index, statuses))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn write_history(dir: &Path, content: &str) {
+        fs::write(dir.join("history.jsonl"), content).expect("Failed to write history.jsonl");
+    }
+
+    #[test]
+    fn test_parse_named_root() {
+        let root = ClaudeRoot::parse("work=/mnt/work/.claude").unwrap();
+        assert_eq!(root.name, "work");
+        assert_eq!(root.path, PathBuf::from("/mnt/work/.claude"));
+    }
+
+    #[test]
+    fn test_parse_unnamed_root_uses_parent_of_dot_claude() {
+        let root = ClaudeRoot::parse("/backups/laptop/.claude").unwrap();
+        assert_eq!(root.name, "laptop");
+    }
+
+    #[test]
+    fn test_parse_unnamed_root_uses_last_component() {
+        let root = ClaudeRoot::parse("/backups/desktop-claude").unwrap();
+        assert_eq!(root.name, "desktop-claude");
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_specs() {
+        assert!(ClaudeRoot::parse("").is_err());
+        assert!(ClaudeRoot::parse("=/tmp").is_err());
+        assert!(ClaudeRoot::parse("work=").is_err());
+    }
+
+    #[test]
+    fn test_build_workspace_index_tags_and_counts() {
+        let work = TempDir::new().unwrap();
+        let home = TempDir::new().unwrap();
+        write_history(
+            work.path(),
+            r#"{"display":"Work prompt","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000"}"#,
+        );
+        write_history(
+            home.path(),
+            r#"{"display":"Home prompt 1","timestamp":1234567891,"sessionId":"550e8400-e29b-41d4-a716-446655440001"}
+{"display":"Home prompt 2","timestamp":1234567892,"sessionId":"550e8400-e29b-41d4-a716-446655440002"}"#,
+        );
+
+        let roots =
+            vec![ClaudeRoot::new("work", work.path()), ClaudeRoot::new("home", home.path())];
+        let (index, statuses) = build_workspace_index(&roots).unwrap();
+
+        assert_eq!(index.len(), 3);
+        assert_eq!(index[0].display_text, "Home prompt 2");
+        assert_eq!(index[0].root.as_deref(), Some("home"));
+        assert_eq!(index[2].root.as_deref(), Some("work"));
+
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(statuses[0].entry_count, 1);
+        assert_eq!(statuses[0].health, RootHealth::Ok);
+        assert_eq!(statuses[1].entry_count, 2);
+    }
+
+    #[test]
+    fn test_build_workspace_index_missing_and_empty_roots() {
+        let empty = TempDir::new().unwrap();
+        write_history(empty.path(), "");
+
+        let roots = vec![
+            ClaudeRoot::new("empty", empty.path()),
+            ClaudeRoot::new("gone", "/nonexistent/claude/root"),
+        ];
+        let (index, statuses) = build_workspace_index(&roots).unwrap();
+
+        assert!(index.is_empty());
+        assert_eq!(statuses[0].health, RootHealth::Empty);
+        assert_eq!(statuses[1].health, RootHealth::Missing);
+    }
+
+    #[test]
+    fn test_build_workspace_index_report_accumulates_roots() {
+        let work = TempDir::new().unwrap();
+        write_history(
+            work.path(),
+            r#"{"display":"Work prompt","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000"}"#,
+        );
+
+        let roots = vec![
+            ClaudeRoot::new("work", work.path()),
+            ClaudeRoot::new("gone", "/nonexistent/claude/root"),
+        ];
+        let mut report = IndexReport::default();
+        build_workspace_index_with_report(&roots, &mut report).unwrap();
+
+        assert_eq!(report.entries_indexed, 1);
+        assert_eq!(report.status, crate::indexer::report::IndexStatus::Partial);
+        assert_eq!(report.skipped[0].path, PathBuf::from("/nonexistent/claude/root"));
+    }
+
+    #[test]
+    fn test_build_workspace_index_isolates_failed_roots() {
+        let work = TempDir::new().unwrap();
+        write_history(
+            work.path(),
+            r#"{"display":"Work prompt","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000"}"#,
+        );
+        // A half-synced backup whose only agent file is corrupt
+        let backup = TempDir::new().unwrap();
+        write_history(backup.path(), "");
+        let project = backup.path().join("projects").join("-Users-test-project");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(project.join("agent-bad.jsonl"), "invalid json").unwrap();
+
+        let roots =
+            vec![ClaudeRoot::new("work", work.path()), ClaudeRoot::new("backup", backup.path())];
+        let mut report = IndexReport::default();
+        let (index, statuses) = build_workspace_index_with_report(&roots, &mut report).unwrap();
+
+        assert_eq!(index.len(), 1);
+        assert_eq!(statuses[0].health, RootHealth::Ok);
+        assert_eq!(statuses[0].report.agent_files_failed, 0);
+        assert_eq!(statuses[1].health, RootHealth::Failed);
+        assert_eq!(statuses[1].report.agent_files_failed, 1);
+        assert!(statuses[1].report.error.as_deref().unwrap().contains("agent files failed"));
+
+        assert_eq!(report.status, crate::indexer::report::IndexStatus::Partial);
+        assert_eq!(report.entries_indexed, 1);
+        assert_eq!(report.agent_files_failed, 1);
+        assert!(report.skipped.iter().any(|s| s.reason.contains("'backup' failed to index")));
+
+        // With no root left, the workspace fails
+        let mut report = IndexReport::default();
+        let err = build_workspace_index_with_report(&roots[1..], &mut report).unwrap_err();
+        assert!(err.to_string().contains("agent files failed"));
+        assert_eq!(report.status, crate::indexer::report::IndexStatus::Failed);
+    }
+
+    #[test]
+    fn test_build_workspace_index_reports_panicking_root_as_failed() {
+        let work = TempDir::new().unwrap();
+        let broken = TempDir::new().unwrap();
+        let roots =
+            vec![ClaudeRoot::new("work", work.path()), ClaudeRoot::new("broken", broken.path())];
+
+        let mut report = IndexReport::default();
+        let (_, statuses) = build_workspace_index_impl(&roots, None, &mut report, |path, _, _| {
+            if path == broken.path() {
+                panic!("bad root");
+            }
+            Ok(Vec::new())
+        })
+        .unwrap();
+
+        assert_eq!(statuses[0].health, RootHealth::Empty);
+        assert_eq!(statuses[1].health, RootHealth::Failed);
+        assert!(statuses[1].report.error.as_deref().unwrap().contains("bad root"));
+        assert!(report.skipped.iter().any(|s| {
+            s.path == broken.path() && s.reason.contains("indexing panicked: bad root")
+        }));
+    }
+
+    #[test]
+    fn test_build_workspace_index_shares_one_memory_budget() {
+        let one = TempDir::new().unwrap();
+        let two = TempDir::new().unwrap();
+        let roots = vec![ClaudeRoot::new("one", one.path()), ClaudeRoot::new("two", two.path())];
+        let budget = MemoryBudget::new(1024);
+        let shared = std::sync::atomic::AtomicUsize::new(0);
+
+        build_workspace_index_impl(
+            &roots,
+            Some(&budget),
+            &mut IndexReport::default(),
+            |_, root_budget, _| {
+                if root_budget.is_some_and(|root_budget| std::ptr::eq(root_budget, &budget)) {
+                    shared.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                }
+                Ok(Vec::new())
+            },
+        )
+        .unwrap();
+
+        assert_eq!(shared.into_inner(), 2);
+    }
+
+    #[test]
+    fn test_build_workspace_index_rejects_duplicate_names() {
+        let roots = vec![ClaudeRoot::new("a", "/tmp/one"), ClaudeRoot::new("a", "/tmp/two")];
+        let err = build_workspace_index(&roots).unwrap_err();
+        assert!(err.to_string().contains("Duplicate Claude root name"));
+    }
+}
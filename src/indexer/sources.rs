@@ -0,0 +1,259 @@
+//! History of other AI coding tools, indexed next to Claude Code's.
+//!
+//! A [`HistorySource`] reads one tool's history into search entries tagged with its
+//! [`SourceKind`]. Claude roots are indexed by the builder (with the index cache); the other
+//! sources are loaded afterwards and merged in with [`merge_sources`]. A source that fails to
+//! load is reported as skipped and the index is built without it.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use super::builder::build_index_with_options;
+use super::report::IndexReport;
+use super::roots::ClaudeRoot;
+use crate::models::{SearchEntry, SourceKind};
+use crate::parsers::{
+    ParseOptions, parse_aider_history, parse_codex_session, parse_markdown_transcript,
+};
+use crate::utils::safe_open_dir;
+
+/// Longest chain of nested directories searched for Codex session files
+const MAX_CODEX_DEPTH: usize = 8;
+
+/// Longest chain of nested directories searched for Markdown transcripts
+const MAX_TRANSCRIPT_DEPTH: usize = 8;
+
+/// History of one AI coding tool
+pub trait HistorySource: Send + Sync {
+    /// Tool whose history this is
+    fn kind(&self) -> SourceKind;
+
+    /// File or directory the history is read from
+    fn location(&self) -> &Path;
+
+    /// Read all entries within the limits of `options`, recording skipped input in `report`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the history cannot be read at all.
+    fn load(&self, options: &ParseOptions, report: &mut IndexReport) -> Result<Vec<SearchEntry>>;
+}
+
+/// A Claude Code directory (`~/.claude`), indexed without the cache
+pub struct ClaudeSource(pub ClaudeRoot);
+
+impl HistorySource for ClaudeSource {
+    fn kind(&self) -> SourceKind {
+        SourceKind::ClaudeCode
+    }
+
+    fn location(&self) -> &Path {
+        &self.0.path
+    }
+
+    fn load(&self, options: &ParseOptions, report: &mut IndexReport) -> Result<Vec<SearchEntry>> {
+        Ok(build_index_with_options(&self.0.path, options, report)?)
+    }
+}
+
+/// The Codex CLI home (`~/.codex`), whose `sessions` directory holds one file per session
+pub struct CodexSource {
+    pub dir: PathBuf,
+}
+
+impl HistorySource for CodexSource {
+    fn kind(&self) -> SourceKind {
+        SourceKind::Codex
+    }
+
+    fn location(&self) -> &Path {
+        &self.dir
+    }
+
+    fn load(&self, options: &ParseOptions, report: &mut IndexReport) -> Result<Vec<SearchEntry>> {
+        let mut files = Vec::new();
+        collect_files(&self.dir.join("sessions"), "jsonl", MAX_CODEX_DEPTH, &mut files)?;
+        files.sort();
+
+        let mut entries = Vec::new();
+        for file in files {
+            match parse_codex_session(&file, options.file_size_limit()) {
+                Ok((parsed, skipped)) => {
+                    report.record_agent_lines_skipped(skipped);
+                    entries.extend(parsed);
+                }
+                Err(e) => report.record_skip(&file, format!("{:#}", e)),
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// One aider chat history (`.aider.chat.history.md`)
+pub struct AiderSource {
+    pub file: PathBuf,
+}
+
+impl HistorySource for AiderSource {
+    fn kind(&self) -> SourceKind {
+        SourceKind::Aider
+    }
+
+    fn location(&self) -> &Path {
+        &self.file
+    }
+
+    fn load(&self, options: &ParseOptions, _report: &mut IndexReport) -> Result<Vec<SearchEntry>> {
+        parse_aider_history(&self.file, options.file_size_limit())
+    }
+}
+
+/// A directory of Markdown chat transcripts (`### User` / `### Assistant`), one session per
+/// file
+pub struct TranscriptSource {
+    pub dir: PathBuf,
+}
+
+impl HistorySource for TranscriptSource {
+    fn kind(&self) -> SourceKind {
+        SourceKind::Transcript
+    }
+
+    fn location(&self) -> &Path {
+        &self.dir
+    }
+
+    fn load(&self, options: &ParseOptions, report: &mut IndexReport) -> Result<Vec<SearchEntry>> {
+        let mut files = Vec::new();
+        collect_files(&self.dir, "md", MAX_TRANSCRIPT_DEPTH, &mut files)?;
+        files.sort();
+
+        let mut entries = Vec::new();
+        for file in files {
+            // Named after the file, e.g. `transcript-2023/rust-lifetimes`
+            let name = file.strip_prefix(&self.dir).unwrap_or(&file).with_extension("");
+            let session_id = format!("transcript-{}", name.display());
+            match parse_markdown_transcript(&file, &session_id, options.file_size_limit()) {
+                Ok(parsed) => entries.extend(parsed),
+                Err(e) => report.record_skip(&file, format!("{:#}", e)),
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// Add the entries of `sources`, read within the limits of `options`, to `index`, newest first
+///
+/// Sources that fail to load are recorded in `report` as skipped.
+pub fn merge_sources(
+    index: &mut Vec<SearchEntry>,
+    sources: &[Arc<dyn HistorySource>],
+    options: &ParseOptions,
+    report: &mut IndexReport,
+) {
+    if sources.is_empty() {
+        return;
+    }
+    for source in sources {
+        match source.load(options, report) {
+            Ok(entries) => {
+                report.entries_indexed += entries.len();
+                index.extend(entries);
+            }
+            Err(e) => report.record_skip(
+                source.location(),
+                format!("Failed to read {} history: {:#}", source.kind().label(), e),
+            ),
+        }
+    }
+    index.sort_by_key(|entry| std::cmp::Reverse(entry.timestamp));
+}
+
+/// Files with `extension` in `dir` and up to `depth` levels of subdirectories
+fn collect_files(
+    dir: &Path,
+    extension: &str,
+    depth: usize,
+    files: &mut Vec<PathBuf>,
+) -> Result<()> {
+    for item in safe_open_dir(dir)?.filter_map(|item| item.ok()) {
+        let Ok(file_type) = item.file_type() else { continue };
+        let path = item.path();
+        if file_type.is_dir() && depth > 0 {
+            collect_files(&path, extension, depth - 1, files)?;
+        } else if file_type.is_file() && path.extension().is_some_and(|ext| ext == extension) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_merge_sources_reads_codex_and_aider() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let codex = temp.path().join(".codex");
+        let day = codex.join("sessions/2025/09/10");
+        fs::create_dir_all(&day).unwrap();
+        fs::write(
+            day.join("rollout-1.jsonl"),
+            r#"{"timestamp":"2025-09-10T10:00:00Z","type":"session_meta","payload":{"id":"c1","cwd":"/work/api"}}
+{"timestamp":"2025-09-10T10:00:02Z","type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"Codex prompt"}]}}"#,
+        )
+        .unwrap();
+        fs::write(day.join("rollout-2.jsonl"), "garbage").unwrap();
+        let aider = temp.path().join("app/.aider.chat.history.md");
+        fs::create_dir_all(aider.parent().unwrap()).unwrap();
+        fs::write(&aider, "# aider chat started at 2020-01-01 10:00:00\n#### Aider prompt\n")
+            .unwrap();
+
+        let sources: Vec<Arc<dyn HistorySource>> = vec![
+            Arc::new(CodexSource { dir: codex }),
+            Arc::new(AiderSource { file: aider }),
+            Arc::new(AiderSource { file: temp.path().join("missing.md") }),
+        ];
+        let mut index = Vec::new();
+        let mut report = IndexReport::default();
+        merge_sources(&mut index, &sources, &ParseOptions::default(), &mut report);
+
+        let texts: Vec<_> = index.iter().map(|e| (e.source, e.display_text.as_str())).collect();
+        assert_eq!(
+            texts,
+            vec![(SourceKind::Codex, "Codex prompt"), (SourceKind::Aider, "Aider prompt")]
+        );
+        assert_eq!(report.entries_indexed, 2);
+        // The malformed session file and the missing aider history
+        assert_eq!(report.skipped.len(), 2);
+        assert!(report.skipped[1].reason.starts_with("Failed to read aider history"));
+    }
+
+    #[test]
+    fn test_transcript_source_reads_one_session_per_file() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let nested = temp.path().join("2023");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join("lifetimes.md"), "### User\nWhy?\n### Assistant\nBorrows.\n")
+            .unwrap();
+        fs::write(temp.path().join("notes.md"), "# Not a transcript\n").unwrap();
+        fs::write(temp.path().join("chat.txt"), "### User\nIgnored\n").unwrap();
+
+        let source = TranscriptSource { dir: temp.path().to_path_buf() };
+        let mut report = IndexReport::default();
+        let entries = source.load(&ParseOptions::default(), &mut report).unwrap();
+
+        let texts: Vec<_> = entries.iter().map(|e| e.display_text.as_str()).collect();
+        assert_eq!(texts, vec!["Why?", "Borrows."]);
+        assert_eq!(
+            entries[0].session_id,
+            format!("transcript-2023{}lifetimes", std::path::MAIN_SEPARATOR)
+        );
+        assert!(report.skipped.is_empty());
+    }
+}
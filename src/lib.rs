@@ -6,8 +6,32 @@
 //! - Parsing user prompts from `history.jsonl`
 //! - Discovering and parsing agent conversations from project directories
 //! - Building searchable indexes of conversation entries
+//! - Starring entries to find them again ([`bookmarks`])
+//! - Exporting a prompt and its answering turns as Markdown or JSON
+//! - Syncing history between machines through bundle files ([`sync`])
+//! - Resuming a found session with `claude --resume` ([`resume`])
+//! - One-line session summaries written by a local program or the Anthropic API ([`summarize`])
 //! - Path encoding/decoding for Claude's project directory format
 //!
+//! # Features
+//!
+//! All enabled by default; library users that only need parsing and indexing can turn them
+//! off with `default-features = false`:
+//!
+//! - `tui`: the interactive interface ([`tui`])
+//! - `clipboard`: system clipboard access ([`copy_to_clipboard`])
+//! - `cache`: persisting the index cache ([`cache`]) between runs
+//! - `parallel`: parse `history.jsonl` and agent conversation files in parallel with rayon
+//! - `snapshot`: compressed point-in-time backups of the history ([`snapshot`])
+//! - `watch`: refresh the TUI when history files change on disk
+//! - `highlight`: syntax-highlighted code blocks in HTML exports ([`export::highlight`])
+//!
+//! Off by default: `fulltext`, a persistent full-text index for large histories, `similar`,
+//! lexical similarity search over hashed word vectors, and `semantic`, nearest neighbours
+//! among sentence embeddings from a local BERT model ([`search`]). `sqlite` stores
+//! the index cache as a SQLite database and `summarize-api` lets [`summarize`] call the
+//! Anthropic API.
+//!
 //! # Example
 //!
 //! ```no_run
@@ -20,17 +44,32 @@
 //! # Ok::<(), anyhow::Error>(())
 //! ```
 
+pub mod analytics;
+pub mod bookmarks;
+pub mod cache;
 pub mod cli;
 pub mod clipboard;
+pub mod config;
+pub mod error;
+pub mod export;
 pub mod filters;
 pub mod indexer;
 pub mod models;
 pub mod parsers;
+pub mod resume;
+#[cfg(any(feature = "fulltext", feature = "similar", feature = "semantic", feature = "tui"))]
+pub mod search;
+#[cfg(feature = "snapshot")]
+pub mod snapshot;
+pub mod summarize;
+pub mod sync;
+#[cfg(feature = "tui")]
 pub mod tui;
 pub mod utils;
 
 // Re-export commonly used types
 pub use clipboard::copy_to_clipboard;
+pub use error::ExplorerError;
 pub use indexer::builder::build_index;
 pub use models::search::SearchEntry;
 pub use parsers::history::parse_history_file;
@@ -1,6 +1,8 @@
+use std::process::ExitCode;
+
 use ai_history_explorer::cli;
 use anyhow::Result;
 
-fn main() -> Result<()> {
+fn main() -> Result<ExitCode> {
     cli::run()
 }
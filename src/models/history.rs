@@ -1,5 +1,8 @@
+use std::path::PathBuf;
+
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Serialize, Serializer};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoryEntry {
@@ -60,16 +63,55 @@ pub enum ContentBlock {
     },
     /// Image content (typically in user messages)
     ///
-    /// Represents an image attachment. Source contains image data/URL in various
-    /// formats (base64, URL, etc.). Optional alt_text provides textual description
-    /// for accessibility or when image cannot be displayed.
+    /// Represents an image attachment. Source describes the image data/URL in various
+    /// formats (base64, URL, etc.), with inline data reduced to its size. Optional alt_text
+    /// provides textual description for accessibility or when image cannot be displayed.
     Image {
-        source: serde_json::Value,
+        #[serde(
+            default,
+            deserialize_with = "crate::parsers::deserializers::deserialize_image_source"
+        )]
+        source: ImageSource,
         #[serde(default)]
         alt_text: Option<String>,
     },
 }
 
+/// Source of an image block without its inline data
+///
+/// Base64 payloads can run to megabytes per image and are never displayed or indexed, so
+/// `data` is dropped while deserializing and only its size is kept. Other source fields
+/// (`type`, `media_type`, `url`, ...) are kept as written.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImageSource {
+    /// Source fields other than `data`
+    pub fields: serde_json::Map<String, serde_json::Value>,
+    /// Bytes of inline data that were dropped (0 if there was none)
+    pub data_bytes: usize,
+}
+
+impl ImageSource {
+    /// Text standing in for the dropped data
+    pub fn placeholder(&self) -> String {
+        format!("[image data omitted: {} bytes]", self.data_bytes)
+    }
+}
+
+/// Serialized as the source object with `data` replaced by [`ImageSource::placeholder`]
+impl Serialize for ImageSource {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let has_data = self.data_bytes > 0;
+        let mut map = serializer.serialize_map(Some(self.fields.len() + has_data as usize))?;
+        for (key, value) in &self.fields {
+            map.serialize_entry(key, value)?;
+        }
+        if has_data {
+            map.serialize_entry("data", &self.placeholder())?;
+        }
+        map.end()
+    }
+}
+
 /// Message content can be either a simple string or an array of content blocks
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -82,6 +124,9 @@ pub enum MessageContent {
 pub struct Message {
     pub role: String,
     pub content: MessageContent,
+    /// Model that wrote an assistant message, e.g. `claude-sonnet-4-5-20250929`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -97,8 +142,37 @@ pub struct ConversationEntry {
     )]
     pub session_id: String,
     pub uuid: String,
-    #[serde(default)]
+    /// `uuid` of the message this one follows (`parentUuid`; None at the start of a session)
+    #[serde(default, rename = "parentUuid", alias = "parent_uuid")]
     pub parent_uuid: Option<String>,
-    #[serde(default)]
+    #[serde(default, rename = "isSidechain", alias = "is_sidechain")]
     pub is_sidechain: Option<bool>,
+    /// Kind of user behind the session (`userType`, e.g. `external`)
+    #[serde(default, rename = "userType")]
+    pub user_type: Option<String>,
+    /// Account identifier, when the client records one (`userId`)
+    #[serde(default, rename = "userId")]
+    pub user_id: Option<String>,
+    /// Account e-mail, when the client records one (`userEmail`)
+    #[serde(default, rename = "userEmail")]
+    pub user_email: Option<String>,
+    /// Git branch checked out in the project when the message was written (`gitBranch`)
+    #[serde(default, rename = "gitBranch", skip_serializing_if = "Option::is_none")]
+    pub git_branch: Option<String>,
+    /// Working directory of the client when the message was written (`cwd`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+}
+
+impl ConversationEntry {
+    /// Best available identity of the user behind this entry
+    ///
+    /// Prefers the most specific account hint: e-mail, then user id, then user type. Blank
+    /// values are ignored.
+    pub fn user_identity(&self) -> Option<&str> {
+        [&self.user_email, &self.user_id, &self.user_type]
+            .into_iter()
+            .filter_map(|value| value.as_deref().map(str::trim))
+            .find(|value| !value.is_empty())
+    }
 }
@@ -8,12 +8,14 @@
 //! - [`ProjectInfo`] - Discovered project metadata and file paths
 //!
 //! These models use serde for JSON deserialization with custom deserializers
-//! for special fields (timestamps, session IDs) in the `deserializers` module.
+//! for special fields (timestamps, session IDs, image sources) in the `deserializers` module.
 
 pub mod history;
 pub mod project;
 pub mod search;
 
-pub use history::{ContentBlock, ConversationEntry, HistoryEntry, Message, MessageContent};
+pub use history::{
+    ContentBlock, ConversationEntry, HistoryEntry, ImageSource, Message, MessageContent,
+};
 pub use project::ProjectInfo;
-pub use search::{EntryType, SearchEntry};
+pub use search::{ContentKinds, EntryType, SearchEntry, SessionOutcome, SourceKind};
@@ -1,18 +1,142 @@
 use std::path::PathBuf;
 
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EntryType {
+    #[default]
     UserPrompt,
     AgentMessage,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// How a session likely ended, guessed from its last messages (see
+/// [`classify_outcome`](crate::indexer::classify_outcome))
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum SessionOutcome {
+    /// The user thanked the agent, or the final exchange committed the work
+    Success,
+    /// The session stopped on a failed tool call or an interrupted request
+    Abandoned,
+    /// Neither signal was found
+    Unclear,
+}
+
+impl SessionOutcome {
+    pub const ALL: [SessionOutcome; 3] =
+        [SessionOutcome::Success, SessionOutcome::Abandoned, SessionOutcome::Unclear];
+
+    /// Name used by the `outcome:` filter and in reports
+    pub fn label(self) -> &'static str {
+        match self {
+            SessionOutcome::Success => "success",
+            SessionOutcome::Abandoned => "abandoned",
+            SessionOutcome::Unclear => "unclear",
+        }
+    }
+
+    /// Parse a label (case-insensitive)
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|outcome| outcome.label().eq_ignore_ascii_case(label))
+    }
+}
+
+/// AI coding tool whose history an entry comes from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceKind {
+    /// Claude Code (`~/.claude`)
+    #[default]
+    ClaudeCode,
+    /// OpenAI Codex CLI (`~/.codex/sessions`)
+    Codex,
+    /// aider (`.aider.chat.history.md` in a project)
+    Aider,
+    /// Chat transcripts saved as Markdown (`### User` / `### Assistant`)
+    Transcript,
+}
+
+impl SourceKind {
+    pub const ALL: [SourceKind; 4] =
+        [SourceKind::ClaudeCode, SourceKind::Codex, SourceKind::Aider, SourceKind::Transcript];
+
+    /// Name used by the `source:` filter and in reports
+    pub fn label(self) -> &'static str {
+        match self {
+            SourceKind::ClaudeCode => "claude",
+            SourceKind::Codex => "codex",
+            SourceKind::Aider => "aider",
+            SourceKind::Transcript => "transcript",
+        }
+    }
+
+    /// Parse a label (case-insensitive)
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|source| source.label().eq_ignore_ascii_case(label))
+    }
+}
+
+bitflags::bitflags! {
+    /// Kinds of content blocks a message contained besides text
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    pub struct ContentKinds: u8 {
+        const THINKING = 1;
+        const TOOL_USE = 1 << 1;
+        const TOOL_RESULT = 1 << 2;
+        const IMAGE = 1 << 3;
+    }
+}
+
+/// One searchable prompt or message
+///
+/// `Default` gives an empty user prompt at the Unix epoch, for filling in the fields not of
+/// interest with `..Default::default()`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SearchEntry {
     pub entry_type: EntryType,
     pub display_text: String,
     pub timestamp: DateTime<Utc>,
     pub project_path: Option<PathBuf>,
     pub session_id: String,
+    /// Name of the Claude root this entry was indexed from (multi-root workspaces)
+    pub root: Option<String>,
+    /// Conversation file the entry was parsed from (None for history.jsonl prompts and the
+    /// entries of other tools)
+    pub source_file: Option<PathBuf>,
+    /// Whether the message carried a tool result flagged as an error
+    pub had_error: bool,
+    /// Kinds of content blocks in the message, whether or not their text was indexed (only
+    /// recorded for Claude Code conversations)
+    #[serde(default)]
+    pub content_kinds: ContentKinds,
+    /// Names of the tools the message called, in order of their first call (only recorded
+    /// for Claude Code conversations)
+    #[serde(default)]
+    pub tools: Vec<String>,
+    /// Identity of the user behind the entry, when the conversation records one (see
+    /// [`ConversationEntry::user_identity`](crate::models::ConversationEntry::user_identity))
+    pub user: Option<String>,
+    /// Model that wrote the message, e.g. `claude-sonnet-4-5-20250929` (agent messages whose
+    /// conversation records it)
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Git branch checked out when the message was written (Claude Code conversations)
+    #[serde(default)]
+    pub git_branch: Option<String>,
+    /// Working directory of the agent when the message was written, which can be a
+    /// subdirectory of the project (Claude Code conversations)
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+    /// `uuid` of the conversation message (None for history.jsonl prompts)
+    pub uuid: Option<String>,
+    /// `uuid` of the nearest indexed ancestor in the message's `parentUuid` chain
+    ///
+    /// Messages without text (tool calls and results) are not indexed, so this skips over
+    /// them to the closest ancestor that is.
+    pub parent_uuid: Option<String>,
+    /// How the entry's session ended (None for sessions without a main conversation file)
+    pub outcome: Option<SessionOutcome>,
+    /// Tool whose history the entry was read from
+    #[serde(default)]
+    pub source: SourceKind,
 }
@@ -0,0 +1,169 @@
+//! Parser for aider chat histories (`.aider.chat.history.md` in a project directory).
+//!
+//! aider appends each chat to the Markdown file of the project it runs in:
+//!
+//! ```text
+//! # aider chat started at 2024-05-01 10:00:00
+//!
+//! #### Add a retry to the client
+//!
+//! Here is the change ...
+//!
+//! > Applied edit to client.py
+//! ```
+//!
+//! `#### ` lines are the user's input (consecutive ones form one prompt), `> ` lines are
+//! command and tool output, and everything else is the assistant's reply. Only the start of
+//! each chat is timestamped (in local time), so every entry of a chat gets that time; the
+//! project is the directory the file is in.
+
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+
+use crate::models::{ContentKinds, EntryType, SearchEntry, SourceKind};
+use crate::utils::safe_open_file;
+
+/// File name aider writes its chat history to
+pub const AIDER_HISTORY_FILE_NAME: &str = ".aider.chat.history.md";
+
+const CHAT_START: &str = "# aider chat started at ";
+const USER_PREFIX: &str = "#### ";
+const OUTPUT_PREFIX: &str = ">";
+
+/// Parse an aider chat history into search entries, oldest first
+///
+/// Lines before the first chat header are ignored.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or is larger than `max_bytes`.
+pub fn parse_aider_history(path: &Path, max_bytes: u64) -> Result<Vec<SearchEntry>> {
+    let reader = BufReader::new(safe_open_file(path, max_bytes)?);
+    let project = path.parent().map(Path::to_path_buf);
+    let mut chat: Option<(String, DateTime<Utc>)> = None;
+    let mut block: Option<(EntryType, Vec<String>)> = None;
+    let mut entries = Vec::new();
+
+    let mut flush = |block: &mut Option<(EntryType, Vec<String>)>,
+                     chat: &Option<(String, DateTime<Utc>)>| {
+        let (Some((entry_type, lines)), Some((session_id, timestamp))) = (block.take(), chat)
+        else {
+            return;
+        };
+        let text = lines.join("\n").trim().to_string();
+        if !text.is_empty() {
+            entries.push(entry(entry_type, text, *timestamp, session_id, project.clone()));
+        }
+    };
+
+    for line in reader.lines() {
+        let line = line.with_context(|| format!("Failed to read {}", path.display()))?;
+        if let Some(started) = line.strip_prefix(CHAT_START) {
+            flush(&mut block, &chat);
+            chat = parse_start(started.trim())
+                .map(|time| (format!("aider-{}", time.format("%Y%m%dT%H%M%SZ")), time));
+        } else if let Some(text) = line.strip_prefix(USER_PREFIX) {
+            if !matches!(block, Some((EntryType::UserPrompt, _))) {
+                flush(&mut block, &chat);
+                block = Some((EntryType::UserPrompt, Vec::new()));
+            }
+            if let Some((_, lines)) = &mut block {
+                lines.push(text.to_string());
+            }
+        } else if line.starts_with(OUTPUT_PREFIX) {
+            flush(&mut block, &chat);
+        } else {
+            // Anything after a prompt is the reply
+            if matches!(block, Some((EntryType::UserPrompt, _))) {
+                flush(&mut block, &chat);
+            }
+            match &mut block {
+                Some((_, lines)) => lines.push(line),
+                None if !line.trim().is_empty() => {
+                    block = Some((EntryType::AgentMessage, vec![line]));
+                }
+                None => {}
+            }
+        }
+    }
+    flush(&mut block, &chat);
+    Ok(entries)
+}
+
+/// Start time of a chat header (`2024-05-01 10:00:00`, local time)
+fn parse_start(text: &str) -> Option<DateTime<Utc>> {
+    let naive = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S").ok()?;
+    Some(Local.from_local_datetime(&naive).earliest()?.with_timezone(&Utc))
+}
+
+fn entry(
+    entry_type: EntryType,
+    text: String,
+    timestamp: DateTime<Utc>,
+    session_id: &str,
+    project: Option<PathBuf>,
+) -> SearchEntry {
+    SearchEntry {
+        entry_type,
+        display_text: text,
+        timestamp,
+        project_path: project,
+        session_id: session_id.to_string(),
+        root: None,
+        source_file: None,
+        had_error: false,
+        content_kinds: ContentKinds::empty(),
+        tools: Vec::new(),
+        user: None,
+        model: None,
+        git_branch: None,
+        cwd: None,
+        uuid: None,
+        parent_uuid: None,
+        outcome: None,
+        source: SourceKind::Aider,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::DEFAULT_MAX_FILE_SIZE_BYTES;
+
+    #[test]
+    fn test_parses_prompts_replies_and_chats() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(AIDER_HISTORY_FILE_NAME);
+        std::fs::write(
+            &path,
+            "\n# aider chat started at 2024-05-01 10:00:00\n\n\
+             > /add client.py\n> Added client.py to the chat\n\n\
+             #### Add a retry to the client\n#### with backoff\n\n\
+             Here is the change.\n\nIt retries three times.\n\n\
+             > Applied edit to client.py\n\n\
+             # aider chat started at 2024-05-02 09:30:00\n\n\
+             #### Explain the tests\n",
+        )
+        .unwrap();
+
+        let entries = parse_aider_history(&path, DEFAULT_MAX_FILE_SIZE_BYTES).unwrap();
+        let texts: Vec<_> =
+            entries.iter().map(|e| (e.entry_type.clone(), e.display_text.as_str())).collect();
+        assert_eq!(
+            texts,
+            vec![
+                (EntryType::UserPrompt, "Add a retry to the client\nwith backoff"),
+                (EntryType::AgentMessage, "Here is the change.\n\nIt retries three times."),
+                (EntryType::UserPrompt, "Explain the tests"),
+            ]
+        );
+        assert_eq!(entries[0].session_id, entries[1].session_id);
+        assert_ne!(entries[0].session_id, entries[2].session_id);
+        assert!(entries[0].timestamp < entries[2].timestamp);
+        assert_eq!(entries[0].project_path.as_deref(), Some(dir.path()));
+        assert!(entries.iter().all(|e| e.source == SourceKind::Aider));
+    }
+}
@@ -0,0 +1,198 @@
+//! Parser for OpenAI Codex CLI session files (`~/.codex/sessions/YYYY/MM/DD/rollout-*.jsonl`).
+//!
+//! Each line is a JSON record. Current versions wrap records as
+//! `{"timestamp": ..., "type": "session_meta" | "response_item" | ..., "payload": {...}}`; the
+//! first one carries the session id and working directory. Older versions wrote the session
+//! metadata (`id`, `timestamp`) as the first line and the response items unwrapped, without
+//! timestamps of their own. Messages of both layouts become search entries; tool calls,
+//! reasoning and other records are skipped, as are the environment and instruction messages
+//! Codex sends on the user's behalf.
+
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use crate::models::{ContentKinds, EntryType, SearchEntry, SourceKind};
+use crate::utils::safe_open_file;
+
+/// Prefixes of user messages that Codex generates itself
+const INJECTED_PREFIXES: &[&str] = &["<environment_context>", "<user_instructions>"];
+
+/// Parse a Codex session file into search entries, oldest first
+///
+/// Returns the entries and the number of malformed lines that were skipped.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or is larger than `max_bytes`, or if more than
+/// half of its lines are malformed.
+pub fn parse_codex_session(path: &Path, max_bytes: u64) -> Result<(Vec<SearchEntry>, usize)> {
+    let reader = BufReader::new(safe_open_file(path, max_bytes)?);
+    let mut session_id = path.file_stem().map(|s| s.to_string_lossy().into_owned());
+    let mut project: Option<PathBuf> = None;
+    let mut last_timestamp: Option<DateTime<Utc>> = None;
+    let mut entries = Vec::new();
+    let mut total_lines = 0;
+    let mut skipped = 0;
+
+    for line in reader.lines() {
+        let line = line.with_context(|| format!("Failed to read {}", path.display()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        total_lines += 1;
+        let Ok(record) = serde_json::from_str::<Value>(&line) else {
+            skipped += 1;
+            continue;
+        };
+
+        let timestamp = record.get("timestamp").and_then(parse_timestamp);
+        let (kind, payload) = match record.get("payload") {
+            Some(payload) => (record.get("type").and_then(Value::as_str), payload),
+            // Older layout: the record is the payload
+            None => (record.get("type").and_then(Value::as_str), &record),
+        };
+
+        match kind {
+            Some("session_meta") | None => {
+                if let Some(id) = payload.get("id").and_then(Value::as_str) {
+                    session_id = Some(id.to_string());
+                }
+                if let Some(cwd) = payload.get("cwd").and_then(Value::as_str) {
+                    project = Some(PathBuf::from(cwd));
+                }
+                let started = payload.get("timestamp").and_then(parse_timestamp).or(timestamp);
+                last_timestamp = started.or(last_timestamp);
+            }
+            Some("response_item") | Some("message") => {
+                let Some(timestamp) = timestamp.or(last_timestamp) else {
+                    continue;
+                };
+                last_timestamp = Some(timestamp);
+                if let Some((entry_type, text)) = message(payload) {
+                    entries.push(SearchEntry {
+                        entry_type,
+                        display_text: text,
+                        timestamp,
+                        project_path: project.clone(),
+                        session_id: session_id.clone().unwrap_or_default(),
+                        root: None,
+                        source_file: None,
+                        had_error: false,
+                        content_kinds: ContentKinds::empty(),
+                        tools: Vec::new(),
+                        user: None,
+                        model: None,
+                        git_branch: None,
+                        cwd: project.clone(),
+                        uuid: None,
+                        parent_uuid: None,
+                        outcome: None,
+                        source: SourceKind::Codex,
+                    });
+                }
+            }
+            Some("turn_context") => {
+                if let Some(cwd) = payload.get("cwd").and_then(Value::as_str) {
+                    project = Some(PathBuf::from(cwd));
+                }
+            }
+            Some(_) => {}
+        }
+    }
+
+    if total_lines > 0 && skipped * 2 > total_lines {
+        bail!("{} of {} lines in {} are malformed", skipped, total_lines, path.display());
+    }
+    Ok((entries, skipped))
+}
+
+/// Role and text of a message item (`None` for other items and injected context)
+fn message(item: &Value) -> Option<(EntryType, String)> {
+    if item.get("type").and_then(Value::as_str) != Some("message") {
+        return None;
+    }
+    let entry_type = match item.get("role").and_then(Value::as_str)? {
+        "user" => EntryType::UserPrompt,
+        "assistant" => EntryType::AgentMessage,
+        _ => return None,
+    };
+    let text: Vec<&str> = item
+        .get("content")?
+        .as_array()?
+        .iter()
+        .filter_map(|block| block.get("text").and_then(Value::as_str))
+        .collect();
+    let text = text.join("\n").trim().to_string();
+    let injected = INJECTED_PREFIXES.iter().any(|prefix| text.starts_with(prefix));
+    (!text.is_empty() && !injected).then_some((entry_type, text))
+}
+
+fn parse_timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    value.as_str()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::DEFAULT_MAX_FILE_SIZE_BYTES;
+
+    fn write(dir: &Path, contents: &str) -> PathBuf {
+        let path = dir.join("rollout-2025-09-10T10-00-00-abc.jsonl");
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_parses_wrapped_records() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = write(
+            dir.path(),
+            r#"{"timestamp":"2025-09-10T10:00:00Z","type":"session_meta","payload":{"id":"0199-abc","timestamp":"2025-09-10T10:00:00Z","cwd":"/work/api"}}
+{"timestamp":"2025-09-10T10:00:01Z","type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"<environment_context>cwd</environment_context>"}]}}
+{"timestamp":"2025-09-10T10:00:02Z","type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"Fix the flaky test"}]}}
+{"timestamp":"2025-09-10T10:00:03Z","type":"event_msg","payload":{"type":"user_message","message":"Fix the flaky test"}}
+{"timestamp":"2025-09-10T10:00:04Z","type":"response_item","payload":{"type":"function_call","name":"shell","arguments":"{}"}}
+{"timestamp":"2025-09-10T10:00:05Z","type":"response_item","payload":{"type":"message","role":"assistant","content":[{"type":"output_text","text":"Fixed the race."}]}}
+not json"#,
+        );
+
+        let (entries, skipped) = parse_codex_session(&path, DEFAULT_MAX_FILE_SIZE_BYTES).unwrap();
+        assert_eq!(skipped, 1);
+        let texts: Vec<_> = entries.iter().map(|e| e.display_text.as_str()).collect();
+        assert_eq!(texts, vec!["Fix the flaky test", "Fixed the race."]);
+        assert_eq!(entries[0].entry_type, EntryType::UserPrompt);
+        assert_eq!(entries[1].entry_type, EntryType::AgentMessage);
+        assert_eq!(entries[0].session_id, "0199-abc");
+        assert_eq!(entries[0].project_path, Some(PathBuf::from("/work/api")));
+        assert_eq!(entries[0].source, SourceKind::Codex);
+        assert_eq!(entries[1].timestamp.to_rfc3339(), "2025-09-10T10:00:05+00:00");
+    }
+
+    #[test]
+    fn test_parses_older_layout() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = write(
+            dir.path(),
+            r#"{"id":"old-1","timestamp":"2025-05-01T08:00:00Z","instructions":null}
+{"record_type":"state"}
+{"type":"message","role":"user","content":[{"type":"input_text","text":"Explain the build"}]}"#,
+        );
+
+        let (entries, _) = parse_codex_session(&path, DEFAULT_MAX_FILE_SIZE_BYTES).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].session_id, "old-1");
+        assert_eq!(entries[0].project_path, None);
+        assert_eq!(entries[0].timestamp.to_rfc3339(), "2025-05-01T08:00:00+00:00");
+    }
+
+    #[test]
+    fn test_mostly_malformed_file_is_an_error() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = write(dir.path(), "garbage\nmore garbage\n{}");
+        assert!(parse_codex_session(&path, DEFAULT_MAX_FILE_SIZE_BYTES).is_err());
+    }
+}
@@ -1,21 +1,180 @@
+use std::borrow::Cow;
+use std::fmt;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
-use anyhow::{Context, Result, bail};
+use anyhow::Context;
+use serde::de::{IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+
+use super::MAX_LINE_ERRORS;
+use crate::error::{ExplorerError, Result};
+use crate::models::{ContentBlock, ConversationEntry, MessageContent};
+use crate::utils::{DEFAULT_MAX_FILE_SIZE_BYTES, safe_open_file};
+
+/// Bytes kept from each end of a sampled tool result
+pub const TOOL_RESULT_SAMPLE_BYTES: usize = 2048;
+
+/// How tool results above [`ParseOptions::max_tool_result_bytes`] are reduced
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OversizedToolResults {
+    /// Keep the first and last [`TOOL_RESULT_SAMPLE_BYTES`] (at most half the limit each)
+    /// with a marker in between
+    #[default]
+    Sample,
+    /// Replace the whole result with a marker
+    Skip,
+}
+
+/// Parse-time limits for conversation files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParseOptions {
+    /// Tool results larger than this many bytes are reduced (`None`: no limit)
+    pub max_tool_result_bytes: Option<usize>,
+    pub oversized_tool_results: OversizedToolResults,
+    /// Files larger than this many bytes are refused, and only this many bytes from the end of
+    /// `history.jsonl` are read (`None`: [`DEFAULT_MAX_FILE_SIZE_BYTES`])
+    ///
+    /// Not part of the index cache's identity: a refused file is never cached.
+    #[serde(skip)]
+    pub max_file_size: Option<u64>,
+    /// Bytes of conversation files parsed at the same time while building an index (`None`:
+    /// as many files at once as there are threads); see [`MemoryBudget`]
+    ///
+    /// Not part of the index cache's identity: it changes how fast files are parsed, not what
+    /// they give.
+    ///
+    /// [`MemoryBudget`]: crate::indexer::MemoryBudget
+    #[serde(skip)]
+    pub parse_memory_limit: Option<usize>,
+}
+
+impl ParseOptions {
+    /// Options that read whole conversations, refusing files larger than `max_file_size`
+    pub fn with_file_size_limit(max_file_size: u64) -> Self {
+        Self { max_file_size: Some(max_file_size), ..Default::default() }
+    }
+
+    /// The file size limit in bytes
+    pub fn file_size_limit(&self) -> u64 {
+        self.max_file_size.unwrap_or(DEFAULT_MAX_FILE_SIZE_BYTES)
+    }
+
+    /// Whether a file parsed with `self` gives the same entries as with `other`
+    ///
+    /// Compares the options that shape entries and ignores the limits on what is parsed.
+    pub fn same_entries(&self, other: &Self) -> bool {
+        self.max_tool_result_bytes == other.max_tool_result_bytes
+            && self.oversized_tool_results == other.oversized_tool_results
+    }
+}
+
+/// The `type` of a JSONL line; all other fields are skipped without being stored
+///
+/// `None` for lines that aren't objects or have no string `type`.
+struct LineType {
+    entry_type: Option<String>,
+}
+
+impl<'de> Deserialize<'de> for LineType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct LineTypeVisitor;
 
-use crate::models::ConversationEntry;
-use crate::utils::safe_open_file;
+        impl<'de> Visitor<'de> for LineTypeVisitor {
+            type Value = LineType;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a JSON value")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<LineType, A::Error> {
+                let mut entry_type = None;
+                while let Some(key) = map.next_key::<Cow<str>>()? {
+                    if key == "type" {
+                        entry_type =
+                            map.next_value::<serde_json::Value>()?.as_str().map(Into::into);
+                    } else {
+                        map.next_value::<IgnoredAny>()?;
+                    }
+                }
+                Ok(LineType { entry_type })
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<LineType, A::Error> {
+                while seq.next_element::<IgnoredAny>()?.is_some() {}
+                Ok(LineType { entry_type: None })
+            }
+
+            fn visit_str<E: serde::de::Error>(self, _v: &str) -> Result<LineType, E> {
+                Ok(LineType { entry_type: None })
+            }
+
+            fn visit_unit<E: serde::de::Error>(self) -> Result<LineType, E> {
+                Ok(LineType { entry_type: None })
+            }
+
+            fn visit_bool<E: serde::de::Error>(self, _v: bool) -> Result<LineType, E> {
+                Ok(LineType { entry_type: None })
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, _v: i64) -> Result<LineType, E> {
+                Ok(LineType { entry_type: None })
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, _v: u64) -> Result<LineType, E> {
+                Ok(LineType { entry_type: None })
+            }
+
+            fn visit_f64<E: serde::de::Error>(self, _v: f64) -> Result<LineType, E> {
+                Ok(LineType { entry_type: None })
+            }
+        }
+
+        deserializer.deserialize_any(LineTypeVisitor)
+    }
+}
+
+/// Parsed entries plus what was left out of them
+#[derive(Debug, Default)]
+pub struct ParsedConversation {
+    pub entries: Vec<ConversationEntry>,
+    /// Malformed lines that were skipped
+    pub skipped_lines: usize,
+    /// Bytes of tool output dropped by sampling/skipping oversized tool results
+    pub tool_output_bytes_skipped: usize,
+    /// Why the first [`MAX_LINE_ERRORS`] skipped lines failed, e.g. `line 3: expected value`
+    pub line_errors: Vec<String>,
+}
 
 /// Parse a conversation JSONL file (agent or session file)
 /// Gracefully handles malformed lines by logging and skipping them
 /// Returns an error if more than 50% of lines fail to parse or >100 consecutive errors
 pub fn parse_conversation_file(path: &Path) -> Result<Vec<ConversationEntry>> {
+    parse_conversation_file_with_skips(path).map(|(entries, _)| entries)
+}
+
+/// Like [`parse_conversation_file`], but also returns the number of malformed lines that were skipped
+pub fn parse_conversation_file_with_skips(path: &Path) -> Result<(Vec<ConversationEntry>, usize)> {
+    parse_conversation_file_with_options(path, &ParseOptions::default())
+        .map(|parsed| (parsed.entries, parsed.skipped_lines))
+}
+
+/// Parse a conversation file, reducing oversized tool results as configured in `options`
+///
+/// Oversized results are reduced line by line as they are parsed, so a single pathological
+/// line never stays in memory beyond its own parse.
+pub fn parse_conversation_file_with_options(
+    path: &Path,
+    options: &ParseOptions,
+) -> Result<ParsedConversation> {
     // Safely open file with TOCTOU protection and validation
-    let file = safe_open_file(path)?;
+    let file = safe_open_file(path, options.file_size_limit())?;
 
     let reader = BufReader::new(file);
     let mut entries = Vec::new();
+    let mut tool_output_bytes_skipped = 0;
     let mut skipped_count = 0;
+    let mut line_errors = Vec::new();
     let mut total_lines = 0;
     let mut consecutive_errors = 0;
     const MAX_CONSECUTIVE_ERRORS: usize = 100;
@@ -31,40 +190,49 @@ pub fn parse_conversation_file(path: &Path) -> Result<Vec<ConversationEntry>> {
         total_lines += 1;
 
         // Pre-filter: only parse conversation entries (user/assistant)
-        // Skip non-conversation entries like file-history-snapshot, summary, system
-        match serde_json::from_str::<serde_json::Value>(&line) {
-            Ok(value) => {
+        // Skip non-conversation entries like file-history-snapshot, summary, system.
+        // Only `type` is read here; the entry itself is parsed straight from the line (not
+        // via serde_json::Value), so large strings such as image data are never copied.
+        match serde_json::from_str::<LineType>(&line) {
+            Ok(line_type) => {
                 // Check if this is a conversation entry
-                let is_conversation = value
-                    .get("type")
-                    .and_then(|t| t.as_str())
-                    .map(|t| t == "user" || t == "assistant")
-                    .unwrap_or(false);
+                let is_conversation =
+                    matches!(line_type.entry_type.as_deref(), Some("user" | "assistant"));
 
                 if is_conversation {
                     // Attempt to parse as ConversationEntry
-                    match serde_json::from_value::<ConversationEntry>(value) {
-                        Ok(entry) => {
+                    match serde_json::from_str::<ConversationEntry>(&line) {
+                        Ok(mut entry) => {
+                            // Only lines longer than the limit can hold an oversized result
+                            if let Some(limit) = options.max_tool_result_bytes
+                                && line.len() > limit
+                            {
+                                tool_output_bytes_skipped += reduce_oversized_tool_results(
+                                    &mut entry,
+                                    limit,
+                                    options.oversized_tool_results,
+                                );
+                            }
                             entries.push(entry);
                             consecutive_errors = 0; // Reset on success
                         }
                         Err(e) => {
-                            eprintln!(
-                                "Warning: Failed to parse line {} in {}: {}",
-                                line_num + 1,
-                                path.display(),
-                                e
-                            );
+                            if line_errors.len() < MAX_LINE_ERRORS {
+                                line_errors.push(format!("line {}: {}", line_num + 1, e));
+                            }
                             skipped_count += 1;
                             consecutive_errors += 1;
 
                             // Bail if too many consecutive errors
                             if consecutive_errors >= MAX_CONSECUTIVE_ERRORS {
-                                bail!(
-                                    "Too many consecutive parse errors ({}) in {} - file may be corrupted",
-                                    consecutive_errors,
-                                    path.display()
-                                );
+                                return Err(ExplorerError::corrupt_file(
+                                    path,
+                                    format!(
+                                        "Too many consecutive parse errors ({}) in {} - file may be corrupted",
+                                        consecutive_errors,
+                                        path.display()
+                                    ),
+                                ));
                             }
                         }
                     }
@@ -72,22 +240,22 @@ pub fn parse_conversation_file(path: &Path) -> Result<Vec<ConversationEntry>> {
                 // Silently skip non-conversation entries (e.g., file-history-snapshot, summary, system)
             }
             Err(e) => {
-                eprintln!(
-                    "Warning: Failed to parse JSON on line {} in {}: {}",
-                    line_num + 1,
-                    path.display(),
-                    e
-                );
+                if line_errors.len() < MAX_LINE_ERRORS {
+                    line_errors.push(format!("line {}: {}", line_num + 1, e));
+                }
                 skipped_count += 1;
                 consecutive_errors += 1;
 
                 // Bail if too many consecutive errors
                 if consecutive_errors >= MAX_CONSECUTIVE_ERRORS {
-                    bail!(
-                        "Too many consecutive parse errors ({}) in {} - file may be corrupted",
-                        consecutive_errors,
-                        path.display()
-                    );
+                    return Err(ExplorerError::corrupt_file(
+                        path,
+                        format!(
+                            "Too many consecutive parse errors ({}) in {} - file may be corrupted",
+                            consecutive_errors,
+                            path.display()
+                        ),
+                    ));
                 }
             }
         }
@@ -97,26 +265,102 @@ pub fn parse_conversation_file(path: &Path) -> Result<Vec<ConversationEntry>> {
     if total_lines > 0 {
         let failure_rate = (skipped_count as f64) / (total_lines as f64);
         if failure_rate > 0.5 {
-            bail!(
-                "Too many parse failures in {}: {} of {} lines failed ({:.1}%)",
-                path.display(),
-                skipped_count,
-                total_lines,
-                failure_rate * 100.0
-            );
+            return Err(ExplorerError::corrupt_file(
+                path,
+                format!(
+                    "Too many parse failures in {}: {} of {} lines failed ({:.1}%)",
+                    path.display(),
+                    skipped_count,
+                    total_lines,
+                    failure_rate * 100.0
+                ),
+            ));
         }
     }
 
-    if skipped_count > 0 {
-        eprintln!(
-            "Parsed {}: {} entries ({} skipped)",
-            path.display(),
-            entries.len(),
-            skipped_count
-        );
+    Ok(ParsedConversation {
+        entries,
+        skipped_lines: skipped_count,
+        tool_output_bytes_skipped,
+        line_errors,
+    })
+}
+
+/// Reduce tool results above `limit` bytes in place; returns the number of bytes dropped
+fn reduce_oversized_tool_results(
+    entry: &mut ConversationEntry,
+    limit: usize,
+    mode: OversizedToolResults,
+) -> usize {
+    let MessageContent::Array(blocks) = &mut entry.message.content else {
+        return 0;
+    };
+
+    let mut dropped = 0;
+    for block in blocks {
+        let ContentBlock::ToolResult { content, .. } = block else {
+            continue;
+        };
+        let text = tool_result_text(content);
+        if text.len() <= limit {
+            continue;
+        }
+
+        let reduced = match mode {
+            OversizedToolResults::Skip => {
+                format!("[skipped {} bytes of tool output]", text.len())
+            }
+            OversizedToolResults::Sample => {
+                let sample = TOOL_RESULT_SAMPLE_BYTES.min(limit / 2);
+                let head = floor_char_boundary(&text, sample);
+                let tail = ceil_char_boundary(&text, text.len() - sample);
+                let skipped = tail - head;
+                format!(
+                    "{}\n[... skipped {} bytes of tool output ...]\n{}",
+                    &text[..head],
+                    skipped,
+                    &text[tail..]
+                )
+            }
+        };
+        dropped += text.len().saturating_sub(reduced.len());
+        *content = serde_json::Value::String(reduced);
+    }
+    dropped
+}
+
+/// Text of a tool result: strings and text blocks verbatim, anything else as compact JSON
+fn tool_result_text(content: &serde_json::Value) -> String {
+    match content {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Array(items) => {
+            items.iter().map(tool_result_text).collect::<Vec<_>>().join("\n")
+        }
+        serde_json::Value::Object(map) => match map.get("text") {
+            Some(serde_json::Value::String(text)) => text.clone(),
+            _ => content.to_string(),
+        },
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
     }
+}
 
-    Ok(entries)
+/// Largest char boundary at or before `index`
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    let mut index = index.min(s.len());
+    while !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Smallest char boundary at or after `index`
+fn ceil_char_boundary(s: &str, index: usize) -> usize {
+    let mut index = index.min(s.len());
+    while !s.is_char_boundary(index) {
+        index += 1;
+    }
+    index
 }
 
 #[cfg(test)]
@@ -230,6 +474,20 @@ invalid line 3"#;
         assert_eq!(entries[0].is_sidechain, Some(true));
     }
 
+    #[test]
+    fn test_parse_conversation_with_user_identity() {
+        let content = r#"{"type":"user","message":{"role":"user","content":"Hi"},"timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u1","userType":"external"}
+{"type":"user","message":{"role":"user","content":"Hi"},"timestamp":1234567891,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u2","userType":"external","userEmail":"alice@example.com"}
+{"type":"user","message":{"role":"user","content":"Hi"},"timestamp":1234567892,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u3","userEmail":"  "}"#;
+        let file = create_test_file(content);
+
+        let entries = parse_conversation_file(file.path()).unwrap();
+
+        assert_eq!(entries[0].user_identity(), Some("external"));
+        assert_eq!(entries[1].user_identity(), Some("alice@example.com"));
+        assert_eq!(entries[2].user_identity(), None);
+    }
+
     #[test]
     fn test_parse_conversation_with_string_content() {
         let content = r#"{"type":"user","message":{"role":"user","content":"Simple string content"},"timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"550e8400-e29b-41d4-a716-446655440001"}"#;
@@ -295,6 +553,32 @@ invalid line 3"#;
         assert_eq!(entries[0].message.role, "assistant");
     }
 
+    #[test]
+    fn test_parse_conversation_keeps_only_size_of_image_data() {
+        let data = "A".repeat(100_000);
+        let content = format!(
+            r#"{{"type":"user","message":{{"role":"user","content":[{{"type":"image","source":{{"type":"base64","media_type":"image/png","data":"{}"}},"alt_text":"chart"}}]}},"timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"550e8400-e29b-41d4-a716-446655440001"}}
+["not", "an", "entry"]
+{{"type":42}}"#,
+            data
+        );
+
+        let file = create_test_file(&content);
+        let parsed =
+            parse_conversation_file_with_options(file.path(), &ParseOptions::default()).unwrap();
+
+        assert_eq!(parsed.entries.len(), 1);
+        assert_eq!(parsed.skipped_lines, 0);
+        let MessageContent::Array(blocks) = &parsed.entries[0].message.content else {
+            panic!("expected content blocks");
+        };
+        let ContentBlock::Image { source, alt_text } = &blocks[0] else {
+            panic!("expected image block");
+        };
+        assert_eq!(source.data_bytes, 100_000);
+        assert_eq!(alt_text.as_deref(), Some("chart"));
+    }
+
     #[test]
     fn test_parse_conversation_skips_non_conversation_entry_types() {
         // Mix of conversation entries (user/assistant) and non-conversation entries
@@ -341,4 +625,75 @@ invalid line 3"#;
         let err = result.unwrap_err();
         assert!(err.to_string().contains("Too many consecutive parse errors"));
     }
+
+    fn tool_result_line(content: &str) -> String {
+        format!(
+            r#"{{"type":"user","message":{{"role":"user","content":[{{"type":"tool_result","tool_use_id":"t1","content":"{content}"}}]}},"timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"550e8400-e29b-41d4-a716-446655440001"}}"#
+        )
+    }
+
+    fn tool_result_content(entry: &ConversationEntry) -> &str {
+        match &entry.message.content {
+            MessageContent::Array(blocks) => match &blocks[0] {
+                ContentBlock::ToolResult { content, .. } => content.as_str().unwrap(),
+                other => panic!("unexpected block {:?}", other),
+            },
+            MessageContent::String(_) => panic!("expected blocks"),
+        }
+    }
+
+    #[test]
+    fn test_oversized_tool_result_is_sampled() {
+        let big = format!("{}{}{}", "a".repeat(3000), "z".repeat(10_000), "c".repeat(3000));
+        let file = create_test_file(&tool_result_line(&big));
+        let options = ParseOptions { max_tool_result_bytes: Some(8192), ..Default::default() };
+
+        let parsed = parse_conversation_file_with_options(file.path(), &options).unwrap();
+
+        let content = tool_result_content(&parsed.entries[0]);
+        assert!(content.starts_with(&"a".repeat(TOOL_RESULT_SAMPLE_BYTES)));
+        assert!(content.ends_with(&"c".repeat(TOOL_RESULT_SAMPLE_BYTES)));
+        assert!(content.contains("[... skipped 11904 bytes of tool output ...]"));
+        assert!(!content.contains('z'));
+        assert_eq!(parsed.tool_output_bytes_skipped, big.len() - content.len());
+    }
+
+    #[test]
+    fn test_oversized_tool_result_is_skipped() {
+        let file = create_test_file(&tool_result_line(&"x".repeat(500)));
+        let options = ParseOptions {
+            max_tool_result_bytes: Some(100),
+            oversized_tool_results: OversizedToolResults::Skip,
+            ..Default::default()
+        };
+
+        let parsed = parse_conversation_file_with_options(file.path(), &options).unwrap();
+
+        assert_eq!(tool_result_content(&parsed.entries[0]), "[skipped 500 bytes of tool output]");
+        assert!(parsed.tool_output_bytes_skipped > 400);
+    }
+
+    #[test]
+    fn test_tool_results_within_limit_are_untouched() {
+        let file = create_test_file(&tool_result_line("small output"));
+        let options = ParseOptions { max_tool_result_bytes: Some(20), ..Default::default() };
+
+        let parsed = parse_conversation_file_with_options(file.path(), &options).unwrap();
+
+        assert_eq!(tool_result_content(&parsed.entries[0]), "small output");
+        assert_eq!(parsed.tool_output_bytes_skipped, 0);
+    }
+
+    #[test]
+    fn test_sampling_respects_char_boundaries() {
+        let big = "é".repeat(200);
+        let file = create_test_file(&tool_result_line(&big));
+        let options = ParseOptions { max_tool_result_bytes: Some(101), ..Default::default() };
+
+        let parsed = parse_conversation_file_with_options(file.path(), &options).unwrap();
+
+        let content = tool_result_content(&parsed.entries[0]);
+        assert!(content.starts_with(&"é".repeat(25)));
+        assert!(content.contains("skipped 300 bytes"));
+    }
 }
@@ -1,10 +1,20 @@
-use chrono::{DateTime, Utc};
-use serde::de::Error;
+use std::fmt;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::de::{Error, IgnoredAny, MapAccess, SeqAccess, Visitor};
 use serde::{Deserialize, Deserializer};
 use serde_json::Value;
-use uuid::Uuid;
+use uuid::{Builder, Uuid};
+
+use crate::models::ImageSource;
+
+/// Formats of string timestamps written without a time zone (read as UTC)
+const NAIVE_TIMESTAMP_FORMATS: &[&str] = &["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"];
 
-/// Custom deserializer for timestamp that accepts both integers (ms) and RFC3339 strings
+/// Custom deserializer for timestamp that accepts both integers (ms) and strings
+///
+/// Strings may be RFC3339, RFC2822, a date and time without a zone (UTC) or milliseconds, as
+/// written by older Claude Code versions.
 pub fn deserialize_timestamp<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
 where
     D: Deserializer<'de>,
@@ -17,15 +27,48 @@ where
             DateTime::from_timestamp_millis(ms)
                 .ok_or_else(|| Error::custom("timestamp out of range"))
         }
-        Value::String(s) => {
-            // Parse as RFC3339
-            s.parse::<DateTime<Utc>>()
-                .map_err(|e| Error::custom(format!("invalid RFC3339 timestamp: {}", e)))
-        }
+        Value::String(s) => parse_timestamp_str(&s).ok_or_else(|| {
+            Error::custom(format!(
+                "invalid timestamp '{}' (expected RFC3339, a date and time or milliseconds)",
+                s
+            ))
+        }),
         _ => Err(Error::custom("timestamp must be a number or string")),
     }
 }
 
+/// Like [`deserialize_timestamp`], with `null` (or a missing field, with `#[serde(default)]`)
+/// read as `None`
+pub fn deserialize_optional_timestamp<'de, D>(
+    deserializer: D,
+) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Timestamp(#[serde(deserialize_with = "deserialize_timestamp")] DateTime<Utc>);
+
+    Ok(Option::<Timestamp>::deserialize(deserializer)?.map(|Timestamp(time)| time))
+}
+
+/// Parse a string timestamp in any of the formats [`deserialize_timestamp`] accepts
+pub fn parse_timestamp_str(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+    if let Ok(time) = s.parse::<DateTime<Utc>>() {
+        return Some(time);
+    }
+    if let Ok(time) = DateTime::parse_from_rfc2822(s) {
+        return Some(time.with_timezone(&Utc));
+    }
+    if let Some(time) = NAIVE_TIMESTAMP_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+    {
+        return Some(time.and_utc());
+    }
+    s.parse::<i64>().ok().and_then(DateTime::from_timestamp_millis)
+}
+
 /// Custom deserializer for session IDs that validates UUID format
 pub fn deserialize_session_id<'de, D>(deserializer: D) -> Result<String, D::Error>
 where
@@ -45,11 +88,203 @@ where
     Ok(s)
 }
 
+/// Like [`deserialize_session_id`], with `null` (or a missing field, with `#[serde(default)]`)
+/// read as `None`
+pub fn deserialize_optional_session_id<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct SessionId(#[serde(deserialize_with = "deserialize_session_id")] String);
+
+    Ok(Option::<SessionId>::deserialize(deserializer)?.map(|SessionId(id)| id))
+}
+
+/// Stable session id for an entry written without one
+///
+/// Entries of the same project and UTC day get the same id, a UUID (version 8) derived from
+/// both, so they are grouped like a session and keep their id across runs.
+pub fn fallback_session_id(project: Option<&str>, timestamp: DateTime<Utc>) -> String {
+    let key = format!("{}\0{}", project.unwrap_or_default(), timestamp.format("%Y-%m-%d"));
+    // 128-bit FNV-1a: unlike `DefaultHasher`, stable across builds
+    let hash = key.bytes().fold(0x6c62_272e_07bb_0142_62b8_2175_6295_c58d_u128, |hash, byte| {
+        (hash ^ u128::from(byte)).wrapping_mul(0x0000_0000_0100_0000_0000_0000_0000_013b)
+    });
+    Builder::from_custom_bytes(hash.to_be_bytes()).into_uuid().to_string()
+}
+
+/// Custom deserializer for image sources that drops inline data, keeping only its size
+///
+/// A plain string source is treated as inline data. When deserializing from a `&str`
+/// (`serde_json::from_str`), the data is never copied: only its length is read.
+pub fn deserialize_image_source<'de, D>(deserializer: D) -> Result<ImageSource, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(ImageSourceVisitor)
+}
+
+struct ImageSourceVisitor;
+
+impl<'de> Visitor<'de> for ImageSourceVisitor {
+    type Value = ImageSource;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an image source object or string")
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<ImageSource, E> {
+        Ok(ImageSource { data_bytes: v.len(), ..Default::default() })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<ImageSource, A::Error> {
+        let mut source = ImageSource::default();
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "data" {
+                source.data_bytes = map.next_value::<DataLength>()?.0;
+            } else {
+                let value = map.next_value::<Value>()?;
+                source.fields.insert(key, value);
+            }
+        }
+        Ok(source)
+    }
+
+    // Anything else carries no data worth keeping
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<ImageSource, A::Error> {
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+        Ok(ImageSource::default())
+    }
+
+    fn visit_unit<E: Error>(self) -> Result<ImageSource, E> {
+        Ok(ImageSource::default())
+    }
+
+    fn visit_bool<E: Error>(self, _v: bool) -> Result<ImageSource, E> {
+        Ok(ImageSource::default())
+    }
+
+    fn visit_i64<E: Error>(self, _v: i64) -> Result<ImageSource, E> {
+        Ok(ImageSource::default())
+    }
+
+    fn visit_u64<E: Error>(self, _v: u64) -> Result<ImageSource, E> {
+        Ok(ImageSource::default())
+    }
+
+    fn visit_f64<E: Error>(self, _v: f64) -> Result<ImageSource, E> {
+        Ok(ImageSource::default())
+    }
+}
+
+/// Length of a string value, read without keeping the string (0 for other values)
+struct DataLength(usize);
+
+impl<'de> Deserialize<'de> for DataLength {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DataLengthVisitor;
+
+        impl<'de> Visitor<'de> for DataLengthVisitor {
+            type Value = DataLength;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("image data")
+            }
+
+            fn visit_str<E: Error>(self, v: &str) -> Result<DataLength, E> {
+                Ok(DataLength(v.len()))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<DataLength, A::Error> {
+                while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+                Ok(DataLength(0))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<DataLength, A::Error> {
+                while seq.next_element::<IgnoredAny>()?.is_some() {}
+                Ok(DataLength(0))
+            }
+
+            fn visit_unit<E: Error>(self) -> Result<DataLength, E> {
+                Ok(DataLength(0))
+            }
+
+            fn visit_bool<E: Error>(self, _v: bool) -> Result<DataLength, E> {
+                Ok(DataLength(0))
+            }
+
+            fn visit_i64<E: Error>(self, _v: i64) -> Result<DataLength, E> {
+                Ok(DataLength(0))
+            }
+
+            fn visit_u64<E: Error>(self, _v: u64) -> Result<DataLength, E> {
+                Ok(DataLength(0))
+            }
+
+            fn visit_f64<E: Error>(self, _v: f64) -> Result<DataLength, E> {
+                Ok(DataLength(0))
+            }
+        }
+
+        deserializer.deserialize_any(DataLengthVisitor)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use chrono::DateTime;
+    use chrono::TimeZone;
 
-    use crate::models::HistoryEntry;
+    use super::*;
+    use crate::models::{ContentBlock, HistoryEntry};
+
+    #[test]
+    fn test_image_source_drops_inline_data() {
+        let data = "iVBORw0KGgo".repeat(1000);
+        let json = format!(
+            r#"{{"type":"image","source":{{"type":"base64","media_type":"image/png","data":"{}"}}}}"#,
+            data
+        );
+
+        let block: ContentBlock = serde_json::from_str(&json).unwrap();
+
+        let ContentBlock::Image { source, alt_text } = block else {
+            panic!("expected image block");
+        };
+        assert_eq!(source.data_bytes, data.len());
+        assert_eq!(source.fields["media_type"], "image/png");
+        assert!(!source.fields.contains_key("data"));
+        assert!(alt_text.is_none());
+
+        // Serialized with a placeholder instead of the data
+        let value = serde_json::to_value(&source).unwrap();
+        assert_eq!(value["type"], "base64");
+        assert_eq!(value["data"], format!("[image data omitted: {} bytes]", data.len()));
+    }
+
+    #[test]
+    fn test_image_source_string_url_and_missing() {
+        let block: ContentBlock =
+            serde_json::from_str(r#"{"type":"image","source":"base64data"}"#).unwrap();
+        let ContentBlock::Image { source, .. } = block else { panic!("expected image block") };
+        assert_eq!(source.data_bytes, 10);
+
+        let block: ContentBlock = serde_json::from_str(
+            r#"{"type":"image","source":{"type":"url","url":"https://example.com/a.png"}}"#,
+        )
+        .unwrap();
+        let ContentBlock::Image { source, .. } = block else { panic!("expected image block") };
+        assert_eq!(source.data_bytes, 0);
+        assert_eq!(source.fields["url"], "https://example.com/a.png");
+        assert!(serde_json::to_value(&source).unwrap().get("data").is_none());
+
+        let block: ContentBlock =
+            serde_json::from_str(r#"{"type":"image","alt_text":"diagram"}"#).unwrap();
+        let ContentBlock::Image { source, alt_text } = block else {
+            panic!("expected image block")
+        };
+        assert_eq!(source, Default::default());
+        assert_eq!(alt_text.as_deref(), Some("diagram"));
+    }
 
     #[test]
     fn test_history_entry_timestamp_integer() {
@@ -215,7 +450,37 @@ mod tests {
 
         let result: Result<HistoryEntry, _> = serde_json::from_str(json);
         assert!(result.is_err(), "Invalid RFC3339 string should fail validation");
-        assert!(result.unwrap_err().to_string().contains("invalid RFC3339"));
+        assert!(result.unwrap_err().to_string().contains("invalid timestamp 'not-a-valid-date'"));
+    }
+
+    #[test]
+    fn test_string_timestamp_formats() {
+        let expected = Utc.with_ymd_and_hms(2024, 1, 15, 10, 30, 0).unwrap();
+        for text in [
+            "2024-01-15T10:30:00Z",
+            "2024-01-15T12:30:00+02:00",
+            "2024-01-15T10:30:00",
+            "2024-01-15 10:30:00",
+            "2024-01-15T10:30:00.000",
+            "Mon, 15 Jan 2024 10:30:00 +0000",
+            "1705314600000",
+        ] {
+            assert_eq!(parse_timestamp_str(text), Some(expected), "{}", text);
+        }
+        assert_eq!(parse_timestamp_str("2024-01-15"), None);
+    }
+
+    #[test]
+    fn test_fallback_session_id_is_stable_per_project_and_day() {
+        let morning = Utc.with_ymd_and_hms(2024, 1, 15, 8, 0, 0).unwrap();
+        let evening = Utc.with_ymd_and_hms(2024, 1, 15, 20, 0, 0).unwrap();
+        let id = fallback_session_id(Some("/work/api"), morning);
+
+        assert!(Uuid::parse_str(&id).is_ok());
+        assert_eq!(id, fallback_session_id(Some("/work/api"), evening));
+        assert_ne!(id, fallback_session_id(Some("/work/web"), morning));
+        assert_ne!(id, fallback_session_id(Some("/work/api"), morning + chrono::Days::new(1)));
+        assert_ne!(id, fallback_session_id(None, morning));
     }
 
     #[test]
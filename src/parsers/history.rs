@@ -1,51 +1,148 @@
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
-use anyhow::{Context, Result, bail};
-
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::Deserialize;
+
+use super::MAX_LINE_ERRORS;
+use super::deserializers::{
+    deserialize_optional_session_id, deserialize_optional_timestamp, fallback_session_id,
+};
+use super::panics::{QuietPanics, catch_quietly};
+use crate::error::{ExplorerError, Result};
 use crate::models::HistoryEntry;
-use crate::utils::safe_open_file;
+use crate::parsers::ParseOptions;
+use crate::utils::safe_open_file_tail;
+
+/// Non-empty lines read before parsing them together (in parallel with the `parallel` feature)
+const PARSE_BATCH_LINES: usize = 4096;
+
+/// A history.jsonl line, which older Claude Code versions wrote without `sessionId` or
+/// `timestamp` at times
+#[derive(Deserialize)]
+struct RawHistoryEntry {
+    display: String,
+    #[serde(default, deserialize_with = "deserialize_optional_timestamp")]
+    timestamp: Option<DateTime<Utc>>,
+    #[serde(default)]
+    project: Option<String>,
+    #[serde(default, rename = "sessionId", deserialize_with = "deserialize_optional_session_id")]
+    session_id: Option<String>,
+}
+
+/// Entries of a history file and what was done to read them
+#[derive(Debug, Default)]
+pub struct ParsedHistory {
+    pub entries: Vec<HistoryEntry>,
+    /// Malformed lines that were skipped
+    pub skipped: usize,
+    /// Entries written without a session id or timestamp, completed with fallbacks
+    pub normalized: usize,
+    /// Bytes at the start of a file over the size limit that were not read
+    pub truncated_bytes: u64,
+    /// Why the first [`MAX_LINE_ERRORS`] skipped lines failed, e.g. `line 3: expected value`
+    pub line_errors: Vec<String>,
+}
 
 /// Parse history.jsonl file and return list of history entries
 /// Gracefully handles malformed lines by logging and skipping them
 /// Returns an error if more than 50% of lines fail to parse or >100 consecutive errors
 pub fn parse_history_file(path: &Path) -> Result<Vec<HistoryEntry>> {
+    parse_history_file_with_skips(path).map(|(entries, _)| entries)
+}
+
+/// Like [`parse_history_file`], but also returns the number of malformed lines that were skipped
+pub fn parse_history_file_with_skips(path: &Path) -> Result<(Vec<HistoryEntry>, usize)> {
+    parse_history_file_with_stats(path, &ParseOptions::default())
+        .map(|parsed| (parsed.entries, parsed.skipped))
+}
+
+/// Like [`parse_history_file`], also counting skipped lines and normalized entries
+///
+/// An entry without a timestamp gets the one of the entry before it (the file is appended to
+/// in order), or of the first entry that has one; without any, the file's modification time.
+/// An entry without a session id gets a stable id per project and day (see
+/// [`fallback_session_id`]).
+///
+/// A file larger than the size limit of `options` is read from its last complete line within
+/// the limit, keeping the most recent entries; the bytes before it are counted in
+/// [`ParsedHistory::truncated_bytes`].
+pub fn parse_history_file_with_stats(path: &Path, options: &ParseOptions) -> Result<ParsedHistory> {
+    parse_history_tail(path, options.file_size_limit())
+}
+
+fn parse_history_tail(path: &Path, max_bytes: u64) -> Result<ParsedHistory> {
     // Safely open file with TOCTOU protection and validation
-    let file = safe_open_file(path)?;
+    let (file, mut truncated_bytes) = safe_open_file_tail(path, max_bytes)?;
+    let mut reader = BufReader::new(file);
+    if truncated_bytes > 0 {
+        // Drop the rest of the line the read started in; stepping back one byte keeps a
+        // line that starts exactly at the cut
+        reader.seek_relative(-1).context("Failed to seek in history file")?;
+        let mut partial = Vec::new();
+        let read = reader
+            .read_until(b'\n', &mut partial)
+            .context("Failed to read line from history file")?;
+        truncated_bytes += read.saturating_sub(1) as u64;
+    }
 
-    let reader = BufReader::new(file);
+    let mut lines = reader.lines().enumerate();
+    let mut batch: Vec<(usize, String)> = Vec::with_capacity(PARSE_BATCH_LINES);
     let mut entries = Vec::new();
     let mut skipped_count = 0;
+    let mut line_errors = Vec::new();
     let mut total_lines = 0;
     let mut consecutive_errors = 0;
     const MAX_CONSECUTIVE_ERRORS: usize = 100;
 
-    for (line_num, line) in reader.lines().enumerate() {
-        let line = line.context("Failed to read line from history file")?;
+    // Lines are read in batches and each batch is parsed at once; the results are then
+    // handled in file order, as if the lines had been parsed one by one
+    loop {
+        batch.clear();
+        for (line_num, line) in lines.by_ref() {
+            let line = line.context("Failed to read line from history file")?;
 
-        // Skip empty lines
-        if line.trim().is_empty() {
-            continue;
+            // Skip empty lines
+            if line.trim().is_empty() {
+                continue;
+            }
+            batch.push((line_num, line));
+            if batch.len() == PARSE_BATCH_LINES {
+                break;
+            }
+        }
+        if batch.is_empty() {
+            break;
         }
 
-        total_lines += 1;
+        for ((line_num, _), parsed) in batch.iter().zip(parse_lines(&batch)) {
+            total_lines += 1;
 
-        match serde_json::from_str::<HistoryEntry>(&line) {
-            Ok(entry) => {
-                entries.push(entry);
-                consecutive_errors = 0; // Reset on success
-            }
-            Err(e) => {
-                eprintln!("Warning: Failed to parse line {} in history file: {}", line_num + 1, e);
-                skipped_count += 1;
-                consecutive_errors += 1;
-
-                // Bail if too many consecutive errors
-                if consecutive_errors >= MAX_CONSECUTIVE_ERRORS {
-                    bail!(
-                        "Too many consecutive parse errors ({}) in history file - file may be corrupted",
-                        consecutive_errors
-                    );
+            match parsed {
+                Ok(entry) => {
+                    entries.push(entry);
+                    consecutive_errors = 0; // Reset on success
+                }
+                Err(e) => {
+                    if line_errors.len() < MAX_LINE_ERRORS {
+                        line_errors.push(format!("line {}: {}", line_num + 1, e));
+                    }
+                    skipped_count += 1;
+                    consecutive_errors += 1;
+
+                    // Bail if too many consecutive errors
+                    if consecutive_errors >= MAX_CONSECUTIVE_ERRORS {
+                        return Err(ExplorerError::corrupt_file(
+                            path,
+                            format!(
+                                "Too many consecutive parse errors ({}) in history file - file may be corrupted",
+                                consecutive_errors
+                            ),
+                        ));
+                    }
                 }
             }
         }
@@ -55,20 +152,75 @@ pub fn parse_history_file(path: &Path) -> Result<Vec<HistoryEntry>> {
     if total_lines > 0 {
         let failure_rate = (skipped_count as f64) / (total_lines as f64);
         if failure_rate > 0.5 {
-            bail!(
-                "Too many parse failures in history file: {} of {} lines failed ({:.1}%)",
-                skipped_count,
-                total_lines,
-                failure_rate * 100.0
-            );
+            return Err(ExplorerError::corrupt_file(
+                path,
+                format!(
+                    "Too many parse failures in history file: {} of {} lines failed ({:.1}%)",
+                    skipped_count,
+                    total_lines,
+                    failure_rate * 100.0
+                ),
+            ));
         }
     }
 
-    if skipped_count > 0 {
-        eprintln!("Parsed history file: {} entries ({} skipped)", entries.len(), skipped_count);
-    }
+    let (entries, normalized) = normalize_entries(path, entries);
+    Ok(ParsedHistory { entries, skipped: skipped_count, normalized, truncated_bytes, line_errors })
+}
 
-    Ok(entries)
+/// Parse a batch of `(line number, line)`, keeping the order of the lines
+fn parse_lines(lines: &[(usize, String)]) -> Vec<std::result::Result<RawHistoryEntry, String>> {
+    parse_lines_with(lines, |line| serde_json::from_str(line))
+}
+
+/// Parse each line of a batch with `parse`; a line whose parse panics fails like a malformed
+/// one instead of taking the whole build down
+fn parse_lines_with<T: Send>(
+    lines: &[(usize, String)],
+    parse: impl Fn(&str) -> serde_json::Result<T> + Sync,
+) -> Vec<std::result::Result<T, String>> {
+    let _quiet = QuietPanics::install();
+    #[cfg(feature = "parallel")]
+    let iter = lines.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let iter = lines.iter();
+    iter.map(|(_, line)| match catch_quietly(|| parse(line)) {
+        Ok(parsed) => parsed.map_err(|e| e.to_string()),
+        Err(message) => Err(format!("parser panicked: {}", message)),
+    })
+    .collect()
+}
+
+/// Fill in missing timestamps and session ids, returning the entries and how many were changed
+fn normalize_entries(path: &Path, raw: Vec<RawHistoryEntry>) -> (Vec<HistoryEntry>, usize) {
+    let first_timestamp = raw.iter().find_map(|entry| entry.timestamp).or_else(|| {
+        let modified = std::fs::metadata(path).and_then(|meta| meta.modified()).ok()?;
+        Some(DateTime::<Utc>::from(modified))
+    });
+    let mut last_timestamp = first_timestamp.unwrap_or_else(Utc::now);
+    let mut normalized = 0;
+
+    let entries = raw
+        .into_iter()
+        .map(|entry| {
+            if entry.timestamp.is_none() || entry.session_id.is_none() {
+                normalized += 1;
+            }
+            let timestamp = entry.timestamp.unwrap_or(last_timestamp);
+            last_timestamp = timestamp;
+            let session_id = entry
+                .session_id
+                .unwrap_or_else(|| fallback_session_id(entry.project.as_deref(), timestamp));
+            HistoryEntry {
+                display: entry.display,
+                timestamp,
+                project: entry.project,
+                session_id,
+                pasted_contents: None,
+            }
+        })
+        .collect();
+    (entries, normalized)
 }
 
 #[cfg(test)]
@@ -127,6 +279,30 @@ mod tests {
         assert_eq!(entries.len(), 0);
     }
 
+    #[test]
+    fn test_parse_normalizes_missing_session_id_and_timestamp() {
+        let content = r#"{"display":"No time yet","sessionId":"550e8400-e29b-41d4-a716-446655440000"}
+{"display":"Old format","timestamp":"2024-01-15 10:30:00","project":"/work/api"}
+{"display":"No time","timestamp":null,"project":"/work/api"}
+{"display":"Complete","timestamp":1705400000000,"sessionId":"550e8400-e29b-41d4-a716-446655440001"}"#;
+
+        let file = create_test_file(content);
+        let parsed = parse_history_file_with_stats(file.path(), &ParseOptions::default()).unwrap();
+
+        assert_eq!(parsed.skipped, 0);
+        assert_eq!(parsed.normalized, 3);
+        let entries = parsed.entries;
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[1].timestamp.to_rfc3339(), "2024-01-15T10:30:00+00:00");
+        // Leading entries take the first known time, later ones the previous entry's
+        assert_eq!(entries[0].timestamp, entries[1].timestamp);
+        assert_eq!(entries[2].timestamp, entries[1].timestamp);
+        assert_eq!(entries[0].session_id, "550e8400-e29b-41d4-a716-446655440000");
+        assert_eq!(entries[1].session_id, entries[2].session_id);
+        assert!(uuid::Uuid::parse_str(&entries[1].session_id).is_ok());
+        assert_eq!(entries[3].session_id, "550e8400-e29b-41d4-a716-446655440001");
+    }
+
     #[test]
     fn test_parse_skips_malformed_lines() {
         let content = r#"{"display":"Valid entry 1","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000"}
@@ -144,6 +320,10 @@ invalid json line
         assert_eq!(entries[0].display, "Valid entry 1");
         assert_eq!(entries[1].display, "Valid entry 2");
         assert_eq!(entries[2].display, "Valid entry 3");
+
+        let parsed = parse_history_file_with_stats(file.path(), &ParseOptions::default()).unwrap();
+        assert_eq!(parsed.line_errors.len(), 2);
+        assert!(parsed.line_errors[0].starts_with("line 2: expected value"));
     }
 
     #[test]
@@ -161,6 +341,7 @@ invalid line 3
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert!(err.to_string().contains("Too many parse failures"));
+        assert!(matches!(err, ExplorerError::CorruptFile { path, .. } if path == file.path()));
     }
 
     #[test]
@@ -214,7 +395,9 @@ invalid line 3
     fn test_parse_nonexistent_file() {
         let result = parse_history_file(Path::new("/nonexistent/path/history.jsonl"));
         assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("Failed to open"));
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("Failed to open"));
+        assert!(matches!(err, ExplorerError::FileNotFound(_)));
     }
 
     #[test]
@@ -286,6 +469,33 @@ invalid line 2"#;
         assert_eq!(entries.len(), 1000);
     }
 
+    #[test]
+    fn test_parse_batches_keep_file_order() {
+        // Spans several batches, with malformed and empty lines across batch boundaries
+        let lines = PARSE_BATCH_LINES * 2 + 10;
+        let mut content = String::new();
+        for i in 0..lines {
+            if i % 1000 == 999 {
+                content.push_str("{not json}\n\n");
+                continue;
+            }
+            content.push_str(&format!(
+                r#"{{"display":"Entry {}","timestamp":{},"sessionId":"550e8400-e29b-41d4-a716-446655440000"}}"#,
+                i,
+                1234567890 + i
+            ));
+            content.push('\n');
+        }
+
+        let file = create_test_file(&content);
+        let parsed = parse_history_file_with_stats(file.path(), &ParseOptions::default()).unwrap();
+
+        assert_eq!(parsed.skipped, lines / 1000);
+        assert_eq!(parsed.entries.len(), lines - lines / 1000);
+        assert_eq!(parsed.entries[999].display, "Entry 1000");
+        assert!(parsed.entries.windows(2).all(|pair| pair[0].timestamp < pair[1].timestamp));
+    }
+
     #[test]
     fn test_parse_file_approaching_size_limit() {
         // Create a file close to 10MB limit (9MB)
@@ -310,6 +520,51 @@ invalid line 2"#;
         assert!(!result.unwrap().is_empty());
     }
 
+    #[test]
+    fn test_parse_oversized_file_reads_most_recent_lines() {
+        let lines: Vec<String> = (1..=4)
+            .map(|i| {
+                format!(
+                    r#"{{"display":"Entry {}","timestamp":123456789{},"sessionId":"550e8400-e29b-41d4-a716-446655440000"}}"#,
+                    i, i
+                )
+            })
+            .collect();
+        let line_len = lines[0].len() as u64 + 1;
+        let file = create_test_file(&(lines.join("\n") + "\n"));
+        let displays = |parsed: &ParsedHistory| {
+            parsed.entries.iter().map(|e| e.display.clone()).collect::<Vec<_>>()
+        };
+
+        // The cut falls inside the second line, which is dropped
+        let parsed = parse_history_tail(file.path(), 2 * line_len + 5).unwrap();
+        assert_eq!(displays(&parsed), ["Entry 3", "Entry 4"]);
+        assert_eq!(parsed.truncated_bytes, 2 * line_len);
+        assert_eq!(parsed.skipped, 0);
+
+        // A line starting exactly at the cut is kept
+        let parsed = parse_history_tail(file.path(), 2 * line_len).unwrap();
+        assert_eq!(displays(&parsed), ["Entry 3", "Entry 4"]);
+        assert_eq!(parsed.truncated_bytes, 2 * line_len);
+
+        let parsed = parse_history_tail(file.path(), 4 * line_len).unwrap();
+        assert_eq!(parsed.entries.len(), 4);
+        assert_eq!(parsed.truncated_bytes, 0);
+    }
+
+    #[test]
+    fn test_panicking_line_fails_alone() {
+        let batch: Vec<(usize, String)> =
+            ["1", "boom", "3"].iter().enumerate().map(|(i, line)| (i, line.to_string())).collect();
+        let parsed = parse_lines_with(&batch, |line| {
+            assert_ne!(line, "boom", "unexpected line");
+            serde_json::from_str::<u32>(line)
+        });
+        assert_eq!(parsed[0], Ok(1));
+        assert!(parsed[1].as_ref().unwrap_err().starts_with("parser panicked: assertion"));
+        assert_eq!(parsed[2], Ok(3));
+    }
+
     // ===== I/O Error Scenario Tests =====
 
     #[test]
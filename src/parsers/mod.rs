@@ -1,17 +1,19 @@
-//! JSONL parsers for Claude Code history and conversation files
+//! Parsers for Claude Code history and conversation files, and for the histories of other
+//! AI coding tools ([`codex`], [`aider`]) and Markdown chat transcripts ([`transcript`])
 //!
 //! # Error Handling Strategy
 //!
 //! This module follows a **graceful degradation** approach suitable for CLI tools:
 //!
-//! - **Individual line failures**: Malformed JSON lines are logged to stderr and skipped,
-//!   allowing parsing to continue. This prevents a single bad line from breaking the entire index.
+//! - **Individual line failures**: Malformed JSON lines are counted and skipped, allowing
+//!   parsing to continue. This prevents a single bad line from breaking the entire index.
 //!
 //! - **Catastrophic failure detection**: If >50% of lines fail to parse, or if >100 consecutive
 //!   errors occur, the parser returns an error. This prevents accepting severely corrupted files.
 //!
-//! - **User feedback**: Summary statistics are printed showing successful entries, warnings, and
-//!   failures, giving users visibility into parse quality.
+//! - **User feedback**: Parsers print nothing (a warning on stderr would corrupt the TUI's
+//!   screen). They return the number of skipped lines and the first [`MAX_LINE_ERRORS`] parse
+//!   errors, which the indexer collects in its `IndexReport`.
 //!
 //! - **Error propagation**: Uses `anyhow::Result` for error handling with context. Since this is
 //!   a binary/CLI tool (not a library), errors are boxed and consumers don't match on error types.
@@ -26,9 +28,28 @@
 //! deeply nested JSON could cause stack overflow. Attempting to parse JSON deeper than 128 levels
 //! will result in a parse error that triggers the graceful degradation logic above.
 
+/// Parse errors of skipped lines kept per file (the rest are only counted)
+pub const MAX_LINE_ERRORS: usize = 5;
+
+pub mod aider;
+pub mod codex;
 pub mod conversation;
 pub mod deserializers;
 pub mod history;
+pub(crate) mod panics;
+pub mod relations;
+pub mod session_cache;
+pub mod transcript;
 
-pub use conversation::parse_conversation_file;
-pub use history::parse_history_file;
+pub use aider::{AIDER_HISTORY_FILE_NAME, parse_aider_history};
+pub use codex::parse_codex_session;
+pub use conversation::{
+    OversizedToolResults, ParseOptions, ParsedConversation, parse_conversation_file,
+    parse_conversation_file_with_options, parse_conversation_file_with_skips,
+};
+pub use history::{
+    ParsedHistory, parse_history_file, parse_history_file_with_skips, parse_history_file_with_stats,
+};
+pub use relations::EntryRelations;
+pub use session_cache::{SessionCache, SessionSummary};
+pub use transcript::parse_markdown_transcript;
@@ -0,0 +1,104 @@
+//! Catching parser panics without printing them.
+//!
+//! A bug in a parser shouldn't abort a whole index build, and the default panic hook shouldn't
+//! write its message to stderr behind the TUI either: the panic is reported as a failed file
+//! or line instead. While a [`QuietPanics`] guard is alive the panic hook ignores panics
+//! raised inside [`catch_quietly`] and passes every other panic to the hook it replaced, which
+//! is restored once the last guard is dropped.
+
+use std::any::Any;
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe, PanicHookInfo};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+type PanicHook = Box<dyn Fn(&PanicHookInfo<'_>) + Sync + Send + 'static>;
+
+thread_local! {
+    /// Whether this thread is running a closure of [`catch_quietly`]
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Number of live [`QuietPanics`] guards and the hook replaced by the first of them
+static QUIET: Mutex<(usize, Option<Arc<PanicHook>>)> = Mutex::new((0, None));
+
+/// Keeps the panics caught by [`catch_quietly`] off stderr while alive
+///
+/// Guards may overlap, on one thread or several; the panic hook is swapped when the first
+/// is installed and restored when the last is dropped.
+pub(crate) struct QuietPanics(());
+
+impl QuietPanics {
+    pub(crate) fn install() -> Self {
+        let mut quiet = QUIET.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if quiet.1.is_none() {
+            let previous = Arc::new(panic::take_hook());
+            let hook = Arc::clone(&previous);
+            panic::set_hook(Box::new(move |info| {
+                if !CATCHING.with(Cell::get) {
+                    hook(info);
+                }
+            }));
+            quiet.1 = Some(previous);
+        }
+        quiet.0 += 1;
+        Self(())
+    }
+}
+
+impl Drop for QuietPanics {
+    fn drop(&mut self) {
+        let mut quiet = QUIET.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        quiet.0 -= 1;
+        // The hook can't be changed while unwinding; the quiet hook then stays until the
+        // next guard is dropped, still passing other panics on
+        if quiet.0 > 0 || thread::panicking() {
+            return;
+        }
+        if let Some(previous) = quiet.1.take() {
+            // Drop the quiet hook and with it its reference to the previous one
+            drop(panic::take_hook());
+            match Arc::try_unwrap(previous) {
+                Ok(previous) => panic::set_hook(previous),
+                Err(previous) => panic::set_hook(Box::new(move |info| previous(info))),
+            }
+        }
+    }
+}
+
+/// Run `f`, returning the message of its panic if it panics
+///
+/// The panic isn't printed while a [`QuietPanics`] guard is alive.
+pub(crate) fn catch_quietly<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    let was_catching = CATCHING.with(|catching| catching.replace(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING.with(|catching| catching.set(was_catching));
+    result.map_err(|payload| panic_message(payload.as_ref()))
+}
+
+/// The message a panic was raised with, if it was a string
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catch_quietly() {
+        let _quiet = QuietPanics::install();
+        assert_eq!(catch_quietly(|| 1), Ok(1));
+        assert_eq!(catch_quietly(|| panic!("bad line {}", 3)), Err::<(), _>("bad line 3".into()));
+        {
+            let _nested = QuietPanics::install();
+            let message = catch_quietly(|| std::panic::panic_any(7)).unwrap_err();
+            assert_eq!(message, "unknown panic");
+        }
+        assert!(!CATCHING.with(Cell::get));
+    }
+}
@@ -0,0 +1,102 @@
+//! Parent/child relations between the messages of a conversation.
+//!
+//! Every message names the message it follows in `parentUuid`. Following these links from a
+//! response back to the session start shows how it was built up: the prompt, the tool calls
+//! and their results in between. Branches (e.g. a retried response) show up as a message with
+//! several children.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::models::ConversationEntry;
+
+/// `parentUuid` links of one conversation, in both directions
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EntryRelations {
+    parents: HashMap<String, String>,
+    /// Children in file order
+    children: HashMap<String, Vec<String>>,
+}
+
+impl EntryRelations {
+    pub fn from_entries(entries: &[ConversationEntry]) -> Self {
+        let mut relations = Self::default();
+        for entry in entries {
+            let Some(parent) = entry.parent_uuid.as_deref().filter(|p| !p.is_empty()) else {
+                continue;
+            };
+            relations.parents.insert(entry.uuid.clone(), parent.to_string());
+            relations.children.entry(parent.to_string()).or_default().push(entry.uuid.clone());
+        }
+        relations
+    }
+
+    pub fn parent(&self, uuid: &str) -> Option<&str> {
+        self.parents.get(uuid).map(String::as_str)
+    }
+
+    pub fn children(&self, uuid: &str) -> &[String] {
+        self.children.get(uuid).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Ancestors of `uuid`, nearest first
+    ///
+    /// Stops at the first repeated uuid, so malformed files with cycles can't loop forever.
+    pub fn ancestors(&self, uuid: &str) -> Vec<&str> {
+        let mut seen = HashSet::from([uuid]);
+        let mut ancestors = Vec::new();
+        let mut current = uuid;
+        while let Some(parent) = self.parent(current) {
+            if !seen.insert(parent) {
+                break;
+            }
+            ancestors.push(parent);
+            current = parent;
+        }
+        ancestors
+    }
+
+    /// Nearest ancestor of `uuid` for which `keep` holds
+    pub fn nearest_ancestor(&self, uuid: &str, keep: impl Fn(&str) -> bool) -> Option<&str> {
+        self.ancestors(uuid).into_iter().find(|ancestor| keep(ancestor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(uuid: &str, parent: Option<&str>) -> ConversationEntry {
+        let parent = parent.map(|p| format!(r#","parentUuid":"{}""#, p)).unwrap_or_default();
+        let line = format!(
+            r#"{{"type":"user","message":{{"role":"user","content":"x"}},"timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"{}"{}}}"#,
+            uuid, parent
+        );
+        serde_json::from_str(&line).unwrap()
+    }
+
+    #[test]
+    fn test_parents_and_children() {
+        let relations = EntryRelations::from_entries(&[
+            entry("a", None),
+            entry("b", Some("a")),
+            entry("c", Some("b")),
+            entry("c2", Some("b")),
+        ]);
+
+        assert_eq!(relations.parent("c"), Some("b"));
+        assert_eq!(relations.parent("a"), None);
+        assert_eq!(relations.children("b"), ["c", "c2"]);
+        assert!(relations.children("c").is_empty());
+        assert_eq!(relations.ancestors("c"), vec!["b", "a"]);
+        assert_eq!(relations.nearest_ancestor("c", |u| u != "b"), Some("a"));
+    }
+
+    #[test]
+    fn test_ancestors_stop_at_cycles() {
+        let relations =
+            EntryRelations::from_entries(&[entry("a", Some("b")), entry("b", Some("a"))]);
+
+        assert_eq!(relations.ancestors("a"), vec!["b"]);
+        assert_eq!(relations.nearest_ancestor("a", |u| u == "x"), None);
+    }
+}
@@ -0,0 +1,264 @@
+//! LRU cache of parsed conversation files.
+//!
+//! Features that need the full source session of an entry (session stats in the preview,
+//! full-conversation copy, context views) would otherwise re-read and re-parse the same
+//! JSONL file every time the selection moves between entries of one session. The cache
+//! keys parsed sessions by file path and modification time, so edits to a file (e.g. an
+//! ongoing conversation) invalidate its cached copy automatically.
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+
+use crate::models::ConversationEntry;
+use crate::parsers::conversation::{ParseOptions, parse_conversation_file_with_options};
+use crate::utils::DEFAULT_MAX_FILE_SIZE_BYTES;
+
+/// Default number of parsed sessions kept in memory
+pub const DEFAULT_SESSION_CACHE_CAPACITY: usize = 16;
+
+struct CachedSession {
+    path: PathBuf,
+    modified: SystemTime,
+    entries: Arc<Vec<ConversationEntry>>,
+}
+
+#[derive(Default)]
+struct CacheInner {
+    // Most recently used at the front
+    sessions: VecDeque<CachedSession>,
+    hits: usize,
+    misses: usize,
+}
+
+/// Thread-safe LRU cache of parsed conversation files keyed by path + mtime
+pub struct SessionCache {
+    capacity: usize,
+    /// Larger files are not parsed
+    max_file_size: u64,
+    inner: Mutex<CacheInner>,
+}
+
+impl SessionCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            max_file_size: DEFAULT_MAX_FILE_SIZE_BYTES,
+            inner: Mutex::new(CacheInner::default()),
+        }
+    }
+
+    /// Parse files of up to `bytes` instead of [`DEFAULT_MAX_FILE_SIZE_BYTES`]
+    pub fn with_max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = bytes;
+        self
+    }
+
+    /// Get the parsed entries of a conversation file, parsing it on a cache miss
+    ///
+    /// A cached copy is only reused if the file's modification time is unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file metadata cannot be read or parsing fails.
+    pub fn get(&self, path: &Path) -> Result<Arc<Vec<ConversationEntry>>> {
+        let modified = std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .with_context(|| format!("Failed to read modification time: {}", path.display()))?;
+
+        {
+            let mut inner = self.lock();
+            if let Some(pos) =
+                inner.sessions.iter().position(|s| s.path == path && s.modified == modified)
+            {
+                let session = inner.sessions.remove(pos).expect("position is in bounds");
+                let entries = Arc::clone(&session.entries);
+                inner.sessions.push_front(session);
+                inner.hits += 1;
+                return Ok(entries);
+            }
+        }
+
+        // Parse outside the lock so other readers aren't blocked on I/O
+        let options = ParseOptions::with_file_size_limit(self.max_file_size);
+        let entries = Arc::new(parse_conversation_file_with_options(path, &options)?.entries);
+
+        let mut inner = self.lock();
+        inner.misses += 1;
+        inner.sessions.retain(|s| s.path != path);
+        inner.sessions.push_front(CachedSession {
+            path: path.to_path_buf(),
+            modified,
+            entries: Arc::clone(&entries),
+        });
+        inner.sessions.truncate(self.capacity);
+
+        Ok(entries)
+    }
+
+    /// Number of sessions currently cached
+    pub fn len(&self) -> usize {
+        self.lock().sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Cache (hits, misses) since creation
+    pub fn stats(&self) -> (usize, usize) {
+        let inner = self.lock();
+        (inner.hits, inner.misses)
+    }
+
+    /// Drop all cached sessions
+    pub fn clear(&self) {
+        self.lock().sessions.clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheInner> {
+        // A panic while holding the lock can't leave the cache inconsistent, so ignore poisoning
+        self.inner.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for SessionCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_SESSION_CACHE_CAPACITY)
+    }
+}
+
+/// Summary statistics of a conversation session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionSummary {
+    pub message_count: usize,
+    pub user_count: usize,
+    pub assistant_count: usize,
+    pub started: Option<DateTime<Utc>>,
+    pub ended: Option<DateTime<Utc>>,
+}
+
+impl SessionSummary {
+    pub fn from_entries(entries: &[ConversationEntry]) -> Self {
+        let user_count = entries.iter().filter(|e| e.message.role == "user").count();
+        let assistant_count = entries.iter().filter(|e| e.message.role == "assistant").count();
+        Self {
+            message_count: entries.len(),
+            user_count,
+            assistant_count,
+            started: entries.iter().map(|e| e.timestamp).min(),
+            ended: entries.iter().map(|e| e.timestamp).max(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::parsers::parse_conversation_file;
+
+    const USER_LINE: &str = r#"{"type":"user","message":{"role":"user","content":"Hello"},"timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u1"}"#;
+    const ASSISTANT_LINE: &str = r#"{"type":"assistant","message":{"role":"assistant","content":"Hi"},"timestamp":1234567895,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u2"}"#;
+
+    fn write_session(dir: &TempDir, name: &str, lines: &[&str]) -> PathBuf {
+        let path = dir.path().join(name);
+        fs::write(&path, lines.join("\n")).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_get_caches_parsed_session() {
+        let dir = TempDir::new().unwrap();
+        let path = write_session(&dir, "agent-1.jsonl", &[USER_LINE, ASSISTANT_LINE]);
+        let cache = SessionCache::new(4);
+
+        let first = cache.get(&path).unwrap();
+        let second = cache.get(&path).unwrap();
+
+        assert_eq!(first.len(), 2);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(cache.stats(), (1, 1));
+    }
+
+    #[test]
+    fn test_get_reparses_after_modification() {
+        let dir = TempDir::new().unwrap();
+        let path = write_session(&dir, "agent-1.jsonl", &[USER_LINE]);
+        let cache = SessionCache::new(4);
+
+        assert_eq!(cache.get(&path).unwrap().len(), 1);
+
+        // Ensure the mtime actually changes on filesystems with coarse resolution
+        sleep(Duration::from_millis(20));
+        fs::write(&path, [USER_LINE, ASSISTANT_LINE].join("\n")).unwrap();
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(1)).unwrap();
+
+        assert_eq!(cache.get(&path).unwrap().len(), 2);
+        assert_eq!(cache.stats(), (0, 2));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let dir = TempDir::new().unwrap();
+        let a = write_session(&dir, "agent-a.jsonl", &[USER_LINE]);
+        let b = write_session(&dir, "agent-b.jsonl", &[USER_LINE]);
+        let c = write_session(&dir, "agent-c.jsonl", &[USER_LINE]);
+        let cache = SessionCache::new(2);
+
+        cache.get(&a).unwrap();
+        cache.get(&b).unwrap();
+        cache.get(&a).unwrap(); // a is now most recently used
+        cache.get(&c).unwrap(); // evicts b
+
+        assert_eq!(cache.len(), 2);
+        cache.get(&a).unwrap();
+        assert_eq!(cache.stats(), (2, 3));
+        cache.get(&b).unwrap();
+        assert_eq!(cache.stats(), (2, 4));
+    }
+
+    #[test]
+    fn test_get_missing_file_errors() {
+        let cache = SessionCache::default();
+        assert!(cache.get(Path::new("/nonexistent/agent.jsonl")).is_err());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_clear() {
+        let dir = TempDir::new().unwrap();
+        let path = write_session(&dir, "agent-1.jsonl", &[USER_LINE]);
+        let cache = SessionCache::default();
+
+        cache.get(&path).unwrap();
+        cache.clear();
+
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_session_summary() {
+        let dir = TempDir::new().unwrap();
+        let path = write_session(&dir, "agent-1.jsonl", &[USER_LINE, ASSISTANT_LINE]);
+        let entries = parse_conversation_file(&path).unwrap();
+
+        let summary = SessionSummary::from_entries(&entries);
+
+        assert_eq!(summary.message_count, 2);
+        assert_eq!(summary.user_count, 1);
+        assert_eq!(summary.assistant_count, 1);
+        assert!(summary.started < summary.ended);
+    }
+}
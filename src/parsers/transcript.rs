@@ -0,0 +1,168 @@
+//! Parser for chat transcripts saved as Markdown.
+//!
+//! Older conversations copied out of chat UIs are often kept as Markdown with one heading per
+//! message:
+//!
+//! ```text
+//! # Rust lifetimes
+//!
+//! ### User
+//!
+//! Why does this borrow outlive the loop?
+//!
+//! ### Assistant
+//!
+//! Because the vector keeps a reference ...
+//! ```
+//!
+//! A heading of any level naming a role starts a message: `User`, `Human`, `You` or `Me` for
+//! prompts and `Assistant`, `AI`, `Claude`, `ChatGPT`, `GPT`, `Bard`, `Gemini`, `Model` or
+//! `Bot` for replies (case-insensitive, with an optional trailing `:`). Text before the first
+//! such heading is ignored, and other headings are part of the message. Transcripts carry no
+//! times, so each file becomes one session ending at the file's modification time, its
+//! messages a second apart in order.
+
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+
+use crate::models::{ContentKinds, EntryType, SearchEntry, SourceKind};
+use crate::utils::safe_open_file;
+
+const USER_ROLES: [&str; 4] = ["user", "human", "you", "me"];
+const ASSISTANT_ROLES: [&str; 9] =
+    ["assistant", "ai", "claude", "chatgpt", "gpt", "bard", "gemini", "model", "bot"];
+
+/// Parse a Markdown transcript into the entries of session `session_id`, oldest first
+///
+/// A file without role headings gives no entries.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or is larger than `max_bytes`.
+pub fn parse_markdown_transcript(
+    path: &Path,
+    session_id: &str,
+    max_bytes: u64,
+) -> Result<Vec<SearchEntry>> {
+    let file = safe_open_file(path, max_bytes)?;
+    let modified = file
+        .metadata()
+        .and_then(|metadata| metadata.modified())
+        .with_context(|| format!("Failed to read the modification time of {}", path.display()))?;
+
+    let mut messages: Vec<(EntryType, Vec<String>)> = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.with_context(|| format!("Failed to read {}", path.display()))?;
+        if let Some(entry_type) = role_heading(&line) {
+            messages.push((entry_type, Vec::new()));
+        } else if let Some((_, lines)) = messages.last_mut() {
+            lines.push(line);
+        }
+    }
+
+    let messages: Vec<(EntryType, String)> = messages
+        .into_iter()
+        .map(|(entry_type, lines)| (entry_type, lines.join("\n").trim().to_string()))
+        .filter(|(_, text)| !text.is_empty())
+        .collect();
+    let end = DateTime::<Utc>::from(modified);
+    let count = messages.len() as i64;
+    Ok(messages
+        .into_iter()
+        .enumerate()
+        .map(|(i, (entry_type, text))| SearchEntry {
+            entry_type,
+            display_text: text,
+            timestamp: end - Duration::seconds(count - 1 - i as i64),
+            project_path: None,
+            session_id: session_id.to_string(),
+            root: None,
+            source_file: None,
+            had_error: false,
+            content_kinds: ContentKinds::empty(),
+            tools: Vec::new(),
+            user: None,
+            model: None,
+            git_branch: None,
+            cwd: None,
+            uuid: None,
+            parent_uuid: None,
+            outcome: None,
+            source: SourceKind::Transcript,
+        })
+        .collect())
+}
+
+/// Role started by a heading line such as `### User` or `## **Assistant:**`
+fn role_heading(line: &str) -> Option<EntryType> {
+    let hashes = line.len() - line.trim_start_matches('#').len();
+    if !(1..=6).contains(&hashes) {
+        return None;
+    }
+    let title = line[hashes..].strip_prefix(' ')?;
+    let role = title.trim().trim_matches('*').trim_end_matches(':').trim().to_ascii_lowercase();
+    if USER_ROLES.contains(&role.as_str()) {
+        Some(EntryType::UserPrompt)
+    } else if ASSISTANT_ROLES.contains(&role.as_str()) {
+        Some(EntryType::AgentMessage)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::DEFAULT_MAX_FILE_SIZE_BYTES;
+
+    #[test]
+    fn test_role_heading() {
+        assert_eq!(role_heading("### User"), Some(EntryType::UserPrompt));
+        assert_eq!(role_heading("## **Assistant:**"), Some(EntryType::AgentMessage));
+        assert_eq!(role_heading("# ChatGPT"), Some(EntryType::AgentMessage));
+        assert_eq!(role_heading("### Users"), None);
+        assert_eq!(role_heading("###User"), None);
+        assert_eq!(role_heading("User"), None);
+    }
+
+    #[test]
+    fn test_parses_messages_ending_at_modification_time() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("lifetimes.md");
+        std::fs::write(
+            &path,
+            "# Rust lifetimes\n\nSaved from the browser.\n\n\
+             ### User\n\nWhy does this borrow outlive the loop?\n\n\
+             ### Assistant\n\nBecause the vector keeps a reference.\n\n#### Fix\n\nClone it.\n\n\
+             ### User\n\n\
+             ### Human:\nThanks!\n",
+        )
+        .unwrap();
+
+        let entries =
+            parse_markdown_transcript(&path, "transcript-lifetimes", DEFAULT_MAX_FILE_SIZE_BYTES)
+                .unwrap();
+        let texts: Vec<_> =
+            entries.iter().map(|e| (e.entry_type.clone(), e.display_text.as_str())).collect();
+        assert_eq!(
+            texts,
+            vec![
+                (EntryType::UserPrompt, "Why does this borrow outlive the loop?"),
+                (
+                    EntryType::AgentMessage,
+                    "Because the vector keeps a reference.\n\n#### Fix\n\nClone it."
+                ),
+                (EntryType::UserPrompt, "Thanks!"),
+            ]
+        );
+
+        let modified = DateTime::<Utc>::from(std::fs::metadata(&path).unwrap().modified().unwrap());
+        assert_eq!(entries[2].timestamp, modified);
+        assert_eq!(entries[0].timestamp, modified - Duration::seconds(2));
+        assert!(entries.iter().all(|e| e.session_id == "transcript-lifetimes"));
+        assert!(entries.iter().all(|e| e.source == SourceKind::Transcript));
+    }
+}
@@ -0,0 +1,151 @@
+//! Resuming conversations in Claude Code.
+//!
+//! `claude --resume <session-id>` continues a conversation, but only from the directory it
+//! was started in: Claude Code keeps the sessions of each project apart. A [`ResumeTarget`]
+//! pairs a session with that directory, for the `resume` command and the TUI.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use anyhow::{Context, Result, anyhow, bail};
+use uuid::Uuid;
+
+use crate::models::{SearchEntry, SourceKind};
+
+/// Claude Code executable, looked up on `PATH`
+pub const CLAUDE_PROGRAM: &str = "claude";
+
+/// A Claude Code session and the directory to resume it from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResumeTarget {
+    pub session_id: String,
+    pub project: PathBuf,
+}
+
+impl ResumeTarget {
+    /// Target for the session `entry` belongs to
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entry wasn't recorded by Claude Code, has no project, or
+    /// belongs to a session written without an id (whose fallback id Claude Code doesn't
+    /// know).
+    pub fn for_entry(entry: &SearchEntry) -> Result<Self> {
+        if entry.source != SourceKind::ClaudeCode {
+            bail!(
+                "Only Claude Code sessions can be resumed (this is a {} entry)",
+                entry.source.label()
+            );
+        }
+        // Fallback ids of entries written without one are version 8 (see `fallback_session_id`)
+        if Uuid::parse_str(&entry.session_id).map_or(true, |id| id.get_version_num() == 8) {
+            bail!("Session '{}' was not recorded with an id Claude Code knows", entry.session_id);
+        }
+        let project = entry
+            .project_path
+            .clone()
+            .ok_or_else(|| anyhow!("Session '{}' has no project directory", entry.session_id))?;
+        Ok(Self { session_id: entry.session_id.clone(), project })
+    }
+
+    /// Target for session `session_id` of `index`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session is not in the index, or as [`for_entry`](Self::for_entry)
+    /// for its entries.
+    pub fn find(index: &[SearchEntry], session_id: &str) -> Result<Self> {
+        let entry = index
+            .iter()
+            .filter(|e| e.session_id == session_id)
+            .min_by_key(|e| e.project_path.is_none())
+            .ok_or_else(|| {
+                anyhow!("No session '{}' in the history (see `list --output jsonl`)", session_id)
+            })?;
+        Self::for_entry(entry)
+    }
+
+    /// `claude --resume <session-id>`, run in the project directory
+    pub fn command(&self) -> Command {
+        let mut command = Command::new(CLAUDE_PROGRAM);
+        command.arg("--resume").arg(&self.session_id).current_dir(&self.project);
+        command
+    }
+
+    /// Resume the session, waiting until Claude Code exits
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project directory no longer exists, if `claude` can't be
+    /// started, or if it exits unsuccessfully.
+    pub fn run(&self) -> Result<()> {
+        if !self.project.is_dir() {
+            bail!("Project directory {} no longer exists", self.project.display());
+        }
+        let status = self.command().status().with_context(|| {
+            format!("Failed to run `{}` (is Claude Code installed and on PATH?)", CLAUDE_PROGRAM)
+        })?;
+        if !status.success() {
+            bail!("`{} --resume` exited with {}", CLAUDE_PROGRAM, status);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+
+    use super::*;
+    use crate::models::EntryType;
+    use crate::parsers::deserializers::fallback_session_id;
+
+    const SESSION: &str = "550e8400-e29b-41d4-a716-446655440000";
+
+    fn entry(session: &str, project: Option<&str>, source: SourceKind) -> SearchEntry {
+        SearchEntry {
+            entry_type: EntryType::UserPrompt,
+            display_text: "Fix the login form".to_string(),
+            timestamp: DateTime::from_timestamp(1_704_099_600, 0).unwrap(),
+            project_path: project.map(PathBuf::from),
+            session_id: session.to_string(),
+            source,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_for_entry_needs_a_claude_code_session_with_project() {
+        let target =
+            ResumeTarget::for_entry(&entry(SESSION, Some("/work/web"), SourceKind::ClaudeCode))
+                .unwrap();
+        assert_eq!(target.project, PathBuf::from("/work/web"));
+
+        let command = target.command();
+        assert_eq!(command.get_program(), CLAUDE_PROGRAM);
+        assert_eq!(command.get_args().collect::<Vec<_>>(), ["--resume", SESSION]);
+        assert_eq!(command.get_current_dir(), Some(std::path::Path::new("/work/web")));
+
+        let codex = entry(SESSION, Some("/work/web"), SourceKind::Codex);
+        assert!(ResumeTarget::for_entry(&codex).unwrap_err().to_string().contains("codex"));
+        assert!(ResumeTarget::for_entry(&entry(SESSION, None, SourceKind::ClaudeCode)).is_err());
+        let fallback = fallback_session_id(Some("/work/web"), DateTime::UNIX_EPOCH);
+        let normalized = entry(&fallback, Some("/work/web"), SourceKind::ClaudeCode);
+        assert!(ResumeTarget::for_entry(&normalized).is_err());
+    }
+
+    #[test]
+    fn test_find_uses_any_entry_with_a_project() {
+        let index = vec![
+            entry(SESSION, None, SourceKind::ClaudeCode),
+            entry(SESSION, Some("/work/web"), SourceKind::ClaudeCode),
+        ];
+        assert_eq!(
+            ResumeTarget::find(&index, SESSION).unwrap().project,
+            PathBuf::from("/work/web")
+        );
+
+        let err = ResumeTarget::find(&index, "550e8400-e29b-41d4-a716-446655440001").unwrap_err();
+        assert!(err.to_string().contains("No session"));
+    }
+}
@@ -0,0 +1,322 @@
+//! Persistent tantivy index of the search entries.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use tantivy::collector::TopDocs;
+use tantivy::directory::MmapDirectory;
+use tantivy::query::QueryParser;
+use tantivy::schema::{FAST, Field, INDEXED, Schema, TEXT};
+use tantivy::{Index, IndexWriter, Searcher, TantivyDocument, Term};
+
+use super::{IndexUpdate, SearchHit, entry_key};
+use crate::cache::CacheDir;
+use crate::indexer::Diagnostic;
+use crate::models::SearchEntry;
+use crate::utils::create_private_dir_all;
+
+/// Name of the index directory; the version is bumped whenever the schema changes
+pub const FULLTEXT_DIR_NAME: &str = "fulltext-v1";
+
+/// Memory the index writer may use before flushing a segment
+const WRITER_MEMORY_BYTES: usize = 64 * 1024 * 1024;
+
+const KEY_FIELD: &str = "key";
+const TEXT_FIELD: &str = "text";
+const PROJECT_FIELD: &str = "project";
+
+/// Full-text index of search entries, on disk or in memory
+pub struct FullTextIndex {
+    index: Index,
+    key: Field,
+    text: Field,
+    project: Field,
+    /// Position of each entry of the last sync, by key
+    positions: HashMap<u64, usize>,
+    /// Why the index on disk was rebuilt when opening, until reported
+    load_problems: Vec<Diagnostic>,
+}
+
+impl FullTextIndex {
+    /// Open the index in the cache directory, or in memory if the cache isn't persisted
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index directory cannot be created or opened.
+    pub fn open(dir: &CacheDir) -> Result<Self> {
+        match dir.location.path() {
+            Some(path) => Self::open_in(&path.join(FULLTEXT_DIR_NAME)),
+            None => Ok(Self::in_memory()),
+        }
+    }
+
+    /// Open the index in `path`, creating it (owner-only) if needed
+    ///
+    /// An index that can't be opened, e.g. after a crash mid-write, is deleted and rebuilt;
+    /// why is kept for [`take_load_problems`](Self::take_load_problems).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created or the index cannot be rebuilt.
+    pub fn open_in(path: &Path) -> Result<Self> {
+        create_private_dir_all(path)?;
+        let opened = MmapDirectory::open(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|dir| Ok(Index::open_or_create(dir, schema())?));
+        let mut load_problems = Vec::new();
+        let index = match opened {
+            Ok(index) => index,
+            Err(e) => {
+                load_problems.push(Diagnostic {
+                    path: path.to_path_buf(),
+                    message: format!("Rebuilding corrupt full-text index: {:#}", e),
+                });
+                fs::remove_dir_all(path)
+                    .with_context(|| format!("Failed to remove {}", path.display()))?;
+                create_private_dir_all(path)?;
+                Index::create_in_dir(path, schema())
+                    .with_context(|| format!("Failed to create {}", path.display()))?
+            }
+        };
+        Ok(Self { load_problems, ..Self::with_index(index)? })
+    }
+
+    /// Why the index on disk was rebuilt when opening, if it was; returned once
+    pub fn take_load_problems(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.load_problems)
+    }
+
+    /// Index that is never persisted
+    pub fn in_memory() -> Self {
+        Self::with_index(Index::create_in_ram(schema())).expect("schema has all fields")
+    }
+
+    fn with_index(index: Index) -> Result<Self> {
+        let schema = index.schema();
+        Ok(Self {
+            key: schema.get_field(KEY_FIELD)?,
+            text: schema.get_field(TEXT_FIELD)?,
+            project: schema.get_field(PROJECT_FIELD)?,
+            index,
+            positions: HashMap::new(),
+            load_problems: Vec::new(),
+        })
+    }
+
+    /// Bring the index in line with `entries`, writing only what changed since the last sync
+    ///
+    /// Hits of later searches refer to positions in `entries`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index cannot be read or written, e.g. while another process
+    /// is updating it.
+    pub fn sync(&mut self, entries: &[SearchEntry]) -> Result<IndexUpdate> {
+        let mut positions = HashMap::with_capacity(entries.len());
+        for (i, entry) in entries.iter().enumerate() {
+            positions.entry(entry_key(entry)).or_insert(i);
+        }
+
+        let stored = self.stored_keys()?;
+        let removed: Vec<u64> =
+            stored.iter().copied().filter(|key| !positions.contains_key(key)).collect();
+        let added: Vec<(u64, usize)> = positions
+            .iter()
+            .filter(|(key, _)| !stored.contains(key))
+            .map(|(&key, &i)| (key, i))
+            .collect();
+
+        if !removed.is_empty() || !added.is_empty() {
+            let mut writer: IndexWriter = self
+                .index
+                .writer(WRITER_MEMORY_BYTES)
+                .context("Failed to lock the full-text index for writing")?;
+            for &key in &removed {
+                writer.delete_term(Term::from_field_u64(self.key, key));
+            }
+            for &(key, i) in &added {
+                writer.add_document(self.document(key, &entries[i]))?;
+            }
+            writer.commit().context("Failed to write the full-text index")?;
+        }
+
+        self.positions = positions;
+        Ok(IndexUpdate { added: added.len(), removed: removed.len() })
+    }
+
+    /// Number of indexed entries
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index cannot be read.
+    pub fn num_entries(&self) -> Result<u64> {
+        Ok(self.searcher()?.num_docs())
+    }
+
+    /// Best matches of `query`, most relevant first
+    ///
+    /// Terms are searched in the entry text and must all match unless joined with `OR`;
+    /// `project:<word>` searches the project path instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query is invalid or the index cannot be read.
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let mut parser = QueryParser::for_index(&self.index, vec![self.text]);
+        parser.set_conjunction_by_default();
+        let parsed =
+            parser.parse_query(query).with_context(|| format!("Invalid query '{}'", query))?;
+
+        let searcher = self.searcher()?;
+        let mut hits = Vec::new();
+        for (score, address) in searcher.search(&parsed, &TopDocs::with_limit(limit))? {
+            let keys = searcher.segment_reader(address.segment_ord).fast_fields().u64(KEY_FIELD)?;
+            // Hits are only mapped for entries of the last sync
+            if let Some(&index) = keys.first(address.doc_id).and_then(|k| self.positions.get(&k)) {
+                hits.push(SearchHit { index, score });
+            }
+        }
+        Ok(hits)
+    }
+
+    fn searcher(&self) -> Result<Searcher> {
+        Ok(self.index.reader().context("Failed to read the full-text index")?.searcher())
+    }
+
+    /// Keys of all entries currently in the index
+    fn stored_keys(&self) -> Result<HashSet<u64>> {
+        let searcher = self.searcher()?;
+        let mut keys = HashSet::new();
+        for segment in searcher.segment_readers() {
+            let column = segment.fast_fields().u64(KEY_FIELD)?;
+            keys.extend(segment.doc_ids_alive().filter_map(|doc| column.first(doc)));
+        }
+        Ok(keys)
+    }
+
+    fn document(&self, key: u64, entry: &SearchEntry) -> TantivyDocument {
+        let mut document = TantivyDocument::default();
+        document.add_u64(self.key, key);
+        document.add_text(self.text, &entry.display_text);
+        if let Some(project) = &entry.project_path {
+            document.add_text(self.project, project.to_string_lossy());
+        }
+        document
+    }
+}
+
+fn schema() -> Schema {
+    let mut builder = Schema::builder();
+    builder.add_u64_field(KEY_FIELD, INDEXED | FAST);
+    builder.add_text_field(TEXT_FIELD, TEXT);
+    builder.add_text_field(PROJECT_FIELD, TEXT);
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use chrono::DateTime;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::models::EntryType;
+
+    fn entry(text: &str, project: &str, secs: i64) -> SearchEntry {
+        SearchEntry {
+            entry_type: EntryType::UserPrompt,
+            display_text: text.to_string(),
+            timestamp: DateTime::from_timestamp(secs, 0).unwrap(),
+            project_path: Some(PathBuf::from(project)),
+            session_id: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn entries() -> Vec<SearchEntry> {
+        vec![
+            entry("Fix the connection pool timeout", "/work/api", 100),
+            entry("Pool the connection handles", "/work/api", 200),
+            entry("Add a retry to the connection", "/work/web", 300),
+        ]
+    }
+
+    fn indices(hits: &[SearchHit]) -> Vec<usize> {
+        let mut indices: Vec<usize> = hits.iter().map(|hit| hit.index).collect();
+        indices.sort_unstable();
+        indices
+    }
+
+    #[test]
+    fn test_phrase_and_boolean_queries() {
+        let mut index = FullTextIndex::in_memory();
+        index.sync(&entries()).unwrap();
+
+        assert_eq!(indices(&index.search("connection pool", 10).unwrap()), vec![0, 1]);
+        assert_eq!(indices(&index.search("\"connection pool\"", 10).unwrap()), vec![0]);
+        assert_eq!(indices(&index.search("connection NOT pool", 10).unwrap()), vec![2]);
+        assert_eq!(indices(&index.search("retry OR timeout", 10).unwrap()), vec![0, 2]);
+        assert_eq!(indices(&index.search("connection project:web", 10).unwrap()), vec![2]);
+        assert!(index.search("connection", 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_ranks_better_matches_first() {
+        let mut index = FullTextIndex::in_memory();
+        let entries = vec![
+            entry("retry once after the long setup of the database fixtures", "/a", 100),
+            entry("retry retry retry", "/a", 200),
+        ];
+        index.sync(&entries).unwrap();
+
+        let hits = index.search("retry", 10).unwrap();
+        assert_eq!(hits[0].index, 1);
+        assert!(hits[0].score > hits[1].score);
+    }
+
+    #[test]
+    fn test_invalid_query_is_an_error() {
+        let index = FullTextIndex::in_memory();
+        let err = format!("{:#}", index.search("nosuchfield:x", 10).unwrap_err());
+        assert!(err.contains("Invalid query 'nosuchfield:x'"));
+    }
+
+    #[test]
+    fn test_sync_writes_only_changes_and_persists() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(FULLTEXT_DIR_NAME);
+        let mut entries = entries();
+
+        let mut index = FullTextIndex::open_in(&path).unwrap();
+        assert_eq!(index.sync(&entries).unwrap(), IndexUpdate { added: 3, removed: 0 });
+        drop(index);
+
+        entries.remove(0);
+        entries.push(entry("Document the retry policy", "/work/web", 400));
+        let mut reopened = FullTextIndex::open_in(&path).unwrap();
+        assert_eq!(reopened.sync(&entries).unwrap(), IndexUpdate { added: 1, removed: 1 });
+        assert_eq!(reopened.num_entries().unwrap(), 3);
+        assert_eq!(indices(&reopened.search("retry", 10).unwrap()), vec![1, 2]);
+        assert_eq!(reopened.sync(&entries).unwrap(), IndexUpdate::default());
+    }
+
+    #[test]
+    fn test_corrupt_index_is_rebuilt() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(FULLTEXT_DIR_NAME);
+        create_private_dir_all(&path).unwrap();
+        fs::write(path.join("meta.json"), "not json").unwrap();
+
+        let mut index = FullTextIndex::open_in(&path).unwrap();
+        let problems = index.take_load_problems();
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].message.starts_with("Rebuilding corrupt full-text index"));
+        assert_eq!(index.sync(&entries()).unwrap().added, 3);
+    }
+}
@@ -0,0 +1,71 @@
+//! Fuzzy matching of entry texts outside the TUI.
+//!
+//! Scores entries the way the TUI's results list does (nucleo with smart case and
+//! normalization, the same query syntax), so `search --fuzzy` finds what typing the query into
+//! the TUI would.
+
+use nucleo::pattern::{CaseMatching, Normalization, Pattern};
+use nucleo::{Matcher, Utf32Str};
+
+use super::SearchHit;
+use crate::models::SearchEntry;
+
+/// Entries whose text matches `query`, best first
+///
+/// Entries with the same score keep their order in `entries`. An empty query matches every
+/// entry with a score of 0.
+pub fn fuzzy_search(entries: &[SearchEntry], query: &str) -> Vec<SearchHit> {
+    let pattern = Pattern::parse(query, CaseMatching::Smart, Normalization::Smart);
+    let mut matcher = Matcher::default();
+    let mut buf = Vec::new();
+    let mut hits: Vec<SearchHit> = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(index, entry)| {
+            let haystack = Utf32Str::new(&entry.display_text, &mut buf);
+            let score = pattern.score(haystack, &mut matcher)?;
+            Some(SearchHit { index, score: score as f32 })
+        })
+        .collect();
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+    use crate::models::EntryType;
+
+    fn entry(text: &str) -> SearchEntry {
+        SearchEntry {
+            entry_type: EntryType::UserPrompt,
+            display_text: text.to_string(),
+            timestamp: Utc.timestamp_opt(1_700_000_000, 0).unwrap(),
+            session_id: "s1".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_search_ranks_like_the_tui() {
+        let entries = [
+            entry("update the changelog"),
+            entry("fix the flaky retry test"),
+            entry("Retry failed uploads"),
+        ];
+
+        let hits = fuzzy_search(&entries, "retry");
+        let texts: Vec<&str> =
+            hits.iter().map(|hit| entries[hit.index].display_text.as_str()).collect();
+        assert_eq!(texts.len(), 2);
+        assert!(texts.contains(&"Retry failed uploads"));
+
+        // Smart case: an uppercase letter makes the query case-sensitive
+        assert_eq!(fuzzy_search(&entries, "Retry").len(), 1);
+        // nucleo's syntax: `!` excludes
+        assert_eq!(fuzzy_search(&entries, "retry !flaky").len(), 1);
+        assert_eq!(fuzzy_search(&entries, "").len(), 3);
+    }
+}
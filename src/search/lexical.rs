@@ -0,0 +1,309 @@
+//! Lexical similarity search over hashed word vectors of the search entries.
+//!
+//! This is not semantic search: there is no language model, so texts are only close when they
+//! share spelling. Each word and each character trigram of it (`<retry>` → `<re`, `ret`,
+//! `etr`, ...) is hashed into one of [`VECTOR_DIM`] signed buckets and the vector is
+//! normalized. Texts sharing words or word stems (`retry`, `retrying`, `retries`) end up close,
+//! even when a fuzzy or full-text query for one spelling misses the others; synonyms
+//! (`retry` and `backoff`) do not.
+//!
+//! Vectors are quantized to one byte per dimension and stored with bincode next to the index
+//! cache, so only new entries are vectorized on the next run.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::{IndexUpdate, SearchHit, entry_key};
+use crate::cache::CacheDir;
+use crate::indexer::Diagnostic;
+use crate::models::SearchEntry;
+
+/// Name of the vectors file; the version is bumped whenever the vectorization changes
+pub const VECTORS_FILE_NAME: &str = "lexical-vectors-v1.bin";
+
+/// Number of dimensions of a word vector
+pub const VECTOR_DIM: usize = 256;
+
+/// Weight of a character trigram relative to its whole word
+const TRIGRAM_WEIGHT: f32 = 0.5;
+
+/// Hits less similar than this are left out
+const MIN_SCORE: f32 = 0.1;
+
+/// Words too common to say anything about a prompt
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "can", "do", "for", "from", "i", "if", "in",
+    "is", "it", "me", "my", "of", "on", "or", "so", "that", "the", "this", "to", "we", "with",
+    "you",
+];
+
+/// A normalized word vector, quantized to `i8` (1.0 is stored as 127)
+type Vector = Vec<i8>;
+
+/// Word vectors of search entries, on disk or in memory
+pub struct LexicalIndex {
+    /// File the vectors are saved to (`None`: never persisted)
+    path: Option<PathBuf>,
+    vectors: HashMap<u64, Vector>,
+    /// Position of each entry of the last sync, by key
+    positions: HashMap<u64, usize>,
+    /// Why the saved vectors were ignored when opening, until reported
+    load_problems: Vec<Diagnostic>,
+}
+
+impl LexicalIndex {
+    /// Open the vectors in the cache directory, or in memory if the cache isn't persisted
+    pub fn open(dir: &CacheDir) -> Self {
+        match dir.location.path() {
+            Some(path) => Self::open_in(&path.join(VECTORS_FILE_NAME)),
+            None => Self::in_memory(),
+        }
+    }
+
+    /// Open the vectors saved in `path`
+    ///
+    /// A missing or corrupt file starts an empty index; the vectors are then recomputed. Why
+    /// a corrupt file was ignored is kept for [`take_load_problems`](Self::take_load_problems).
+    pub fn open_in(path: &Path) -> Self {
+        let mut load_problems = Vec::new();
+        let vectors = load(path).unwrap_or_else(|e| {
+            let message = format!("{:#}; recomputing the vectors", e);
+            load_problems.push(Diagnostic { path: path.to_path_buf(), message });
+            HashMap::new()
+        });
+        Self { path: Some(path.to_path_buf()), vectors, positions: HashMap::new(), load_problems }
+    }
+
+    /// Index that is never persisted
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            vectors: HashMap::new(),
+            positions: HashMap::new(),
+            load_problems: Vec::new(),
+        }
+    }
+
+    /// Why the saved vectors were ignored when opening, if they were; returned once
+    pub fn take_load_problems(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.load_problems)
+    }
+
+    /// Bring the vectors in line with `entries`, vectorizing only entries not seen before
+    ///
+    /// Hits of later searches refer to positions in `entries`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the changed vectors cannot be saved.
+    pub fn sync(&mut self, entries: &[SearchEntry]) -> Result<IndexUpdate> {
+        let mut positions = HashMap::with_capacity(entries.len());
+        for (i, entry) in entries.iter().enumerate() {
+            positions.entry(entry_key(entry)).or_insert(i);
+        }
+
+        let before = self.vectors.len();
+        self.vectors.retain(|key, _| positions.contains_key(key));
+        let removed = before - self.vectors.len();
+        let mut added = 0;
+        for (&key, &i) in &positions {
+            if let Entry::Vacant(slot) = self.vectors.entry(key) {
+                slot.insert(quantize(&word_vector(&entries[i].display_text)));
+                added += 1;
+            }
+        }
+
+        self.positions = positions;
+        if (added > 0 || removed > 0)
+            && let Some(path) = &self.path
+        {
+            store(path, &self.vectors)?;
+        }
+        Ok(IndexUpdate { added, removed })
+    }
+
+    /// Number of vectorized entries
+    pub fn num_entries(&self) -> usize {
+        self.vectors.len()
+    }
+
+    /// Entries sharing the most words and word parts with `query`, most similar first
+    ///
+    /// The score is the cosine similarity of the word vectors (0 to 1 in practice).
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchHit> {
+        let query = quantize(&word_vector(query));
+        let mut hits: Vec<SearchHit> = self
+            .positions
+            .iter()
+            .filter_map(|(key, &index)| {
+                let score = similarity(&query, self.vectors.get(key)?);
+                (score >= MIN_SCORE).then_some(SearchHit { index, score })
+            })
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.index.cmp(&b.index)));
+        hits.truncate(limit);
+        hits
+    }
+}
+
+/// Hash the words of a text into a unit vector of [`VECTOR_DIM`] dimensions (all zeros without words)
+pub fn word_vector(text: &str) -> Vec<f32> {
+    let mut vector = vec![0.0; VECTOR_DIM];
+    let stopwords: HashSet<&str> = STOPWORDS.iter().copied().collect();
+    let lowercase = text.to_lowercase();
+    let words = lowercase
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty() && !stopwords.contains(word));
+    for word in words {
+        add_feature(&mut vector, word.as_bytes(), 1.0);
+        let padded: Vec<char> = format!("<{}>", word).chars().collect();
+        for trigram in padded.windows(3) {
+            let trigram: String = trigram.iter().collect();
+            add_feature(&mut vector, trigram.as_bytes(), TRIGRAM_WEIGHT);
+        }
+    }
+
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+    vector
+}
+
+/// Add `weight` to the bucket of `feature`, with a sign from the hash so collisions cancel out
+/// on average
+fn add_feature(vector: &mut [f32], feature: &[u8], weight: f32) {
+    let hash = fnv1a(feature);
+    let bucket = (hash % VECTOR_DIM as u64) as usize;
+    vector[bucket] += if hash >> 63 == 0 { weight } else { -weight };
+}
+
+/// FNV-1a: unlike `DefaultHasher`, stable across builds, so saved vectors stay comparable
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+fn quantize(vector: &[f32]) -> Vector {
+    vector.iter().map(|v| (v * 127.0).round().clamp(-127.0, 127.0) as i8).collect()
+}
+
+/// Cosine similarity of two quantized unit vectors
+fn similarity(a: &[i8], b: &[i8]) -> f32 {
+    let dot: i32 = a.iter().zip(b).map(|(&x, &y)| i32::from(x) * i32::from(y)).sum();
+    dot as f32 / (127.0 * 127.0)
+}
+
+/// Vectors saved in `path`; none for a missing file or one of another dimension
+fn load(path: &Path) -> Result<HashMap<u64, Vector>> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(anyhow::anyhow!("Failed to read word vectors: {}", e)),
+    };
+    match bincode::deserialize::<StoredVectors>(&bytes) {
+        Ok(stored) if stored.dim == VECTOR_DIM => Ok(stored.vectors),
+        Ok(_) => Ok(HashMap::new()),
+        Err(e) => Err(anyhow::anyhow!("Corrupt word vectors: {}", e)),
+    }
+}
+
+/// Write via a temporary file and rename, so readers never see partial vectors
+fn store(path: &Path, vectors: &HashMap<u64, Vector>) -> Result<()> {
+    #[derive(Serialize)]
+    struct StoredVectorsRef<'a> {
+        dim: usize,
+        vectors: &'a HashMap<u64, Vector>,
+    }
+
+    let bytes = bincode::serialize(&StoredVectorsRef { dim: VECTOR_DIM, vectors })
+        .context("Failed to serialize word vectors")?;
+    let temp = path.with_extension("bin.tmp");
+    crate::utils::write_private_file(&temp, bytes)?;
+    fs::rename(&temp, path)
+        .with_context(|| format!("Failed to write word vectors {}", path.display()))
+}
+
+#[derive(Deserialize)]
+struct StoredVectors {
+    dim: usize,
+    vectors: HashMap<u64, Vector>,
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::models::EntryType;
+
+    fn entry(text: &str, secs: i64) -> SearchEntry {
+        SearchEntry {
+            entry_type: EntryType::UserPrompt,
+            display_text: text.to_string(),
+            timestamp: DateTime::from_timestamp(secs, 0).unwrap(),
+            session_id: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn entries() -> Vec<SearchEntry> {
+        vec![
+            entry("Style the navigation sidebar with CSS grid", 100),
+            entry("The HTTP client should retry failed requests", 200),
+            entry("Add retries with backoff to the flaky requests", 300),
+        ]
+    }
+
+    #[test]
+    fn test_word_vector_is_normalized() {
+        let norm: f32 = word_vector("Retry the request").iter().map(|v| v * v).sum();
+        assert!((norm - 1.0).abs() < 1e-5);
+        assert!(word_vector("the of a").iter().all(|v| *v == 0.0));
+        assert_eq!(word_vector("Retry!"), word_vector("retry"));
+    }
+
+    #[test]
+    fn test_finds_related_word_forms() {
+        let mut index = LexicalIndex::in_memory();
+        index.sync(&entries()).unwrap();
+
+        let hits = index.search("retrying requests", 10);
+        let indices: Vec<usize> = hits.iter().map(|hit| hit.index).collect();
+        assert_eq!(&indices[..2], &[1, 2]);
+        assert!(!indices.contains(&0));
+        assert!(hits[0].score >= hits[1].score);
+        assert_eq!(index.search("retry", 1).len(), 1);
+    }
+
+    #[test]
+    fn test_sync_vectorizes_only_changes_and_persists() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(VECTORS_FILE_NAME);
+        let mut entries = entries();
+
+        let mut index = LexicalIndex::open_in(&path);
+        assert_eq!(index.sync(&entries).unwrap(), IndexUpdate { added: 3, removed: 0 });
+
+        entries.remove(0);
+        entries.push(entry("Document the retry policy", 400));
+        let mut reopened = LexicalIndex::open_in(&path);
+        assert_eq!(reopened.num_entries(), 3);
+        assert_eq!(reopened.sync(&entries).unwrap(), IndexUpdate { added: 1, removed: 1 });
+        assert_eq!(reopened.search("retry policy", 1)[0].index, 2);
+
+        assert!(reopened.take_load_problems().is_empty());
+
+        fs::write(&path, b"garbage").unwrap();
+        let mut corrupt = LexicalIndex::open_in(&path);
+        assert_eq!(corrupt.num_entries(), 0);
+        assert_eq!(corrupt.take_load_problems()[0].path, path);
+    }
+}
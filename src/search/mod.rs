@@ -0,0 +1,81 @@
+//! Full-text, lexical similarity, semantic and fuzzy search over the indexed entries.
+//!
+//! The fuzzy matcher scores every entry for every query, which gets slow on histories with
+//! hundreds of thousands of entries. [`FullTextIndex`] keeps a tantivy inverted index of the
+//! entries instead, with phrase queries (`"connection pool"`), boolean operators (`AND`, `OR`,
+//! `NOT`, `+term`, `-term`) and BM25 ranking.
+//!
+//! [`LexicalIndex`] ranks entries by how many words and word parts they share with the query,
+//! which also finds prompts that use other forms of the query's words (but not synonyms).
+//! [`SemanticIndex`] ranks them by the distance of sentence embeddings from a local language
+//! model, so it finds prompts about the same thing in other words.
+//!
+//! These indexes are persisted next to the index cache (see [`crate::cache`]) and updated
+//! incrementally: each run only processes the entries added or removed since the last one.
+//!
+//! [`fuzzy_search`] scores entries with the TUI's fuzzy matcher and keeps no index, for
+//! `search --fuzzy` from scripts and over SSH.
+//!
+//! Requires the `fulltext`, `similar` or `semantic` feature, or `tui` for fuzzy search.
+
+#[cfg(any(feature = "fulltext", feature = "similar", feature = "semantic"))]
+use std::collections::hash_map::DefaultHasher;
+#[cfg(any(feature = "fulltext", feature = "similar", feature = "semantic"))]
+use std::hash::{Hash, Hasher};
+
+#[cfg(any(feature = "fulltext", feature = "similar", feature = "semantic"))]
+use crate::models::SearchEntry;
+
+#[cfg(feature = "fulltext")]
+pub mod fulltext;
+#[cfg(feature = "tui")]
+pub mod fuzzy;
+#[cfg(feature = "similar")]
+pub mod lexical;
+#[cfg(feature = "semantic")]
+pub mod semantic;
+
+#[cfg(feature = "fulltext")]
+pub use fulltext::{FULLTEXT_DIR_NAME, FullTextIndex};
+#[cfg(feature = "tui")]
+pub use fuzzy::fuzzy_search;
+#[cfg(feature = "similar")]
+pub use lexical::{LexicalIndex, VECTORS_FILE_NAME, word_vector};
+#[cfg(feature = "semantic")]
+pub use semantic::{BertEmbedder, Embedder, SemanticIndex};
+
+/// One ranked search result
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchHit {
+    /// Position of the entry in the slice last given to `sync`
+    pub index: usize,
+    /// Relevance (BM25, cosine similarity or fuzzy score); higher is better
+    pub score: f32,
+}
+
+/// What `sync` changed
+#[cfg(any(feature = "fulltext", feature = "similar", feature = "semantic"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IndexUpdate {
+    pub added: usize,
+    pub removed: usize,
+}
+
+/// Identity of an entry across runs
+///
+/// `DefaultHasher::new` is unkeyed, so keys are stable between runs of the same build. A
+/// different build at worst re-adds every entry once.
+#[cfg(any(feature = "fulltext", feature = "similar", feature = "semantic"))]
+fn entry_key(entry: &SearchEntry) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (
+        entry.root.as_deref(),
+        &entry.session_id,
+        entry.timestamp,
+        entry.uuid.as_deref(),
+        &entry.project_path,
+        &entry.display_text,
+    )
+        .hash(&mut hasher);
+    hasher.finish()
+}
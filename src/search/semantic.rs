@@ -0,0 +1,534 @@
+//! Semantic search: nearest neighbours of the query among sentence embeddings of the entries.
+//!
+//! The embeddings come from a BERT sentence-embedding model run in-process with candle, on
+//! the CPU and without a server: by default `sentence-transformers/all-MiniLM-L6-v2`,
+//! downloaded once into the Hugging Face cache, or the model or local directory named by
+//! `embedding_model` in the config. The token embeddings of a text are averaged and
+//! normalized, so texts about the same thing end up close even when they share no word
+//! (`retry` and `backoff`), which [`LexicalIndex`](super::LexicalIndex) cannot do.
+//!
+//! Vectors are quantized to one byte per dimension and stored with bincode next to the index
+//! cache, together with the model that computed them, so only new entries are embedded on
+//! the next run and switching models embeds everything again.
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use candle_core::{DType, Device, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::bert::{BertModel, Config as BertConfig, DTYPE};
+use serde::{Deserialize, Serialize};
+use tokenizers::{PaddingParams, Tokenizer, TruncationParams};
+
+use super::{IndexUpdate, SearchHit, entry_key};
+use crate::cache::CacheDir;
+use crate::indexer::Diagnostic;
+use crate::models::SearchEntry;
+
+/// Name of the vectors file; the version is bumped whenever the stored format changes
+pub const VECTORS_FILE_NAME: &str = "semantic-vectors-v1.bin";
+
+/// Tokens of a text the model sees; the rest is cut off
+const MAX_TOKENS: usize = 256;
+
+/// Characters of a text handed to the tokenizer, so huge pastes don't slow tokenizing down
+const MAX_TEXT_CHARS: usize = 2_000;
+
+/// Texts embedded in one forward pass
+const BATCH_SIZE: usize = 32;
+
+/// An embedding quantized to `i8`, its largest component stored as ±127
+type Vector = Vec<i8>;
+
+/// Turns texts into unit vectors
+pub trait Embedder {
+    /// Model the vectors come from; vectors saved by another model are recomputed
+    fn model_id(&self) -> &str;
+
+    /// One unit vector per text, all of the same dimension
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the model fails to run.
+    fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>>;
+}
+
+/// BERT sentence-embedding model with mean pooling, run on the CPU
+pub struct BertEmbedder {
+    model_id: String,
+    model: BertModel,
+    tokenizer: Tokenizer,
+}
+
+impl BertEmbedder {
+    /// Load `model`: a directory holding `config.json`, `tokenizer.json` and
+    /// `model.safetensors`, or else a Hugging Face model id whose files are downloaded into
+    /// the Hugging Face cache the first time
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the files cannot be downloaded or read, or aren't a BERT model.
+    pub fn load(model: &str) -> Result<Self> {
+        let dir = Path::new(model);
+        let files = if dir.is_dir() {
+            ["config.json", "tokenizer.json", "model.safetensors"].map(|name| dir.join(name))
+        } else {
+            download(model)?
+        };
+        let [config, tokenizer, weights] = &files;
+        Self::load_files(model, config, tokenizer, weights)
+    }
+
+    fn load_files(model_id: &str, config: &Path, tokenizer: &Path, weights: &Path) -> Result<Self> {
+        let config: BertConfig = serde_json::from_slice(
+            &fs::read(config).with_context(|| format!("Failed to read {}", config.display()))?,
+        )
+        .with_context(|| format!("{} is not a BERT model config", config.display()))?;
+
+        let mut tokenizer = Tokenizer::from_file(tokenizer)
+            .map_err(|e| anyhow!("Failed to load tokenizer {}: {}", tokenizer.display(), e))?;
+        tokenizer.with_padding(Some(PaddingParams::default()));
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: MAX_TOKENS,
+                ..Default::default()
+            }))
+            .map_err(|e| anyhow!("Failed to set up tokenizer: {}", e))?;
+
+        let weights = fs::read(weights)
+            .with_context(|| format!("Failed to read model weights {}", weights.display()))?;
+        let vb = VarBuilder::from_buffered_safetensors(weights, DTYPE, &Device::Cpu)?;
+        let model = BertModel::load(vb, &config)
+            .with_context(|| format!("Failed to load embedding model {}", model_id))?;
+        Ok(Self { model_id: model_id.to_string(), model, tokenizer })
+    }
+}
+
+impl Embedder for BertEmbedder {
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let texts: Vec<&str> = texts.iter().map(|text| truncate(text)).collect();
+        let encodings = self
+            .tokenizer
+            .encode_batch(texts, true)
+            .map_err(|e| anyhow!("Failed to tokenize: {}", e))?;
+        let device = &self.model.device;
+        let stack = |rows: Vec<&[u32]>| -> Result<Tensor> {
+            let rows = rows.into_iter().map(|row| Tensor::new(row, device));
+            Ok(Tensor::stack(&rows.collect::<candle_core::Result<Vec<_>>>()?, 0)?)
+        };
+        let ids = stack(encodings.iter().map(|e| e.get_ids()).collect())?;
+        let type_ids = stack(encodings.iter().map(|e| e.get_type_ids()).collect())?;
+        let mask = stack(encodings.iter().map(|e| e.get_attention_mask()).collect())?;
+
+        let tokens = self.model.forward(&ids, &type_ids, Some(&mask))?;
+        // Average the token embeddings, leaving out the padding
+        let mask = mask.to_dtype(DType::F32)?.unsqueeze(2)?;
+        let pooled = tokens.broadcast_mul(&mask)?.sum(1)?.broadcast_div(&mask.sum(1)?)?;
+        let norm = pooled.sqr()?.sum_keepdim(1)?.sqrt()?;
+        Ok(pooled.broadcast_div(&norm)?.to_vec2::<f32>()?)
+    }
+}
+
+/// Sentence embeddings of search entries, on disk or in memory
+pub struct SemanticIndex {
+    embedder: Box<dyn Embedder>,
+    /// File the vectors are saved to (`None`: never persisted)
+    path: Option<PathBuf>,
+    vectors: HashMap<u64, Vector>,
+    /// Position of each entry of the last sync, by key
+    positions: HashMap<u64, usize>,
+    /// Why the saved vectors were ignored when opening, until reported
+    load_problems: Vec<Diagnostic>,
+}
+
+impl SemanticIndex {
+    /// Open the vectors in the cache directory, or in memory if the cache isn't persisted
+    pub fn open(dir: &CacheDir, embedder: Box<dyn Embedder>) -> Self {
+        match dir.location.path() {
+            Some(path) => Self::open_in(&path.join(VECTORS_FILE_NAME), embedder),
+            None => Self::in_memory(embedder),
+        }
+    }
+
+    /// Open the vectors saved in `path`
+    ///
+    /// A missing or corrupt file, or one written with another model, starts an empty index;
+    /// the vectors are then recomputed. Why a corrupt file was ignored is kept for
+    /// [`take_load_problems`](Self::take_load_problems).
+    pub fn open_in(path: &Path, embedder: Box<dyn Embedder>) -> Self {
+        let mut load_problems = Vec::new();
+        let vectors = load(path, embedder.model_id()).unwrap_or_else(|e| {
+            let message = format!("{:#}; recomputing the embeddings", e);
+            load_problems.push(Diagnostic { path: path.to_path_buf(), message });
+            HashMap::new()
+        });
+        Self {
+            embedder,
+            path: Some(path.to_path_buf()),
+            vectors,
+            positions: HashMap::new(),
+            load_problems,
+        }
+    }
+
+    /// Index that is never persisted
+    pub fn in_memory(embedder: Box<dyn Embedder>) -> Self {
+        Self {
+            embedder,
+            path: None,
+            vectors: HashMap::new(),
+            positions: HashMap::new(),
+            load_problems: Vec::new(),
+        }
+    }
+
+    /// Why the saved vectors were ignored when opening, if they were; returned once
+    pub fn take_load_problems(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.load_problems)
+    }
+
+    /// Bring the vectors in line with `entries`, embedding only entries not seen before
+    ///
+    /// Hits of later searches refer to positions in `entries`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the model fails or the changed vectors cannot be saved.
+    pub fn sync(&mut self, entries: &[SearchEntry]) -> Result<IndexUpdate> {
+        let mut positions = HashMap::with_capacity(entries.len());
+        for (i, entry) in entries.iter().enumerate() {
+            positions.entry(entry_key(entry)).or_insert(i);
+        }
+
+        let before = self.vectors.len();
+        self.vectors.retain(|key, _| positions.contains_key(key));
+        let removed = before - self.vectors.len();
+        let mut missing: Vec<(u64, usize)> = positions
+            .iter()
+            .filter(|(key, _)| !self.vectors.contains_key(key))
+            .map(|(&key, &i)| (key, i))
+            .collect();
+        missing.sort_unstable_by_key(|&(_, i)| i);
+        for batch in missing.chunks(BATCH_SIZE) {
+            let texts: Vec<&str> =
+                batch.iter().map(|&(_, i)| entries[i].display_text.as_str()).collect();
+            let embeddings = self.embedder.embed(&texts)?;
+            for (&(key, _), embedding) in batch.iter().zip(&embeddings) {
+                if let Entry::Vacant(slot) = self.vectors.entry(key) {
+                    slot.insert(quantize(embedding));
+                }
+            }
+        }
+
+        self.positions = positions;
+        let added = missing.len();
+        if (added > 0 || removed > 0)
+            && let Some(path) = &self.path
+        {
+            store(path, self.embedder.model_id(), &self.vectors)?;
+        }
+        Ok(IndexUpdate { added, removed })
+    }
+
+    /// Number of embedded entries
+    pub fn num_entries(&self) -> usize {
+        self.vectors.len()
+    }
+
+    /// The `limit` entries nearest to `query` in meaning, most similar first
+    ///
+    /// The score is the cosine similarity of the embeddings (-1 to 1).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the model fails to embed the query.
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>> {
+        let query = self.embedder.embed(&[query])?.pop().unwrap_or_default();
+        let mut hits: Vec<SearchHit> = self
+            .positions
+            .iter()
+            .filter_map(|(key, &index)| {
+                Some(SearchHit { index, score: similarity(&query, self.vectors.get(key)?) })
+            })
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.index.cmp(&b.index)));
+        hits.truncate(limit);
+        Ok(hits)
+    }
+}
+
+/// Files of a Hugging Face model, fetched into the Hugging Face cache unless already there
+fn download(model: &str) -> Result<[PathBuf; 3]> {
+    let repo = hf_hub::api::sync::Api::new()
+        .context("Failed to set up the Hugging Face download")?
+        .model(model.to_string());
+    let get = |name: &str| {
+        repo.get(name).with_context(|| format!("Failed to download {} of model {}", name, model))
+    };
+    Ok([get("config.json")?, get("tokenizer.json")?, get("model.safetensors")?])
+}
+
+/// The start of `text` that is given to the tokenizer
+fn truncate(text: &str) -> &str {
+    match text.char_indices().nth(MAX_TEXT_CHARS) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+fn quantize(vector: &[f32]) -> Vector {
+    let max = vector.iter().fold(0.0_f32, |max, v| max.max(v.abs()));
+    if max == 0.0 {
+        return vec![0; vector.len()];
+    }
+    vector.iter().map(|v| (v / max * 127.0).round() as i8).collect()
+}
+
+/// Cosine similarity of a query embedding and a quantized entry embedding
+fn similarity(query: &[f32], vector: &[i8]) -> f32 {
+    let (mut dot, mut norm) = (0.0, 0.0);
+    for (&q, &v) in query.iter().zip(vector) {
+        let v = f32::from(v);
+        dot += q * v;
+        norm += v * v;
+    }
+    if norm == 0.0 { 0.0 } else { dot / norm.sqrt() }
+}
+
+/// Vectors saved in `path`; none for a missing file or one written with another model
+fn load(path: &Path, model_id: &str) -> Result<HashMap<u64, Vector>> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(anyhow!("Failed to read embeddings: {}", e)),
+    };
+    match bincode::deserialize::<StoredVectors>(&bytes) {
+        Ok(stored) if stored.model == model_id => Ok(stored.vectors),
+        Ok(_) => Ok(HashMap::new()),
+        Err(e) => Err(anyhow!("Corrupt embeddings: {}", e)),
+    }
+}
+
+/// Write via a temporary file and rename, so readers never see partial vectors
+fn store(path: &Path, model: &str, vectors: &HashMap<u64, Vector>) -> Result<()> {
+    #[derive(Serialize)]
+    struct StoredVectorsRef<'a> {
+        model: &'a str,
+        vectors: &'a HashMap<u64, Vector>,
+    }
+
+    let bytes = bincode::serialize(&StoredVectorsRef { model, vectors })
+        .context("Failed to serialize embeddings")?;
+    let temp = path.with_extension("bin.tmp");
+    crate::utils::write_private_file(&temp, bytes)?;
+    fs::rename(&temp, path)
+        .with_context(|| format!("Failed to write embeddings {}", path.display()))
+}
+
+#[derive(Deserialize)]
+struct StoredVectors {
+    model: String,
+    vectors: HashMap<u64, Vector>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use candle_nn::VarMap;
+    use chrono::DateTime;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::models::EntryType;
+
+    /// Embeds texts onto a few concept axes by keyword, so related words land together, and
+    /// counts the texts it was given
+    struct ConceptEmbedder {
+        model: &'static str,
+        embedded: Rc<Cell<usize>>,
+    }
+
+    const CONCEPTS: &[&[&str]] = &[
+        &["retry", "retries", "backoff", "flaky", "timeout"],
+        &["css", "sidebar", "layout", "navigation", "style"],
+        &["database", "migration", "schema", "sql"],
+    ];
+
+    impl Embedder for ConceptEmbedder {
+        fn model_id(&self) -> &str {
+            self.model
+        }
+
+        fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+            self.embedded.set(self.embedded.get() + texts.len());
+            Ok(texts
+                .iter()
+                .map(|text| {
+                    let text = text.to_lowercase();
+                    let mut vector: Vec<f32> = CONCEPTS
+                        .iter()
+                        .map(|words| {
+                            words.iter().filter(|word| text.contains(*word)).count() as f32
+                        })
+                        .collect();
+                    vector.push(0.1);
+                    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+                    vector.iter().map(|v| v / norm).collect()
+                })
+                .collect())
+        }
+    }
+
+    fn embedder(model: &'static str) -> (Box<dyn Embedder>, Rc<Cell<usize>>) {
+        let embedded = Rc::new(Cell::new(0));
+        (Box::new(ConceptEmbedder { model, embedded: Rc::clone(&embedded) }), embedded)
+    }
+
+    fn entry(text: &str, secs: i64) -> SearchEntry {
+        SearchEntry {
+            entry_type: EntryType::UserPrompt,
+            display_text: text.to_string(),
+            timestamp: DateTime::from_timestamp(secs, 0).unwrap(),
+            session_id: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn entries() -> Vec<SearchEntry> {
+        vec![
+            entry("Style the navigation sidebar with CSS grid", 100),
+            entry("Add exponential backoff to the HTTP client", 200),
+            entry("Write the schema migration for the users table", 300),
+        ]
+    }
+
+    #[test]
+    fn test_finds_entries_without_shared_words() {
+        let mut index = SemanticIndex::in_memory(embedder("concepts").0);
+        index.sync(&entries()).unwrap();
+
+        let hits = index.search("the tests are flaky and need retries", 3).unwrap();
+        assert_eq!(hits.len(), 3);
+        assert_eq!(hits[0].index, 1);
+        assert!(hits[0].score > 0.9 && hits[1].score < 0.5);
+        assert_eq!(index.search("fix the page layout", 1).unwrap()[0].index, 0);
+    }
+
+    #[test]
+    fn test_sync_embeds_only_changes_and_persists() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(VECTORS_FILE_NAME);
+        let mut entries = entries();
+
+        let (model, embedded) = embedder("concepts");
+        let mut index = SemanticIndex::open_in(&path, model);
+        assert_eq!(index.sync(&entries).unwrap(), IndexUpdate { added: 3, removed: 0 });
+        assert_eq!(embedded.get(), 3);
+
+        entries.remove(0);
+        entries.push(entry("Retry the SQL query on timeout", 400));
+        let (model, embedded) = embedder("concepts");
+        let mut reopened = SemanticIndex::open_in(&path, model);
+        assert_eq!(reopened.num_entries(), 3);
+        assert_eq!(reopened.sync(&entries).unwrap(), IndexUpdate { added: 1, removed: 1 });
+        assert_eq!(embedded.get(), 1);
+        assert!(reopened.take_load_problems().is_empty());
+
+        // Vectors of another model are not comparable, so they are all recomputed
+        let mut other = SemanticIndex::open_in(&path, embedder("other").0);
+        assert_eq!(other.num_entries(), 0);
+        assert!(other.take_load_problems().is_empty());
+
+        fs::write(&path, b"garbage").unwrap();
+        let mut corrupt = SemanticIndex::open_in(&path, embedder("concepts").0);
+        assert_eq!(corrupt.num_entries(), 0);
+        assert_eq!(corrupt.take_load_problems()[0].path, path);
+    }
+
+    #[test]
+    fn test_quantized_similarity_matches_cosine() {
+        let a = [0.6, -0.8, 0.0];
+        let b = [0.8, 0.0, 0.6];
+        assert!((similarity(&a, &quantize(&a)) - 1.0).abs() < 1e-3);
+        assert!((similarity(&a, &quantize(&b)) - 0.48).abs() < 1e-2);
+        assert_eq!(similarity(&a, &quantize(&[0.0; 3])), 0.0);
+        assert_eq!(truncate(&"é".repeat(MAX_TEXT_CHARS + 5)).chars().count(), MAX_TEXT_CHARS);
+    }
+
+    /// Write a randomly initialized two-layer BERT and a word-level tokenizer to `dir`
+    fn write_tiny_model(dir: &Path) {
+        let words = ["[PAD]", "[UNK]", "retry", "the", "request", "style", "sidebar"];
+        let vocab: serde_json::Map<String, serde_json::Value> =
+            words.iter().enumerate().map(|(i, word)| (word.to_string(), i.into())).collect();
+        let tokenizer = serde_json::json!({
+            "version": "1.0",
+            "truncation": null,
+            "padding": null,
+            "added_tokens": [],
+            "normalizer": { "type": "Lowercase" },
+            "pre_tokenizer": { "type": "Whitespace" },
+            "post_processor": null,
+            "decoder": null,
+            "model": { "type": "WordLevel", "vocab": vocab, "unk_token": "[UNK]" }
+        });
+        fs::write(dir.join("tokenizer.json"), tokenizer.to_string()).unwrap();
+
+        let config = serde_json::json!({
+            "vocab_size": words.len(),
+            "hidden_size": 16,
+            "num_hidden_layers": 2,
+            "num_attention_heads": 2,
+            "intermediate_size": 32,
+            "hidden_act": "gelu",
+            "hidden_dropout_prob": 0.0,
+            "max_position_embeddings": 64,
+            "type_vocab_size": 2,
+            "initializer_range": 0.02,
+            "layer_norm_eps": 1e-12,
+            "pad_token_id": 0,
+            "classifier_dropout": null,
+            "model_type": "bert"
+        });
+        fs::write(dir.join("config.json"), config.to_string()).unwrap();
+        let config: BertConfig = serde_json::from_value(config).unwrap();
+        let varmap = VarMap::new();
+        BertModel::load(VarBuilder::from_varmap(&varmap, DTYPE, &Device::Cpu), &config).unwrap();
+        varmap.save(dir.join("model.safetensors")).unwrap();
+    }
+
+    #[test]
+    fn test_bert_embedder_pools_unit_vectors_ignoring_padding() {
+        let temp = TempDir::new().unwrap();
+        write_tiny_model(temp.path());
+        let model = temp.path().to_str().unwrap();
+        let embedder = BertEmbedder::load(model).unwrap();
+        assert_eq!(embedder.model_id(), model);
+
+        let alone = embedder.embed(&["Retry the request"]).unwrap();
+        let batch =
+            embedder.embed(&["Retry the request", "style the sidebar retry the request"]).unwrap();
+        assert_eq!(batch.len(), 2);
+        assert_eq!(batch[0].len(), 16);
+        for vector in &batch {
+            let norm: f32 = vector.iter().map(|v| v * v).sum();
+            assert!((norm - 1.0).abs() < 1e-4);
+        }
+        // Padding the shorter text to the longer one's length doesn't change its embedding
+        for (a, b) in alone[0].iter().zip(&batch[0]) {
+            assert!((a - b).abs() < 1e-4, "{} != {}", a, b);
+        }
+
+        let empty = temp.path().join("empty");
+        fs::create_dir(&empty).unwrap();
+        assert!(BertEmbedder::load(empty.to_str().unwrap()).is_err());
+    }
+}
@@ -0,0 +1,593 @@
+//! Taking, listing and restoring snapshots.
+
+use std::fs;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, NaiveDate, Utc};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+
+use super::manifest::{MANIFEST_FILE_NAME, MANIFEST_VERSION, Manifest, ManifestFile};
+use crate::utils::{Plan, create_private_dir_all, create_private_file, safe_open_file};
+
+/// Directory inside a snapshot that holds the compressed files
+const FILES_DIR_NAME: &str = "files";
+
+/// Format of snapshot directory names
+const SNAPSHOT_NAME_FORMAT: &str = "%Y-%m-%d";
+
+/// Archive used when none is given: `ai-history-explorer/snapshots` in the platform data
+/// directory (`~/.local/share` on Linux)
+pub fn default_archive_dir() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|dir| dir.join("ai-history-explorer").join("snapshots"))
+}
+
+/// Outcome of [`take_snapshot`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotSummary {
+    pub name: String,
+    pub dir: PathBuf,
+    pub new: usize,
+    pub changed: usize,
+    pub unchanged: usize,
+    /// Files of the previous snapshot that no longer exist in the source
+    pub removed: usize,
+    /// Compressed bytes written into this snapshot
+    pub bytes_written: u64,
+    /// Files that could not be copied, with the reason (the previous copy is kept, if any)
+    pub skipped: Vec<(PathBuf, String)>,
+}
+
+/// A snapshot found in an archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotInfo {
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    /// Files present at snapshot time
+    pub files: usize,
+    /// Files whose contents are stored in this snapshot (new or changed at the time)
+    pub stored: usize,
+    /// Total size of the original files in bytes
+    pub total_bytes: u64,
+}
+
+/// Outcome of [`restore_snapshot`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestoreSummary {
+    pub name: String,
+    pub files: usize,
+}
+
+/// Snapshot `source` (a Claude directory) into `archive`, naming it after the date of `now`
+///
+/// Copies `history.jsonl` and the conversation files under `projects/`. Symlinks are never
+/// followed, and files the indexer would refuse to read are skipped and reported.
+///
+/// # Errors
+///
+/// Returns an error if `source` is not a directory, the previous manifest can't be read or
+/// the snapshot directory or manifest can't be written.
+pub fn take_snapshot(source: &Path, archive: &Path, now: DateTime<Utc>) -> Result<SnapshotSummary> {
+    snapshot_impl(source, archive, now, None)
+}
+
+/// Like [`take_snapshot`], but records the files it would write in `plan` instead of writing
+/// them
+///
+/// New and changed files are still compressed, into nothing, so the plan has their sizes.
+///
+/// # Errors
+///
+/// Same as [`take_snapshot`].
+pub fn plan_snapshot(
+    source: &Path,
+    archive: &Path,
+    now: DateTime<Utc>,
+    plan: &mut Plan,
+) -> Result<SnapshotSummary> {
+    snapshot_impl(source, archive, now, Some(plan))
+}
+
+/// Take a snapshot, or record its writes in `plan` when there is one
+fn snapshot_impl(
+    source: &Path,
+    archive: &Path,
+    now: DateTime<Utc>,
+    mut plan: Option<&mut Plan>,
+) -> Result<SnapshotSummary> {
+    if !source.is_dir() {
+        bail!("{} is not a directory", source.display());
+    }
+    let previous = match snapshot_names(archive)?.last() {
+        Some(name) => Some(Manifest::load(&archive.join(name))?),
+        None => None,
+    };
+
+    let name = now.format(SNAPSHOT_NAME_FORMAT).to_string();
+    let dir = archive.join(&name);
+    if plan.is_none() {
+        create_private_dir_all(&dir.join(FILES_DIR_NAME))?;
+    }
+
+    let mut summary =
+        SnapshotSummary { name: name.clone(), dir: dir.clone(), ..Default::default() };
+    let mut files = Vec::new();
+    for path in collect_source_files(source, &mut summary.skipped) {
+        let source_file = source.join(&path);
+        let previous_file = previous.as_ref().and_then(|m| m.file(&path));
+        let kept = previous_file.filter(|f| stored_path(archive, f).is_ok_and(|p| p.exists()));
+
+        let (size, modified_ms) = match file_stamp(&source_file) {
+            Ok(stamp) => stamp,
+            Err(e) => {
+                summary.skipped.push((source_file, format!("{:#}", e)));
+                files.extend(kept.cloned());
+                continue;
+            }
+        };
+        if let Some(file) = kept.filter(|f| f.is_unchanged(size, modified_ms)) {
+            summary.unchanged += 1;
+            files.push(file.clone());
+            continue;
+        }
+
+        let mut file = ManifestFile { path, size, modified_ms, stored_in: name.clone() };
+        let stored = stored_path(archive, &file)?;
+        let compressed = match plan.as_deref_mut() {
+            Some(plan) => compressed_size(&source_file).map(|(copied, compressed)| {
+                let change = if previous_file.is_some() { "changed" } else { "new" };
+                plan.write_file(&stored, compressed).note(format!("{} {}", change, file.path));
+                (copied, compressed)
+            }),
+            None => compress_file(&source_file, &stored),
+        };
+        match compressed {
+            Ok((copied, compressed)) => {
+                file.size = copied;
+                summary.bytes_written += compressed;
+                if previous_file.is_some() {
+                    summary.changed += 1;
+                } else {
+                    summary.new += 1;
+                }
+                files.push(file);
+            }
+            Err(e) => {
+                summary.skipped.push((source_file, format!("{:#}", e)));
+                files.extend(kept.cloned());
+            }
+        }
+    }
+
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    if let Some(previous) = &previous {
+        let current: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        summary.removed = previous
+            .files
+            .iter()
+            .filter(|f| current.binary_search(&f.path.as_str()).is_err())
+            .count();
+    }
+
+    let manifest = Manifest {
+        version: MANIFEST_VERSION,
+        created_at: now,
+        source: source.to_path_buf(),
+        files,
+    };
+    match plan {
+        Some(plan) => {
+            let json = manifest.to_json()?;
+            plan.write_file(&dir.join(MANIFEST_FILE_NAME), json.len() as u64)
+                .note(format!("manifest of {} files", manifest.files.len()));
+        }
+        None => manifest.save(&dir)?,
+    }
+    Ok(summary)
+}
+
+/// Snapshots in `archive`, oldest first (empty if the archive doesn't exist yet)
+///
+/// # Errors
+///
+/// Returns an error if the archive or a manifest can't be read.
+pub fn list_snapshots(archive: &Path) -> Result<Vec<SnapshotInfo>> {
+    snapshot_names(archive)?
+        .into_iter()
+        .map(|name| {
+            let manifest = Manifest::load(&archive.join(&name))?;
+            Ok(SnapshotInfo {
+                created_at: manifest.created_at,
+                files: manifest.files.len(),
+                stored: manifest.files.iter().filter(|f| f.stored_in == name).count(),
+                total_bytes: manifest.files.iter().map(|f| f.size).sum(),
+                name,
+            })
+        })
+        .collect()
+}
+
+/// Rebuild the Claude directory captured by snapshot `name` (the latest if `None`) in `dest`
+///
+/// `dest` must not exist or be empty. The result has the original layout and can be indexed
+/// with `--claude-dir`.
+///
+/// # Errors
+///
+/// Returns an error if there is no such snapshot, `dest` is not empty, or a stored file is
+/// missing or corrupt.
+pub fn restore_snapshot(archive: &Path, name: Option<&str>, dest: &Path) -> Result<RestoreSummary> {
+    restore_impl(archive, name, dest, None)
+}
+
+/// Like [`restore_snapshot`], but records the files it would write in `plan` instead of
+/// writing them
+///
+/// # Errors
+///
+/// Same as [`restore_snapshot`], apart from stored files being checked for existence only.
+pub fn plan_restore(
+    archive: &Path,
+    name: Option<&str>,
+    dest: &Path,
+    plan: &mut Plan,
+) -> Result<RestoreSummary> {
+    restore_impl(archive, name, dest, Some(plan))
+}
+
+/// Restore a snapshot, or record its writes in `plan` when there is one
+fn restore_impl(
+    archive: &Path,
+    name: Option<&str>,
+    dest: &Path,
+    mut plan: Option<&mut Plan>,
+) -> Result<RestoreSummary> {
+    let names = snapshot_names(archive)?;
+    let name = match name {
+        Some(name) if names.iter().any(|n| n == name) => name.to_string(),
+        Some(name) => bail!("No snapshot named '{}' in {}", name, archive.display()),
+        None => match names.last() {
+            Some(name) => name.clone(),
+            None => bail!("No snapshots in {}", archive.display()),
+        },
+    };
+    let manifest = Manifest::load(&archive.join(&name))?;
+
+    if dest.exists()
+        && fs::read_dir(dest)
+            .with_context(|| format!("Failed to read {}", dest.display()))?
+            .next()
+            .is_some()
+    {
+        bail!("{} is not empty; restore into a new directory", dest.display());
+    }
+    if plan.is_none() {
+        create_private_dir_all(dest)?;
+    }
+
+    for file in &manifest.files {
+        let target = dest.join(file.relative_path()?);
+        let stored = stored_path(archive, file)?;
+        match plan.as_deref_mut() {
+            Some(_) if !stored.is_file() => {
+                bail!("Failed to restore {}: {} is missing", file.path, stored.display())
+            }
+            Some(plan) => {
+                plan.write_file(&target, file.size)
+                    .note(format!("from snapshot {}", file.stored_in));
+            }
+            None => decompress_file(&stored, &target, file.size)
+                .with_context(|| format!("Failed to restore {}", file.path))?,
+        }
+    }
+    Ok(RestoreSummary { name, files: manifest.files.len() })
+}
+
+/// Names of the snapshots in `archive` that have a manifest, oldest first
+fn snapshot_names(archive: &Path) -> Result<Vec<String>> {
+    if !archive.exists() {
+        return Ok(Vec::new());
+    }
+    let mut names = Vec::new();
+    for entry in
+        fs::read_dir(archive).with_context(|| format!("Failed to read {}", archive.display()))?
+    {
+        let entry = entry.context("Failed to read directory entry")?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if is_snapshot_name(&name) && entry.path().join(super::MANIFEST_FILE_NAME).is_file() {
+            names.push(name);
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+fn is_snapshot_name(name: &str) -> bool {
+    NaiveDate::parse_from_str(name, SNAPSHOT_NAME_FORMAT).is_ok()
+}
+
+/// `/`-separated paths (relative to `source`) of `history.jsonl` and the `.jsonl` files under
+/// `projects/`, sorted. Unreadable entries and non-UTF-8 names are added to `skipped`.
+fn collect_source_files(source: &Path, skipped: &mut Vec<(PathBuf, String)>) -> Vec<String> {
+    let mut paths = Vec::new();
+    if source.join("history.jsonl").is_file() {
+        paths.push("history.jsonl".to_string());
+    }
+
+    let projects = source.join("projects");
+    if projects.is_dir() {
+        for entry in walkdir::WalkDir::new(&projects).follow_links(false) {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    let path = e.path().unwrap_or(&projects).to_path_buf();
+                    skipped.push((path, e.to_string()));
+                    continue;
+                }
+            };
+            if !entry.file_type().is_file()
+                || entry.path().extension().is_none_or(|ext| ext != "jsonl")
+            {
+                continue;
+            }
+            let relative = entry.path().strip_prefix(source).unwrap_or(entry.path());
+            let components: Option<Vec<&str>> =
+                relative.components().map(|c| c.as_os_str().to_str()).collect();
+            match components {
+                Some(components) => paths.push(components.join("/")),
+                None => skipped.push((entry.path().to_path_buf(), "Non UTF-8 file name".into())),
+            }
+        }
+    }
+    paths.sort();
+    paths
+}
+
+/// Size and modification time (milliseconds) of a file
+fn file_stamp(path: &Path) -> Result<(u64, i64)> {
+    let metadata = fs::symlink_metadata(path)
+        .with_context(|| format!("Failed to read metadata for {}", path.display()))?;
+    let modified = metadata
+        .modified()
+        .with_context(|| format!("No modification time for {}", path.display()))?;
+    Ok((metadata.len(), DateTime::<Utc>::from(modified).timestamp_millis()))
+}
+
+/// Where the compressed contents of `file` are kept
+fn stored_path(archive: &Path, file: &ManifestFile) -> Result<PathBuf> {
+    if !is_snapshot_name(&file.stored_in) {
+        bail!("Invalid snapshot name in manifest: '{}'", file.stored_in);
+    }
+    let mut path = archive
+        .join(&file.stored_in)
+        .join(FILES_DIR_NAME)
+        .join(file.relative_path()?)
+        .into_os_string();
+    path.push(".gz");
+    Ok(path.into())
+}
+
+/// Gzip `source` into `dest` (replacing it atomically); returns the bytes read and written
+fn compress_file(source: &Path, dest: &Path) -> Result<(u64, u64)> {
+    // Streamed, never parsed: a backup keeps files of any size
+    let input = safe_open_file(source, u64::MAX)?;
+    if let Some(parent) = dest.parent() {
+        create_private_dir_all(parent)?;
+    }
+    let mut temp = dest.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+
+    let result = (|| {
+        let output = BufWriter::new(create_private_file(&temp)?);
+        let mut encoder = GzEncoder::new(output, Compression::default());
+        let copied = io::copy(&mut BufReader::new(input), &mut encoder)
+            .with_context(|| format!("Failed to compress {}", source.display()))?;
+        encoder
+            .finish()
+            .and_then(|mut output| output.flush())
+            .with_context(|| format!("Failed to write {}", temp.display()))?;
+        fs::rename(&temp, dest).with_context(|| format!("Failed to write {}", dest.display()))?;
+        let compressed = fs::metadata(dest)
+            .with_context(|| format!("Failed to read metadata for {}", dest.display()))?
+            .len();
+        Ok((copied, compressed))
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Bytes `source` has and would take gzipped, without writing anything
+fn compressed_size(source: &Path) -> Result<(u64, u64)> {
+    let input = safe_open_file(source, u64::MAX)?;
+    let mut encoder = flate2::read::GzEncoder::new(BufReader::new(input), Compression::default());
+    let compressed = io::copy(&mut encoder, &mut io::sink())
+        .with_context(|| format!("Failed to compress {}", source.display()))?;
+    Ok((encoder.get_ref().get_ref().metadata().map_or(0, |m| m.len()), compressed))
+}
+
+/// Decompress `source` into a new file at `dest`, checking it has exactly `size` bytes
+fn decompress_file(source: &Path, dest: &Path, size: u64) -> Result<()> {
+    let input =
+        fs::File::open(source).with_context(|| format!("Failed to open {}", source.display()))?;
+    if let Some(parent) = dest.parent() {
+        create_private_dir_all(parent)?;
+    }
+    let mut output = BufWriter::new(create_private_file(dest)?);
+    // Read at most one byte more than expected, so a corrupt file can't fill the disk
+    let mut decoder = GzDecoder::new(BufReader::new(input)).take(size + 1);
+    let copied = io::copy(&mut decoder, &mut output)
+        .with_context(|| format!("Failed to decompress {}", source.display()))?;
+    output.flush().with_context(|| format!("Failed to write {}", dest.display()))?;
+    if copied != size {
+        bail!("{} holds {} bytes, expected {}", source.display(), copied, size);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::indexer::builder::build_index;
+
+    const SESSION_FILE: &str = "projects/-work-app/550e8400-e29b-41d4-a716-446655440000.jsonl";
+
+    fn day(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, day, 3, 0, 0).unwrap()
+    }
+
+    fn history_line(text: &str, millis: i64) -> String {
+        format!(
+            r#"{{"display":"{}","timestamp":{},"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/app"}}"#,
+            text, millis
+        ) + "\n"
+    }
+
+    fn claude_dir() -> tempfile::TempDir {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("history.jsonl"),
+            history_line("first prompt", 1_717_200_000_000),
+        )
+        .unwrap();
+        let session = dir.path().join(SESSION_FILE);
+        fs::create_dir_all(session.parent().unwrap()).unwrap();
+        fs::write(&session, "").unwrap();
+        fs::write(session.with_file_name("notes.txt"), "not history").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_first_snapshot_stores_everything() {
+        let source = claude_dir();
+        let archive = tempfile::TempDir::new().unwrap();
+
+        let summary = take_snapshot(source.path(), archive.path(), day(1)).unwrap();
+
+        assert_eq!(summary.name, "2024-06-01");
+        assert_eq!((summary.new, summary.changed, summary.unchanged), (2, 0, 0));
+        assert!(summary.skipped.is_empty());
+        assert!(summary.dir.join("files/history.jsonl.gz").is_file());
+        assert!(summary.dir.join(format!("files/{}.gz", SESSION_FILE)).is_file());
+        assert!(!summary.dir.join("files/projects/-work-app/notes.txt.gz").exists());
+    }
+
+    #[test]
+    fn test_plans_match_what_is_written() {
+        let source = claude_dir();
+        let archive = tempfile::TempDir::new().unwrap();
+
+        let mut plan = Plan::default();
+        let planned = plan_snapshot(source.path(), archive.path(), day(1), &mut plan).unwrap();
+        assert_eq!(fs::read_dir(archive.path()).unwrap().count(), 0);
+        let summary = take_snapshot(source.path(), archive.path(), day(1)).unwrap();
+        assert_eq!(planned, summary);
+        // Both files and the manifest, with their sizes once written
+        assert_eq!(plan.changes.len(), 3);
+        for change in &plan.changes {
+            assert_eq!(fs::metadata(&change.path).unwrap().len(), change.bytes);
+        }
+
+        let dest = archive.path().join("restored");
+        let mut plan = Plan::default();
+        plan_restore(archive.path(), None, &dest, &mut plan).unwrap();
+        assert!(!dest.exists());
+        let paths: Vec<_> = plan.changes.iter().map(|c| c.path.clone()).collect();
+        assert_eq!(paths, vec![dest.join("history.jsonl"), dest.join(SESSION_FILE)]);
+    }
+
+    #[test]
+    fn test_later_snapshot_stores_only_changes() {
+        let source = claude_dir();
+        let archive = tempfile::TempDir::new().unwrap();
+        take_snapshot(source.path(), archive.path(), day(1)).unwrap();
+
+        let mut history = fs::read_to_string(source.path().join("history.jsonl")).unwrap();
+        history.push_str(&history_line("second prompt", 1_717_300_000_000));
+        fs::write(source.path().join("history.jsonl"), history).unwrap();
+        fs::remove_file(source.path().join(SESSION_FILE)).unwrap();
+        let other = source.path().join("projects/-work-lib/agent-1.jsonl");
+        fs::create_dir_all(other.parent().unwrap()).unwrap();
+        fs::write(&other, "").unwrap();
+
+        let summary = take_snapshot(source.path(), archive.path(), day(2)).unwrap();
+
+        assert_eq!((summary.new, summary.changed, summary.unchanged), (1, 1, 0));
+        assert_eq!(summary.removed, 1);
+        assert!(summary.dir.join("files/history.jsonl.gz").is_file());
+
+        // Nothing changed: the next snapshot stores no files but lists them all
+        let summary = take_snapshot(source.path(), archive.path(), day(3)).unwrap();
+        assert_eq!((summary.new, summary.changed, summary.unchanged), (0, 0, 2));
+        assert_eq!(summary.bytes_written, 0);
+
+        let infos = list_snapshots(archive.path()).unwrap();
+        let names: Vec<_> = infos.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, vec!["2024-06-01", "2024-06-02", "2024-06-03"]);
+        assert_eq!((infos[2].files, infos[2].stored), (2, 0));
+    }
+
+    #[test]
+    fn test_restore_rebuilds_indexable_claude_dir() {
+        let source = claude_dir();
+        let archive = tempfile::TempDir::new().unwrap();
+        take_snapshot(source.path(), archive.path(), day(1)).unwrap();
+        let mut history = fs::read_to_string(source.path().join("history.jsonl")).unwrap();
+        history.push_str(&history_line("second prompt", 1_717_300_000_000));
+        fs::write(source.path().join("history.jsonl"), &history).unwrap();
+        take_snapshot(source.path(), archive.path(), day(2)).unwrap();
+        take_snapshot(source.path(), archive.path(), day(3)).unwrap();
+
+        let dest = tempfile::TempDir::new().unwrap();
+        let restored = dest.path().join("claude");
+        let summary = restore_snapshot(archive.path(), None, &restored).unwrap();
+
+        assert_eq!(summary.name, "2024-06-03");
+        assert_eq!(fs::read_to_string(restored.join("history.jsonl")).unwrap(), history);
+        assert!(restored.join(SESSION_FILE).is_file());
+        assert_eq!(build_index(&restored).unwrap().len(), 2);
+
+        // An older point in time
+        let older = dest.path().join("older");
+        restore_snapshot(archive.path(), Some("2024-06-01"), &older).unwrap();
+        assert_eq!(build_index(&older).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_restore_refuses_non_empty_dest_and_unknown_names() {
+        let source = claude_dir();
+        let archive = tempfile::TempDir::new().unwrap();
+
+        let err = restore_snapshot(archive.path(), None, source.path()).unwrap_err();
+        assert!(err.to_string().contains("No snapshots"));
+
+        take_snapshot(source.path(), archive.path(), day(1)).unwrap();
+        let err = restore_snapshot(archive.path(), None, source.path()).unwrap_err();
+        assert!(err.to_string().contains("is not empty"));
+        let err = restore_snapshot(archive.path(), Some("2020-01-01"), Path::new("x")).unwrap_err();
+        assert!(err.to_string().contains("No snapshot named '2020-01-01'"));
+    }
+
+    #[test]
+    fn test_restore_detects_truncated_files() {
+        let source = claude_dir();
+        let archive = tempfile::TempDir::new().unwrap();
+        let summary = take_snapshot(source.path(), archive.path(), day(1)).unwrap();
+        let stored = summary.dir.join("files/history.jsonl.gz");
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"{}").unwrap();
+        fs::write(&stored, encoder.finish().unwrap()).unwrap();
+
+        let dest = tempfile::TempDir::new().unwrap();
+        let err = restore_snapshot(archive.path(), None, &dest.path().join("c")).unwrap_err();
+
+        assert!(format!("{:#}", err).contains("expected"));
+    }
+}
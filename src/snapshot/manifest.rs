@@ -0,0 +1,172 @@
+//! Snapshot manifest: every file of a snapshot and where its contents are stored.
+
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::write_private_file;
+
+/// Name of the manifest inside a snapshot directory
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// Manifest format written by this version
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Files of one snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    /// Claude directory the snapshot was taken from
+    pub source: PathBuf,
+    /// Sorted by path
+    pub files: Vec<ManifestFile>,
+}
+
+/// One file of a snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestFile {
+    /// Path relative to the Claude directory, `/`-separated (e.g. `projects/-work-app/x.jsonl`)
+    pub path: String,
+    /// Size of the original file in bytes
+    pub size: u64,
+    /// Modification time of the original file (milliseconds since the epoch)
+    pub modified_ms: i64,
+    /// Name of the snapshot whose `files/` directory holds the compressed contents
+    pub stored_in: String,
+}
+
+impl ManifestFile {
+    /// Whether `size` and `modified_ms` still describe the file recorded here
+    pub fn is_unchanged(&self, size: u64, modified_ms: i64) -> bool {
+        self.size == size && self.modified_ms == modified_ms
+    }
+
+    /// Relative path of the original file, rejecting anything that could leave the
+    /// destination directory (absolute paths, `..`)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path is empty, absolute or contains `..` components.
+    pub fn relative_path(&self) -> Result<PathBuf> {
+        let path = Path::new(&self.path);
+        if self.path.is_empty()
+            || !path.components().all(|component| matches!(component, Component::Normal(_)))
+        {
+            bail!("Invalid path in snapshot manifest: '{}'", self.path);
+        }
+        Ok(path.to_path_buf())
+    }
+}
+
+impl Manifest {
+    pub fn file(&self, path: &str) -> Option<&ManifestFile> {
+        self.files.binary_search_by(|f| f.path.as_str().cmp(path)).ok().map(|i| &self.files[i])
+    }
+
+    /// Read the manifest of the snapshot directory `dir`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the manifest is missing, malformed or from a newer version.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(MANIFEST_FILE_NAME);
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let manifest: Manifest = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        if manifest.version > MANIFEST_VERSION {
+            bail!(
+                "{} has format version {}; this version reads up to {}",
+                path.display(),
+                manifest.version,
+                MANIFEST_VERSION
+            );
+        }
+        Ok(manifest)
+    }
+
+    /// The manifest as saved
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the manifest cannot be serialized.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("Failed to serialize manifest")
+    }
+
+    /// Write the manifest into the snapshot directory `dir`
+    ///
+    /// Written to a temporary file first, so an interrupted run never leaves a truncated
+    /// manifest behind.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the manifest cannot be serialized or written.
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let json = self.to_json()?;
+        let path = dir.join(MANIFEST_FILE_NAME);
+        let temp = dir.join(format!("{}.tmp", MANIFEST_FILE_NAME));
+        write_private_file(&temp, json)?;
+        std::fs::rename(&temp, &path).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str) -> ManifestFile {
+        ManifestFile {
+            path: path.to_string(),
+            size: 10,
+            modified_ms: 1_704_099_600_000,
+            stored_in: "2024-01-01".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_relative_path_rejects_escapes() {
+        assert_eq!(
+            file("projects/-work-app/a.jsonl").relative_path().unwrap(),
+            PathBuf::from("projects/-work-app/a.jsonl")
+        );
+        assert!(file("../outside.jsonl").relative_path().is_err());
+        assert!(file("/etc/passwd").relative_path().is_err());
+        assert!(file("").relative_path().is_err());
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let manifest = Manifest {
+            version: MANIFEST_VERSION,
+            created_at: DateTime::from_timestamp_millis(1_704_099_600_000).unwrap(),
+            source: PathBuf::from("/home/alice/.claude"),
+            files: vec![file("history.jsonl"), file("projects/-work-app/a.jsonl")],
+        };
+
+        manifest.save(dir.path()).unwrap();
+
+        let loaded = Manifest::load(dir.path()).unwrap();
+        assert_eq!(loaded, manifest);
+        assert_eq!(loaded.file("history.jsonl").unwrap().size, 10);
+        assert!(loaded.file("missing.jsonl").is_none());
+        assert!(!dir.path().join("manifest.json.tmp").exists());
+    }
+
+    #[test]
+    fn test_load_rejects_newer_version() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join(MANIFEST_FILE_NAME),
+            r#"{"version":99,"created_at":"2024-01-01T00:00:00Z","source":"/x","files":[]}"#,
+        )
+        .unwrap();
+
+        let err = Manifest::load(dir.path()).unwrap_err().to_string();
+        assert!(err.contains("format version 99"));
+    }
+}
@@ -0,0 +1,28 @@
+//! Point-in-time backups of Claude history.
+//!
+//! Claude Code prunes old conversation files, so history that isn't copied elsewhere is
+//! eventually lost. A snapshot archive keeps one dated directory per snapshot:
+//!
+//! ```text
+//! <archive>/<root name>/2024-06-01/manifest.json
+//! <archive>/<root name>/2024-06-01/files/history.jsonl.gz
+//! <archive>/<root name>/2024-06-01/files/projects/<encoded path>/<session>.jsonl.gz
+//! ```
+//!
+//! Only files that are new or changed since the previous snapshot (by size and modification
+//! time) are compressed into the new directory. The [`Manifest`] lists every file present at
+//! snapshot time and which snapshot holds its contents, so any snapshot can be restored in
+//! full. [`restore_snapshot`] rebuilds the original `~/.claude` layout, which is indexed like
+//! any other Claude directory (`--claude-dir`).
+//!
+//! Taking a snapshot more than once a day updates that day's snapshot. Requires the
+//! `snapshot` feature.
+
+pub mod archive;
+pub mod manifest;
+
+pub use archive::{
+    RestoreSummary, SnapshotInfo, SnapshotSummary, default_archive_dir, list_snapshots,
+    plan_restore, plan_snapshot, restore_snapshot, take_snapshot,
+};
+pub use manifest::{MANIFEST_FILE_NAME, MANIFEST_VERSION, Manifest, ManifestFile};
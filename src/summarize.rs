@@ -0,0 +1,381 @@
+//! One-line session summaries.
+//!
+//! The `summarize` command sends each session's messages to the config's `summarizer` — a
+//! local program such as `ollama run llama3.2`, or the Anthropic API with the
+//! `summarize-api` feature — and saves the line it answers with to `summaries.json` in the
+//! cache directory. The TUI shows the summary on the session rows of results grouped by
+//! session (Ctrl+S) instead of the session's first message.
+//!
+//! A summary records how many entries its session had and when the last one was written;
+//! once the session grows it is out of date and summarized again on the next run.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::cache::CACHE_DIR_NAME;
+use crate::models::{EntryType, SearchEntry};
+use crate::utils::terminal::strip_ansi_codes;
+use crate::utils::{create_private_dir_all, write_private_file};
+
+/// Name of the summaries file inside the cache directory
+pub const SUMMARIES_FILE_NAME: &str = "summaries.json";
+
+/// Longest summary kept, in characters
+pub const MAX_SUMMARY_CHARS: usize = 120;
+
+/// Characters of a session sent to the summarizer; later messages are left out
+const MAX_INPUT_CHARS: usize = 12_000;
+
+/// Characters of one message sent to the summarizer
+const MAX_MESSAGE_CHARS: usize = 1_000;
+
+const INSTRUCTION: &str = "Summarize this coding assistant session in one line of at most \
+                           12 words, naming the task worked on. Reply with the summary only.";
+
+/// What writes the summaries (`summarizer` in the config)
+///
+/// ```json
+/// { "summarizer": { "command": ["ollama", "run", "llama3.2"] } }
+/// { "summarizer": { "anthropic": { "model": "claude-haiku-4-5" } } }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Summarizer {
+    /// Program and its arguments, run without a shell; it reads the session on stdin and
+    /// the first line it prints is the summary
+    Command(Vec<String>),
+    /// Anthropic Messages API (`summarize-api` feature)
+    Anthropic {
+        model: String,
+        /// Environment variable holding the API key
+        #[serde(default = "default_api_key_env")]
+        api_key_env: String,
+    },
+}
+
+fn default_api_key_env() -> String {
+    "ANTHROPIC_API_KEY".to_string()
+}
+
+impl Summarizer {
+    /// Summary of the session whose `entries` are given in chronological order
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the program or API fails or answers with nothing.
+    pub fn summarize(&self, entries: &[&SearchEntry]) -> Result<String> {
+        let input = session_input(entries);
+        let output = match self {
+            Summarizer::Command(argv) => run_command(argv, &input)?,
+            Summarizer::Anthropic { model, api_key_env } => {
+                request_anthropic(model, api_key_env, &input)?
+            }
+        };
+        first_line(&output).context("The summarizer returned an empty summary")
+    }
+}
+
+/// Instruction and the session's messages, each cut to [`MAX_MESSAGE_CHARS`]
+fn session_input(entries: &[&SearchEntry]) -> String {
+    let mut input = format!("{}\n", INSTRUCTION);
+    for entry in entries {
+        let role = match entry.entry_type {
+            EntryType::UserPrompt => "User",
+            EntryType::AgentMessage => "Assistant",
+        };
+        let text: String = entry.display_text.trim().chars().take(MAX_MESSAGE_CHARS).collect();
+        let message = format!("\n{}: {}\n", role, text);
+        if input.len() + message.len() > MAX_INPUT_CHARS {
+            break;
+        }
+        input.push_str(&message);
+    }
+    input
+}
+
+fn run_command(argv: &[String], input: &str) -> Result<String> {
+    let Some((program, args)) = argv.split_first() else {
+        bail!("The summarizer command is empty");
+    };
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run {}", program))?;
+    // A program that exits without reading its input closes the pipe; its output still counts
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(input.as_bytes());
+    }
+    let output = child.wait_with_output().with_context(|| format!("Failed to run {}", program))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.lines().find(|line| !line.trim().is_empty()).unwrap_or_default();
+        bail!("{} failed ({}): {}", program, output.status, reason.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(feature = "summarize-api")]
+fn request_anthropic(model: &str, api_key_env: &str, input: &str) -> Result<String> {
+    const URL: &str = "https://api.anthropic.com/v1/messages";
+    const API_VERSION: &str = "2023-06-01";
+    const MAX_TOKENS: u32 = 64;
+    const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+    let key = std::env::var(api_key_env).with_context(|| format!("{} is not set", api_key_env))?;
+    let agent: ureq::Agent =
+        ureq::Agent::config_builder().timeout_global(Some(TIMEOUT)).build().into();
+    let body = serde_json::json!({
+        "model": model,
+        "max_tokens": MAX_TOKENS,
+        "messages": [{ "role": "user", "content": input }],
+    });
+    let response: serde_json::Value = agent
+        .post(URL)
+        .header("x-api-key", &key)
+        .header("anthropic-version", API_VERSION)
+        .send_json(&body)
+        .context("Anthropic API request failed")?
+        .body_mut()
+        .read_json()
+        .context("Failed to read the Anthropic API response")?;
+    let text = response["content"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|block| block["type"] == "text")
+        .filter_map(|block| block["text"].as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    Ok(text)
+}
+
+#[cfg(not(feature = "summarize-api"))]
+fn request_anthropic(_model: &str, _api_key_env: &str, _input: &str) -> Result<String> {
+    bail!("The anthropic summarizer needs a build with the `summarize-api` feature")
+}
+
+/// First non-empty line of `output`, without escape codes and cut to [`MAX_SUMMARY_CHARS`]
+fn first_line(output: &str) -> Option<String> {
+    let line = output.lines().map(|line| strip_ansi_codes(line.trim())).find(|l| !l.is_empty())?;
+    let line = line.trim().trim_matches('"');
+    Some(match line.char_indices().nth(MAX_SUMMARY_CHARS - 1) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_string(),
+    })
+}
+
+/// Entries of each session in chronological order, the most recently active session first
+///
+/// Entries without a session id are left out.
+pub fn group_sessions(entries: &[SearchEntry]) -> Vec<Vec<&SearchEntry>> {
+    let mut sessions: HashMap<&str, Vec<&SearchEntry>> = HashMap::new();
+    for entry in entries.iter().filter(|e| !e.session_id.is_empty()) {
+        sessions.entry(&entry.session_id).or_default().push(entry);
+    }
+    let mut sessions: Vec<Vec<&SearchEntry>> = sessions.into_values().collect();
+    for session in &mut sessions {
+        session.sort_by_key(|entry| entry.timestamp);
+    }
+    sessions.sort_by_key(|session| std::cmp::Reverse(session.last().map(|e| e.timestamp)));
+    sessions
+}
+
+/// Summary of one session and the state of the session it was written for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedSummary {
+    pub summary: String,
+    /// Entries the session had
+    pub entries: usize,
+    /// Time of the session's last entry
+    pub last_entry: DateTime<Utc>,
+}
+
+/// Summaries by session id
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Summaries {
+    sessions: BTreeMap<String, SavedSummary>,
+}
+
+impl Summaries {
+    /// Default location of the summaries file, if the platform has a cache directory
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join(CACHE_DIR_NAME).join(SUMMARIES_FILE_NAME))
+    }
+
+    /// Read the summaries file; a missing file means no summaries
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed, so that saving
+    /// doesn't overwrite summaries that were only unreadable.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", path.display()));
+            }
+        };
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse summaries in {}", path.display()))
+    }
+
+    /// Write the summaries, creating their directory owner-only if needed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            create_private_dir_all(dir)?;
+        }
+        write_private_file(path, self.to_json()?)
+    }
+
+    /// The summaries as saved
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("Failed to serialize summaries")
+    }
+
+    pub fn get(&self, session_id: &str) -> Option<&str> {
+        self.sessions.get(session_id).map(|s| s.summary.as_str())
+    }
+
+    /// Whether the session of `entries` (in chronological order) has a summary written since
+    /// its last entry
+    pub fn is_current(&self, entries: &[&SearchEntry]) -> bool {
+        let (Some(first), Some(last)) = (entries.first(), entries.last()) else {
+            return false;
+        };
+        self.sessions.get(&first.session_id).is_some_and(|summary| {
+            summary.entries == entries.len() && summary.last_entry == last.timestamp
+        })
+    }
+
+    /// Record `summary` for the session of `entries` (in chronological order)
+    pub fn insert(&mut self, entries: &[&SearchEntry], summary: String) {
+        let (Some(first), Some(last)) = (entries.first(), entries.last()) else {
+            return;
+        };
+        let summary = SavedSummary { summary, entries: entries.len(), last_entry: last.timestamp };
+        self.sessions.insert(first.session_id.clone(), summary);
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(session: &str, text: &str, secs: i64) -> SearchEntry {
+        SearchEntry {
+            entry_type: if secs % 2 == 0 { EntryType::UserPrompt } else { EntryType::AgentMessage },
+            display_text: text.to_string(),
+            timestamp: DateTime::from_timestamp(1_704_099_600 + secs, 0).unwrap(),
+            session_id: session.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_group_sessions_newest_first() {
+        let entries = [
+            entry("a", "fix the parser", 2),
+            entry("b", "add a flag", 0),
+            entry("a", "done", 1),
+            entry("", "no session", 9),
+            entry("b", "ok", 5),
+        ];
+        let sessions = group_sessions(&entries);
+
+        let texts: Vec<Vec<&str>> =
+            sessions.iter().map(|s| s.iter().map(|e| e.display_text.as_str()).collect()).collect();
+        assert_eq!(texts, vec![vec!["add a flag", "ok"], vec!["done", "fix the parser"]]);
+    }
+
+    #[test]
+    fn test_session_input_labels_roles_and_stops_at_limit() {
+        let long = "x".repeat(MAX_MESSAGE_CHARS * 2);
+        let entries: Vec<SearchEntry> = (0..40).map(|i| entry("a", &long, i)).collect();
+        let refs: Vec<&SearchEntry> = entries.iter().collect();
+        let input = session_input(&refs);
+
+        assert!(input.starts_with(INSTRUCTION));
+        assert!(input.contains(&format!("\nUser: {}\n", "x".repeat(MAX_MESSAGE_CHARS))));
+        assert!(input.contains("\nAssistant: "));
+        assert!(input.len() <= MAX_INPUT_CHARS);
+    }
+
+    #[test]
+    fn test_first_line_cleans_output() {
+        assert_eq!(
+            first_line("\n  \"Fix the \u{1b}[1mparser\u{1b}[0m\"\nmore").unwrap(),
+            "Fix the parser"
+        );
+        assert_eq!(first_line(&"y".repeat(200)).unwrap().chars().count(), MAX_SUMMARY_CHARS);
+        assert!(first_line(" \n\n").is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_summarizer_reads_stdin() {
+        let entries = [entry("a", "fix the parser", 0)];
+        let refs: Vec<&SearchEntry> = entries.iter().collect();
+        let grep = Summarizer::Command(vec!["grep".into(), "-o".into(), "fix the [a-z]*".into()]);
+        assert_eq!(grep.summarize(&refs).unwrap(), "fix the parser");
+
+        let failing = Summarizer::Command(vec!["false".into()]);
+        assert!(failing.summarize(&refs).unwrap_err().to_string().contains("false failed"));
+        assert!(Summarizer::Command(Vec::new()).summarize(&refs).is_err());
+    }
+
+    #[test]
+    fn test_summaries_go_out_of_date_when_session_grows() {
+        let mut entries = vec![entry("a", "fix the parser", 0), entry("a", "done", 1)];
+        let mut summaries = Summaries::default();
+        {
+            let refs: Vec<&SearchEntry> = entries.iter().collect();
+            assert!(!summaries.is_current(&refs));
+            summaries.insert(&refs, "Parser fix".to_string());
+            assert!(summaries.is_current(&refs));
+        }
+        assert_eq!(summaries.get("a"), Some("Parser fix"));
+
+        entries.push(entry("a", "one more thing", 2));
+        let refs: Vec<&SearchEntry> = entries.iter().collect();
+        assert!(!summaries.is_current(&refs));
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("nested").join(SUMMARIES_FILE_NAME);
+        let entries = [entry("a", "fix the parser", 0)];
+        let refs: Vec<&SearchEntry> = entries.iter().collect();
+        let mut summaries = Summaries::default();
+        summaries.insert(&refs, "Parser fix".to_string());
+
+        summaries.save(&path).unwrap();
+        assert_eq!(Summaries::load(&path).unwrap(), summaries);
+        assert!(Summaries::load(&temp.path().join("missing.json")).unwrap().is_empty());
+        std::fs::write(&path, "not json").unwrap();
+        assert!(Summaries::load(&path).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+//! Sync bundle file format.
+//!
+//! A bundle is JSON Lines: a [`BundleHeader`] on the first line, then one
+//! [`SearchEntry`] per line, newest first.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::SearchEntry;
+use crate::utils::write_private_file;
+
+/// `format` of every bundle header
+pub const BUNDLE_FORMAT: &str = "ai-history-explorer-sync";
+
+/// Current bundle version; bundles with a newer version are rejected
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// First line of a bundle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleHeader {
+    pub format: String,
+    pub version: u32,
+    /// Machine the entries were indexed on
+    pub machine_id: String,
+    /// Position of the newest entry exported so far (`None` if nothing was ever exported)
+    pub cursor: Option<String>,
+    pub exported_at: DateTime<Utc>,
+}
+
+/// Entries exported from one machine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bundle {
+    pub header: BundleHeader,
+    pub entries: Vec<SearchEntry>,
+}
+
+impl Bundle {
+    pub fn new(machine_id: &str, cursor: Option<String>, entries: Vec<SearchEntry>) -> Self {
+        let header = BundleHeader {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            machine_id: machine_id.to_string(),
+            cursor,
+            exported_at: Utc::now(),
+        };
+        Self { header, entries }
+    }
+
+    pub fn to_jsonl(&self) -> Result<String> {
+        let mut out = serde_json::to_string(&self.header).context("Failed to serialize bundle")?;
+        out.push('\n');
+        for entry in &self.entries {
+            out.push_str(&serde_json::to_string(entry).context("Failed to serialize bundle")?);
+            out.push('\n');
+        }
+        Ok(out)
+    }
+
+    /// Parse a bundle written by [`Bundle::to_jsonl`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text isn't a bundle, comes from a newer version or has a
+    /// malformed entry (a truncated bundle is rejected rather than partially imported).
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+        let header: BundleHeader = lines
+            .next()
+            .and_then(|line| serde_json::from_str(line).ok())
+            .filter(|header: &BundleHeader| header.format == BUNDLE_FORMAT)
+            .context("Not a sync bundle")?;
+        if header.version > BUNDLE_VERSION {
+            bail!(
+                "Sync bundle version {} is newer than this version of ai-history-explorer \
+                 supports ({})",
+                header.version,
+                BUNDLE_VERSION
+            );
+        }
+        let entries = lines
+            .enumerate()
+            .map(|(i, line)| {
+                serde_json::from_str(line)
+                    .with_context(|| format!("Malformed entry on line {} of the bundle", i + 2))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { header, entries })
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("Failed to import {}", path.display()))
+    }
+
+    /// Write the bundle, readable by the owner only (it holds prompts and answers)
+    pub fn write(&self, path: &Path) -> Result<()> {
+        write_private_file(path, self.to_jsonl()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::EntryType;
+
+    fn entry(text: &str) -> SearchEntry {
+        SearchEntry {
+            entry_type: EntryType::UserPrompt,
+            display_text: text.to_string(),
+            timestamp: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            session_id: "s1".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let bundle = Bundle::new("m1", Some("abc".to_string()), vec![entry("a"), entry("b")]);
+        assert_eq!(Bundle::parse(&bundle.to_jsonl().unwrap()).unwrap(), bundle);
+    }
+
+    #[test]
+    fn test_rejects_other_files_and_newer_versions() {
+        let err = Bundle::parse(r#"{"display":"x"}"#).unwrap_err();
+        assert_eq!(err.to_string(), "Not a sync bundle");
+
+        let mut bundle = Bundle::new("m1", None, vec![entry("a")]);
+        bundle.header.version = BUNDLE_VERSION + 1;
+        let err = Bundle::parse(&bundle.to_jsonl().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("is newer than this version"));
+
+        let truncated = Bundle::new("m1", None, vec![entry("a")]).to_jsonl().unwrap();
+        let err = Bundle::parse(&truncated[..truncated.len() - 10]).unwrap_err();
+        assert_eq!(err.to_string(), "Malformed entry on line 2 of the bundle");
+    }
+}
@@ -0,0 +1,24 @@
+//! Differential history sync between machines, without a server.
+//!
+//! `sync export` writes the entries indexed on this machine since the last export to a
+//! bundle file; `sync import` merges a bundle from another machine into the local sync
+//! store. Bundles are plain files, so they can travel over rsync, a USB stick or a shared
+//! folder, and running both commands on both machines syncs history both ways.
+//!
+//! ```text
+//! <store>/machine-id                  random id of this machine
+//! <store>/state.json                  last export cursor, last imported cursor per machine
+//! <store>/imported/<machine id>.jsonl entries imported from that machine
+//! ```
+//!
+//! Positions in the history are [`Cursor`](crate::filters::Cursor)s, as used for paging
+//! `list`: an export remembers the newest entry it wrote and the next one starts after it, and
+//! an import skips what it already has from the same machine, so bundles can be imported
+//! more than once or out of order. Imported entries join the index of every command,
+//! attributed to their machine ([`SYNC_ROOT_PREFIX`]); they are never exported again.
+
+pub mod bundle;
+pub mod store;
+
+pub use bundle::{BUNDLE_FORMAT, BUNDLE_VERSION, Bundle, BundleHeader};
+pub use store::{ExportSummary, ImportSummary, SYNC_ROOT_PREFIX, SyncState, SyncStore};
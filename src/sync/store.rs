@@ -0,0 +1,458 @@
+//! Local sync store: this machine's id, sync cursors and imported entries.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use super::bundle::Bundle;
+use crate::filters::Cursor;
+use crate::indexer::IndexReport;
+use crate::models::SearchEntry;
+use crate::utils::{Plan, write_private_file};
+
+/// Prefix of the root name imported entries are attributed to, followed by the first
+/// characters of their machine id (e.g. `sync:3f2a9c1e`)
+pub const SYNC_ROOT_PREFIX: &str = "sync:";
+
+const MACHINE_ID_FILE: &str = "machine-id";
+const STATE_FILE: &str = "state.json";
+const IMPORTED_DIR: &str = "imported";
+
+/// Characters of the machine id shown in root names
+const SHORT_ID_CHARS: usize = 8;
+
+/// Cursors of past exports and imports
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncState {
+    /// Newest entry written by the last export
+    pub exported: Option<String>,
+    /// Newest entry imported so far, by machine id
+    #[serde(default)]
+    pub imported: BTreeMap<String, String>,
+}
+
+/// Outcome of [`SyncStore::export`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportSummary {
+    pub entries: usize,
+    /// Cursor the next export starts after
+    pub cursor: Option<String>,
+}
+
+/// Outcome of [`SyncStore::import`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub machine_id: String,
+    pub imported: usize,
+    /// Entries of the bundle that an earlier import already brought in
+    pub already_present: usize,
+}
+
+/// Sync store directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncStore {
+    dir: PathBuf,
+}
+
+impl SyncStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// `ai-history-explorer/sync` in the platform data directory, if there is one
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::data_local_dir().map(|dir| dir.join("ai-history-explorer").join("sync"))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Id of this machine, generated on first use
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the id file cannot be read or created.
+    pub fn machine_id(&self) -> Result<String> {
+        self.machine_id_impl(None)
+    }
+
+    /// Id of this machine, if it has one yet
+    fn existing_machine_id(&self) -> Result<Option<String>> {
+        let path = self.dir.join(MACHINE_ID_FILE);
+        match fs::read_to_string(&path) {
+            Ok(id) if !id.trim().is_empty() => Ok(Some(id.trim().to_string())),
+            Ok(_) => Ok(None),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    /// Id of this machine; a new id is saved, or recorded in `plan` when there is one
+    fn machine_id_impl(&self, plan: Option<&mut Plan>) -> Result<String> {
+        if let Some(id) = self.existing_machine_id()? {
+            return Ok(id);
+        }
+        let id = uuid::Uuid::new_v4().to_string();
+        self.write(&self.dir.join(MACHINE_ID_FILE), format!("{}\n", id), plan, "new machine id")?;
+        Ok(id)
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the state file exists but cannot be read or parsed.
+    pub fn state(&self) -> Result<SyncState> {
+        let path = self.dir.join(STATE_FILE);
+        match fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text)
+                .with_context(|| format!("Failed to parse {}", path.display())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(SyncState::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    fn save_state(&self, state: &SyncState, plan: Option<&mut Plan>) -> Result<()> {
+        let json = serde_json::to_string_pretty(state).context("Failed to serialize sync state")?;
+        self.write(&self.dir.join(STATE_FILE), json, plan, "sync cursors")
+    }
+
+    /// Write `contents` to `path` in the store, or record the write in `plan` when there is one
+    fn write(
+        &self,
+        path: &Path,
+        contents: String,
+        plan: Option<&mut Plan>,
+        note: &str,
+    ) -> Result<()> {
+        if let Some(plan) = plan {
+            plan.write_file(path, contents.len() as u64).note(note);
+            return Ok(());
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        write_private_file(path, contents)
+    }
+
+    /// Write the entries of `index` newer than `since` to a bundle at `output`
+    ///
+    /// Without `since`, the export continues after the previous one (or includes everything
+    /// the first time). Imported entries are left out, so history never bounces back to the
+    /// machine it came from. The export cursor is only advanced once the bundle is written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `since` is not a valid cursor or the bundle or state cannot be
+    /// written.
+    pub fn export(
+        &self,
+        index: &[SearchEntry],
+        since: Option<&str>,
+        output: &Path,
+    ) -> Result<ExportSummary> {
+        self.export_impl(index, since, output, None)
+    }
+
+    /// Like [`export`](Self::export), but records the files it would write in `plan` instead
+    /// of writing them
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `since` is not a valid cursor or the state cannot be read.
+    pub fn plan_export(
+        &self,
+        index: &[SearchEntry],
+        since: Option<&str>,
+        output: &Path,
+        plan: &mut Plan,
+    ) -> Result<ExportSummary> {
+        self.export_impl(index, since, output, Some(plan))
+    }
+
+    fn export_impl(
+        &self,
+        index: &[SearchEntry],
+        since: Option<&str>,
+        output: &Path,
+        mut plan: Option<&mut Plan>,
+    ) -> Result<ExportSummary> {
+        let mut state = self.state()?;
+        let since = match since.or(state.exported.as_deref()) {
+            Some(cursor) => Some(Cursor::decode(cursor)?),
+            None => None,
+        };
+        let entries: Vec<SearchEntry> = index
+            .iter()
+            .filter(|entry| !is_imported(entry))
+            .filter(|entry| since.as_ref().is_none_or(|since| Cursor::after(entry) > *since))
+            .cloned()
+            .collect();
+        let cursor = entries.iter().map(Cursor::after).max().or(since).map(|c| c.encode());
+
+        let bundle =
+            Bundle::new(&self.machine_id_impl(plan.as_deref_mut())?, cursor.clone(), entries);
+        match plan.as_deref_mut() {
+            Some(plan) => {
+                let size = bundle.to_jsonl()?.len() as u64;
+                plan.write_file(output, size).note(format!("{} entries", bundle.entries.len()));
+            }
+            None => bundle.write(output)?,
+        }
+        state.exported = cursor.clone();
+        self.save_state(&state, plan)?;
+        Ok(ExportSummary { entries: bundle.entries.len(), cursor })
+    }
+
+    /// Merge a bundle from another machine into the store
+    ///
+    /// Entries at or before the newest entry already imported from that machine are
+    /// skipped, so importing the same bundle twice changes nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bundle cannot be read, was exported on this machine, or the
+    /// store cannot be written.
+    pub fn import(&self, bundle: &Path) -> Result<ImportSummary> {
+        self.import_impl(bundle, None)
+    }
+
+    /// Like [`import`](Self::import), but records the files it would write in `plan` instead
+    /// of writing them
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bundle or the store cannot be read or the bundle was exported
+    /// on this machine.
+    pub fn plan_import(&self, bundle: &Path, plan: &mut Plan) -> Result<ImportSummary> {
+        self.import_impl(bundle, Some(plan))
+    }
+
+    fn import_impl(&self, bundle: &Path, mut plan: Option<&mut Plan>) -> Result<ImportSummary> {
+        let bundle = Bundle::read(bundle)?;
+        let machine_id = bundle.header.machine_id.clone();
+        // Without an id yet, no bundle can come from this machine
+        if self.existing_machine_id()?.is_some_and(|id| id == machine_id) {
+            bail!("This bundle was exported on this machine; import it on the other one");
+        }
+        if machine_id.is_empty() || machine_id.contains(['/', '\\', '.']) {
+            bail!("Invalid machine id in bundle: '{}'", machine_id);
+        }
+
+        let mut state = self.state()?;
+        let known = match state.imported.get(&machine_id) {
+            Some(cursor) => Some(Cursor::decode(cursor)?),
+            None => None,
+        };
+        let total = bundle.entries.len();
+        let root = format!("{}{}", SYNC_ROOT_PREFIX, short_id(&machine_id));
+        let mut new: Vec<SearchEntry> = bundle
+            .entries
+            .into_iter()
+            .filter(|entry| known.as_ref().is_none_or(|known| Cursor::after(entry) > *known))
+            .map(|mut entry| {
+                entry.root = Some(root.clone());
+                // The conversation file is on the other machine
+                entry.source_file = None;
+                entry
+            })
+            .collect();
+        let imported = new.len();
+        let newest = new.iter().map(Cursor::after).max();
+
+        if !new.is_empty() {
+            let path = self.imported_file(&machine_id);
+            let mut entries = read_entries(&path)?;
+            entries.append(&mut new);
+            entries.sort_by_key(|entry| std::cmp::Reverse(entry.timestamp));
+            let mut jsonl = String::new();
+            for entry in &entries {
+                jsonl.push_str(&serde_json::to_string(entry).context("Failed to serialize entry")?);
+                jsonl.push('\n');
+            }
+            let note = format!("{} entries, {} new", entries.len(), imported);
+            self.write(&path, jsonl, plan.as_deref_mut(), &note)?;
+        }
+        if let Some(newest) = newest.into_iter().chain(known).max() {
+            state.imported.insert(machine_id.clone(), newest.encode());
+        }
+        self.save_state(&state, plan)?;
+        Ok(ImportSummary { machine_id, imported, already_present: total - imported })
+    }
+
+    /// All imported entries, newest first
+    ///
+    /// Unreadable files are recorded in `report` and skipped.
+    pub fn imported_entries(&self, report: &mut IndexReport) -> Vec<SearchEntry> {
+        let Ok(dir) = fs::read_dir(self.dir.join(IMPORTED_DIR)) else {
+            return Vec::new();
+        };
+        let mut entries = Vec::new();
+        for path in dir.filter_map(|item| item.ok()).map(|item| item.path()) {
+            if path.extension().is_none_or(|ext| ext != "jsonl") {
+                continue;
+            }
+            match read_entries(&path) {
+                Ok(mut imported) => entries.append(&mut imported),
+                Err(e) => report.record_skip(&path, format!("{:#}", e)),
+            }
+        }
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.timestamp));
+        entries
+    }
+
+    /// Add the imported entries to `index`, keeping it sorted newest first
+    pub fn merge_into(&self, index: &mut Vec<SearchEntry>, report: &mut IndexReport) {
+        let imported = self.imported_entries(report);
+        if !imported.is_empty() {
+            index.extend(imported);
+            index.sort_by_key(|entry| std::cmp::Reverse(entry.timestamp));
+        }
+    }
+
+    fn imported_file(&self, machine_id: &str) -> PathBuf {
+        self.dir.join(IMPORTED_DIR).join(format!("{}.jsonl", machine_id))
+    }
+}
+
+/// Whether `entry` was imported from another machine
+pub fn is_imported(entry: &SearchEntry) -> bool {
+    entry.root.as_deref().is_some_and(|root| root.starts_with(SYNC_ROOT_PREFIX))
+}
+
+fn short_id(machine_id: &str) -> String {
+    machine_id.chars().take(SHORT_ID_CHARS).collect()
+}
+
+fn read_entries(path: &Path) -> Result<Vec<SearchEntry>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line)
+                .with_context(|| format!("Malformed imported entry in {}", path.display()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::models::EntryType;
+
+    fn entry(uuid: &str, secs: i64) -> SearchEntry {
+        SearchEntry {
+            entry_type: EntryType::AgentMessage,
+            display_text: uuid.to_string(),
+            timestamp: DateTime::from_timestamp(secs, 0).unwrap(),
+            session_id: "s1".to_string(),
+            source_file: Some(PathBuf::from("/home/a/.claude/projects/p/s1.jsonl")),
+            uuid: Some(uuid.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn texts(entries: &[SearchEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.display_text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_machine_id_is_stable() {
+        let temp = TempDir::new().unwrap();
+        let store = SyncStore::new(temp.path());
+        let id = store.machine_id().unwrap();
+        assert_eq!(store.machine_id().unwrap(), id);
+        assert_ne!(SyncStore::new(temp.path().join("other")).machine_id().unwrap(), id);
+    }
+
+    #[test]
+    fn test_export_continues_after_the_previous_one() {
+        let temp = TempDir::new().unwrap();
+        let store = SyncStore::new(temp.path().join("store"));
+        let bundle = temp.path().join("bundle.jsonl");
+        let mut index = vec![entry("b", 200), entry("a", 100)];
+
+        let first = store.export(&index, None, &bundle).unwrap();
+        assert_eq!(first.entries, 2);
+        let since = first.cursor.clone().unwrap();
+
+        index.insert(0, entry("c", 300));
+        let mut imported = entry("from-elsewhere", 400);
+        imported.root = Some(format!("{}1234", SYNC_ROOT_PREFIX));
+        index.insert(0, imported);
+        let second = store.export(&index, None, &bundle).unwrap();
+        assert_eq!(second.entries, 1);
+        assert_eq!(texts(&Bundle::read(&bundle).unwrap().entries), vec!["c"]);
+
+        // An explicit cursor overrides the stored one; nothing new keeps the cursor
+        assert_eq!(store.export(&index, Some(&since), &bundle).unwrap().entries, 1);
+        let empty = store.export(&index, None, &bundle).unwrap();
+        assert_eq!(empty.entries, 0);
+        assert_eq!(empty.cursor, second.cursor);
+    }
+
+    #[test]
+    fn test_import_skips_entries_it_already_has() {
+        let temp = TempDir::new().unwrap();
+        let laptop = SyncStore::new(temp.path().join("laptop"));
+        let desktop = SyncStore::new(temp.path().join("desktop"));
+        let first = temp.path().join("first.jsonl");
+        let second = temp.path().join("second.jsonl");
+        laptop.export(&[entry("b", 200), entry("a", 100)], None, &first).unwrap();
+        laptop.export(&[entry("c", 300), entry("b", 200)], Some("MTAwMDAwOmE"), &second).unwrap();
+
+        let summary = desktop.import(&first).unwrap();
+        assert_eq!((summary.imported, summary.already_present), (2, 0));
+        let summary = desktop.import(&second).unwrap();
+        assert_eq!((summary.imported, summary.already_present), (1, 1));
+        assert_eq!(desktop.import(&first).unwrap().imported, 0);
+
+        let mut report = IndexReport::default();
+        let imported = desktop.imported_entries(&mut report);
+        assert_eq!(texts(&imported), vec!["c", "b", "a"]);
+        let root = format!("{}{}", SYNC_ROOT_PREFIX, &laptop.machine_id().unwrap()[..8]);
+        assert!(
+            imported.iter().all(|e| e.root.as_deref() == Some(&root) && e.source_file.is_none())
+        );
+
+        let err = laptop.import(&first).unwrap_err();
+        assert!(err.to_string().contains("exported on this machine"));
+    }
+
+    #[test]
+    fn test_plans_write_nothing() {
+        let temp = TempDir::new().unwrap();
+        let laptop = SyncStore::new(temp.path().join("laptop"));
+        let desktop = SyncStore::new(temp.path().join("desktop"));
+        let bundle = temp.path().join("bundle.jsonl");
+        let index = [entry("b", 200), entry("a", 100)];
+
+        let mut plan = Plan::default();
+        let planned = laptop.plan_export(&index, None, &bundle, &mut plan).unwrap();
+        assert_eq!(planned.entries, 2);
+        let paths: Vec<_> = plan.changes.iter().map(|c| c.path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![laptop.dir.join(MACHINE_ID_FILE), bundle.clone(), laptop.dir.join(STATE_FILE)]
+        );
+        assert!(!laptop.dir.exists() && !bundle.exists());
+
+        laptop.export(&index, None, &bundle).unwrap();
+        let mut plan = Plan::default();
+        assert_eq!(desktop.plan_import(&bundle, &mut plan).unwrap().imported, 2);
+        assert_eq!(plan.changes.len(), 2);
+        assert!(!desktop.dir.exists());
+        assert_eq!(desktop.import(&bundle).unwrap().imported, 2);
+    }
+}
@@ -0,0 +1,162 @@
+//! Activity calendar popup state.
+//!
+//! Counts entries per day in the display zone (the zone timestamps are shown in, so a day
+//! matches its `on:DATE` filter) and lays the days out as a heatmap: one column per week
+//! (Monday-based, oldest on the left) and one row per weekday. Days without entries are kept
+//! to show gaps. Picking a day filters the results to it with `on:DATE`.
+
+use chrono::{Duration, NaiveDate};
+
+use super::minimap::week_start;
+use crate::models::SearchEntry;
+use crate::utils::{DisplayZone, display_zone};
+
+/// Number of shades a day can have, including the one for days without entries
+pub const ACTIVITY_LEVELS: usize = 5;
+
+/// State of the activity calendar popup
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActivityCalendar {
+    /// Monday of the oldest week
+    pub first_day: NaiveDate,
+    /// Entries per day, from `first_day` to the newest day with entries
+    pub counts: Vec<usize>,
+    /// Selected day, as a position in `counts`
+    pub selected: usize,
+}
+
+impl ActivityCalendar {
+    /// Calendar of `entries`, with the newest day selected
+    pub fn build(entries: &[&SearchEntry]) -> Self {
+        Self::build_in(entries, display_zone())
+    }
+
+    /// [`ActivityCalendar::build`] with days in `zone`
+    pub fn build_in(entries: &[&SearchEntry], zone: DisplayZone) -> Self {
+        let days: Vec<NaiveDate> = entries.iter().map(|e| zone.date(&e.timestamp)).collect();
+        let (Some(&newest), Some(&oldest)) = (days.iter().max(), days.iter().min()) else {
+            return Self::default();
+        };
+
+        let first_day = week_start(oldest);
+        let mut counts = vec![0; (newest - first_day).num_days() as usize + 1];
+        for day in days {
+            counts[(day - first_day).num_days() as usize] += 1;
+        }
+        let selected = counts.len() - 1;
+        Self { first_day, counts, selected }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    pub fn weeks(&self) -> usize {
+        self.counts.len().div_ceil(7)
+    }
+
+    pub fn date(&self, day: usize) -> NaiveDate {
+        self.first_day + Duration::days(day as i64)
+    }
+
+    pub fn selected_date(&self) -> Option<NaiveDate> {
+        (!self.is_empty()).then(|| self.date(self.selected))
+    }
+
+    /// Position in `counts` of the weekday (0 = Monday) in the week column, if recorded
+    pub fn day_at(&self, week: usize, weekday: usize) -> Option<usize> {
+        let day = week * 7 + weekday;
+        (day < self.counts.len()).then_some(day)
+    }
+
+    /// Move by `delta` days (7 for a week), staying within the recorded days
+    pub fn move_selection(&mut self, delta: isize) {
+        let max = self.counts.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(max);
+    }
+
+    /// Shade of a day: 0 without entries, else 1 to `ACTIVITY_LEVELS - 1` relative to the
+    /// busiest day
+    pub fn level(&self, day: usize) -> usize {
+        let count = self.counts.get(day).copied().unwrap_or(0);
+        let max = self.counts.iter().copied().max().unwrap_or(0);
+        if count == 0 {
+            return 0;
+        }
+        (count * (ACTIVITY_LEVELS - 1)).div_ceil(max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeZone, Utc};
+
+    use super::*;
+    use crate::models::EntryType;
+
+    fn entry(month: u32, day: u32, hour: u32) -> SearchEntry {
+        let timestamp: DateTime<Utc> = Utc.with_ymd_and_hms(2024, month, day, hour, 0, 0).unwrap();
+        SearchEntry {
+            entry_type: EntryType::UserPrompt,
+            display_text: "prompt".to_string(),
+            timestamp,
+            session_id: "s1".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_counts_per_day_from_monday_of_oldest_week() {
+        // Wednesday 2024-01-03 to Tuesday 2024-01-09
+        let entries = [entry(1, 9, 8), entry(1, 3, 10), entry(1, 3, 23), entry(1, 9, 0)];
+        let refs: Vec<&SearchEntry> = entries.iter().collect();
+        let calendar = ActivityCalendar::build(&refs);
+
+        assert_eq!(calendar.first_day, NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
+        assert_eq!(calendar.counts, vec![0, 0, 2, 0, 0, 0, 0, 0, 2]);
+        assert_eq!(calendar.weeks(), 2);
+        assert_eq!(calendar.selected_date(), NaiveDate::from_ymd_opt(2024, 1, 9));
+        assert_eq!(calendar.day_at(1, 1), Some(8));
+        assert_eq!(calendar.day_at(1, 2), None);
+    }
+
+    #[test]
+    fn test_levels_relative_to_busiest_day() {
+        let entries = [entry(1, 1, 1), entry(1, 2, 1), entry(1, 2, 2), entry(1, 2, 3)];
+        let refs: Vec<&SearchEntry> = entries.iter().collect();
+        let mut calendar = ActivityCalendar::build(&refs);
+        calendar.counts.push(0);
+
+        assert_eq!(calendar.level(0), 2);
+        assert_eq!(calendar.level(1), ACTIVITY_LEVELS - 1);
+        assert_eq!(calendar.level(2), 0);
+    }
+
+    #[test]
+    fn test_move_selection_clamps() {
+        let entries = [entry(1, 1, 1), entry(1, 20, 1)];
+        let refs: Vec<&SearchEntry> = entries.iter().collect();
+        let mut calendar = ActivityCalendar::build(&refs);
+
+        calendar.move_selection(7);
+        assert_eq!(calendar.selected, 19);
+        calendar.move_selection(-7);
+        assert_eq!(calendar.selected, 12);
+        calendar.move_selection(-100);
+        assert_eq!(calendar.selected_date(), NaiveDate::from_ymd_opt(2024, 1, 1));
+        assert!(ActivityCalendar::build(&[]).selected_date().is_none());
+    }
+
+    #[test]
+    fn test_days_follow_display_zone() {
+        // 04:00 UTC on Jan 16 is still Jan 15 in Los Angeles
+        let entries = [entry(1, 15, 12), entry(1, 16, 4)];
+        let refs: Vec<&SearchEntry> = entries.iter().collect();
+        let pacific = DisplayZone::parse("America/Los_Angeles").unwrap();
+
+        let calendar = ActivityCalendar::build_in(&refs, pacific);
+        assert_eq!(calendar.counts, vec![2]);
+        assert_eq!(calendar.selected_date(), NaiveDate::from_ymd_opt(2024, 1, 15));
+        assert_eq!(ActivityCalendar::build_in(&refs, DisplayZone::Utc).counts, vec![1, 1]);
+    }
+}
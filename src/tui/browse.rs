@@ -0,0 +1,278 @@
+//! Session list and project overview popup state.
+//!
+//! Groups the indexed entries by session or by project, most recently active first. Picking
+//! a session selects its newest entry in the results; picking a project filters the results
+//! to it. Projects can also be marked to scope the search to several of them at once. Each row
+//! shows how many of the current results belong to it, so it is clear what picking it would
+//! leave.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+
+use crate::export::timeline::condense;
+use crate::models::{EntryType, SearchEntry};
+use crate::utils::PathDisplay;
+
+/// Longest session title (its first prompt), in characters
+const MAX_TITLE_CHARS: usize = 100;
+
+/// Whether the popup lists sessions or projects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrowseKind {
+    Sessions,
+    Projects,
+}
+
+/// What a row stands for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BrowseTarget {
+    Session(String),
+    Project(PathBuf),
+}
+
+/// One session or project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrowseRow {
+    pub target: BrowseTarget,
+    pub label: String,
+    /// Secondary text, e.g. the project of a session and its entry count
+    pub detail: String,
+    pub last_active: DateTime<Utc>,
+    /// Entries of the session or project among the current results
+    pub matches: usize,
+}
+
+/// State of the session list / project overview popup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrowseView {
+    pub kind: BrowseKind,
+    pub rows: Vec<BrowseRow>,
+    pub selected: usize,
+    /// Projects marked to scope the search to
+    pub marked: HashSet<PathBuf>,
+}
+
+impl BrowseView {
+    /// Rows for all `entries`, with match counts from the current results `matched`
+    pub fn new(
+        kind: BrowseKind,
+        entries: &[SearchEntry],
+        matched: &[&SearchEntry],
+        paths: &PathDisplay,
+    ) -> Self {
+        let mut rows = match kind {
+            BrowseKind::Sessions => {
+                let matches = count_by(matched, |e| Some(e.session_id.as_str()));
+                session_rows(entries, &matches, paths)
+            }
+            BrowseKind::Projects => {
+                let matches = count_by(matched, |e| e.project_path.as_ref());
+                project_rows(entries, &matches, paths)
+            }
+        };
+        rows.sort_by_key(|row| std::cmp::Reverse(row.last_active));
+        Self { kind, rows, selected: 0, marked: HashSet::new() }
+    }
+
+    /// Mark or unmark the selected project (sessions can't be marked)
+    pub fn toggle_mark(&mut self) {
+        let Some(BrowseTarget::Project(project)) = self.selected_row().map(|row| &row.target)
+        else {
+            return;
+        };
+        let project = project.clone();
+        if !self.marked.remove(&project) {
+            self.marked.insert(project);
+        }
+    }
+
+    pub fn is_marked(&self, row: &BrowseRow) -> bool {
+        matches!(&row.target, BrowseTarget::Project(project) if self.marked.contains(project))
+    }
+
+    pub fn selected_row(&self) -> Option<&BrowseRow> {
+        self.rows.get(self.selected)
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let max = self.rows.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(max);
+    }
+}
+
+/// Number of entries per value of `key` (entries without a value are skipped)
+fn count_by<'a, K: Eq + Hash>(
+    entries: &[&'a SearchEntry],
+    key: impl Fn(&'a SearchEntry) -> Option<K>,
+) -> HashMap<K, usize> {
+    let mut counts = HashMap::new();
+    for entry in entries {
+        if let Some(value) = key(entry) {
+            *counts.entry(value).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+fn session_rows(
+    entries: &[SearchEntry],
+    matches: &HashMap<&str, usize>,
+    paths: &PathDisplay,
+) -> Vec<BrowseRow> {
+    let mut sessions: HashMap<&str, Vec<&SearchEntry>> = HashMap::new();
+    for entry in entries {
+        sessions.entry(&entry.session_id).or_default().push(entry);
+    }
+
+    sessions
+        .into_iter()
+        .map(|(session_id, mut entries)| {
+            entries.sort_by_key(|e| e.timestamp);
+            let title = entries
+                .iter()
+                .find(|e| e.entry_type == EntryType::UserPrompt)
+                .map(|e| condense(&e.display_text, MAX_TITLE_CHARS))
+                .unwrap_or_else(|| "(no prompts)".to_string());
+            let project = entries
+                .iter()
+                .find_map(|e| e.project_path.as_deref())
+                .map(|p| paths.format(p))
+                .unwrap_or_else(|| "no project".to_string());
+            BrowseRow {
+                target: BrowseTarget::Session(session_id.to_string()),
+                label: title,
+                detail: format!("{} · {} entries", project, entries.len()),
+                last_active: entries.last().map(|e| e.timestamp).unwrap_or_default(),
+                matches: matches.get(session_id).copied().unwrap_or(0),
+            }
+        })
+        .collect()
+}
+
+fn project_rows(
+    entries: &[SearchEntry],
+    matches: &HashMap<&PathBuf, usize>,
+    paths: &PathDisplay,
+) -> Vec<BrowseRow> {
+    let mut projects: HashMap<&PathBuf, Vec<&SearchEntry>> = HashMap::new();
+    for entry in entries {
+        if let Some(project) = &entry.project_path {
+            projects.entry(project).or_default().push(entry);
+        }
+    }
+
+    projects
+        .into_iter()
+        .map(|(project, entries)| {
+            let mut sessions: Vec<&str> = entries.iter().map(|e| e.session_id.as_str()).collect();
+            sessions.sort_unstable();
+            sessions.dedup();
+            BrowseRow {
+                target: BrowseTarget::Project(project.clone()),
+                label: paths.format(project),
+                detail: format!("{} sessions · {} entries", sessions.len(), entries.len()),
+                last_active: entries.iter().map(|e| e.timestamp).max().unwrap_or_default(),
+                matches: matches.get(project).copied().unwrap_or(0),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::WorkspaceRoot;
+
+    fn entry(entry_type: EntryType, session: &str, project: &str, millis: i64) -> SearchEntry {
+        SearchEntry {
+            entry_type,
+            display_text: format!("{} text", session),
+            timestamp: DateTime::from_timestamp_millis(millis).unwrap(),
+            project_path: Some(PathBuf::from(project)),
+            session_id: session.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn entries() -> Vec<SearchEntry> {
+        vec![
+            entry(EntryType::UserPrompt, "s1", "/work/app", 1_000),
+            entry(EntryType::AgentMessage, "s1", "/work/app", 4_000),
+            entry(EntryType::AgentMessage, "s2", "/work/lib", 2_000),
+            entry(EntryType::UserPrompt, "s3", "/work/app", 3_000),
+        ]
+    }
+
+    #[test]
+    fn test_sessions_newest_first() {
+        let entries = entries();
+        let view = BrowseView::new(BrowseKind::Sessions, &entries, &[], &PathDisplay::default());
+
+        let targets: Vec<_> = view.rows.iter().map(|r| r.target.clone()).collect();
+        assert_eq!(
+            targets,
+            vec![
+                BrowseTarget::Session("s1".to_string()),
+                BrowseTarget::Session("s3".to_string()),
+                BrowseTarget::Session("s2".to_string()),
+            ]
+        );
+        assert_eq!(view.rows[0].label, "s1 text");
+        assert_eq!(view.rows[0].detail, "/work/app · 2 entries");
+        assert_eq!(view.rows[2].label, "(no prompts)");
+    }
+
+    #[test]
+    fn test_projects_count_sessions() {
+        let paths = PathDisplay::workspace(vec![WorkspaceRoot {
+            path: PathBuf::from("/work"),
+            label: None,
+        }]);
+        let mut view = BrowseView::new(BrowseKind::Projects, &entries(), &[], &paths);
+
+        assert_eq!(view.rows.len(), 2);
+        assert_eq!(view.rows[0].label, "app");
+        assert_eq!(view.rows[0].detail, "2 sessions · 3 entries");
+        view.move_selection(5);
+        assert_eq!(view.selected_row().unwrap().label, "lib");
+    }
+
+    #[test]
+    fn test_toggle_mark_projects_only() {
+        let entries = entries();
+        let mut projects =
+            BrowseView::new(BrowseKind::Projects, &entries, &[], &PathDisplay::default());
+        projects.toggle_mark();
+        projects.move_selection(1);
+        projects.toggle_mark();
+        assert_eq!(projects.marked.len(), 2);
+        projects.toggle_mark();
+        assert_eq!(projects.marked, HashSet::from([PathBuf::from("/work/app")]));
+        assert!(projects.is_marked(&projects.rows[0]));
+        assert!(!projects.is_marked(&projects.rows[1]));
+
+        let mut sessions =
+            BrowseView::new(BrowseKind::Sessions, &entries, &[], &PathDisplay::default());
+        sessions.toggle_mark();
+        assert!(sessions.marked.is_empty());
+    }
+
+    #[test]
+    fn test_rows_count_current_matches() {
+        let entries = entries();
+        let matched = [&entries[0], &entries[1], &entries[2]];
+
+        let projects =
+            BrowseView::new(BrowseKind::Projects, &entries, &matched, &PathDisplay::default());
+        let counts: Vec<_> = projects.rows.iter().map(|r| (r.label.as_str(), r.matches)).collect();
+        assert_eq!(counts, vec![("/work/app", 2), ("/work/lib", 1)]);
+
+        let sessions =
+            BrowseView::new(BrowseKind::Sessions, &entries, &matched, &PathDisplay::default());
+        let counts: Vec<_> = sessions.rows.iter().map(|r| r.matches).collect();
+        assert_eq!(counts, vec![2, 0, 1]);
+    }
+}
@@ -0,0 +1,368 @@
+//! Index cache management popup state.
+//!
+//! Shows where the index cache lives, how large it is and how many entries it holds per
+//! Claude root, with actions to clear, rebuild or compact it. Actions run on a background
+//! thread, so the TUI stays responsive while a rebuild re-parses every conversation file.
+//! The same handle re-indexes the roots when the TUI refreshes its entries (Ctrl+R or watch
+//! mode); only files changed since they were cached are parsed again.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+
+use crate::cache::IndexCache;
+use crate::indexer::{
+    ClaudeRoot, Diagnostic, HistorySource, IndexReport, RootStatus, SkippedItem,
+    apply_project_merges, build_index_with_cache, build_workspace_index_with_cache, merge_sources,
+};
+use crate::models::SearchEntry;
+use crate::parsers::ParseOptions;
+use crate::sync::SyncStore;
+use crate::utils::format_path_with_tilde;
+
+/// Name of the row counting cached files outside every current root
+const OTHER_SOURCE_NAME: &str = "(other)";
+
+/// Maintenance action on the index cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheJob {
+    Clear,
+    Rebuild,
+    Compact,
+}
+
+impl CacheJob {
+    /// All actions, in the order they are listed
+    pub const ALL: [CacheJob; 3] = [CacheJob::Clear, CacheJob::Rebuild, CacheJob::Compact];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CacheJob::Clear => "Clear",
+            CacheJob::Rebuild => "Rebuild",
+            CacheJob::Compact => "Compact",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            CacheJob::Clear => "Delete the cache; files are parsed again on the next start",
+            CacheJob::Rebuild => "Re-parse every conversation file into a fresh cache",
+            CacheJob::Compact => "Drop files that no longer exist and rewrite the cache file",
+        }
+    }
+}
+
+/// Cached files and entries of one Claude root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceCount {
+    pub name: String,
+    pub files: usize,
+    pub entries: usize,
+}
+
+/// State of the cache at the time the popup was opened or a job finished
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheSummary {
+    /// Directory and kind of location, e.g. `~/.cache/ai-history-explorer (platform ...)`
+    pub location: String,
+    /// Size of the cache file (0 when not persisted)
+    pub size_bytes: u64,
+    /// Per root, in root order; files outside every root are counted last
+    pub sources: Vec<SourceCount>,
+    pub last_saved: Option<DateTime<Local>>,
+}
+
+/// A fresh index of the roots, for refreshing the TUI's entries
+#[derive(Debug, Clone, Default)]
+pub struct Reindexed {
+    pub entries: Vec<SearchEntry>,
+    /// Per-root statuses (empty unless the roots form a workspace)
+    pub roots: Vec<RootStatus>,
+    pub warnings: Vec<SkippedItem>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// The index cache and the roots it is built from, shared with background jobs
+#[derive(Clone)]
+pub struct CacheControl {
+    cache: Arc<IndexCache>,
+    roots: Vec<ClaudeRoot>,
+    /// Whether entries are indexed as a multi-root workspace (attributed to their root)
+    workspace: bool,
+    /// Store of the entries imported from other machines
+    sync_store: Option<SyncStore>,
+    /// Project paths shown as another project
+    project_merges: BTreeMap<PathBuf, PathBuf>,
+    /// Histories of other tools added to the index
+    sources: Vec<Arc<dyn HistorySource>>,
+}
+
+impl CacheControl {
+    pub fn new(cache: Arc<IndexCache>, roots: Vec<ClaudeRoot>) -> Self {
+        Self {
+            cache,
+            roots,
+            workspace: false,
+            sync_store: None,
+            project_merges: BTreeMap::new(),
+            sources: Vec::new(),
+        }
+    }
+
+    /// Re-index the roots as a workspace, like `--claude-dir` does
+    /// Parse options of the index cache
+    pub fn parse_options(&self) -> &ParseOptions {
+        self.cache.options()
+    }
+
+    pub fn for_workspace(mut self) -> Self {
+        self.workspace = true;
+        self
+    }
+
+    /// Include the entries imported into `store` when re-indexing
+    pub fn with_sync_store(mut self, store: SyncStore) -> Self {
+        self.sync_store = Some(store);
+        self
+    }
+
+    /// Merge renamed projects when re-indexing
+    pub fn with_project_merges(mut self, merges: BTreeMap<PathBuf, PathBuf>) -> Self {
+        self.project_merges = merges;
+        self
+    }
+
+    /// Add the entries of other tools when re-indexing
+    pub fn with_sources(mut self, sources: Vec<Arc<dyn HistorySource>>) -> Self {
+        self.sources = sources;
+        self
+    }
+
+    pub fn roots(&self) -> &[ClaudeRoot] {
+        &self.roots
+    }
+
+    pub fn summary(&self) -> CacheSummary {
+        let dir = self.cache.dir();
+        let location = if !IndexCache::persistence_enabled() {
+            "in memory only (built without the `cache` feature)".to_string()
+        } else {
+            match dir.location.path() {
+                Some(path) => {
+                    format!("{} ({})", format_path_with_tilde(path), dir.location.label())
+                }
+                None => dir.location.label().to_string(),
+            }
+        };
+
+        let mut sources: Vec<SourceCount> = self
+            .roots
+            .iter()
+            .map(|root| SourceCount { name: root.name.clone(), files: 0, entries: 0 })
+            .collect();
+        let mut other = SourceCount { name: OTHER_SOURCE_NAME.to_string(), files: 0, entries: 0 };
+        for (path, entries) in self.cache.entry_counts() {
+            let source = match self.roots.iter().position(|root| path.starts_with(&root.path)) {
+                Some(i) => &mut sources[i],
+                None => &mut other,
+            };
+            source.files += 1;
+            source.entries += entries;
+        }
+        if other.files > 0 {
+            sources.push(other);
+        }
+
+        CacheSummary {
+            location,
+            size_bytes: self
+                .cache
+                .index_file()
+                .and_then(|path| std::fs::metadata(path).ok())
+                .map_or(0, |m| m.len()),
+            sources,
+            last_saved: self.cache.last_saved().map(DateTime::from),
+        }
+    }
+
+    /// Run `job` on a background thread; the receiver gets its outcome as a status message
+    pub fn start(&self, job: CacheJob) -> Receiver<Result<String>> {
+        let (sender, receiver) = mpsc::channel();
+        let control = self.clone();
+        thread::spawn(move || {
+            // The TUI may have quit and dropped the receiver; nothing left to report to
+            let _ = sender.send(control.run(job));
+        });
+        receiver
+    }
+
+    /// Re-index the roots on a background thread
+    pub fn start_reindex(&self) -> Receiver<Result<Reindexed>> {
+        let (sender, receiver) = mpsc::channel();
+        let control = self.clone();
+        thread::spawn(move || {
+            let _ = sender.send(control.reindex());
+        });
+        receiver
+    }
+
+    /// Index the roots again, reusing the cached files that didn't change
+    pub fn reindex(&self) -> Result<Reindexed> {
+        let mut report = IndexReport::default();
+        let (mut entries, roots) = if self.workspace {
+            build_workspace_index_with_cache(&self.roots, &self.cache, &mut report)?
+        } else {
+            let mut entries = Vec::new();
+            for root in self.roots.iter().filter(|root| root.path.exists()) {
+                entries.extend(build_index_with_cache(&root.path, &self.cache, &mut report)?);
+            }
+            (entries, Vec::new())
+        };
+        merge_sources(&mut entries, &self.sources, self.cache.options(), &mut report);
+        if let Some(store) = &self.sync_store {
+            store.merge_into(&mut entries, &mut report);
+        }
+        apply_project_merges(&mut entries, &self.project_merges);
+        self.cache.save()?;
+        Ok(Reindexed { entries, roots, warnings: report.skipped, diagnostics: report.diagnostics })
+    }
+
+    fn run(&self, job: CacheJob) -> Result<String> {
+        match job {
+            CacheJob::Clear => {
+                self.cache.clear()?;
+                Ok("✓ Cache cleared".to_string())
+            }
+            CacheJob::Rebuild => {
+                self.cache.clear()?;
+                let mut report = IndexReport::default();
+                for root in self.roots.iter().filter(|root| root.path.exists()) {
+                    build_index_with_cache(&root.path, &self.cache, &mut report)?;
+                }
+                self.cache.save()?;
+                Ok(format!("✓ Cache rebuilt: {} files", self.cache.len()))
+            }
+            CacheJob::Compact => {
+                let dropped = self.cache.compact()?;
+                Ok(format!("✓ Cache compacted: {} stale files dropped", dropped))
+            }
+        }
+    }
+}
+
+/// State of the cache management popup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheScreen {
+    pub summary: CacheSummary,
+    /// Index into [`CacheJob::ALL`]
+    pub selected: usize,
+    /// Job running in the background, if any
+    pub running: Option<CacheJob>,
+}
+
+impl CacheScreen {
+    pub fn new(summary: CacheSummary, running: Option<CacheJob>) -> Self {
+        Self { summary, selected: 0, running }
+    }
+
+    pub fn selected_job(&self) -> CacheJob {
+        CacheJob::ALL[self.selected]
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let max = CacheJob::ALL.len() - 1;
+        self.selected = self.selected.saturating_add_signed(delta).min(max);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::time::Duration;
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::cache::{CacheDir, IndexedFile};
+
+    fn control(temp: &TempDir) -> CacheControl {
+        let cache = IndexCache::open(
+            CacheDir::resolve_from(Some(temp.path().join("cache")), temp.path().join("tmp")),
+            ParseOptions::default(),
+        );
+        let roots = vec![
+            ClaudeRoot::new("laptop", temp.path().join("laptop")),
+            ClaudeRoot::new("desktop", temp.path().join("desktop")),
+        ];
+        CacheControl::new(Arc::new(cache), roots)
+    }
+
+    fn cache_file(control: &CacheControl, path: &std::path::Path) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "{}").unwrap();
+        control.cache.get_or_index(path, || Ok(IndexedFile::default())).unwrap();
+    }
+
+    #[test]
+    fn test_summary_counts_files_per_root() {
+        let temp = TempDir::new().unwrap();
+        let control = control(&temp);
+        cache_file(&control, &temp.path().join("laptop/projects/a.jsonl"));
+        cache_file(&control, &temp.path().join("laptop/projects/b.jsonl"));
+        cache_file(&control, &temp.path().join("elsewhere/c.jsonl"));
+
+        let summary = control.summary();
+
+        let files: Vec<_> = summary.sources.iter().map(|s| (s.name.as_str(), s.files)).collect();
+        assert_eq!(files, vec![("laptop", 2), ("desktop", 0), ("(other)", 1)]);
+    }
+
+    #[test]
+    fn test_clear_job_runs_in_background() {
+        let temp = TempDir::new().unwrap();
+        let control = control(&temp);
+        cache_file(&control, &temp.path().join("laptop/projects/a.jsonl"));
+
+        let outcome = control.start(CacheJob::Clear).recv_timeout(Duration::from_secs(10));
+
+        assert_eq!(outcome.unwrap().unwrap(), "✓ Cache cleared");
+        assert!(control.cache.is_empty());
+    }
+
+    #[test]
+    fn test_reindex_picks_up_new_history() {
+        let temp = TempDir::new().unwrap();
+        let control = control(&temp).for_workspace();
+        let laptop = temp.path().join("laptop");
+        fs::create_dir_all(&laptop).unwrap();
+        let line = r#"{"display":"first","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000"}"#;
+        fs::write(laptop.join("history.jsonl"), format!("{}\n", line)).unwrap();
+        assert_eq!(control.reindex().unwrap().entries.len(), 1);
+
+        let next = line.replace("first", "second").replace("890", "891");
+        fs::write(laptop.join("history.jsonl"), format!("{}\n{}\n", line, next)).unwrap();
+        let reindexed = control.reindex().unwrap();
+
+        assert_eq!(reindexed.entries[0].display_text, "second");
+        assert_eq!(reindexed.entries[0].root.as_deref(), Some("laptop"));
+        assert_eq!(reindexed.roots.len(), 2);
+        // The desktop root doesn't exist
+        assert_eq!(reindexed.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_move_selection_clamps() {
+        let temp = TempDir::new().unwrap();
+        let mut screen = CacheScreen::new(control(&temp).summary(), None);
+
+        screen.move_selection(5);
+        assert_eq!(screen.selected_job(), CacheJob::Compact);
+        screen.move_selection(-5);
+        assert_eq!(screen.selected_job(), CacheJob::Clear);
+    }
+}
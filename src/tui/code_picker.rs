@@ -0,0 +1,78 @@
+//! Code block picker state.
+//!
+//! Lists the fenced code blocks of the selected entry (or its whole session), lets the user
+//! pick one and either copy it or write it to a file named in an inline prompt.
+
+use crate::utils::code_blocks::CodeBlock;
+
+/// State of the code block picker popup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeBlockPicker {
+    pub blocks: Vec<CodeBlock>,
+    pub selected: usize,
+    /// Whether blocks come from the whole session rather than the selected entry
+    pub session_scope: bool,
+    /// File name being typed, while the "write to file" prompt is open
+    pub filename: Option<String>,
+}
+
+impl CodeBlockPicker {
+    pub fn new(blocks: Vec<CodeBlock>, session_scope: bool) -> Self {
+        Self { blocks, selected: 0, session_scope, filename: None }
+    }
+
+    pub fn selected_block(&self) -> Option<&CodeBlock> {
+        self.blocks.get(self.selected)
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let max = self.blocks.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(max);
+    }
+
+    /// Open the file name prompt, pre-filled with `snippet-<n>.<ext>`
+    pub fn start_filename_prompt(&mut self) {
+        if let Some(block) = self.selected_block() {
+            self.filename =
+                Some(format!("snippet-{}.{}", self.selected + 1, block.file_extension()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(language: &str, code: &str) -> CodeBlock {
+        CodeBlock { language: Some(language.to_string()), code: code.to_string() }
+    }
+
+    #[test]
+    fn test_move_selection_clamps() {
+        let mut picker = CodeBlockPicker::new(vec![block("rust", "a"), block("sh", "b")], false);
+        picker.move_selection(-1);
+        assert_eq!(picker.selected, 0);
+        picker.move_selection(5);
+        assert_eq!(picker.selected, 1);
+        assert_eq!(picker.selected_block().unwrap().code, "b");
+    }
+
+    #[test]
+    fn test_filename_prompt_uses_extension() {
+        let mut picker =
+            CodeBlockPicker::new(vec![block("rust", "a"), block("python", "b")], false);
+        picker.move_selection(1);
+        picker.start_filename_prompt();
+        assert_eq!(picker.filename.as_deref(), Some("snippet-2.py"));
+    }
+
+    #[test]
+    fn test_empty_picker() {
+        let mut picker = CodeBlockPicker::new(vec![], true);
+        picker.move_selection(1);
+        picker.start_filename_prompt();
+        assert_eq!(picker.selected, 0);
+        assert!(picker.selected_block().is_none());
+        assert!(picker.filename.is_none());
+    }
+}
@@ -0,0 +1,136 @@
+//! Columns of the results list.
+//!
+//! The config's `list_columns` picks which columns a result row shows and in which order, each
+//! optionally with a width in terminal columns (`project:24`). Without it rows show
+//! [`DEFAULT_COLUMNS`]: `👤 Sep 13, 2020 | ~/work/api | Add retry logic`.
+
+use anyhow::{Result, anyhow, bail};
+
+/// What a column of the results list shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    /// 👤 for prompts, 🤖 for agent messages
+    Icon,
+    Timestamp,
+    Project,
+    /// First 8 characters of the session id
+    Session,
+    /// First line of the entry's text
+    Text,
+}
+
+impl ColumnKind {
+    fn parse(name: &str) -> Result<Self> {
+        Ok(match name.trim().to_lowercase().as_str() {
+            "icon" => Self::Icon,
+            "timestamp" | "time" => Self::Timestamp,
+            "project" => Self::Project,
+            "session" => Self::Session,
+            "text" | "preview" => Self::Text,
+            _ => bail!(
+                "Unknown column '{}' in list_columns (valid columns: icon, timestamp, project, \
+                 session, text)",
+                name.trim()
+            ),
+        })
+    }
+}
+
+/// A column of the results list, fitted to `width` terminal columns if given
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListColumn {
+    pub kind: ColumnKind,
+    pub width: Option<usize>,
+}
+
+impl ListColumn {
+    pub const fn new(kind: ColumnKind) -> Self {
+        Self { kind, width: None }
+    }
+
+    /// Parse `name` or `name:width`, e.g. `project:24`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (name, width) = match spec.split_once(':') {
+            Some((name, width)) => {
+                let width = width
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|width| *width > 0)
+                    .ok_or_else(|| anyhow!("Invalid width in list column '{}'", spec))?;
+                (name, Some(width))
+            }
+            None => (spec, None),
+        };
+        Ok(Self { kind: ColumnKind::parse(name)?, width })
+    }
+}
+
+/// Characters of text shown by a text column without a width
+pub const DEFAULT_TEXT_WIDTH: usize = 50;
+
+/// Columns shown unless the config lists others
+pub const DEFAULT_COLUMNS: &[ListColumn] = &[
+    ListColumn::new(ColumnKind::Icon),
+    ListColumn::new(ColumnKind::Timestamp),
+    ListColumn::new(ColumnKind::Project),
+    ListColumn::new(ColumnKind::Text),
+];
+
+/// Parse the config's `list_columns`; an empty list gives [`DEFAULT_COLUMNS`]
+///
+/// # Errors
+///
+/// Returns an error for an unknown column, an invalid width or a column listed twice.
+pub fn parse_columns(specs: &[String]) -> Result<Vec<ListColumn>> {
+    if specs.is_empty() {
+        return Ok(DEFAULT_COLUMNS.to_vec());
+    }
+    let mut columns: Vec<ListColumn> = Vec::with_capacity(specs.len());
+    for spec in specs {
+        let column = ListColumn::parse(spec)?;
+        if columns.iter().any(|listed| listed.kind == column.kind) {
+            bail!("Column '{}' is listed twice in list_columns", spec.trim());
+        }
+        columns.push(column);
+    }
+    Ok(columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn specs(specs: &[&str]) -> Vec<String> {
+        specs.iter().map(|spec| spec.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_columns() {
+        assert_eq!(parse_columns(&[]).unwrap(), DEFAULT_COLUMNS);
+
+        let columns = parse_columns(&specs(&["Time", "project:24", "session", "text:80"])).unwrap();
+        assert_eq!(
+            columns,
+            vec![
+                ListColumn::new(ColumnKind::Timestamp),
+                ListColumn { kind: ColumnKind::Project, width: Some(24) },
+                ListColumn::new(ColumnKind::Session),
+                ListColumn { kind: ColumnKind::Text, width: Some(80) },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_columns_errors() {
+        let err = parse_columns(&specs(&["icon", "size"])).unwrap_err();
+        assert!(err.to_string().starts_with("Unknown column 'size' in list_columns"));
+
+        let err = parse_columns(&specs(&["project:wide"])).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid width in list column 'project:wide'");
+        assert!(parse_columns(&specs(&["project:0"])).is_err());
+
+        let err = parse_columns(&specs(&["text", "preview"])).unwrap_err();
+        assert_eq!(err.to_string(), "Column 'preview' is listed twice in list_columns");
+    }
+}
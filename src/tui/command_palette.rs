@@ -0,0 +1,104 @@
+//! Command palette popup state.
+//!
+//! Lists the bound commands (Alt+P) with their keys and descriptions, narrowed by a typed
+//! query, so a screen or toggle can be run without remembering its key.
+
+use super::events::Action;
+use super::keymap::{Keymap, filter_help_rows};
+
+/// State of the command palette popup
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandPalette {
+    /// (keys, action name, description) of every command, in keymap order
+    rows: Vec<(String, &'static str, &'static str)>,
+    /// Actions of `rows`, by position
+    actions: Vec<Action>,
+    pub query: String,
+    pub selected: usize,
+}
+
+impl CommandPalette {
+    pub fn new(keymap: &Keymap) -> Self {
+        let (rows, actions) = keymap
+            .help_rows()
+            .into_iter()
+            .filter_map(|row| {
+                let (_, action) =
+                    keymap.bindings().iter().find(|(_, action)| action.name() == row.1)?;
+                action.is_command().then(|| (row, action.clone()))
+            })
+            .unzip();
+        Self { rows, actions, query: String::new(), selected: 0 }
+    }
+
+    /// Commands matching the query: (keys, action name, description)
+    pub fn matches(&self) -> Vec<&(String, &'static str, &'static str)> {
+        filter_help_rows(&self.rows, &self.query)
+    }
+
+    /// The action of the selected matching command
+    pub fn selected_action(&self) -> Option<Action> {
+        let (_, name, _) = self.matches().get(self.selected).copied()?;
+        self.actions.iter().find(|action| action.name() == *name).cloned()
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let max = self.matches().len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(max);
+    }
+
+    pub fn push(&mut self, c: char) {
+        if self.query.len() < 64 {
+            self.query.push(c);
+            self.selected = 0;
+        }
+    }
+
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.selected = 0;
+    }
+
+    pub fn clear(&mut self) {
+        self.query.clear();
+        self.selected = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lists_commands_but_not_movement() {
+        let palette = CommandPalette::new(&Keymap::default());
+        let names: Vec<_> = palette.matches().iter().map(|(_, name, _)| *name).collect();
+
+        assert!(names.contains(&"cache"));
+        assert!(names.contains(&"activity"));
+        assert!(!names.contains(&"move_down"));
+        assert!(!names.contains(&"delete_word"));
+        assert!(!names.contains(&"command_palette"));
+    }
+
+    #[test]
+    fn test_query_narrows_and_selects() {
+        let mut palette = CommandPalette::new(&Keymap::default());
+        palette.move_selection(3);
+        for c in "index cache".chars() {
+            palette.push(c);
+        }
+
+        assert_eq!(palette.selected, 0);
+        assert_eq!(palette.selected_action(), Some(Action::CacheManager));
+
+        palette.move_selection(10);
+        assert_eq!(palette.selected, palette.matches().len() - 1);
+
+        for c in "zzz".chars() {
+            palette.push(c);
+        }
+        assert!(palette.matches().is_empty());
+        assert_eq!(palette.selected_action(), None);
+    }
+}
@@ -1,71 +1,301 @@
 use std::time::Duration;
 
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{self, Event, MouseButton, MouseEventKind};
+
+use super::keymap::Keymap;
 
 /// User actions from keyboard events
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Action {
     Quit,
     ClearSearch,
     MoveUp,
     MoveDown,
+    HistoryPrev,
+    HistoryNext,
     PageUp,
     PageDown,
+    MoveTop,
+    MoveBottom,
     ApplyFilter,
     CopyToClipboard,
+    CopySession,
+    CopyContext,
     ToggleFilter,
     ToggleFocus,
     Refresh,
+    ToggleRoots,
+    ToggleWarnings,
+    ToggleHelp,
+    CodeBlocks,
+    ToolLeaderboard,
+    ExportExchange,
+    PromptTimeline,
+    SessionList,
+    ProjectList,
+    CacheManager,
+    CommandPalette,
+    ActivityCalendar,
+    SavedSearches,
+    SaveSearch,
+    CollapseSimilar,
+    GroupBySession,
+    ToggleGroup,
+    ToggleMinimap,
+    CycleDensity,
+    CycleMatchColumns,
+    ToggleTimestamps,
+    TimeJumpNewer,
+    TimeJumpOlder,
+    JumpToParent,
+    JumpToChild,
+    Scratchpad,
+    ToggleStar,
+    RevealSensitive,
+    ResumeSession,
+    /// Left mouse click at a terminal cell (only reported while mouse capture is on)
+    Click {
+        column: u16,
+        row: u16,
+    },
+    /// The terminal was resized
+    Resize,
     UpdateSearch(char),
     DeleteChar,
+    CursorLeft,
+    CursorRight,
+    CursorHome,
+    CursorEnd,
+    DeleteForward,
+    DeleteWord,
+    DeleteToStart,
     None,
 }
 
-/// Poll for keyboard events and convert to actions
-pub fn poll_event(timeout: Duration) -> anyhow::Result<Action> {
-    if event::poll(timeout)?
-        && let Event::Key(key) = event::read()?
-    {
-        return Ok(key_to_action(key));
+impl Action {
+    /// Stable identifier used in keymap exports and configuration
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::ClearSearch => "clear_search",
+            Action::MoveUp => "move_up",
+            Action::MoveDown => "move_down",
+            Action::HistoryPrev => "history_prev",
+            Action::HistoryNext => "history_next",
+            Action::PageUp => "page_up",
+            Action::PageDown => "page_down",
+            Action::MoveTop => "move_top",
+            Action::MoveBottom => "move_bottom",
+            Action::ApplyFilter => "apply_filter",
+            Action::CopyToClipboard => "copy",
+            Action::CopySession => "copy_session",
+            Action::CopyContext => "copy_context",
+            Action::ToggleFilter => "toggle_filter",
+            Action::ToggleFocus => "toggle_focus",
+            Action::Refresh => "refresh",
+            Action::ToggleRoots => "toggle_roots",
+            Action::ToggleWarnings => "toggle_warnings",
+            Action::ToggleHelp => "help",
+            Action::CodeBlocks => "code_blocks",
+            Action::ToolLeaderboard => "tool_leaderboard",
+            Action::ExportExchange => "export_exchange",
+            Action::PromptTimeline => "prompt_timeline",
+            Action::SessionList => "sessions",
+            Action::ProjectList => "projects",
+            Action::CacheManager => "cache",
+            Action::CommandPalette => "command_palette",
+            Action::ActivityCalendar => "activity",
+            Action::SavedSearches => "saved_searches",
+            Action::SaveSearch => "save_search",
+            Action::CollapseSimilar => "collapse_similar",
+            Action::GroupBySession => "group_by_session",
+            Action::ToggleGroup => "toggle_group",
+            Action::ToggleMinimap => "toggle_minimap",
+            Action::CycleDensity => "cycle_density",
+            Action::CycleMatchColumns => "cycle_match_columns",
+            Action::ToggleTimestamps => "toggle_timestamps",
+            Action::TimeJumpNewer => "time_jump_newer",
+            Action::TimeJumpOlder => "time_jump_older",
+            Action::JumpToParent => "jump_to_parent",
+            Action::JumpToChild => "jump_to_child",
+            Action::Scratchpad => "scratchpad",
+            Action::ToggleStar => "toggle_star",
+            Action::RevealSensitive => "reveal_sensitive",
+            Action::ResumeSession => "resume_session",
+            Action::Click { .. } => "click",
+            Action::Resize => "resize",
+            Action::UpdateSearch(_) => "search_input",
+            Action::DeleteChar => "delete_char",
+            Action::CursorLeft => "cursor_left",
+            Action::CursorRight => "cursor_right",
+            Action::CursorHome => "cursor_home",
+            Action::CursorEnd => "cursor_end",
+            Action::DeleteForward => "delete_forward",
+            Action::DeleteWord => "delete_word",
+            Action::DeleteToStart => "delete_to_start",
+            Action::None => "none",
+        }
     }
-    Ok(Action::None)
-}
 
-fn key_to_action(key: KeyEvent) -> Action {
-    match (key.code, key.modifiers) {
-        // Quit
-        (KeyCode::Char('c'), KeyModifiers::CONTROL) => Action::Quit,
-        (KeyCode::Esc, _) => Action::ClearSearch,
-
-        // Navigation (Vim/Emacs style)
-        (KeyCode::Char('p'), KeyModifiers::CONTROL) => Action::MoveUp,
-        (KeyCode::Char('n'), KeyModifiers::CONTROL) => Action::MoveDown,
-        (KeyCode::Up, _) => Action::MoveUp,
-        (KeyCode::Down, _) => Action::MoveDown,
-        (KeyCode::PageUp, _) => Action::PageUp,
-        (KeyCode::PageDown, _) => Action::PageDown,
-
-        // Actions
-        (KeyCode::Enter, _) => Action::ApplyFilter,
-        (KeyCode::Char('y'), KeyModifiers::CONTROL) => Action::CopyToClipboard,
-        (KeyCode::Char('/'), KeyModifiers::NONE) => Action::ToggleFilter,
-        (KeyCode::Tab, _) => Action::ToggleFocus,
-        (KeyCode::Char('r'), KeyModifiers::CONTROL) => Action::Refresh,
-
-        // Search input
-        (KeyCode::Char(c), KeyModifiers::NONE) | (KeyCode::Char(c), KeyModifiers::SHIFT) => {
-            Action::UpdateSearch(c)
+    /// Human-readable description shown in the help overlay
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::ClearSearch => "Clear search (quit if empty)",
+            Action::MoveUp => "Previous entry",
+            Action::MoveDown => "Next entry",
+            Action::HistoryPrev => "Previous search from the history (up in popups)",
+            Action::HistoryNext => "Next search from the history (down in popups)",
+            Action::PageUp => "Move up 10 entries",
+            Action::PageDown => "Move down 10 entries",
+            Action::MoveTop => "First entry",
+            Action::MoveBottom => "Last entry",
+            Action::ApplyFilter => {
+                "Apply a changed filter, else replay the selected entry's session"
+            }
+            Action::CopyToClipboard => "Copy selected entry to clipboard",
+            Action::CopySession => "Copy the selected entry's whole conversation as Markdown",
+            Action::CopyContext => {
+                "Copy the selected prompt with its project, branch, model and time as Markdown"
+            }
+            Action::ToggleFilter => "Toggle filter mode",
+            Action::ToggleFocus => "Toggle focus between results and preview",
+            Action::Refresh => "Refresh index (picks up new history without restarting)",
+            Action::ToggleRoots => "Workspace roots overview",
+            Action::ToggleWarnings => "Indexing warnings (retry failed files)",
+            Action::ToggleHelp => "Show/search this help (also ? in an empty search box)",
+            Action::CodeBlocks => "Pick a code block from the selected entry to copy or save",
+            Action::ToolLeaderboard => "Tool-output size leaderboard per tool and project",
+            Action::ExportExchange => "Export the selected prompt and its answer as Markdown/JSON",
+            Action::PromptTimeline => {
+                "Prompts of the selected entry's project, oldest first (copy/write as Markdown)"
+            }
+            Action::SessionList => {
+                "Sessions, most recently active first (Enter: select newest entry)"
+            }
+            Action::ProjectList => {
+                "Projects, most recently active first (Enter: filter to project)"
+            }
+            Action::CacheManager => "Index cache: size and entries per root, clear/rebuild/compact",
+            Action::CommandPalette => "Command palette: type to find a command, Enter to run it",
+            Action::ActivityCalendar => {
+                "Calendar heatmap of entries per day (Enter: filter to day, Tab/Esc: back to results)"
+            }
+            Action::SavedSearches => "Saved searches (Enter: open, d: delete)",
+            Action::SaveSearch => "Save the current search under a name",
+            Action::CollapseSimilar => "Collapse near-duplicate results into one row (+N)",
+            Action::GroupBySession => {
+                "Group results by session (one row per session with its span)"
+            }
+            Action::ToggleGroup => "Expand/collapse the selected group or session",
+            Action::ToggleMinimap => "Show/hide the weekly time minimap (click a row to jump)",
+            Action::CycleDensity => "Cycle the results between compact, comfortable and detailed",
+            Action::CycleMatchColumns => {
+                "Cycle what the fuzzy query matches: text, + project path, + session id"
+            }
+            Action::ToggleTimestamps => "Switch between relative (2h ago) and absolute times",
+            Action::TimeJumpNewer => "Jump to the first result of the next newer week",
+            Action::TimeJumpOlder => "Jump to the first result of the next older week",
+            Action::JumpToParent => "Jump to the message the selected entry replies to",
+            Action::JumpToChild => "Jump to the first reply to the selected entry",
+            Action::Scratchpad => {
+                "Scratchpad for composing a prompt (a: append from the preview or code blocks)"
+            }
+            Action::ToggleStar => "Star/unstar the selected entry (filter with starred:true)",
+            Action::RevealSensitive => {
+                "Reveal the selected entry's sensitive project (again: hide it)"
+            }
+            Action::ResumeSession => {
+                "Quit and resume the selected entry's session with `claude --resume`"
+            }
+            Action::Click { .. } => "Select a time region in the minimap",
+            Action::Resize => "Redraw for the new terminal size",
+            Action::UpdateSearch(_) => "Type into the search box",
+            Action::DeleteChar => "Delete the search character before the cursor",
+            Action::CursorLeft => "Move the search cursor left (while typing)",
+            Action::CursorRight => "Move the search cursor right (while typing)",
+            Action::CursorHome => "Move the search cursor to the start (while typing)",
+            Action::CursorEnd => "Move the search cursor to the end (while typing)",
+            Action::DeleteForward => "Delete the search character under the cursor (while typing)",
+            Action::DeleteWord => "Delete the search word before the cursor (while typing)",
+            Action::DeleteToStart => "Delete the search up to the cursor (while typing)",
+            Action::None => "",
         }
-        (KeyCode::Backspace, _) => Action::DeleteChar,
+    }
 
-        _ => Action::None,
+    /// Whether the action is listed in the command palette: screens, toggles and commands on
+    /// the selection, but not moving, typing or the palette itself
+    pub fn is_command(&self) -> bool {
+        !self.edits_search()
+            && !matches!(
+                self,
+                Action::ClearSearch
+                    | Action::MoveUp
+                    | Action::MoveDown
+                    | Action::HistoryPrev
+                    | Action::HistoryNext
+                    | Action::PageUp
+                    | Action::PageDown
+                    | Action::MoveTop
+                    | Action::MoveBottom
+                    | Action::CommandPalette
+                    | Action::Click { .. }
+                    | Action::Resize
+                    | Action::UpdateSearch(_)
+                    | Action::DeleteChar
+                    | Action::None
+            )
+    }
+
+    /// Whether the action edits the search box at its cursor, which only its keys do while a
+    /// search is being typed (Home, End, Ctrl+W, ... act on the results otherwise)
+    pub fn edits_search(&self) -> bool {
+        matches!(
+            self,
+            Action::CursorLeft
+                | Action::CursorRight
+                | Action::CursorHome
+                | Action::CursorEnd
+                | Action::DeleteForward
+                | Action::DeleteWord
+                | Action::DeleteToStart
+        )
     }
 }
 
+/// Poll for keyboard and mouse events and convert to actions using the given keymap
+///
+/// Mouse events only arrive while mouse capture is enabled (the minimap is shown): left
+/// clicks become [`Action::Click`] and the wheel moves the selection.
+pub fn poll_event(timeout: Duration, keymap: &mut Keymap) -> anyhow::Result<Action> {
+    if !event::poll(timeout)? {
+        return Ok(Action::None);
+    }
+    Ok(match event::read()? {
+        Event::Key(key) => keymap.action_for(&key),
+        Event::Resize(..) => Action::Resize,
+        Event::Mouse(mouse) => match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                Action::Click { column: mouse.column, row: mouse.row }
+            }
+            MouseEventKind::ScrollUp => Action::MoveUp,
+            MouseEventKind::ScrollDown => Action::MoveDown,
+            _ => Action::None,
+        },
+        _ => Action::None,
+    })
+}
+
 #[cfg(test)]
 mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
     use super::*;
 
+    fn key_to_action(key: KeyEvent) -> Action {
+        Keymap::default().action_for(&key)
+    }
+
     #[test]
     fn test_quit_actions() {
         let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
@@ -81,10 +311,10 @@ mod tests {
     #[test]
     fn test_navigation_vim_style() {
         let ctrl_p = KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL);
-        assert_eq!(key_to_action(ctrl_p), Action::MoveUp);
+        assert_eq!(key_to_action(ctrl_p), Action::HistoryPrev);
 
         let ctrl_n = KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL);
-        assert_eq!(key_to_action(ctrl_n), Action::MoveDown);
+        assert_eq!(key_to_action(ctrl_n), Action::HistoryNext);
     }
 
     #[test]
@@ -94,6 +324,12 @@ mod tests {
 
         let down = KeyEvent::new(KeyCode::Down, KeyModifiers::NONE);
         assert_eq!(key_to_action(down), Action::MoveDown);
+
+        let home = KeyEvent::new(KeyCode::Home, KeyModifiers::NONE);
+        assert_eq!(key_to_action(home), Action::MoveTop);
+
+        let end = KeyEvent::new(KeyCode::End, KeyModifiers::NONE);
+        assert_eq!(key_to_action(end), Action::MoveBottom);
     }
 
     #[test]
@@ -108,6 +344,23 @@ mod tests {
         assert_eq!(key_to_action(backspace), Action::DeleteChar);
     }
 
+    #[test]
+    fn test_cursor_keys_while_typing() {
+        let mut keymap = Keymap::default();
+        let ctrl_w = KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL);
+        let home = KeyEvent::new(KeyCode::Home, KeyModifiers::NONE);
+        let left = KeyEvent::new(KeyCode::Left, KeyModifiers::NONE);
+        assert_eq!(keymap.action_for(&ctrl_w), Action::ToggleWarnings);
+        assert_eq!(keymap.action_for(&left), Action::None);
+
+        keymap.set_editing_search(true);
+        assert_eq!(keymap.action_for(&ctrl_w), Action::DeleteWord);
+        assert_eq!(keymap.action_for(&home), Action::CursorHome);
+        assert_eq!(keymap.action_for(&left), Action::CursorLeft);
+        let alt_left = KeyEvent::new(KeyCode::Left, KeyModifiers::ALT);
+        assert_eq!(keymap.action_for(&alt_left), Action::JumpToParent);
+    }
+
     #[test]
     fn test_page_navigation() {
         let page_up = KeyEvent::new(KeyCode::PageUp, KeyModifiers::NONE);
@@ -125,6 +378,12 @@ mod tests {
         let ctrl_y = KeyEvent::new(KeyCode::Char('y'), KeyModifiers::CONTROL);
         assert_eq!(key_to_action(ctrl_y), Action::CopyToClipboard);
 
+        let alt_y = KeyEvent::new(KeyCode::Char('y'), KeyModifiers::ALT);
+        assert_eq!(key_to_action(alt_y), Action::CopySession);
+
+        let alt_i = KeyEvent::new(KeyCode::Char('i'), KeyModifiers::ALT);
+        assert_eq!(key_to_action(alt_i), Action::CopyContext);
+
         let slash = KeyEvent::new(KeyCode::Char('/'), KeyModifiers::NONE);
         assert_eq!(key_to_action(slash), Action::ToggleFilter);
 
@@ -133,6 +392,96 @@ mod tests {
 
         let ctrl_r = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL);
         assert_eq!(key_to_action(ctrl_r), Action::Refresh);
+
+        let ctrl_o = KeyEvent::new(KeyCode::Char('o'), KeyModifiers::CONTROL);
+        assert_eq!(key_to_action(ctrl_o), Action::ToggleRoots);
+
+        let ctrl_w = KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL);
+        assert_eq!(key_to_action(ctrl_w), Action::ToggleWarnings);
+
+        let f1 = KeyEvent::new(KeyCode::F(1), KeyModifiers::NONE);
+        assert_eq!(key_to_action(f1), Action::ToggleHelp);
+
+        let ctrl_b = KeyEvent::new(KeyCode::Char('b'), KeyModifiers::CONTROL);
+        assert_eq!(key_to_action(ctrl_b), Action::CodeBlocks);
+
+        let ctrl_t = KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL);
+        assert_eq!(key_to_action(ctrl_t), Action::ToolLeaderboard);
+
+        let ctrl_e = KeyEvent::new(KeyCode::Char('e'), KeyModifiers::CONTROL);
+        assert_eq!(key_to_action(ctrl_e), Action::ExportExchange);
+
+        let ctrl_l = KeyEvent::new(KeyCode::Char('l'), KeyModifiers::CONTROL);
+        assert_eq!(key_to_action(ctrl_l), Action::PromptTimeline);
+
+        let f2 = KeyEvent::new(KeyCode::F(2), KeyModifiers::NONE);
+        assert_eq!(key_to_action(f2), Action::SessionList);
+
+        let f3 = KeyEvent::new(KeyCode::F(3), KeyModifiers::NONE);
+        assert_eq!(key_to_action(f3), Action::ProjectList);
+
+        let f4 = KeyEvent::new(KeyCode::F(4), KeyModifiers::NONE);
+        assert_eq!(key_to_action(f4), Action::CacheManager);
+
+        let f5 = KeyEvent::new(KeyCode::F(5), KeyModifiers::NONE);
+        assert_eq!(key_to_action(f5), Action::ActivityCalendar);
+
+        let shift_tab = KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT);
+        assert_eq!(key_to_action(shift_tab), Action::ActivityCalendar);
+
+        let alt_p = KeyEvent::new(KeyCode::Char('p'), KeyModifiers::ALT);
+        assert_eq!(key_to_action(alt_p), Action::CommandPalette);
+
+        let f6 = KeyEvent::new(KeyCode::F(6), KeyModifiers::NONE);
+        assert_eq!(key_to_action(f6), Action::SavedSearches);
+
+        let alt_s = KeyEvent::new(KeyCode::Char('s'), KeyModifiers::ALT);
+        assert_eq!(key_to_action(alt_s), Action::SaveSearch);
+
+        let ctrl_d = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL);
+        assert_eq!(key_to_action(ctrl_d), Action::CollapseSimilar);
+
+        let ctrl_s = KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL);
+        assert_eq!(key_to_action(ctrl_s), Action::GroupBySession);
+
+        let right = KeyEvent::new(KeyCode::Right, KeyModifiers::NONE);
+        assert_eq!(key_to_action(right), Action::ToggleGroup);
+
+        let ctrl_g = KeyEvent::new(KeyCode::Char('g'), KeyModifiers::CONTROL);
+        assert_eq!(key_to_action(ctrl_g), Action::ToggleMinimap);
+
+        let alt_d = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::ALT);
+        assert_eq!(key_to_action(alt_d), Action::CycleDensity);
+
+        let alt_m = KeyEvent::new(KeyCode::Char('m'), KeyModifiers::ALT);
+        assert_eq!(key_to_action(alt_m), Action::CycleMatchColumns);
+
+        let alt_t = KeyEvent::new(KeyCode::Char('t'), KeyModifiers::ALT);
+        assert_eq!(key_to_action(alt_t), Action::ToggleTimestamps);
+
+        let alt_up = KeyEvent::new(KeyCode::Up, KeyModifiers::ALT);
+        assert_eq!(key_to_action(alt_up), Action::TimeJumpNewer);
+
+        let alt_down = KeyEvent::new(KeyCode::Down, KeyModifiers::ALT);
+        assert_eq!(key_to_action(alt_down), Action::TimeJumpOlder);
+
+        let alt_left = KeyEvent::new(KeyCode::Left, KeyModifiers::ALT);
+        assert_eq!(key_to_action(alt_left), Action::JumpToParent);
+
+        let alt_right = KeyEvent::new(KeyCode::Right, KeyModifiers::ALT);
+        assert_eq!(key_to_action(alt_right), Action::JumpToChild);
+
+        let ctrl_k = KeyEvent::new(KeyCode::Char('k'), KeyModifiers::CONTROL);
+        assert_eq!(key_to_action(ctrl_k), Action::Scratchpad);
+
+        let ctrl_f = KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL);
+        assert_eq!(key_to_action(ctrl_f), Action::ToggleStar);
+
+        let ctrl_u = KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL);
+        assert_eq!(key_to_action(ctrl_u), Action::RevealSensitive);
+
+        let alt_r = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::ALT);
+        assert_eq!(key_to_action(alt_r), Action::ResumeSession);
     }
 
     #[test]
@@ -143,7 +492,7 @@ mod tests {
 
     #[test]
     fn test_unknown_key() {
-        let unknown = KeyEvent::new(KeyCode::F(1), KeyModifiers::NONE);
+        let unknown = KeyEvent::new(KeyCode::F(12), KeyModifiers::NONE);
         assert_eq!(key_to_action(unknown), Action::None);
     }
 }
@@ -0,0 +1,112 @@
+//! Exchange export dialog state.
+//!
+//! Holds the exchange around the selected entry (its prompt plus the answering turns), the
+//! chosen format, and the file name while the "write to file" prompt is open.
+
+use std::path::PathBuf;
+
+use crate::export::{Exchange, ExportFormat};
+
+/// State of the export popup
+#[derive(Debug, Clone)]
+pub struct ExportDialog {
+    pub exchange: Exchange,
+    /// Conversation file the exchange was read from, for its image data
+    pub conversation_file: PathBuf,
+    pub format: ExportFormat,
+    /// Whether writing Markdown also saves the images to `attachments/`
+    pub save_images: bool,
+    /// File name being typed, while the "write to file" prompt is open
+    pub filename: Option<String>,
+}
+
+impl ExportDialog {
+    pub fn new(exchange: Exchange, conversation_file: PathBuf) -> Self {
+        Self {
+            exchange,
+            conversation_file,
+            format: ExportFormat::default(),
+            save_images: false,
+            filename: None,
+        }
+    }
+
+    /// Whether writing the export saves the images (only Markdown links to them)
+    pub fn saves_images(&self) -> bool {
+        self.save_images && self.format == ExportFormat::Markdown
+    }
+
+    /// Switch format, keeping a pre-filled file name's extension in sync
+    pub fn toggle_format(&mut self) {
+        let old_extension = format!(".{}", self.format.file_extension());
+        self.format = self.format.toggle();
+        if let Some(name) = self.filename.as_mut()
+            && let Some(stem) = name.strip_suffix(&old_extension)
+        {
+            *name = format!("{}.{}", stem, self.format.file_extension());
+        }
+    }
+
+    /// Open the file name prompt, pre-filled with `exchange-<prompt time>.<ext>`
+    pub fn start_filename_prompt(&mut self) {
+        let stamp = self
+            .exchange
+            .started_at()
+            .map(|t| t.format("%Y%m%d-%H%M%S").to_string())
+            .unwrap_or_else(|| "export".to_string());
+        self.filename = Some(format!("exchange-{}.{}", stamp, self.format.file_extension()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dialog() -> ExportDialog {
+        let line = r#"{"type":"user","message":{"role":"user","content":"hi"},"timestamp":1704099600000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u1"}"#;
+        ExportDialog::new(
+            Exchange {
+                session_id: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+                project: None,
+                turns: vec![serde_json::from_str(line).unwrap()],
+            },
+            PathBuf::from("session.jsonl"),
+        )
+    }
+
+    #[test]
+    fn test_filename_prompt_uses_prompt_time_and_format() {
+        let mut dialog = dialog();
+        dialog.start_filename_prompt();
+        assert_eq!(dialog.filename.as_deref(), Some("exchange-20240101-090000.md"));
+    }
+
+    #[test]
+    fn test_toggle_format_updates_extension() {
+        let mut dialog = dialog();
+        dialog.start_filename_prompt();
+
+        dialog.toggle_format();
+
+        assert_eq!(dialog.format, ExportFormat::Json);
+        assert_eq!(dialog.filename.as_deref(), Some("exchange-20240101-090000.json"));
+    }
+
+    #[test]
+    fn test_images_are_saved_for_markdown_only() {
+        let mut dialog = dialog();
+        assert!(!dialog.saves_images());
+        dialog.save_images = true;
+        assert!(dialog.saves_images());
+        dialog.toggle_format();
+        assert!(!dialog.saves_images());
+    }
+
+    #[test]
+    fn test_toggle_format_keeps_custom_name() {
+        let mut dialog = dialog();
+        dialog.filename = Some("notes.txt".to_string());
+        dialog.toggle_format();
+        assert_eq!(dialog.filename.as_deref(), Some("notes.txt"));
+    }
+}
@@ -0,0 +1,103 @@
+//! Match count of a filter that is typed but not applied yet.
+//!
+//! Applying a filter re-injects every matching entry into the fuzzy matcher, so it waits for
+//! Enter. Meanwhile the status bar shows how many entries the typed filter would match,
+//! counted on an evenly spaced sample of at most [`SAMPLE_SIZE`] entries and scaled up
+//! (exact for smaller histories), so filters can be tuned without applying each attempt.
+
+use crate::bookmarks::Bookmarks;
+use crate::filters::apply::apply_filters;
+use crate::filters::parser::parse_filter;
+use crate::models::SearchEntry;
+
+/// Entries evaluated per estimate
+pub const SAMPLE_SIZE: usize = 2_000;
+
+/// Matches of a pending filter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterEstimate {
+    /// Filter portion of the query the estimate is for
+    pub filter: String,
+    pub matches: usize,
+    /// Whether every entry was evaluated (otherwise `matches` is scaled from a sample)
+    pub exact: bool,
+}
+
+impl FilterEstimate {
+    /// Status bar text, e.g. `~1200 matches`
+    pub fn label(&self) -> String {
+        let approx = if self.exact { "" } else { "~" };
+        let noun = if self.matches == 1 { "match" } else { "matches" };
+        format!("{}{} {}", approx, self.matches, noun)
+    }
+}
+
+/// Estimate how many of `entries` `filter` matches
+///
+/// Returns `None` for filters that don't parse or can't be evaluated (e.g. an invalid regex);
+/// applying them shows the error.
+pub fn estimate_matches(
+    filter: &str,
+    entries: &[&SearchEntry],
+    bookmarks: &Bookmarks,
+) -> Option<FilterEstimate> {
+    let expr = parse_filter(filter).ok()?;
+    let step = entries.len().div_ceil(SAMPLE_SIZE).max(1);
+    let sample: Vec<SearchEntry> = entries.iter().step_by(step).map(|&e| e.clone()).collect();
+    let sampled = sample.len();
+    let matched = apply_filters(sample, &expr, bookmarks).ok()?.len();
+    let matches = if step == 1 {
+        matched
+    } else {
+        (matched as f64 * entries.len() as f64 / sampled as f64).round() as usize
+    };
+    Some(FilterEstimate { filter: filter.to_string(), matches, exact: step == 1 })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+
+    use super::*;
+    use crate::models::EntryType;
+
+    fn entry(i: usize) -> SearchEntry {
+        SearchEntry {
+            entry_type: if i.is_multiple_of(4) {
+                EntryType::UserPrompt
+            } else {
+                EntryType::AgentMessage
+            },
+            display_text: format!("message {}", i),
+            timestamp: DateTime::from_timestamp(1_704_099_600 + i as i64, 0).unwrap(),
+            session_id: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_small_histories_are_counted_exactly() {
+        let entries: Vec<SearchEntry> = (0..10).map(entry).collect();
+        let refs: Vec<&SearchEntry> = entries.iter().collect();
+
+        let estimate = estimate_matches("type:user", &refs, &Bookmarks::default()).unwrap();
+        assert_eq!(estimate.matches, 3);
+        assert!(estimate.exact);
+        assert_eq!(estimate.label(), "3 matches");
+
+        assert!(estimate_matches("typo:user", &refs, &Bookmarks::default()).is_none());
+        assert!(estimate_matches("regex:[", &refs, &Bookmarks::default()).is_none());
+    }
+
+    #[test]
+    fn test_large_histories_are_sampled() {
+        let entries: Vec<SearchEntry> = (0..SAMPLE_SIZE * 5).map(entry).collect();
+        let refs: Vec<&SearchEntry> = entries.iter().collect();
+
+        let estimate = estimate_matches("type:agent", &refs, &Bookmarks::default()).unwrap();
+        assert!(!estimate.exact);
+        let exact = SAMPLE_SIZE * 5 * 3 / 4;
+        assert!(estimate.matches.abs_diff(exact) <= exact / 10, "{}", estimate.matches);
+        assert!(estimate.label().starts_with('~'));
+    }
+}
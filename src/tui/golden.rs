@@ -0,0 +1,102 @@
+//! Golden-file checks for rendered screens.
+//!
+//! A test renders into a `TestBackend` and compares the buffer, one text line per row, with
+//! `tests/golden/<name>.txt`. On a mismatch the test fails with a line diff. Run the tests
+//! with `UPDATE_GOLDEN=1` to write the current output instead, then review the changed files
+//! like any other diff.
+
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use ratatui::buffer::Buffer;
+use ratatui::text::Span;
+
+/// Set to rewrite the golden files instead of comparing with them
+const UPDATE_ENV: &str = "UPDATE_GOLDEN";
+
+/// Text of a rendered buffer, one line per row without trailing spaces
+pub fn buffer_text(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let mut text = String::new();
+    for y in area.top()..area.bottom() {
+        let mut line = String::new();
+        let mut skip = 0;
+        for x in area.left()..area.right() {
+            // Wide characters span two cells: the second one is blank padding
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            let symbol = buffer[(x, y)].symbol();
+            skip = Span::raw(symbol).width().saturating_sub(1);
+            line.push_str(symbol);
+        }
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+/// Compare a rendered buffer with the golden file `name`
+///
+/// Panics with the differing lines on a mismatch, or if the file is missing.
+pub fn assert_golden(name: &str, buffer: &Buffer) {
+    let path = golden_path(name);
+    let actual = buffer_text(buffer);
+
+    if std::env::var_os(UPDATE_ENV).is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, &actual).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!("missing golden file {} (run with {}=1 to create it)", path.display(), UPDATE_ENV)
+    });
+    if expected != actual {
+        panic!(
+            "{} does not match the rendered screen (run with {}=1 to accept):\n{}",
+            path.display(),
+            UPDATE_ENV,
+            line_diff(&expected, &actual)
+        );
+    }
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{}.txt", name))
+}
+
+/// Differing lines, numbered, as `-expected` / `+actual` pairs
+fn line_diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let mut diff = String::new();
+    for i in 0..expected.len().max(actual.len()) {
+        let (old, new) = (expected.get(i), actual.get(i));
+        if old != new {
+            let _ = writeln!(diff, "{:>3} - {}", i + 1, old.unwrap_or(&""));
+            let _ = writeln!(diff, "{:>3} + {}", i + 1, new.unwrap_or(&""));
+        }
+    }
+    diff
+}
+
+mod tests {
+    use ratatui::layout::Rect;
+
+    use super::*;
+
+    #[test]
+    fn test_buffer_text_trims_rows() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 6, 2));
+        buffer.set_string(0, 0, "ab", ratatui::style::Style::default());
+        buffer.set_string(1, 1, "日x", ratatui::style::Style::default());
+        assert_eq!(buffer_text(&buffer), "ab\n 日x\n");
+    }
+
+    #[test]
+    fn test_line_diff() {
+        assert_eq!(line_diff("a\nb\n", "a\nc\nd\n"), "  2 - b\n  2 + c\n  3 - \n  3 + d\n");
+    }
+}
@@ -0,0 +1,299 @@
+//! Display-time grouping of results.
+//!
+//! Retyped prompts ("fix the test", "fix the tests") clutter the results. With "collapse
+//! similar" on, a result whose words overlap enough with a recent result of the same type is
+//! folded into that result's row, which shows a `+N` badge and can be expanded in place.
+//!
+//! Long sessions clutter them too. Grouped by session, the results of each session are folded
+//! into the row of its first result, which shows how many results the session has and when
+//! they were written; expanding it lists the others below it.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+
+use crate::analytics::normalized_words;
+use crate::models::SearchEntry;
+
+/// Minimum word overlap (Jaccard similarity) for two results to be grouped
+pub const SIMILARITY_THRESHOLD: f64 = 0.8;
+
+/// Number of most recent groups each result is compared with
+const GROUP_WINDOW: usize = 50;
+
+/// Only the beginning of each entry is compared
+const COMPARED_CHARS: usize = 300;
+
+/// How results are grouped into rows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Grouping {
+    /// Every result on its own row
+    #[default]
+    Off,
+    /// Near-duplicate results folded into one row
+    Similar,
+    /// One row per session
+    Session,
+}
+
+/// How a visible result row relates to its group
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowKind {
+    /// No similar results
+    Single,
+    /// Leader of a collapsed group with this many hidden results
+    Collapsed(usize),
+    /// Leader of an expanded group with this many results listed below it
+    Expanded(usize),
+    /// Result listed below the leader of an expanded group
+    Member,
+    /// First result of a session, with the number of results in the session and the time
+    /// span they cover
+    Session { results: usize, first: DateTime<Utc>, last: DateTime<Utc>, expanded: bool },
+}
+
+impl RowKind {
+    /// Whether the row leads a group that can be expanded or collapsed
+    pub fn is_leader(self) -> bool {
+        match self {
+            RowKind::Collapsed(_) | RowKind::Expanded(_) => true,
+            RowKind::Session { results, .. } => results > 1,
+            RowKind::Single | RowKind::Member => false,
+        }
+    }
+
+    /// Whether the row leads a group listed below it
+    pub fn is_expanded(self) -> bool {
+        matches!(self, RowKind::Expanded(_) | RowKind::Session { expanded: true, .. })
+    }
+}
+
+/// Groups of results, by position in the result list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResultGroups {
+    /// Position of each result's group leader (the leader's own position for leaders)
+    leaders: Vec<usize>,
+    /// Positions of each leader's other members, in result order
+    members: Vec<Vec<usize>>,
+    /// Time span of each leader's session (only when grouped by session)
+    spans: HashMap<usize, (DateTime<Utc>, DateTime<Utc>)>,
+}
+
+impl ResultGroups {
+    /// Group `entries` the way `grouping` asks for
+    pub fn build(entries: &[&SearchEntry], grouping: Grouping) -> Self {
+        match grouping {
+            Grouping::Off => Self::by_leader(entries.len(), |i| i),
+            Grouping::Similar => Self::similar(entries),
+            Grouping::Session => Self::by_session(entries),
+        }
+    }
+
+    /// Group `entries`, each joining the first recent group it is similar enough to
+    pub fn similar(entries: &[&SearchEntry]) -> Self {
+        let words: Vec<HashSet<String>> =
+            entries.iter().map(|e| word_set(&e.display_text)).collect();
+        let mut leaders = Vec::with_capacity(entries.len());
+        let mut members = vec![Vec::new(); entries.len()];
+        // Leaders of the most recent groups, newest last
+        let mut recent: Vec<usize> = Vec::new();
+
+        for (i, entry) in entries.iter().enumerate() {
+            let leader = recent.iter().rev().copied().find(|&leader| {
+                entries[leader].entry_type == entry.entry_type
+                    && similarity(&words[leader], &words[i]) >= SIMILARITY_THRESHOLD
+            });
+            match leader {
+                Some(leader) => {
+                    leaders.push(leader);
+                    members[leader].push(i);
+                }
+                None => {
+                    leaders.push(i);
+                    recent.push(i);
+                    if recent.len() > GROUP_WINDOW {
+                        recent.remove(0);
+                    }
+                }
+            }
+        }
+        Self { leaders, members, spans: HashMap::new() }
+    }
+
+    /// Group `entries` by session, each session led by its first result
+    pub fn by_session(entries: &[&SearchEntry]) -> Self {
+        let mut firsts: HashMap<&str, usize> = HashMap::new();
+        for (i, entry) in entries.iter().enumerate() {
+            firsts.entry(entry.session_id.as_str()).or_insert(i);
+        }
+        let mut groups = Self::by_leader(entries.len(), |i| firsts[entries[i].session_id.as_str()]);
+        for (i, entry) in entries.iter().enumerate() {
+            let span =
+                groups.spans.entry(groups.leaders[i]).or_insert((entry.timestamp, entry.timestamp));
+            span.0 = span.0.min(entry.timestamp);
+            span.1 = span.1.max(entry.timestamp);
+        }
+        groups
+    }
+
+    /// Groups of `len` results where `leader` gives the (earlier or own) leader of each
+    fn by_leader(len: usize, leader: impl Fn(usize) -> usize) -> Self {
+        let leaders: Vec<usize> = (0..len).map(leader).collect();
+        let mut members = vec![Vec::new(); len];
+        for (i, &leader) in leaders.iter().enumerate() {
+            if leader != i {
+                members[leader].push(i);
+            }
+        }
+        Self { leaders, members, spans: HashMap::new() }
+    }
+
+    /// Position of the leader of the result at `index`
+    pub fn leader_of(&self, index: usize) -> usize {
+        self.leaders[index]
+    }
+
+    /// Visible rows as (result position, row kind), expanding the leaders `expanded` accepts
+    pub fn rows(&self, expanded: impl Fn(usize) -> bool) -> Vec<(usize, RowKind)> {
+        let mut rows = Vec::new();
+        for (i, &leader) in self.leaders.iter().enumerate() {
+            if leader != i {
+                continue;
+            }
+            let members = &self.members[i];
+            if let Some(&(first, last)) = self.spans.get(&i) {
+                let expanded = !members.is_empty() && expanded(i);
+                rows.push((
+                    i,
+                    RowKind::Session { results: members.len() + 1, first, last, expanded },
+                ));
+                if expanded {
+                    rows.extend(members.iter().map(|&m| (m, RowKind::Member)));
+                }
+            } else if members.is_empty() {
+                rows.push((i, RowKind::Single));
+            } else if expanded(i) {
+                rows.push((i, RowKind::Expanded(members.len())));
+                rows.extend(members.iter().map(|&m| (m, RowKind::Member)));
+            } else {
+                rows.push((i, RowKind::Collapsed(members.len())));
+            }
+        }
+        rows
+    }
+}
+
+/// Lowercase words of the start of `text`, with a plural `s` dropped (`tests` → `test`)
+pub fn word_set(text: &str) -> HashSet<String> {
+    let prefix: String = text.chars().take(COMPARED_CHARS).collect();
+    normalized_words(&prefix).collect()
+}
+
+/// Jaccard similarity of two word sets (0 when either is empty)
+pub fn similarity(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let shared = a.intersection(b).count();
+    shared as f64 / (a.len() + b.len() - shared) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+
+    use super::*;
+    use crate::models::EntryType;
+
+    fn entry(text: &str) -> SearchEntry {
+        SearchEntry {
+            entry_type: EntryType::UserPrompt,
+            display_text: text.to_string(),
+            timestamp: DateTime::from_timestamp(0, 0).unwrap(),
+            session_id: "s1".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_word_set_drops_plural_s() {
+        let words = word_set("Fix the Tests, please!");
+        let expected: HashSet<String> =
+            ["fix", "the", "test", "please"].iter().map(|w| w.to_string()).collect();
+        assert_eq!(words, expected);
+        // Short words and double-s endings are kept as they are
+        assert!(word_set("is class").contains("class"));
+        assert!(word_set("is class").contains("is"));
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity(&word_set("fix the test"), &word_set("fix the tests")), 1.0);
+        assert!(similarity(&word_set("fix the test"), &word_set("add a test")) < 0.5);
+        assert_eq!(similarity(&word_set("..."), &word_set("...")), 0.0);
+    }
+
+    #[test]
+    fn test_groups_similar_results() {
+        let entries = [
+            entry("fix the test"),
+            entry("add a readme"),
+            entry("fix the tests"),
+            entry("Fix the test"),
+        ];
+        let refs: Vec<&SearchEntry> = entries.iter().collect();
+        let mut agent = entry("fix the test");
+        agent.entry_type = EntryType::AgentMessage;
+
+        let groups = ResultGroups::similar(&refs);
+        assert_eq!(groups.leader_of(2), 0);
+        assert_eq!(groups.leader_of(3), 0);
+        assert_eq!(groups.rows(|_| false), vec![(0, RowKind::Collapsed(2)), (1, RowKind::Single)]);
+        assert_eq!(
+            groups.rows(|leader| leader == 0),
+            vec![
+                (0, RowKind::Expanded(2)),
+                (2, RowKind::Member),
+                (3, RowKind::Member),
+                (1, RowKind::Single),
+            ]
+        );
+
+        // Other entry types are never grouped together
+        let groups = ResultGroups::similar(&[&entries[0], &agent]);
+        assert_eq!(groups.rows(|_| false).len(), 2);
+    }
+
+    #[test]
+    fn test_groups_results_by_session() {
+        let mut entries = [entry("newest"), entry("other"), entry("oldest")];
+        entries[1].session_id = "s2".to_string();
+        for (entry, secs) in entries.iter_mut().zip([300, 200, 100]) {
+            entry.timestamp = DateTime::from_timestamp(secs, 0).unwrap();
+        }
+        let refs: Vec<&SearchEntry> = entries.iter().collect();
+        let at = |secs| DateTime::from_timestamp(secs, 0).unwrap();
+
+        let groups = ResultGroups::build(&refs, Grouping::Session);
+        assert_eq!(groups.leader_of(2), 0);
+        let session = |results, first, last, expanded| RowKind::Session {
+            results,
+            first: at(first),
+            last: at(last),
+            expanded,
+        };
+        assert_eq!(
+            groups.rows(|_| false),
+            vec![(0, session(2, 100, 300, false)), (1, session(1, 200, 200, false))]
+        );
+        assert_eq!(
+            groups.rows(|_| true),
+            vec![
+                (0, session(2, 100, 300, true)),
+                (2, RowKind::Member),
+                (1, session(1, 200, 200, false)),
+            ]
+        );
+        assert!(!session(1, 200, 200, false).is_leader());
+    }
+}
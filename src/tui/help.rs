@@ -0,0 +1,77 @@
+//! Reference sections of the help overlay besides the key bindings.
+//!
+//! The key bindings come from the [`Keymap`](super::keymap::Keymap); the filter syntax and the
+//! status bar legend are fixed and listed here, so they can be looked up without knowing the
+//! README (or triggering a filter error).
+
+/// Filter syntax: (example, meaning)
+pub const FILTER_SYNTAX: &[(&str, &str)] = &[
+    ("filter | text", "Filters before the |, fuzzy search after it"),
+    ("project:PATH", "Project path contains PATH (~ expands)"),
+    ("type:user", "User prompts (type:agent: agent responses)"),
+    ("after:DATE", "From DATE on (since: too); YYYY-MM-DD or 12h, 7d, 2w"),
+    ("before:DATE", "Before DATE"),
+    ("on:DATE", "On one day (UTC)"),
+    ("user:NAME", "Recorded user contains NAME"),
+    ("outcome:VALUE", "Session ended in success, abandoned or unclear"),
+    ("source:TOOL", "From claude, codex, aider or transcript history"),
+    ("root:NAME", "Indexed from the named Claude directory"),
+    ("tool:NAME", "Agent messages that called the tool"),
+    ("model:NAME", "Agent messages by a model containing NAME"),
+    ("branch:NAME", "Written on a git branch containing NAME"),
+    ("cwd:PATH", "Working directory contains PATH"),
+    ("starred:true", "Starred entries (starred:false: the others)"),
+    ("error:true", "Messages with a failed tool call (has:error too)"),
+    ("regex:PATTERN", "Text matches the regex (quote spaces and |)"),
+    ("has:KIND", "Messages with a thinking, tool_use, tool_result or image block"),
+    ("len>N", "Text longer than N characters (<, <=, =, >= too)"),
+    ("messages>N", "Sessions with more than N entries"),
+    ("a:x b:y", "Different fields: all must match (AND)"),
+    ("a:x a:y", "Same field: any may match (OR); AND/OR also explicit"),
+    ("-a:x", "Entries a:x doesn't match (NOT a:x too)"),
+    ("(a:x OR b:y) c:z", "Parentheses group clauses; operators go left to right"),
+];
+
+/// Status bar legend: (what is shown, meaning)
+pub const STATUS_BAR_LEGEND: &[(&str, &str)] = &[
+    ("[FUZZY]", "Search mode"),
+    ("M/N total", "M of all N entries match"),
+    ("M/F (N total)", "M fuzzy matches of the F entries the filter keeps"),
+    ("N projects", "Search scoped to the projects marked in F3"),
+    ("filter: ...", "The applied filter"),
+    ("entry I/M", "Position of the selected result"),
+    ("Enter: apply (~N)", "The typed filter is not applied yet; it would match ~N"),
+    ("[ERROR] ...", "The filter cannot be parsed"),
+];
+
+/// Rows of a reference section whose text contains the query (case-insensitive)
+pub fn filter_reference<'a>(
+    rows: &'a [(&'static str, &'static str)],
+    query: &str,
+) -> Vec<&'a (&'static str, &'static str)> {
+    let query = query.trim().to_lowercase();
+    rows.iter()
+        .filter(|(example, meaning)| {
+            query.is_empty()
+                || example.to_lowercase().contains(&query)
+                || meaning.to_lowercase().contains(&query)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_reference() {
+        assert_eq!(filter_reference(FILTER_SYNTAX, "").len(), FILTER_SYNTAX.len());
+
+        let branch = filter_reference(FILTER_SYNTAX, "BRANCH");
+        assert_eq!(branch.len(), 1);
+        assert_eq!(branch[0].0, "branch:NAME");
+
+        assert_eq!(filter_reference(STATUS_BAR_LEGEND, "fuzzy").len(), 2);
+        assert!(filter_reference(STATUS_BAR_LEGEND, "zzz-no-match").is_empty());
+    }
+}
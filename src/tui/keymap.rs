@@ -0,0 +1,644 @@
+//! Key bindings for the TUI.
+//!
+//! The keymap is the single source of truth for which key triggers which [`Action`]. It is
+//! consulted by the event loop, rendered (and filtered) in the help overlay, and exported as
+//! Markdown by `ai-history-explorer keymap export` so teams can share their setups.
+//!
+//! A binding is a sequence of one or more chords: `ctrl+y`, or `g g` (`g` pressed twice) and
+//! `ctrl+x ctrl+s` for vim and emacs habits. Bindings are overridden by action name, from the
+//! config's `keybindings` and from [`KEYMAP_FILE_NAME`] next to the config file:
+//!
+//! ```toml
+//! move_top = ["g g", "home"]
+//! move_bottom = ["G", "end"]
+//! copy = ["y y"]
+//! ```
+//!
+//! Bindings of actions that edit the search box at its cursor ([`Action::edits_search`]) only
+//! apply while a search is being typed, and then win over the others: `Home` moves the cursor
+//! while typing and selects the first result otherwise.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use super::events::Action;
+
+/// A single key press with modifiers (e.g. `Ctrl+Y`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    /// Whether a terminal key event triggers this chord
+    ///
+    /// Shift is ignored for plain characters since it is already reflected in the character.
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        if key.code != self.code {
+            return false;
+        }
+        if matches!(key.code, KeyCode::Char(_)) {
+            key.modifiers - KeyModifiers::SHIFT == self.modifiers - KeyModifiers::SHIFT
+        } else {
+            key.modifiers == self.modifiers
+        }
+    }
+
+    /// Like [`matches`](Self::matches), but unmodified non-character chords (arrows, Esc,
+    /// Enter, ...) also match when modifiers are held, so e.g. Shift+Up still moves up.
+    fn matches_loosely(&self, key: &KeyEvent) -> bool {
+        self.matches(key)
+            || (key.code == self.code
+                && self.modifiers.is_empty()
+                && !matches!(self.code, KeyCode::Char(_)))
+    }
+
+    /// Parse a chord like `ctrl+y`, `Up`, `F1` or `?` (case-insensitive modifiers and names)
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        if spec.is_empty() {
+            return Err(anyhow!("Empty key specification"));
+        }
+
+        // A trailing '+' is the plus key itself (e.g. "ctrl++")
+        let (mods_part, key_part) = match spec.strip_suffix("++") {
+            Some(mods) => (Some(mods), "+"),
+            None => match spec.rsplit_once('+') {
+                Some((mods, key)) if !mods.is_empty() => (Some(mods), key),
+                _ => (None, spec),
+            },
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        if let Some(mods) = mods_part {
+            for m in mods.split('+') {
+                modifiers |= match m.to_lowercase().as_str() {
+                    "ctrl" | "control" | "c" => KeyModifiers::CONTROL,
+                    "alt" | "meta" | "m" => KeyModifiers::ALT,
+                    "shift" | "s" => KeyModifiers::SHIFT,
+                    _ => return Err(anyhow!("Unknown modifier '{}' in '{}'", m, spec)),
+                };
+            }
+        }
+
+        let code = match key_part.to_lowercase().as_str() {
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" | "pgup" => KeyCode::PageUp,
+            "pagedown" | "pgdn" => KeyCode::PageDown,
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "space" => KeyCode::Char(' '),
+            lower => {
+                if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    KeyCode::F(n)
+                } else {
+                    let mut chars = key_part.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) if modifiers.contains(KeyModifiers::CONTROL) => {
+                            KeyCode::Char(c.to_ascii_lowercase())
+                        }
+                        (Some(c), None) => KeyCode::Char(c),
+                        _ => return Err(anyhow!("Unknown key '{}' in '{}'", key_part, spec)),
+                    }
+                }
+            }
+        };
+
+        // Terminals report Shift+Tab as BackTab rather than Tab with SHIFT held
+        if code == KeyCode::Tab && modifiers.contains(KeyModifiers::SHIFT) {
+            return Ok(Self { code: KeyCode::BackTab, modifiers: modifiers - KeyModifiers::SHIFT });
+        }
+
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) if self.modifiers.contains(KeyModifiers::CONTROL) => {
+                write!(f, "{}", c.to_ascii_uppercase())
+            }
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::Up => write!(f, "Up"),
+            KeyCode::Down => write!(f, "Down"),
+            KeyCode::Left => write!(f, "Left"),
+            KeyCode::Right => write!(f, "Right"),
+            KeyCode::Home => write!(f, "Home"),
+            KeyCode::End => write!(f, "End"),
+            KeyCode::PageUp => write!(f, "PageUp"),
+            KeyCode::PageDown => write!(f, "PageDown"),
+            KeyCode::Enter => write!(f, "Enter"),
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::Tab => write!(f, "Tab"),
+            KeyCode::BackTab => write!(f, "Shift+Tab"),
+            KeyCode::Backspace => write!(f, "Backspace"),
+            KeyCode::Delete => write!(f, "Delete"),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+/// Chords pressed one after another (e.g. `g g`); most bindings are a single chord
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeySequence(Vec<KeyChord>);
+
+impl KeySequence {
+    /// Parse whitespace-separated chords like `g g` or `ctrl+x ctrl+s`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let chords = spec.split_whitespace().map(KeyChord::parse).collect::<Result<Vec<_>>>()?;
+        if chords.is_empty() {
+            return Err(anyhow!("Empty key specification"));
+        }
+        Ok(Self(chords))
+    }
+
+    pub fn chords(&self) -> &[KeyChord] {
+        &self.0
+    }
+
+    /// Whether `keys` are the first keys of this sequence (or the whole of it)
+    fn starts_with(&self, keys: &[KeyEvent]) -> bool {
+        keys.len() <= self.0.len() && self.0.iter().zip(keys).all(|(chord, key)| chord.matches(key))
+    }
+}
+
+impl From<KeyChord> for KeySequence {
+    fn from(chord: KeyChord) -> Self {
+        Self(vec![chord])
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, chord) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", chord)?;
+        }
+        Ok(())
+    }
+}
+
+/// Ordered list of key bindings, with the keys typed so far of an unfinished sequence
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(KeySequence, Action)>,
+    pending: Vec<KeyEvent>,
+    /// Whether a search is being typed, enabling the search editing bindings
+    editing_search: bool,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use KeyCode::*;
+        const CTRL: KeyModifiers = KeyModifiers::CONTROL;
+        const NONE: KeyModifiers = KeyModifiers::NONE;
+        const ALT: KeyModifiers = KeyModifiers::ALT;
+
+        let bindings = vec![
+            // Quit
+            (KeyChord::new(Char('c'), CTRL), Action::Quit),
+            (KeyChord::new(Esc, NONE), Action::ClearSearch),
+            // Navigation (Vim/Emacs style)
+            (KeyChord::new(Up, NONE), Action::MoveUp),
+            (KeyChord::new(Down, NONE), Action::MoveDown),
+            (KeyChord::new(Char('p'), CTRL), Action::HistoryPrev),
+            (KeyChord::new(Char('n'), CTRL), Action::HistoryNext),
+            (KeyChord::new(PageUp, NONE), Action::PageUp),
+            (KeyChord::new(PageDown, NONE), Action::PageDown),
+            (KeyChord::new(Home, NONE), Action::MoveTop),
+            (KeyChord::new(End, NONE), Action::MoveBottom),
+            (KeyChord::new(Up, ALT), Action::TimeJumpNewer),
+            (KeyChord::new(Down, ALT), Action::TimeJumpOlder),
+            (KeyChord::new(Left, ALT), Action::JumpToParent),
+            (KeyChord::new(Right, ALT), Action::JumpToChild),
+            (KeyChord::new(Right, NONE), Action::ToggleGroup),
+            // Actions
+            (KeyChord::new(Enter, NONE), Action::ApplyFilter),
+            (KeyChord::new(Char('y'), CTRL), Action::CopyToClipboard),
+            (KeyChord::new(Char('y'), ALT), Action::CopySession),
+            (KeyChord::new(Char('i'), ALT), Action::CopyContext),
+            (KeyChord::new(Char('/'), NONE), Action::ToggleFilter),
+            (KeyChord::new(Tab, NONE), Action::ToggleFocus),
+            (KeyChord::new(Char('r'), CTRL), Action::Refresh),
+            (KeyChord::new(Char('o'), CTRL), Action::ToggleRoots),
+            (KeyChord::new(F(1), NONE), Action::ToggleHelp),
+            (KeyChord::new(F(2), NONE), Action::SessionList),
+            (KeyChord::new(F(3), NONE), Action::ProjectList),
+            (KeyChord::new(F(4), NONE), Action::CacheManager),
+            (KeyChord::new(Char('p'), ALT), Action::CommandPalette),
+            (KeyChord::new(BackTab, NONE), Action::ActivityCalendar),
+            (KeyChord::new(F(5), NONE), Action::ActivityCalendar),
+            (KeyChord::new(F(6), NONE), Action::SavedSearches),
+            (KeyChord::new(Char('s'), ALT), Action::SaveSearch),
+            (KeyChord::new(Char('b'), CTRL), Action::CodeBlocks),
+            (KeyChord::new(Char('t'), CTRL), Action::ToolLeaderboard),
+            (KeyChord::new(Char('e'), CTRL), Action::ExportExchange),
+            (KeyChord::new(Char('l'), CTRL), Action::PromptTimeline),
+            (KeyChord::new(Char('w'), CTRL), Action::ToggleWarnings),
+            (KeyChord::new(Char('g'), CTRL), Action::ToggleMinimap),
+            (KeyChord::new(Char('d'), ALT), Action::CycleDensity),
+            (KeyChord::new(Char('m'), ALT), Action::CycleMatchColumns),
+            (KeyChord::new(Char('t'), ALT), Action::ToggleTimestamps),
+            (KeyChord::new(Char('d'), CTRL), Action::CollapseSimilar),
+            (KeyChord::new(Char('s'), CTRL), Action::GroupBySession),
+            (KeyChord::new(Char('k'), CTRL), Action::Scratchpad),
+            (KeyChord::new(Char('f'), CTRL), Action::ToggleStar),
+            (KeyChord::new(Char('u'), CTRL), Action::RevealSensitive),
+            (KeyChord::new(Char('r'), ALT), Action::ResumeSession),
+            // Search input
+            (KeyChord::new(Backspace, NONE), Action::DeleteChar),
+            (KeyChord::new(Left, NONE), Action::CursorLeft),
+            (KeyChord::new(Right, NONE), Action::CursorRight),
+            (KeyChord::new(Home, NONE), Action::CursorHome),
+            (KeyChord::new(Char('a'), CTRL), Action::CursorHome),
+            (KeyChord::new(End, NONE), Action::CursorEnd),
+            (KeyChord::new(Delete, NONE), Action::DeleteForward),
+            (KeyChord::new(Char('w'), CTRL), Action::DeleteWord),
+            (KeyChord::new(Backspace, ALT), Action::DeleteWord),
+            (KeyChord::new(Char('u'), CTRL), Action::DeleteToStart),
+        ];
+
+        let bindings = bindings.into_iter().map(|(chord, action)| (chord.into(), action)).collect();
+        Self { bindings, pending: Vec::new(), editing_search: false }
+    }
+}
+
+impl Keymap {
+    /// Enable the search editing bindings while a search is being typed
+    pub fn set_editing_search(&mut self, editing: bool) {
+        self.editing_search = editing;
+    }
+
+    /// Bindings that currently apply: the search editing ones first while typing a search
+    fn active_bindings(&self) -> impl Iterator<Item = &(KeySequence, Action)> {
+        let editing = self.editing_search;
+        let (edits, others): (Vec<_>, Vec<_>) =
+            self.bindings.iter().partition(|(_, action)| action.edits_search());
+        edits.into_iter().filter(move |_| editing).chain(others)
+    }
+
+    /// Map a key event to an action
+    ///
+    /// A key that starts (or continues) a longer binding gives [`Action::None`] until the
+    /// sequence is complete. A key that doesn't continue the sequence drops the keys typed so
+    /// far and is mapped on its own. Unbound printable characters are treated as search input.
+    pub fn action_for(&mut self, key: &KeyEvent) -> Action {
+        if !self.pending.is_empty() {
+            let mut keys = std::mem::take(&mut self.pending);
+            keys.push(*key);
+            let mut continued = false;
+            for (sequence, action) in self.active_bindings() {
+                if sequence.chords().len() == keys.len() && sequence.starts_with(&keys) {
+                    return action.clone();
+                }
+                continued |= sequence.starts_with(&keys);
+            }
+            if continued {
+                self.pending = keys;
+                return Action::None;
+            }
+        }
+
+        let single = |matches: fn(&KeyChord, &KeyEvent) -> bool| {
+            self.active_bindings().find(|(sequence, _)| match sequence.chords() {
+                [chord] => matches(chord, key),
+                _ => false,
+            })
+        };
+        if let Some((_, action)) =
+            single(KeyChord::matches).or_else(|| single(KeyChord::matches_loosely))
+        {
+            return action.clone();
+        }
+        let keys = [*key];
+        if self.active_bindings().any(|(sequence, _)| sequence.starts_with(&keys)) {
+            self.pending.push(*key);
+            return Action::None;
+        }
+
+        match (key.code, key.modifiers) {
+            (KeyCode::Char(c), KeyModifiers::NONE) | (KeyCode::Char(c), KeyModifiers::SHIFT) => {
+                Action::UpdateSearch(c)
+            }
+            _ => Action::None,
+        }
+    }
+
+    /// Bind actions to other keys, by action name (`keybindings` in the config)
+    ///
+    /// Each listed action gets exactly the given keys, in place of its defaults; a key that
+    /// was bound to another action is unbound from it, unless only one of them edits the
+    /// search (they apply at different times).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for an unknown action name or an invalid key.
+    pub fn with_overrides(mut self, overrides: &BTreeMap<String, Vec<String>>) -> Result<Self> {
+        let defaults = Self::default();
+        for (name, keys) in overrides {
+            let action = defaults
+                .bindings
+                .iter()
+                .map(|(_, action)| action)
+                .find(|action| action.name() == name)
+                .cloned()
+                .ok_or_else(|| anyhow!("Unknown action '{}' in keybindings", name))?;
+            let sequences =
+                keys.iter().map(|key| KeySequence::parse(key)).collect::<Result<Vec<_>>>()?;
+
+            let position = self.bindings.iter().position(|(_, bound)| *bound == action);
+            self.bindings.retain(|(keys, bound)| {
+                *bound != action
+                    && !(sequences.contains(keys) && bound.edits_search() == action.edits_search())
+            });
+            let position = position.unwrap_or(self.bindings.len()).min(self.bindings.len());
+            self.bindings.splice(
+                position..position,
+                sequences.into_iter().map(|keys| (keys, action.clone())),
+            );
+        }
+        Ok(self)
+    }
+
+    /// All bindings in definition order
+    pub fn bindings(&self) -> &[(KeySequence, Action)] {
+        &self.bindings
+    }
+
+    /// Bindings grouped by action: (keys, action name, description), in definition order
+    pub fn help_rows(&self) -> Vec<(String, &'static str, &'static str)> {
+        let mut rows: Vec<(Vec<String>, &Action)> = Vec::new();
+        for (sequence, action) in &self.bindings {
+            match rows.iter_mut().find(|(_, a)| *a == action) {
+                Some((keys, _)) => keys.push(sequence.to_string()),
+                None => rows.push((vec![sequence.to_string()], action)),
+            }
+        }
+        rows.into_iter()
+            .map(|(keys, action)| (keys.join(", "), action.name(), action.description()))
+            .collect()
+    }
+
+    /// Render the keymap as a Markdown cheat sheet
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# ai-history-explorer keymap\n\n");
+        out.push_str("| Keys | Action | Description |\n");
+        out.push_str("|------|--------|-------------|\n");
+        for (keys, name, description) in self.help_rows() {
+            let keys = keys
+                .split(", ")
+                .map(|k| format!("`{}`", k.replace('|', "\\|")))
+                .collect::<Vec<_>>()
+                .join(", ");
+            out.push_str(&format!("| {} | `{}` | {} |\n", keys, name, description));
+        }
+        out
+    }
+}
+
+/// Name of the key binding file, in the same directory as the config file
+pub const KEYMAP_FILE_NAME: &str = "keymap.toml";
+
+/// Default location of the key binding file, if the platform has a config directory
+pub fn default_bindings_path() -> Option<PathBuf> {
+    crate::config::Config::default_path().map(|path| path.with_file_name(KEYMAP_FILE_NAME))
+}
+
+/// Read key bindings by action name from a TOML file (`action = ["keys", ...]`)
+///
+/// A missing file gives no bindings.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read or parsed.
+pub fn load_bindings_file(path: &Path) -> Result<BTreeMap<String, Vec<String>>> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    toml::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Filter help rows by a case-insensitive query over keys, action names and descriptions
+pub fn filter_help_rows<'a>(
+    rows: &'a [(String, &'static str, &'static str)],
+    query: &str,
+) -> Vec<&'a (String, &'static str, &'static str)> {
+    let query = query.trim().to_lowercase();
+    rows.iter()
+        .filter(|(keys, name, description)| {
+            query.is_empty()
+                || keys.to_lowercase().contains(&query)
+                || name.contains(&query)
+                || description.to_lowercase().contains(&query)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display_round_trip() {
+        for spec in
+            ["Ctrl+Y", "Up", "PageDown", "F1", "Esc", "/", "Ctrl+Alt+X", "Space", "Shift+Tab"]
+        {
+            let chord = KeyChord::parse(spec).unwrap();
+            assert_eq!(chord.to_string(), spec);
+        }
+    }
+
+    #[test]
+    fn test_parse_is_case_insensitive() {
+        let chord = KeyChord::parse("ctrl+y").unwrap();
+        assert_eq!(chord, KeyChord::new(KeyCode::Char('y'), KeyModifiers::CONTROL));
+
+        let chord = KeyChord::parse("PGUP").unwrap();
+        assert_eq!(chord.code, KeyCode::PageUp);
+
+        let chord = KeyChord::parse("backtab").unwrap();
+        assert_eq!(chord, KeyChord::parse("shift+tab").unwrap());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(KeyChord::parse("").is_err());
+        assert!(KeyChord::parse("hyper+x").is_err());
+        assert!(KeyChord::parse("ctrl+nope").is_err());
+    }
+
+    #[test]
+    fn test_matches_ignores_shift_for_chars() {
+        let chord = KeyChord::new(KeyCode::Char('?'), KeyModifiers::NONE);
+        assert!(chord.matches(&KeyEvent::new(KeyCode::Char('?'), KeyModifiers::SHIFT)));
+        assert!(!chord.matches(&KeyEvent::new(KeyCode::Char('?'), KeyModifiers::CONTROL)));
+    }
+
+    #[test]
+    fn test_action_for_falls_back_to_search_input() {
+        let mut keymap = Keymap::default();
+        let key = KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE);
+        assert_eq!(keymap.action_for(&key), Action::UpdateSearch('x'));
+
+        let key = KeyEvent::new(KeyCode::Char('x'), KeyModifiers::ALT);
+        assert_eq!(keymap.action_for(&key), Action::None);
+    }
+
+    #[test]
+    fn test_action_for_unmodified_keys_match_with_modifiers() {
+        let mut keymap = Keymap::default();
+        let key = KeyEvent::new(KeyCode::Up, KeyModifiers::SHIFT);
+        assert_eq!(keymap.action_for(&key), Action::MoveUp);
+    }
+
+    #[test]
+    fn test_help_rows_group_keys_by_action() {
+        let overrides =
+            BTreeMap::from([("move_up".to_string(), vec!["ctrl+k".to_string(), "up".to_string()])]);
+        let rows = Keymap::default().with_overrides(&overrides).unwrap().help_rows();
+        let move_up = rows.iter().find(|(_, name, _)| *name == "move_up").unwrap();
+        assert_eq!(move_up.0, "Ctrl+K, Up");
+    }
+
+    #[test]
+    fn test_filter_help_rows() {
+        let rows = Keymap::default().help_rows();
+
+        let copy = filter_help_rows(&rows, "clipboard");
+        assert_eq!(copy.len(), 1);
+        assert_eq!(copy[0].1, "copy");
+
+        let ctrl = filter_help_rows(&rows, "ctrl+y");
+        assert_eq!(ctrl.len(), 1);
+
+        assert_eq!(filter_help_rows(&rows, "").len(), rows.len());
+        assert!(filter_help_rows(&rows, "zzz-no-match").is_empty());
+    }
+
+    #[test]
+    fn test_with_overrides() {
+        let overrides = BTreeMap::from([
+            ("copy".to_string(), vec!["alt+c".to_string(), "ctrl+t".to_string()]),
+            ("quit".to_string(), vec!["ctrl+q".to_string()]),
+        ]);
+        let mut keymap = Keymap::default().with_overrides(&overrides).unwrap();
+
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        assert_eq!(keymap.action_for(&ctrl('q')), Action::Quit);
+        assert_eq!(keymap.action_for(&ctrl('c')), Action::None);
+        assert_eq!(keymap.action_for(&ctrl('y')), Action::None);
+        // Taken from the tool leaderboard
+        assert_eq!(keymap.action_for(&ctrl('t')), Action::CopyToClipboard);
+        assert!(keymap.help_rows().iter().all(|(_, name, _)| *name != "tool_leaderboard"));
+        assert_eq!(keymap.help_rows()[0].0, "Ctrl+Q");
+
+        let unknown = BTreeMap::from([("fly".to_string(), vec!["f".to_string()])]);
+        let err = Keymap::default().with_overrides(&unknown).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown action 'fly' in keybindings");
+        let bad_key = BTreeMap::from([("quit".to_string(), vec!["hyper+q".to_string()])]);
+        assert!(Keymap::default().with_overrides(&bad_key).is_err());
+    }
+
+    #[test]
+    fn test_overrides_keep_search_editing_keys_apart() {
+        let overrides = BTreeMap::from([(
+            "toggle_warnings".to_string(),
+            vec!["ctrl+w".to_string(), "f9".to_string()],
+        )]);
+        let mut keymap = Keymap::default().with_overrides(&overrides).unwrap();
+        let ctrl_w = KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL);
+        assert_eq!(keymap.action_for(&ctrl_w), Action::ToggleWarnings);
+
+        keymap.set_editing_search(true);
+        assert_eq!(keymap.action_for(&ctrl_w), Action::DeleteWord);
+    }
+
+    #[test]
+    fn test_sequences() {
+        let overrides = BTreeMap::from([
+            ("move_top".to_string(), vec!["g g".to_string()]),
+            ("copy".to_string(), vec!["ctrl+x ctrl+c".to_string()]),
+        ]);
+        let mut keymap = Keymap::default().with_overrides(&overrides).unwrap();
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+
+        assert_eq!(keymap.action_for(&key('g')), Action::None);
+        assert_eq!(keymap.action_for(&key('g')), Action::MoveTop);
+        assert_eq!(keymap.action_for(&ctrl('x')), Action::None);
+        assert_eq!(keymap.action_for(&ctrl('c')), Action::CopyToClipboard);
+        // Ctrl+C on its own still quits
+        assert_eq!(keymap.action_for(&ctrl('c')), Action::Quit);
+
+        // A key that doesn't continue the sequence is mapped on its own
+        assert_eq!(keymap.action_for(&key('g')), Action::None);
+        assert_eq!(keymap.action_for(&key('x')), Action::UpdateSearch('x'));
+        assert_eq!(keymap.action_for(&key('g')), Action::None);
+        assert_eq!(keymap.action_for(&ctrl('x')), Action::None);
+        assert_eq!(keymap.action_for(&ctrl('c')), Action::CopyToClipboard);
+
+        let move_top = keymap.help_rows().into_iter().find(|(_, name, _)| *name == "move_top");
+        assert_eq!(move_top.unwrap().0, "g g");
+        assert!(keymap.to_markdown().contains("| `Ctrl+X Ctrl+C` | `copy` |"));
+        assert!(KeySequence::parse("  ").is_err());
+        assert!(KeySequence::parse("g hyper+g").is_err());
+    }
+
+    #[test]
+    fn test_load_bindings_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(KEYMAP_FILE_NAME);
+        assert!(load_bindings_file(&path).unwrap().is_empty());
+
+        std::fs::write(&path, "move_top = [\"g g\", \"home\"]\nquit = [\"ctrl+q\"]\n").unwrap();
+        let bindings = load_bindings_file(&path).unwrap();
+        assert_eq!(bindings["move_top"], vec!["g g", "home"]);
+        assert!(Keymap::default().with_overrides(&bindings).is_ok());
+
+        std::fs::write(&path, "quit = \"ctrl+q").unwrap();
+        let err = load_bindings_file(&path).unwrap_err();
+        assert!(err.to_string().starts_with("Failed to parse"));
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let markdown = Keymap::default().to_markdown();
+        assert!(markdown.starts_with("# ai-history-explorer keymap"));
+        assert!(markdown.contains("| `Ctrl+Y` | `copy` | Copy selected entry to clipboard |"));
+        assert!(markdown.contains("| `F1` | `help` |"));
+    }
+}
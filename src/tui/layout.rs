@@ -1,8 +1,13 @@
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 
+/// Width of the time minimap sidebar, borders included
+pub const MINIMAP_WIDTH: u16 = 14;
+
 /// Split-pane layout configuration
 pub struct AppLayout {
     pub results_area: Rect,
+    /// Time minimap sidebar left of the results, when shown
+    pub minimap_area: Option<Rect>,
     pub preview_area: Rect,
     pub status_area: Rect,
 }
@@ -12,7 +17,9 @@ impl AppLayout {
     /// - Results list: 60% width (left)
     /// - Preview pane: 40% width (right)
     /// - Status bar: bottom row
-    pub fn new(area: Rect) -> Self {
+    ///
+    /// With `show_minimap`, the minimap sidebar is taken from the left of the results column.
+    pub fn new(area: Rect, show_minimap: bool) -> Self {
         // Vertical split: main area + status bar
         let vertical_chunks = Layout::default()
             .direction(Direction::Vertical)
@@ -31,14 +38,48 @@ impl AppLayout {
             ])
             .split(vertical_chunks[0]);
 
+        let (minimap_area, results_area) = if show_minimap {
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Length(MINIMAP_WIDTH), Constraint::Min(0)])
+                .split(horizontal_chunks[0]);
+            (Some(chunks[0]), chunks[1])
+        } else {
+            (None, horizontal_chunks[0])
+        };
+
         Self {
-            results_area: horizontal_chunks[0],
+            results_area,
+            minimap_area,
             preview_area: horizontal_chunks[1],
             status_area: vertical_chunks[1],
         }
     }
 }
 
+/// Compute a rectangle centered in `area` taking the given percentage of its size
+///
+/// Used for popup overlays drawn on top of the main layout.
+pub fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1])[1]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -46,7 +87,7 @@ mod tests {
     #[test]
     fn test_layout_splits_correctly() {
         let area = Rect::new(0, 0, 100, 30);
-        let layout = AppLayout::new(area);
+        let layout = AppLayout::new(area, false);
 
         // Status bar should be 1 row at bottom
         assert_eq!(layout.status_area.height, 1);
@@ -66,7 +107,7 @@ mod tests {
     #[test]
     fn test_layout_minimum_height() {
         let area = Rect::new(0, 0, 100, 4);
-        let layout = AppLayout::new(area);
+        let layout = AppLayout::new(area, false);
 
         // Status bar gets 1 row
         assert_eq!(layout.status_area.height, 1);
@@ -74,4 +115,29 @@ mod tests {
         assert_eq!(layout.results_area.height, 3);
         assert_eq!(layout.preview_area.height, 3);
     }
+
+    #[test]
+    fn test_layout_with_minimap() {
+        let area = Rect::new(0, 0, 100, 30);
+        let layout = AppLayout::new(area, true);
+
+        let minimap = layout.minimap_area.unwrap();
+        assert_eq!(minimap.x, 0);
+        assert_eq!(minimap.width, MINIMAP_WIDTH);
+        assert_eq!(layout.results_area.x, MINIMAP_WIDTH);
+        assert_eq!(layout.results_area.width, 60 - MINIMAP_WIDTH);
+        assert_eq!(layout.preview_area.width, 40);
+        assert!(AppLayout::new(area, false).minimap_area.is_none());
+    }
+
+    #[test]
+    fn test_centered_rect() {
+        let area = Rect::new(0, 0, 100, 40);
+        let popup = centered_rect(60, 50, area);
+
+        assert_eq!(popup.width, 60);
+        assert_eq!(popup.height, 20);
+        assert_eq!(popup.x, 20);
+        assert_eq!(popup.y, 10);
+    }
 }
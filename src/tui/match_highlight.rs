@@ -0,0 +1,104 @@
+//! Highlighting of what the fuzzy query matched.
+//!
+//! The results list marks the characters nucleo matched the entry with, so it's visible why
+//! an entry is in the results. The preview marks exact occurrences of the query's words
+//! instead, which are easier to spot in long text than scattered fuzzy matches.
+
+use std::cell::RefCell;
+use std::ops::Range;
+
+use nucleo::pattern::{CaseMatching, Normalization, Pattern};
+use nucleo::{Matcher, Utf32Str};
+
+/// Finds the matched parts of entry texts for one fuzzy query
+#[derive(Default)]
+pub struct MatchHighlighter {
+    pattern: Pattern,
+    /// Words of the query as typed, with nucleo's `^`, `'` and `$` markers removed
+    words: Vec<String>,
+    matcher: RefCell<Matcher>,
+}
+
+impl MatchHighlighter {
+    /// Highlighter for the fuzzy portion of the query (matched the way the results are)
+    pub fn new(query: &str) -> Self {
+        let words = query
+            .split_whitespace()
+            .filter(|word| !word.starts_with('!'))
+            .map(|word| word.trim_start_matches(['^', '\'']).trim_end_matches('$').to_string())
+            .filter(|word| !word.is_empty())
+            .collect();
+        Self {
+            pattern: Pattern::parse(query, CaseMatching::Smart, Normalization::Smart),
+            words,
+            matcher: RefCell::new(Matcher::default()),
+        }
+    }
+
+    /// Character positions of `text` matched by the query, ascending (empty without a match)
+    pub fn fuzzy_indices(&self, text: &str) -> Vec<usize> {
+        if self.pattern.atoms.is_empty() {
+            return Vec::new();
+        }
+        let mut buf = Vec::new();
+        let mut indices = Vec::new();
+        let haystack = Utf32Str::new(text, &mut buf);
+        if self.pattern.indices(haystack, &mut self.matcher.borrow_mut(), &mut indices).is_none() {
+            return Vec::new();
+        }
+        indices.sort_unstable();
+        indices.dedup();
+        indices.into_iter().map(|index| index as usize).collect()
+    }
+
+    /// Byte ranges of `line` where a query word occurs, sorted and merged
+    ///
+    /// Words are matched ignoring ASCII case unless they contain an uppercase letter, like
+    /// nucleo's smart case.
+    pub fn exact_ranges(&self, line: &str) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        let folded = line.to_ascii_lowercase();
+        for word in &self.words {
+            let case_sensitive = word.chars().any(char::is_uppercase);
+            let (haystack, needle) = if case_sensitive {
+                (line, word.clone())
+            } else {
+                (folded.as_str(), word.to_ascii_lowercase())
+            };
+            ranges.extend(haystack.match_indices(&needle).map(|(i, m)| i..i + m.len()));
+        }
+        ranges.sort_by_key(|range| range.start);
+
+        let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        merged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_indices() {
+        let highlighter = MatchHighlighter::new("prs");
+        assert_eq!(highlighter.fuzzy_indices("parser"), vec![0, 2, 3]);
+        assert!(highlighter.fuzzy_indices("lexer").is_empty());
+        assert!(MatchHighlighter::new("").fuzzy_indices("parser").is_empty());
+    }
+
+    #[test]
+    fn test_exact_ranges_smart_case_and_merging() {
+        let highlighter = MatchHighlighter::new("^pars 'ser !lexer");
+        assert_eq!(highlighter.exact_ranges("Fix the Parser"), vec![8..14]);
+        assert!(highlighter.exact_ranges("lexer only").is_empty());
+
+        let case_sensitive = MatchHighlighter::new("API");
+        assert_eq!(case_sensitive.exact_ranges("api API"), vec![4..7]);
+    }
+}
@@ -0,0 +1,220 @@
+//! Time minimap sidebar state.
+//!
+//! Buckets the current results by week (Monday-based, in the display zone) into a continuous timeline from the
+//! newest to the oldest week, so long result lists can be navigated by time region. Empty weeks
+//! are kept to show gaps. When there are more weeks than sidebar rows, neighbouring weeks share
+//! a row.
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+use crate::models::SearchEntry;
+use crate::utils::{DisplayZone, display_zone};
+
+/// Results of one week
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeekBucket {
+    /// Monday of the week
+    pub start: NaiveDate,
+    pub count: usize,
+    /// Lowest result index in this week (None for empty weeks)
+    pub first_index: Option<usize>,
+}
+
+/// One sidebar row: one or more consecutive weeks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinimapRow {
+    /// Monday of the newest week in the row
+    pub start: NaiveDate,
+    pub count: usize,
+    pub first_index: Option<usize>,
+    /// Range of week positions (into [`Minimap::weeks`]) covered by the row
+    pub weeks: std::ops::Range<usize>,
+}
+
+/// Weekly density of a result list, newest week first
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Minimap {
+    pub weeks: Vec<WeekBucket>,
+    /// Week position of every result, in result order
+    week_of: Vec<usize>,
+}
+
+impl Minimap {
+    pub fn build(entries: &[&SearchEntry]) -> Self {
+        Self::build_in(entries, display_zone())
+    }
+
+    /// [`Minimap::build`] with weeks in `zone`
+    pub fn build_in(entries: &[&SearchEntry], zone: DisplayZone) -> Self {
+        let starts: Vec<NaiveDate> =
+            entries.iter().map(|e| week_start(zone.date(&e.timestamp))).collect();
+        let (Some(&newest), Some(&oldest)) = (starts.iter().max(), starts.iter().min()) else {
+            return Self::default();
+        };
+
+        let week_count = ((newest - oldest).num_weeks() + 1) as usize;
+        let mut weeks: Vec<WeekBucket> = (0..week_count)
+            .map(|i| WeekBucket {
+                start: newest - Duration::weeks(i as i64),
+                count: 0,
+                first_index: None,
+            })
+            .collect();
+
+        let mut week_of = Vec::with_capacity(starts.len());
+        for (index, start) in starts.iter().enumerate() {
+            let position = (newest - *start).num_weeks() as usize;
+            let week = &mut weeks[position];
+            week.count += 1;
+            week.first_index.get_or_insert(index);
+            week_of.push(position);
+        }
+
+        Self { weeks, week_of }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.weeks.is_empty()
+    }
+
+    /// Group the weeks into at most `height` rows of (nearly) equal week spans
+    pub fn rows(&self, height: usize) -> Vec<MinimapRow> {
+        if height == 0 || self.weeks.is_empty() {
+            return Vec::new();
+        }
+        let row_count = height.min(self.weeks.len());
+        (0..row_count)
+            .map(|row| {
+                let span =
+                    row * self.weeks.len() / row_count..(row + 1) * self.weeks.len() / row_count;
+                let weeks = &self.weeks[span.clone()];
+                MinimapRow {
+                    start: weeks[0].start,
+                    count: weeks.iter().map(|w| w.count).sum(),
+                    first_index: weeks.iter().filter_map(|w| w.first_index).min(),
+                    weeks: span,
+                }
+            })
+            .collect()
+    }
+
+    /// Week position of the result at `index`
+    pub fn week_of(&self, index: usize) -> Option<usize> {
+        self.week_of.get(index).copied()
+    }
+
+    /// First result of the nearest non-empty week older (`delta > 0`) or newer (`delta < 0`)
+    /// than the week of the result at `index`
+    pub fn jump_from(&self, index: usize, delta: isize) -> Option<usize> {
+        let current = self.week_of(index)?;
+        let mut position = current;
+        loop {
+            position = position.checked_add_signed(delta.signum())?;
+            let week = self.weeks.get(position)?;
+            if week.first_index.is_some() {
+                return week.first_index;
+            }
+        }
+    }
+}
+
+/// Result to select when a row is clicked: its first result, or the nearest older one for
+/// an empty row (the nearest newer one if there is nothing older)
+pub fn row_target(rows: &[MinimapRow], row: usize) -> Option<usize> {
+    let row = row.min(rows.len().checked_sub(1)?);
+    rows[row..]
+        .iter()
+        .find_map(|r| r.first_index)
+        .or_else(|| rows[..row].iter().rev().find_map(|r| r.first_index))
+}
+
+/// Monday of the week containing `date`
+pub(super) fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeZone, Utc};
+
+    use super::*;
+    use crate::models::EntryType;
+
+    fn entry(year: i32, month: u32, day: u32) -> SearchEntry {
+        let timestamp: DateTime<Utc> = Utc.with_ymd_and_hms(year, month, day, 12, 0, 0).unwrap();
+        SearchEntry {
+            entry_type: EntryType::UserPrompt,
+            display_text: "prompt".to_string(),
+            timestamp,
+            session_id: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn build(entries: &[SearchEntry]) -> Minimap {
+        Minimap::build(&entries.iter().collect::<Vec<_>>())
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_build_buckets_by_week_with_gaps() {
+        // 2024-01-17 (Wed) and 2024-01-15 (Mon) share a week; nothing in the week of Jan 8
+        let minimap = build(&[entry(2024, 1, 17), entry(2024, 1, 15), entry(2024, 1, 3)]);
+
+        assert_eq!(minimap.weeks.len(), 3);
+        assert_eq!(minimap.weeks[0].start, date(2024, 1, 15));
+        assert_eq!(minimap.weeks[0].count, 2);
+        assert_eq!(minimap.weeks[1].count, 0);
+        assert_eq!(minimap.weeks[1].first_index, None);
+        assert_eq!(minimap.weeks[2].start, date(2024, 1, 1));
+        assert_eq!(minimap.weeks[2].first_index, Some(2));
+        assert_eq!(minimap.week_of(1), Some(0));
+    }
+
+    #[test]
+    fn test_build_empty() {
+        let minimap = build(&[]);
+        assert!(minimap.is_empty());
+        assert!(minimap.rows(10).is_empty());
+    }
+
+    #[test]
+    fn test_rows_merge_weeks_when_short_on_space() {
+        let entries: Vec<SearchEntry> = (0..10).map(|i| entry(2024, 3, 31 - i * 3)).collect();
+        let minimap = build(&entries);
+
+        let rows = minimap.rows(2);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows.iter().map(|r| r.count).sum::<usize>(), 10);
+        assert_eq!(rows[0].first_index, Some(0));
+        assert_eq!(rows[1].weeks.end, minimap.weeks.len());
+
+        // One row per week when there is enough room
+        assert_eq!(minimap.rows(100).len(), minimap.weeks.len());
+    }
+
+    #[test]
+    fn test_jump_skips_empty_weeks() {
+        let minimap = build(&[entry(2024, 1, 17), entry(2024, 1, 15), entry(2024, 1, 3)]);
+
+        assert_eq!(minimap.jump_from(0, 1), Some(2));
+        assert_eq!(minimap.jump_from(2, -1), Some(0));
+        assert_eq!(minimap.jump_from(2, 1), None);
+        assert_eq!(minimap.jump_from(0, -1), None);
+    }
+
+    #[test]
+    fn test_row_target_for_empty_row() {
+        let minimap = build(&[entry(2024, 1, 17), entry(2024, 1, 3)]);
+        let rows = minimap.rows(10);
+
+        assert_eq!(row_target(&rows, 1), Some(1));
+        assert_eq!(row_target(&rows, 0), Some(0));
+        // Clicks below the last row select the oldest region
+        assert_eq!(row_target(&rows, 50), Some(1));
+        assert_eq!(row_target(&[], 0), None);
+    }
+}
@@ -1,28 +1,219 @@
 // TUI module for interactive search interface
+mod activity;
 mod app;
+mod browse;
+mod cache_screen;
+mod code_picker;
+mod columns;
+mod command_palette;
 mod events;
+mod export_dialog;
+mod filter_preview;
+#[cfg(test)]
+mod golden;
+mod grouping;
+mod help;
+pub mod keymap;
 mod layout;
+mod match_highlight;
+mod minimap;
+mod power;
+mod preview;
+mod privacy;
+mod prompt_timeline;
 mod rendering;
+mod replay;
+mod saved_searches;
+mod scratchpad;
+mod search_history;
+mod search_input;
 mod terminal;
+mod theme;
 mod timestamps;
+mod tool_leaderboard;
+mod ui_state;
+#[cfg(feature = "watch")]
+mod watch;
 
 use anyhow::Result;
-pub use app::App;
+pub use app::{App, split_search_query};
+pub use cache_screen::CacheControl;
+use columns::{DEFAULT_COLUMNS, ListColumn};
+pub use keymap::Keymap;
+use search_history::{DEFAULT_SEARCH_HISTORY_SIZE, SearchHistory};
 use terminal::TerminalManager;
+pub use theme::Theme;
+pub use ui_state::{SelectedEntry, UiState};
 
+use crate::bookmarks::Bookmarks;
+use crate::config::{Config, StartupView};
+use crate::indexer::{IndexReport, RootStatus};
 use crate::models::SearchEntry;
+use crate::summarize::Summaries;
 
 /// Run the interactive TUI
-pub fn run_interactive(entries: Vec<SearchEntry>) -> Result<()> {
+///
+/// `report` lists what was skipped while indexing and other warnings; failed files can be
+/// retried from the warnings view (Ctrl+W). `config` picks what the TUI opens into and how paths are shown.
+/// With `cache`, the index cache the entries came from can be managed from the TUI (F4).
+/// A `query` (a saved search picked with `--preset`) is opened instead of the startup view.
+pub fn run_interactive(
+    entries: Vec<SearchEntry>,
+    report: &IndexReport,
+    config: &Config,
+    cache: Option<CacheControl>,
+    query: Option<&str>,
+) -> Result<()> {
+    run_app(App::new(entries).with_report(report), config, cache, query)
+}
+
+/// Run the interactive TUI over a multi-root workspace
+///
+/// Same as [`run_interactive`], with the roots overview (Ctrl+O) populated from `roots`.
+pub fn run_interactive_workspace(
+    entries: Vec<SearchEntry>,
+    roots: Vec<RootStatus>,
+    report: &IndexReport,
+    config: &Config,
+    cache: Option<CacheControl>,
+    query: Option<&str>,
+) -> Result<()> {
+    run_app(App::new(entries).with_roots(roots).with_report(report), config, cache, query)
+}
+
+/// Open the startup view (or `query`), run until quit and save the query for
+/// [`StartupView::LastQuery`]
+fn run_app(
+    app: App,
+    config: &Config,
+    cache: Option<CacheControl>,
+    query: Option<&str>,
+) -> Result<()> {
+    let startup = config.startup_view;
+    let state_path = UiState::default_path().filter(|_| startup == StartupView::LastQuery);
+    // With no history kept, the file is still written so earlier searches are forgotten
+    let history_size = config.search_history_size.unwrap_or(DEFAULT_SEARCH_HISTORY_SIZE);
+    let history_path = SearchHistory::default_path();
+    let history = history_path
+        .as_deref()
+        .filter(|_| history_size > 0)
+        .map(SearchHistory::load)
+        .unwrap_or_default();
+    let app = match cache {
+        Some(cache) => with_watcher(app, &cache).with_cache_control(cache),
+        None => app,
+    };
+    match Theme::from_config(config.theme, config.accent_color.as_deref(), &config.theme_colors) {
+        Ok(theme) => theme::set_theme(theme),
+        Err(e) => {
+            eprintln!("Warning: {:#}; using the theme's own colors", e);
+            theme::set_theme(Theme::preset(config.theme));
+        }
+    }
+    let bookmarks_path = Bookmarks::default_path();
+    let bookmarks =
+        bookmarks_path.as_deref().map_or_else(|| Ok(Bookmarks::default()), Bookmarks::load);
+    let app = match bookmarks {
+        Ok(bookmarks) => app.with_bookmarks(bookmarks, bookmarks_path),
+        // Starring would overwrite the unreadable file; keep stars for this run only
+        Err(e) => {
+            eprintln!("Warning: {:#}; starred entries won't be saved", e);
+            app
+        }
+    };
+    let summaries = Summaries::default_path()
+        .map_or_else(|| Ok(Summaries::default()), |path| Summaries::load(&path));
+    let app = match summaries {
+        Ok(summaries) => app.with_summaries(summaries),
+        Err(e) => {
+            eprintln!("Warning: {:#}; sessions are shown without summaries", e);
+            app
+        }
+    };
+    let app = app
+        .with_keymap(keymap(config))
+        .with_path_display(config.path_display())
+        .with_power_mode(config.power_mode)
+        .with_search_selection(config.search_selection)
+        .with_list_density(config.list_density, Config::default_path())
+        .with_list_columns(list_columns(config))
+        .with_timestamps(config.timestamps.unwrap_or_default())
+        .with_match_columns(config.match_columns)
+        .with_saved_searches(config.saved_searches.clone())
+        .with_search_history(history, history_size)
+        .with_sensitive_projects(
+            config.sensitive_project_paths(),
+            config.sensitive_passphrase.clone(),
+        );
+    let mut app = match query {
+        Some(query) => app.with_query(query),
+        None => app
+            .with_default_filter(config.default_filter.as_deref().unwrap_or_default())
+            .with_startup_view(startup, state_path.as_deref().and_then(UiState::load)),
+    };
     let mut manager = TerminalManager::new()?;
-    let mut app = App::new(entries);
 
     let result = app.run(manager.terminal_mut());
 
     // Restore terminal (Drop will also clean up if this fails)
     manager.restore()?;
 
-    result
+    if let Some(path) = state_path
+        && let Err(e) = app.ui_state().save(&path)
+    {
+        eprintln!("Warning: Failed to save UI state: {:#}", e);
+    }
+    if let Some(path) = history_path
+        && let Err(e) = app.search_history().save(&path)
+    {
+        eprintln!("Warning: Failed to save search history: {:#}", e);
+    }
+
+    result?;
+    match app.take_resume() {
+        Some(target) => target.run(),
+        None => Ok(()),
+    }
+}
+
+/// Default key bindings with the config's `keybindings` and the keymap file applied
+///
+/// Bindings in the keymap file win over the config's for the same action. Falls back to the
+/// defaults (with a warning) if the overrides are invalid.
+pub fn keymap(config: &Config) -> Keymap {
+    let mut overrides = config.keybindings.clone();
+    if let Some(path) = keymap::default_bindings_path() {
+        match keymap::load_bindings_file(&path) {
+            Ok(bindings) => overrides.extend(bindings),
+            Err(e) => eprintln!("Warning: {:#}", e),
+        }
+    }
+    Keymap::default().with_overrides(&overrides).unwrap_or_else(|e| {
+        eprintln!("Warning: {:#}; using the default key bindings", e);
+        Keymap::default()
+    })
+}
+
+/// Result columns of the config's `list_columns`
+///
+/// Falls back to the default columns (with a warning) if the list is invalid.
+fn list_columns(config: &Config) -> Vec<ListColumn> {
+    columns::parse_columns(&config.list_columns).unwrap_or_else(|e| {
+        eprintln!("Warning: {:#}; using the default list columns", e);
+        DEFAULT_COLUMNS.to_vec()
+    })
+}
+
+/// Refresh the entries automatically while the history files of the cache's roots change
+#[cfg(feature = "watch")]
+fn with_watcher(app: App, cache: &CacheControl) -> App {
+    let paths: Vec<_> = cache.roots().iter().map(|root| root.path.clone()).collect();
+    app.with_watcher(watch::HistoryWatcher::new(&paths))
+}
+
+#[cfg(not(feature = "watch"))]
+fn with_watcher(app: App, _cache: &CacheControl) -> App {
+    app
 }
 
 #[cfg(test)]
@@ -0,0 +1,183 @@
+//! Low-power pacing of the event loop.
+//!
+//! By default the TUI wakes every 100ms to poll for input and redraws at least that often
+//! (picking up terminal resizes and relative timestamps). Left open all day on a laptop, that
+//! is ten wakeups a second for nothing. In low-power mode the loop polls less often, redraws
+//! only when something changed, and watch mode stops refreshing the index while the machine
+//! runs on battery (the changes are picked up once it is plugged in, or with Ctrl+R).
+
+use std::time::{Duration, Instant};
+
+use crate::config::PowerMode;
+
+/// Input poll interval and forced redraw interval in normal mode
+pub const NORMAL_TICK: Duration = Duration::from_millis(100);
+
+/// Input poll interval in low-power mode
+pub const LOW_POWER_TICK: Duration = Duration::from_millis(500);
+
+/// How often the power source is checked again
+const BATTERY_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Power mode and the last known power source
+#[derive(Debug, Clone)]
+pub struct PowerState {
+    mode: PowerMode,
+    /// `None` if the power source can't be detected on this system
+    on_battery: Option<bool>,
+    checked_at: Instant,
+    probe: fn() -> Option<bool>,
+}
+
+impl Default for PowerState {
+    fn default() -> Self {
+        Self::new(PowerMode::default())
+    }
+}
+
+impl PowerState {
+    pub fn new(mode: PowerMode) -> Self {
+        Self::with_probe(mode, on_battery)
+    }
+
+    /// Detect the power source with `probe` instead of asking the system
+    pub fn with_probe(mode: PowerMode, probe: fn() -> Option<bool>) -> Self {
+        let on_battery = if mode == PowerMode::Normal { None } else { probe() };
+        Self { mode, on_battery, checked_at: Instant::now(), probe }
+    }
+
+    /// Check the power source again if the last check is old enough
+    pub fn update(&mut self) {
+        if self.mode != PowerMode::Normal && self.checked_at.elapsed() >= BATTERY_CHECK_INTERVAL {
+            self.on_battery = (self.probe)();
+            self.checked_at = Instant::now();
+        }
+    }
+
+    /// Whether the event loop runs at the low-power pace
+    pub fn low_power(&self) -> bool {
+        match self.mode {
+            PowerMode::Normal => false,
+            PowerMode::Low => true,
+            PowerMode::Auto => self.on_battery == Some(true),
+        }
+    }
+
+    /// Whether watch mode should hold back background refreshes
+    #[cfg(any(feature = "watch", test))]
+    pub fn pause_background_refresh(&self) -> bool {
+        self.low_power() && self.on_battery == Some(true)
+    }
+
+    /// How long to wait for input before the next loop iteration
+    pub fn poll_interval(&self) -> Duration {
+        if self.low_power() { LOW_POWER_TICK } else { NORMAL_TICK }
+    }
+
+    /// Redraw at least this often even if nothing changed (`None`: only on changes)
+    pub fn forced_redraw_interval(&self) -> Option<Duration> {
+        (!self.low_power()).then_some(NORMAL_TICK)
+    }
+}
+
+/// Whether the machine runs on battery, if that can be detected
+#[cfg(target_os = "linux")]
+pub fn on_battery() -> Option<bool> {
+    on_battery_from_sysfs(std::path::Path::new("/sys/class/power_supply"))
+}
+
+/// Whether the machine runs on battery, if that can be detected
+#[cfg(target_os = "macos")]
+pub fn on_battery() -> Option<bool> {
+    let output = std::process::Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let source = text.lines().next()?;
+    Some(source.contains("'Battery Power'"))
+}
+
+/// Whether the machine runs on battery, if that can be detected
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn on_battery() -> Option<bool> {
+    None
+}
+
+/// Read the power supplies under `dir`: on battery if no mains adapter is online but a
+/// battery is present
+#[cfg(any(target_os = "linux", test))]
+fn on_battery_from_sysfs(dir: &std::path::Path) -> Option<bool> {
+    let read = |path: std::path::PathBuf| {
+        std::fs::read_to_string(path).map(|s| s.trim().to_string()).unwrap_or_default()
+    };
+    let mut has_battery = false;
+    let mut has_mains = false;
+    for supply in std::fs::read_dir(dir).ok()?.filter_map(|item| item.ok()) {
+        match read(supply.path().join("type")).as_str() {
+            "Battery" => has_battery = true,
+            "Mains" | "USB" => {
+                has_mains = true;
+                if read(supply.path().join("online")) == "1" {
+                    return Some(false);
+                }
+            }
+            _ => {}
+        }
+    }
+    match (has_battery, has_mains) {
+        (false, _) => Some(false),
+        (true, true) => Some(true),
+        // A battery without an adapter to ask: can't tell
+        (true, false) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn supply(dir: &std::path::Path, name: &str, kind: &str, online: Option<&str>) {
+        let path = dir.join(name);
+        fs::create_dir_all(&path).unwrap();
+        fs::write(path.join("type"), format!("{}\n", kind)).unwrap();
+        if let Some(online) = online {
+            fs::write(path.join("online"), format!("{}\n", online)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_modes() {
+        let normal = PowerState::with_probe(PowerMode::Normal, || Some(true));
+        assert!(!normal.low_power());
+        assert_eq!(normal.forced_redraw_interval(), Some(NORMAL_TICK));
+
+        let low = PowerState::with_probe(PowerMode::Low, || None);
+        assert!(low.low_power());
+        assert_eq!(low.poll_interval(), LOW_POWER_TICK);
+        assert_eq!(low.forced_redraw_interval(), None);
+        // Unknown power source: keep refreshing
+        assert!(!low.pause_background_refresh());
+
+        let plugged_in = PowerState::with_probe(PowerMode::Auto, || Some(false));
+        assert!(!plugged_in.low_power());
+        let on_battery = PowerState::with_probe(PowerMode::Auto, || Some(true));
+        assert!(on_battery.low_power());
+        assert!(on_battery.pause_background_refresh());
+    }
+
+    #[test]
+    fn test_sysfs_detection() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let dir = temp.path();
+        assert_eq!(on_battery_from_sysfs(&dir.join("missing")), None);
+        // Desktop: no battery
+        assert_eq!(on_battery_from_sysfs(dir), Some(false));
+
+        supply(dir, "BAT0", "Battery", None);
+        supply(dir, "AC", "Mains", Some("0"));
+        assert_eq!(on_battery_from_sysfs(dir), Some(true));
+
+        supply(dir, "AC", "Mains", Some("1"));
+        assert_eq!(on_battery_from_sysfs(dir), Some(false));
+    }
+}
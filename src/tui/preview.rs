@@ -0,0 +1,118 @@
+//! Line cursor and visual-mode selection for the focused preview pane.
+//!
+//! When the preview has focus, a cursor moves over the lines of the selected entry's text.
+//! Starting visual mode anchors the current line; moving the cursor then extends a
+//! line-wise selection that can be copied on its own instead of the whole entry.
+
+/// Cursor and optional visual-mode anchor, both as line indices into the entry text
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PreviewSelection {
+    cursor: usize,
+    anchor: Option<usize>,
+}
+
+impl PreviewSelection {
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn is_visual(&self) -> bool {
+        self.anchor.is_some()
+    }
+
+    /// Move the cursor by `delta` lines, clamped to `line_count`
+    pub fn move_cursor(&mut self, delta: isize, line_count: usize) {
+        let max = line_count.saturating_sub(1);
+        self.cursor = self.cursor.saturating_add_signed(delta).min(max);
+    }
+
+    /// Jump to the first (`false`) or last (`true`) line
+    pub fn jump(&mut self, to_end: bool, line_count: usize) {
+        self.cursor = if to_end { line_count.saturating_sub(1) } else { 0 };
+    }
+
+    /// Start visual mode at the cursor, or leave it if already active
+    pub fn toggle_visual(&mut self) {
+        self.anchor = match self.anchor {
+            Some(_) => None,
+            None => Some(self.cursor),
+        };
+    }
+
+    pub fn cancel_visual(&mut self) {
+        self.anchor = None;
+    }
+
+    /// Inclusive line range of the visual selection, if active
+    pub fn range(&self) -> Option<(usize, usize)> {
+        self.anchor.map(|anchor| (anchor.min(self.cursor), anchor.max(self.cursor)))
+    }
+
+    /// Text of the selected lines, or `None` outside visual mode
+    pub fn selected_text(&self, text: &str) -> Option<String> {
+        let (start, end) = self.range()?;
+        let lines: Vec<&str> = text.lines().skip(start).take(end - start + 1).collect();
+        Some(lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "intro\n```rust\nfn main() {}\n```\noutro";
+
+    #[test]
+    fn test_move_cursor_clamps() {
+        let mut selection = PreviewSelection::default();
+        selection.move_cursor(-1, 5);
+        assert_eq!(selection.cursor(), 0);
+        selection.move_cursor(10, 5);
+        assert_eq!(selection.cursor(), 4);
+        selection.move_cursor(1, 0);
+        assert_eq!(selection.cursor(), 0);
+    }
+
+    #[test]
+    fn test_jump() {
+        let mut selection = PreviewSelection::default();
+        selection.jump(true, 5);
+        assert_eq!(selection.cursor(), 4);
+        selection.jump(false, 5);
+        assert_eq!(selection.cursor(), 0);
+    }
+
+    #[test]
+    fn test_visual_selection_forward_and_backward() {
+        let mut selection = PreviewSelection::default();
+        selection.move_cursor(1, 5);
+        selection.toggle_visual();
+        selection.move_cursor(2, 5);
+        assert_eq!(selection.range(), Some((1, 3)));
+        assert_eq!(selection.selected_text(TEXT).unwrap(), "```rust\nfn main() {}\n```");
+
+        // Moving above the anchor selects upwards
+        selection.move_cursor(-3, 5);
+        assert_eq!(selection.range(), Some((0, 1)));
+    }
+
+    #[test]
+    fn test_no_selection_outside_visual_mode() {
+        let mut selection = PreviewSelection::default();
+        assert_eq!(selection.selected_text(TEXT), None);
+
+        selection.toggle_visual();
+        assert!(selection.is_visual());
+        selection.cancel_visual();
+        assert!(!selection.is_visual());
+        assert_eq!(selection.range(), None);
+    }
+
+    #[test]
+    fn test_single_line_selection() {
+        let mut selection = PreviewSelection::default();
+        selection.jump(true, 5);
+        selection.toggle_visual();
+        assert_eq!(selection.selected_text(TEXT).unwrap(), "outro");
+    }
+}
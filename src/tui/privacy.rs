@@ -0,0 +1,123 @@
+//! Privacy lock for sensitive projects.
+//!
+//! Entries of projects listed in `sensitive_projects` are redacted in the results, preview
+//! and session list until revealed, and can't be copied or exported while hidden. Revealing
+//! takes an explicit confirmation (plus `sensitive_passphrase`, if set) and lasts until the
+//! project is hidden again or the TUI exits. This guards against onlookers while demoing,
+//! not against anyone who can read the history files.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::models::SearchEntry;
+
+/// Text shown in place of a hidden entry
+pub const REDACTED_TEXT: &str = "🔒 Sensitive project (Ctrl+U to reveal)";
+
+/// Sensitive projects and which of them are revealed
+#[derive(Debug, Clone, Default)]
+pub struct PrivacyLock {
+    /// Project paths whose entries (including subdirectories) are sensitive
+    sensitive: Vec<PathBuf>,
+    passphrase: Option<String>,
+    revealed: HashSet<PathBuf>,
+}
+
+/// Confirmation popup for revealing a sensitive project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevealPrompt {
+    /// Sensitive project being revealed
+    pub project: PathBuf,
+    /// Passphrase typed so far, when one is required
+    pub passphrase: Option<String>,
+}
+
+impl PrivacyLock {
+    pub fn new(sensitive: Vec<PathBuf>, passphrase: Option<String>) -> Self {
+        Self {
+            sensitive,
+            passphrase: passphrase.filter(|p| !p.is_empty()),
+            revealed: HashSet::new(),
+        }
+    }
+
+    /// Sensitive project `entry` belongs to, revealed or not
+    pub fn sensitive_project(&self, entry: &SearchEntry) -> Option<&Path> {
+        let project = entry.project_path.as_deref()?;
+        self.sensitive.iter().map(PathBuf::as_path).find(|root| project.starts_with(root))
+    }
+
+    /// Whether `entry` is redacted
+    pub fn is_locked(&self, entry: &SearchEntry) -> bool {
+        self.sensitive_project(entry).is_some_and(|root| !self.revealed.contains(root))
+    }
+
+    /// Popup asking to reveal `project`, with a passphrase field if one is configured
+    pub fn prompt(&self, project: &Path) -> RevealPrompt {
+        RevealPrompt {
+            project: project.to_path_buf(),
+            passphrase: self.passphrase.as_ref().map(|_| String::new()),
+        }
+    }
+
+    /// Reveal the prompt's project if its passphrase (if required) is right
+    pub fn reveal(&mut self, prompt: &RevealPrompt) -> bool {
+        if self.passphrase.is_some() && self.passphrase != prompt.passphrase {
+            return false;
+        }
+        self.revealed.insert(prompt.project.clone());
+        true
+    }
+
+    /// Redact `project` again
+    pub fn hide(&mut self, project: &Path) {
+        self.revealed.remove(project);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+
+    use super::*;
+    use crate::models::EntryType;
+
+    fn entry(project: Option<&str>) -> SearchEntry {
+        SearchEntry {
+            entry_type: EntryType::UserPrompt,
+            display_text: "rotate the client's API keys".to_string(),
+            timestamp: DateTime::from_timestamp(1_704_099_600, 0).unwrap(),
+            project_path: project.map(PathBuf::from),
+            session_id: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_sensitive_projects_include_subdirectories() {
+        let lock = PrivacyLock::new(vec![PathBuf::from("/work/client")], None);
+        assert!(lock.is_locked(&entry(Some("/work/client"))));
+        assert!(lock.is_locked(&entry(Some("/work/client/api"))));
+        assert!(!lock.is_locked(&entry(Some("/work/client-tools"))));
+        assert!(!lock.is_locked(&entry(None)));
+    }
+
+    #[test]
+    fn test_reveal_checks_passphrase_and_hide_locks_again() {
+        let mut lock =
+            PrivacyLock::new(vec![PathBuf::from("/work/client")], Some("hunter2".to_string()));
+        let entry = entry(Some("/work/client/api"));
+        let mut prompt = lock.prompt(lock.sensitive_project(&entry).unwrap());
+        assert_eq!(prompt.passphrase.as_deref(), Some(""));
+
+        assert!(!lock.reveal(&prompt));
+        assert!(lock.is_locked(&entry));
+
+        prompt.passphrase = Some("hunter2".to_string());
+        assert!(lock.reveal(&prompt));
+        assert!(!lock.is_locked(&entry));
+
+        lock.hide(Path::new("/work/client"));
+        assert!(lock.is_locked(&entry));
+    }
+}
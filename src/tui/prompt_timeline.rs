@@ -0,0 +1,85 @@
+//! Prompt timeline popup state.
+//!
+//! Shows the user prompts of the selected entry's project oldest first, one line each, and
+//! lets the list be copied or written to a file as Markdown.
+
+use crate::export::{PromptTimeline, TimelinePrompt};
+
+/// State of the prompt timeline popup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptTimelineView {
+    pub timeline: PromptTimeline,
+    pub selected: usize,
+    /// File name being typed, while the "write to file" prompt is open
+    pub filename: Option<String>,
+}
+
+impl PromptTimelineView {
+    /// Open with the newest prompt selected
+    pub fn new(timeline: PromptTimeline) -> Self {
+        let selected = timeline.prompts.len().saturating_sub(1);
+        Self { timeline, selected, filename: None }
+    }
+
+    pub fn selected_prompt(&self) -> Option<&TimelinePrompt> {
+        self.timeline.prompts.get(self.selected)
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let max = self.timeline.prompts.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(max);
+    }
+
+    /// Open the file name prompt, pre-filled with `prompts-<project dir name>.md`
+    pub fn start_filename_prompt(&mut self) {
+        let name = self
+            .timeline
+            .project
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "project".to_string());
+        self.filename = Some(format!("prompts-{}.md", name));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use chrono::DateTime;
+
+    use super::*;
+    use crate::models::{EntryType, SearchEntry};
+
+    fn view(prompts: usize) -> PromptTimelineView {
+        let entries: Vec<SearchEntry> = (0..prompts)
+            .map(|i| SearchEntry {
+                entry_type: EntryType::UserPrompt,
+                display_text: format!("prompt {}", i),
+                timestamp: DateTime::from_timestamp_millis(1_704_099_600_000 + i as i64).unwrap(),
+                project_path: Some(PathBuf::from("/work/app")),
+                session_id: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+                ..Default::default()
+            })
+            .collect();
+        PromptTimelineView::new(PromptTimeline::for_project(&entries, Path::new("/work/app")))
+    }
+
+    #[test]
+    fn test_opens_on_newest_prompt_and_clamps() {
+        let mut view = view(3);
+        assert_eq!(view.selected_prompt().unwrap().line, "prompt 2");
+        view.move_selection(5);
+        assert_eq!(view.selected, 2);
+        view.move_selection(-10);
+        assert_eq!(view.selected, 0);
+    }
+
+    #[test]
+    fn test_filename_prompt_uses_project_name() {
+        let mut view = view(0);
+        assert!(view.selected_prompt().is_none());
+        view.start_filename_prompt();
+        assert_eq!(view.filename.as_deref(), Some("prompts-app.md"));
+    }
+}
@@ -4,14 +4,27 @@
 //! the "view" layer of the TUI architecture. It renders:
 //!
 //! - **Results list**: Scrollable list of matched entries with icons and metadata
-//! - **Preview pane**: Detailed view of selected entry (timestamp, project, content)
+//! - **Preview pane**: Detailed view of selected entry (timestamp, project, content), with a
+//!   line cursor and visual-mode selection highlight when focused
 //! - **Status bar**: Count indicators, active filters, keybindings, and status messages
+//! - **Code block picker**: Popup listing fenced code blocks (language + first line)
+//! - **Export dialog**: Popup summarizing the exchange to export, its format and file prompt
+//! - **Tool leaderboard**: Popup table of tool-output bytes per tool and project
+//! - **Prompt timeline**: Popup listing a project's prompts oldest first, one line each
+//! - **Help overlay**: Popup listing key bindings, filtered by a typed query
+//! - **Warnings overview**: Popup listing files skipped while indexing, with their reasons
+//! - **Roots overview**: Popup listing workspace roots with counts, refresh time and health
+//! - **Cache screen**: Popup with the index cache's location, size and entries per root, and
+//!   the clear/rebuild/compact actions
+//! - **Time minimap**: Optional sidebar with the weekly density of the results and the
+//!   visible range highlighted
 //!
 //! # Design Philosophy
 //!
 //! - **Pure rendering**: Functions take immutable references and render to frame
 //! - **No state mutation**: All rendering logic is side-effect-free
-//! - **Color consistency**: Uses RGB values for consistent theme across terminal emulators
+//! - **Color consistency**: RGB colors from the [theme](super::theme), the same in every
+//!   terminal emulator
 //!
 //! # Layout
 //!
@@ -26,32 +39,160 @@
 //! └─────────────────────────────────────────────────┘
 //! ```
 
+use std::collections::HashSet;
+use std::ops::Range;
+
+use chrono::Datelike;
 use ratatui::Frame;
-use ratatui::layout::Rect;
-use ratatui::style::{Color, Modifier, Style};
+use ratatui::layout::{Constraint, Rect};
+use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span, Text};
-use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph, Wrap};
-
-use super::app::{MessageType, StatusMessage};
-use super::layout::AppLayout;
-use super::timestamps::format_timestamp;
-use crate::models::{EntryType, SearchEntry};
-use crate::utils::format_path_with_tilde;
+use ratatui::widgets::{
+    Block, Borders, Cell, Clear, List, ListItem, ListState, Paragraph, Row, Table, TableState, Wrap,
+};
+
+use super::activity::{ACTIVITY_LEVELS, ActivityCalendar};
+use super::app::{MessageType, StatusMessage, split_search_query};
+use super::browse::{BrowseKind, BrowseView};
+use super::cache_screen::{CacheJob, CacheScreen};
+use super::code_picker::CodeBlockPicker;
+use super::columns::{ColumnKind, DEFAULT_TEXT_WIDTH, ListColumn};
+use super::command_palette::CommandPalette;
+use super::export_dialog::ExportDialog;
+use super::filter_preview::FilterEstimate;
+use super::grouping::RowKind;
+use super::layout::{AppLayout, centered_rect};
+use super::match_highlight::MatchHighlighter;
+use super::minimap::Minimap;
+use super::preview::PreviewSelection;
+use super::privacy::{PrivacyLock, REDACTED_TEXT, RevealPrompt};
+use super::prompt_timeline::PromptTimelineView;
+use super::replay::SessionReplay;
+use super::saved_searches::SavedSearchList;
+use super::scratchpad::Scratchpad;
+use super::theme::theme;
+use super::timestamps::{format_exact, format_timestamp};
+use super::tool_leaderboard::ToolLeaderboard;
+use crate::analytics::{ToolUsageSort, format_bytes};
+use crate::bookmarks::Bookmarks;
+use crate::config::ListDensity;
+use crate::export::ATTACHMENTS_DIR_NAME;
+use crate::indexer::{Diagnostic, RootHealth, RootStatus, SkippedItem};
+use crate::models::{ContentBlock, EntryType, MessageContent, SearchEntry, SourceKind};
+use crate::parsers::SessionSummary;
+use crate::summarize::Summaries;
+use crate::utils::{PathDisplay, TimestampStyle, format_path_with_tilde};
 
 /// App state needed for rendering
 pub struct RenderState<'a> {
     pub search_query: &'a str,
+    /// Byte offset of the search cursor, present while a search is being typed
+    pub search_cursor: Option<usize>,
     pub filtered_count: usize,
     pub total_count: usize,
+    /// Number of projects the search is scoped to (0 when it isn't)
+    pub scoped_projects: usize,
     pub filter_error: Option<&'a str>,
+    /// Matches of the typed filter while it differs from the applied one
+    pub filter_estimate: Option<&'a FilterEstimate>,
     pub status_message: Option<&'a StatusMessage>,
+    pub roots_overview: Option<RootsOverview<'a>>,
+    pub warnings: Option<WarningsOverview<'a>>,
+    pub session_summary: Option<&'a SessionSummary>,
+    /// Cursor/selection of the preview, present while the preview has focus
+    pub preview_selection: Option<&'a PreviewSelection>,
+    pub code_picker: Option<&'a CodeBlockPicker>,
+    /// Present while the scratchpad popup is open
+    pub scratchpad: Option<&'a Scratchpad>,
+    pub tool_leaderboard: Option<&'a ToolLeaderboard>,
+    pub export_dialog: Option<&'a ExportDialog>,
+    pub prompt_timeline: Option<&'a PromptTimelineView>,
+    pub browse: Option<&'a BrowseView>,
+    pub activity: Option<&'a ActivityCalendar>,
+    pub saved_searches: Option<&'a SavedSearchList>,
+    pub replay: Option<&'a SessionReplay>,
+    pub cache_screen: Option<&'a CacheScreen>,
+    pub command_palette: Option<&'a CommandPalette>,
+    /// Group role of each result row while results are grouped (else empty)
+    pub row_kinds: &'a [RowKind],
+    /// How project paths are shown
+    pub path_display: &'a PathDisplay,
+    /// Starred entries, marked with ★ in the results
+    pub bookmarks: &'a Bookmarks,
+    /// Session summaries, shown on the session rows instead of their first result
+    pub summaries: &'a Summaries,
+    /// What the fuzzy query matched, highlighted in the results and preview
+    pub highlighter: &'a MatchHighlighter,
+    /// Sensitive projects whose entries are redacted, and the reveal confirmation if open
+    pub privacy: &'a PrivacyLock,
+    pub reveal_prompt: Option<&'a RevealPrompt>,
+    pub help: Option<HelpOverlay<'a>>,
+    /// Weekly density of the matched entries, present while the minimap is shown
+    pub minimap: Option<&'a Minimap>,
+    /// Lines per result in the results list
+    pub density: ListDensity,
+    /// Columns of each result, in order
+    pub columns: &'a [ListColumn],
+    /// How the results and the session replay show times
+    pub timestamps: TimestampStyle,
+}
+
+/// Help overlay state: bindings and reference rows already filtered by the typed query
+pub struct HelpOverlay<'a> {
+    pub rows: Vec<&'a (String, &'static str, &'static str)>,
+    pub filter_syntax: Vec<&'static (&'static str, &'static str)>,
+    pub status_bar: Vec<&'static (&'static str, &'static str)>,
+    pub query: &'a str,
+    /// Lines scrolled past
+    pub scroll: u16,
+}
+
+impl HelpOverlay<'_> {
+    /// Number of lines in the overlay, without its border
+    pub fn line_count(&self) -> usize {
+        help_lines(self).len()
+    }
+}
+
+/// Workspace roots overview popup state
+pub struct RootsOverview<'a> {
+    pub roots: &'a [RootStatus],
+    pub disabled: &'a HashSet<String>,
+    pub selected: usize,
+}
+
+/// Indexing warnings popup state
+pub struct WarningsOverview<'a> {
+    pub items: &'a [SkippedItem],
+    /// Listed after the skipped items; not selectable
+    pub diagnostics: &'a [Diagnostic],
+    pub selected: usize,
+}
+
+/// How result rows are marked besides their entry's text
+struct RowDecorations<'a> {
+    /// Group role of each row (empty when not grouped)
+    kinds: &'a [RowKind],
+    paths: &'a PathDisplay,
+    bookmarks: &'a Bookmarks,
+    summaries: &'a Summaries,
+    highlighter: &'a MatchHighlighter,
+    privacy: &'a PrivacyLock,
+    /// Lines per result
+    density: ListDensity,
+    /// Columns of each result, in order
+    columns: &'a [ListColumn],
+    timestamps: TimestampStyle,
 }
 
 /// Status bar entry counts
-struct StatusCounts {
+struct StatusCounts<'a> {
     matched: usize,
     filtered: usize,
     total: usize,
+    scoped_projects: usize,
+    /// Estimated matches of the typed filter, until it is applied
+    pending: Option<&'a FilterEstimate>,
 }
 
 /// Render the entire UI
@@ -61,10 +202,44 @@ pub fn render_ui(
     selected_idx: usize,
     state: &RenderState,
 ) {
-    let layout = AppLayout::new(frame.area());
-
-    render_results_list(frame, layout.results_area, entries, selected_idx);
-    render_preview(frame, layout.preview_area, entries.get(selected_idx).copied());
+    let layout = AppLayout::new(frame.area(), state.minimap.is_some());
+
+    let visible = visible_results(selected_idx, layout.results_area, state.density);
+    let decorations = RowDecorations {
+        kinds: state.row_kinds,
+        paths: state.path_display,
+        bookmarks: state.bookmarks,
+        summaries: state.summaries,
+        highlighter: state.highlighter,
+        privacy: state.privacy,
+        density: state.density,
+        columns: state.columns,
+        timestamps: state.timestamps,
+    };
+    let title = results_title(
+        state.search_query,
+        state.search_cursor,
+        layout.results_area.width.saturating_sub(2) as usize,
+    );
+    render_results_list(frame, layout.results_area, title, entries, selected_idx, &decorations);
+    if let (Some(area), Some(minimap)) = (layout.minimap_area, state.minimap) {
+        render_minimap(frame, area, minimap, visible, selected_idx);
+    }
+    // A hidden entry is previewed with its text redacted
+    let selected = entries.get(selected_idx).copied();
+    let redacted = selected
+        .filter(|entry| state.privacy.is_locked(entry))
+        .map(|entry| SearchEntry { display_text: REDACTED_TEXT.to_string(), ..entry.clone() });
+    let no_highlights = MatchHighlighter::default();
+    render_preview(
+        frame,
+        layout.preview_area,
+        redacted.as_ref().or(selected),
+        state.session_summary,
+        state.preview_selection,
+        state.path_display,
+        if redacted.is_some() { &no_highlights } else { state.highlighter },
+    );
     render_status_bar(
         frame,
         layout.status_area,
@@ -72,633 +247,3265 @@ pub fn render_ui(
             matched: entries.len(),
             filtered: state.filtered_count,
             total: state.total_count,
+            scoped_projects: state.scoped_projects,
+            pending: state.filter_estimate,
         },
         selected_idx,
         state.search_query,
         state.filter_error,
         state.status_message,
     );
+
+    if let Some(overview) = &state.roots_overview {
+        render_roots_overview(frame, centered_rect(70, 50, frame.area()), overview);
+    }
+    if let Some(warnings) = &state.warnings {
+        render_warnings_overview(frame, centered_rect(80, 60, frame.area()), warnings);
+    }
+    if let Some(picker) = state.code_picker {
+        render_code_picker(frame, centered_rect(70, 60, frame.area()), picker);
+    }
+    if let Some(scratchpad) = state.scratchpad {
+        render_scratchpad(frame, centered_rect(70, 60, frame.area()), scratchpad);
+    }
+    if let Some(dialog) = state.export_dialog {
+        render_export_dialog(frame, centered_rect(60, 40, frame.area()), dialog);
+    }
+    if let Some(board) = state.tool_leaderboard {
+        render_tool_leaderboard(
+            frame,
+            centered_rect(80, 70, frame.area()),
+            board,
+            state.path_display,
+        );
+    }
+    if let Some(view) = state.prompt_timeline {
+        render_prompt_timeline(
+            frame,
+            centered_rect(80, 80, frame.area()),
+            view,
+            state.path_display,
+        );
+    }
+    if let Some(view) = state.browse {
+        render_browse(frame, centered_rect(80, 80, frame.area()), view);
+    }
+    if let Some(calendar) = state.activity {
+        render_activity(frame, centered_rect(80, 50, frame.area()), calendar);
+    }
+    if let Some(list) = state.saved_searches {
+        render_saved_searches(frame, centered_rect(70, 50, frame.area()), list, state.search_query);
+    }
+    if let Some(replay) = state.replay {
+        let area = centered_rect(90, 90, frame.area());
+        render_replay(frame, area, replay, state.path_display, state.timestamps);
+    }
+    if let Some(screen) = state.cache_screen {
+        render_cache_screen(frame, centered_rect(70, 60, frame.area()), screen);
+    }
+    if let Some(palette) = state.command_palette {
+        render_command_palette(frame, centered_rect(70, 50, frame.area()), palette);
+    }
+    if let Some(help) = &state.help {
+        render_help_overlay(frame, centered_rect(70, 70, frame.area()), help);
+    }
+    if let Some(prompt) = state.reveal_prompt {
+        render_reveal_prompt(
+            frame,
+            centered_rect(60, 25, frame.area()),
+            prompt,
+            state.path_display,
+        );
+    }
+}
+
+fn render_reveal_prompt(frame: &mut Frame, area: Rect, prompt: &RevealPrompt, paths: &PathDisplay) {
+    let muted = Style::default().fg(theme().muted);
+    let accent = theme().accent;
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("Reveal entries of ", muted),
+            Span::raw(paths.format(&prompt.project)),
+            Span::styled("?", muted),
+        ]),
+        Line::styled("They stay visible until hidden again (Ctrl+U) or exit.", muted),
+    ];
+    if let Some(passphrase) = &prompt.passphrase {
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::styled("Passphrase: ", muted),
+            Span::raw("•".repeat(passphrase.chars().count())),
+            Span::styled("▏", Style::default().fg(accent)),
+        ]));
+    }
+
+    let title = if prompt.passphrase.is_some() {
+        " Sensitive project | Enter: reveal | Esc: cancel "
+    } else {
+        " Sensitive project | Enter/y: reveal | Esc: cancel "
+    };
+    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(accent))
+            .title(title),
+    );
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
 }
 
-fn render_results_list(
-    frame: &mut Frame,
-    area: Rect,
-    entries: &[&SearchEntry],
-    selected_idx: usize,
-) {
-    let items: Vec<ListItem> = entries
+/// Search line, then a titled section each for key bindings, filter syntax and the status bar
+fn help_lines<'a>(help: &HelpOverlay<'a>) -> Vec<Line<'a>> {
+    let muted = Style::default().fg(theme().muted);
+    let heading = Style::default().fg(theme().text).add_modifier(Modifier::BOLD);
+    let key = Style::default().fg(theme().accent).add_modifier(Modifier::BOLD);
+    let row = |left: &str, right: &'static str| {
+        Line::from(vec![Span::styled(format!("{:<20}", left), key), Span::raw(right)])
+    };
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("Search: ", muted),
+            Span::raw(help.query.to_string()),
+            Span::styled("▏", Style::default().fg(theme().accent)),
+        ]),
+        Line::from(""),
+    ];
+
+    if help.rows.is_empty() && help.filter_syntax.is_empty() && help.status_bar.is_empty() {
+        lines.push(Line::styled("No matching help", muted));
+        return lines;
+    }
+    if !help.rows.is_empty() {
+        lines.push(Line::styled("Key bindings", heading));
+        lines.extend(help.rows.iter().map(|(keys, _, description)| row(keys, description)));
+    }
+    let references = [("Filter syntax", &help.filter_syntax), ("Status bar", &help.status_bar)];
+    for (title, rows) in references {
+        if rows.is_empty() {
+            continue;
+        }
+        if lines.len() > 2 {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::styled(title, heading));
+        lines.extend(rows.iter().map(|(example, meaning)| row(example, meaning)));
+    }
+    lines
+}
+
+fn render_help_overlay(frame: &mut Frame, area: Rect, help: &HelpOverlay) {
+    let paragraph = Paragraph::new(help_lines(help)).scroll((help.scroll, 0)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme().accent))
+            .title(" Help | type to filter | ↑/↓: scroll | Esc: close "),
+    );
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
+}
+
+fn render_command_palette(frame: &mut Frame, area: Rect, palette: &CommandPalette) {
+    let muted = Style::default().fg(theme().muted);
+    let accent = theme().accent;
+
+    let mut items = vec![
+        ListItem::new(Line::from(vec![
+            Span::styled("> ", Style::default().fg(accent)),
+            Span::raw(palette.query.clone()),
+            Span::styled("▏", Style::default().fg(accent)),
+        ])),
+        ListItem::new(""),
+    ];
+    let matches = palette.matches();
+    if matches.is_empty() {
+        items.push(ListItem::new(Line::styled("No matching commands", muted)));
+    }
+    items.extend(matches.iter().enumerate().map(|(i, (keys, _, description))| {
+        let style = if i == palette.selected {
+            Style::default().bg(theme().highlight).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        ListItem::new(Line::from(vec![
+            Span::raw(format!("{} ", description)),
+            Span::styled(keys.clone(), muted),
+        ]))
+        .style(style)
+    }));
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(accent))
+        .title(" Commands | type to filter | Enter: run | Esc ");
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(List::new(items).block(block), area);
+}
+
+fn render_saved_searches(frame: &mut Frame, area: Rect, list: &SavedSearchList, current: &str) {
+    let muted = Style::default().fg(theme().muted);
+    let accent = theme().accent;
+
+    let mut items: Vec<ListItem> = list
+        .searches
         .iter()
         .enumerate()
-        .map(|(idx, entry)| {
-            let icon = match entry.entry_type {
-                EntryType::UserPrompt => "👤",
-                EntryType::AgentMessage => "🤖",
+        .map(|(i, (name, query))| {
+            let style = if i == list.selected && list.name.is_none() {
+                Style::default().bg(theme().highlight).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
             };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:<20} ", name), Style::default().fg(accent)),
+                Span::raw(query.clone()),
+            ]))
+            .style(style)
+        })
+        .collect();
 
-            let timestamp = format_timestamp(&entry.timestamp);
-            let project = entry
-                .project_path
-                .as_ref()
-                .map(|p| format_path_with_tilde(p))
-                .unwrap_or_else(|| "global".to_string());
+    if items.is_empty() {
+        items.push(ListItem::new(Line::styled(
+            "No saved searches (s: save the current one)",
+            muted,
+        )));
+    }
+    if let Some(name) = &list.name {
+        items.push(ListItem::new(""));
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled("Save ", muted),
+            Span::raw(current.trim().to_string()),
+            Span::styled(" as: ", muted),
+            Span::raw(name.clone()),
+            Span::styled("▏", Style::default().fg(accent)),
+        ])));
+    }
+
+    let title = if list.name.is_some() {
+        " Saved searches | Enter: save | Esc: cancel "
+    } else {
+        " Saved searches | Enter: open | s: save current | d: delete | Esc "
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(accent))
+        .title(title);
 
-            // Truncate display text for list view (first line only)
-            let preview_text = entry
-                .display_text
-                .lines()
-                .next()
-                .unwrap_or("")
-                .chars()
-                .take(50)
-                .collect::<String>();
+    frame.render_widget(Clear, area);
+    frame.render_widget(List::new(items).block(block), area);
+}
 
-            let content = format!("{} {} | {} | {}", icon, timestamp, project, preview_text);
+fn render_code_picker(frame: &mut Frame, area: Rect, picker: &CodeBlockPicker) {
+    let muted = Style::default().fg(theme().muted);
+    let accent = theme().accent;
 
-            let style = if idx == selected_idx {
-                Style::default()
-                    .fg(Color::Rgb(250, 250, 250)) // Bright text
-                    .bg(Color::Rgb(16, 185, 129)) // Emerald background
-                    .add_modifier(Modifier::BOLD)
+    let mut items: Vec<ListItem> = picker
+        .blocks
+        .iter()
+        .enumerate()
+        .map(|(i, block)| {
+            let style = if i == picker.selected {
+                Style::default().bg(theme().highlight).add_modifier(Modifier::BOLD)
             } else {
-                Style::default().fg(Color::Rgb(113, 113, 122)) // Muted text
+                Style::default()
             };
-
-            ListItem::new(content).style(style)
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:<12}", block.language.as_deref().unwrap_or("text")),
+                    Style::default().fg(accent),
+                ),
+                Span::raw(block.first_line().to_string()),
+            ]))
+            .style(style)
         })
         .collect();
 
+    if items.is_empty() {
+        items.push(ListItem::new(Line::styled("No code blocks found", muted)));
+    }
+    if let Some(filename) = &picker.filename {
+        items.push(ListItem::new(""));
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled("Write to: ", muted),
+            Span::raw(filename.clone()),
+            Span::styled("▏", Style::default().fg(accent)),
+        ])));
+    }
+
+    let scope = if picker.session_scope { "session" } else { "entry" };
+    let title = if picker.filename.is_some() {
+        format!(" Code blocks ({}) | Enter: write | Esc: cancel ", scope)
+    } else {
+        format!(
+            " Code blocks ({}) | Enter: copy | a: scratchpad | w: write | s: entry/session | Esc ",
+            scope
+        )
+    };
+
     let list = List::new(items).block(
         Block::default()
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Rgb(113, 113, 122)))
-            .title(" Results "),
+            .border_style(Style::default().fg(accent))
+            .title(title),
     );
 
+    frame.render_widget(Clear, area);
     frame.render_widget(list, area);
 }
 
-fn render_preview(frame: &mut Frame, area: Rect, entry: Option<&SearchEntry>) {
-    let content = if let Some(entry) = entry {
-        let timestamp = format_timestamp(&entry.timestamp);
-        let project = entry
-            .project_path
-            .as_ref()
-            .map(|p| format_path_with_tilde(p))
-            .unwrap_or_else(|| "global".to_string());
-        let session_id = entry.session_id.clone();
-
-        let mut lines = vec![
-            Line::from(vec![
-                Span::styled("Timestamp: ", Style::default().fg(Color::Rgb(113, 113, 122))),
-                Span::raw(timestamp),
-            ]),
-            Line::from(vec![
-                Span::styled("Project: ", Style::default().fg(Color::Rgb(113, 113, 122))),
-                Span::raw(project),
-            ]),
-            Line::from(vec![
-                Span::styled("Session: ", Style::default().fg(Color::Rgb(113, 113, 122))),
-                Span::raw(session_id),
-            ]),
-            Line::from(""),
-        ];
-
-        // Add display text (already truncated by SearchEntry)
-        for line in entry.display_text.lines() {
-            lines.push(Line::from(line));
-        }
+fn render_scratchpad(frame: &mut Frame, area: Rect, scratchpad: &Scratchpad) {
+    let muted = Style::default().fg(theme().muted);
+    let accent = theme().accent;
 
-        Text::from(lines)
+    let mut lines: Vec<Line> = if scratchpad.is_empty() {
+        vec![Line::styled(
+            "Empty: press a in the focused preview or the code block picker to append",
+            muted,
+        )]
     } else {
-        Text::from("No entry selected")
+        // split('\n') rather than lines() keeps a trailing empty line for the cursor
+        scratchpad.text().split('\n').map(|line| Line::raw(line.to_string())).collect()
     };
+    if let Some(last) = lines.last_mut() {
+        last.push_span(Span::styled("▏", Style::default().fg(accent)));
+    }
+    // Keep the end, where editing happens, in view
+    let height = area.height.saturating_sub(2) as usize;
+    let hidden = lines.len().saturating_sub(height);
+    lines.drain(..hidden);
+
+    let title = format!(
+        " Scratchpad ({} lines) | Ctrl+Y: copy | Ctrl+W: delete word | Esc: close ",
+        scratchpad.line_count()
+    );
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(accent))
+            .title(title),
+    );
 
-    let paragraph = Paragraph::new(content)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Rgb(113, 113, 122)))
-                .title(" Preview "),
-        )
-        .wrap(Wrap { trim: false });
-
+    frame.render_widget(Clear, area);
     frame.render_widget(paragraph, area);
 }
 
-fn render_status_bar(
+fn render_prompt_timeline(
     frame: &mut Frame,
     area: Rect,
-    counts: StatusCounts,
-    selected_idx: usize,
-    search_query: &str,
-    filter_error: Option<&str>,
-    status_message: Option<&StatusMessage>,
+    view: &PromptTimelineView,
+    paths: &PathDisplay,
 ) {
-    // Parse input to extract filter portion
-    let (filter_part, fuzzy_part) = if let Some(pipe_pos) = search_query.find('|') {
-        let filter = search_query[..pipe_pos].trim();
-        let fuzzy = search_query[pipe_pos + 1..].trim();
-        (if filter.is_empty() { None } else { Some(filter) }, fuzzy)
+    let muted = Style::default().fg(theme().muted);
+    let accent = theme().accent;
+    let timeline = &view.timeline;
+
+    let mut items: Vec<ListItem> = timeline
+        .prompts
+        .iter()
+        .map(|prompt| {
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{}  ", prompt.timestamp.format("%Y-%m-%d %H:%M")), muted),
+                Span::raw(prompt.line.clone()),
+            ]))
+        })
+        .collect();
+    if items.is_empty() {
+        items.push(ListItem::new(Line::styled("No prompts recorded for this project", muted)));
+    }
+
+    let project = paths.format(&timeline.project);
+    let title = if view.filename.is_some() {
+        format!(" Prompt timeline: {} | Enter: write | Esc: cancel ", project)
     } else {
-        (None, search_query)
+        format!(
+            " Prompt timeline: {} ({}) | Enter: jump | y: copy | w: write Markdown | Esc ",
+            project,
+            timeline.prompts.len()
+        )
     };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(accent))
+        .title(title);
+    let inner = block.inner(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let mut list_area = inner;
+    if let Some(filename) = &view.filename
+        && inner.height > 1
+    {
+        list_area.height -= 1;
+        let prompt = Paragraph::new(Line::from(vec![
+            Span::styled("Write to: ", muted),
+            Span::raw(filename.clone()),
+            Span::styled("▏", Style::default().fg(accent)),
+        ]));
+        frame.render_widget(prompt, Rect { y: inner.y + list_area.height, height: 1, ..inner });
+    }
 
-    let (status_text, style) = if let Some(msg) = status_message {
-        // Show status message with appropriate color
-        let (fg, bg) = match msg.message_type {
-            MessageType::Success => (Color::Rgb(16, 185, 129), Color::Rgb(24, 24, 27)), // Green
-            MessageType::Error => (Color::Rgb(239, 68, 68), Color::Rgb(24, 24, 27)),    // Red
-        };
-        (format!(" {} ", msg.text), Style::default().fg(fg).bg(bg))
-    } else if let Some(error) = filter_error {
-        // Show error in red
-        (
-            format!(" [ERROR] {} ", error),
-            Style::default().fg(Color::Rgb(239, 68, 68)).bg(Color::Rgb(24, 24, 27)), // Red
-        )
-    } else if counts.matched == 0 {
-        (
-            " No entries | Enter: apply filter | Esc: clear | Ctrl+C: quit ".to_string(),
-            Style::default().fg(Color::Rgb(250, 250, 250)).bg(Color::Rgb(24, 24, 27)),
-        )
-    } else {
-        let mut parts = vec![];
+    let list = List::new(items)
+        .highlight_style(Style::default().bg(theme().highlight).add_modifier(Modifier::BOLD));
+    let mut state = ListState::default().with_selected(Some(view.selected));
+    frame.render_stateful_widget(list, list_area, &mut state);
+}
 
-        // Mode indicator
-        parts.push("[FUZZY]".to_string());
+fn render_browse(frame: &mut Frame, area: Rect, view: &BrowseView) {
+    let muted = Style::default().fg(theme().muted);
+    let accent = theme().accent;
 
-        // Match counts: matched/filtered (total)
-        if counts.filtered < counts.total {
-            parts.push(format!("{}/{} ({} total)", counts.matched, counts.filtered, counts.total));
-        } else {
-            // No filter active, just show matched/total
-            parts.push(format!("{}/{} total", counts.matched, counts.total));
-        }
+    let mut items: Vec<ListItem> = view
+        .rows
+        .iter()
+        .map(|row| {
+            let badge = if row.matches > 0 { Style::default().fg(accent) } else { muted };
+            let mark = match (view.kind, view.is_marked(row)) {
+                (BrowseKind::Sessions, _) => "",
+                (BrowseKind::Projects, true) => "[x] ",
+                (BrowseKind::Projects, false) => "[ ] ",
+            };
+            ListItem::new(Line::from(vec![
+                Span::raw(mark),
+                Span::styled(format!("{:>5}  ", row.matches), badge),
+                Span::styled(format!("{}  ", row.last_active.format("%Y-%m-%d %H:%M")), muted),
+                Span::raw(row.label.clone()),
+                Span::styled(format!("  {}", row.detail), muted),
+            ]))
+        })
+        .collect();
+    if items.is_empty() {
+        items.push(ListItem::new(Line::styled("Nothing indexed", muted)));
+    }
 
-        // Active filter
-        if let Some(filter) = filter_part {
-            parts.push(format!("filter: {}", filter));
+    let title = match view.kind {
+        BrowseKind::Sessions => {
+            format!(" Sessions ({}) | #: in results | Enter: select | Esc ", view.rows.len())
         }
-
-        // Current selection
-        if counts.matched > 0 {
-            parts.push(format!("entry {}/{}", selected_idx + 1, counts.matched));
+        BrowseKind::Projects => {
+            format!(
+                " Projects ({}) | #: in results | Space: mark | Enter: search marked / filter | Esc ",
+                view.rows.len()
+            )
         }
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(accent))
+        .title(title);
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().bg(theme().highlight).add_modifier(Modifier::BOLD));
+    let mut state = ListState::default().with_selected(Some(view.selected));
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut state);
+}
 
-        // Keybindings
-        if !fuzzy_part.is_empty() {
-            parts.push("Esc: clear".to_string());
+/// Shades of the activity calendar, from days without entries to the busiest days
+const ACTIVITY_SHADES: [&str; ACTIVITY_LEVELS] = ["· ", "░░", "▒▒", "▓▓", "██"];
+
+fn render_activity(frame: &mut Frame, area: Rect, calendar: &ActivityCalendar) {
+    const LABEL_WIDTH: usize = 4;
+    let muted = Style::default().fg(theme().muted);
+    let accent = theme().accent;
+
+    let active_days = calendar.counts.iter().filter(|&&count| count > 0).count();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(accent))
+        .title(format!(
+            " Activity ({} days) | ↑↓: day | PgUp/PgDn: week | Enter: filter to day | Esc ",
+            active_days
+        ));
+    let inner = block.inner(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+    if calendar.is_empty() {
+        frame.render_widget(Paragraph::new(Line::styled("Nothing indexed", muted)), inner);
+        return;
+    }
+
+    // Newest weeks that fit, scrolled back to keep the selected day in view
+    let visible = ((inner.width as usize).saturating_sub(LABEL_WIDTH) / 2).max(1);
+    let first_week = (calendar.selected / 7 + 1).saturating_sub(visible);
+    let weeks = first_week..calendar.weeks().min(first_week + visible);
+
+    // Month names above the first week of each month
+    let mut header = " ".repeat(LABEL_WIDTH);
+    let mut previous_month = None;
+    for week in weeks.clone() {
+        let month = calendar.date(week * 7).month();
+        let position = LABEL_WIDTH + (week - first_week) * 2;
+        if previous_month != Some(month) && header.len() <= position {
+            header.push_str(&" ".repeat(position - header.len()));
+            header.push_str(&calendar.date(week * 7).format("%b").to_string());
         }
-        parts.push("Enter: apply".to_string());
-        parts.push("Ctrl+Y: copy".to_string());
-        parts.push("Ctrl+C: quit".to_string());
+        previous_month = Some(month);
+    }
 
-        (
-            format!(" {} ", parts.join(" | ")),
-            Style::default().fg(Color::Rgb(250, 250, 250)).bg(Color::Rgb(24, 24, 27)),
-        )
-    };
+    let mut lines = vec![Line::styled(header, muted)];
+    for weekday in 0..7 {
+        let mut spans = vec![Span::styled(
+            format!("{:<width$}", calendar.date(weekday).format("%a"), width = LABEL_WIDTH),
+            muted,
+        )];
+        for week in weeks.clone() {
+            let Some(day) = calendar.day_at(week, weekday) else {
+                spans.push(Span::raw("  "));
+                continue;
+            };
+            let level = calendar.level(day);
+            let mut style = if level == 0 { muted } else { Style::default().fg(accent) };
+            if day == calendar.selected {
+                style = style.bg(theme().highlight).add_modifier(Modifier::BOLD);
+            }
+            spans.push(Span::styled(ACTIVITY_SHADES[level], style));
+        }
+        lines.push(Line::from(spans));
+    }
 
-    let paragraph = Paragraph::new(status_text).style(style);
+    let count = calendar.counts[calendar.selected];
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled(
+            calendar.date(calendar.selected).format("%Y-%m-%d %a").to_string(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(format!(" · {} {}", count, if count == 1 { "entry" } else { "entries" })),
+    ]));
+    let mut legend = vec![Span::styled("Less ", muted)];
+    for (level, shade) in ACTIVITY_SHADES.iter().enumerate() {
+        let style = if level == 0 { muted } else { Style::default().fg(accent) };
+        legend.push(Span::styled(*shade, style));
+    }
+    legend.push(Span::styled(" More (UTC days)", muted));
+    lines.push(Line::from(legend));
 
-    frame.render_widget(paragraph, area);
+    frame.render_widget(Paragraph::new(lines), inner);
 }
 
-#[cfg(test)]
-mod tests {
-    use chrono::{TimeZone, Utc};
-    use ratatui::Terminal;
-    use ratatui::backend::TestBackend;
+fn render_cache_screen(frame: &mut Frame, area: Rect, screen: &CacheScreen) {
+    let muted = Style::default().fg(theme().muted);
+    let accent = theme().accent;
+    let summary = &screen.summary;
 
-    use super::*;
+    let field = |label: &str, value: String| {
+        Line::from(vec![Span::styled(format!("{:<12}", label), muted), Span::raw(value)])
+    };
+    let last_saved = summary
+        .last_saved
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| "never".to_string());
+    let mut lines = vec![
+        field("Location", summary.location.clone()),
+        field("Size", format_bytes(summary.size_bytes as usize)),
+        field("Last saved", last_saved),
+        Line::from(""),
+        Line::styled("Cached per root", Style::default().add_modifier(Modifier::BOLD)),
+    ];
+    if summary.sources.is_empty() {
+        lines.push(Line::styled("  Nothing cached", muted));
+    }
+    for source in &summary.sources {
+        lines.push(Line::from(format!(
+            "  {:<20} {:>6} files {:>9} entries",
+            source.name, source.files, source.entries
+        )));
+    }
 
-    fn create_test_entry(text: &str) -> SearchEntry {
-        SearchEntry {
-            entry_type: EntryType::UserPrompt,
-            display_text: text.to_string(),
-            timestamp: Utc.timestamp_opt(1234567890, 0).unwrap(),
-            project_path: None,
-            session_id: "test-session".to_string(),
+    lines.push(Line::from(""));
+    for (i, job) in CacheJob::ALL.iter().enumerate() {
+        let style = if i == screen.selected {
+            Style::default().fg(accent).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        let marker = if i == screen.selected { "▶ " } else { "  " };
+        let mut spans = vec![
+            Span::styled(format!("{}{:<9}", marker, job.label()), style),
+            Span::styled(job.description(), muted),
+        ];
+        if screen.running == Some(*job) {
+            spans.push(Span::styled("  running…", Style::default().fg(accent)));
         }
+        lines.push(Line::from(spans));
     }
 
-    #[test]
-    fn test_render_ui_with_entries() {
-        let backend = TestBackend::new(100, 30);
-        let mut terminal = Terminal::new(backend).unwrap();
-
-        let entries = [create_test_entry("First entry"), create_test_entry("Second entry")];
-        let entry_refs: Vec<&SearchEntry> = entries.iter().collect();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(accent))
+        .title(" Index cache | ↑↓: action | Enter: run | c/r/m: clear/rebuild/compact | Esc ");
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }).block(block), area);
+}
 
-        terminal
-            .draw(|f| {
-                let state = RenderState {
-                    search_query: "test",
-                    filtered_count: 2,
-                    total_count: 2,
-                    filter_error: None,
-                    status_message: None,
-                };
-                render_ui(f, &entry_refs, 0, &state);
-            })
-            .unwrap();
+fn render_replay(
+    frame: &mut Frame,
+    area: Rect,
+    replay: &SessionReplay,
+    paths: &PathDisplay,
+    timestamps: TimestampStyle,
+) {
+    let muted = Style::default().fg(theme().muted);
+    let accent = theme().accent;
+
+    let project = replay
+        .entries
+        .first()
+        .and_then(|e| e.project_path.as_ref())
+        .map(|p| paths.format(p))
+        .unwrap_or_else(|| "global".to_string());
+    let title = format!(
+        " Session replay: {} | {}/{} | ↑↓: message | PgUp/PgDn: scroll | Enter: select | Esc ",
+        project,
+        replay.current + 1,
+        replay.entries.len()
+    );
 
-        // Just verify it doesn't panic
+    let mut lines = Vec::new();
+    if let Some(entry) = replay.current_entry() {
+        let (icon, role) = match entry.entry_type {
+            EntryType::UserPrompt => ("👤", "User"),
+            EntryType::AgentMessage => ("🤖", "Assistant"),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{} {}", icon, role), Style::default().fg(accent)),
+            Span::styled(format!("  {}", format_timestamp(&entry.timestamp, timestamps)), muted),
+        ]));
+        lines.push(Line::from(""));
+        lines.extend(entry.display_text.lines().map(|line| Line::from(line.to_string())));
     }
 
-    #[test]
-    fn test_render_ui_empty_entries() {
+    let paragraph =
+        Paragraph::new(lines).wrap(Wrap { trim: false }).scroll((replay.scroll, 0)).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(accent))
+                .title(title),
+        );
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
+}
+
+fn render_export_dialog(frame: &mut Frame, area: Rect, dialog: &ExportDialog) {
+    let muted = Style::default().fg(theme().muted);
+    let accent = theme().accent;
+    let exchange = &dialog.exchange;
+
+    let prompt = exchange
+        .turns
+        .first()
+        .map(|turn| match &turn.message.content {
+            MessageContent::String(text) => text.lines().next().unwrap_or("").to_string(),
+            MessageContent::Array(blocks) => blocks
+                .iter()
+                .find_map(|block| match block {
+                    ContentBlock::Text { text } => text.lines().next().map(str::to_string),
+                    _ => None,
+                })
+                .unwrap_or_default(),
+        })
+        .unwrap_or_default();
+    let tool_calls = exchange
+        .turns
+        .iter()
+        .filter_map(|turn| match &turn.message.content {
+            MessageContent::Array(blocks) => Some(blocks),
+            MessageContent::String(_) => None,
+        })
+        .flatten()
+        .filter(|block| matches!(block, ContentBlock::ToolUse { .. }))
+        .count();
+
+    let mut lines = vec![
+        Line::from(vec![Span::styled("Prompt:  ", muted), Span::raw(prompt)]),
+        Line::from(vec![
+            Span::styled("Turns:   ", muted),
+            Span::raw(format!("{} ({} tool calls)", exchange.turns.len(), tool_calls)),
+        ]),
+        Line::from(vec![
+            Span::styled("Format:  ", muted),
+            Span::styled(dialog.format.label(), Style::default().fg(accent)),
+        ]),
+    ];
+    let images = exchange.image_count();
+    if images > 0 {
+        let handling = if dialog.saves_images() {
+            format!("saved to {}/ when written (a: don't save)", ATTACHMENTS_DIR_NAME)
+        } else if dialog.save_images {
+            "only saved with Markdown".to_string()
+        } else {
+            "left as placeholders (a: save when written)".to_string()
+        };
+        lines.push(Line::from(vec![
+            Span::styled("Images:  ", muted),
+            Span::raw(format!("{}, {}", images, handling)),
+        ]));
+    }
+    if let Some(filename) = &dialog.filename {
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::styled("Write to: ", muted),
+            Span::raw(filename.clone()),
+            Span::styled("▏", Style::default().fg(accent)),
+        ]));
+    }
+
+    let title = if dialog.filename.is_some() {
+        " Export exchange | Enter: write | Esc: cancel ".to_string()
+    } else {
+        " Export exchange | Enter: copy | w: write | f: format | Esc ".to_string()
+    };
+    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(accent))
+            .title(title),
+    );
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
+}
+
+fn render_tool_leaderboard(
+    frame: &mut Frame,
+    area: Rect,
+    board: &ToolLeaderboard,
+    paths: &PathDisplay,
+) {
+    let muted = Style::default().fg(theme().muted);
+    let accent = theme().accent;
+
+    // Mark the column the table is sorted by
+    let header_cell = |label: &'static str, sort: ToolUsageSort| {
+        if board.sort == sort {
+            Cell::from(format!("{} ▼", label)).style(Style::default().fg(accent))
+        } else {
+            Cell::from(label)
+        }
+    };
+    let header = Row::new(vec![
+        header_cell("Tool", ToolUsageSort::Tool),
+        header_cell("Total", ToolUsageSort::TotalBytes),
+        header_cell("Calls", ToolUsageSort::Calls),
+        header_cell("Largest", ToolUsageSort::MaxBytes),
+        Cell::from("Project"),
+    ])
+    .style(Style::default().add_modifier(Modifier::BOLD));
+
+    let rows: Vec<Row> = board
+        .usage
+        .iter()
+        .map(|usage| {
+            let project = usage
+                .project
+                .as_deref()
+                .map(|p| paths.format(p))
+                .unwrap_or_else(|| "(no project)".to_string());
+            Row::new(vec![
+                Cell::from(usage.tool.clone()),
+                Cell::from(format_bytes(usage.total_bytes)),
+                Cell::from(usage.calls.to_string()),
+                Cell::from(format_bytes(usage.max_bytes)),
+                Cell::from(Span::styled(project, muted)),
+            ])
+        })
+        .collect();
+
+    let title = format!(
+        " Tool output ({} results, {}) | s: sort by {} | Esc ",
+        board.total_results,
+        format_bytes(board.total_bytes),
+        board.sort.next().label()
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(accent))
+        .title(title);
+
+    frame.render_widget(Clear, area);
+    if rows.is_empty() {
+        let empty = Paragraph::new(Line::styled("No tool results in indexed sessions", muted));
+        frame.render_widget(empty.block(block), area);
+        return;
+    }
+
+    let widths = [
+        Constraint::Length(18),
+        Constraint::Length(12),
+        Constraint::Length(8),
+        Constraint::Length(12),
+        Constraint::Min(10),
+    ];
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(block)
+        .row_highlight_style(Style::default().bg(theme().highlight));
+    let mut table_state = TableState::default().with_selected(Some(board.selected));
+    frame.render_stateful_widget(table, area, &mut table_state);
+}
+
+fn render_warnings_overview(frame: &mut Frame, area: Rect, overview: &WarningsOverview) {
+    let muted = Style::default().fg(theme().muted);
+
+    let mut items: Vec<ListItem> = overview
+        .items
+        .iter()
+        .enumerate()
+        .map(|(idx, item)| {
+            let lines = vec![
+                Line::raw(format_path_with_tilde(&item.path)),
+                Line::styled(format!("  {}", item.reason), Style::default().fg(theme().error)),
+            ];
+            let style = if idx == overview.selected {
+                Style::default().bg(theme().highlight).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            ListItem::new(lines).style(style)
+        })
+        .collect();
+
+    if items.is_empty() {
+        items.push(ListItem::new(Line::styled("Nothing was skipped while indexing", muted)));
+    }
+    if !overview.diagnostics.is_empty() {
+        items.push(ListItem::new(Line::styled("Diagnostics", muted.add_modifier(Modifier::BOLD))));
+    }
+    items.extend(overview.diagnostics.iter().map(|diagnostic| {
+        ListItem::new(vec![
+            Line::raw(format_path_with_tilde(&diagnostic.path)),
+            Line::styled(format!("  {}", diagnostic.message), Style::default().fg(theme().warning)),
+        ])
+    }));
+
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme().warning))
+            .title(format!(
+                " Warnings ({}) | Diagnostics ({}) | r: retry file | Esc: close ",
+                overview.items.len(),
+                overview.diagnostics.len()
+            )),
+    );
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(list, area);
+}
+
+fn render_roots_overview(frame: &mut Frame, area: Rect, overview: &RootsOverview) {
+    let muted = Style::default().fg(theme().muted);
+
+    let items: Vec<ListItem> = overview
+        .roots
+        .iter()
+        .enumerate()
+        .map(|(idx, root)| {
+            let enabled = !overview.disabled.contains(&root.name);
+            let checkbox = if enabled { "[x]" } else { "[ ]" };
+            let health_color = match root.health {
+                RootHealth::Ok => theme().accent,
+                RootHealth::Empty => theme().warning,
+                RootHealth::Missing | RootHealth::Failed => theme().error,
+            };
+
+            let line = Line::from(vec![
+                Span::raw(format!("{} {:<12} ", checkbox, root.name)),
+                Span::raw(format!("{:>8} entries  ", root.entry_count)),
+                Span::styled(
+                    format!("{:<8}", root.health.label()),
+                    Style::default().fg(health_color),
+                ),
+                Span::styled(
+                    format!(
+                        "refreshed {}  {}",
+                        format_timestamp(&root.last_refresh, TimestampStyle::Relative),
+                        format_path_with_tilde(&root.path)
+                    ),
+                    muted,
+                ),
+            ]);
+
+            let style = if idx == overview.selected {
+                Style::default().bg(theme().highlight).add_modifier(Modifier::BOLD)
+            } else if enabled {
+                Style::default().fg(theme().text)
+            } else {
+                muted
+            };
+
+            ListItem::new(line).style(style)
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme().accent))
+            .title(" Roots | Space: toggle | Esc: close "),
+    );
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(list, area);
+}
+
+/// Range of result indices shown in the results list, scrolled so the selection stays visible
+fn visible_results(selected_idx: usize, area: Rect, density: ListDensity) -> Range<usize> {
+    let rows = (area.height.saturating_sub(2) as usize / density.lines()).max(1);
+    let offset = (selected_idx + 1).saturating_sub(rows);
+    offset..offset + rows
+}
+
+/// Title of the results list: the search once something is typed, with the cursor while
+/// typing, scrolled so the cursor stays in view
+fn results_title(query: &str, cursor: Option<usize>, width: usize) -> Line<'static> {
+    const LABEL: &str = " Search: ";
+    if query.is_empty() {
+        return Line::from(" Results ");
+    }
+    let muted = Style::default().fg(theme().muted);
+    let text = Style::default().fg(theme().text);
+    let chars: Vec<char> = query.chars().collect();
+    let at = cursor.map_or(chars.len(), |cursor| query[..cursor].chars().count());
+    // Cells up to and including the cursor's
+    let needed = at + usize::from(cursor.is_some());
+
+    // Cells left for the search after the label and the closing space; when scrolled, the
+    // first one shows an ellipsis
+    let room = width.saturating_sub(LABEL.len() + 1).max(2);
+    let (start, end) =
+        if needed <= room { (0, room.min(chars.len())) } else { (needed - room + 1, needed) };
+    let end = end.min(chars.len());
+    let slice = |range: Range<usize>| chars[range].iter().collect::<String>();
+
+    let mut spans = vec![Span::styled(LABEL, muted)];
+    if start > 0 {
+        spans.push(Span::styled(ELLIPSIS, muted));
+    }
+    match cursor {
+        Some(_) => {
+            let under = chars.get(at).map_or(" ".to_string(), char::to_string);
+            spans.push(Span::styled(slice(start..at), text));
+            spans.push(Span::styled(under, text.add_modifier(Modifier::REVERSED)));
+            spans.push(Span::styled(slice((at + 1).min(end)..end), text));
+        }
+        None => spans.push(Span::styled(slice(start..end), text)),
+    }
+    spans.push(Span::raw(" "));
+    Line::from(spans)
+}
+
+fn render_results_list(
+    frame: &mut Frame,
+    area: Rect,
+    title: Line,
+    entries: &[&SearchEntry],
+    selected_idx: usize,
+    decorations: &RowDecorations,
+) {
+    let RowDecorations {
+        kinds: row_kinds,
+        paths,
+        bookmarks,
+        summaries,
+        highlighter,
+        privacy,
+        density,
+        columns,
+        timestamps,
+    } = decorations;
+    // Text width inside the borders, for the rows that fit their text to the list
+    let width = area.width.saturating_sub(2) as usize;
+    let items: Vec<ListItem> = entries
+        .iter()
+        .enumerate()
+        .map(|(idx, entry)| {
+            let icon = match entry.entry_type {
+                EntryType::UserPrompt => "👤",
+                EntryType::AgentMessage => "🤖",
+            };
+
+            let timestamp = format_timestamp(&entry.timestamp, *timestamps);
+            let project = entry
+                .project_path
+                .as_ref()
+                .map(|p| paths.format(p))
+                .unwrap_or_else(|| "global".to_string());
+
+            let hidden = privacy.is_locked(entry);
+            // Session rows show the session's summary, if it has one, instead of its first result
+            let summary = match row_kinds.get(idx) {
+                Some(RowKind::Session { .. }) if !hidden => summaries.get(&entry.session_id),
+                _ => None,
+            };
+            let text = match summary {
+                Some(summary) => summary,
+                None if hidden => REDACTED_TEXT,
+                None => &entry.display_text,
+            };
+            let first_line = text.lines().next().unwrap_or("");
+
+            let group = match row_kinds.get(idx) {
+                Some(RowKind::Collapsed(hidden)) => format!("[+{}] ", hidden),
+                Some(RowKind::Expanded(shown)) => format!("[-{}] ", shown),
+                Some(RowKind::Member) => "  ↳ ".to_string(),
+                Some(RowKind::Session { results: 1, .. }) => String::new(),
+                Some(RowKind::Session { expanded: false, .. }) => "[+] ".to_string(),
+                Some(RowKind::Session { expanded: true, .. }) => "[-] ".to_string(),
+                Some(RowKind::Single) | None => String::new(),
+            };
+            // Session rows show the session's size and span in place of the entry's timestamp
+            let timestamp = match row_kinds.get(idx) {
+                Some(&RowKind::Session { results, first, last, .. }) => {
+                    let noun = if results == 1 { "msg" } else { "msgs" };
+                    if first == last {
+                        format!("{} {} | {}", results, noun, timestamp)
+                    } else {
+                        let (first, last) = (
+                            format_timestamp(&first, *timestamps),
+                            format_timestamp(&last, *timestamps),
+                        );
+                        format!("{} {} | {} → {}", results, noun, first, last)
+                    }
+                }
+                _ => timestamp,
+            };
+            let star = if bookmarks.contains(entry) { "★ " } else { "" };
+            let selected = idx == selected_idx;
+            let matched = if hidden || summary.is_some() {
+                Vec::new()
+            } else {
+                highlighter.fuzzy_indices(&entry.display_text)
+            };
+
+            // Mark the fuzzy-matched characters that fall into the shown text
+            let highlighted = |shown: &str| {
+                let mut ranges: Vec<Range<usize>> = Vec::new();
+                for (i, (start, c)) in shown.char_indices().enumerate() {
+                    if matched.binary_search(&i).is_err() {
+                        continue;
+                    }
+                    match ranges.last_mut() {
+                        Some(last) if last.end == start => last.end += c.len_utf8(),
+                        _ => ranges.push(start..start + c.len_utf8()),
+                    }
+                }
+                highlight_ranges(shown, &ranges, match_style(selected))
+            };
+            let cell = |column: &ListColumn| {
+                let value = match column.kind {
+                    ColumnKind::Icon => icon.to_string(),
+                    ColumnKind::Timestamp => timestamp.clone(),
+                    ColumnKind::Project => project.clone(),
+                    ColumnKind::Session => entry.session_id.chars().take(8).collect(),
+                    ColumnKind::Text => String::new(),
+                };
+                match column.width {
+                    Some(width) => pad_to_width(&truncate_to_width(&value, width), width),
+                    None => value,
+                }
+            };
+
+            // Compact rows put the columns on one line, in order, and the text (50 characters
+            // unless its column has a width); the other densities give the text a line of its
+            // own, fitted to the list's width, and put the other columns below it
+            let mut spans = vec![Span::raw(format!("{}{}", group, star))];
+            let mut lines = Vec::new();
+            match density {
+                ListDensity::Compact => {
+                    for (i, column) in columns.iter().enumerate() {
+                        if i > 0 {
+                            let after_icon = columns[i - 1].kind == ColumnKind::Icon;
+                            spans.push(Span::raw(if after_icon { " " } else { " | " }));
+                        }
+                        if column.kind != ColumnKind::Text {
+                            spans.push(Span::raw(cell(column)));
+                            continue;
+                        }
+                        let limit = column.width.unwrap_or(DEFAULT_TEXT_WIDTH);
+                        let end = first_line
+                            .char_indices()
+                            .nth(limit)
+                            .map_or(first_line.len(), |(i, _)| i);
+                        let shown = &first_line[..end];
+                        spans.extend(highlighted(shown));
+                        // Keep the columns after the text aligned
+                        if i + 1 < columns.len() {
+                            let padding = limit.saturating_sub(shown.chars().count());
+                            spans.push(Span::raw(" ".repeat(padding)));
+                        }
+                    }
+                    lines.push(Line::from(spans));
+                }
+                ListDensity::Comfortable | ListDensity::Detailed => {
+                    if columns.iter().any(|column| column.kind == ColumnKind::Icon) {
+                        spans.push(Span::raw(format!("{} ", icon)));
+                    }
+                    let prefix_width: usize = spans.iter().map(Span::width).sum();
+                    let (shown, cut) = fit_to_width(first_line, width.saturating_sub(prefix_width));
+                    spans.extend(highlighted(shown));
+                    if cut {
+                        spans.push(Span::raw(ELLIPSIS));
+                    }
+                    lines.push(Line::from(spans));
+
+                    let meta: Vec<String> = columns
+                        .iter()
+                        .filter(|column| {
+                            !matches!(column.kind, ColumnKind::Icon | ColumnKind::Text)
+                        })
+                        .map(cell)
+                        .collect();
+                    let meta = format!("{}{}", ROW_INDENT, meta.join(" | "));
+                    lines.push(Line::from(truncate_to_width(&meta, width)));
+                }
+            }
+            if *density == ListDensity::Detailed {
+                let detail = row_detail(entry, hidden);
+                lines.push(Line::from(truncate_to_width(
+                    &format!("{}{}", ROW_INDENT, detail),
+                    width,
+                )));
+            }
+            ListItem::new(Text::from(lines)).style(row_style(selected))
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme().muted))
+            .title(title),
+    );
+
+    let offset = visible_results(selected_idx, area, *density).start;
+    let mut state = ListState::default().with_offset(offset);
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+const ELLIPSIS: &str = "…";
+
+/// Indent of the second and third line of a result
+const ROW_INDENT: &str = "   ";
+
+/// Tags and the text after the first line of a result, for the third line of detailed rows
+///
+/// Tags are the tools called, a failed tool call, the session outcome and the tool the entry
+/// comes from unless it is Claude Code. The text is left out of hidden entries.
+fn row_detail(entry: &SearchEntry, hidden: bool) -> String {
+    let mut parts = Vec::new();
+    if !entry.tools.is_empty() {
+        parts.push(format!("⚙ {}", entry.tools.join(", ")));
+    }
+    if entry.had_error {
+        parts.push("✗ error".to_string());
+    }
+    if let Some(outcome) = entry.outcome {
+        parts.push(format!("outcome: {}", outcome.label()));
+    }
+    if entry.source != SourceKind::ClaudeCode {
+        parts.push(entry.source.label().to_string());
+    }
+    if !hidden {
+        let rest = entry.display_text.lines().skip(1).collect::<Vec<_>>().join(" ");
+        let snippet = rest.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !snippet.is_empty() {
+            parts.push(snippet);
+        }
+    }
+    parts.join(" · ")
+}
+
+/// The start of `text` that fits into `width` terminal columns, and whether it was cut
+///
+/// A cut leaves one column free for an ellipsis.
+fn fit_to_width(text: &str, width: usize) -> (&str, bool) {
+    if Span::raw(text).width() <= width {
+        return (text, false);
+    }
+    let room = width.saturating_sub(1);
+    let mut used = 0;
+    for (i, c) in text.char_indices() {
+        let mut buf = [0; 4];
+        used += Span::raw(&*c.encode_utf8(&mut buf)).width();
+        if used > room {
+            return (&text[..i], true);
+        }
+    }
+    (text, false)
+}
+
+/// `text` followed by spaces up to `width` terminal columns
+fn pad_to_width(text: &str, width: usize) -> String {
+    let padding = width.saturating_sub(Span::raw(text).width());
+    format!("{}{}", text, " ".repeat(padding))
+}
+
+/// `text` cut to at most `width` terminal columns, ending in `…` if it was cut
+fn truncate_to_width(text: &str, width: usize) -> String {
+    match fit_to_width(text, width) {
+        (shown, true) => format!("{}{}", shown, ELLIPSIS),
+        (shown, false) => shown.to_string(),
+    }
+}
+
+/// Split `text` into spans with the byte `ranges` (sorted, disjoint) in `style`
+fn highlight_ranges(text: &str, ranges: &[Range<usize>], style: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::with_capacity(ranges.len() * 2 + 1);
+    let mut end = 0;
+    for range in ranges {
+        if range.start > end {
+            spans.push(Span::raw(text[end..range.start].to_string()));
+        }
+        spans.push(Span::styled(text[range.clone()].to_string(), style));
+        end = range.end;
+    }
+    if end < text.len() || spans.is_empty() {
+        spans.push(Span::raw(text[end..].to_string()));
+    }
+    spans
+}
+
+/// Style of matched characters, on top of the row style
+fn match_style(selected: bool) -> Style {
+    let style = Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+    if selected { style } else { style.fg(theme().matched) }
+}
+
+fn row_style(selected: bool) -> Style {
+    if selected {
+        Style::default().fg(theme().on_accent).bg(theme().accent).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(theme().muted)
+    }
+}
+
+/// Bar characters in eighths, for sub-cell density resolution
+const BAR_EIGHTHS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// Render `count` relative to `max` as a horizontal bar of at most `width` cells
+fn density_bar(count: usize, max: usize, width: usize) -> String {
+    if count == 0 || max == 0 {
+        return String::new();
+    }
+    let eighths = (count * width * 8 / max).max(1);
+    let mut bar = "█".repeat(eighths / 8);
+    let partial = BAR_EIGHTHS[eighths % 8];
+    if partial != ' ' {
+        bar.push(partial);
+    }
+    bar
+}
+
+fn render_minimap(
+    frame: &mut Frame,
+    area: Rect,
+    minimap: &Minimap,
+    visible: Range<usize>,
+    selected_idx: usize,
+) {
+    let accent = theme().accent;
+    let muted = Style::default().fg(theme().muted);
+
+    let rows = minimap.rows(area.height.saturating_sub(2) as usize);
+    let max = rows.iter().map(|r| r.count).max().unwrap_or(0);
+    let bar_width = (area.width as usize).saturating_sub(2 + 7);
+    let visible_weeks: HashSet<usize> =
+        visible.filter_map(|index| minimap.week_of(index)).collect();
+    let selected_week = minimap.week_of(selected_idx);
+
+    let mut lines = Vec::with_capacity(rows.len());
+    let mut previous_month = None;
+    for row in &rows {
+        let month = row.start.format("%b %y").to_string();
+        let label = if previous_month.as_ref() != Some(&month) {
+            format!("{:<7}", month)
+        } else {
+            " ".repeat(7)
+        };
+        previous_month = Some(month);
+
+        let in_view = row.weeks.clone().any(|week| visible_weeks.contains(&week));
+        let selected = selected_week.is_some_and(|week| row.weeks.contains(&week));
+        let bar_style = if selected {
+            Style::default().fg(accent).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme().subtle)
+        };
+        let line = Line::from(vec![
+            Span::styled(label, muted),
+            Span::styled(density_bar(row.count, max, bar_width), bar_style),
+        ]);
+        lines.push(if in_view { line.style(Style::default().bg(theme().highlight)) } else { line });
+    }
+    if minimap.is_empty() {
+        lines.push(Line::styled("No results", muted));
+    }
+
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme().muted))
+            .title(" Weeks "),
+    );
+    frame.render_widget(paragraph, area);
+}
+
+fn render_preview(
+    frame: &mut Frame,
+    area: Rect,
+    entry: Option<&SearchEntry>,
+    session: Option<&SessionSummary>,
+    selection: Option<&PreviewSelection>,
+    paths: &PathDisplay,
+    highlighter: &MatchHighlighter,
+) {
+    let mut scroll = 0;
+    let content = if let Some(entry) = entry {
+        let timestamp = format_exact(&entry.timestamp);
+        let project = entry
+            .project_path
+            .as_ref()
+            .map(|p| paths.format(p))
+            .unwrap_or_else(|| "global".to_string());
+        let session_id = entry.session_id.clone();
+
+        let mut lines = vec![
+            Line::from(vec![
+                Span::styled("Timestamp: ", Style::default().fg(theme().muted)),
+                Span::raw(timestamp),
+            ]),
+            Line::from(vec![
+                Span::styled("Project: ", Style::default().fg(theme().muted)),
+                Span::raw(project),
+            ]),
+            Line::from(vec![
+                Span::styled("Session: ", Style::default().fg(theme().muted)),
+                Span::raw(session_id),
+            ]),
+        ];
+
+        if entry.source != SourceKind::ClaudeCode {
+            lines.push(Line::from(vec![
+                Span::styled("Source: ", Style::default().fg(theme().muted)),
+                Span::raw(entry.source.label()),
+            ]));
+        }
+        if let Some(model) = &entry.model {
+            lines.push(Line::from(vec![
+                Span::styled("Model: ", Style::default().fg(theme().muted)),
+                Span::raw(model.clone()),
+            ]));
+        }
+        if let Some(branch) = &entry.git_branch {
+            lines.push(Line::from(vec![
+                Span::styled("Branch: ", Style::default().fg(theme().muted)),
+                Span::raw(branch.clone()),
+            ]));
+        }
+        // The working directory is usually the project itself
+        if let Some(cwd) = entry.cwd.as_ref().filter(|cwd| entry.project_path.as_ref() != Some(cwd))
+        {
+            lines.push(Line::from(vec![
+                Span::styled("Directory: ", Style::default().fg(theme().muted)),
+                Span::raw(paths.format(cwd)),
+            ]));
+        }
+        if let Some(summary) = session {
+            lines.push(Line::from(vec![
+                Span::styled("Messages: ", Style::default().fg(theme().muted)),
+                Span::raw(format!(
+                    "{} ({} user, {} agent)",
+                    summary.message_count, summary.user_count, summary.assistant_count
+                )),
+            ]));
+        }
+        lines.push(Line::from(""));
+
+        // Add display text (already truncated by SearchEntry)
+        let header_len = lines.len();
+        for (i, line) in entry.display_text.lines().enumerate() {
+            let style = match selection {
+                Some(sel) if i == sel.cursor() => Style::default().bg(theme().cursor),
+                Some(sel) if sel.range().is_some_and(|(start, end)| (start..=end).contains(&i)) => {
+                    Style::default().bg(theme().selection)
+                }
+                _ => Style::default(),
+            };
+            let matched = Style::default().fg(theme().matched).add_modifier(Modifier::BOLD);
+            let spans = highlight_ranges(line, &highlighter.exact_ranges(line), matched);
+            lines.push(Line::from(spans).style(style));
+        }
+
+        // Keep the cursor line visible (approximate: ignores wrapped lines)
+        if let Some(sel) = selection {
+            let visible = area.height.saturating_sub(2) as usize;
+            let cursor_row = header_len + sel.cursor();
+            if visible > 0 && cursor_row >= visible {
+                scroll = (cursor_row + 1 - visible).min(u16::MAX as usize) as u16;
+            }
+        }
+
+        Text::from(lines)
+    } else {
+        Text::from("No entry selected")
+    };
+
+    let (border_color, title) = match selection {
+        Some(sel) if sel.is_visual() => (
+            theme().accent,
+            " Preview [VISUAL] j/k: extend | y: copy | a: scratchpad | Esc: cancel ",
+        ),
+        Some(_) => (theme().accent, " Preview | v: visual | y: copy | a: scratchpad | Tab: back "),
+        None => (theme().muted, " Preview "),
+    };
+
+    let paragraph = Paragraph::new(content)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(border_color))
+                .title(title),
+        )
+        .wrap(Wrap { trim: false })
+        .scroll((scroll, 0));
+
+    frame.render_widget(paragraph, area);
+}
+
+fn render_status_bar(
+    frame: &mut Frame,
+    area: Rect,
+    counts: StatusCounts,
+    selected_idx: usize,
+    search_query: &str,
+    filter_error: Option<&str>,
+    status_message: Option<&StatusMessage>,
+) {
+    // Parse input to extract filter portion
+    let (filter_part, fuzzy_part) = split_search_query(search_query);
+    let apply_hint = match counts.pending {
+        Some(estimate) => format!("Enter: apply ({})", estimate.label()),
+        None => "Enter: apply".to_string(),
+    };
+
+    let (status_text, style) = if let Some(msg) = status_message {
+        // Show status message with appropriate color
+        let (fg, bg) = match msg.message_type {
+            MessageType::Success => (theme().accent, theme().surface),
+            MessageType::Error => (theme().error, theme().surface),
+        };
+        (format!(" {} ", msg.text), Style::default().fg(fg).bg(bg))
+    } else if let Some(error) = filter_error {
+        // Show error in red
+        (format!(" [ERROR] {} ", error), Style::default().fg(theme().error).bg(theme().surface))
+    } else if counts.matched == 0 {
+        (
+            format!(" No entries | {} | Esc: clear | Ctrl+C: quit ", apply_hint),
+            Style::default().fg(theme().text).bg(theme().surface),
+        )
+    } else {
+        let mut parts = vec![];
+
+        // Mode indicator
+        parts.push("[FUZZY]".to_string());
+
+        // Match counts: matched/filtered (total)
+        if counts.filtered < counts.total {
+            parts.push(format!("{}/{} ({} total)", counts.matched, counts.filtered, counts.total));
+        } else {
+            // No filter active, just show matched/total
+            parts.push(format!("{}/{} total", counts.matched, counts.total));
+        }
+
+        if counts.scoped_projects > 0 {
+            parts.push(format!("{} projects", counts.scoped_projects));
+        }
+
+        // Active filter
+        if let Some(filter) = filter_part {
+            parts.push(format!("filter: {}", filter));
+        }
+
+        // Current selection
+        if counts.matched > 0 {
+            parts.push(format!("entry {}/{}", selected_idx + 1, counts.matched));
+        }
+
+        // Keybindings
+        if !fuzzy_part.is_empty() {
+            parts.push("Esc: clear".to_string());
+        }
+        parts.push(apply_hint);
+        parts.push("Ctrl+Y: copy".to_string());
+        parts.push("Ctrl+C: quit".to_string());
+
+        (format!(" {} ", parts.join(" | ")), Style::default().fg(theme().text).bg(theme().surface))
+    };
+
+    let paragraph = Paragraph::new(status_text).style(style);
+
+    frame.render_widget(paragraph, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use chrono::{TimeZone, Utc};
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+    use ratatui::layout::Layout;
+    use ratatui::style::Color;
+
+    use super::*;
+    use crate::tui::columns::DEFAULT_COLUMNS;
+    use crate::tui::help::{FILTER_SYNTAX, STATUS_BAR_LEGEND};
+
+    fn create_test_entry(text: &str) -> SearchEntry {
+        SearchEntry {
+            entry_type: EntryType::UserPrompt,
+            display_text: text.to_string(),
+            timestamp: Utc.timestamp_opt(1234567890, 0).unwrap(),
+            session_id: "test-session".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_ui_with_entries() {
+        let backend = TestBackend::new(100, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        let entries = [create_test_entry("First entry"), create_test_entry("Second entry")];
+        let entry_refs: Vec<&SearchEntry> = entries.iter().collect();
+
+        terminal
+            .draw(|f| {
+                let state = RenderState {
+                    search_query: "test",
+                    search_cursor: None,
+                    filtered_count: 2,
+                    total_count: 2,
+                    scoped_projects: 0,
+                    filter_error: None,
+                    filter_estimate: None,
+                    status_message: None,
+                    roots_overview: None,
+                    warnings: None,
+                    session_summary: None,
+                    preview_selection: None,
+                    code_picker: None,
+                    scratchpad: None,
+                    tool_leaderboard: None,
+                    export_dialog: None,
+                    prompt_timeline: None,
+                    browse: None,
+                    activity: None,
+                    saved_searches: None,
+                    replay: None,
+                    cache_screen: None,
+                    command_palette: None,
+                    row_kinds: &[],
+                    path_display: &PathDisplay::default(),
+                    bookmarks: &Bookmarks::default(),
+                    summaries: &Summaries::default(),
+                    highlighter: &MatchHighlighter::default(),
+                    privacy: &PrivacyLock::default(),
+                    reveal_prompt: None,
+                    help: None,
+                    minimap: None,
+                    density: ListDensity::Compact,
+                    columns: DEFAULT_COLUMNS,
+                    timestamps: TimestampStyle::Relative,
+                };
+                render_ui(f, &entry_refs, 0, &state);
+            })
+            .unwrap();
+
+        // Just verify it doesn't panic
+    }
+
+    #[test]
+    fn test_render_ui_empty_entries() {
+        let backend = TestBackend::new(100, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        let entries: Vec<&SearchEntry> = vec![];
+
+        terminal
+            .draw(|f| {
+                let state = RenderState {
+                    search_query: "",
+                    search_cursor: None,
+                    filtered_count: 0,
+                    total_count: 0,
+                    scoped_projects: 0,
+                    filter_error: None,
+                    filter_estimate: None,
+                    status_message: None,
+                    roots_overview: None,
+                    warnings: None,
+                    session_summary: None,
+                    preview_selection: None,
+                    code_picker: None,
+                    scratchpad: None,
+                    tool_leaderboard: None,
+                    export_dialog: None,
+                    prompt_timeline: None,
+                    browse: None,
+                    activity: None,
+                    saved_searches: None,
+                    replay: None,
+                    cache_screen: None,
+                    command_palette: None,
+                    row_kinds: &[],
+                    path_display: &PathDisplay::default(),
+                    bookmarks: &Bookmarks::default(),
+                    summaries: &Summaries::default(),
+                    highlighter: &MatchHighlighter::default(),
+                    privacy: &PrivacyLock::default(),
+                    reveal_prompt: None,
+                    help: None,
+                    minimap: None,
+                    density: ListDensity::Compact,
+                    columns: DEFAULT_COLUMNS,
+                    timestamps: TimestampStyle::Relative,
+                };
+                render_ui(f, &entries, 0, &state);
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_render_preview_with_entry() {
+        let backend = TestBackend::new(80, 20);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        let mut entry = create_test_entry("Test content");
+        entry.model = Some("claude-sonnet-4-5-20250929".to_string());
+        entry.git_branch = Some("feature-x".to_string());
+        entry.project_path = Some(std::path::PathBuf::from("/work/app"));
+        entry.cwd = entry.project_path.clone();
+
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                render_preview(
+                    f,
+                    area,
+                    Some(&entry),
+                    None,
+                    None,
+                    &PathDisplay::default(),
+                    &MatchHighlighter::default(),
+                );
+            })
+            .unwrap();
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("Model: claude-sonnet-4-5-20250929"));
+        assert!(content.contains("Branch: feature-x"));
+        assert!(!content.contains("Directory:"));
+    }
+
+    #[test]
+    fn test_render_preview_no_entry() {
+        let backend = TestBackend::new(80, 20);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                render_preview(
+                    f,
+                    area,
+                    None,
+                    None,
+                    None,
+                    &PathDisplay::default(),
+                    &MatchHighlighter::default(),
+                );
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_render_results_list_with_project_path() {
+        let backend = TestBackend::new(100, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        let mut entry = create_test_entry("Entry with path");
+        entry.project_path = Some(std::path::PathBuf::from("/Users/test/project"));
+
+        let entries = vec![&entry];
+
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                render_results_list(
+                    f,
+                    area,
+                    Line::from(" Results "),
+                    &entries,
+                    0,
+                    &RowDecorations {
+                        kinds: &[],
+                        paths: &PathDisplay::default(),
+                        bookmarks: &Bookmarks::default(),
+                        summaries: &Summaries::default(),
+                        highlighter: &MatchHighlighter::default(),
+                        privacy: &PrivacyLock::default(),
+                        density: ListDensity::Compact,
+                        columns: DEFAULT_COLUMNS,
+                        timestamps: TimestampStyle::Relative,
+                    },
+                );
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_render_list_and_preview_with_workspace_paths() {
+        let backend = TestBackend::new(100, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let paths = PathDisplay::workspace(vec![crate::utils::WorkspaceRoot {
+            path: std::path::PathBuf::from("/Users/test/code"),
+            label: Some("work".to_string()),
+        }]);
+
+        let mut entry = create_test_entry("Entry with path");
+        entry.project_path = Some(std::path::PathBuf::from("/Users/test/code/api"));
+        let entries = vec![&entry];
+
+        terminal
+            .draw(|f| {
+                let [list, preview] =
+                    Layout::vertical([Constraint::Length(5), Constraint::Fill(1)]).areas(f.area());
+                render_results_list(
+                    f,
+                    list,
+                    Line::from(" Results "),
+                    &entries,
+                    0,
+                    &RowDecorations {
+                        kinds: &[],
+                        paths: &paths,
+                        bookmarks: &Bookmarks::default(),
+                        summaries: &Summaries::default(),
+                        highlighter: &MatchHighlighter::default(),
+                        privacy: &PrivacyLock::default(),
+                        density: ListDensity::Compact,
+                        columns: DEFAULT_COLUMNS,
+                        timestamps: TimestampStyle::Relative,
+                    },
+                );
+                render_preview(
+                    f,
+                    preview,
+                    Some(&entry),
+                    None,
+                    None,
+                    &paths,
+                    &MatchHighlighter::default(),
+                );
+            })
+            .unwrap();
+
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert_eq!(content.matches("work/api").count(), 2);
+        assert!(!content.contains("/Users/test/code"));
+    }
+
+    #[test]
+    fn test_render_highlights_matches() {
+        let backend = TestBackend::new(100, 12);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let entry = create_test_entry("fix the parser\nthen run the tests");
+        let entries = vec![&entry];
+        let highlighter = MatchHighlighter::new("parser");
+
+        terminal
+            .draw(|f| {
+                let [list, preview] =
+                    Layout::vertical([Constraint::Length(3), Constraint::Fill(1)]).areas(f.area());
+                let decorations = RowDecorations {
+                    kinds: &[],
+                    paths: &PathDisplay::default(),
+                    bookmarks: &Bookmarks::default(),
+                    summaries: &Summaries::default(),
+                    highlighter: &highlighter,
+                    privacy: &PrivacyLock::default(),
+                    density: ListDensity::Compact,
+                    columns: DEFAULT_COLUMNS,
+                    timestamps: TimestampStyle::Relative,
+                };
+                render_results_list(f, list, Line::from(" Results "), &entries, 1, &decorations);
+                render_preview(
+                    f,
+                    preview,
+                    Some(&entry),
+                    None,
+                    None,
+                    &PathDisplay::default(),
+                    &highlighter,
+                );
+            })
+            .unwrap();
+
+        // "parser" in the list row and in the preview
+        let buffer = terminal.backend().buffer();
+        let highlighted: String = buffer
+            .content()
+            .iter()
+            .filter(|cell| cell.fg == theme().matched)
+            .map(|cell| cell.symbol())
+            .collect();
+        assert_eq!(highlighted, "parserparser");
+    }
+
+    #[test]
+    fn test_render_results_list_marks_starred_entries() {
+        let backend = TestBackend::new(100, 5);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut starred = create_test_entry("starred entry");
+        starred.uuid = Some("u1".to_string());
+        let mut other = create_test_entry("other entry");
+        other.uuid = Some("u2".to_string());
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.toggle(&starred);
+        let entries = vec![&starred, &other];
+
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                render_results_list(
+                    f,
+                    area,
+                    Line::from(" Results "),
+                    &entries,
+                    0,
+                    &RowDecorations {
+                        kinds: &[],
+                        paths: &PathDisplay::default(),
+                        bookmarks: &bookmarks,
+                        summaries: &Summaries::default(),
+                        highlighter: &MatchHighlighter::default(),
+                        privacy: &PrivacyLock::default(),
+                        density: ListDensity::Compact,
+                        columns: DEFAULT_COLUMNS,
+                        timestamps: TimestampStyle::Relative,
+                    },
+                );
+            })
+            .unwrap();
+
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert_eq!(content.matches('★').count(), 1);
+    }
+
+    #[test]
+    fn test_render_ui_redacts_sensitive_entries() {
+        let backend = TestBackend::new(100, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut entry = create_test_entry("client secret rotation");
+        entry.project_path = Some(PathBuf::from("/work/client"));
+        let entries = [&entry];
+        let privacy = PrivacyLock::new(vec![PathBuf::from("/work/client")], None);
+        let prompt = privacy.prompt(Path::new("/work/client"));
+
+        terminal
+            .draw(|f| {
+                let state = RenderState {
+                    search_query: "secret",
+                    search_cursor: None,
+                    filtered_count: 1,
+                    total_count: 1,
+                    scoped_projects: 0,
+                    filter_error: None,
+                    filter_estimate: None,
+                    status_message: None,
+                    roots_overview: None,
+                    warnings: None,
+                    session_summary: None,
+                    preview_selection: None,
+                    code_picker: None,
+                    scratchpad: None,
+                    tool_leaderboard: None,
+                    export_dialog: None,
+                    prompt_timeline: None,
+                    browse: None,
+                    activity: None,
+                    saved_searches: None,
+                    replay: None,
+                    cache_screen: None,
+                    command_palette: None,
+                    row_kinds: &[],
+                    path_display: &PathDisplay::default(),
+                    bookmarks: &Bookmarks::default(),
+                    summaries: &Summaries::default(),
+                    highlighter: &MatchHighlighter::new("secret"),
+                    privacy: &privacy,
+                    reveal_prompt: Some(&prompt),
+                    help: None,
+                    minimap: None,
+                    density: ListDensity::Compact,
+                    columns: DEFAULT_COLUMNS,
+                    timestamps: TimestampStyle::Relative,
+                };
+                render_ui(f, &entries, 0, &state);
+            })
+            .unwrap();
+
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(!content.contains("rotation"));
+        // Both the results row and the preview show the placeholder
+        assert_eq!(content.matches("🔒").count(), 2);
+        assert!(content.contains("Reveal entries of /work/client?"));
+    }
+
+    #[test]
+    fn test_render_results_list_group_badges() {
+        let backend = TestBackend::new(100, 5);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let leader = create_test_entry("fix the test");
+        let member = create_test_entry("fix the tests");
+        let entries = vec![&leader, &member];
+
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                let kinds = [RowKind::Expanded(1), RowKind::Member];
+                render_results_list(
+                    f,
+                    area,
+                    Line::from(" Results "),
+                    &entries,
+                    0,
+                    &RowDecorations {
+                        kinds: &kinds,
+                        paths: &PathDisplay::default(),
+                        bookmarks: &Bookmarks::default(),
+                        summaries: &Summaries::default(),
+                        highlighter: &MatchHighlighter::default(),
+                        privacy: &PrivacyLock::default(),
+                        density: ListDensity::Compact,
+                        columns: DEFAULT_COLUMNS,
+                        timestamps: TimestampStyle::Relative,
+                    },
+                );
+            })
+            .unwrap();
+
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("[-1]"));
+        assert!(content.contains("↳"));
+
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                let kinds = [RowKind::Collapsed(1)];
+                render_results_list(
+                    f,
+                    area,
+                    Line::from(" Results "),
+                    &entries[..1],
+                    0,
+                    &RowDecorations {
+                        kinds: &kinds,
+                        paths: &PathDisplay::default(),
+                        bookmarks: &Bookmarks::default(),
+                        summaries: &Summaries::default(),
+                        highlighter: &MatchHighlighter::default(),
+                        privacy: &PrivacyLock::default(),
+                        density: ListDensity::Compact,
+                        columns: DEFAULT_COLUMNS,
+                        timestamps: TimestampStyle::Relative,
+                    },
+                );
+            })
+            .unwrap();
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("[+1]"));
+
+        let first = Utc.with_ymd_and_hms(2024, 1, 3, 10, 0, 0).unwrap();
+        let last = Utc.with_ymd_and_hms(2024, 1, 5, 12, 0, 0).unwrap();
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                let kinds = [RowKind::Session { results: 12, first, last, expanded: false }];
+                render_results_list(
+                    f,
+                    area,
+                    Line::from(" Results "),
+                    &entries[..1],
+                    0,
+                    &RowDecorations {
+                        kinds: &kinds,
+                        paths: &PathDisplay::default(),
+                        bookmarks: &Bookmarks::default(),
+                        summaries: &Summaries::default(),
+                        highlighter: &MatchHighlighter::default(),
+                        privacy: &PrivacyLock::default(),
+                        density: ListDensity::Compact,
+                        columns: DEFAULT_COLUMNS,
+                        timestamps: TimestampStyle::Relative,
+                    },
+                );
+            })
+            .unwrap();
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("[+] "));
+        assert!(content.contains("12 msgs | Jan 3, 2024 → Jan 5, 2024"));
+
+        // A summarized session shows its summary in place of the first result
+        let mut summaries = Summaries::default();
+        summaries.insert(&[&leader], "Make the test suite pass".to_string());
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                let kinds = [
+                    RowKind::Session { results: 12, first, last, expanded: true },
+                    RowKind::Member,
+                ];
+                render_results_list(
+                    f,
+                    area,
+                    Line::from(" Results "),
+                    &entries,
+                    0,
+                    &RowDecorations {
+                        kinds: &kinds,
+                        paths: &PathDisplay::default(),
+                        bookmarks: &Bookmarks::default(),
+                        summaries: &summaries,
+                        highlighter: &MatchHighlighter::default(),
+                        privacy: &PrivacyLock::default(),
+                        density: ListDensity::Compact,
+                        columns: DEFAULT_COLUMNS,
+                        timestamps: TimestampStyle::Relative,
+                    },
+                );
+            })
+            .unwrap();
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("[-] "));
+        assert!(content.contains("| Make the test suite pass"));
+        assert!(content.contains("fix the tests"));
+        assert!(!content.contains("fix the test "));
+    }
+
+    #[test]
+    fn test_render_results_list_agent_message() {
+        let backend = TestBackend::new(100, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        let mut entry = create_test_entry("Agent response");
+        entry.entry_type = EntryType::AgentMessage;
+
+        let entries = vec![&entry];
+
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                render_results_list(
+                    f,
+                    area,
+                    Line::from(" Results "),
+                    &entries,
+                    0,
+                    &RowDecorations {
+                        kinds: &[],
+                        paths: &PathDisplay::default(),
+                        bookmarks: &Bookmarks::default(),
+                        summaries: &Summaries::default(),
+                        highlighter: &MatchHighlighter::default(),
+                        privacy: &PrivacyLock::default(),
+                        density: ListDensity::Compact,
+                        columns: DEFAULT_COLUMNS,
+                        timestamps: TimestampStyle::Relative,
+                    },
+                );
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_render_status_bar_with_search() {
+        let backend = TestBackend::new(100, 1);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                render_status_bar(
+                    f,
+                    area,
+                    StatusCounts {
+                        matched: 10,
+                        filtered: 10,
+                        total: 10,
+                        scoped_projects: 0,
+                        pending: None,
+                    },
+                    5,
+                    "search query",
+                    None,
+                    None,
+                );
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_render_status_bar_no_search() {
+        let backend = TestBackend::new(100, 1);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                render_status_bar(
+                    f,
+                    area,
+                    StatusCounts {
+                        matched: 10,
+                        filtered: 10,
+                        total: 10,
+                        scoped_projects: 0,
+                        pending: None,
+                    },
+                    0,
+                    "",
+                    None,
+                    None,
+                );
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_render_preview_multiline_content() {
+        let backend = TestBackend::new(80, 20);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        let entry = create_test_entry("Line 1\nLine 2\nLine 3");
+
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                render_preview(
+                    f,
+                    area,
+                    Some(&entry),
+                    None,
+                    None,
+                    &PathDisplay::default(),
+                    &MatchHighlighter::default(),
+                );
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_render_status_bar_empty_entries() {
+        let backend = TestBackend::new(100, 1);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                render_status_bar(
+                    f,
+                    area,
+                    StatusCounts {
+                        matched: 0,
+                        filtered: 0,
+                        total: 0,
+                        scoped_projects: 0,
+                        pending: None,
+                    },
+                    0,
+                    "",
+                    None,
+                    None,
+                );
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_render_status_bar_with_filter_error() {
+        let backend = TestBackend::new(100, 1);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                render_status_bar(
+                    f,
+                    area,
+                    StatusCounts {
+                        matched: 10,
+                        filtered: 10,
+                        total: 10,
+                        scoped_projects: 0,
+                        pending: None,
+                    },
+                    0,
+                    "test query",
+                    Some("Parse error: invalid filter"),
+                    None,
+                );
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_render_status_bar_with_filter_portion() {
+        let backend = TestBackend::new(100, 1);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                render_status_bar(
+                    f,
+                    area,
+                    StatusCounts {
+                        matched: 5,
+                        filtered: 8,
+                        total: 10,
+                        scoped_projects: 0,
+                        pending: None,
+                    },
+                    0,
+                    "type:user | search",
+                    None,
+                    None,
+                );
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_render_status_bar_with_filtered_count() {
+        let backend = TestBackend::new(100, 1);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                // matched_count=5, filtered_count=8, total_count=10
+                render_status_bar(
+                    f,
+                    area,
+                    StatusCounts {
+                        matched: 5,
+                        filtered: 8,
+                        total: 10,
+                        scoped_projects: 0,
+                        pending: None,
+                    },
+                    0,
+                    "search",
+                    None,
+                    None,
+                );
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_render_ui_with_filter_error() {
         let backend = TestBackend::new(100, 30);
         let mut terminal = Terminal::new(backend).unwrap();
 
-        let entries: Vec<&SearchEntry> = vec![];
+        let entries = [create_test_entry("First entry")];
+        let entry_refs: Vec<&SearchEntry> = entries.iter().collect();
+
+        terminal
+            .draw(|f| {
+                let state = RenderState {
+                    search_query: "invalid::: | test",
+                    search_cursor: None,
+                    filtered_count: 1,
+                    total_count: 1,
+                    scoped_projects: 0,
+                    filter_error: Some("Filter parse error"),
+                    filter_estimate: None,
+                    status_message: None,
+                    roots_overview: None,
+                    warnings: None,
+                    session_summary: None,
+                    preview_selection: None,
+                    code_picker: None,
+                    scratchpad: None,
+                    tool_leaderboard: None,
+                    export_dialog: None,
+                    prompt_timeline: None,
+                    browse: None,
+                    activity: None,
+                    saved_searches: None,
+                    replay: None,
+                    cache_screen: None,
+                    command_palette: None,
+                    row_kinds: &[],
+                    path_display: &PathDisplay::default(),
+                    bookmarks: &Bookmarks::default(),
+                    summaries: &Summaries::default(),
+                    highlighter: &MatchHighlighter::default(),
+                    privacy: &PrivacyLock::default(),
+                    reveal_prompt: None,
+                    help: None,
+                    minimap: None,
+                    density: ListDensity::Compact,
+                    columns: DEFAULT_COLUMNS,
+                    timestamps: TimestampStyle::Relative,
+                };
+                render_ui(f, &entry_refs, 0, &state);
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_render_status_bar_with_empty_fuzzy_part() {
+        let backend = TestBackend::new(100, 1);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                // Empty fuzzy part should not show "Esc: clear"
+                render_status_bar(
+                    f,
+                    area,
+                    StatusCounts {
+                        matched: 5,
+                        filtered: 5,
+                        total: 10,
+                        scoped_projects: 0,
+                        pending: None,
+                    },
+                    0,
+                    "type:user |",
+                    None,
+                    None,
+                );
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_render_status_bar_with_success_message() {
+        use std::time::{Duration, Instant};
+
+        let backend = TestBackend::new(100, 1);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        let status_msg = StatusMessage {
+            text: "✓ Copied to clipboard".to_string(),
+            message_type: MessageType::Success,
+            expires_at: Instant::now() + Duration::from_secs(3),
+        };
+
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                render_status_bar(
+                    f,
+                    area,
+                    StatusCounts {
+                        matched: 5,
+                        filtered: 5,
+                        total: 10,
+                        scoped_projects: 0,
+                        pending: None,
+                    },
+                    0,
+                    "search",
+                    None,
+                    Some(&status_msg),
+                );
+            })
+            .unwrap();
+
+        // Verify rendering succeeded (color verification would require inspecting buffer)
+    }
+
+    #[test]
+    fn test_render_status_bar_with_error_message() {
+        use std::time::{Duration, Instant};
+
+        let backend = TestBackend::new(100, 1);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        let status_msg = StatusMessage {
+            text: "✗ No entries to copy".to_string(),
+            message_type: MessageType::Error,
+            expires_at: Instant::now() + Duration::from_secs(3),
+        };
+
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                render_status_bar(
+                    f,
+                    area,
+                    StatusCounts {
+                        matched: 0,
+                        filtered: 0,
+                        total: 10,
+                        scoped_projects: 0,
+                        pending: None,
+                    },
+                    0,
+                    "search",
+                    None,
+                    Some(&status_msg),
+                );
+            })
+            .unwrap();
+
+        // Verify rendering succeeded (color verification would require inspecting buffer)
+    }
+
+    #[test]
+    fn test_render_status_bar_status_message_priority() {
+        use std::time::{Duration, Instant};
+
+        let backend = TestBackend::new(100, 1);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        // Both status message and filter error present
+        let status_msg = StatusMessage {
+            text: "✓ Copied to clipboard".to_string(),
+            message_type: MessageType::Success,
+            expires_at: Instant::now() + Duration::from_secs(3),
+        };
+
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                // Status message should take priority over filter error
+                render_status_bar(
+                    f,
+                    area,
+                    StatusCounts {
+                        matched: 5,
+                        filtered: 5,
+                        total: 10,
+                        scoped_projects: 0,
+                        pending: None,
+                    },
+                    0,
+                    "search",
+                    Some("This error should be hidden"),
+                    Some(&status_msg),
+                );
+            })
+            .unwrap();
+
+        // Verify rendering succeeded (status message should be shown, not filter error)
+    }
+
+    #[test]
+    fn test_render_ui_with_status_message() {
+        use std::time::{Duration, Instant};
+
+        let backend = TestBackend::new(100, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        let entries = [create_test_entry("First entry")];
+        let entry_refs: Vec<&SearchEntry> = entries.iter().collect();
+
+        let status_msg = StatusMessage {
+            text: "✓ Copied to clipboard".to_string(),
+            message_type: MessageType::Success,
+            expires_at: Instant::now() + Duration::from_secs(3),
+        };
+
+        terminal
+            .draw(|f| {
+                let state = RenderState {
+                    search_query: "test",
+                    search_cursor: None,
+                    filtered_count: 1,
+                    total_count: 1,
+                    scoped_projects: 0,
+                    filter_error: None,
+                    filter_estimate: None,
+                    status_message: Some(&status_msg),
+                    roots_overview: None,
+                    warnings: None,
+                    session_summary: None,
+                    preview_selection: None,
+                    code_picker: None,
+                    scratchpad: None,
+                    tool_leaderboard: None,
+                    export_dialog: None,
+                    prompt_timeline: None,
+                    browse: None,
+                    activity: None,
+                    saved_searches: None,
+                    replay: None,
+                    cache_screen: None,
+                    command_palette: None,
+                    row_kinds: &[],
+                    path_display: &PathDisplay::default(),
+                    bookmarks: &Bookmarks::default(),
+                    summaries: &Summaries::default(),
+                    highlighter: &MatchHighlighter::default(),
+                    privacy: &PrivacyLock::default(),
+                    reveal_prompt: None,
+                    help: None,
+                    minimap: None,
+                    density: ListDensity::Compact,
+                    columns: DEFAULT_COLUMNS,
+                    timestamps: TimestampStyle::Relative,
+                };
+                render_ui(f, &entry_refs, 0, &state);
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_render_ui_with_roots_overview() {
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        let entries = [create_test_entry("First entry")];
+        let entry_refs: Vec<&SearchEntry> = entries.iter().collect();
+        let roots = vec![
+            RootStatus {
+                name: "work".to_string(),
+                path: std::path::PathBuf::from("/roots/work/.claude"),
+                entry_count: 42,
+                last_refresh: Utc::now(),
+                health: RootHealth::Ok,
+                report: crate::indexer::IndexReport::default(),
+            },
+            RootStatus {
+                name: "backup".to_string(),
+                path: std::path::PathBuf::from("/roots/backup/.claude"),
+                entry_count: 0,
+                last_refresh: Utc::now(),
+                health: RootHealth::Missing,
+                report: crate::indexer::IndexReport::default(),
+            },
+        ];
+        let disabled: HashSet<String> = ["backup".to_string()].into_iter().collect();
 
         terminal
             .draw(|f| {
                 let state = RenderState {
                     search_query: "",
-                    filtered_count: 0,
-                    total_count: 0,
+                    search_cursor: None,
+                    filtered_count: 1,
+                    total_count: 1,
+                    scoped_projects: 0,
                     filter_error: None,
+                    filter_estimate: None,
                     status_message: None,
+                    roots_overview: Some(RootsOverview {
+                        roots: &roots,
+                        disabled: &disabled,
+                        selected: 0,
+                    }),
+                    warnings: None,
+                    session_summary: None,
+                    preview_selection: None,
+                    code_picker: None,
+                    scratchpad: None,
+                    tool_leaderboard: None,
+                    export_dialog: None,
+                    prompt_timeline: None,
+                    browse: None,
+                    activity: None,
+                    saved_searches: None,
+                    replay: None,
+                    cache_screen: None,
+                    command_palette: None,
+                    row_kinds: &[],
+                    path_display: &PathDisplay::default(),
+                    bookmarks: &Bookmarks::default(),
+                    summaries: &Summaries::default(),
+                    highlighter: &MatchHighlighter::default(),
+                    privacy: &PrivacyLock::default(),
+                    reveal_prompt: None,
+                    help: None,
+                    minimap: None,
+                    density: ListDensity::Compact,
+                    columns: DEFAULT_COLUMNS,
+                    timestamps: TimestampStyle::Relative,
                 };
-                render_ui(f, &entries, 0, &state);
+                render_ui(f, &entry_refs, 0, &state);
             })
             .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let content: String = buffer.content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("[x] work"));
+        assert!(content.contains("[ ] backup"));
+        assert!(content.contains("missing"));
     }
 
     #[test]
-    fn test_render_preview_with_entry() {
+    fn test_render_preview_with_session_summary() {
         let backend = TestBackend::new(80, 20);
         let mut terminal = Terminal::new(backend).unwrap();
 
         let entry = create_test_entry("Test content");
+        let summary = SessionSummary {
+            message_count: 12,
+            user_count: 5,
+            assistant_count: 7,
+            started: None,
+            ended: None,
+        };
 
         terminal
             .draw(|f| {
                 let area = f.area();
-                render_preview(f, area, Some(&entry));
+                render_preview(
+                    f,
+                    area,
+                    Some(&entry),
+                    Some(&summary),
+                    None,
+                    &PathDisplay::default(),
+                    &MatchHighlighter::default(),
+                );
             })
             .unwrap();
+
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("12 (5 user, 7 agent)"));
     }
 
     #[test]
-    fn test_render_preview_no_entry() {
-        let backend = TestBackend::new(80, 20);
+    fn test_render_help_overlay_filtered() {
+        let backend = TestBackend::new(100, 30);
         let mut terminal = Terminal::new(backend).unwrap();
 
+        let rows = [
+            ("Ctrl+Y".to_string(), "copy", "Copy selected entry to clipboard"),
+            ("Ctrl+C".to_string(), "quit", "Quit"),
+        ];
+
         terminal
             .draw(|f| {
                 let area = f.area();
-                render_preview(f, area, None);
+                render_help_overlay(
+                    f,
+                    area,
+                    &HelpOverlay {
+                        rows: rows.iter().take(1).collect(),
+                        filter_syntax: vec![],
+                        status_bar: vec![],
+                        query: "copy",
+                        scroll: 0,
+                    },
+                );
             })
             .unwrap();
+
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("Key bindings"));
+        assert!(content.contains("Ctrl+Y"));
+        assert!(content.contains("Copy selected entry"));
+        assert!(!content.contains("Ctrl+C"));
+        assert!(!content.contains("Filter syntax"));
     }
 
     #[test]
-    fn test_render_results_list_with_project_path() {
-        let backend = TestBackend::new(100, 30);
+    fn test_render_help_overlay_reference_sections() {
+        let backend = TestBackend::new(100, 20);
         let mut terminal = Terminal::new(backend).unwrap();
+        let help = HelpOverlay {
+            rows: vec![],
+            filter_syntax: FILTER_SYNTAX.iter().collect(),
+            status_bar: STATUS_BAR_LEGEND.iter().collect(),
+            query: "",
+            scroll: 0,
+        };
+        // Search line, blank line, both sections with their titles, and a blank between them
+        assert_eq!(help.line_count(), 2 + 1 + FILTER_SYNTAX.len() + 2 + STATUS_BAR_LEGEND.len());
+
+        terminal.draw(|f| render_help_overlay(f, f.area(), &help)).unwrap();
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("Filter syntax"));
+        assert!(content.contains("project:PATH"));
+        assert!(!content.contains("Status bar"));
+
+        // Scrolled down to the legend
+        let help = HelpOverlay { scroll: FILTER_SYNTAX.len() as u16 + 2, ..help };
+        terminal.draw(|f| render_help_overlay(f, f.area(), &help)).unwrap();
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("Status bar"));
+        assert!(content.contains("entry I/M"));
+        assert!(!content.contains("Search:"));
+    }
 
-        let mut entry = create_test_entry("Entry with path");
-        entry.project_path = Some(std::path::PathBuf::from("/Users/test/project"));
-
-        let entries = vec![&entry];
+    #[test]
+    fn test_render_help_overlay_no_matches() {
+        let backend = TestBackend::new(80, 20);
+        let mut terminal = Terminal::new(backend).unwrap();
 
         terminal
             .draw(|f| {
                 let area = f.area();
-                render_results_list(f, area, &entries, 0);
+                render_help_overlay(
+                    f,
+                    area,
+                    &HelpOverlay {
+                        rows: vec![],
+                        filter_syntax: vec![],
+                        status_bar: vec![],
+                        query: "zzz",
+                        scroll: 0,
+                    },
+                );
             })
             .unwrap();
+
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("No matching help"));
     }
 
     #[test]
-    fn test_render_results_list_agent_message() {
-        let backend = TestBackend::new(100, 30);
+    fn test_render_preview_visual_selection() {
+        let backend = TestBackend::new(80, 20);
         let mut terminal = Terminal::new(backend).unwrap();
-
-        let mut entry = create_test_entry("Agent response");
-        entry.entry_type = EntryType::AgentMessage;
-
-        let entries = vec![&entry];
+        let entry = create_test_entry("first\nsecond\nthird");
+        let mut selection = PreviewSelection::default();
+        selection.toggle_visual();
+        selection.move_cursor(1, 3);
 
         terminal
             .draw(|f| {
                 let area = f.area();
-                render_results_list(f, area, &entries, 0);
+                render_preview(
+                    f,
+                    area,
+                    Some(&entry),
+                    None,
+                    Some(&selection),
+                    &PathDisplay::default(),
+                    &MatchHighlighter::default(),
+                );
             })
             .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let content: String = buffer.content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("[VISUAL]"));
+
+        // Rows: border, 3 header lines, blank, then "first" (selected) and "second" (cursor)
+        assert_eq!(buffer[(1, 5)].bg, Color::Rgb(6, 78, 59));
+        assert_eq!(buffer[(1, 6)].bg, Color::Rgb(63, 63, 70));
+        assert_eq!(buffer[(1, 7)].bg, Color::Reset);
     }
 
     #[test]
-    fn test_render_status_bar_with_search() {
-        let backend = TestBackend::new(100, 1);
-        let mut terminal = Terminal::new(backend).unwrap();
+    fn test_render_scratchpad_shows_the_end() {
+        let mut scratchpad = Scratchpad::default();
+        scratchpad.append("first fragment");
+        scratchpad.append(&(1..=20).map(|i| format!("line {}", i)).collect::<Vec<_>>().join("\n"));
 
+        let backend = TestBackend::new(80, 10);
+        let mut terminal = Terminal::new(backend).unwrap();
         terminal
             .draw(|f| {
                 let area = f.area();
-                render_status_bar(
-                    f,
-                    area,
-                    StatusCounts { matched: 10, filtered: 10, total: 10 },
-                    5,
-                    "search query",
-                    None,
-                    None,
-                );
+                render_scratchpad(f, area, &scratchpad);
             })
             .unwrap();
+
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("Scratchpad (22 lines)"));
+        assert!(content.contains("line 20▏"));
+        assert!(!content.contains("first fragment"));
     }
 
     #[test]
-    fn test_render_status_bar_no_search() {
-        let backend = TestBackend::new(100, 1);
+    fn test_render_code_picker() {
+        use crate::utils::CodeBlock;
+
+        let backend = TestBackend::new(100, 20);
         let mut terminal = Terminal::new(backend).unwrap();
+        let mut picker = CodeBlockPicker::new(
+            vec![
+                CodeBlock { language: Some("rust".into()), code: "\nfn main() {}".into() },
+                CodeBlock { language: None, code: "plain text".into() },
+            ],
+            false,
+        );
+        picker.start_filename_prompt();
 
         terminal
             .draw(|f| {
                 let area = f.area();
-                render_status_bar(
-                    f,
-                    area,
-                    StatusCounts { matched: 10, filtered: 10, total: 10 },
-                    0,
-                    "",
-                    None,
-                    None,
-                );
+                render_code_picker(f, area, &picker);
             })
             .unwrap();
+
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("Code blocks (entry)"));
+        assert!(content.contains("rust"));
+        assert!(content.contains("fn main() {}"));
+        assert!(content.contains("plain text"));
+        assert!(content.contains("Write to: snippet-1.rs"));
     }
 
     #[test]
-    fn test_render_preview_multiline_content() {
-        let backend = TestBackend::new(80, 20);
-        let mut terminal = Terminal::new(backend).unwrap();
+    fn test_render_prompt_timeline() {
+        use crate::export::{PromptTimeline, TimelinePrompt};
 
-        let entry = create_test_entry("Line 1\nLine 2\nLine 3");
+        let backend = TestBackend::new(100, 20);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let prompt = |millis: i64, line: &str| TimelinePrompt {
+            timestamp: chrono::DateTime::from_timestamp_millis(millis).unwrap(),
+            session_id: "s1".into(),
+            line: line.into(),
+        };
+        let mut view = PromptTimelineView::new(PromptTimeline {
+            project: "/work/app".into(),
+            prompts: vec![
+                prompt(1_704_099_600_000, "set up the parser"),
+                prompt(1_706_778_000_000, "add tests"),
+            ],
+        });
+        view.start_filename_prompt();
 
         terminal
             .draw(|f| {
                 let area = f.area();
-                render_preview(f, area, Some(&entry));
+                render_prompt_timeline(f, area, &view, &PathDisplay::default());
             })
             .unwrap();
+
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("Prompt timeline: /work/app"));
+        assert!(content.contains("2024-01-01 09:00  set up the parser"));
+        assert!(content.contains("2024-02-01 09:00  add tests"));
+        assert!(content.contains("Write to: prompts-app.md"));
     }
 
     #[test]
-    fn test_render_status_bar_empty_entries() {
-        let backend = TestBackend::new(100, 1);
+    fn test_render_activity() {
+        let backend = TestBackend::new(60, 14);
         let mut terminal = Terminal::new(backend).unwrap();
+        let calendar = ActivityCalendar {
+            first_day: chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            counts: vec![4, 0, 1, 0, 0, 0, 0, 0, 2, 1],
+            selected: 8,
+        };
 
         terminal
             .draw(|f| {
                 let area = f.area();
-                render_status_bar(
-                    f,
-                    area,
-                    StatusCounts { matched: 0, filtered: 0, total: 0 },
-                    0,
-                    "",
-                    None,
-                    None,
-                );
+                render_activity(f, area, &calendar);
             })
             .unwrap();
+
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("Activity (4 days)"));
+        assert!(content.contains("Jan"));
+        assert!(content.contains("Mon ██· "));
+        assert!(content.contains("Tue · ▒▒"));
+        assert!(content.contains("Sun · "));
+        assert!(content.contains("2024-01-09 Tue · 2 entries"));
     }
 
     #[test]
-    fn test_render_status_bar_with_filter_error() {
-        let backend = TestBackend::new(100, 1);
+    fn test_render_replay() {
+        let backend = TestBackend::new(120, 20);
         let mut terminal = Terminal::new(backend).unwrap();
+        let question = create_test_entry("How do I parse this?");
+        let mut answer = create_test_entry("Use serde.\nThen test it.");
+        answer.entry_type = EntryType::AgentMessage;
+        answer.timestamp += chrono::Duration::seconds(5);
+        let replay = SessionReplay::new(&[answer.clone(), question], &answer);
 
         terminal
             .draw(|f| {
                 let area = f.area();
-                render_status_bar(
-                    f,
-                    area,
-                    StatusCounts { matched: 10, filtered: 10, total: 10 },
-                    0,
-                    "test query",
-                    Some("Parse error: invalid filter"),
-                    None,
-                );
+                render_replay(f, area, &replay, &PathDisplay::default(), TimestampStyle::Relative);
             })
             .unwrap();
+
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("Session replay"));
+        assert!(content.contains("2/2"));
+        assert!(content.contains("Assistant"));
+        assert!(content.contains("Use serde."));
+        assert!(content.contains("Then test it."));
+        assert!(!content.contains("How do I parse this?"));
     }
 
     #[test]
-    fn test_render_status_bar_with_filter_portion() {
-        let backend = TestBackend::new(100, 1);
+    fn test_render_cache_screen() {
+        use super::super::cache_screen::{CacheSummary, SourceCount};
+
+        let backend = TestBackend::new(120, 20);
         let mut terminal = Terminal::new(backend).unwrap();
+        let mut screen = CacheScreen::new(
+            CacheSummary {
+                location: "~/.cache/ai-history-explorer (platform cache directory)".to_string(),
+                size_bytes: 2048,
+                sources: vec![SourceCount { name: "laptop".to_string(), files: 3, entries: 42 }],
+                last_saved: None,
+            },
+            Some(CacheJob::Rebuild),
+        );
+        screen.move_selection(2);
 
         terminal
             .draw(|f| {
                 let area = f.area();
-                render_status_bar(
-                    f,
-                    area,
-                    StatusCounts { matched: 5, filtered: 8, total: 10 },
-                    0,
-                    "type:user | search",
-                    None,
-                    None,
-                );
+                render_cache_screen(f, area, &screen);
             })
             .unwrap();
+
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("~/.cache/ai-history-explorer"));
+        assert!(content.contains("2.0 KB"));
+        assert!(content.contains("never"));
+        assert!(content.contains("laptop"));
+        assert!(content.contains("42 entries"));
+        assert!(content.contains("▶ Compact"));
+        assert!(content.contains("running…"));
     }
 
     #[test]
-    fn test_render_status_bar_with_filtered_count() {
-        let backend = TestBackend::new(100, 1);
+    fn test_render_tool_leaderboard() {
+        use crate::analytics::{ToolOutputReport, ToolUsage};
+
+        let backend = TestBackend::new(100, 20);
         let mut terminal = Terminal::new(backend).unwrap();
+        let board = ToolLeaderboard::new(ToolOutputReport {
+            largest: vec![],
+            usage: vec![ToolUsage {
+                tool: "Bash".into(),
+                project: Some("/work/app".into()),
+                calls: 7,
+                total_bytes: 4096,
+                max_bytes: 2048,
+            }],
+            total_results: 7,
+            total_bytes: 4096,
+        });
 
         terminal
             .draw(|f| {
                 let area = f.area();
-                // matched_count=5, filtered_count=8, total_count=10
-                render_status_bar(
-                    f,
-                    area,
-                    StatusCounts { matched: 5, filtered: 8, total: 10 },
-                    0,
-                    "search",
-                    None,
-                    None,
-                );
+                render_tool_leaderboard(f, area, &board, &PathDisplay::default());
             })
             .unwrap();
+
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("Tool output (7 results, 4.0 KB)"));
+        assert!(content.contains("s: sort by calls"));
+        assert!(content.contains("Total ▼"));
+        assert!(content.contains("Bash"));
+        assert!(content.contains("2.0 KB"));
+        assert!(content.contains("/work/app"));
     }
 
     #[test]
-    fn test_render_ui_with_filter_error() {
-        let backend = TestBackend::new(100, 30);
-        let mut terminal = Terminal::new(backend).unwrap();
+    fn test_results_title_shows_the_search_cursor() {
+        let text = |line: &Line| line.spans.iter().map(|s| s.content.as_ref()).collect::<String>();
+        assert_eq!(text(&results_title("", None, 40)), " Results ");
+        assert_eq!(text(&results_title("type:user", None, 40)), " Search: type:user ");
+
+        let line = results_title("type:user", Some(4), 40);
+        assert_eq!(text(&line), " Search: type:user ");
+        let cursor = line.spans.iter().find(|s| s.style.add_modifier.contains(Modifier::REVERSED));
+        assert_eq!(cursor.unwrap().content, ":");
+
+        // Scrolled to keep the cursor in view, with a cell for it after the last character
+        let line = results_title("project:api | retry backoff", None, 20);
+        assert_eq!(text(&line), " Search: …y backoff ");
+        let line = results_title("project:api | retry backoff", Some(27), 20);
+        assert_eq!(text(&line), " Search: … backoff  ");
+        let line = results_title("project:api | retry backoff", Some(3), 20);
+        assert_eq!(text(&line), " Search: project:ap ");
+    }
 
-        let entries = [create_test_entry("First entry")];
-        let entry_refs: Vec<&SearchEntry> = entries.iter().collect();
+    #[test]
+    fn test_render_command_palette() {
+        let backend = TestBackend::new(100, 10);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut palette = CommandPalette::new(&crate::tui::keymap::Keymap::default());
+        for c in "cache".chars() {
+            palette.push(c);
+        }
 
         terminal
             .draw(|f| {
-                let state = RenderState {
-                    search_query: "invalid::: | test",
-                    filtered_count: 1,
-                    total_count: 1,
-                    filter_error: Some("Filter parse error"),
-                    status_message: None,
-                };
-                render_ui(f, &entry_refs, 0, &state);
+                let area = f.area();
+                render_command_palette(f, area, &palette);
             })
             .unwrap();
+
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("Commands | type to filter"));
+        assert!(content.contains("> cache"));
+        assert!(content.contains("Index cache: size and entries per root"));
+        assert!(content.contains("F4"));
+        assert!(!content.contains("Saved searches"));
     }
 
     #[test]
-    fn test_render_status_bar_with_empty_fuzzy_part() {
-        let backend = TestBackend::new(100, 1);
+    fn test_render_saved_searches() {
+        let backend = TestBackend::new(80, 10);
         let mut terminal = Terminal::new(backend).unwrap();
+        let searches = std::collections::BTreeMap::from([(
+            "api".to_string(),
+            "project:api | retry".to_string(),
+        )]);
+        let mut list = SavedSearchList::new(&searches);
+        list.name = Some("mi".to_string());
 
         terminal
             .draw(|f| {
                 let area = f.area();
-                // Empty fuzzy part should not show "Esc: clear"
-                render_status_bar(
-                    f,
-                    area,
-                    StatusCounts { matched: 5, filtered: 5, total: 10 },
-                    0,
-                    "type:user |",
-                    None,
-                    None,
-                );
+                render_saved_searches(f, area, &list, "type:user ");
             })
             .unwrap();
+
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("Saved searches | Enter: save"));
+        assert!(content.contains("api"));
+        assert!(content.contains("project:api | retry"));
+        assert!(content.contains("Save type:user as: mi"));
     }
 
     #[test]
-    fn test_render_status_bar_with_success_message() {
-        use std::time::{Duration, Instant};
-
-        let backend = TestBackend::new(100, 1);
-        let mut terminal = Terminal::new(backend).unwrap();
+    fn test_render_export_dialog() {
+        use crate::export::extract_exchange;
 
-        let status_msg = StatusMessage {
-            text: "✓ Copied to clipboard".to_string(),
-            message_type: MessageType::Success,
-            expires_at: Instant::now() + Duration::from_secs(3),
-        };
+        let lines = [
+            r#"{"type":"user","message":{"role":"user","content":"Refactor the parser\nplease"},"timestamp":1704099600000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u1"}"#,
+            r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"tool_use","id":"t1","name":"Read","input":{}}]},"timestamp":1704099601000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"a1"}"#,
+        ];
+        let conversation: Vec<_> = lines.iter().map(|l| serde_json::from_str(l).unwrap()).collect();
+        let exchange = extract_exchange(&conversation, conversation[0].timestamp, None).unwrap();
+        let mut dialog = ExportDialog::new(exchange, std::path::PathBuf::from("session.jsonl"));
+        dialog.toggle_format();
 
+        let backend = TestBackend::new(100, 12);
+        let mut terminal = Terminal::new(backend).unwrap();
         terminal
             .draw(|f| {
                 let area = f.area();
-                render_status_bar(
-                    f,
-                    area,
-                    StatusCounts { matched: 5, filtered: 5, total: 10 },
-                    0,
-                    "search",
-                    None,
-                    Some(&status_msg),
-                );
+                render_export_dialog(f, area, &dialog);
             })
             .unwrap();
 
-        // Verify rendering succeeded (color verification would require inspecting buffer)
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("Export exchange"));
+        assert!(content.contains("Refactor the parser"));
+        assert!(!content.contains("please"));
+        assert!(content.contains("2 (1 tool calls)"));
+        assert!(content.contains("JSON"));
     }
 
     #[test]
-    fn test_render_status_bar_with_error_message() {
-        use std::time::{Duration, Instant};
-
-        let backend = TestBackend::new(100, 1);
+    fn test_render_warnings_overview() {
+        let backend = TestBackend::new(100, 12);
         let mut terminal = Terminal::new(backend).unwrap();
-
-        let status_msg = StatusMessage {
-            text: "✗ No entries to copy".to_string(),
-            message_type: MessageType::Error,
-            expires_at: Instant::now() + Duration::from_secs(3),
-        };
+        let items = vec![SkippedItem {
+            path: std::path::PathBuf::from("/data/.claude/projects/-p/agent-1.jsonl"),
+            reason: "Too many parse errors".to_string(),
+        }];
+        let diagnostics = vec![Diagnostic {
+            path: std::path::PathBuf::from("/data/.claude/history.jsonl"),
+            message: "line 3: expected value".to_string(),
+        }];
 
         terminal
             .draw(|f| {
                 let area = f.area();
-                render_status_bar(
-                    f,
-                    area,
-                    StatusCounts { matched: 0, filtered: 0, total: 10 },
-                    0,
-                    "search",
-                    None,
-                    Some(&status_msg),
-                );
+                let overview =
+                    WarningsOverview { items: &items, diagnostics: &diagnostics, selected: 0 };
+                render_warnings_overview(f, area, &overview);
             })
             .unwrap();
 
-        // Verify rendering succeeded (color verification would require inspecting buffer)
+        let content: String =
+            terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("Warnings (1) | Diagnostics (1)"));
+        assert!(content.contains("agent-1.jsonl"));
+        assert!(content.contains("Too many parse errors"));
+        assert!(content.contains("line 3: expected value"));
     }
 
     #[test]
-    fn test_render_status_bar_status_message_priority() {
-        use std::time::{Duration, Instant};
-
-        let backend = TestBackend::new(100, 1);
-        let mut terminal = Terminal::new(backend).unwrap();
+    fn test_visible_results_scrolls_to_selection() {
+        let area = Rect::new(0, 0, 50, 12);
+        assert_eq!(visible_results(0, area, ListDensity::Compact), 0..10);
+        assert_eq!(visible_results(9, area, ListDensity::Compact), 0..10);
+        assert_eq!(visible_results(25, area, ListDensity::Compact), 16..26);
+        assert_eq!(visible_results(4, area, ListDensity::Comfortable), 0..5);
+        assert_eq!(visible_results(4, area, ListDensity::Detailed), 2..5);
+    }
 
-        // Both status message and filter error present
-        let status_msg = StatusMessage {
-            text: "✓ Copied to clipboard".to_string(),
-            message_type: MessageType::Success,
-            expires_at: Instant::now() + Duration::from_secs(3),
-        };
+    #[test]
+    fn test_render_results_list_detailed_rows_fit_width() {
+        let mut terminal = Terminal::new(TestBackend::new(40, 5)).unwrap();
+        let mut entry =
+            create_test_entry("refactor the parser so that errors carry spans\nsee lexer.rs");
+        entry.tools = vec!["Edit".to_string()];
+        entry.had_error = true;
+        let entries = vec![&entry];
 
         terminal
             .draw(|f| {
-                let area = f.area();
-                // Status message should take priority over filter error
-                render_status_bar(
+                render_results_list(
                     f,
-                    area,
-                    StatusCounts { matched: 5, filtered: 5, total: 10 },
+                    f.area(),
+                    Line::from(" Results "),
+                    &entries,
                     0,
-                    "search",
-                    Some("This error should be hidden"),
-                    Some(&status_msg),
+                    &RowDecorations {
+                        kinds: &[],
+                        paths: &PathDisplay::default(),
+                        bookmarks: &Bookmarks::default(),
+                        summaries: &Summaries::default(),
+                        highlighter: &MatchHighlighter::default(),
+                        privacy: &PrivacyLock::default(),
+                        density: ListDensity::Detailed,
+                        columns: DEFAULT_COLUMNS,
+                        timestamps: TimestampStyle::Relative,
+                    },
                 );
             })
             .unwrap();
 
-        // Verify rendering succeeded (status message should be shown, not filter error)
+        let buffer = terminal.backend().buffer();
+        let rows: Vec<String> =
+            (1..4).map(|y| (1..39).map(|x| buffer[(x, y)].symbol()).collect::<String>()).collect();
+        assert!(rows[0].ends_with("refactor the parser so that errors…"), "{:?}", rows);
+        assert!(rows[1].contains("| global"), "{:?}", rows);
+        assert!(rows[2].contains("⚙ Edit · ✗ error · see lexer.rs"), "{:?}", rows);
     }
 
     #[test]
-    fn test_render_ui_with_status_message() {
-        use std::time::{Duration, Instant};
+    fn test_render_results_list_configured_columns() {
+        let entry = create_test_entry("refactor the parser");
+        let entries = vec![&entry];
+        let columns = [
+            ListColumn::new(ColumnKind::Session),
+            ListColumn { kind: ColumnKind::Project, width: Some(8) },
+            ListColumn { kind: ColumnKind::Text, width: Some(10) },
+            ListColumn::new(ColumnKind::Icon),
+        ];
+        let render = |density| {
+            let mut terminal = Terminal::new(TestBackend::new(60, 4)).unwrap();
+            terminal
+                .draw(|f| {
+                    render_results_list(
+                        f,
+                        f.area(),
+                        Line::from(" Results "),
+                        &entries,
+                        0,
+                        &RowDecorations {
+                            kinds: &[],
+                            paths: &PathDisplay::default(),
+                            bookmarks: &Bookmarks::default(),
+                            summaries: &Summaries::default(),
+                            highlighter: &MatchHighlighter::default(),
+                            privacy: &PrivacyLock::default(),
+                            density,
+                            columns: &columns,
+                            timestamps: TimestampStyle::Relative,
+                        },
+                    );
+                })
+                .unwrap();
+            let buffer = terminal.backend().buffer().clone();
+            (1..3)
+                .map(|y| (1..59).map(|x| buffer[(x, y)].symbol()).collect::<String>())
+                .collect::<Vec<_>>()
+        };
 
-        let backend = TestBackend::new(100, 30);
-        let mut terminal = Terminal::new(backend).unwrap();
+        let rows = render(ListDensity::Compact);
+        assert!(rows[0].starts_with("test-ses | global   | refactor t | 👤"), "{:?}", rows);
 
-        let entries = [create_test_entry("First entry")];
-        let entry_refs: Vec<&SearchEntry> = entries.iter().collect();
+        // The text gets its own line, the other columns (without the icon) the next one
+        let rows = render(ListDensity::Comfortable);
+        assert!(rows[0].starts_with("👤"), "{:?}", rows);
+        assert!(rows[0].contains(" refactor the parser"), "{:?}", rows);
+        assert!(rows[1].starts_with("   test-ses | global  "), "{:?}", rows);
+    }
 
-        let status_msg = StatusMessage {
-            text: "✓ Copied to clipboard".to_string(),
-            message_type: MessageType::Success,
-            expires_at: Instant::now() + Duration::from_secs(3),
-        };
+    #[test]
+    fn test_truncate_to_width() {
+        assert_eq!(truncate_to_width("short", 10), "short");
+        assert_eq!(truncate_to_width("a longer line", 8), "a longe…");
+        assert_eq!(truncate_to_width("日本語のテキスト", 7), "日本語…");
+    }
+
+    #[test]
+    fn test_density_bar() {
+        assert_eq!(density_bar(0, 10, 5), "");
+        assert_eq!(density_bar(10, 10, 5), "█████");
+        assert_eq!(density_bar(5, 10, 5), "██▌");
+        assert_eq!(density_bar(1, 1000, 5), "▏");
+    }
+
+    #[test]
+    fn test_render_minimap() {
+        let backend = TestBackend::new(14, 10);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut new = create_test_entry("New entry");
+        new.timestamp = chrono::DateTime::from_timestamp_millis(1_704_099_600_000).unwrap();
+        let entries = [new, create_test_entry("Old entry")];
+        let entry_refs: Vec<&SearchEntry> = entries.iter().collect();
+        let minimap = Minimap::build(&entry_refs);
 
         terminal
             .draw(|f| {
-                let state = RenderState {
-                    search_query: "test",
-                    filtered_count: 1,
-                    total_count: 1,
-                    filter_error: None,
-                    status_message: Some(&status_msg),
-                };
-                render_ui(f, &entry_refs, 0, &state);
+                let area = f.area();
+                render_minimap(f, area, &minimap, 0..1, 0);
             })
             .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let content: String = buffer.content().iter().map(|c| c.symbol()).collect();
+        assert!(content.contains("Weeks"));
+        assert!(content.contains("█"));
+        // The first row holds the visible (newest) entry
+        assert_eq!(buffer[(1, 1)].bg, Color::Rgb(39, 39, 42));
+        assert_eq!(buffer[(1, 8)].bg, Color::Reset);
     }
 }
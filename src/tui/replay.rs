@@ -0,0 +1,103 @@
+//! Session replay popup state.
+//!
+//! Shows every indexed entry of one session in chronological order, one message at a time,
+//! so a matched prompt can be read in the context of the conversation around it.
+
+use crate::models::SearchEntry;
+
+/// State of the session replay popup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionReplay {
+    /// Entries of the session, oldest first
+    pub entries: Vec<SearchEntry>,
+    pub current: usize,
+    /// Lines scrolled within the current message
+    pub scroll: u16,
+}
+
+impl SessionReplay {
+    /// Replay the session of `selected`, starting at `selected`
+    pub fn new(all_entries: &[SearchEntry], selected: &SearchEntry) -> Self {
+        let mut entries: Vec<SearchEntry> =
+            all_entries.iter().filter(|e| e.session_id == selected.session_id).cloned().collect();
+        entries.sort_by_key(|e| e.timestamp);
+        let current = entries.iter().position(|e| e == selected).unwrap_or(0);
+        Self { entries, current, scroll: 0 }
+    }
+
+    pub fn current_entry(&self) -> Option<&SearchEntry> {
+        self.entries.get(self.current)
+    }
+
+    /// Move `delta` messages forward (or back), starting the new message at its top
+    pub fn step(&mut self, delta: isize) {
+        let max = self.entries.len().saturating_sub(1);
+        let next = self.current.saturating_add_signed(delta).min(max);
+        if next != self.current {
+            self.current = next;
+            self.scroll = 0;
+        }
+    }
+
+    pub fn jump_to(&mut self, index: usize) {
+        self.step(index as isize - self.current as isize);
+    }
+
+    pub fn scroll_by(&mut self, delta: i16) {
+        self.scroll = self.scroll.saturating_add_signed(delta);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+
+    use super::*;
+    use crate::models::EntryType;
+
+    fn entry(text: &str, session: &str, secs: i64) -> SearchEntry {
+        SearchEntry {
+            entry_type: EntryType::UserPrompt,
+            display_text: text.to_string(),
+            timestamp: DateTime::from_timestamp(secs, 0).unwrap(),
+            session_id: session.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_replay_starts_at_selected_entry_in_order() {
+        let entries = vec![
+            entry("third", "s1", 300),
+            entry("other session", "s2", 250),
+            entry("first", "s1", 100),
+            entry("second", "s1", 200),
+        ];
+
+        let replay = SessionReplay::new(&entries, &entries[3]);
+
+        let texts: Vec<_> = replay.entries.iter().map(|e| e.display_text.as_str()).collect();
+        assert_eq!(texts, vec!["first", "second", "third"]);
+        assert_eq!(replay.current_entry().unwrap().display_text, "second");
+    }
+
+    #[test]
+    fn test_step_clamps_and_resets_scroll() {
+        let entries = vec![entry("first", "s1", 100), entry("second", "s1", 200)];
+        let mut replay = SessionReplay::new(&entries, &entries[0]);
+
+        replay.scroll_by(5);
+        replay.step(1);
+        assert_eq!(replay.current, 1);
+        assert_eq!(replay.scroll, 0);
+
+        replay.scroll_by(3);
+        replay.step(1);
+        assert_eq!(replay.scroll, 3);
+
+        replay.jump_to(0);
+        assert_eq!(replay.current, 0);
+        replay.scroll_by(-1);
+        assert_eq!(replay.scroll, 0);
+    }
+}
@@ -0,0 +1,106 @@
+//! Saved searches popup state.
+//!
+//! Lists the searches saved in the config's `saved_searches` (F6) and applies the picked one;
+//! the current search is saved under a name typed in an inline prompt (Alt+S, or `s` in the
+//! list).
+
+use std::collections::BTreeMap;
+
+/// State of the saved searches popup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedSearchList {
+    /// (name, search box contents), sorted by name
+    pub searches: Vec<(String, String)>,
+    pub selected: usize,
+    /// Name being typed, while the "save current search" prompt is open
+    pub name: Option<String>,
+}
+
+impl SavedSearchList {
+    pub fn new(searches: &BTreeMap<String, String>) -> Self {
+        Self {
+            searches: searches.iter().map(|(name, query)| (name.clone(), query.clone())).collect(),
+            selected: 0,
+            name: None,
+        }
+    }
+
+    pub fn selected_search(&self) -> Option<&(String, String)> {
+        self.searches.get(self.selected)
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let max = self.searches.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(max);
+    }
+
+    /// Open the name prompt, pre-filled with the selected search's name so it can be replaced
+    pub fn start_name_prompt(&mut self) {
+        self.name = Some(self.selected_search().map(|(name, _)| name.clone()).unwrap_or_default());
+    }
+
+    /// Add or replace the search `name` and select it
+    pub fn insert(&mut self, name: &str, query: &str) {
+        let position = match self.searches.binary_search_by(|(listed, _)| listed.as_str().cmp(name))
+        {
+            Ok(position) => {
+                self.searches[position].1 = query.to_string();
+                position
+            }
+            Err(position) => {
+                self.searches.insert(position, (name.to_string(), query.to_string()));
+                position
+            }
+        };
+        self.selected = position;
+    }
+
+    /// Remove the selected search and return its name
+    pub fn remove_selected(&mut self) -> Option<String> {
+        if self.selected >= self.searches.len() {
+            return None;
+        }
+        let (name, _) = self.searches.remove(self.selected);
+        self.move_selection(0);
+        Some(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list() -> SavedSearchList {
+        let searches = BTreeMap::from([
+            ("web".to_string(), "project:web".to_string()),
+            ("api".to_string(), "project:api | retry".to_string()),
+        ]);
+        SavedSearchList::new(&searches)
+    }
+
+    #[test]
+    fn test_insert_keeps_names_sorted() {
+        let mut list = list();
+        assert_eq!(list.selected_search().unwrap().0, "api");
+
+        list.insert("cli", "project:cli");
+        assert_eq!(list.selected, 1);
+        list.insert("web", "type:user");
+        assert_eq!(list.selected, 2);
+
+        let names: Vec<_> = list.searches.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["api", "cli", "web"]);
+        assert_eq!(list.selected_search().unwrap().1, "type:user");
+    }
+
+    #[test]
+    fn test_remove_selected_clamps_selection() {
+        let mut list = list();
+        list.move_selection(5);
+
+        assert_eq!(list.remove_selected().as_deref(), Some("web"));
+        assert_eq!(list.selected, 0);
+        assert_eq!(list.remove_selected().as_deref(), Some("api"));
+        assert_eq!(list.remove_selected(), None);
+    }
+}
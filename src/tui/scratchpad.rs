@@ -0,0 +1,98 @@
+//! Scratch buffer for composing a prompt from pieces of past conversations.
+//!
+//! Entry texts, selected preview lines and code blocks are appended as fragments separated
+//! by a blank line. The buffer can then be edited lightly at its end (typing, Backspace,
+//! deleting the last word) and copied as a whole. It lives for the whole TUI session, so it
+//! keeps its content while the popup is closed.
+
+/// Text collected in the scratchpad
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scratchpad {
+    text: String,
+}
+
+impl Scratchpad {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.text.lines().count()
+    }
+
+    /// Append `fragment` after a blank line, without its trailing newlines
+    ///
+    /// Returns the number of lines appended (0 for blank fragments, which are ignored).
+    pub fn append(&mut self, fragment: &str) -> usize {
+        let fragment = fragment.trim_end_matches(['\n', '\r']);
+        if fragment.trim().is_empty() {
+            return 0;
+        }
+        if !self.text.is_empty() {
+            let trimmed = self.text.trim_end_matches('\n').len();
+            self.text.truncate(trimmed);
+            self.text.push_str("\n\n");
+        }
+        self.text.push_str(fragment);
+        fragment.lines().count()
+    }
+
+    /// Type a character (or a newline) at the end
+    pub fn push(&mut self, c: char) {
+        self.text.push(c);
+    }
+
+    /// Delete the last character
+    pub fn backspace(&mut self) {
+        self.text.pop();
+    }
+
+    /// Delete the last word and the whitespace after it
+    pub fn delete_word(&mut self) {
+        let kept = self.text.trim_end().trim_end_matches(|c: char| !c.is_whitespace()).len();
+        self.text.truncate(kept);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_separates_fragments() {
+        let mut pad = Scratchpad::default();
+        assert_eq!(pad.append("first entry\n"), 1);
+        assert_eq!(pad.append("```rust\nfn main() {}\n```"), 3);
+        assert_eq!(pad.append("  \n"), 0);
+
+        assert_eq!(pad.text(), "first entry\n\n```rust\nfn main() {}\n```");
+        assert_eq!(pad.line_count(), 5);
+    }
+
+    #[test]
+    fn test_edit_at_end() {
+        let mut pad = Scratchpad::default();
+        pad.append("fix the parser");
+        pad.push('\n');
+        for c in "and tests".chars() {
+            pad.push(c);
+        }
+        pad.backspace();
+        assert_eq!(pad.text(), "fix the parser\nand test");
+
+        pad.delete_word();
+        assert_eq!(pad.text(), "fix the parser\nand ");
+        pad.delete_word();
+        pad.delete_word();
+        assert_eq!(pad.text(), "fix the ");
+
+        // A newline typed after the last fragment doesn't leave extra blank lines
+        pad.push('\n');
+        pad.append("next");
+        assert_eq!(pad.text(), "fix the \n\nnext");
+    }
+}
@@ -0,0 +1,135 @@
+//! Searches typed in the TUI, recalled like a shell's history.
+//!
+//! Saved to `search-history.json` in the cache directory when the TUI exits. A search is
+//! recorded when `Enter` applies it and when the TUI exits with it in the search box; repeats
+//! move to the newest position instead of being stored twice.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::cache::CACHE_DIR_NAME;
+use crate::utils::{create_private_dir_all, write_private_file};
+
+/// Name of the history file inside the cache directory
+pub const SEARCH_HISTORY_FILE_NAME: &str = "search-history.json";
+
+/// Searches kept unless the config's `search_history_size` says otherwise
+pub const DEFAULT_SEARCH_HISTORY_SIZE: usize = 500;
+
+/// Previous searches, oldest first
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SearchHistory {
+    queries: Vec<String>,
+}
+
+impl SearchHistory {
+    /// Default location of the history file, if the platform has a cache directory
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join(CACHE_DIR_NAME).join(SEARCH_HISTORY_FILE_NAME))
+    }
+
+    /// Read the history; a missing or unreadable file is treated as no history
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the history, creating its directory owner-only if needed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            create_private_dir_all(dir)?;
+        }
+        let json =
+            serde_json::to_string_pretty(self).context("Failed to serialize search history")?;
+        write_private_file(path, json)
+    }
+
+    pub fn len(&self) -> usize {
+        self.queries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queries.is_empty()
+    }
+
+    /// Search at `index` (0 is the oldest)
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.queries.get(index).map(String::as_str)
+    }
+
+    /// Record `query` as the newest search, keeping at most `limit` searches
+    ///
+    /// Blank queries are not recorded.
+    pub fn push(&mut self, query: &str, limit: usize) {
+        let query = query.trim();
+        if query.is_empty() {
+            return;
+        }
+        self.queries.retain(|recorded| recorded != query);
+        self.queries.push(query.to_string());
+        let excess = self.queries.len().saturating_sub(limit);
+        self.queries.drain(..excess);
+    }
+
+    /// Index of the newest search older than `before` (all searches if `None`) that differs
+    /// from `current`
+    pub fn older(&self, before: Option<usize>, current: &str) -> Option<usize> {
+        let end = before.unwrap_or(self.queries.len()).min(self.queries.len());
+        self.queries[..end].iter().rposition(|query| query != current.trim())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(queries: &[&str]) -> SearchHistory {
+        let mut history = SearchHistory::default();
+        for query in queries {
+            history.push(query, DEFAULT_SEARCH_HISTORY_SIZE);
+        }
+        history
+    }
+
+    #[test]
+    fn test_push_moves_repeats_and_trims_to_limit() {
+        let mut history = history(&["a", "b", "  ", "c"]);
+        history.push("a ", 3);
+        assert_eq!(history.queries, ["b", "c", "a"]);
+
+        history.push("d", 2);
+        assert_eq!(history.queries, ["a", "d"]);
+    }
+
+    #[test]
+    fn test_older_skips_the_current_query() {
+        let history = history(&["a", "b", "c"]);
+        assert_eq!(history.older(None, ""), Some(2));
+        assert_eq!(history.older(None, "c"), Some(1));
+        assert_eq!(history.older(Some(1), "b"), Some(0));
+        assert_eq!(history.older(Some(0), "a"), None);
+        assert_eq!(SearchHistory::default().older(None, ""), None);
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("nested").join(SEARCH_HISTORY_FILE_NAME);
+        let history = history(&["type:user | retry", "project:api"]);
+
+        history.save(&path).unwrap();
+        assert_eq!(SearchHistory::load(&path), history);
+
+        std::fs::write(&path, "{not json").unwrap();
+        assert!(SearchHistory::load(&path).is_empty());
+    }
+}
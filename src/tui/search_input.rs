@@ -0,0 +1,172 @@
+//! Cursor of the search box.
+//!
+//! Typing inserts at the cursor and Backspace deletes before it; the cursor moves with
+//! Left/Right/Home/End and Ctrl+W / Ctrl+U delete the word before it or everything before it,
+//! like in a shell. An unset cursor sits at the end of the query, which is where it goes when
+//! the query is replaced (a recalled or saved search, Esc).
+
+/// Position of the cursor in the search box, as a byte offset into the query
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InputCursor(Option<usize>);
+
+impl InputCursor {
+    /// Byte offset of the cursor in `text`, clamped to the end and to a character boundary
+    pub fn position(self, text: &str) -> usize {
+        let mut position = self.0.unwrap_or(text.len()).min(text.len());
+        while !text.is_char_boundary(position) {
+            position -= 1;
+        }
+        position
+    }
+
+    fn set(&mut self, text: &str, position: usize) {
+        self.0 = (position < text.len()).then_some(position);
+    }
+
+    /// Insert `c` before the cursor
+    pub fn insert(&mut self, text: &mut String, c: char) {
+        let position = self.position(text);
+        text.insert(position, c);
+        self.set(text, position + c.len_utf8());
+    }
+
+    /// Delete the character before the cursor; false if there is none
+    pub fn backspace(&mut self, text: &mut String) -> bool {
+        let position = self.position(text);
+        let Some(c) = text[..position].chars().next_back() else {
+            return false;
+        };
+        let start = position - c.len_utf8();
+        text.replace_range(start..position, "");
+        self.set(text, start);
+        true
+    }
+
+    /// Delete the character under the cursor; false at the end
+    pub fn delete(&mut self, text: &mut String) -> bool {
+        let position = self.position(text);
+        let Some(c) = text[position..].chars().next() else {
+            return false;
+        };
+        text.replace_range(position..position + c.len_utf8(), "");
+        self.set(text, position);
+        true
+    }
+
+    /// Delete the word before the cursor and the spaces after it
+    pub fn delete_word(&mut self, text: &mut String) -> bool {
+        let position = self.position(text);
+        let before = text[..position].trim_end();
+        let start = before
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        if start == position {
+            return false;
+        }
+        text.replace_range(start..position, "");
+        self.set(text, start);
+        true
+    }
+
+    /// Delete everything before the cursor
+    pub fn delete_to_start(&mut self, text: &mut String) -> bool {
+        let position = self.position(text);
+        if position == 0 {
+            return false;
+        }
+        text.replace_range(..position, "");
+        self.set(text, 0);
+        true
+    }
+
+    pub fn left(&mut self, text: &str) {
+        let position = self.position(text);
+        if let Some(c) = text[..position].chars().next_back() {
+            self.set(text, position - c.len_utf8());
+        }
+    }
+
+    pub fn right(&mut self, text: &str) {
+        let position = self.position(text);
+        if let Some(c) = text[position..].chars().next() {
+            self.set(text, position + c.len_utf8());
+        }
+    }
+
+    pub fn home(&mut self, text: &str) {
+        self.set(text, 0);
+    }
+
+    pub fn end(&mut self) {
+        self.0 = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `text` with the cursor drawn as `|`
+    fn show(text: &str, cursor: InputCursor) -> String {
+        let position = cursor.position(text);
+        format!("{}|{}", &text[..position], &text[position..])
+    }
+
+    #[test]
+    fn test_insert_and_delete_at_the_cursor() {
+        let mut text = "type:user | retry".to_string();
+        let mut cursor = InputCursor::default();
+        cursor.home(&text);
+        for _ in 0.."type:".len() {
+            cursor.right(&text);
+        }
+        cursor.delete_word(&mut text);
+        assert_eq!(show(&text, cursor), "|user | retry");
+
+        cursor.insert(&mut text, 'é');
+        cursor.left(&text);
+        cursor.right(&text);
+        assert!(cursor.backspace(&mut text));
+        assert!(!cursor.backspace(&mut text));
+        assert!(cursor.delete(&mut text));
+        assert_eq!(show(&text, cursor), "|ser | retry");
+
+        cursor.end();
+        cursor.insert(&mut text, '!');
+        assert_eq!(show(&text, cursor), "ser | retry!|");
+        assert!(!cursor.delete(&mut text));
+    }
+
+    #[test]
+    fn test_delete_word_and_to_start() {
+        let mut text = "project:api  type:user  | retry".to_string();
+        let mut cursor = InputCursor::default();
+        cursor.delete_word(&mut text);
+        assert_eq!(show(&text, cursor), "project:api  type:user  | |");
+
+        for _ in 0.."  | ".len() {
+            cursor.left(&text);
+        }
+        assert!(cursor.delete_word(&mut text));
+        assert_eq!(show(&text, cursor), "project:api  |  | ");
+
+        assert!(cursor.delete_to_start(&mut text));
+        assert_eq!(show(&text, cursor), "|  | ");
+        assert!(!cursor.delete_to_start(&mut text));
+        assert!(!cursor.delete_word(&mut text));
+    }
+
+    #[test]
+    fn test_position_is_clamped() {
+        let mut cursor = InputCursor::default();
+        cursor.home("abcdef");
+        cursor.right("abcdef");
+        cursor.right("abcdef");
+        assert_eq!(cursor.position("abcdef"), 2);
+        assert_eq!(cursor.position("a"), 1);
+        assert_eq!(cursor.position("aé"), 1);
+        assert_eq!(cursor.position("ab"), 2);
+    }
+}
@@ -1,6 +1,7 @@
 use std::io;
 
 use anyhow::Result;
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
 use crossterm::execute;
 use crossterm::terminal::{
     EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
@@ -42,7 +43,7 @@ impl TerminalManager {
     /// Restore terminal to normal mode
     pub fn restore(mut self) -> Result<()> {
         disable_raw_mode()?;
-        execute!(self.terminal.backend_mut(), LeaveAlternateScreen)?;
+        execute!(self.terminal.backend_mut(), DisableMouseCapture, LeaveAlternateScreen)?;
         self.terminal.show_cursor()?;
         Ok(())
     }
@@ -53,11 +54,24 @@ impl Drop for TerminalManager {
     fn drop(&mut self) {
         // Best effort cleanup - ignore errors since we're already unwinding
         let _ = disable_raw_mode();
-        let _ = execute!(self.terminal.backend_mut(), LeaveAlternateScreen);
+        let _ = execute!(self.terminal.backend_mut(), DisableMouseCapture, LeaveAlternateScreen);
         let _ = self.terminal.show_cursor();
     }
 }
 
+/// Turn mouse reporting on or off
+///
+/// Only enabled while something clickable (the minimap) is shown, so the terminal's own
+/// text selection keeps working otherwise.
+pub fn set_mouse_capture(enabled: bool) -> Result<()> {
+    if enabled {
+        execute!(io::stdout(), EnableMouseCapture)?;
+    } else {
+        execute!(io::stdout(), DisableMouseCapture)?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
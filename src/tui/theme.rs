@@ -0,0 +1,251 @@
+//! Colors of the TUI.
+//!
+//! Rendering reads the colors from [`theme()`], which is set once at startup from the config
+//! file (`theme`, `accent_color` and `theme_colors`) and defaults to [`Theme::DARK`], the
+//! zinc/emerald palette the TUI has always used.
+//!
+//! With `theme: auto` (the default) the light or dark palette is picked from `COLORFGBG`,
+//! which many terminals (rxvt, Konsole, iTerm2, ...) set to their foreground and background
+//! color indexes.
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use anyhow::{Result, anyhow, bail};
+use ratatui::style::Color;
+
+use crate::config::ThemeBase;
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Colors of the TUI, by role
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// Borders of focused panes, icons, badges and the selected row
+    pub accent: Color,
+    /// Text on an `accent` background
+    pub on_accent: Color,
+    /// Regular text where it is set explicitly
+    pub text: Color,
+    /// Labels, hints and unfocused borders
+    pub muted: Color,
+    /// Secondary text such as minimap bars
+    pub subtle: Color,
+    /// Background of the status bar
+    pub surface: Color,
+    /// Background of the selected row in popups
+    pub highlight: Color,
+    /// Background of the preview cursor line
+    pub cursor: Color,
+    /// Background of selected preview lines
+    pub selection: Color,
+    /// Text the search query matched
+    pub matched: Color,
+    pub error: Color,
+    pub warning: Color,
+}
+
+impl Theme {
+    /// Light text on the terminal's (dark) background
+    pub const DARK: Self = Self {
+        accent: Color::Rgb(16, 185, 129),
+        on_accent: Color::Rgb(250, 250, 250),
+        text: Color::Rgb(250, 250, 250),
+        muted: Color::Rgb(113, 113, 122),
+        subtle: Color::Rgb(161, 161, 170),
+        surface: Color::Rgb(24, 24, 27),
+        highlight: Color::Rgb(39, 39, 42),
+        cursor: Color::Rgb(63, 63, 70),
+        selection: Color::Rgb(6, 78, 59),
+        matched: Color::Rgb(251, 191, 36),
+        error: Color::Rgb(239, 68, 68),
+        warning: Color::Rgb(234, 179, 8),
+    };
+
+    /// Dark text for terminals with a light background
+    pub const LIGHT: Self = Self {
+        accent: Color::Rgb(5, 150, 105),
+        on_accent: Color::Rgb(250, 250, 250),
+        text: Color::Rgb(24, 24, 27),
+        muted: Color::Rgb(113, 113, 122),
+        subtle: Color::Rgb(82, 82, 91),
+        surface: Color::Rgb(244, 244, 245),
+        highlight: Color::Rgb(228, 228, 231),
+        cursor: Color::Rgb(212, 212, 216),
+        selection: Color::Rgb(209, 250, 229),
+        matched: Color::Rgb(180, 83, 9),
+        error: Color::Rgb(220, 38, 38),
+        warning: Color::Rgb(202, 138, 4),
+    };
+
+    /// Solarized, on its dark background colors
+    pub const SOLARIZED_DARK: Self = Self {
+        accent: Color::Rgb(38, 139, 210),
+        on_accent: Color::Rgb(253, 246, 227),
+        text: Color::Rgb(147, 161, 161),
+        muted: Color::Rgb(88, 110, 117),
+        subtle: Color::Rgb(131, 148, 150),
+        surface: Color::Rgb(7, 54, 66),
+        highlight: Color::Rgb(7, 54, 66),
+        cursor: Color::Rgb(16, 72, 86),
+        selection: Color::Rgb(23, 82, 110),
+        matched: Color::Rgb(181, 137, 0),
+        error: Color::Rgb(220, 50, 47),
+        warning: Color::Rgb(203, 75, 22),
+    };
+
+    /// Solarized, on its light background colors
+    pub const SOLARIZED_LIGHT: Self = Self {
+        accent: Color::Rgb(38, 139, 210),
+        on_accent: Color::Rgb(253, 246, 227),
+        text: Color::Rgb(88, 110, 117),
+        muted: Color::Rgb(147, 161, 161),
+        subtle: Color::Rgb(101, 123, 131),
+        surface: Color::Rgb(238, 232, 213),
+        highlight: Color::Rgb(238, 232, 213),
+        cursor: Color::Rgb(227, 220, 197),
+        selection: Color::Rgb(214, 230, 240),
+        matched: Color::Rgb(203, 75, 22),
+        error: Color::Rgb(220, 50, 47),
+        warning: Color::Rgb(181, 137, 0),
+    };
+
+    /// Built-in palette of a `theme`, detecting the background for [`ThemeBase::Auto`]
+    pub fn preset(base: ThemeBase) -> Self {
+        match base {
+            ThemeBase::Auto => {
+                let colorfgbg = std::env::var("COLORFGBG").ok();
+                if light_background(colorfgbg.as_deref()) == Some(true) {
+                    Self::LIGHT
+                } else {
+                    Self::DARK
+                }
+            }
+            ThemeBase::Dark => Self::DARK,
+            ThemeBase::Light => Self::LIGHT,
+            ThemeBase::SolarizedDark => Self::SOLARIZED_DARK,
+            ThemeBase::SolarizedLight => Self::SOLARIZED_LIGHT,
+        }
+    }
+
+    /// Theme for the config's `theme`, `accent_color` and `theme_colors` (`#rrggbb`)
+    ///
+    /// `theme_colors` is keyed by role (the field names of [`Theme`]) and wins over
+    /// `accent_color`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a color is not a `#rrggbb` hex color or a role is unknown.
+    pub fn from_config(
+        base: ThemeBase,
+        accent: Option<&str>,
+        colors: &BTreeMap<String, String>,
+    ) -> Result<Self> {
+        let mut theme = Self::preset(base);
+        if let Some(accent) = accent {
+            theme.accent = parse_hex_color(accent)?;
+        }
+        for (role, color) in colors {
+            *theme.role_mut(role)? = parse_hex_color(color)?;
+        }
+        Ok(theme)
+    }
+
+    fn role_mut(&mut self, role: &str) -> Result<&mut Color> {
+        Ok(match role {
+            "accent" => &mut self.accent,
+            "on_accent" => &mut self.on_accent,
+            "text" => &mut self.text,
+            "muted" => &mut self.muted,
+            "subtle" => &mut self.subtle,
+            "surface" => &mut self.surface,
+            "highlight" => &mut self.highlight,
+            "cursor" => &mut self.cursor,
+            "selection" => &mut self.selection,
+            "matched" => &mut self.matched,
+            "error" => &mut self.error,
+            "warning" => &mut self.warning,
+            _ => return Err(anyhow!("Unknown color '{}' in theme_colors", role)),
+        })
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DARK
+    }
+}
+
+/// Use `theme` for the rest of the process; only the first call has an effect
+pub fn set_theme(theme: Theme) {
+    let _ = THEME.set(theme);
+}
+
+/// Colors to render with
+pub fn theme() -> &'static Theme {
+    THEME.get().unwrap_or(&Theme::DARK)
+}
+
+/// Whether `COLORFGBG` (`fg;bg` or `fg;default;bg`) names a light background
+///
+/// Like vim, background colors 0-6 and 8 count as dark and the other indexes as light; None
+/// if the variable is unset or its background isn't a color index.
+fn light_background(colorfgbg: Option<&str>) -> Option<bool> {
+    let background = colorfgbg?.rsplit(';').next()?.trim().parse::<u8>().ok()?;
+    Some(!matches!(background, 0..=6 | 8))
+}
+
+fn parse_hex_color(text: &str) -> Result<Color> {
+    let hex = text.trim().strip_prefix('#').unwrap_or(text.trim());
+    let channel = |i: usize| hex.get(i..i + 2).and_then(|c| u8::from_str_radix(c, 16).ok());
+    match (hex.len(), channel(0), channel(2), channel(4)) {
+        (6, Some(r), Some(g), Some(b)) => Ok(Color::Rgb(r, g, b)),
+        _ => bail!("Invalid color '{}' (expected #rrggbb)", text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_config() {
+        let none = BTreeMap::new();
+        assert_eq!(Theme::from_config(ThemeBase::Dark, None, &none).unwrap(), Theme::DARK);
+        let light = Theme::from_config(ThemeBase::Light, Some("#3B82F6"), &none).unwrap();
+        assert_eq!(light.accent, Color::Rgb(59, 130, 246));
+        assert_eq!(light.text, Theme::LIGHT.text);
+        let solarized = Theme::from_config(ThemeBase::SolarizedLight, None, &none).unwrap();
+        assert_eq!(solarized, Theme::SOLARIZED_LIGHT);
+
+        let err = Theme::from_config(ThemeBase::Dark, Some("blue"), &none).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid color 'blue' (expected #rrggbb)");
+        assert!(Theme::from_config(ThemeBase::Dark, Some("#12345"), &none).is_err());
+    }
+
+    #[test]
+    fn test_from_config_theme_colors() {
+        let colors = BTreeMap::from([
+            ("accent".to_string(), "#000001".to_string()),
+            ("matched".to_string(), "#f97316".to_string()),
+        ]);
+        let theme = Theme::from_config(ThemeBase::Dark, Some("#3b82f6"), &colors).unwrap();
+        assert_eq!(theme.accent, Color::Rgb(0, 0, 1));
+        assert_eq!(theme.matched, Color::Rgb(249, 115, 22));
+        assert_eq!(theme.text, Theme::DARK.text);
+
+        let unknown = BTreeMap::from([("shiny".to_string(), "#ffffff".to_string())]);
+        let err = Theme::from_config(ThemeBase::Dark, None, &unknown).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown color 'shiny' in theme_colors");
+    }
+
+    #[test]
+    fn test_light_background() {
+        assert_eq!(light_background(None), None);
+        assert_eq!(light_background(Some("15;0")), Some(false));
+        assert_eq!(light_background(Some("0;15")), Some(true));
+        assert_eq!(light_background(Some("0;default;7")), Some(true));
+        assert_eq!(light_background(Some("7;8")), Some(false));
+        assert_eq!(light_background(Some("15;default")), None);
+    }
+}
@@ -0,0 +1,79 @@
+//! Tool-output leaderboard popup state.
+//!
+//! Shows per tool and project how many bytes of tool output were produced, as a table that
+//! can be re-sorted by any column.
+
+use crate::analytics::{ToolOutputReport, ToolUsage, ToolUsageSort};
+
+/// State of the tool-output leaderboard popup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolLeaderboard {
+    pub usage: Vec<ToolUsage>,
+    pub sort: ToolUsageSort,
+    pub selected: usize,
+    pub total_results: usize,
+    pub total_bytes: usize,
+}
+
+impl ToolLeaderboard {
+    pub fn new(report: ToolOutputReport) -> Self {
+        Self {
+            usage: report.usage,
+            sort: ToolUsageSort::default(),
+            selected: 0,
+            total_results: report.total_results,
+            total_bytes: report.total_bytes,
+        }
+    }
+
+    /// Sort by the next column and move the selection back to the top
+    pub fn cycle_sort(&mut self) {
+        self.sort = self.sort.next();
+        self.sort.sort(&mut self.usage);
+        self.selected = 0;
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let max = self.usage.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(max);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(tool: &str, calls: usize, total_bytes: usize) -> ToolUsage {
+        ToolUsage { tool: tool.to_string(), project: None, calls, total_bytes, max_bytes: 0 }
+    }
+
+    fn leaderboard() -> ToolLeaderboard {
+        ToolLeaderboard::new(ToolOutputReport {
+            largest: vec![],
+            usage: vec![usage("Read", 2, 500), usage("Bash", 9, 100)],
+            total_results: 11,
+            total_bytes: 600,
+        })
+    }
+
+    #[test]
+    fn test_cycle_sort_resorts_and_resets_selection() {
+        let mut board = leaderboard();
+        board.move_selection(1);
+
+        board.cycle_sort();
+
+        assert_eq!(board.sort, ToolUsageSort::Calls);
+        assert_eq!(board.usage[0].tool, "Bash");
+        assert_eq!(board.selected, 0);
+    }
+
+    #[test]
+    fn test_move_selection_clamps() {
+        let mut board = leaderboard();
+        board.move_selection(5);
+        assert_eq!(board.selected, 1);
+        board.move_selection(-5);
+        assert_eq!(board.selected, 0);
+    }
+}
@@ -0,0 +1,107 @@
+//! TUI state kept between runs for the `last_query` startup view.
+//!
+//! Saved to `ai-history-explorer/ui-state.json` in the platform data directory when the TUI
+//! exits, and only while `last_query` is configured, since the query may be sensitive.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::SearchEntry;
+use crate::utils::{create_private_dir_all, write_private_file};
+
+/// Name of the state file inside the data directory
+pub const UI_STATE_FILE_NAME: &str = "ui-state.json";
+
+/// Query and selection at exit
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UiState {
+    pub query: String,
+    pub selected: Option<SelectedEntry>,
+}
+
+/// Identifies the selected entry across runs (positions change as history grows)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelectedEntry {
+    pub session_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub uuid: Option<String>,
+}
+
+impl SelectedEntry {
+    pub fn of(entry: &SearchEntry) -> Self {
+        Self {
+            session_id: entry.session_id.clone(),
+            timestamp: entry.timestamp,
+            uuid: entry.uuid.clone(),
+        }
+    }
+
+    pub fn matches(&self, entry: &SearchEntry) -> bool {
+        entry.session_id == self.session_id
+            && entry.timestamp == self.timestamp
+            && entry.uuid == self.uuid
+    }
+}
+
+impl UiState {
+    /// Default location of the state file, if the platform has a data directory
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_local_dir()
+            .map(|dir| dir.join(crate::config::CONFIG_DIR_NAME).join(UI_STATE_FILE_NAME))
+    }
+
+    /// Read the saved state; a missing or unreadable file is treated as no state
+    pub fn load(path: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    /// Write the state, creating its directory owner-only if needed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            create_private_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize UI state")?;
+        write_private_file(path, json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("nested").join(UI_STATE_FILE_NAME);
+        let state = UiState {
+            query: "project:app | parser".to_string(),
+            selected: Some(SelectedEntry {
+                session_id: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+                timestamp: DateTime::from_timestamp_millis(1_704_099_600_000).unwrap(),
+                uuid: Some("u1".to_string()),
+            }),
+        };
+
+        state.save(&path).unwrap();
+
+        assert_eq!(UiState::load(&path), Some(state));
+    }
+
+    #[test]
+    fn test_load_ignores_missing_and_corrupt_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(UI_STATE_FILE_NAME);
+        assert_eq!(UiState::load(&path), None);
+
+        std::fs::write(&path, "{not json").unwrap();
+        assert_eq!(UiState::load(&path), None);
+    }
+}
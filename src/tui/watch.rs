@@ -0,0 +1,115 @@
+//! Filesystem watcher for watch mode.
+//!
+//! Claude Code appends to `history.jsonl` and the agent conversation files under
+//! `projects/` while the TUI is open. The watcher reports writes to those JSONL files; the app
+//! waits until they have been quiet for [`DEBOUNCE`] (an answer is written in bursts) and then
+//! re-indexes in the background, so new entries show up without restarting.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+/// Quiet time after the last change before the index is refreshed
+pub const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Watches the history and conversation files of some Claude roots
+pub struct HistoryWatcher {
+    // Stops watching when dropped
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+}
+
+impl HistoryWatcher {
+    /// Watch `history.jsonl` and the `projects/` tree of each root directory
+    ///
+    /// Roots that don't exist (yet) are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the platform watcher cannot be created or a directory cannot be
+    /// watched (e.g. the inotify watch limit is reached).
+    pub fn new(roots: &[PathBuf]) -> Result<Self> {
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = sender.send(event);
+        })
+        .context("Failed to start the file watcher")?;
+
+        for root in roots.iter().filter(|root| root.is_dir()) {
+            watch(&mut watcher, root, RecursiveMode::NonRecursive)?;
+            let projects = root.join("projects");
+            if projects.is_dir() {
+                watch(&mut watcher, &projects, RecursiveMode::Recursive)?;
+            }
+        }
+        Ok(Self { _watcher: watcher, events })
+    }
+
+    /// Whether history files were written since the last call
+    pub fn changed(&self) -> bool {
+        let mut changed = false;
+        while let Ok(event) = self.events.try_recv() {
+            // Watch errors (e.g. overflowed event queues) may hide changes
+            changed |= match event {
+                Ok(event) => is_history_write(&event),
+                Err(_) => true,
+            };
+        }
+        changed
+    }
+}
+
+fn watch(watcher: &mut RecommendedWatcher, path: &Path, mode: RecursiveMode) -> Result<()> {
+    watcher.watch(path, mode).with_context(|| format!("Failed to watch {}", path.display()))
+}
+
+/// Whether `event` creates or modifies a JSONL file
+fn is_history_write(event: &Event) -> bool {
+    matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+        && event.paths.iter().any(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::time::Instant;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn wait_for_change(watcher: &HistoryWatcher) -> bool {
+        let started = Instant::now();
+        while started.elapsed() < Duration::from_secs(5) {
+            if watcher.changed() {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        false
+    }
+
+    #[test]
+    fn test_reports_writes_to_history_files() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("projects/-work-app");
+        fs::create_dir_all(&project).unwrap();
+        let watcher = HistoryWatcher::new(&[temp.path().to_path_buf()]).unwrap();
+
+        fs::write(temp.path().join("history.jsonl"), "{}\n").unwrap();
+        assert!(wait_for_change(&watcher));
+
+        fs::write(project.join("session.jsonl"), "{}\n").unwrap();
+        assert!(wait_for_change(&watcher));
+
+        // Other files don't trigger a refresh (once the events of the last write are drained)
+        std::thread::sleep(Duration::from_millis(200));
+        watcher.changed();
+        fs::write(temp.path().join("settings.json"), "{}").unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(!watcher.changed());
+    }
+}
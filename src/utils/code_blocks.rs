@@ -0,0 +1,209 @@
+//! Extraction of fenced code blocks from message text.
+
+/// A fenced code block (```` ``` ```` or `~~~`) found in message text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeBlock {
+    /// First word of the fence info string (e.g. `rust` in ```` ```rust ````)
+    pub language: Option<String>,
+    /// Block contents, verbatim, without the fences
+    pub code: String,
+}
+
+impl CodeBlock {
+    /// First non-blank line of the code, for listing blocks compactly
+    pub fn first_line(&self) -> &str {
+        self.code.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or("")
+    }
+
+    /// File extension conventionally used for the block's language
+    pub fn file_extension(&self) -> &'static str {
+        let language = self.language.as_deref().unwrap_or("").to_ascii_lowercase();
+        match language.as_str() {
+            "rust" | "rs" => "rs",
+            "python" | "py" => "py",
+            "javascript" | "js" => "js",
+            "typescript" | "ts" => "ts",
+            "tsx" => "tsx",
+            "jsx" => "jsx",
+            "bash" | "sh" | "shell" | "zsh" => "sh",
+            "go" | "golang" => "go",
+            "java" => "java",
+            "c" => "c",
+            "cpp" | "c++" => "cpp",
+            "json" => "json",
+            "yaml" | "yml" => "yaml",
+            "toml" => "toml",
+            "html" => "html",
+            "css" => "css",
+            "sql" => "sql",
+            "markdown" | "md" => "md",
+            "diff" | "patch" => "diff",
+            _ => "txt",
+        }
+    }
+}
+
+/// A piece of message text: prose or a fenced code block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextSegment {
+    /// Lines outside code blocks, verbatim
+    Prose(String),
+    Code(CodeBlock),
+}
+
+/// Extract all fenced code blocks from `text`, in order of appearance
+///
+/// Follows CommonMark fence rules loosely: a fence is three or more backticks or tildes,
+/// indented by at most three spaces, and is closed by a fence of the same character that is
+/// at least as long. An unclosed block runs to the end of the text.
+pub fn extract_code_blocks(text: &str) -> Vec<CodeBlock> {
+    split_code_blocks(text)
+        .into_iter()
+        .filter_map(|segment| match segment {
+            TextSegment::Code(block) => Some(block),
+            TextSegment::Prose(_) => None,
+        })
+        .collect()
+}
+
+/// Split `text` into prose and the fenced code blocks between it, in order
+///
+/// Fences are found as by [`extract_code_blocks`] and are not part of any segment.
+pub fn split_code_blocks(text: &str) -> Vec<TextSegment> {
+    let mut segments = Vec::new();
+    let mut prose: Vec<&str> = Vec::new();
+    // (fence char, fence length, language, collected lines)
+    let mut open: Option<(char, usize, Option<String>, Vec<&str>)> = None;
+
+    for line in text.lines() {
+        let fence = parse_fence(line);
+        match (&mut open, fence) {
+            (None, Some((ch, len, info))) => {
+                flush_prose(&mut prose, &mut segments);
+                let language = info.split_whitespace().next().map(str::to_string);
+                open = Some((ch, len, language, Vec::new()));
+            }
+            (None, None) => prose.push(line),
+            (Some((ch, len, _, _)), Some((close_ch, close_len, info)))
+                if close_ch == *ch && close_len >= *len && info.is_empty() =>
+            {
+                let (_, _, language, lines) = open.take().expect("block is open");
+                segments.push(TextSegment::Code(CodeBlock { language, code: lines.join("\n") }));
+            }
+            (Some((_, _, _, lines)), _) => lines.push(line),
+        }
+    }
+
+    if let Some((_, _, language, lines)) = open {
+        segments.push(TextSegment::Code(CodeBlock { language, code: lines.join("\n") }));
+    }
+    flush_prose(&mut prose, &mut segments);
+    segments
+}
+
+fn flush_prose(prose: &mut Vec<&str>, segments: &mut Vec<TextSegment>) {
+    if !prose.is_empty() {
+        segments.push(TextSegment::Prose(prose.join("\n")));
+        prose.clear();
+    }
+}
+
+/// Parse a fence line into (fence char, fence length, info string)
+fn parse_fence(line: &str) -> Option<(char, usize, &str)> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let rest = &line[indent..];
+    let ch = rest.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = rest.chars().take_while(|c| *c == ch).count();
+    if len < 3 {
+        return None;
+    }
+    let info = rest[len..].trim();
+    // Backtick fences can't have backticks in their info string
+    if ch == '`' && info.contains('`') {
+        return None;
+    }
+    Some((ch, len, info))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extracts_blocks_with_language() {
+        let text =
+            "Here:\n```rust\nfn main() {\n    println!(\"hi\");\n}\n```\nand\n```\nplain\n```";
+
+        let blocks = extract_code_blocks(text);
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].language.as_deref(), Some("rust"));
+        assert_eq!(blocks[0].code, "fn main() {\n    println!(\"hi\");\n}");
+        assert_eq!(blocks[0].first_line(), "fn main() {");
+        assert_eq!(blocks[1].language, None);
+        assert_eq!(blocks[1].code, "plain");
+    }
+
+    #[test]
+    fn test_tilde_fence_and_longer_closing_fence() {
+        let text = "~~~python title=x\nprint(1)\n~~~~\n";
+        let blocks = extract_code_blocks(text);
+        assert_eq!(
+            blocks,
+            vec![CodeBlock { language: Some("python".into()), code: "print(1)".into() }]
+        );
+    }
+
+    #[test]
+    fn test_nested_shorter_fence_is_content() {
+        let text = "````markdown\n```rust\nlet x = 1;\n```\n````";
+        let blocks = extract_code_blocks(text);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].code, "```rust\nlet x = 1;\n```");
+    }
+
+    #[test]
+    fn test_unclosed_block_runs_to_end() {
+        let blocks = extract_code_blocks("```sh\necho hi\necho bye");
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].code, "echo hi\necho bye");
+    }
+
+    #[test]
+    fn test_no_blocks_and_inline_backticks() {
+        assert!(extract_code_blocks("use `cargo build` here").is_empty());
+        assert!(extract_code_blocks("    ```indented too far").is_empty());
+    }
+
+    #[test]
+    fn test_split_keeps_prose_between_blocks() {
+        let segments = split_code_blocks("Run:\n```sh\ncargo test\n```\nThen check.\n\nDone");
+        assert_eq!(
+            segments,
+            vec![
+                TextSegment::Prose("Run:".into()),
+                TextSegment::Code(CodeBlock {
+                    language: Some("sh".into()),
+                    code: "cargo test".into()
+                }),
+                TextSegment::Prose("Then check.\n\nDone".into()),
+            ]
+        );
+        assert_eq!(split_code_blocks(""), vec![]);
+    }
+
+    #[test]
+    fn test_file_extension() {
+        let block = |lang: Option<&str>| CodeBlock {
+            language: lang.map(str::to_string),
+            code: String::new(),
+        };
+        assert_eq!(block(Some("Rust")).file_extension(), "rs");
+        assert_eq!(block(Some("bash")).file_extension(), "sh");
+        assert_eq!(block(Some("unknown")).file_extension(), "txt");
+        assert_eq!(block(None).file_extension(), "txt");
+    }
+}
@@ -1,11 +1,23 @@
+pub mod code_blocks;
 pub mod environment;
+pub mod notify;
 pub mod paths;
+pub mod permissions;
+pub mod plan;
 pub mod terminal;
+pub mod time;
 
+pub use code_blocks::{CodeBlock, TextSegment, extract_code_blocks, split_code_blocks};
 pub use environment::get_claude_dir;
+pub use notify::{CompletionNotifier, DEFAULT_NOTIFY_AFTER, NotifyMethod};
 pub use paths::{
-    decode_and_validate_path, decode_path, encode_path, format_path_with_tilde, safe_open_dir,
-    safe_open_file, validate_decoded_path, validate_file_size, validate_not_hardlink,
-    validate_path_not_symlink,
+    DEFAULT_MAX_FILE_SIZE_BYTES, PathDisplay, WorkspaceRoot, decode_and_validate_path, decode_path,
+    encode_path, format_path_with_tilde, safe_open_dir, safe_open_file, safe_open_file_tail,
+    validate_decoded_path, validate_file_size, validate_not_hardlink, validate_path_not_symlink,
 };
+pub use permissions::{create_private_dir_all, create_private_file, write_private_file};
+pub use plan::{ChangeKind, Plan, PlannedChange};
 pub use terminal::strip_ansi_codes;
+pub use time::{
+    DisplayZone, TimestampStyle, display_zone, format_time, format_time_in, set_display_zone,
+};
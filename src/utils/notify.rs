@@ -0,0 +1,129 @@
+//! Completion notices for slow operations.
+//!
+//! A cold index build over a large history can take long enough that the user switches to
+//! another window. [`CompletionNotifier`] rings the terminal bell (which terminals surface as
+//! an urgency hint or badge when the window is not focused) or shows a desktop notification
+//! once the operation finishes, but only if it took longer than a threshold.
+//!
+//! Desktop notifications need the `desktop-notifications` feature; without it they fall back
+//! to the bell.
+
+use std::io::{IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+/// Default minimum duration before a completion notice is given
+pub const DEFAULT_NOTIFY_AFTER: Duration = Duration::from_secs(5);
+
+/// How completion is signalled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NotifyMethod {
+    /// No notice
+    Off,
+    /// Terminal bell on stderr (only when stderr is a terminal)
+    #[default]
+    Bell,
+    /// Desktop notification (`desktop-notifications` feature; bell otherwise)
+    Desktop,
+}
+
+/// Signals completion of operations that took at least `threshold`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompletionNotifier {
+    pub method: NotifyMethod,
+    pub threshold: Duration,
+}
+
+impl Default for CompletionNotifier {
+    fn default() -> Self {
+        Self { method: NotifyMethod::default(), threshold: DEFAULT_NOTIFY_AFTER }
+    }
+}
+
+impl CompletionNotifier {
+    pub fn new(method: NotifyMethod, threshold: Duration) -> Self {
+        Self { method, threshold }
+    }
+
+    /// Notifier that never signals anything
+    pub fn off() -> Self {
+        Self::new(NotifyMethod::Off, DEFAULT_NOTIFY_AFTER)
+    }
+
+    /// Whether an operation that ran for `elapsed` should be signalled
+    pub fn should_notify(&self, elapsed: Duration) -> bool {
+        self.method != NotifyMethod::Off && elapsed >= self.threshold
+    }
+
+    /// Signal that the operation started at `started` finished, if it was slow enough
+    ///
+    /// `message` is the notification body (the bell has none). A desktop notification that
+    /// can't be shown falls back to the bell.
+    ///
+    /// # Errors
+    ///
+    /// Returns why the desktop notification couldn't be shown, for the caller to warn about;
+    /// it never fails the operation itself.
+    pub fn finished(&self, started: Instant, message: &str) -> anyhow::Result<()> {
+        if !self.should_notify(started.elapsed()) {
+            return Ok(());
+        }
+        match self.method {
+            NotifyMethod::Off => Ok(()),
+            NotifyMethod::Bell => {
+                ring_bell();
+                Ok(())
+            }
+            NotifyMethod::Desktop => show_desktop_notification(message),
+        }
+    }
+}
+
+fn ring_bell() {
+    let mut stderr = std::io::stderr();
+    if stderr.is_terminal() {
+        let _ = stderr.write_all(b"\x07").and_then(|()| stderr.flush());
+    }
+}
+
+#[cfg(feature = "desktop-notifications")]
+fn show_desktop_notification(message: &str) -> anyhow::Result<()> {
+    let result = notify_rust::Notification::new()
+        .appname("ai-history-explorer")
+        .summary("ai-history-explorer")
+        .body(message)
+        .show();
+    if let Err(e) = result {
+        ring_bell();
+        anyhow::bail!("Failed to show desktop notification: {}", e);
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "desktop-notifications"))]
+fn show_desktop_notification(_message: &str) -> anyhow::Result<()> {
+    ring_bell();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_notify_respects_threshold() {
+        let notifier = CompletionNotifier::new(NotifyMethod::Bell, Duration::from_secs(5));
+        assert!(!notifier.should_notify(Duration::from_secs(4)));
+        assert!(notifier.should_notify(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_off_never_notifies() {
+        assert!(!CompletionNotifier::off().should_notify(Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn test_finished_on_fast_operation_is_silent() {
+        // Nothing to observe beyond not panicking; fast operations return before notifying
+        CompletionNotifier::default().finished(Instant::now(), "Index ready").unwrap();
+    }
+}
@@ -5,9 +5,14 @@ use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, bail};
 use percent_encoding::{AsciiSet, CONTROLS, percent_decode_str, utf8_percent_encode};
+use serde::{Deserialize, Serialize};
 
-// Maximum file size for JSONL files: 10MB
-const MAX_FILE_SIZE_BYTES: u64 = 10 * 1024 * 1024;
+use crate::error::ExplorerError;
+
+/// Default maximum size of history and conversation files: 10MB
+///
+/// Indexing takes the limit from [`ParseOptions::max_file_size`](crate::parsers::ParseOptions).
+pub const DEFAULT_MAX_FILE_SIZE_BYTES: u64 = 10 * 1024 * 1024;
 
 // Define characters to percent-encode (everything except alphanumeric and safe chars)
 const ENCODE_SET: &AsciiSet = &CONTROLS
@@ -143,7 +148,7 @@ pub fn decode_and_validate_path(encoded: &str) -> Result<PathBuf> {
     Ok(decoded)
 }
 
-/// Validates that a file's size is within acceptable limits (10MB)
+/// Validates that a file is at most `max_bytes` long
 ///
 /// Takes an open file handle to avoid TOCTOU (time-of-check-time-of-use)
 /// race conditions where the file could be modified between the size check
@@ -153,25 +158,31 @@ pub fn decode_and_validate_path(encoded: &str) -> Result<PathBuf> {
 ///
 /// Returns an error if:
 /// - The file metadata cannot be read
-/// - The file is larger than 10MB
-pub fn validate_file_size(file: &File, path: &Path) -> Result<()> {
+/// - The file is larger than `max_bytes`
+pub fn validate_file_size(file: &File, path: &Path, max_bytes: u64) -> Result<()> {
     let metadata = file
         .metadata()
         .with_context(|| format!("Failed to read file metadata: {}", path.display()))?;
+    check_file_size(metadata.len(), path, max_bytes)
+}
 
-    let file_size = metadata.len();
-    if file_size > MAX_FILE_SIZE_BYTES {
-        bail!(
-            "File too large: {} ({} bytes, max {} bytes)",
-            path.display(),
-            file_size,
-            MAX_FILE_SIZE_BYTES
-        );
+fn check_file_size(size: u64, path: &Path, limit: u64) -> Result<()> {
+    if size > limit {
+        bail!(ExplorerError::FileTooLarge { path: path.to_path_buf(), size, limit });
     }
-
     Ok(())
 }
 
+/// Context for a failed open of `path`, keeping "not found" recognizable as
+/// [`ExplorerError::FileNotFound`]
+fn open_error(error: std::io::Error, path: &Path) -> anyhow::Error {
+    if error.kind() == std::io::ErrorKind::NotFound {
+        ExplorerError::FileNotFound(path.to_path_buf()).into()
+    } else {
+        anyhow::Error::new(error).context(format!("Failed to open {}", path.display()))
+    }
+}
+
 /// Validates that a file is not a hardlink with multiple references
 ///
 /// # Security
@@ -218,10 +229,40 @@ pub fn validate_not_hardlink(_path: &Path) -> Result<()> {
 ///
 /// Returns an error if:
 /// - The path is a symbolic link
-/// - The file is larger than 10MB
+/// - The file is larger than `max_bytes` (usually [`DEFAULT_MAX_FILE_SIZE_BYTES`] or the
+///   configured limit)
 /// - The file is not a regular file
 /// - The file has multiple hardlinks (Unix only)
-pub fn safe_open_file(path: &Path) -> Result<File> {
+pub fn safe_open_file(path: &Path, max_bytes: u64) -> Result<File> {
+    let (file, size) = open_regular_file(path)?;
+    check_file_size(size, path, max_bytes)?;
+    Ok(file)
+}
+
+/// Like [`safe_open_file`], but a file larger than `max_bytes` is opened at its last
+/// `max_bytes` bytes instead of being refused
+///
+/// For append-only files whose newest content is at the end. Returns the file, positioned
+/// where reading should start, and the number of bytes before that position.
+///
+/// # Errors
+///
+/// Same as [`safe_open_file`], apart from the size check, plus seek failures.
+pub fn safe_open_file_tail(path: &Path, max_bytes: u64) -> Result<(File, u64)> {
+    use std::io::{Seek, SeekFrom};
+
+    let (mut file, size) = open_regular_file(path)?;
+    let skipped = size.saturating_sub(max_bytes);
+    if skipped > 0 {
+        file.seek(SeekFrom::Start(skipped))
+            .with_context(|| format!("Failed to seek in {}", path.display()))?;
+    }
+    Ok((file, skipped))
+}
+
+/// Open `path` without following symlinks and check that it is a regular file without other
+/// hard links; returns the file and its size
+fn open_regular_file(path: &Path) -> Result<(File, u64)> {
     #[cfg(unix)]
     {
         use std::os::unix::fs::{MetadataExt, OpenOptionsExt};
@@ -231,23 +272,13 @@ pub fn safe_open_file(path: &Path) -> Result<File> {
             .read(true)
             .custom_flags(libc::O_NOFOLLOW)
             .open(path)
-            .with_context(|| format!("Failed to open {}", path.display()))?;
+            .map_err(|e| open_error(e, path))?;
 
-        // Now validate size on the ALREADY OPEN file (same file descriptor)
+        // Now validate the ALREADY OPEN file (same file descriptor)
         let metadata = file
             .metadata()
             .with_context(|| format!("Failed to read file metadata: {}", path.display()))?;
 
-        let size = metadata.len();
-        if size > MAX_FILE_SIZE_BYTES {
-            bail!(
-                "File too large: {} ({} bytes, max {} bytes)",
-                path.display(),
-                size,
-                MAX_FILE_SIZE_BYTES
-            );
-        }
-
         // Check it's a regular file (not FIFO, device, etc.)
         let mode = metadata.mode();
         #[allow(clippy::unnecessary_cast)]
@@ -261,7 +292,7 @@ pub fn safe_open_file(path: &Path) -> Result<File> {
             bail!("{} has {} hard links (possible hardlink attack)", path.display(), nlink);
         }
 
-        Ok(file)
+        Ok((file, metadata.len()))
     }
 
     #[cfg(not(unix))]
@@ -269,8 +300,7 @@ pub fn safe_open_file(path: &Path) -> Result<File> {
         use std::os::windows::fs::MetadataExt;
 
         // Inspect metadata without following symlinks (best-effort TOCTOU mitigation)
-        let pre_metadata = std::fs::symlink_metadata(path)
-            .with_context(|| format!("Failed to read metadata for: {}", path.display()))?;
+        let pre_metadata = std::fs::symlink_metadata(path).map_err(|e| open_error(e, path))?;
 
         if pre_metadata.file_type().is_symlink() {
             bail!("{} is a symbolic link (symlinks not allowed for security)", path.display());
@@ -283,8 +313,6 @@ pub fn safe_open_file(path: &Path) -> Result<File> {
         let file =
             File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
 
-        validate_file_size(&file, path)?;
-
         let metadata = file
             .metadata()
             .with_context(|| format!("Failed to read file metadata: {}", path.display()))?;
@@ -301,7 +329,7 @@ pub fn safe_open_file(path: &Path) -> Result<File> {
             );
         }
 
-        Ok(file)
+        Ok((file, metadata.len()))
     }
 }
 
@@ -406,10 +434,82 @@ pub(crate) fn format_path_with_tilde_internal(path: &Path, home_override: Option
     }
 }
 
+/// Directory whose projects are shown relative to it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspaceRoot {
+    pub path: PathBuf,
+    /// Shown in front of the relative path (e.g. `oss` for `~/src/github.com`)
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+/// How project paths are shown in the TUI and exports
+///
+/// Without workspace roots every path is shown `~`-shortened. With roots, paths under a root
+/// are shown relative to it (the deepest matching root wins), e.g. `work/api` for
+/// `~/code/work/api` with a root at `~/code`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathDisplay {
+    /// Deepest first
+    roots: Vec<WorkspaceRoot>,
+}
+
+impl PathDisplay {
+    pub fn workspace(mut roots: Vec<WorkspaceRoot>) -> Self {
+        roots.sort_by_key(|root| std::cmp::Reverse(root.path.components().count()));
+        Self { roots }
+    }
+
+    /// Workspace-relative form of `path`, if it is under a workspace root
+    pub fn relative(&self, path: &Path) -> Option<String> {
+        self.roots.iter().find_map(|root| {
+            let rest = path.strip_prefix(&root.path).ok()?;
+            let rest: Vec<_> = rest.components().map(|c| c.as_os_str().to_string_lossy()).collect();
+            let prefix = match &root.label {
+                Some(label) => label.clone(),
+                None if rest.is_empty() => root.path.file_name()?.to_string_lossy().into_owned(),
+                None => return Some(rest.join("/")),
+            };
+            Some(if rest.is_empty() { prefix } else { format!("{}/{}", prefix, rest.join("/")) })
+        })
+    }
+
+    /// Workspace-relative form of `path`, else the `~`-shortened path
+    pub fn format(&self, path: &Path) -> String {
+        self.relative(path).unwrap_or_else(|| format_path_with_tilde(path))
+    }
+
+    /// Workspace-relative form of `path`, else the full path (for exports read elsewhere)
+    pub fn format_full(&self, path: &Path) -> String {
+        self.relative(path).unwrap_or_else(|| path.display().to_string())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_path_display_relative_to_deepest_root() {
+        let root = |path: &str, label: Option<&str>| WorkspaceRoot {
+            path: PathBuf::from(path),
+            label: label.map(str::to_string),
+        };
+        let display = PathDisplay::workspace(vec![
+            root("/home/alice/code", None),
+            root("/home/alice/code/github.com", Some("oss")),
+        ]);
+
+        assert_eq!(display.format(Path::new("/home/alice/code/work/api")), "work/api");
+        assert_eq!(display.format(Path::new("/home/alice/code/github.com/ratatui")), "oss/ratatui");
+        assert_eq!(display.format(Path::new("/home/alice/code/github.com")), "oss");
+        assert_eq!(display.format(Path::new("/home/alice/code")), "code");
+        // Only whole components match
+        assert_eq!(display.relative(Path::new("/home/alice/codex/app")), None);
+        assert_eq!(display.format(Path::new("/opt/app")), "/opt/app");
+        assert_eq!(PathDisplay::default().relative(Path::new("/home/alice/code/x")), None);
+    }
+
     #[test]
     fn test_encode_path() {
         let path = PathBuf::from("/Users/foo/bar");
@@ -656,7 +756,7 @@ mod tests {
         temp.flush().unwrap();
 
         let file = File::open(temp.path()).unwrap();
-        let result = validate_file_size(&file, temp.path());
+        let result = validate_file_size(&file, temp.path(), DEFAULT_MAX_FILE_SIZE_BYTES);
         assert!(result.is_ok(), "Empty file should pass validation");
     }
 
@@ -672,7 +772,7 @@ mod tests {
         temp.flush().unwrap();
 
         let file = File::open(temp.path()).unwrap();
-        let result = validate_file_size(&file, temp.path());
+        let result = validate_file_size(&file, temp.path(), DEFAULT_MAX_FILE_SIZE_BYTES);
         assert!(result.is_ok(), "Small file should pass validation");
     }
 
@@ -691,7 +791,7 @@ mod tests {
         temp.flush().unwrap();
 
         let file = File::open(temp.path()).unwrap();
-        let result = validate_file_size(&file, temp.path());
+        let result = validate_file_size(&file, temp.path(), DEFAULT_MAX_FILE_SIZE_BYTES);
         assert!(result.is_ok(), "Exactly 10MB file should pass validation");
     }
 
@@ -711,7 +811,7 @@ mod tests {
         temp.flush().unwrap();
 
         let file = File::open(temp.path()).unwrap();
-        let result = validate_file_size(&file, temp.path());
+        let result = validate_file_size(&file, temp.path(), DEFAULT_MAX_FILE_SIZE_BYTES);
         assert!(result.is_err(), "File over 10MB should fail validation");
         assert!(result.unwrap_err().to_string().contains("File too large"));
     }
@@ -731,7 +831,7 @@ mod tests {
         temp.flush().unwrap();
 
         let file = File::open(temp.path()).unwrap();
-        let result = validate_file_size(&file, temp.path());
+        let result = validate_file_size(&file, temp.path(), DEFAULT_MAX_FILE_SIZE_BYTES);
         assert!(result.is_err(), "File way over limit should fail validation");
     }
 
@@ -938,7 +1038,7 @@ mod tests {
         let link = temp_dir.path().join("link.jsonl");
         symlink_file(&target, &link).unwrap();
 
-        let result = safe_open_file(&link);
+        let result = safe_open_file(&link, DEFAULT_MAX_FILE_SIZE_BYTES);
         assert!(result.is_err(), "safe_open_file should reject symbolic links on Windows");
     }
 
@@ -954,7 +1054,7 @@ mod tests {
         let link = temp_dir.path().join("link.jsonl");
         std::fs::hard_link(&target, &link).unwrap();
 
-        let result = safe_open_file(&link);
+        let result = safe_open_file(&link, DEFAULT_MAX_FILE_SIZE_BYTES);
         assert!(result.is_err(), "safe_open_file should reject hard links on Windows");
     }
 
@@ -965,7 +1065,7 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let nonexistent = temp_dir.path().join("nonexistent.jsonl");
 
-        let result = safe_open_file(&nonexistent);
+        let result = safe_open_file(&nonexistent, DEFAULT_MAX_FILE_SIZE_BYTES);
         assert!(result.is_err(), "safe_open_file should fail on nonexistent file");
     }
 
@@ -977,7 +1077,7 @@ mod tests {
         let dir = temp_dir.path().join("subdir");
         std::fs::create_dir(&dir).unwrap();
 
-        let result = safe_open_file(&dir);
+        let result = safe_open_file(&dir, DEFAULT_MAX_FILE_SIZE_BYTES);
         assert!(result.is_err(), "safe_open_file should reject directories");
     }
 
@@ -990,10 +1090,29 @@ mod tests {
         let file = temp_dir.path().join("regular.jsonl");
         std::fs::write(&file, b"test content").unwrap();
 
-        let result = safe_open_file(&file);
+        let result = safe_open_file(&file, DEFAULT_MAX_FILE_SIZE_BYTES);
         assert!(result.is_ok(), "safe_open_file should succeed on regular file");
     }
 
+    #[test]
+    fn test_safe_open_file_tail_starts_at_last_bytes() {
+        use std::io::Read;
+
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("history.jsonl");
+        std::fs::write(&file, b"0123456789").unwrap();
+
+        let (mut tail, skipped) = safe_open_file_tail(&file, 4).unwrap();
+        let mut content = String::new();
+        tail.read_to_string(&mut content).unwrap();
+        assert_eq!((skipped, content.as_str()), (6, "6789"));
+
+        let (_, skipped) = safe_open_file_tail(&file, 100).unwrap();
+        assert_eq!(skipped, 0);
+    }
+
     #[test]
     fn test_safe_open_dir_success() {
         use tempfile::TempDir;
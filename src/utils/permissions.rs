@@ -0,0 +1,255 @@
+//! Owner-only permissions for files the tool creates and reads.
+//!
+//! Conversation history routinely contains source code, credentials pasted into prompts and
+//! other private data. Everything derived from it (caches, exports, sidecar metadata) is
+//! therefore written with `0600` (files) / `0700` (directories) on Unix, and `doctor` can scan
+//! existing files for permissions that let other users read them.
+//!
+//! Elsewhere, including Windows, created files keep the ACL they inherit from their parent
+//! directory: nothing restricts them further, and `doctor` reports the permission check as
+//! unsupported instead of passing it.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+/// Mode for private files: read/write for the owner only
+pub const PRIVATE_FILE_MODE: u32 = 0o600;
+
+/// Mode for private directories: full access for the owner only
+pub const PRIVATE_DIR_MODE: u32 = 0o700;
+
+/// Write `contents` to `path`, creating or truncating it with owner-only permissions
+///
+/// Permissions of an existing file are tightened as well, so re-exporting over an old,
+/// world-readable file fixes it.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be created, written or its permissions changed.
+pub fn write_private_file(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let mut file = create_private_file(path)?;
+    file.write_all(contents.as_ref())
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
+
+/// Create or truncate `path` with owner-only permissions and open it for writing
+///
+/// For contents that are streamed rather than held in memory; see [`write_private_file`].
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be created or its permissions changed.
+pub fn create_private_file(path: &Path) -> Result<fs::File> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(PRIVATE_FILE_MODE);
+    }
+
+    let file =
+        options.open(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    restrict_permissions(path, false)?;
+    Ok(file)
+}
+
+/// Create a directory (and missing parents) with owner-only permissions
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be created.
+pub fn create_private_dir_all(path: &Path) -> Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(PRIVATE_DIR_MODE);
+    }
+
+    builder.create(path).with_context(|| format!("Failed to create directory {}", path.display()))
+}
+
+/// A file or directory accessible by users other than its owner
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionIssue {
+    pub path: PathBuf,
+    pub mode: u32,
+    pub is_dir: bool,
+}
+
+impl PermissionIssue {
+    /// Mode [`fix_permissions`] sets: `0700` for directories, `0600` for files
+    pub fn restricted_mode(&self) -> u32 {
+        if self.is_dir { PRIVATE_DIR_MODE } else { PRIVATE_FILE_MODE }
+    }
+}
+
+/// What [`find_permissive_files`] found under a root
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PermissionScan {
+    pub issues: Vec<PermissionIssue>,
+    /// Paths that couldn't be checked, with why
+    pub unreadable: Vec<(PathBuf, String)>,
+}
+
+/// Find files and directories under `root` (inclusive) that group or others can access
+///
+/// Symlinks are not followed. Returns `None` on non-Unix platforms, where nothing is checked.
+///
+/// # Errors
+///
+/// Returns an error if `root` cannot be read.
+#[cfg(unix)]
+pub fn find_permissive_files(root: &Path) -> Result<Option<PermissionScan>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::symlink_metadata(root).with_context(|| format!("Failed to read {}", root.display()))?;
+
+    let mut issues = Vec::new();
+    let mut unreadable = Vec::new();
+    for entry in walkdir::WalkDir::new(root).follow_links(false) {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                let path = e.path().unwrap_or(root).to_path_buf();
+                unreadable.push((path, e.to_string()));
+                continue;
+            }
+        };
+        if entry.file_type().is_symlink() {
+            continue;
+        }
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                unreadable.push((entry.path().to_path_buf(), e.to_string()));
+                continue;
+            }
+        };
+
+        let mode = metadata.permissions().mode() & 0o777;
+        if mode & 0o077 != 0 {
+            issues.push(PermissionIssue {
+                path: entry.path().to_path_buf(),
+                mode,
+                is_dir: metadata.is_dir(),
+            });
+        }
+    }
+    Ok(Some(PermissionScan { issues, unreadable }))
+}
+
+#[cfg(not(unix))]
+pub fn find_permissive_files(_root: &Path) -> Result<Option<PermissionScan>> {
+    Ok(None)
+}
+
+/// Restrict each reported path to its owner (`0600` files, `0700` directories)
+///
+/// # Errors
+///
+/// Returns an error on the first path whose permissions cannot be changed.
+pub fn fix_permissions(issues: &[PermissionIssue]) -> Result<()> {
+    for issue in issues {
+        restrict_permissions(&issue.path, issue.is_dir)?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn restrict_permissions(path: &Path, is_dir: bool) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = if is_dir { PRIVATE_DIR_MODE } else { PRIVATE_FILE_MODE };
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+        .with_context(|| format!("Failed to set permissions on {}", path.display()))
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_path: &Path, _is_dir: bool) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn mode_of(path: &Path) -> u32 {
+        fs::metadata(path).unwrap().permissions().mode() & 0o777
+    }
+
+    #[test]
+    fn test_write_private_file_creates_0600() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("export.md");
+
+        write_private_file(&path, "secret").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "secret");
+        assert_eq!(mode_of(&path), 0o600);
+    }
+
+    #[test]
+    fn test_write_private_file_tightens_existing_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("export.md");
+        fs::write(&path, "old contents that are longer").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_private_file(&path, "new").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(mode_of(&path), 0o600);
+    }
+
+    #[test]
+    fn test_create_private_dir_all() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("cache").join("nested");
+
+        create_private_dir_all(&path).unwrap();
+
+        assert!(path.is_dir());
+        assert_eq!(mode_of(&path), 0o700);
+    }
+
+    #[test]
+    fn test_find_and_fix_permissive_files() {
+        let dir = TempDir::new().unwrap();
+        fs::set_permissions(dir.path(), fs::Permissions::from_mode(0o700)).unwrap();
+        let private = dir.path().join("private.jsonl");
+        let shared = dir.path().join("history.jsonl");
+        write_private_file(&private, "").unwrap();
+        fs::write(&shared, "").unwrap();
+        fs::set_permissions(&shared, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let PermissionScan { issues, unreadable } =
+            find_permissive_files(dir.path()).unwrap().unwrap();
+        assert!(unreadable.is_empty());
+        assert_eq!(
+            issues,
+            vec![PermissionIssue { path: shared.clone(), mode: 0o644, is_dir: false }]
+        );
+
+        fix_permissions(&issues).unwrap();
+        assert_eq!(mode_of(&shared), 0o600);
+        assert!(find_permissive_files(dir.path()).unwrap().unwrap().issues.is_empty());
+    }
+
+    #[test]
+    fn test_find_permissive_files_missing_root() {
+        assert!(find_permissive_files(Path::new("/nonexistent/claude")).is_err());
+    }
+}
@@ -0,0 +1,258 @@
+//! Dry-run plans of commands that write files.
+//!
+//! A command run with `--dry-run` does everything up to its first write, records each file it
+//! would create, overwrite, delete or restrict to its owner in a [`Plan`] and prints the plan
+//! instead, so every command previews its changes the same way.
+
+use std::fs;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+use crate::analytics::format_bytes;
+use crate::utils::format_path_with_tilde;
+
+const RESET: &str = "\x1b[0m";
+const DIM: &str = "\x1b[2m";
+
+/// What happens to a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Create,
+    Overwrite,
+    Delete,
+    /// Permissions narrowed to the owner; the contents are unchanged
+    Restrict,
+}
+
+impl ChangeKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            ChangeKind::Create => "create",
+            ChangeKind::Overwrite => "overwrite",
+            ChangeKind::Delete => "delete",
+            ChangeKind::Restrict => "restrict",
+        }
+    }
+
+    /// ANSI color of the label: green adds, yellow replaces, red removes, cyan restricts
+    fn color(self) -> &'static str {
+        match self {
+            ChangeKind::Create => "\x1b[32m",
+            ChangeKind::Overwrite => "\x1b[33m",
+            ChangeKind::Delete => "\x1b[31m",
+            ChangeKind::Restrict => "\x1b[36m",
+        }
+    }
+}
+
+/// One file a command would change
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedChange {
+    pub kind: ChangeKind,
+    pub path: PathBuf,
+    /// Size after the change (0 for deletions and permission changes)
+    pub bytes: u64,
+    /// Size before the change (0 for new files and permission changes)
+    pub previous_bytes: u64,
+    /// What the change contains, e.g. `42 entries`
+    pub notes: Vec<String>,
+}
+
+impl PlannedChange {
+    /// Add a line describing the change
+    pub fn note(&mut self, note: impl Into<String>) -> &mut Self {
+        self.notes.push(note.into());
+        self
+    }
+}
+
+/// Files a command would change, in the order it would change them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Plan {
+    pub changes: Vec<PlannedChange>,
+}
+
+impl Plan {
+    /// Record writing `bytes` bytes to `path`: an overwrite if it exists, otherwise a create
+    pub fn write_file(&mut self, path: &Path, bytes: u64) -> &mut PlannedChange {
+        let (kind, previous_bytes) = match fs::metadata(path) {
+            Ok(metadata) => (ChangeKind::Overwrite, metadata.len()),
+            Err(_) => (ChangeKind::Create, 0),
+        };
+        self.push(PlannedChange {
+            kind,
+            path: path.to_path_buf(),
+            bytes,
+            previous_bytes,
+            notes: Vec::new(),
+        })
+    }
+
+    /// Record deleting `path`
+    pub fn delete_file(&mut self, path: &Path) -> &mut PlannedChange {
+        let previous_bytes = fs::metadata(path).map_or(0, |metadata| metadata.len());
+        self.push(PlannedChange {
+            kind: ChangeKind::Delete,
+            path: path.to_path_buf(),
+            bytes: 0,
+            previous_bytes,
+            notes: Vec::new(),
+        })
+    }
+
+    /// Record restricting `path` from `mode` to the owner-only `restricted` mode
+    pub fn restrict(&mut self, path: &Path, mode: u32, restricted: u32) -> &mut PlannedChange {
+        self.push(PlannedChange {
+            kind: ChangeKind::Restrict,
+            path: path.to_path_buf(),
+            bytes: 0,
+            previous_bytes: 0,
+            notes: vec![format!("{:04o} -> {:04o}", mode, restricted)],
+        })
+    }
+
+    fn push(&mut self, change: PlannedChange) -> &mut PlannedChange {
+        self.changes.push(change);
+        self.changes.last_mut().expect("change was just pushed")
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Bytes the changes would write
+    pub fn bytes_written(&self) -> u64 {
+        self.changes.iter().map(|change| change.bytes).sum()
+    }
+
+    /// Bytes that deleted files and shrunk overwrites would free
+    pub fn bytes_freed(&self) -> u64 {
+        self.changes.iter().map(|change| change.previous_bytes.saturating_sub(change.bytes)).sum()
+    }
+
+    fn count(&self, kind: ChangeKind) -> usize {
+        self.changes.iter().filter(|change| change.kind == kind).count()
+    }
+
+    /// The plan as printed by `--dry-run`, colored with ANSI codes if `color`
+    pub fn render(&self, color: bool) -> String {
+        let paint = |code: &str, text: &str| {
+            if color { format!("{}{}{}", code, text, RESET) } else { text.to_string() }
+        };
+
+        let mut out = String::from("Dry run: nothing was changed\n");
+        if self.is_empty() {
+            out.push_str("No files would be changed\n");
+            return out;
+        }
+        for change in &self.changes {
+            let size = match change.kind {
+                ChangeKind::Create => format_bytes(change.bytes as usize),
+                ChangeKind::Overwrite => format!(
+                    "{} (was {})",
+                    format_bytes(change.bytes as usize),
+                    format_bytes(change.previous_bytes as usize)
+                ),
+                ChangeKind::Delete => {
+                    format!("frees {}", format_bytes(change.previous_bytes as usize))
+                }
+                ChangeKind::Restrict => "owner only".to_string(),
+            };
+            out.push_str(&format!(
+                "  {}  {}  {}\n",
+                paint(change.kind.color(), &format!("{:<9}", change.kind.label())),
+                format_path_with_tilde(&change.path),
+                paint(DIM, &size)
+            ));
+            for note in &change.notes {
+                out.push_str(&format!("             {}\n", note));
+            }
+        }
+
+        let kinds =
+            [ChangeKind::Create, ChangeKind::Overwrite, ChangeKind::Delete, ChangeKind::Restrict];
+        let counts: Vec<String> = kinds
+            .into_iter()
+            .filter_map(|kind| match self.count(kind) {
+                0 => None,
+                n => Some(format!("{} to {}", n, kind.label())),
+            })
+            .collect();
+        out.push_str(&format!(
+            "{} ({} {}): {} written, {} freed\n",
+            counts.join(", "),
+            self.changes.len(),
+            if self.changes.len() == 1 { "file" } else { "files" },
+            format_bytes(self.bytes_written() as usize),
+            format_bytes(self.bytes_freed() as usize)
+        ));
+        out
+    }
+
+    /// Print the plan to stdout, in color on a terminal unless `NO_COLOR` is set
+    pub fn print(&self) {
+        let color = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+        print!("{}", self.render(color));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_file_detects_overwrites() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let existing = dir.path().join("page.html");
+        fs::write(&existing, vec![b'x'; 2048]).unwrap();
+        let new = dir.path().join("new.html");
+
+        let mut plan = Plan::default();
+        plan.write_file(&new, 100).note("3 entries");
+        plan.write_file(&existing, 1024);
+        plan.delete_file(&new);
+
+        assert_eq!(plan.changes[0].kind, ChangeKind::Create);
+        assert_eq!(plan.changes[1].kind, ChangeKind::Overwrite);
+        assert_eq!(plan.changes[1].previous_bytes, 2048);
+        assert_eq!(plan.bytes_written(), 1124);
+        assert_eq!(plan.bytes_freed(), 1024);
+        assert!(!new.exists(), "planning must not create files");
+    }
+
+    #[test]
+    fn test_render_lists_changes_and_totals() {
+        let mut plan = Plan::default();
+        plan.changes.push(PlannedChange {
+            kind: ChangeKind::Overwrite,
+            path: PathBuf::from("/tmp/page.html"),
+            bytes: 2048,
+            previous_bytes: 1024,
+            notes: vec!["42 entries".to_string()],
+        });
+
+        let text = plan.render(false);
+        assert_eq!(
+            text,
+            "Dry run: nothing was changed\n  \
+             overwrite  /tmp/page.html  2.0 KB (was 1.0 KB)\n             42 entries\n\
+             1 to overwrite (1 file): 2.0 KB written, 0 B freed\n"
+        );
+        assert!(plan.render(true).contains("\x1b[33moverwrite"));
+        assert!(Plan::default().render(false).contains("No files would be changed"));
+    }
+
+    #[test]
+    fn test_restrict_changes_no_bytes() {
+        let mut plan = Plan::default();
+        plan.restrict(Path::new("/tmp/history.jsonl"), 0o644, 0o600);
+
+        let text = plan.render(false);
+        assert!(
+            text.contains(
+                "  restrict   /tmp/history.jsonl  owner only\n             0644 -> 0600\n"
+            )
+        );
+        assert!(text.ends_with("1 to restrict (1 file): 0 B written, 0 B freed\n"));
+    }
+}
@@ -0,0 +1,271 @@
+//! Human-readable timestamps for the TUI and the CLI's text output.
+//!
+//! Entries are stored in UTC and shown in the display zone ([`set_display_zone`]): the local
+//! zone unless the config's `timezone` names another.
+
+use std::sync::RwLock;
+
+use anyhow::{Result, anyhow};
+use chrono::{
+    DateTime, Datelike, FixedOffset, Local, NaiveDate, NaiveTime, TimeDelta, TimeZone, Utc,
+};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// How timestamps are shown (`timestamps` in the config)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampStyle {
+    /// "5m ago", "yesterday", "3 weeks ago", then dates for entries older than four weeks
+    #[default]
+    Relative,
+    /// Date and time in the display zone, e.g. "2024-01-15 14:05"
+    Absolute,
+}
+
+impl TimestampStyle {
+    /// The other style
+    pub fn toggled(self) -> Self {
+        match self {
+            TimestampStyle::Relative => TimestampStyle::Absolute,
+            TimestampStyle::Absolute => TimestampStyle::Relative,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TimestampStyle::Relative => "relative",
+            TimestampStyle::Absolute => "absolute",
+        }
+    }
+}
+
+/// Time zone timestamps are shown in (`timezone` in the config)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayZone {
+    Utc,
+    /// The system's zone when its name cannot be found (shown with a numeric offset)
+    Local,
+    Named(Tz),
+}
+
+impl DisplayZone {
+    /// Parse `local`, `utc` or an IANA zone name such as `Europe/Berlin`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for a name that is not in the time zone database.
+    pub fn parse(name: &str) -> Result<Self> {
+        let name = name.trim();
+        match name.to_lowercase().as_str() {
+            "local" => Ok(Self::local()),
+            "utc" => Ok(Self::Utc),
+            _ => name.parse::<Tz>().map(Self::Named).map_err(|_| {
+                anyhow!(
+                    "Unknown time zone '{}' (use local, utc or a name like Europe/Berlin)",
+                    name
+                )
+            }),
+        }
+    }
+
+    /// The system's zone: `TZ` if set, otherwise the zone the OS is configured with
+    pub fn local() -> Self {
+        std::env::var("TZ")
+            .ok()
+            .map(|tz| tz.trim_start_matches(':').to_string())
+            .filter(|tz| !tz.is_empty())
+            .or_else(|| iana_time_zone::get_timezone().ok())
+            .and_then(|name| name.parse::<Tz>().ok())
+            .map(Self::Named)
+            .unwrap_or(Self::Local)
+    }
+
+    /// `timestamp` as wall-clock time in this zone
+    pub fn convert(self, timestamp: &DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self {
+            Self::Utc => timestamp.fixed_offset(),
+            Self::Local => timestamp.with_timezone(&Local).fixed_offset(),
+            Self::Named(tz) => timestamp.with_timezone(&tz).fixed_offset(),
+        }
+    }
+
+    /// Calendar day of `timestamp` in this zone
+    pub fn date(self, timestamp: &DateTime<Utc>) -> NaiveDate {
+        self.convert(timestamp).date_naive()
+    }
+
+    /// First instant of `day` in this zone: midnight, or the first whole hour after it on a day
+    /// whose midnight a daylight saving change skips
+    pub fn start_of_day(self, day: NaiveDate) -> DateTime<Utc> {
+        let midnight = day.and_time(NaiveTime::MIN);
+        (0..24)
+            .map(|hour| midnight + TimeDelta::hours(hour))
+            .find_map(|local| match self {
+                Self::Utc => Some(local.and_utc()),
+                Self::Local => Local.from_local_datetime(&local).earliest().map(|t| t.to_utc()),
+                Self::Named(tz) => tz.from_local_datetime(&local).earliest().map(|t| t.to_utc()),
+            })
+            .unwrap_or_else(|| midnight.and_utc())
+    }
+
+    /// Abbreviation of the zone at `timestamp`, e.g. "CET" or "PDT"
+    ///
+    /// Zones without an abbreviation (and an unnamed local zone) give the offset, e.g. "+05:30".
+    pub fn abbreviation(self, timestamp: &DateTime<Utc>) -> String {
+        match self {
+            Self::Utc => "UTC".to_string(),
+            Self::Local => self.convert(timestamp).format("%:z").to_string(),
+            Self::Named(tz) => {
+                let abbreviation = tz.offset_from_utc_datetime(&timestamp.naive_utc()).to_string();
+                if abbreviation.starts_with(['+', '-']) && abbreviation.len() == 3 {
+                    format!("{}:00", abbreviation)
+                } else {
+                    abbreviation
+                }
+            }
+        }
+    }
+}
+
+/// Zone timestamps are shown in; UTC until [`set_display_zone`] is called
+static DISPLAY_ZONE: RwLock<DisplayZone> = RwLock::new(DisplayZone::Utc);
+
+/// Show timestamps in `zone` from now on
+pub fn set_display_zone(zone: DisplayZone) {
+    *DISPLAY_ZONE.write().unwrap_or_else(|e| e.into_inner()) = zone;
+}
+
+/// Zone timestamps are shown in (see [`set_display_zone`])
+pub fn display_zone() -> DisplayZone {
+    *DISPLAY_ZONE.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Format `timestamp` in `style`, relative to `now`, in the display zone
+///
+/// Relative times are tiered: "just now", minutes and hours ("45m ago", "3h ago"),
+/// "yesterday", days up to a week ("5d ago"), weeks up to four ("2 weeks ago"), and older
+/// entries as dates ("Jan 15", or "Dec 3, 2024" in another year).
+pub fn format_time(timestamp: &DateTime<Utc>, style: TimestampStyle, now: DateTime<Utc>) -> String {
+    format_time_in(timestamp, style, now, display_zone())
+}
+
+/// [`format_time`] in `zone`
+pub fn format_time_in(
+    timestamp: &DateTime<Utc>,
+    style: TimestampStyle,
+    now: DateTime<Utc>,
+    zone: DisplayZone,
+) -> String {
+    let local = zone.convert(timestamp);
+    if style == TimestampStyle::Absolute {
+        return local.format("%Y-%m-%d %H:%M").to_string();
+    }
+
+    let seconds = now.signed_duration_since(*timestamp).num_seconds();
+    let minutes = seconds / 60;
+    let hours = minutes / 60;
+    let days = hours / 24;
+    let weeks = days / 7;
+
+    if weeks >= 4 {
+        if local.year() == zone.convert(&now).year() {
+            local.format("%b %-d").to_string()
+        } else {
+            local.format("%b %-d, %Y").to_string()
+        }
+    } else if weeks > 1 {
+        format!("{} weeks ago", weeks)
+    } else if weeks == 1 {
+        "1 week ago".to_string()
+    } else if days > 1 {
+        format!("{}d ago", days)
+    } else if days == 1 {
+        "yesterday".to_string()
+    } else if hours > 0 {
+        format!("{}h ago", hours)
+    } else if minutes > 0 {
+        format!("{}m ago", minutes)
+    } else {
+        "just now".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+
+    fn relative(ago: Duration) -> String {
+        let now = Utc.with_ymd_and_hms(2024, 6, 15, 12, 0, 0).unwrap();
+        format_time(&(now - ago), TimestampStyle::Relative, now)
+    }
+
+    #[test]
+    fn test_relative_tiers() {
+        assert_eq!(relative(Duration::seconds(30)), "just now");
+        assert_eq!(relative(Duration::seconds(-30)), "just now");
+        assert_eq!(relative(Duration::minutes(45)), "45m ago");
+        assert_eq!(relative(Duration::hours(3)), "3h ago");
+        assert_eq!(relative(Duration::hours(30)), "yesterday");
+        assert_eq!(relative(Duration::days(5)), "5d ago");
+        assert_eq!(relative(Duration::days(8)), "1 week ago");
+        assert_eq!(relative(Duration::days(25)), "3 weeks ago");
+    }
+
+    #[test]
+    fn test_relative_falls_back_to_dates() {
+        assert_eq!(relative(Duration::days(30)), "May 16");
+        assert_eq!(relative(Duration::days(400)), "May 12, 2023");
+    }
+
+    #[test]
+    fn test_absolute() {
+        let now = Utc.with_ymd_and_hms(2024, 6, 15, 12, 0, 0).unwrap();
+        let timestamp = now - Duration::minutes(5);
+        assert_eq!(format_time(&timestamp, TimestampStyle::Absolute, now), "2024-06-15 11:55");
+        assert_eq!(TimestampStyle::Absolute.toggled(), TimestampStyle::Relative);
+    }
+
+    #[test]
+    fn test_display_zones() {
+        let now = Utc.with_ymd_and_hms(2024, 6, 15, 12, 0, 0).unwrap();
+        let winter = Utc.with_ymd_and_hms(2023, 12, 31, 23, 30, 0).unwrap();
+        let berlin = DisplayZone::parse("Europe/Berlin").unwrap();
+
+        assert_eq!(format_time_in(&now, TimestampStyle::Absolute, now, berlin), "2024-06-15 14:00");
+        assert_eq!(berlin.abbreviation(&now), "CEST");
+        assert_eq!(berlin.abbreviation(&winter), "CET");
+        // Dates of older entries follow the zone too: 23:30 UTC is already January 1 in Berlin
+        assert_eq!(format_time_in(&winter, TimestampStyle::Relative, now, berlin), "Jan 1");
+        assert_eq!(
+            format_time_in(&winter, TimestampStyle::Relative, now, DisplayZone::Utc),
+            "Dec 31, 2023"
+        );
+
+        assert_eq!(DisplayZone::parse(" UTC ").unwrap(), DisplayZone::Utc);
+        assert_eq!(DisplayZone::Utc.abbreviation(&now), "UTC");
+        let err = DisplayZone::parse("Mars/Olympus").unwrap_err();
+        assert!(err.to_string().starts_with("Unknown time zone 'Mars/Olympus'"));
+    }
+
+    #[test]
+    fn test_zone_days() {
+        let los_angeles = DisplayZone::parse("America/Los_Angeles").unwrap();
+        // Jan 15 20:00 PST is already Jan 16 in UTC
+        let evening = Utc.with_ymd_and_hms(2024, 1, 16, 4, 0, 0).unwrap();
+        let jan_15 = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        assert_eq!(los_angeles.date(&evening), jan_15);
+        assert_eq!(DisplayZone::Utc.date(&evening), jan_15.succ_opt().unwrap());
+        assert_eq!(
+            los_angeles.start_of_day(jan_15),
+            Utc.with_ymd_and_hms(2024, 1, 15, 8, 0, 0).unwrap()
+        );
+
+        // Midnight doesn't exist in Santiago on the day clocks go forward
+        let santiago = DisplayZone::parse("America/Santiago").unwrap();
+        let start = santiago.start_of_day(NaiveDate::from_ymd_opt(2023, 9, 3).unwrap());
+        assert_eq!(start, Utc.with_ymd_and_hms(2023, 9, 3, 4, 0, 0).unwrap());
+    }
+}
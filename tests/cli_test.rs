@@ -86,7 +86,7 @@ fn test_cli_stats_with_missing_claude_dir() {
         .assert()
         .success() // Should succeed with warning and empty results
         .stdout(predicate::str::contains("Total entries: 0"))
-        .stderr(predicate::str::contains("history.jsonl not found"));
+        .stderr(predicate::str::contains("Claude directory not found"));
 }
 
 #[test]
@@ -106,7 +106,7 @@ invalid line 3
     cmd.env("HOME", temp_home.path())
         .arg("stats")
         .assert()
-        .success() // Graceful degradation - continues with warning
+        .code(3) // Graceful degradation - continues with warning, reported as partial
         .stdout(predicate::str::contains("Total entries: 0"))
         .stderr(predicate::str::contains("Too many parse failures"));
 }
@@ -127,6 +127,1334 @@ invalid line
     cmd.env("HOME", temp_home.path())
         .arg("stats")
         .assert()
-        .success() // Should succeed with partial corruption
+        .code(3) // Index is built, but skipped lines make it partial
         .stdout(predicate::str::contains("Total entries: 2"));
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["stats", "--verbose"])
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("history.jsonl: line 2: expected value"))
+        .stderr(predicate::str::contains("Indexed 2 entries (0 agent files parsed, 0 failed)"))
+        .stderr(predicate::str::contains("Skipped 1 malformed history lines"));
+}
+
+#[test]
+fn test_cli_stats_report_json_partial() {
+    let history_content = r#"{"display":"Valid 1","timestamp":1000,"sessionId":"550e8400-e29b-41d4-a716-446655440000"}
+invalid line
+{"display":"Valid 2","timestamp":2000,"sessionId":"550e8400-e29b-41d4-a716-446655440001"}"#;
+
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(claude_dir.join("history.jsonl"), history_content).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["stats", "--report", "json"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(3));
+    // stdout contains only the report
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["status"], "partial");
+    assert_eq!(report["entries_indexed"], 2);
+    assert_eq!(report["history_lines_skipped"], 1);
+}
+
+#[test]
+fn test_cli_stats_report_json_success() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        r#"{"display":"Valid","timestamp":1000,"sessionId":"550e8400-e29b-41d4-a716-446655440000"}"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"));
+    cmd.env("HOME", temp_home.path())
+        .args(["stats", "--report", "json"])
+        .assert()
+        .code(0)
+        .stdout(predicate::str::contains(r#""status": "success""#))
+        .stdout(predicate::str::contains("Claude Code History Statistics").not());
+}
+
+#[test]
+fn test_cli_max_tool_output_reports_skipped_bytes() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    let project_dir = claude_dir.join("projects").join("-Users%2Ftest%2Fproject");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::write(claude_dir.join("history.jsonl"), "").unwrap();
+    std::fs::write(
+        project_dir.join("agent-1.jsonl"),
+        format!(
+            r#"{{"type":"user","message":{{"role":"user","content":[{{"type":"tool_result","tool_use_id":"t1","content":"{}"}}]}},"timestamp":1000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u1"}}"#,
+            "x".repeat(5000)
+        ),
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["stats", "--report", "json", "--max-tool-output", "1000"])
+        .args(["--oversized-tool-output", "skip"])
+        .output()
+        .unwrap();
+
+    // Dropping oversized output on request doesn't make the index partial
+    assert_eq!(output.status.code(), Some(0));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(report["tool_output_bytes_skipped"].as_u64().unwrap() > 4900);
+}
+
+#[test]
+fn test_cli_max_file_size_reads_tail_of_large_history() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    // ~1.5MB of history
+    let history: String = (0..15_000)
+        .map(|i| {
+            format!(
+                "{{\"display\":\"Prompt {:05} {}\",\"timestamp\":{},\"sessionId\":\"550e8400-e29b-41d4-a716-446655440000\"}}\n",
+                i,
+                "x".repeat(20),
+                1000 + i
+            )
+        })
+        .collect();
+    std::fs::write(claude_dir.join("history.jsonl"), history).unwrap();
+
+    let run = |mb: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+            .env("HOME", temp_home.path())
+            .args(["stats", "--report", "json", "--max-file-size", mb])
+            .output()
+            .unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        (output.status.code(), report)
+    };
+
+    // Over the limit, the most recent entries are indexed and the run is partial
+    let (code, report) = run("1");
+    assert_eq!(code, Some(3));
+    let indexed = report["entries_indexed"].as_u64().unwrap();
+    assert!(indexed > 5_000 && indexed < 15_000, "indexed {indexed}");
+    assert!(report["skipped"][0]["reason"].as_str().unwrap().contains("size limit"));
+
+    let (code, report) = run("2");
+    assert_eq!(code, Some(0));
+    assert_eq!(report["entries_indexed"], 15_000);
+}
+
+#[test]
+fn test_cli_stats_failed_threshold_exit_code() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    let project_dir = claude_dir.join("projects").join("-Users%2Ftest%2Fproject");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::write(claude_dir.join("history.jsonl"), "").unwrap();
+    std::fs::write(project_dir.join("agent-1.jsonl"), "invalid json").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["stats", "--report", "json"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(4));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["status"], "failed");
+    assert_eq!(report["agent_files_failed"], 1);
+    assert!(report["skipped"][0]["path"].as_str().unwrap().ends_with("agent-1.jsonl"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Index building failed"));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_cli_cache_info_falls_back_when_cache_dir_unwritable() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let blocker = temp_home.path().join("not-a-dir");
+    std::fs::write(&blocker, "").unwrap();
+    let temp_root = temp_home.path().join("tmp");
+    std::fs::create_dir(&temp_root).unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .env("XDG_CACHE_HOME", blocker.join("cache"))
+        .env("TMPDIR", &temp_root)
+        .args(["cache", "info"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(temp dir fallback)"))
+        .stdout(predicate::str::contains("Problems:"));
+}
+
+#[cfg(feature = "cache")]
+#[test]
+fn test_cli_cache_verify_matches_after_indexing() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let project = temp_home.path().join(".claude/projects/-work-api");
+    std::fs::create_dir_all(&project).unwrap();
+    std::fs::write(
+        project.join("agent-1.jsonl"),
+        r#"{"type":"assistant","message":{"role":"assistant","content":"Done"},"timestamp":"2025-01-01T10:00:00Z","sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"a1"}"#,
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+            .env("HOME", temp_home.path())
+            .env("XDG_CACHE_HOME", temp_home.path().join("cache"))
+            .args(args)
+            .assert()
+    };
+
+    run(&["cache", "verify"])
+        .success()
+        .stdout(predicate::str::contains("Not cached yet:    1\n"))
+        .stdout(predicate::str::contains("The cache matches a fresh parse."));
+    run(&["stats"]).success();
+    run(&["cache", "verify"])
+        .success()
+        .stdout(predicate::str::contains("Compared:          1 files\n"))
+        .stdout(predicate::str::contains("The cache matches a fresh parse."));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_cli_stats_shows_cache_fallback_notice_once() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(claude_dir.join("history.jsonl"), "").unwrap();
+    let blocker = temp_home.path().join("not-a-dir");
+    std::fs::write(&blocker, "").unwrap();
+    let temp_root = temp_home.path().join("tmp");
+    std::fs::create_dir(&temp_root).unwrap();
+
+    let run = || {
+        Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+            .env("HOME", temp_home.path())
+            .env("XDG_CACHE_HOME", blocker.join("cache"))
+            .env("TMPDIR", &temp_root)
+            .arg("stats")
+            .output()
+            .unwrap()
+    };
+
+    let first = run();
+    assert!(first.status.success());
+    assert!(String::from_utf8_lossy(&first.stderr).contains("caching in"));
+
+    let second = run();
+    assert!(second.status.success());
+    assert!(!String::from_utf8_lossy(&second.stderr).contains("caching in"));
+}
+
+#[test]
+fn test_cli_export_html_applies_filter() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        r#"{"display":"Prompt in app","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/app"}
+{"display":"Prompt elsewhere","timestamp":1234567891,"sessionId":"550e8400-e29b-41d4-a716-446655440001","project":"/work/other"}"#,
+    )
+    .unwrap();
+    let output = temp_home.path().join("page.html");
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["export", "html", "--filter", "project:app", "-o"])
+        .arg(&output)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Exported 1 entries"));
+
+    let page = std::fs::read_to_string(&output).unwrap();
+    assert!(page.contains("Prompt in app"));
+    assert!(!page.contains("Prompt elsewhere"));
+}
+
+#[test]
+fn test_cli_list_prints_csv() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        r#"{"display":"Fix \"login\", then deploy","timestamp":1704099600000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/app"}"#,
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["list", "--output", "csv"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff(
+            "timestamp,type,project,session_id,text\r\n\
+             2024-01-01 09:00:00,user,/work/app,550e8400-e29b-41d4-a716-446655440000,\
+             \"Fix \"\"login\"\", then deploy\"\r\n",
+        ));
+}
+
+#[test]
+fn test_cli_export_dry_run_leaves_files_alone() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        r#"{"display":"Prompt in app","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/app"}"#,
+    )
+    .unwrap();
+    let output = temp_home.path().join("page.html");
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["export", "html", "--dry-run", "-o"])
+        .arg(&output)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Dry run: nothing was changed"))
+        .stdout(predicate::str::contains("create"))
+        .stdout(predicate::str::contains("1 entries"));
+    assert!(!output.exists());
+
+    std::fs::write(&output, "old page").unwrap();
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["export", "html", "--dry-run", "-o"])
+        .arg(&output)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("overwrite"))
+        .stdout(predicate::str::contains("(was 8 B)"))
+        .stdout(predicate::str::contains("1 to overwrite (1 file)"));
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), "old page");
+}
+
+#[test]
+fn test_cli_export_html_renders_session() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let project_dir = temp_home.path().join(".claude/projects/-work-app");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::write(
+        project_dir.join("550e8400-e29b-41d4-a716-446655440000.jsonl"),
+        r#"{"type":"user","message":{"role":"user","content":"Run the tests"},"timestamp":1704099600000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u1"}
+{"type":"assistant","message":{"role":"assistant","content":[{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"cargo test"}}]},"timestamp":1704099601000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u2","parentUuid":"u1"}
+{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"3 passed"}]},"timestamp":1704099602000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u3","parentUuid":"u2"}
+{"type":"assistant","message":{"role":"assistant","content":"All tests pass."},"timestamp":1704099603000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u4","parentUuid":"u3"}"#,
+    )
+    .unwrap();
+    let output = temp_home.path().join("session.html");
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["export", "html", "--session", "550e8400-e29b-41d4-a716-446655440000", "-o"])
+        .arg(&output)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Exported 4 turns"));
+
+    let page = std::fs::read_to_string(&output).unwrap();
+    assert!(page.contains("<title>Run the tests</title>"));
+    assert!(page.contains(r##"<a href="#prompt-1">Run the tests</a>"##));
+    assert!(page.contains("<summary>Tool call: <code>Bash</code></summary>"));
+    assert!(page.contains("3 passed"));
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["export", "html", "--session", "missing", "-o"])
+        .arg(&output)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No session 'missing'"));
+}
+
+#[test]
+fn test_cli_session_exports_save_attachments() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let project_dir = temp_home.path().join(".claude/projects/-work-app");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    // "PNG" as base64
+    std::fs::write(
+        project_dir.join("550e8400-e29b-41d4-a716-446655440000.jsonl"),
+        r#"{"type":"user","message":{"role":"user","content":[{"type":"text","text":"Why is this red?"},{"type":"image","source":{"type":"base64","media_type":"image/png","data":"UE5H"}}]},"timestamp":1704099600000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u1"}
+{"type":"assistant","message":{"role":"assistant","content":"A failing check."},"timestamp":1704099601000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u2","parentUuid":"u1"}"#,
+    )
+    .unwrap();
+    let export = |format: &str, output: &std::path::Path| {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"));
+        cmd.env("HOME", temp_home.path())
+            .args(["export", format, "--session", "550e8400-e29b-41d4-a716-446655440000"])
+            .arg("--attachments")
+            .arg("-o")
+            .arg(output);
+        cmd
+    };
+
+    let markdown = temp_home.path().join("session.md");
+    export("markdown", &markdown)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Exported 2 turns"))
+        .stdout(predicate::str::contains("and 1 image to attachments/"));
+    let text = std::fs::read_to_string(&markdown).unwrap();
+    assert!(text.contains("Why is this red?"));
+    assert!(text.contains("![image](attachments/session-1.png)"));
+    assert_eq!(std::fs::read(temp_home.path().join("attachments/session-1.png")).unwrap(), b"PNG");
+
+    let page = temp_home.path().join("page.html");
+    export("html", &page).arg("--dry-run").assert().success().stdout(
+        predicate::str::contains("attachments/page-1.png")
+            .and(predicate::str::contains("2 to create (2 files)")),
+    );
+    assert!(!page.exists());
+    export("html", &page).assert().success();
+    let html = std::fs::read_to_string(&page).unwrap();
+    assert!(html.contains(r#"<img src="attachments/page-1.png" alt="image">"#));
+}
+
+#[test]
+fn test_cli_export_timeline_prints_project_prompts() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        r#"{"display":"Later prompt","timestamp":1706778000000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/app"}
+{"display":"First prompt","timestamp":1704099600000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/app"}
+{"display":"Prompt elsewhere","timestamp":1704099600000,"sessionId":"550e8400-e29b-41d4-a716-446655440001","project":"/work/other"}"#,
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["export", "timeline", "--project", "app"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "## 2024-01\n\n- **2024-01-01 09:00** First prompt\n\n## 2024-02\n\n- **2024-02-01 09:00** Later prompt",
+        ))
+        .stdout(predicate::str::contains("Prompt elsewhere").not());
+}
+
+#[test]
+fn test_cli_show_traces_parent_chain() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let project_dir = temp_home.path().join(".claude/projects/-work-app");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::write(
+        project_dir.join("550e8400-e29b-41d4-a716-446655440000.jsonl"),
+        r#"{"type":"user","message":{"role":"user","content":"Run the tests"},"timestamp":1704099600000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u1"}
+{"type":"assistant","message":{"role":"assistant","content":[{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"cargo test"}}]},"timestamp":1704099601000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u2","parentUuid":"u1"}
+{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"3 passed"}]},"timestamp":1704099602000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u3","parentUuid":"u2"}
+{"type":"assistant","message":{"role":"assistant","content":"All tests pass."},"timestamp":1704099603000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","uuid":"u4","parentUuid":"u3"}"#,
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["show", "parent:u4"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Trace of u4 (4 messages, oldest first)"))
+        .stdout(predicate::str::contains("[tool call Bash]"))
+        .stdout(predicate::str::contains("[tool result] 3 passed"))
+        .stdout(predicate::str::contains("All tests pass."));
+
+    // Tool results have no text and aren't indexed, but can still be shown
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["show", "u3"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Parent:   u2\nReplies:  u4\n"));
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["show", "missing"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No conversation message with uuid 'missing'"));
+
+    // A session id prints the whole conversation, colored only when asked to when piped
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["show", "550e8400-e29b-41d4-a716-446655440000", "--width", "60"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Session 550e8400-e29b-41d4-a716-446655440000"))
+        .stdout(predicate::str::contains("━━ User "))
+        .stdout(predicate::str::contains("Run the tests"))
+        .stdout(predicate::str::contains("┌─ Tool call: Bash"))
+        .stdout(predicate::str::contains("│ 3 passed"))
+        .stdout(predicate::str::contains("All tests pass."))
+        .stdout(predicate::str::contains("\x1b[").not());
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["show", "550e8400-e29b-41d4-a716-446655440000", "--color", "always"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b[32m━━ Assistant"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_summarize_saves_summaries_once() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        r#"{"display":"Fix the flaky login test","timestamp":1704099600000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/app"}"#,
+    )
+    .unwrap();
+    let summarize = || {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"));
+        cmd.env("HOME", temp_home.path())
+            .env("XDG_CONFIG_HOME", temp_home.path().join("config"))
+            .env("XDG_CACHE_HOME", temp_home.path().join("cache"))
+            .arg("summarize");
+        cmd
+    };
+
+    summarize().assert().failure().stderr(predicate::str::contains("No summarizer configured"));
+
+    // The "model" echoes the first prompt it is given
+    let config_dir = temp_home.path().join("config/ai-history-explorer");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(
+        config_dir.join("config.json"),
+        r#"{ "summarizer": { "command": ["sed", "-n", "s/^User: //p"] } }"#,
+    )
+    .unwrap();
+    summarize().assert().success().stdout(predicate::str::contains(
+        "550e8400-e29b-41d4-a716-446655440000  Fix the flaky login test\n\
+         Summarized 1 of 1 sessions (0 failed, 1 summaries saved)",
+    ));
+    let saved =
+        std::fs::read_to_string(temp_home.path().join("cache/ai-history-explorer/summaries.json"))
+            .unwrap();
+    assert!(saved.contains("\"summary\": \"Fix the flaky login test\""));
+
+    summarize().assert().success().stdout(predicate::str::contains("Summarized 0 of 0 sessions"));
+}
+
+/// The binary with its config, cache and data directories inside `home`
+fn isolated_cli(home: &std::path::Path) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"));
+    cmd.env("HOME", home)
+        .env("XDG_CONFIG_HOME", home.join("config"))
+        .env("XDG_CACHE_HOME", home.join("cache"))
+        .env("XDG_DATA_HOME", home.join("data"));
+    cmd
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_summarize_dry_run_saves_nothing() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        r#"{"display":"Fix the flaky login test","timestamp":1704099600000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/app"}"#,
+    )
+    .unwrap();
+    let config_dir = temp_home.path().join("config/ai-history-explorer");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(
+        config_dir.join("config.json"),
+        r#"{ "summarizer": { "command": ["sed", "-n", "s/^User: //p"] } }"#,
+    )
+    .unwrap();
+    let before = common::disk_state(temp_home.path());
+
+    isolated_cli(temp_home.path())
+        .args(["summarize", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "550e8400-e29b-41d4-a716-446655440000  Fix the flaky login test\n\
+             Dry run: nothing was changed",
+        ))
+        .stdout(predicate::str::contains("summaries.json"))
+        .stdout(predicate::str::contains("1 summaries (1 from this run)"));
+    assert_eq!(common::disk_state(temp_home.path()), before);
+}
+
+#[cfg(feature = "fulltext")]
+#[test]
+fn test_cli_search_ranks_phrase_matches() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        r#"{"display":"Fix the connection pool timeout","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/api"}
+{"display":"Pool the connection handles","timestamp":1234567891,"sessionId":"550e8400-e29b-41d4-a716-446655440001","project":"/work/api"}"#,
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["search", "\"connection pool\""])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Matches: 1 (best first)"))
+        .stdout(predicate::str::contains("Fix the connection pool timeout"));
+
+    // The second run reuses the persisted index
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["search", "connection NOT timeout"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Matches: 1 (best first)"))
+        .stdout(predicate::str::contains("Pool the connection handles"));
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["search", "handles", "--output", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"display_text\": \"Pool the connection handles\""))
+        .stdout(predicate::str::contains("\"score\": "));
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["search", "nosuchfield:x"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid query 'nosuchfield:x'"));
+}
+
+#[cfg(feature = "tui")]
+#[test]
+fn test_cli_fuzzy_search_filters_and_sorts() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        r#"{"display":"Retry failed uploads","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/api"}
+{"display":"Add a retry to the client","timestamp":1234567891,"sessionId":"550e8400-e29b-41d4-a716-446655440001","project":"/work/api"}
+{"display":"Retry the deploy","timestamp":1234567892,"sessionId":"550e8400-e29b-41d4-a716-446655440002","project":"/work/web"}"#,
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["search", "--fuzzy", "--sort", "oldest", "--limit", "1", "project:api | retry"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Matches: 1 (oldest first)"))
+        .stdout(predicate::str::contains("Retry failed uploads"));
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["search", "--fuzzy", "--sort", "newest", "--output", "csv", "retry"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "timestamp,type,project,session_id,text\r\n1970-01-15 06:56:07,user,/work/web,",
+        ));
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["search", "--fuzzy", "nosuchfield:x | retry"])
+        .assert()
+        .failure();
+}
+
+#[cfg(feature = "similar")]
+#[test]
+fn test_cli_similar_search_finds_other_word_forms() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        r#"{"display":"Add retries with backoff to the HTTP client","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/api"}
+{"display":"Style the navigation sidebar","timestamp":1234567891,"sessionId":"550e8400-e29b-41d4-a716-446655440001","project":"/work/web"}"#,
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["search", "--similar", "retrying http requests"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Matches: 1 (best first)"))
+        .stdout(predicate::str::contains("Add retries with backoff"));
+}
+
+#[test]
+fn test_cli_semantic_prefix_uses_the_configured_embedding_model() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        r#"{"display":"Add retries with backoff to the HTTP client","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/api"}"#,
+    )
+    .unwrap();
+    // A local model directory whose config isn't BERT's, so nothing is downloaded
+    let model_dir = temp_home.path().join("model");
+    std::fs::create_dir(&model_dir).unwrap();
+    std::fs::write(model_dir.join("config.json"), "{}").unwrap();
+    let config_dir = temp_home.path().join("config/ai-history-explorer");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(
+        config_dir.join("config.json"),
+        serde_json::json!({ "embedding_model": model_dir }).to_string(),
+    )
+    .unwrap();
+
+    let expected = if cfg!(feature = "semantic") {
+        "is not a BERT model config"
+    } else {
+        "built without the `semantic` feature"
+    };
+    for args in [&["search", "semantic: flaky requests"][..], &["search", "--semantic", "flaky"]] {
+        isolated_cli(temp_home.path())
+            .args(args)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(expected));
+    }
+    isolated_cli(temp_home.path())
+        .args(["search", "--semantic", "--similar", "flaky"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[cfg(feature = "snapshot")]
+#[test]
+fn test_cli_snapshot_and_restore() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        r#"{"display":"Prompt to keep","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/app"}"#,
+    )
+    .unwrap();
+    let archive = temp_home.path().join("archive");
+    let restored = temp_home.path().join("restored");
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["snapshot", "--archive"])
+        .arg(&archive)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("of 'default': 1 new, 0 changed"));
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["snapshot", "list", "--archive"])
+        .arg(&archive)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 files"));
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["snapshot", "restore", "--archive"])
+        .arg(&archive)
+        .arg("--to")
+        .arg(&restored)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(1 files)"));
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .arg("--claude-dir")
+        .arg(&restored)
+        .args(["export", "timeline", "--project", "app"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Prompt to keep"));
+}
+
+#[cfg(feature = "snapshot")]
+#[test]
+fn test_cli_snapshot_and_restore_dry_runs_write_nothing() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        r#"{"display":"Prompt to keep","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/app"}"#,
+    )
+    .unwrap();
+    let archive = temp_home.path().join("archive");
+    let restored = temp_home.path().join("restored");
+
+    let before = common::disk_state(temp_home.path());
+    isolated_cli(temp_home.path())
+        .args(["snapshot", "--dry-run", "--archive"])
+        .arg(&archive)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Dry run: nothing was changed"))
+        .stdout(predicate::str::contains("history.jsonl.gz"))
+        .stdout(predicate::str::contains("new history.jsonl"))
+        .stdout(predicate::str::contains("manifest of 1 files"));
+    assert_eq!(common::disk_state(temp_home.path()), before);
+
+    isolated_cli(temp_home.path()).args(["snapshot", "--archive"]).arg(&archive).assert().success();
+    let before = common::disk_state(temp_home.path());
+    isolated_cli(temp_home.path())
+        .args(["snapshot", "restore", "--dry-run", "--archive"])
+        .arg(&archive)
+        .arg("--to")
+        .arg(&restored)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("create"))
+        .stdout(predicate::str::contains("restored/history.jsonl"));
+    assert_eq!(common::disk_state(temp_home.path()), before);
+}
+
+#[test]
+fn test_cli_explain_counts_clauses() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        r#"{"display":"Prompt in app","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/app"}
+{"display":"Prompt elsewhere","timestamp":1234567891,"sessionId":"550e8400-e29b-41d4-a716-446655440001","project":"/work/other"}"#,
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["explain", "project:app user:alice"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Whole filter: 0 of 2 entries"))
+        .stdout(predicate::str::contains("Clause 2 matches nothing on its own"));
+}
+
+#[test]
+fn test_cli_list_prints_jsonl() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        r#"{"display":"Fix the login form","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/web"}
+{"display":"Add a cache","timestamp":1234567891,"sessionId":"550e8400-e29b-41d4-a716-446655440001","project":"/work/api"}"#,
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["list", "--filter", "project:web", "--output", "jsonl"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<serde_json::Value> =
+        stdout.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0]["display_text"], "Fix the login form");
+    assert_eq!(lines[0]["project_path"], "/work/web");
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["list", "--limit", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Entries: 1 (newest first)"))
+        .stdout(predicate::str::contains("Add a cache"));
+}
+
+#[test]
+fn test_cli_list_shows_times_in_configured_zone() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        r#"{"display":"Fix the flaky login test","timestamp":1704099600000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/app"}"#,
+    )
+    .unwrap();
+    let config_dir = temp_home.path().join("config/ai-history-explorer");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    let list = |timezone: &str| {
+        std::fs::write(
+            config_dir.join("config.json"),
+            format!(r#"{{ "timezone": "{}" }}"#, timezone),
+        )
+        .unwrap();
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"));
+        cmd.env("HOME", temp_home.path())
+            .env("XDG_CONFIG_HOME", temp_home.path().join("config"))
+            .arg("list");
+        cmd
+    };
+
+    // 09:00 UTC
+    list("Asia/Tokyo").assert().success().stdout(predicate::str::contains("2024-01-01 18:00"));
+    list("Mars/Olympus")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2024-01-01 09:00"))
+        .stderr(predicate::str::contains("Unknown time zone 'Mars/Olympus'"));
+}
+
+#[test]
+fn test_cli_interactive_unknown_preset() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let config_dir = temp_home.path().join("config/ai-history-explorer");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(
+        config_dir.join("config.json"),
+        r#"{ "saved_searches": { "api": "project:api | retry", "web": "project:web" } }"#,
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .env("XDG_CONFIG_HOME", temp_home.path().join("config"))
+        .args(["interactive", "--preset", "cli"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No saved search named 'cli' (saved: api, web)"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_resume_runs_claude_in_project_directory() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let project = temp_home.path().join("web");
+    std::fs::create_dir(&project).unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        format!(
+            r#"{{"display":"Fix the login form","timestamp":1234567890000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"{}"}}"#,
+            project.display()
+        ),
+    )
+    .unwrap();
+    // Stand-in for Claude Code that records its arguments and directory
+    let bin = temp_home.path().join("bin");
+    std::fs::create_dir(&bin).unwrap();
+    let claude = bin.join("claude");
+    std::fs::write(&claude, "#!/bin/sh\necho \"$@\" > ../resumed\npwd >> ../resumed\n").unwrap();
+    std::fs::set_permissions(&claude, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap_or_default());
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .env("PATH", &path)
+        .args(["resume", "550e8400-e29b-41d4-a716-446655440000"])
+        .assert()
+        .success();
+    let resumed = std::fs::read_to_string(temp_home.path().join("resumed")).unwrap();
+    assert_eq!(
+        resumed,
+        format!("--resume 550e8400-e29b-41d4-a716-446655440000\n{}\n", project.display())
+    );
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .env("PATH", &path)
+        .args(["resume", "550e8400-e29b-41d4-a716-446655440001"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No session"));
+}
+
+#[test]
+fn test_cli_dedupe_shows_repeated_prompts() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        r#"{"display":"Run the tests","timestamp":1234567890000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/web"}
+{"display":"Add a cache","timestamp":1234567891000,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/web"}
+{"display":"run the tests!","timestamp":1234571490000,"sessionId":"550e8400-e29b-41d4-a716-446655440001","project":"/work/api"}"#,
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["dedupe", "--show-duplicates", "--output", "jsonl"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<serde_json::Value> =
+        stdout.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0]["display_text"], "run the tests!");
+    assert_eq!(lines[0]["occurrences"], 2);
+    assert_eq!(lines[0]["sessions"], 2);
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .arg("dedupe")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Distinct prompts: 2 (newest first)"))
+        .stdout(predicate::str::contains("×2"));
+}
+
+#[test]
+fn test_cli_list_includes_codex_and_aider_history() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        r#"{"display":"Fix the login form","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/web"}"#,
+    )
+    .unwrap();
+    let sessions = temp_home.path().join(".codex/sessions/2025/09/10");
+    std::fs::create_dir_all(&sessions).unwrap();
+    std::fs::write(
+        sessions.join("rollout-1.jsonl"),
+        r#"{"timestamp":"2025-09-10T10:00:00Z","type":"session_meta","payload":{"id":"c1","cwd":"/work/api"}}
+{"timestamp":"2025-09-10T10:00:02Z","type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"Speed up the build"}]}}"#,
+    )
+    .unwrap();
+    let aider = temp_home.path().join(".aider.chat.history.md");
+    std::fs::write(&aider, "# aider chat started at 2024-05-01 10:00:00\n#### Rename the module\n")
+        .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .arg("--codex-dir")
+        .arg(temp_home.path().join(".codex"))
+        .arg("--aider-history")
+        .arg(&aider)
+        .args(["list", "--filter", "source:codex source:aider", "--output", "jsonl"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<serde_json::Value> =
+        stdout.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let texts: Vec<_> = lines.iter().map(|l| (&l["source"], &l["display_text"])).collect();
+    assert_eq!(
+        texts,
+        vec![
+            (&"codex".into(), &"Speed up the build".into()),
+            (&"aider".into(), &"Rename the module".into())
+        ]
+    );
+}
+
+#[test]
+fn test_cli_list_includes_markdown_transcripts() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir(temp_home.path().join(".claude")).unwrap();
+    let chats = temp_home.path().join("chats");
+    std::fs::create_dir(&chats).unwrap();
+    std::fs::write(
+        chats.join("lifetimes.md"),
+        "# Rust lifetimes\n\n### User\n\nWhy does the borrow outlive the loop?\n\n\
+         ### Assistant\n\nThe vector keeps a reference.\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .arg("--transcript-dir")
+        .arg(&chats)
+        .args(["list", "--filter", "source:transcript", "--output", "jsonl"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<serde_json::Value> =
+        stdout.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let texts: Vec<_> = lines.iter().map(|l| (&l["entry_type"], &l["display_text"])).collect();
+    assert_eq!(
+        texts,
+        vec![
+            (&"AgentMessage".into(), &"The vector keeps a reference.".into()),
+            (&"UserPrompt".into(), &"Why does the borrow outlive the loop?".into())
+        ]
+    );
+    assert!(lines.iter().all(|l| l["session_id"] == "transcript-lifetimes"));
+}
+
+#[test]
+fn test_cli_list_filters_by_root() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    for (dir, text) in [("work", "Fix the deploy"), ("personal", "Plan the garden")] {
+        let claude_dir = temp_home.path().join(dir);
+        std::fs::create_dir(&claude_dir).unwrap();
+        std::fs::write(
+            claude_dir.join("history.jsonl"),
+            format!(
+                r#"{{"display":"{}","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000"}}"#,
+                text
+            ),
+        )
+        .unwrap();
+    }
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .arg("--claude-dir")
+        .arg(format!("work={}", temp_home.path().join("work").display()))
+        .arg("--claude-dir")
+        .arg(format!("home={}", temp_home.path().join("personal").display()))
+        .args(["list", "--filter", "root:work"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Fix the deploy"))
+        .stdout(predicate::str::contains("Plan the garden").not());
+}
+
+#[test]
+fn test_cli_list_pages_with_cursor() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        r#"{"display":"Fix the login form","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/web"}
+{"display":"Add a cache","timestamp":1234567891,"sessionId":"550e8400-e29b-41d4-a716-446655440001","project":"/work/api"}"#,
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["list", "--limit", "1", "--output", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let cursor = stderr
+        .lines()
+        .find_map(|line| line.strip_prefix("Next page: --cursor "))
+        .expect("next page cursor");
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["list", "--limit", "1", "--cursor", cursor])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Fix the login form"))
+        .stdout(predicate::str::contains("Add a cache").not())
+        .stderr(predicate::str::contains("Next page").not());
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["list", "--cursor", "bogus"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid cursor: 'bogus'"));
+}
+
+#[test]
+fn test_cli_sync_export_and_import() {
+    let laptop = tempfile::TempDir::new().unwrap();
+    let desktop = tempfile::TempDir::new().unwrap();
+    let claude_dir = laptop.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::create_dir(desktop.path().join(".claude")).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        r#"{"display":"Fix the login form","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/web"}"#,
+    )
+    .unwrap();
+    let bundle = laptop.path().join("laptop.jsonl");
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", laptop.path())
+        .args(["sync", "export", "-o"])
+        .arg(&bundle)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Exported 1 entries"));
+
+    for expected in ["Imported 1 new entries", "Imported 0 new entries"] {
+        Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+            .env("HOME", desktop.path())
+            .args(["sync", "import"])
+            .arg(&bundle)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(expected));
+    }
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", desktop.path())
+        .args(["list", "--output", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Fix the login form"))
+        .stdout(predicate::str::contains("sync:"));
+}
+
+#[test]
+fn test_cli_sync_dry_runs_write_nothing() {
+    let laptop = tempfile::TempDir::new().unwrap();
+    let desktop = tempfile::TempDir::new().unwrap();
+    let claude_dir = laptop.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::create_dir(desktop.path().join(".claude")).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        r#"{"display":"Fix the login form","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/web"}"#,
+    )
+    .unwrap();
+    let bundle = laptop.path().join("laptop.jsonl");
+
+    let before = common::disk_state(laptop.path());
+    isolated_cli(laptop.path())
+        .args(["sync", "export", "--dry-run", "-o"])
+        .arg(&bundle)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 entries"))
+        .stdout(predicate::str::contains("new machine id"))
+        .stdout(predicate::str::contains("sync cursors"));
+    assert_eq!(common::disk_state(laptop.path()), before);
+
+    isolated_cli(laptop.path()).args(["sync", "export", "-o"]).arg(&bundle).assert().success();
+    let before = common::disk_state(desktop.path());
+    isolated_cli(desktop.path())
+        .args(["sync", "import", "--dry-run"])
+        .arg(&bundle)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 entries, 1 new"))
+        .stdout(predicate::str::contains("2 to create (2 files)"));
+    assert_eq!(common::disk_state(desktop.path()), before);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_doctor_fix_dry_run_changes_no_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::set_permissions(&claude_dir, std::fs::Permissions::from_mode(0o700)).unwrap();
+    let history = claude_dir.join("history.jsonl");
+    std::fs::write(&history, "").unwrap();
+    std::fs::set_permissions(&history, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+    let before = common::disk_state(temp_home.path());
+    isolated_cli(temp_home.path())
+        .args(["doctor", "--fix", "--dry-run"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("0644 -> 0600"))
+        .stdout(predicate::str::contains("1 to restrict (1 file)"));
+    assert_eq!(common::disk_state(temp_home.path()), before);
+}
+
+#[test]
+fn test_cli_merges_renamed_projects() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        r#"{"display":"Before the move","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/code/app"}
+{"display":"After the move","timestamp":1234567990,"sessionId":"550e8400-e29b-41d4-a716-446655440001","project":"/work/app"}"#,
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["renames", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("create"))
+        .stdout(predicate::str::contains("merge /code/app → /work/app"));
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["list", "--filter", "project:/work/app"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Before the move").not());
+
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .arg("renames")
+        .write_stdin("y\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("/code/app → /work/app"))
+        .stdout(predicate::str::contains("Merged 1 projects"));
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .args(["list", "--filter", "project:/work/app"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Before the move"))
+        .stdout(predicate::str::contains("After the move"));
+
+    Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+        .env("HOME", temp_home.path())
+        .arg("renames")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No renamed projects found"));
+}
+
+#[test]
+fn test_cli_writes_metrics_file() {
+    let temp_home = tempfile::TempDir::new().unwrap();
+    let claude_dir = temp_home.path().join(".claude");
+    std::fs::create_dir(&claude_dir).unwrap();
+    std::fs::write(
+        claude_dir.join("history.jsonl"),
+        r#"{"display":"Prompt one","timestamp":1234567890,"sessionId":"550e8400-e29b-41d4-a716-446655440000","project":"/work/app"}
+not json"#,
+    )
+    .unwrap();
+    let metrics = temp_home.path().join("ai-history-explorer.prom");
+
+    for _ in 0..2 {
+        Command::new(env!("CARGO_BIN_EXE_ai-history-explorer"))
+            .env("HOME", temp_home.path())
+            .args(["list", "--metrics-file"])
+            .arg(&metrics)
+            .assert()
+            .code(3);
+    }
+
+    let text = std::fs::read_to_string(&metrics).unwrap();
+    assert!(text.contains("ai_history_explorer_index_entries 1\n"));
+    assert!(text.contains("ai_history_explorer_last_run_exit_code 3\n"));
+    assert!(text.contains("ai_history_explorer_runs_total{command=\"list\"} 2\n"));
+    assert!(text.contains("ai_history_explorer_parse_failures_total{kind=\"history_line\"} 2\n"));
 }
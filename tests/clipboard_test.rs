@@ -1,3 +1,5 @@
+#![cfg(feature = "clipboard")]
+
 use ai_history_explorer::copy_to_clipboard;
 use arboard::Clipboard;
 
@@ -1,9 +1,10 @@
 //! Shared test utilities for integration tests
 #![allow(dead_code)]
 
+use std::collections::BTreeMap;
 use std::fs;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use tempfile::TempDir;
 
@@ -311,3 +312,22 @@ pub fn realistic_claude_dir() -> TempDir {
         )
         .build()
 }
+
+/// Every file and directory under `dir` with its contents (`None` for directories) and, on
+/// Unix, its permission bits, to check that a command changed nothing on disk
+pub fn disk_state(dir: &Path) -> BTreeMap<PathBuf, (Option<Vec<u8>>, u32)> {
+    walkdir::WalkDir::new(dir)
+        .follow_links(false)
+        .into_iter()
+        .map(|entry| {
+            let entry = entry.unwrap();
+            let metadata = entry.metadata().unwrap();
+            #[cfg(unix)]
+            let mode = std::os::unix::fs::PermissionsExt::mode(&metadata.permissions()) & 0o777;
+            #[cfg(not(unix))]
+            let mode = u32::from(metadata.permissions().readonly());
+            let contents = metadata.is_file().then(|| fs::read(entry.path()).unwrap());
+            (entry.path().to_path_buf(), (contents, mode))
+        })
+        .collect()
+}
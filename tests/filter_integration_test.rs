@@ -1,5 +1,6 @@
 //! Integration tests for filter functionality
 
+use ai_history_explorer::bookmarks::Bookmarks;
 use ai_history_explorer::filters::apply::apply_filters;
 use ai_history_explorer::filters::parser::parse_filter;
 use ai_history_explorer::models::{EntryType, SearchEntry};
@@ -16,6 +17,7 @@ fn create_test_entry(
         timestamp: Utc.timestamp_opt(1234567890, 0).unwrap(),
         project_path: project_path.map(|s| s.into()),
         session_id: "test-session".to_string(),
+        ..Default::default()
     }
 }
 
@@ -28,7 +30,7 @@ fn test_filter_integration_project() {
     ];
 
     let filter = parse_filter("project:ai-history").expect("Parse filter");
-    let filtered = apply_filters(entries, &filter).expect("Apply filter");
+    let filtered = apply_filters(entries, &filter, &Bookmarks::default()).expect("Apply filter");
 
     assert_eq!(filtered.len(), 1);
     assert!(filtered[0].project_path.as_ref().unwrap().to_string_lossy().contains("ai-history"));
@@ -43,7 +45,7 @@ fn test_filter_integration_type() {
     ];
 
     let filter = parse_filter("type:user").expect("Parse filter");
-    let filtered = apply_filters(entries, &filter).expect("Apply filter");
+    let filtered = apply_filters(entries, &filter, &Bookmarks::default()).expect("Apply filter");
 
     assert_eq!(filtered.len(), 2);
     assert!(filtered.iter().all(|e| matches!(e.entry_type, EntryType::UserPrompt)));
@@ -58,7 +60,7 @@ fn test_filter_integration_combined() {
     ];
 
     let filter = parse_filter("project:ai-history type:user").expect("Parse filter");
-    let filtered = apply_filters(entries, &filter).expect("Apply filter");
+    let filtered = apply_filters(entries, &filter, &Bookmarks::default()).expect("Apply filter");
 
     assert_eq!(filtered.len(), 1);
     assert!(filtered[0].project_path.as_ref().unwrap().to_string_lossy().contains("ai-history"));
@@ -74,11 +76,63 @@ fn test_filter_integration_or_operator() {
     ];
 
     let filter = parse_filter("project:project1 project:project2").expect("Parse filter");
-    let filtered = apply_filters(entries, &filter).expect("Apply filter");
+    let filtered = apply_filters(entries, &filter, &Bookmarks::default()).expect("Apply filter");
 
     assert_eq!(filtered.len(), 2);
 }
 
+#[test]
+fn test_filter_integration_groups_and_not() {
+    let entries = vec![
+        create_test_entry("Entry 1", Some("/Users/test/project1"), EntryType::UserPrompt),
+        create_test_entry("Entry 2", Some("/Users/test/project1"), EntryType::AgentMessage),
+        create_test_entry("Entry 3", Some("/Users/test/project2"), EntryType::UserPrompt),
+        create_test_entry("Entry 4", Some("/Users/test/project3"), EntryType::UserPrompt),
+    ];
+
+    let filter =
+        parse_filter("(project:project1 OR project:project2) -type:agent").expect("Parse filter");
+    let filtered =
+        apply_filters(entries.clone(), &filter, &Bookmarks::default()).expect("Apply filter");
+    let texts: Vec<&str> = filtered.iter().map(|e| e.display_text.as_str()).collect();
+    assert_eq!(texts, vec!["Entry 1", "Entry 3"]);
+
+    let filter = parse_filter("NOT project:project1").expect("Parse filter");
+    let filtered = apply_filters(entries, &filter, &Bookmarks::default()).expect("Apply filter");
+    assert_eq!(filtered.len(), 2);
+}
+
+#[test]
+fn test_filter_integration_sizes() {
+    let mut entries = vec![
+        create_test_entry("ok", Some("/Users/test/project1"), EntryType::UserPrompt),
+        create_test_entry(
+            &"long ".repeat(300),
+            Some("/Users/test/project1"),
+            EntryType::UserPrompt,
+        ),
+        create_test_entry("mid-sized prompt", Some("/Users/test/project2"), EntryType::UserPrompt),
+    ];
+    entries[2].session_id = "other-session".to_string();
+
+    let filter = parse_filter("len>1000").expect("Parse filter");
+    let filtered =
+        apply_filters(entries.clone(), &filter, &Bookmarks::default()).expect("Apply filter");
+    assert_eq!(filtered.len(), 1);
+    assert!(filtered[0].display_text.starts_with("long"));
+
+    let filter = parse_filter("len>=5 len<1000").expect("Parse filter");
+    let filtered =
+        apply_filters(entries.clone(), &filter, &Bookmarks::default()).expect("Apply filter");
+    assert_eq!(filtered[0].display_text, "mid-sized prompt");
+    assert_eq!(filtered.len(), 1);
+
+    let filter = parse_filter("messages>1 -len>100").expect("Parse filter");
+    let filtered = apply_filters(entries, &filter, &Bookmarks::default()).expect("Apply filter");
+    let texts: Vec<&str> = filtered.iter().map(|e| e.display_text.as_str()).collect();
+    assert_eq!(texts, vec!["ok"]);
+}
+
 #[test]
 fn test_filter_integration_invalid_filter() {
     let result = parse_filter("invalid:field");
@@ -92,7 +146,7 @@ fn test_filter_integration_empty_result() {
         vec![create_test_entry("Entry 1", Some("/Users/test/project"), EntryType::UserPrompt)];
 
     let filter = parse_filter("project:nonexistent").expect("Parse filter");
-    let filtered = apply_filters(entries, &filter).expect("Apply filter");
+    let filtered = apply_filters(entries, &filter, &Bookmarks::default()).expect("Apply filter");
 
     assert_eq!(filtered.len(), 0);
 }
@@ -104,20 +158,20 @@ fn test_filter_integration_since() {
             entry_type: EntryType::UserPrompt,
             display_text: "Old entry".to_string(),
             timestamp: Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap(),
-            project_path: None,
             session_id: "test".to_string(),
+            ..Default::default()
         },
         SearchEntry {
             entry_type: EntryType::UserPrompt,
             display_text: "New entry".to_string(),
             timestamp: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
-            project_path: None,
             session_id: "test".to_string(),
+            ..Default::default()
         },
     ];
 
     let filter = parse_filter("since:2023-01-01").expect("Parse filter");
-    let filtered = apply_filters(entries, &filter).expect("Apply filter");
+    let filtered = apply_filters(entries, &filter, &Bookmarks::default()).expect("Apply filter");
 
     assert_eq!(filtered.len(), 1);
     assert_eq!(filtered[0].display_text, "New entry");
@@ -132,7 +186,7 @@ fn test_filter_integration_case_insensitive_project() {
     )];
 
     let filter = parse_filter("project:ai-history").expect("Parse filter");
-    let filtered = apply_filters(entries, &filter).expect("Apply filter");
+    let filtered = apply_filters(entries, &filter, &Bookmarks::default()).expect("Apply filter");
 
     assert_eq!(filtered.len(), 1);
 }
@@ -143,7 +197,7 @@ fn test_filter_integration_quoted_value() {
         vec![create_test_entry("Entry", Some("/Users/test/my project"), EntryType::UserPrompt)];
 
     let filter = parse_filter("project:\"my project\"").expect("Parse filter");
-    let filtered = apply_filters(entries, &filter).expect("Apply filter");
+    let filtered = apply_filters(entries, &filter, &Bookmarks::default()).expect("Apply filter");
 
     assert_eq!(filtered.len(), 1);
 }
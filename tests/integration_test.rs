@@ -252,7 +252,7 @@ invalid line 3"#;
 
     let claude_dir = ClaudeDirBuilder::new().with_history(history_content).build();
 
-    // Build index succeeds with graceful degradation (warning printed to stderr)
+    // Build index succeeds with graceful degradation (warning recorded in the report)
     let result = build_index(claude_dir.path());
     assert!(result.is_ok(), "Should succeed with graceful degradation");
 